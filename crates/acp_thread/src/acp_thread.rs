@@ -42,7 +42,7 @@ use serde_json::to_string_pretty;
 use task::{Shell, ShellBuilder};
 pub use terminal::*;
 
-use action_log::{ActionLog, ActionLogTelemetry};
+use action_log::{ActionLog, ActionLogTelemetry, FileCheckpoint};
 use agent_client_protocol::{self as acp};
 use anyhow::{Context as _, Result, anyhow};
 use futures::{FutureExt, channel::oneshot, future::BoxFuture};
@@ -75,10 +75,20 @@ pub struct UserMessage {
 
 #[derive(Debug)]
 pub struct Checkpoint {
-    git_checkpoint: GitStoreCheckpoint,
+    /// Absent for projects without a git repository.
+    git_checkpoint: Option<GitStoreCheckpoint>,
+    /// Covers the files touched by the agent, including untracked files and files outside of
+    /// any repository.
+    file_checkpoint: FileCheckpoint,
     pub show: bool,
 }
 
+impl Checkpoint {
+    pub fn file_checkpoint(&self) -> FileCheckpoint {
+        self.file_checkpoint
+    }
+}
+
 impl UserMessage {
     fn to_markdown(&self, cx: &App) -> String {
         let mut markdown = String::new();
@@ -955,6 +965,9 @@ pub struct AcpThread {
     connection: Rc<dyn AgentConnection>,
     session_id: acp::SessionId,
     token_usage: Option<TokenUsage>,
+    /// The first user message that is still sent to the model, if the messages before it were
+    /// replaced by a summary to save context.
+    compacted_before: Option<UserMessageId>,
     prompt_capabilities: acp::PromptCapabilities,
     _observe_prompt_capabilities: Task<anyhow::Result<()>>,
     terminals: HashMap<acp::TerminalId, Entity<Terminal>>,
@@ -1192,6 +1205,7 @@ impl AcpThread {
             connection,
             session_id,
             token_usage: None,
+            compacted_before: None,
             prompt_capabilities,
             _observe_prompt_capabilities: task,
             terminals: HashMap::default(),
@@ -1491,6 +1505,24 @@ impl AcpThread {
         cx.emit(AcpThreadEvent::TokenUsageUpdated);
     }
 
+    pub fn compacted_before(&self) -> Option<&UserMessageId> {
+        self.compacted_before.as_ref()
+    }
+
+    /// Records that the messages before the user message with the given id were summarized, and
+    /// are no longer sent to the model. They are kept in the thread, so they can still be viewed.
+    pub fn context_compacted(&mut self, compacted_before: UserMessageId, cx: &mut Context<Self>) {
+        let ix = self.entries.iter().position(|entry| {
+            entry
+                .user_message()
+                .is_some_and(|message| message.id.as_ref() == Some(&compacted_before))
+        });
+        self.compacted_before = Some(compacted_before);
+        if let Some(ix) = ix {
+            cx.emit(AcpThreadEvent::EntryUpdated(ix));
+        }
+    }
+
     pub fn update_retry_status(&mut self, status: RetryStatus, cx: &mut Context<Self>) {
         cx.emit(AcpThreadEvent::Retry(status));
     }
@@ -1905,9 +1937,13 @@ impl AcpThread {
                 .context("failed to get old checkpoint")
                 .log_err();
             this.update(cx, |this, cx| {
+                let file_checkpoint = this
+                    .action_log
+                    .update(cx, |action_log, cx| action_log.checkpoint(cx));
                 if let Some((_ix, message)) = this.last_user_message() {
-                    message.checkpoint = old_checkpoint.map(|git_checkpoint| Checkpoint {
-                        git_checkpoint,
+                    message.checkpoint = Some(Checkpoint {
+                        git_checkpoint: old_checkpoint,
+                        file_checkpoint,
                         show: false,
                     });
                 }
@@ -2077,7 +2113,8 @@ impl AcpThread {
         }
     }
 
-    /// Restores the git working tree to the state at the given checkpoint (if one exists)
+    /// Restores the git working tree and the files touched by the agent to the state at the
+    /// given checkpoint (if one exists)
     pub fn restore_checkpoint(
         &mut self,
         id: UserMessageId,
@@ -2087,24 +2124,33 @@ impl AcpThread {
             return Task::ready(Err(anyhow!("message not found")));
         };
 
-        let checkpoint = message
+        let (git_checkpoint, file_checkpoint) = message
             .checkpoint
             .as_ref()
-            .map(|c| c.git_checkpoint.clone());
+            .map(|c| (c.git_checkpoint.clone(), Some(c.file_checkpoint)))
+            .unwrap_or_default();
 
         // Cancel any in-progress generation before restoring
         let cancel_task = self.cancel(cx);
         let rewind = self.rewind(id.clone(), cx);
         let git_store = self.project.read(cx).git_store().clone();
+        let action_log = self.action_log.clone();
 
         cx.spawn(async move |_, cx| {
             cancel_task.await;
             rewind.await?;
-            if let Some(checkpoint) = checkpoint {
+            if let Some(checkpoint) = git_checkpoint {
                 git_store
                     .update(cx, |git, cx| git.restore_checkpoint(checkpoint, cx))
                     .await?;
             }
+            if let Some(checkpoint) = file_checkpoint {
+                action_log
+                    .update(cx, |action_log, cx| {
+                        action_log.restore_checkpoint(checkpoint, cx)
+                    })
+                    .await?;
+            }
 
             Ok(())
         })
@@ -2165,23 +2211,38 @@ impl AcpThread {
             return Task::ready(Ok(()));
         };
         let old_checkpoint = checkpoint.git_checkpoint.clone();
+        let file_checkpoint = checkpoint.file_checkpoint;
+        let file_changes = self.action_log.update(cx, |action_log, cx| {
+            action_log.changes_since_checkpoint(file_checkpoint, cx)
+        });
 
         let new_checkpoint = git_store.update(cx, |git, cx| git.checkpoint(cx));
         cx.spawn(async move |this, cx| {
-            let Some(new_checkpoint) = new_checkpoint
+            let files_equal = file_changes
                 .await
-                .context("failed to get new checkpoint")
+                .context("failed to compare file checkpoint")
                 .log_err()
-            else {
-                return Ok(());
-            };
+                .is_none_or(|changes| changes.is_empty());
+
+            let git_equal = if let Some(old_checkpoint) = old_checkpoint {
+                let Some(new_checkpoint) = new_checkpoint
+                    .await
+                    .context("failed to get new checkpoint")
+                    .log_err()
+                else {
+                    return Ok(());
+                };
 
-            let equal = git_store
-                .update(cx, |git, cx| {
-                    git.compare_checkpoints(old_checkpoint.clone(), new_checkpoint, cx)
-                })
-                .await
-                .unwrap_or(true);
+                git_store
+                    .update(cx, |git, cx| {
+                        git.compare_checkpoints(old_checkpoint.clone(), new_checkpoint, cx)
+                    })
+                    .await
+                    .unwrap_or(true)
+            } else {
+                true
+            };
+            let equal = git_equal && files_equal;
 
             this.update(cx, |this, cx| {
                 if let Some((ix, message)) = this.user_message_mut(&user_message_id) {
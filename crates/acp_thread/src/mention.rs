@@ -60,6 +60,11 @@ pub enum MentionUri {
     GitDiff {
         base_ref: String,
     },
+    McpResource {
+        server_id: String,
+        uri: String,
+        name: String,
+    },
 }
 
 impl MentionUri {
@@ -215,6 +220,22 @@ impl MentionUri {
                     let base_ref =
                         single_query_param(&url, "base")?.unwrap_or_else(|| "main".to_string());
                     Ok(Self::GitDiff { base_ref })
+                } else if let Some(server_id) = path.strip_prefix("/agent/mcp-resource/") {
+                    let mut uri = None;
+                    let mut name = None;
+                    for (key, value) in url.query_pairs() {
+                        match key.as_ref() {
+                            "uri" => uri = Some(value.into_owned()),
+                            "name" => name = Some(value.into_owned()),
+                            _ => bail!("invalid query parameter"),
+                        }
+                    }
+                    let uri = uri.context("Missing uri for MCP resource")?;
+                    Ok(Self::McpResource {
+                        server_id: decode(server_id)?.into_owned(),
+                        name: name.unwrap_or_else(|| uri.clone()),
+                        uri,
+                    })
                 } else {
                     bail!("invalid zed url: {:?}", input);
                 }
@@ -245,6 +266,7 @@ impl MentionUri {
                 }
             }
             MentionUri::GitDiff { base_ref } => format!("Branch Diff ({})", base_ref),
+            MentionUri::McpResource { name, .. } => name.clone(),
             MentionUri::Selection {
                 abs_path: path,
                 line_range,
@@ -271,6 +293,7 @@ impl MentionUri {
             MentionUri::Selection { .. } => IconName::Reader.path().into(),
             MentionUri::Fetch { .. } => IconName::ToolWeb.path().into(),
             MentionUri::GitDiff { .. } => IconName::GitBranch.path().into(),
+            MentionUri::McpResource { .. } => IconName::ToolHammer.path().into(),
         }
     }
 
@@ -374,6 +397,18 @@ impl MentionUri {
                 url.query_pairs_mut().append_pair("base", base_ref);
                 url
             }
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                let mut url = Url::parse("zed:///").unwrap();
+                url.set_path(&format!("/agent/mcp-resource/{server_id}"));
+                url.query_pairs_mut()
+                    .append_pair("uri", uri)
+                    .append_pair("name", name);
+                url
+            }
         }
     }
 }
@@ -508,6 +543,26 @@ mod tests {
         assert_eq!(parsed.to_uri().to_string(), file_uri);
     }
 
+    #[test]
+    fn test_parse_mcp_resource_uri() {
+        let resource_uri =
+            "zed:///agent/mcp-resource/docs-server?uri=docs%3A%2F%2Fguide%2Fintro.md&name=Intro";
+        let parsed = MentionUri::parse(resource_uri, PathStyle::local()).unwrap();
+        match &parsed {
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                assert_eq!(server_id, "docs-server");
+                assert_eq!(uri, "docs://guide/intro.md");
+                assert_eq!(name, "Intro");
+            }
+            _ => panic!("Expected McpResource variant"),
+        }
+        assert_eq!(parsed.to_uri().to_string(), resource_uri);
+    }
+
     #[test]
     fn test_parse_untitled_selection_uri() {
         let selection_uri = uri!("zed:///agent/untitled-buffer#L1:10");
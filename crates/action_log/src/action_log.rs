@@ -1,5 +1,8 @@
+mod checkpoint;
+
 use anyhow::{Context as _, Result};
 use buffer_diff::BufferDiff;
+use checkpoint::{CheckpointStore, ContentHash};
 use clock;
 use collections::BTreeMap;
 use futures::{FutureExt, StreamExt, channel::mpsc};
@@ -12,6 +15,8 @@ use std::{cmp, ops::Range, sync::Arc};
 use text::{Edit, Patch, Rope};
 use util::{RangeExt, ResultExt as _};
 
+pub use checkpoint::FileCheckpoint;
+
 /// Stores undo information for a single buffer's rejected edits
 #[derive(Clone)]
 pub struct PerBufferUndo {
@@ -50,6 +55,10 @@ pub struct ActionLog {
     project: Entity<Project>,
     /// Stores undo information for the most recent reject operation
     last_reject_undo: Option<LastRejectUndo>,
+    /// Snapshots of the files touched by agent tools, for projects or files that git can't restore.
+    checkpoints: CheckpointStore,
+    /// Where the unreviewed edits collected from other action logs came from, per buffer.
+    collected_edits: BTreeMap<Entity<Buffer>, CollectedEdits>,
 }
 
 impl ActionLog {
@@ -59,6 +68,8 @@ impl ActionLog {
             tracked_buffers: BTreeMap::default(),
             project,
             last_reject_undo: None,
+            checkpoints: CheckpointStore::default(),
+            collected_edits: BTreeMap::default(),
         }
     }
 
@@ -103,6 +114,21 @@ impl ActionLog {
             TrackedBufferStatus::Modified
         };
 
+        if !self.tracked_buffers.contains_key(&buffer)
+            && let Some(project_path) = buffer.read(cx).project_path(cx)
+        {
+            let untouched_text = match &status {
+                TrackedBufferStatus::Created {
+                    existing_file_content,
+                } => existing_file_content.clone(),
+                TrackedBufferStatus::Modified | TrackedBufferStatus::Deleted => {
+                    Some(buffer.read(cx).as_rope().clone())
+                }
+            };
+            self.checkpoints
+                .record_untouched(project_path, untouched_text);
+        }
+
         let tracked_buffer = self
             .tracked_buffers
             .entry(buffer.clone())
@@ -489,6 +515,9 @@ impl ActionLog {
             tracked_buffer.diff_base = new_diff_base;
             tracked_buffer.snapshot = buffer_snapshot;
             tracked_buffer.unreviewed_edits = unreviewed_edits;
+            if tracked_buffer.unreviewed_edits.is_empty() {
+                this.collected_edits.remove(buffer);
+            }
             cx.notify();
             anyhow::Ok(())
         })?
@@ -914,6 +943,143 @@ impl ActionLog {
             .collect()
     }
 
+    /// Tracks the unreviewed edits of another action log, e.g. the one of a subagent, so that
+    /// they can be reviewed in this one.
+    ///
+    /// Buffers that end up with unreviewed edits from more than one source are reported by
+    /// [`Self::edit_conflicts`].
+    pub fn collect_edits(
+        &mut self,
+        source: SharedString,
+        other: &Entity<ActionLog>,
+        cx: &mut Context<Self>,
+    ) {
+        let edits = other
+            .read(cx)
+            .tracked_buffers
+            .iter()
+            .filter(|(_, tracked)| tracked.has_edits(cx))
+            .map(|(buffer, tracked)| {
+                (
+                    buffer.clone(),
+                    tracked.diff_base.clone(),
+                    tracked.status.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (buffer, diff_base, status) in edits {
+            let has_unreviewed_edits = self.tracked_buffers.get(&buffer).is_some_and(|tracked| {
+                tracked.has_edits(cx) || self.collected_edits.contains_key(&buffer)
+            });
+            let collected = self.collected_edits.entry(buffer.clone()).or_default();
+            if !has_unreviewed_edits {
+                *collected = CollectedEdits::default();
+            } else if collected.sources.is_empty() {
+                collected.includes_own_edits = true;
+            }
+            collected.sources.push(source.clone());
+
+            if has_unreviewed_edits {
+                self.rebase_collected_edits(buffer, diff_base, cx);
+                continue;
+            }
+
+            if !self.tracked_buffers.contains_key(&buffer)
+                && let Some(project_path) = buffer.read(cx).project_path(cx)
+            {
+                let untouched_text = match &status {
+                    TrackedBufferStatus::Created {
+                        existing_file_content,
+                    } => existing_file_content.clone(),
+                    TrackedBufferStatus::Modified | TrackedBufferStatus::Deleted => {
+                        Some(diff_base.clone())
+                    }
+                };
+                self.checkpoints
+                    .record_untouched(project_path, untouched_text);
+            }
+            let tracked_buffer = self.track_buffer_internal(buffer, false, cx);
+            tracked_buffer.diff_base = diff_base;
+            tracked_buffer.status = status;
+            tracked_buffer.schedule_diff_update(ChangeAuthor::Agent, cx);
+        }
+        cx.notify();
+    }
+
+    /// The edits of another action log to a buffer with unreviewed edits were made on top of them,
+    /// so this one treated them as user edits and added them to the diff base. Reverts them in
+    /// the diff base, except where they overlap the unreviewed edits, so that they can be
+    /// reviewed too.
+    fn rebase_collected_edits(
+        &mut self,
+        buffer: Entity<Buffer>,
+        other_diff_base: Rope,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tracked_buffer) = self.tracked_buffers.get(&buffer) else {
+            return;
+        };
+        let mut new_diff_base = tracked_buffer.diff_base.clone();
+        let text = buffer.read(cx).as_rope().clone();
+        let rebase = cx.background_spawn(async move {
+            let unreviewed_edits = Patch::new(
+                language::line_diff(&new_diff_base.to_string(), &text.to_string())
+                    .into_iter()
+                    .map(|(old, new)| Edit { old, new })
+                    .collect(),
+            );
+            let reverted_edits =
+                language::line_diff(&text.to_string(), &other_diff_base.to_string())
+                    .into_iter()
+                    .map(|(old, new)| Edit { old, new })
+                    .collect();
+            apply_non_conflicting_edits(
+                &unreviewed_edits,
+                reverted_edits,
+                &mut new_diff_base,
+                &other_diff_base,
+            );
+            new_diff_base
+        });
+        cx.spawn(async move |this, cx| {
+            let new_diff_base = rebase.await;
+            this.update(cx, |this, cx| {
+                if let Some(tracked_buffer) = this.tracked_buffers.get_mut(&buffer) {
+                    tracked_buffer.diff_base = new_diff_base;
+                    tracked_buffer.schedule_diff_update(ChangeAuthor::Agent, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Returns the buffers whose unreviewed edits were collected from more than one source, or
+    /// that also have edits made directly in this action log.
+    pub fn edit_conflicts(&self, cx: &App) -> Vec<EditConflict> {
+        self.collected_edits
+            .iter()
+            .filter(|(_, collected)| collected.sources.len() > 1 || collected.includes_own_edits)
+            .filter(|(buffer, _)| {
+                self.tracked_buffers
+                    .get(*buffer)
+                    .is_some_and(|tracked| tracked.has_edits(cx))
+            })
+            .map(|(buffer, collected)| EditConflict {
+                buffer: buffer.clone(),
+                sources: collected.sources.clone(),
+                includes_own_edits: collected.includes_own_edits,
+            })
+            .collect()
+    }
+
+    /// Stops reporting the buffer as conflicting, e.g. after the user reviewed it.
+    pub fn resolve_edit_conflict(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        if self.collected_edits.remove(buffer).is_some() {
+            cx.notify();
+        }
+    }
+
     /// Returns all tracked buffers for debugging purposes
     #[cfg(any(test, feature = "test-support"))]
     pub fn tracked_buffers_for_debug(
@@ -923,6 +1089,96 @@ impl ActionLog {
         self.tracked_buffers.iter()
     }
 
+    /// Takes a checkpoint of the files touched by agent tools, which can be restored even if they
+    /// aren't tracked by git.
+    ///
+    /// Only the files this action log tracks are recorded: their current contents, or their absence
+    /// if the agent deleted them. Checkpoints are kept in memory and don't survive a restart.
+    pub fn checkpoint(&mut self, cx: &App) -> FileCheckpoint {
+        let files = self
+            .tracked_buffers
+            .iter()
+            .filter_map(|(buffer, tracked_buffer)| {
+                let buffer = buffer.read(cx);
+                let text = match tracked_buffer.status {
+                    TrackedBufferStatus::Deleted => None,
+                    TrackedBufferStatus::Created { .. } | TrackedBufferStatus::Modified => {
+                        file_text(buffer)
+                    }
+                };
+                Some((buffer.project_path(cx)?, text))
+            })
+            .collect::<Vec<_>>();
+        self.checkpoints.create(files)
+    }
+
+    /// Returns the files whose contents changed since the given checkpoint.
+    pub fn changes_since_checkpoint(
+        &self,
+        checkpoint: FileCheckpoint,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CheckpointChange>>> {
+        let files = self
+            .checkpoints
+            .files(checkpoint)
+            .map(|(path, hash)| (path.clone(), hash))
+            .collect::<Vec<_>>();
+        let project = self.project.clone();
+        cx.spawn(async move |this, cx| {
+            let mut changes = Vec::new();
+            for (path, hash) in files {
+                let buffer = project
+                    .update(cx, |project, cx| project.open_buffer(path, cx))
+                    .await?;
+                let current_hash = buffer.read_with(cx, |buffer, _| {
+                    file_text(buffer).map(|text| ContentHash::new(&text))
+                });
+                if current_hash != hash {
+                    let checkpoint_text = this.read_with(cx, |this, _| {
+                        hash.and_then(|hash| this.checkpoints.blob(hash).cloned())
+                    })?;
+                    changes.push(CheckpointChange {
+                        buffer,
+                        checkpoint_text,
+                    });
+                }
+            }
+            Ok(changes)
+        })
+    }
+
+    /// Restores the files touched by agent tools to their contents at the given checkpoint,
+    /// deleting the ones that didn't exist yet.
+    pub fn restore_checkpoint(
+        &mut self,
+        checkpoint: FileCheckpoint,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let changes = self.changes_since_checkpoint(checkpoint, cx);
+        let project = self.project.clone();
+        cx.spawn(async move |_, cx| {
+            for CheckpointChange {
+                buffer,
+                checkpoint_text,
+            } in changes.await?
+            {
+                if let Some(text) = checkpoint_text {
+                    buffer.update(cx, |buffer, cx| buffer.set_text(text.to_string(), cx));
+                    project
+                        .update(cx, |project, cx| project.save_buffer(buffer, cx))
+                        .await?;
+                } else if let Some(entry_id) =
+                    buffer.read_with(cx, |buffer, cx| buffer.entry_id(cx))
+                    && let Some(delete) =
+                        project.update(cx, |project, cx| project.delete_entry(entry_id, false, cx))
+                {
+                    delete.await?;
+                }
+            }
+            Ok(())
+        })
+    }
+
     /// Iterate over buffers changed since last read or edited by the model
     pub fn stale_buffers<'a>(&'a self, cx: &'a App) -> impl Iterator<Item = &'a Entity<Buffer>> {
         self.tracked_buffers
@@ -939,6 +1195,37 @@ impl ActionLog {
     }
 }
 
+/// The sources of the edits that were collected into a buffer with [`ActionLog::collect_edits`].
+#[derive(Default)]
+struct CollectedEdits {
+    sources: Vec<SharedString>,
+    includes_own_edits: bool,
+}
+
+/// A buffer with unreviewed edits from more than one source, which might conflict.
+#[derive(Clone, Debug)]
+pub struct EditConflict {
+    pub buffer: Entity<Buffer>,
+    /// The sources whose edits were collected, in the order they were collected.
+    pub sources: Vec<SharedString>,
+    /// Whether the buffer also has edits that were made directly in this action log.
+    pub includes_own_edits: bool,
+}
+
+/// A file whose contents changed since a [`FileCheckpoint`].
+#[derive(Clone)]
+pub struct CheckpointChange {
+    pub buffer: Entity<Buffer>,
+    /// The contents of the file at the checkpoint, or `None` if it didn't exist yet.
+    pub checkpoint_text: Option<Rope>,
+}
+
+/// Returns the contents of the buffer's file, or `None` if it doesn't exist.
+fn file_text(buffer: &Buffer) -> Option<Rope> {
+    let exists = buffer.is_dirty() || buffer.file().is_some_and(|file| file.disk_state().exists());
+    exists.then(|| buffer.as_rope().clone())
+}
+
 #[derive(Clone)]
 pub struct ActionLogTelemetry {
     pub agent_telemetry_id: SharedString,
@@ -1115,7 +1402,7 @@ enum ChangeAuthor {
     Agent,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum TrackedBufferStatus {
     Created { existing_file_content: Option<Rope> },
     Modified,
@@ -1979,6 +2266,210 @@ mod tests {
         assert_eq!(unreviewed_hunks(&action_log, cx), vec![]);
     }
 
+    #[gpui::test(iterations = 10)]
+    async fn test_file_checkpoints(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({"edited": "one"}))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let checkpoint = action_log.update(cx, |log, cx| log.checkpoint(cx));
+
+        let edited_path = project
+            .read_with(cx, |project, cx| {
+                project.find_project_path("dir/edited", cx)
+            })
+            .unwrap();
+        let edited_buffer = project
+            .update(cx, |project, cx| project.open_buffer(edited_path, cx))
+            .await
+            .unwrap();
+        cx.update(|cx| {
+            action_log.update(cx, |log, cx| log.buffer_read(edited_buffer.clone(), cx));
+            edited_buffer.update(cx, |buffer, cx| buffer.set_text("two", cx));
+            action_log.update(cx, |log, cx| log.buffer_edited(edited_buffer.clone(), cx));
+        });
+        project
+            .update(cx, |project, cx| {
+                project.save_buffer(edited_buffer.clone(), cx)
+            })
+            .await
+            .unwrap();
+
+        let created_path = project
+            .read_with(cx, |project, cx| {
+                project.find_project_path("dir/created", cx)
+            })
+            .unwrap();
+        let created_buffer = project
+            .update(cx, |project, cx| project.open_buffer(created_path, cx))
+            .await
+            .unwrap();
+        cx.update(|cx| {
+            action_log.update(cx, |log, cx| log.buffer_created(created_buffer.clone(), cx));
+            created_buffer.update(cx, |buffer, cx| buffer.set_text("new", cx));
+            action_log.update(cx, |log, cx| log.buffer_edited(created_buffer.clone(), cx));
+        });
+        project
+            .update(cx, |project, cx| {
+                project.save_buffer(created_buffer.clone(), cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        // A checkpoint taken after the edits only differs from the current state once the
+        // files change again.
+        let later_checkpoint = action_log.update(cx, |log, cx| log.checkpoint(cx));
+        let changes = action_log
+            .update(cx, |log, cx| {
+                log.changes_since_checkpoint(later_checkpoint, cx)
+            })
+            .await
+            .unwrap();
+        assert!(changes.is_empty());
+
+        let changes = action_log
+            .update(cx, |log, cx| log.changes_since_checkpoint(checkpoint, cx))
+            .await
+            .unwrap();
+        let mut changes = changes
+            .into_iter()
+            .map(|change| {
+                (
+                    change.buffer.read_with(cx, |buffer, _| buffer.text()),
+                    change.checkpoint_text.map(|text| text.to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+        changes.sort();
+        assert_eq!(
+            changes,
+            vec![
+                ("new".to_string(), None),
+                ("two".to_string(), Some("one".to_string()))
+            ]
+        );
+
+        action_log
+            .update(cx, |log, cx| log.restore_checkpoint(checkpoint, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(fs.load(path!("/dir/edited").as_ref()).await.unwrap(), "one");
+        assert!(!fs.is_file(path!("/dir/created").as_ref()).await);
+        let changes = action_log
+            .update(cx, |log, cx| log.changes_since_checkpoint(checkpoint, cx))
+            .await
+            .unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_collect_edits(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({"shared": "abc\ndef\nghi", "other": "jkl"}),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let first_log = cx.new(|_| ActionLog::new(project.clone()));
+        let second_log = cx.new(|_| ActionLog::new(project.clone()));
+
+        let open_buffer = |path: &str, cx: &mut TestAppContext| {
+            let path = project
+                .read_with(cx, |project, cx| project.find_project_path(path, cx))
+                .unwrap();
+            project.update(cx, |project, cx| project.open_buffer(path, cx))
+        };
+        let shared_buffer = open_buffer("dir/shared", cx).await.unwrap();
+        let other_buffer = open_buffer("dir/other", cx).await.unwrap();
+
+        cx.update(|cx| {
+            first_log.update(cx, |log, cx| log.buffer_read(shared_buffer.clone(), cx));
+            shared_buffer.update(cx, |buffer, cx| {
+                buffer.edit([(Point::new(0, 0)..Point::new(0, 3), "ABC")], None, cx)
+            });
+            first_log.update(cx, |log, cx| log.buffer_edited(shared_buffer.clone(), cx));
+
+            second_log.update(cx, |log, cx| log.buffer_read(shared_buffer.clone(), cx));
+            shared_buffer.update(cx, |buffer, cx| {
+                buffer.edit([(Point::new(2, 0)..Point::new(2, 3), "GHI")], None, cx)
+            });
+            second_log.update(cx, |log, cx| log.buffer_edited(shared_buffer.clone(), cx));
+
+            second_log.update(cx, |log, cx| log.buffer_read(other_buffer.clone(), cx));
+            other_buffer.update(cx, |buffer, cx| {
+                buffer.edit([(Point::new(0, 0)..Point::new(0, 3), "JKL")], None, cx)
+            });
+            second_log.update(cx, |log, cx| log.buffer_edited(other_buffer.clone(), cx));
+        });
+        cx.run_until_parked();
+
+        action_log.update(cx, |log, cx| {
+            log.collect_edits("First".into(), &first_log, cx);
+            log.collect_edits("Second".into(), &second_log, cx);
+        });
+        cx.run_until_parked();
+
+        let mut hunks = unreviewed_hunks(&action_log, cx)
+            .into_iter()
+            .map(|(buffer, hunks)| (buffer.read_with(cx, |buffer, _| buffer.text()), hunks))
+            .collect::<Vec<_>>();
+        hunks.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            hunks,
+            vec![
+                (
+                    "ABC\ndef\nGHI".to_string(),
+                    vec![
+                        HunkStatus {
+                            range: Point::new(0, 0)..Point::new(1, 0),
+                            diff_status: DiffHunkStatusKind::Modified,
+                            old_text: "abc\n".into(),
+                        },
+                        HunkStatus {
+                            range: Point::new(2, 0)..Point::new(2, 3),
+                            diff_status: DiffHunkStatusKind::Modified,
+                            old_text: "ghi".into(),
+                        }
+                    ]
+                ),
+                (
+                    "JKL".to_string(),
+                    vec![HunkStatus {
+                        range: Point::new(0, 0)..Point::new(0, 3),
+                        diff_status: DiffHunkStatusKind::Modified,
+                        old_text: "jkl".into(),
+                    }]
+                ),
+            ]
+        );
+
+        let conflicts = action_log.read_with(cx, |log, cx| log.edit_conflicts(cx));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].buffer, shared_buffer);
+        assert_eq!(
+            conflicts[0].sources,
+            vec![SharedString::from("First"), SharedString::from("Second")]
+        );
+        assert!(!conflicts[0].includes_own_edits);
+
+        action_log.update(cx, |log, cx| log.keep_all_edits(None, cx));
+        cx.run_until_parked();
+        assert!(
+            action_log
+                .read_with(cx, |log, cx| log.edit_conflicts(cx))
+                .is_empty()
+        );
+    }
+
     #[gpui::test(iterations = 10)]
     async fn test_reject_deleted_file(cx: &mut TestAppContext) {
         init_test(cx);
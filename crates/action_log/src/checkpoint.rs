@@ -0,0 +1,87 @@
+use collections::{BTreeMap, HashMap};
+use project::ProjectPath;
+use sha2::{Digest, Sha256};
+use text::Rope;
+
+/// Identifies the contents of a file in a [`CheckpointStore`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ContentHash([u8; 32]);
+
+impl ContentHash {
+    pub fn new(text: &Rope) -> Self {
+        let mut hasher = Sha256::new();
+        for chunk in text.chunks() {
+            hasher.update(chunk.as_bytes());
+        }
+        Self(hasher.finalize().into())
+    }
+}
+
+/// A snapshot of the files touched by agent tools, which can be restored without git.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileCheckpoint(usize);
+
+/// Stores the checkpoints of an [`ActionLog`](crate::ActionLog).
+///
+/// File contents are content-addressed, so that a file that is unchanged across many checkpoints
+/// is only stored once. A checkpoint only records the files that had been touched when it was
+/// taken; files that are touched for the first time afterwards are recorded in it when they are,
+/// since their contents at that point are the ones they had when the checkpoint was taken.
+#[derive(Default)]
+pub(crate) struct CheckpointStore {
+    blobs: HashMap<ContentHash, Rope>,
+    /// For every checkpoint, the contents of each file, or `None` if it didn't exist.
+    checkpoints: Vec<BTreeMap<ProjectPath, Option<ContentHash>>>,
+}
+
+impl CheckpointStore {
+    pub fn create(
+        &mut self,
+        files: impl IntoIterator<Item = (ProjectPath, Option<Rope>)>,
+    ) -> FileCheckpoint {
+        let files = files
+            .into_iter()
+            .map(|(path, text)| (path, text.map(|text| self.insert_blob(text))))
+            .collect();
+        self.checkpoints.push(files);
+        FileCheckpoint(self.checkpoints.len() - 1)
+    }
+
+    /// Records the contents a file had before agent tools first touched it, in every checkpoint
+    /// that was taken without it.
+    pub fn record_untouched(&mut self, path: ProjectPath, text: Option<Rope>) {
+        if self
+            .checkpoints
+            .iter()
+            .all(|checkpoint| checkpoint.contains_key(&path))
+        {
+            return;
+        }
+        let hash = text.map(|text| self.insert_blob(text));
+        for checkpoint in &mut self.checkpoints {
+            checkpoint.entry(path.clone()).or_insert(hash);
+        }
+    }
+
+    /// Returns the files recorded in the checkpoint, with their contents at that point.
+    pub fn files(
+        &self,
+        checkpoint: FileCheckpoint,
+    ) -> impl Iterator<Item = (&ProjectPath, Option<ContentHash>)> {
+        self.checkpoints
+            .get(checkpoint.0)
+            .into_iter()
+            .flatten()
+            .map(|(path, hash)| (path, *hash))
+    }
+
+    pub fn blob(&self, hash: ContentHash) -> Option<&Rope> {
+        self.blobs.get(&hash)
+    }
+
+    fn insert_blob(&mut self, text: Rope) -> ContentHash {
+        let hash = ContentHash::new(&text);
+        self.blobs.entry(hash).or_insert(text);
+        hash
+    }
+}
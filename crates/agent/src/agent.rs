@@ -4,7 +4,9 @@ mod legacy_thread;
 mod native_agent_server;
 pub mod outline;
 mod pattern_extraction;
+pub mod repo_map;
 mod templates;
+mod terminal_sandbox;
 #[cfg(test)]
 mod tests;
 mod thread;
@@ -41,8 +43,8 @@ use gpui::{
 use language_model::{IconOrSvg, LanguageModel, LanguageModelProvider, LanguageModelRegistry};
 use project::{Project, ProjectItem, ProjectPath, Worktree};
 use prompt_store::{
-    ProjectContext, PromptStore, RULES_FILE_NAMES, RulesFileContext, UserRulesContext,
-    WorktreeContext,
+    ProjectContext, PromptStore, RULES_FILE_NAMES, RulesFileContext, ScopedRulesFileContext,
+    UserRulesContext, WorktreeContext, parse_rules_file,
 };
 use serde::{Deserialize, Serialize};
 use settings::{LanguageModelSelection, update_settings_file};
@@ -383,6 +385,10 @@ impl NativeAgent {
         let subscriptions = vec![
             cx.subscribe(&thread_handle, Self::handle_thread_title_updated),
             cx.subscribe(&thread_handle, Self::handle_thread_token_usage_updated),
+            cx.subscribe(
+                &thread_handle,
+                Self::handle_thread_model_token_usage_recorded,
+            ),
             cx.observe(&thread_handle, move |this, thread, cx| {
                 this.save_thread(thread, cx)
             }),
@@ -504,24 +510,70 @@ impl NativeAgent {
             root_name,
             abs_path,
             rules_file: None,
+            scoped_rules_files: Vec::new(),
         };
 
-        let rules_task = Self::load_worktree_rules_file(worktree, project, cx);
-        let Some(rules_task) = rules_task else {
-            return Task::ready((context, None));
-        };
+        let rules_task = Self::load_worktree_rules_file(worktree.clone(), project.clone(), cx);
+        let scoped_rules_tasks = Self::find_nested_rules_files(worktree.read(cx))
+            .into_iter()
+            .map(|path_in_worktree| {
+                Self::load_rules_file(worktree.clone(), path_in_worktree, project.clone(), cx)
+            })
+            .collect::<Vec<_>>();
 
         cx.spawn(async move |_| {
-            let (rules_file, rules_file_error) = match rules_task.await {
-                Ok(rules_file) => (Some(rules_file), None),
-                Err(err) => (
-                    None,
-                    Some(RulesLoadingError {
-                        message: format!("{err}").into(),
-                    }),
-                ),
-            };
-            context.rules_file = rules_file;
+            let mut rules_file_error = None;
+            let mut rules_files = Vec::new();
+            if let Some(rules_task) = rules_task {
+                match rules_task.await {
+                    Ok(rules_file) => rules_files.push(rules_file),
+                    Err(err) => {
+                        rules_file_error = Some(RulesLoadingError {
+                            message: format!("{err}").into(),
+                        })
+                    }
+                }
+            }
+            for scoped_rules_task in future::join_all(scoped_rules_tasks).await {
+                match scoped_rules_task {
+                    Ok(rules_file) => rules_files.push(rules_file),
+                    Err(err) => {
+                        rules_file_error.get_or_insert(RulesLoadingError {
+                            message: format!("{err}").into(),
+                        });
+                    }
+                }
+            }
+
+            for rules_file in rules_files {
+                let is_root = RULES_FILE_NAMES.iter().any(|name| {
+                    rules_file.path_in_worktree.as_ref() == RelPath::unix(name).unwrap()
+                });
+                let (applies_to, text) = parse_rules_file(&rules_file.text);
+                let text = text.trim().to_string();
+                // Rules at the root of the worktree apply everywhere, unless they're restricted by globs.
+                if is_root && applies_to.is_empty() {
+                    context.rules_file = Some(RulesFileContext { text, ..rules_file });
+                    continue;
+                }
+                match ScopedRulesFileContext::new(
+                    rules_file.path_in_worktree.clone(),
+                    applies_to,
+                    text,
+                    rules_file.project_entry_id,
+                ) {
+                    Ok(scoped_rules_file) => context.scoped_rules_files.push(scoped_rules_file),
+                    Err(err) => {
+                        rules_file_error.get_or_insert(RulesLoadingError {
+                            message: format!(
+                                "Invalid `applies_to` globs in {:?}: {err}",
+                                rules_file.path_in_worktree
+                            )
+                            .into(),
+                        });
+                    }
+                }
+            }
             (context, rules_file_error)
         })
     }
@@ -531,12 +583,11 @@ impl NativeAgent {
         project: Entity<Project>,
         cx: &mut App,
     ) -> Option<Task<Result<RulesFileContext>>> {
-        let worktree = worktree.read(cx);
-        let worktree_id = worktree.id();
         let selected_rules_file = RULES_FILE_NAMES
             .into_iter()
             .filter_map(|name| {
                 worktree
+                    .read(cx)
                     .entry_for_path(RelPath::unix(name).unwrap())
                     .filter(|entry| entry.is_file())
                     .map(|entry| entry.path.clone())
@@ -545,29 +596,63 @@ impl NativeAgent {
 
         // Note that Cline supports `.clinerules` being a directory, but that is not currently
         // supported. This doesn't seem to occur often in GitHub repositories.
-        selected_rules_file.map(|path_in_worktree| {
-            let project_path = ProjectPath {
-                worktree_id,
-                path: path_in_worktree.clone(),
-            };
-            let buffer_task =
-                project.update(cx, |project, cx| project.open_buffer(project_path, cx));
-            let rope_task = cx.spawn(async move |cx| {
-                let buffer = buffer_task.await?;
-                let (project_entry_id, rope) = buffer.read_with(cx, |buffer, cx| {
-                    let project_entry_id = buffer.entry_id(cx).context("buffer has no file")?;
-                    anyhow::Ok((project_entry_id, buffer.as_rope().clone()))
-                })?;
-                anyhow::Ok((project_entry_id, rope))
-            });
-            // Build a string from the rope on a background thread.
-            cx.background_spawn(async move {
-                let (project_entry_id, rope) = rope_task.await?;
-                anyhow::Ok(RulesFileContext {
-                    path_in_worktree,
-                    text: rope.to_string().trim().to_string(),
-                    project_entry_id: project_entry_id.to_usize(),
-                })
+        selected_rules_file
+            .map(|path_in_worktree| Self::load_rules_file(worktree, path_in_worktree, project, cx))
+    }
+
+    /// Returns the rules files in the worktree's subdirectories, picking one
+    /// file per directory in the same order of preference as at the root.
+    fn find_nested_rules_files(worktree: &Worktree) -> Vec<Arc<RelPath>> {
+        let mut rules_files_by_directory = HashMap::<String, (usize, Arc<RelPath>)>::default();
+        for entry in worktree.files(false, 0) {
+            for (preference, name) in RULES_FILE_NAMES.iter().enumerate() {
+                let Some(directory) = entry
+                    .path
+                    .as_unix_str()
+                    .strip_suffix(name)
+                    .and_then(|directory| directory.strip_suffix('/'))
+                else {
+                    continue;
+                };
+                let rules_file = rules_files_by_directory
+                    .entry(directory.to_string())
+                    .or_insert_with(|| (preference, entry.path.clone()));
+                if preference < rules_file.0 {
+                    *rules_file = (preference, entry.path.clone());
+                }
+            }
+        }
+        let mut rules_files = rules_files_by_directory.into_iter().collect::<Vec<_>>();
+        rules_files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        rules_files.into_iter().map(|(_, (_, path))| path).collect()
+    }
+
+    fn load_rules_file(
+        worktree: Entity<Worktree>,
+        path_in_worktree: Arc<RelPath>,
+        project: Entity<Project>,
+        cx: &mut App,
+    ) -> Task<Result<RulesFileContext>> {
+        let project_path = ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: path_in_worktree.clone(),
+        };
+        let buffer_task = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        let rope_task = cx.spawn(async move |cx| {
+            let buffer = buffer_task.await?;
+            let (project_entry_id, rope) = buffer.read_with(cx, |buffer, cx| {
+                let project_entry_id = buffer.entry_id(cx).context("buffer has no file")?;
+                anyhow::Ok((project_entry_id, buffer.as_rope().clone()))
+            })?;
+            anyhow::Ok((project_entry_id, rope))
+        });
+        // Build a string from the rope on a background thread.
+        cx.background_spawn(async move {
+            let (project_entry_id, rope) = rope_task.await?;
+            anyhow::Ok(RulesFileContext {
+                path_in_worktree,
+                text: rope.to_string().trim().to_string(),
+                project_entry_id: project_entry_id.to_usize(),
             })
         })
     }
@@ -606,6 +691,29 @@ impl NativeAgent {
         });
     }
 
+    fn handle_thread_model_token_usage_recorded(
+        &mut self,
+        thread: Entity<Thread>,
+        event: &ModelTokenUsageRecorded,
+        cx: &mut Context<Self>,
+    ) {
+        self.thread_store.update(cx, |store, cx| {
+            store.record_token_usage(event.0.clone(), cx);
+        });
+
+        // What a subagent uses also counts towards the threads that spawned it.
+        let mut parent_id = thread.read(cx).parent_thread_id();
+        while let Some(parent) = parent_id
+            .and_then(|parent_id| self.sessions.get(&parent_id))
+            .map(|session| session.thread.clone())
+        {
+            parent_id = parent.update(cx, |parent, cx| {
+                parent.add_subagent_token_usage(event.0.clone(), cx);
+                parent.parent_thread_id()
+            });
+        }
+    }
+
     fn handle_project_event(
         &mut self,
         _project: Entity<Project>,
@@ -620,7 +728,7 @@ impl NativeAgent {
                 if items.iter().any(|(path, _, _)| {
                     RULES_FILE_NAMES
                         .iter()
-                        .any(|name| path.as_ref() == RelPath::unix(name).unwrap())
+                        .any(|name| path.ends_with(RelPath::unix(name).unwrap()))
                 }) {
                     self.project_context_needs_refresh.send(()).ok();
                 }
@@ -1071,6 +1179,11 @@ impl NativeAgentConnection {
                                     thread.update_retry_status(status, cx)
                                 })?;
                             }
+                            ThreadEvent::ContextCompacted(compacted_before) => {
+                                acp_thread.update(cx, |thread, cx| {
+                                    thread.context_compacted(compacted_before, cx)
+                                })?;
+                            }
                             ThreadEvent::Stop(stop_reason) => {
                                 log::debug!("Assistant message complete: {:?}", stop_reason);
                                 return Ok(acp::PromptResponse::new(stop_reason));
@@ -1585,6 +1698,7 @@ impl NativeThreadEnvironment {
         parent_thread_entity: Entity<Thread>,
         label: String,
         initial_prompt: String,
+        path_scope: Option<PathScope>,
         cx: &mut App,
     ) -> Result<Rc<dyn SubagentHandle>> {
         let parent_thread = parent_thread_entity.read(cx);
@@ -1596,10 +1710,14 @@ impl NativeThreadEnvironment {
                 MAX_SUBAGENT_DEPTH
             ));
         }
+        if let Some(path_scope) = &path_scope {
+            Self::ensure_disjoint_path_scope(&parent_thread_entity, path_scope, cx)?;
+        }
 
         let subagent_thread: Entity<Thread> = cx.new(|cx| {
             let mut thread = Thread::new_subagent(&parent_thread_entity, cx);
             thread.set_title(label.into(), cx);
+            thread.set_path_scope(path_scope);
             thread
         });
 
@@ -1633,6 +1751,9 @@ impl NativeThreadEnvironment {
                 .ok_or_else(|| anyhow!("No subagent session found with id {session_id}"))?;
             anyhow::Ok((session.thread.clone(), session.acp_thread.clone()))
         })??;
+        if let Some(path_scope) = subagent_thread.read(cx).path_scope() {
+            Self::ensure_disjoint_path_scope(&parent_thread_entity, path_scope, cx)?;
+        }
 
         Self::prompt_subagent(
             session_id,
@@ -1644,6 +1765,26 @@ impl NativeThreadEnvironment {
         )
     }
 
+    /// Subagents that run in parallel must modify disjoint sets of paths, so that their edits
+    /// don't conflict.
+    fn ensure_disjoint_path_scope(
+        parent_thread: &Entity<Thread>,
+        path_scope: &PathScope,
+        cx: &App,
+    ) -> Result<()> {
+        if let Some(subagent) = parent_thread
+            .read(cx)
+            .running_subagent_overlapping(path_scope, cx)
+        {
+            return Err(anyhow!(
+                "The paths overlap with the ones assigned to the running agent \"{}\". \
+                 Assign disjoint paths to agents that run in parallel.",
+                subagent.read(cx).title()
+            ));
+        }
+        Ok(())
+    }
+
     fn prompt_subagent(
         session_id: acp::SessionId,
         subagent_thread: Entity<Thread>,
@@ -1702,6 +1843,7 @@ impl ThreadEnvironment for NativeThreadEnvironment {
         parent_thread_entity: Entity<Thread>,
         label: String,
         initial_prompt: String,
+        path_scope: Option<PathScope>,
         cx: &mut App,
     ) -> Result<Rc<dyn SubagentHandle>> {
         Self::create_subagent_thread(
@@ -1709,6 +1851,7 @@ impl ThreadEnvironment for NativeThreadEnvironment {
             parent_thread_entity,
             label,
             initial_prompt,
+            path_scope,
             cx,
         )
     }
@@ -1855,7 +1998,21 @@ impl SubagentHandle for NativeSubagentHandle {
 
             parent_thread
                 .update(cx, |parent_thread, cx| {
-                    parent_thread.unregister_running_subagent(&subagent_session_id, cx)
+                    parent_thread.unregister_running_subagent(&subagent_session_id, cx);
+
+                    // Agents restricted to some paths work on a part of the parent's task, so
+                    // their edits are moved to the parent's action log, to be reviewed along
+                    // with the parent's.
+                    let subagent = thread.read(cx);
+                    if subagent.path_scope().is_some() {
+                        let title = subagent.title();
+                        let subagent_action_log = subagent.action_log().clone();
+                        parent_thread.action_log().update(cx, |action_log, cx| {
+                            action_log.collect_edits(title, &subagent_action_log, cx)
+                        });
+                        subagent_action_log
+                            .update(cx, |action_log, cx| action_log.keep_all_edits(None, cx));
+                    }
                 })
                 .ok();
 
@@ -1953,7 +2110,8 @@ mod internal_tests {
                 vec![WorktreeContext {
                     root_name: "a".into(),
                     abs_path: Path::new("/a").into(),
-                    rules_file: None
+                    rules_file: None,
+                    scoped_rules_files: Vec::new(),
                 }]
             )
         });
@@ -1975,7 +2133,8 @@ mod internal_tests {
                         path_in_worktree: rel_path(".rules").into(),
                         text: "".into(),
                         project_entry_id: rules_entry.id.to_usize()
-                    })
+                    }),
+                    scoped_rules_files: Vec::new(),
                 }]
             )
         });
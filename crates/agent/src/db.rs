@@ -3,11 +3,12 @@ use acp_thread::UserMessageId;
 use agent_client_protocol as acp;
 use agent_settings::AgentProfileId;
 use anyhow::{Result, anyhow};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use collections::{HashMap, IndexMap};
 use futures::{FutureExt, future::Shared};
 use gpui::{BackgroundExecutor, Global, Task};
 use indoc::indoc;
+use language_model::LanguageModel;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -46,6 +47,8 @@ pub struct DbThread {
     #[serde(default)]
     pub request_token_usage: HashMap<acp_thread::UserMessageId, language_model::TokenUsage>,
     #[serde(default)]
+    pub model_token_usage: Vec<ModelTokenUsage>,
+    #[serde(default)]
     pub model: Option<DbLanguageModel>,
     #[serde(default)]
     pub profile: Option<AgentProfileId>,
@@ -55,6 +58,49 @@ pub struct DbThread {
     pub subagent_context: Option<crate::SubagentContext>,
 }
 
+/// The tokens used with a model and their estimated cost.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelTokenUsage {
+    pub provider: SharedString,
+    pub model: SharedString,
+    pub usage: language_model::TokenUsage,
+    /// The cost in USD, if the model's pricing is known.
+    #[serde(default)]
+    pub cost: Option<f64>,
+}
+
+impl ModelTokenUsage {
+    pub fn new(model: &dyn LanguageModel, usage: language_model::TokenUsage) -> Self {
+        Self {
+            provider: model.provider_id().0,
+            model: model.id().0,
+            usage,
+            cost: model
+                .model_cost_info()
+                .and_then(|cost_info| cost_info.cost(&usage)),
+        }
+    }
+
+    pub fn is_same_model(&self, other: &Self) -> bool {
+        self.provider == other.provider && self.model == other.model
+    }
+
+    pub fn add(&mut self, other: &Self) {
+        self.usage = self.usage + other.usage;
+        self.cost = match (self.cost, other.cost) {
+            (Some(cost), Some(other_cost)) => Some(cost + other_cost),
+            (cost, other_cost) => cost.or(other_cost),
+        };
+    }
+}
+
+/// Token usage of a model on a given local day, across all threads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyTokenUsage {
+    pub day: NaiveDate,
+    pub usage: ModelTokenUsage,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SharedThread {
     pub title: SharedString,
@@ -87,6 +133,7 @@ impl SharedThread {
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
             request_token_usage: Default::default(),
+            model_token_usage: Vec::new(),
             model: self.model,
             profile: None,
             imported: true,
@@ -261,6 +308,7 @@ impl DbThread {
             initial_project_snapshot: thread.initial_project_snapshot,
             cumulative_token_usage: thread.cumulative_token_usage,
             request_token_usage,
+            model_token_usage: Vec::new(),
             model: thread.model,
             profile: thread.profile,
             imported: false,
@@ -369,6 +417,21 @@ impl ThreadsDatabase {
             s().ok();
         }
 
+        connection.exec(indoc! {"
+            CREATE TABLE IF NOT EXISTS daily_token_usage (
+                day TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cache_creation_input_tokens INTEGER NOT NULL,
+                cache_read_input_tokens INTEGER NOT NULL,
+                cost REAL,
+                PRIMARY KEY (day, provider, model)
+            )
+        "})?()
+        .map_err(|e| anyhow!("Failed to create daily_token_usage table: {}", e))?;
+
         let db = Self {
             executor,
             connection: Arc::new(Mutex::new(connection)),
@@ -493,6 +556,113 @@ impl ThreadsDatabase {
         })
     }
 
+    /// Adds the given usage to the totals of its model on the given day.
+    pub fn record_token_usage(&self, usage: DailyTokenUsage) -> Task<Result<()>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+
+            let mut insert = connection.exec_bound::<(
+                String,
+                Arc<str>,
+                Arc<str>,
+                i64,
+                i64,
+                i64,
+                i64,
+                Option<f64>,
+            )>(indoc! {"
+                INSERT INTO daily_token_usage (
+                    day, provider, model, input_tokens, output_tokens,
+                    cache_creation_input_tokens, cache_read_input_tokens, cost
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT (day, provider, model) DO UPDATE SET
+                    input_tokens = input_tokens + excluded.input_tokens,
+                    output_tokens = output_tokens + excluded.output_tokens,
+                    cache_creation_input_tokens = cache_creation_input_tokens + excluded.cache_creation_input_tokens,
+                    cache_read_input_tokens = cache_read_input_tokens + excluded.cache_read_input_tokens,
+                    cost = CASE
+                        WHEN cost IS NULL THEN excluded.cost
+                        WHEN excluded.cost IS NULL THEN cost
+                        ELSE cost + excluded.cost
+                    END
+            "})?;
+
+            let DailyTokenUsage { day, usage } = usage;
+            insert((
+                day.to_string(),
+                usage.provider.into(),
+                usage.model.into(),
+                usage.usage.input_tokens as i64,
+                usage.usage.output_tokens as i64,
+                usage.usage.cache_creation_input_tokens as i64,
+                usage.usage.cache_read_input_tokens as i64,
+                usage.cost,
+            ))?;
+
+            Ok(())
+        })
+    }
+
+    /// Returns the token usage per model and day, starting at the given day.
+    pub fn daily_token_usage(&self, since: NaiveDate) -> Task<Result<Vec<DailyTokenUsage>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+
+            let mut select = connection.select_bound::<String, (
+                String,
+                Arc<str>,
+                Arc<str>,
+                i64,
+                i64,
+                i64,
+                i64,
+                Option<f64>,
+            )>(indoc! {"
+                SELECT
+                    day, provider, model, input_tokens, output_tokens,
+                    cache_creation_input_tokens, cache_read_input_tokens, cost
+                FROM daily_token_usage
+                WHERE day >= ?
+                ORDER BY day DESC, provider, model
+            "})?;
+
+            let rows = select(since.to_string())?;
+            let mut usage = Vec::new();
+            for (
+                day,
+                provider,
+                model,
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens,
+                cache_read_input_tokens,
+                cost,
+            ) in rows
+            {
+                usage.push(DailyTokenUsage {
+                    day: day.parse()?,
+                    usage: ModelTokenUsage {
+                        provider: provider.into(),
+                        model: model.into(),
+                        usage: language_model::TokenUsage {
+                            input_tokens: input_tokens as u64,
+                            output_tokens: output_tokens as u64,
+                            cache_creation_input_tokens: cache_creation_input_tokens as u64,
+                            cache_read_input_tokens: cache_read_input_tokens as u64,
+                        },
+                        cost,
+                    },
+                });
+            }
+
+            Ok(usage)
+        })
+    }
+
     pub fn delete_threads(&self) -> Task<Result<()>> {
         let connection = self.connection.clone();
 
@@ -566,6 +736,7 @@ mod tests {
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
             request_token_usage: HashMap::default(),
+            model_token_usage: Vec::new(),
             model: None,
             profile: None,
             imported: false,
@@ -667,6 +838,7 @@ mod tests {
         child_thread.subagent_context = Some(crate::SubagentContext {
             parent_thread_id: parent_id.clone(),
             depth: 2,
+            path_scope: None,
         });
 
         database
@@ -713,4 +885,51 @@ mod tests {
             "Regular threads should have no subagent_context"
         );
     }
+
+    #[gpui::test]
+    async fn test_daily_token_usage(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        let model_usage = |model: &str, input_tokens, cost| ModelTokenUsage {
+            provider: "anthropic".into(),
+            model: model.to_string().into(),
+            usage: language_model::TokenUsage {
+                input_tokens,
+                output_tokens: 10,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 0,
+            },
+            cost,
+        };
+
+        for (day, usage) in [
+            (date(1), model_usage("claude-sonnet-4-5", 100, Some(1.))),
+            (date(2), model_usage("claude-sonnet-4-5", 100, Some(1.))),
+            (date(2), model_usage("claude-sonnet-4-5", 50, Some(0.5))),
+            (date(2), model_usage("custom-model", 100, None)),
+        ] {
+            database
+                .record_token_usage(DailyTokenUsage { day, usage })
+                .await
+                .unwrap();
+        }
+
+        let mut expected = model_usage("claude-sonnet-4-5", 150, Some(1.5));
+        expected.usage.output_tokens = 20;
+        assert_eq!(
+            database.daily_token_usage(date(2)).await.unwrap(),
+            vec![
+                DailyTokenUsage {
+                    day: date(2),
+                    usage: expected,
+                },
+                DailyTokenUsage {
+                    day: date(2),
+                    usage: model_usage("custom-model", 100, None),
+                },
+            ]
+        );
+        assert_eq!(database.daily_token_usage(date(1)).await.unwrap().len(), 3);
+    }
 }
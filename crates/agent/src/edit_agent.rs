@@ -15,13 +15,12 @@ use futures::{
     Stream, StreamExt,
     channel::mpsc::{self, UnboundedReceiver},
     pin_mut,
-    stream::BoxStream,
 };
 use gpui::{AppContext, AsyncApp, Entity, Task};
 use language::{Anchor, Buffer, BufferSnapshot, LineIndent, Point, TextBufferSnapshot};
 use language_model::{
     LanguageModel, LanguageModelCompletionError, LanguageModelRequest, LanguageModelRequestMessage,
-    LanguageModelToolChoice, MessageContent, Role,
+    LanguageModelTextStream, LanguageModelToolChoice, MessageContent, Role, TokenUsage,
 };
 use project::{AgentLocation, Project};
 use schemars::JsonSchema;
@@ -72,6 +71,9 @@ pub enum EditAgentOutputEvent {
 pub struct EditAgentOutput {
     pub raw_edits: String,
     pub parser_metrics: EditParserMetrics,
+    /// The tokens used by the request that produced the edits.
+    #[serde(skip)]
+    pub token_usage: TokenUsage,
 }
 
 #[derive(Clone)]
@@ -128,11 +130,14 @@ impl EditAgent {
                 .request(conversation, CompletionIntent::CreateFile, prompt, cx)
                 .await?;
 
-            let (output, mut inner_events) = this.overwrite_with_chunks(buffer, new_chunks, cx);
+            let (output, mut inner_events) =
+                this.overwrite_with_chunks(buffer, new_chunks.stream, cx);
             while let Some(event) = inner_events.next().await {
                 events_tx.unbounded_send(event).ok();
             }
-            output.await
+            let mut output = output.await?;
+            output.token_usage = *new_chunks.last_token_usage.lock();
+            Ok(output)
         });
         (output, events_rx)
     }
@@ -265,8 +270,11 @@ impl EditAgent {
             let edit_chunks = this
                 .request(conversation, CompletionIntent::EditFile, prompt, cx)
                 .await?;
-            this.apply_edit_chunks(buffer, edit_chunks, events_tx, cx)
-                .await
+            let mut output = this
+                .apply_edit_chunks(buffer, edit_chunks.stream, events_tx, cx)
+                .await?;
+            output.token_usage = *edit_chunks.last_token_usage.lock();
+            Ok(output)
         });
         (output, events_rx)
     }
@@ -433,6 +441,7 @@ impl EditAgent {
             Ok(EditAgentOutput {
                 raw_edits,
                 parser_metrics: parser.finish(),
+                token_usage: TokenUsage::default(),
             })
         });
         (output, rx)
@@ -471,6 +480,7 @@ impl EditAgent {
             Ok(EditAgentOutput {
                 raw_edits,
                 parser_metrics: EditParserMetrics::default(),
+                token_usage: TokenUsage::default(),
             })
         });
         (output, rx)
@@ -689,7 +699,7 @@ impl EditAgent {
         intent: CompletionIntent,
         prompt: String,
         cx: &mut AsyncApp,
-    ) -> Result<BoxStream<'static, Result<String, LanguageModelCompletionError>>> {
+    ) -> Result<LanguageModelTextStream> {
         let mut messages_iter = conversation.messages.iter_mut();
         if let Some(last_message) = messages_iter.next_back()
             && last_message.role == Role::Assistant
@@ -749,9 +759,10 @@ impl EditAgent {
             temperature: None,
             thinking_allowed: self.thinking_allowed,
             thinking_effort: None,
+            max_tokens: None,
         };
 
-        Ok(self.model.stream_completion_text(request, cx).await?.stream)
+        Ok(self.model.stream_completion_text(request, cx).await?)
     }
 }
 
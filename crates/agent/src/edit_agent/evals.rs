@@ -1523,6 +1523,7 @@ impl EditAgentTest {
                 root_name: "root".to_string(),
                 abs_path: Path::new("/path/to/root").into(),
                 rules_file: None,
+                scoped_rules_files: Vec::new(),
             }];
             let project_context = ProjectContext::new(worktrees, Vec::default());
             let tool_names = tools
@@ -0,0 +1,527 @@
+use collections::{HashMap, HashSet};
+use futures::{FutureExt as _, future::Shared};
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task, WeakEntity,
+};
+use language::{Buffer, BufferSnapshot, LanguageRegistry, OffsetRangeExt as _};
+use project::{PathChange, Project, ProjectPath, WorktreeId};
+use std::{cmp::Reverse, fmt::Write as _, sync::Arc};
+use text::Rope;
+use util::rel_path::RelPath;
+
+/// Files larger than this are left out of the map, since they are usually generated.
+const MAX_INDEXED_FILE_SIZE: u64 = 512 * 1024;
+/// Upper bound on the number of files indexed per worktree.
+const MAX_INDEXED_FILES_PER_WORKTREE: usize = 20_000;
+/// Rough conversion used to keep the rendered map within a token budget.
+const BYTES_PER_TOKEN: usize = 4;
+
+/// A symbol extracted from a file's outline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RepoSymbol {
+    /// The symbol's identifier, e.g. `Thread` for `pub struct Thread`.
+    pub name: String,
+    /// The outline text describing the symbol, e.g. `pub struct Thread`.
+    pub text: String,
+    /// Nesting depth within the file's outline.
+    pub depth: usize,
+    /// Zero-based row of the symbol's name.
+    pub row: u32,
+    /// Zero-based column of the symbol's name.
+    pub column: u32,
+}
+
+/// A symbol together with the file it was found in.
+#[derive(Clone, Debug)]
+pub struct RepoSymbolMatch {
+    pub path: ProjectPath,
+    pub symbol: RepoSymbol,
+}
+
+struct FileSymbols {
+    symbols: Vec<RepoSymbol>,
+    identifiers: HashSet<String>,
+}
+
+#[derive(Default)]
+struct WorktreeRepoMap {
+    files: HashMap<Arc<RelPath>, FileSymbols>,
+    stale: HashSet<Arc<RelPath>>,
+    scanned: bool,
+}
+
+#[derive(Default)]
+struct RepoMaps(HashMap<EntityId, WeakEntity<RepoMap>>);
+
+impl Global for RepoMaps {}
+
+/// An index of the symbols defined in a project, built from each language's
+/// outline query.
+///
+/// The map is shared by every thread working on the same project. Each worktree
+/// is scanned the first time the map is refreshed, after which only the files
+/// reported as changed by the worktree are re-indexed.
+pub struct RepoMap {
+    project: WeakEntity<Project>,
+    languages: Arc<LanguageRegistry>,
+    worktrees: HashMap<WorktreeId, WorktreeRepoMap>,
+    pending_refresh: Option<Shared<Task<()>>>,
+    _subscription: Subscription,
+}
+
+impl RepoMap {
+    /// Returns the map for the given project, creating it if no thread holds one yet.
+    pub fn for_project(project: &Entity<Project>, cx: &mut App) -> Entity<Self> {
+        let project_id = project.entity_id();
+        if let Some(repo_map) = cx
+            .try_global::<RepoMaps>()
+            .and_then(|maps| maps.0.get(&project_id))
+            .and_then(|repo_map| repo_map.upgrade())
+        {
+            return repo_map;
+        }
+
+        let repo_map = cx.new(|cx| Self::new(project.clone(), cx));
+        let maps = cx.default_global::<RepoMaps>();
+        maps.0.retain(|_, repo_map| repo_map.upgrade().is_some());
+        maps.0.insert(project_id, repo_map.downgrade());
+        repo_map
+    }
+
+    fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let languages = project.read(cx).languages().clone();
+        let subscription = cx.subscribe(&project, Self::handle_project_event);
+        Self {
+            project: project.downgrade(),
+            languages,
+            worktrees: HashMap::default(),
+            pending_refresh: None,
+            _subscription: subscription,
+        }
+    }
+
+    fn handle_project_event(
+        &mut self,
+        _project: Entity<Project>,
+        event: &project::Event,
+        _cx: &mut Context<Self>,
+    ) {
+        match event {
+            project::Event::WorktreeRemoved(worktree_id) => {
+                self.worktrees.remove(worktree_id);
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                let Some(worktree) = self.worktrees.get_mut(worktree_id) else {
+                    return;
+                };
+                for (path, _, change) in changes.iter() {
+                    if *change == PathChange::Removed {
+                        worktree
+                            .files
+                            .retain(|file_path, _| !file_path.starts_with(path));
+                        worktree.stale.remove(path);
+                    } else {
+                        worktree.stale.insert(path.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Indexes any worktree that hasn't been scanned yet and re-indexes files
+    /// that changed since the last refresh.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) -> Shared<Task<()>> {
+        if let Some(pending_refresh) = self.pending_refresh.clone() {
+            return pending_refresh;
+        }
+
+        let Some(project) = self.project.upgrade() else {
+            return Task::ready(()).shared();
+        };
+
+        let mut paths_to_index = Vec::new();
+        for worktree in project.read(cx).visible_worktrees(cx) {
+            let worktree_entity = worktree.clone();
+            let snapshot = worktree.read(cx).snapshot();
+            let worktree_map = self.worktrees.entry(snapshot.id()).or_default();
+            let is_indexable = |path: &RelPath| {
+                snapshot.entry_for_path(path).is_some_and(|entry| {
+                    entry.is_file()
+                        && !entry.is_ignored
+                        && !entry.is_external
+                        && !entry.is_private
+                        && entry.size <= MAX_INDEXED_FILE_SIZE
+                }) && self
+                    .languages
+                    .language_for_file_path(path.as_std_path())
+                    .is_some()
+            };
+
+            if worktree_map.scanned {
+                for path in worktree_map.stale.drain() {
+                    if is_indexable(&path) {
+                        paths_to_index.push((worktree_entity.clone(), path));
+                    } else {
+                        worktree_map.files.remove(&path);
+                    }
+                }
+            } else {
+                worktree_map.scanned = true;
+                worktree_map.stale.clear();
+                paths_to_index.extend(
+                    snapshot
+                        .files(false, 0)
+                        .filter(|entry| is_indexable(&entry.path))
+                        .take(MAX_INDEXED_FILES_PER_WORKTREE)
+                        .map(|entry| (worktree_entity.clone(), entry.path.clone())),
+                );
+            }
+        }
+
+        if paths_to_index.is_empty() {
+            return Task::ready(()).shared();
+        }
+
+        let languages = self.languages.clone();
+        let task = cx
+            .spawn(async move |this, cx| {
+                for (worktree, path) in paths_to_index {
+                    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+                    let file_symbols =
+                        index_file(worktree, path.clone(), languages.clone(), cx).await;
+                    let updated = this.update(cx, |this, _| {
+                        let Some(worktree_map) = this.worktrees.get_mut(&worktree_id) else {
+                            return;
+                        };
+                        match file_symbols {
+                            Some(file_symbols) => {
+                                worktree_map.files.insert(path, file_symbols);
+                            }
+                            None => {
+                                worktree_map.files.remove(&path);
+                            }
+                        }
+                    });
+                    if updated.is_err() {
+                        return;
+                    }
+                }
+                this.update(cx, |this, _| this.pending_refresh = None).ok();
+            })
+            .shared();
+        self.pending_refresh = Some(task.clone());
+        task
+    }
+
+    /// Returns every indexed symbol with the given name.
+    pub fn definitions(&self, name: &str) -> Vec<RepoSymbolMatch> {
+        let mut matches = self
+            .files()
+            .flat_map(|(path, file)| {
+                file.symbols
+                    .iter()
+                    .filter(|symbol| symbol.name == name)
+                    .map(move |symbol| RepoSymbolMatch {
+                        path: path.clone(),
+                        symbol: symbol.clone(),
+                    })
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| {
+            (a.symbol.depth, &a.path, a.symbol.row).cmp(&(b.symbol.depth, &b.path, b.symbol.row))
+        });
+        matches
+    }
+
+    /// Returns the indexed files that mention the given identifier.
+    pub fn files_mentioning(&self, name: &str) -> Vec<ProjectPath> {
+        let mut paths = self
+            .files()
+            .filter(|(_, file)| file.identifiers.contains(name))
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    /// Renders the most referenced symbols, grouped by file, without exceeding
+    /// roughly `max_tokens` tokens.
+    ///
+    /// Symbols are ranked by the number of other files that mention their name,
+    /// so that widely used types and functions come first.
+    pub fn render(&self, path_prefix: Option<&ProjectPath>, max_tokens: usize, cx: &App) -> String {
+        let Some(project) = self.project.upgrade() else {
+            return String::new();
+        };
+        let project = project.read(cx);
+        let path_style = project.path_style(cx);
+
+        let mut reference_counts = HashMap::<&str, usize>::default();
+        for (_, file) in self.files() {
+            for symbol in &file.symbols {
+                reference_counts.insert(symbol.name.as_str(), 0);
+            }
+        }
+        for (_, file) in self.files() {
+            for identifier in &file.identifiers {
+                if let Some(count) = reference_counts.get_mut(identifier.as_str()) {
+                    *count += 1;
+                }
+            }
+        }
+
+        let mut ranked = self
+            .files()
+            .filter(|(path, _)| {
+                path_prefix.is_none_or(|prefix| {
+                    path.worktree_id == prefix.worktree_id && path.path.starts_with(&prefix.path)
+                })
+            })
+            .flat_map(|(path, file)| {
+                let reference_counts = &reference_counts;
+                file.symbols.iter().map(move |symbol| {
+                    // The defining file always mentions the symbol, so don't count it.
+                    let references = reference_counts
+                        .get(symbol.name.as_str())
+                        .map_or(0, |count| count.saturating_sub(1));
+                    (references, path.clone(), symbol)
+                })
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|(a_references, a_path, a), (b_references, b_path, b)| {
+            (Reverse(a_references), a.depth, a_path, a.row).cmp(&(
+                Reverse(b_references),
+                b.depth,
+                b_path,
+                b.row,
+            ))
+        });
+
+        let max_bytes = max_tokens.saturating_mul(BYTES_PER_TOKEN);
+        let mut used_bytes = 0;
+        let mut included = HashMap::<ProjectPath, Vec<&RepoSymbol>>::default();
+        let mut omitted = 0;
+        for (_, path, symbol) in ranked {
+            let mut cost = symbol_line_len(symbol);
+            if !included.contains_key(&path) {
+                cost += path.path.as_unix_str().len() + 1;
+            }
+            if used_bytes + cost > max_bytes {
+                omitted += 1;
+                continue;
+            }
+            used_bytes += cost;
+            included.entry(path).or_default().push(symbol);
+        }
+
+        let mut files = included.into_iter().collect::<Vec<_>>();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut output = String::new();
+        for (path, mut symbols) in files {
+            let Some(worktree) = project.worktree_for_id(path.worktree_id, cx) else {
+                continue;
+            };
+            let full_path = worktree.read(cx).root_name().join(&path.path);
+            writeln!(output, "{}", full_path.display(path_style)).ok();
+            symbols.sort_by_key(|symbol| symbol.row);
+            for symbol in symbols {
+                write_symbol_line(&mut output, symbol);
+            }
+        }
+
+        if omitted > 0 {
+            writeln!(
+                output,
+                "\n{omitted} less referenced symbols were omitted. Pass a larger `max_tokens` or a `path` to see more."
+            )
+            .ok();
+        }
+        output
+    }
+
+    fn files(&self) -> impl Iterator<Item = (ProjectPath, &FileSymbols)> {
+        self.worktrees.iter().flat_map(|(worktree_id, worktree)| {
+            worktree.files.iter().map(|(path, file)| {
+                (
+                    ProjectPath {
+                        worktree_id: *worktree_id,
+                        path: path.clone(),
+                    },
+                    file,
+                )
+            })
+        })
+    }
+}
+
+fn write_symbol_line(output: &mut String, symbol: &RepoSymbol) {
+    writeln!(
+        output,
+        "{:indent$}{} [L{}]",
+        "",
+        symbol.text,
+        symbol.row + 1,
+        indent = (symbol.depth + 1) * 2
+    )
+    .ok();
+}
+
+fn symbol_line_len(symbol: &RepoSymbol) -> usize {
+    let mut line = String::new();
+    write_symbol_line(&mut line, symbol);
+    line.len()
+}
+
+async fn index_file(
+    worktree: Entity<project::Worktree>,
+    path: Arc<RelPath>,
+    languages: Arc<LanguageRegistry>,
+    cx: &mut gpui::AsyncApp,
+) -> Option<FileSymbols> {
+    let language = languages
+        .load_language_for_file_path(path.as_std_path())
+        .await
+        .ok()?;
+    let loaded_file = worktree
+        .update(cx, |worktree, cx| worktree.load_file(&path, cx))
+        .await
+        .ok()?;
+    let snapshot = cx
+        .update(|cx| {
+            Buffer::build_snapshot(
+                Rope::from(loaded_file.text.as_str()),
+                Some(language),
+                Some(languages),
+                cx,
+            )
+        })
+        .await;
+    Some(
+        cx.background_spawn(async move { extract_file_symbols(&snapshot) })
+            .await,
+    )
+}
+
+fn extract_file_symbols(snapshot: &BufferSnapshot) -> FileSymbols {
+    let symbols = snapshot
+        .outline(None)
+        .items
+        .into_iter()
+        .filter_map(|item| {
+            let name_range = item.name_ranges.last()?;
+            let name = item.text.get(name_range.clone())?.to_string();
+            let source_range = item.source_range_for_text.to_offset(snapshot);
+            let source_text = snapshot
+                .text_for_range(source_range.clone())
+                .collect::<String>();
+            let name_offset = source_range.start + source_text.find(&name).unwrap_or(0);
+            let position = snapshot.offset_to_point(name_offset);
+            Some(RepoSymbol {
+                name,
+                text: item.text,
+                depth: item.depth,
+                row: position.row,
+                column: position.column,
+            })
+        })
+        .collect();
+
+    let mut identifiers = HashSet::default();
+    for chunk in snapshot.text_for_range(0..snapshot.len()) {
+        // Identifiers split across chunk boundaries are rare and only affect ranking.
+        identifiers.extend(
+            chunk
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .filter(|word| word.len() > 1)
+                .filter(|word| !word.starts_with(|c: char| c.is_ascii_digit()))
+                .map(ToString::to_string),
+        );
+    }
+
+    FileSymbols {
+        symbols,
+        identifiers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_repo_map_ranks_and_updates(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "src": {
+                    "lib.rs": "pub struct Widget;\n\npub fn rarely_used() {}\n",
+                    "a.rs": "fn make_a() -> Widget { Widget }\n",
+                    "b.rs": "fn make_b() -> Widget { Widget }\n",
+                },
+                "README.md": "Not code.",
+            }),
+        )
+        .await;
+        let project = project::Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        project.update(cx, |project, _| {
+            project.languages().add(language::rust_lang())
+        });
+
+        let repo_map = cx.update(|cx| RepoMap::for_project(&project, cx));
+        let same_repo_map = cx.update(|cx| RepoMap::for_project(&project, cx));
+        assert_eq!(repo_map, same_repo_map);
+
+        repo_map
+            .update(cx, |repo_map, cx| repo_map.refresh(cx))
+            .await;
+        let rendered = repo_map.read_with(cx, |repo_map, cx| repo_map.render(None, 1024, cx));
+        assert!(rendered.contains("pub struct Widget"), "{rendered}");
+        assert!(rendered.contains("fn make_a"), "{rendered}");
+        assert!(!rendered.contains("README"));
+
+        // A tight budget keeps only the most referenced symbol.
+        let rendered = repo_map.read_with(cx, |repo_map, cx| repo_map.render(None, 12, cx));
+        assert!(rendered.contains("pub struct Widget"), "{rendered}");
+        assert!(!rendered.contains("rarely_used"), "{rendered}");
+
+        let definitions = repo_map.read_with(cx, |repo_map, _| repo_map.definitions("Widget"));
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].symbol.row, 0);
+        assert_eq!(definitions[0].symbol.column, 11);
+
+        fs.insert_file(
+            path!("/root/src/c.rs"),
+            "pub fn make_c() -> Widget { Widget }\n".into(),
+        )
+        .await;
+        fs.remove_file(path!("/root/src/a.rs").as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        repo_map
+            .update(cx, |repo_map, cx| repo_map.refresh(cx))
+            .await;
+        let mentioning = repo_map.read_with(cx, |repo_map, _| {
+            repo_map
+                .files_mentioning("Widget")
+                .into_iter()
+                .map(|path| path.path.as_unix_str().to_string())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(mentioning, vec!["src/b.rs", "src/c.rs", "src/lib.rs"]);
+    }
+}
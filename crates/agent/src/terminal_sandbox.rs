@@ -0,0 +1,215 @@
+use agent_settings::TerminalSandboxSettings;
+use anyhow::{Context as _, Result};
+use gpui::{App, Entity};
+use project::Project;
+use std::path::{Path, PathBuf};
+use util::{get_default_system_shell_preferring_bash, paths::home_dir, shell::ShellKind};
+
+const WRITE_DENIED: &str = "The command tried to write outside of the project's worktrees, which the terminal sandbox doesn't allow. \
+    Write to a path inside the project instead, or ask the user to add the path to the sandbox's `writable_paths` setting.";
+
+const NETWORK_DENIED: &str = "The command tried to access the network, which the terminal sandbox doesn't allow. \
+    Ask the user to enable `allow_network` in the sandbox settings if network access is required.";
+
+const SETUP_FAILED: &str = "The terminal sandbox couldn't be set up, so the command didn't run. \
+    Ask the user to check that bubblewrap can create user namespaces on this system, or to disable the sandbox in the agent profile.";
+
+/// The exit code of bubblewrap when it fails to set up the sandbox, in which case it prints an
+/// error prefixed with `bwrap: `.
+const BWRAP_FAILURE_EXIT_CODE: u32 = 1;
+
+/// The errors that commands print when the sandbox denies them an operation: the descriptions of
+/// `EROFS` for writes to the read-only file system, and of `ENETUNREACH` and the resolver's
+/// `EAI_AGAIN` for network access without a network namespace. curl reports the latter in its own
+/// words.
+const DENIED_ERRORS: &[(&str, &str)] = &[
+    ("Read-only file system", WRITE_DENIED),
+    ("Network is unreachable", NETWORK_DENIED),
+    ("Temporary failure in name resolution", NETWORK_DENIED),
+    ("Could not resolve host", NETWORK_DENIED),
+];
+
+/// Runs the commands of the terminal tool with bubblewrap, in new mount, PID, IPC and (unless
+/// network access is allowed) network namespaces.
+///
+/// Inside the sandbox, the file system is mounted read-only except for the project's worktrees,
+/// the configured writable paths and a private `/tmp`. System calls aren't filtered: there's no
+/// seccomp or Landlock policy, so the namespaces are the only isolation.
+pub struct TerminalSandbox {
+    bwrap: PathBuf,
+    shell: String,
+    writable_paths: Vec<PathBuf>,
+    allow_network: bool,
+}
+
+impl TerminalSandbox {
+    pub fn new(
+        settings: &TerminalSandboxSettings,
+        project: &Entity<Project>,
+        cx: &App,
+    ) -> Result<Self> {
+        anyhow::ensure!(
+            cfg!(target_os = "linux"),
+            "The terminal sandbox is only supported on Linux. Disable it in the agent profile to run commands."
+        );
+        let project = project.read(cx);
+        anyhow::ensure!(
+            project.is_local(),
+            "The terminal sandbox isn't supported in remote projects. Disable it in the agent profile to run commands."
+        );
+        let bwrap = which::which("bwrap").context(
+            "The terminal sandbox requires bubblewrap (`bwrap`) to be installed. Install it or disable the sandbox in the agent profile to run commands.",
+        )?;
+
+        let writable_paths = project
+            .worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .chain(
+                settings
+                    .writable_paths
+                    .iter()
+                    .map(|path| match path.strip_prefix("~") {
+                        Ok(relative_path) => home_dir().join(relative_path),
+                        Err(_) => path.clone(),
+                    }),
+            )
+            .collect();
+
+        Ok(Self {
+            bwrap,
+            shell: get_default_system_shell_preferring_bash(),
+            writable_paths,
+            allow_network: settings.allow_network,
+        })
+    }
+
+    /// Returns a shell command that runs `command` in the sandbox.
+    pub fn wrap_command(&self, command: &str, cwd: Option<&Path>) -> Result<String> {
+        let mut args = vec![
+            self.bwrap.to_string_lossy().into_owned(),
+            "--ro-bind".into(),
+            "/".into(),
+            "/".into(),
+            "--dev".into(),
+            "/dev".into(),
+            "--proc".into(),
+            "/proc".into(),
+            "--tmpfs".into(),
+            "/tmp".into(),
+        ];
+        for path in &self.writable_paths {
+            let path = path.to_string_lossy().into_owned();
+            args.extend(["--bind-try".into(), path.clone(), path]);
+        }
+        if !self.allow_network {
+            args.push("--unshare-net".into());
+        }
+        args.extend([
+            "--unshare-pid".into(),
+            "--unshare-ipc".into(),
+            "--unshare-uts".into(),
+            "--die-with-parent".into(),
+            // Prevents commands from injecting input into the terminal.
+            "--new-session".into(),
+        ]);
+        if let Some(cwd) = cwd {
+            args.extend(["--chdir".into(), cwd.to_string_lossy().into_owned()]);
+        }
+        args.extend(["--".into(), self.shell.clone(), "-c".into(), command.into()]);
+
+        let args = args
+            .iter()
+            .map(|arg| {
+                ShellKind::Posix
+                    .try_quote(arg)
+                    .with_context(|| format!("failed to quote {arg:?} for the terminal sandbox"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(args.join(" "))
+    }
+}
+
+/// Returns an explanation for the model if a sandboxed command failed because the sandbox
+/// couldn't be set up, or because it denied the command an operation.
+pub fn denied_operation(exit_code: Option<u32>, output: &str) -> Option<&'static str> {
+    if exit_code == Some(0) {
+        return None;
+    }
+    if exit_code == Some(BWRAP_FAILURE_EXIT_CODE) && output.trim_start().starts_with("bwrap: ") {
+        return Some(SETUP_FAILED);
+    }
+    DENIED_ERRORS
+        .iter()
+        .find(|(error, _)| output.contains(error))
+        .map(|(_, explanation)| *explanation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_command() {
+        let sandbox = TerminalSandbox {
+            bwrap: "/usr/bin/bwrap".into(),
+            shell: "bash".into(),
+            writable_paths: vec!["/home/user/project".into(), "/home/user/my cache".into()],
+            allow_network: false,
+        };
+        assert_eq!(
+            sandbox
+                .wrap_command("echo hi > out.txt", Some(Path::new("/home/user/project")))
+                .unwrap(),
+            "/usr/bin/bwrap --ro-bind / / --dev /dev --proc /proc --tmpfs /tmp \
+            --bind-try /home/user/project /home/user/project \
+            --bind-try '/home/user/my cache' '/home/user/my cache' \
+            --unshare-net --unshare-pid --unshare-ipc --unshare-uts --die-with-parent --new-session \
+            --chdir /home/user/project -- bash -c 'echo hi > out.txt'"
+        );
+
+        let sandbox = TerminalSandbox {
+            allow_network: true,
+            ..sandbox
+        };
+        assert!(
+            !sandbox
+                .wrap_command("curl example.com", None)
+                .unwrap()
+                .contains("--unshare-net")
+        );
+    }
+
+    #[test]
+    fn test_denied_operation() {
+        assert_eq!(
+            denied_operation(
+                Some(1),
+                "touch: cannot touch '/etc/foo': Read-only file system"
+            ),
+            Some(WRITE_DENIED)
+        );
+        assert_eq!(
+            denied_operation(Some(6), "curl: (6) Could not resolve host: example.com"),
+            Some(NETWORK_DENIED)
+        );
+        assert_eq!(
+            denied_operation(Some(2), "ping: connect: Network is unreachable"),
+            Some(NETWORK_DENIED)
+        );
+        assert_eq!(
+            denied_operation(
+                Some(1),
+                "bwrap: No permissions to create new namespace, likely because the kernel does not allow non-privileged user namespaces."
+            ),
+            Some(SETUP_FAILED)
+        );
+        assert_eq!(
+            denied_operation(Some(0), "Read-only file system, remounting"),
+            None
+        );
+        assert_eq!(
+            denied_operation(Some(101), "error: could not compile `foo`"),
+            None
+        );
+    }
+}
@@ -21,7 +21,7 @@ use futures::{
     future::{Fuse, Shared},
 };
 use gpui::{
-    App, AppContext, AsyncApp, Entity, Task, TestAppContext, UpdateGlobal,
+    App, AppContext, AsyncApp, Entity, Task, TestAppContext, UpdateGlobal, WeakEntity,
     http_client::FakeHttpClient,
 };
 use indoc::indoc;
@@ -30,6 +30,7 @@ use language_model::{
     LanguageModelProviderName, LanguageModelRegistry, LanguageModelRequest,
     LanguageModelRequestMessage, LanguageModelToolResult, LanguageModelToolSchemaFormat,
     LanguageModelToolUse, MessageContent, Role, StopReason, TokenUsage,
+    cassette_provider::{CassetteLanguageModel, CassetteMode},
     fake_provider::FakeLanguageModel,
 };
 use pretty_assertions::assert_eq;
@@ -208,6 +209,7 @@ impl crate::ThreadEnvironment for FakeThreadEnvironment {
         _parent_thread: Entity<Thread>,
         _label: String,
         _initial_prompt: String,
+        _path_scope: Option<crate::PathScope>,
         _cx: &mut App,
     ) -> Result<Rc<dyn SubagentHandle>> {
         Ok(self
@@ -253,6 +255,7 @@ impl crate::ThreadEnvironment for MultiTerminalEnvironment {
         _parent_thread: Entity<Thread>,
         _label: String,
         _initial_prompt: String,
+        _path_scope: Option<crate::PathScope>,
         _cx: &mut App,
     ) -> Result<Rc<dyn SubagentHandle>> {
         unimplemented!()
@@ -291,6 +294,91 @@ async fn test_echo(cx: &mut TestAppContext) {
     assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
 }
 
+#[gpui::test]
+async fn test_cassette_replay(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        project_context,
+        context_server_store,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    let cassette_dir = tempfile::tempdir().unwrap();
+    let cassette_path = cassette_dir.path().join("echo.json");
+
+    // Record a turn that calls a tool, so the cassette holds the agent's real requests.
+    let recorder =
+        CassetteLanguageModel::new(model.clone(), CassetteMode::Record, &cassette_path).unwrap();
+    thread.update(cx, |thread, cx| {
+        thread.set_model(Arc::new(recorder), cx);
+        thread.add_tool(EchoTool);
+    });
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Use the echo tool"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: EchoTool::NAME.into(),
+            raw_input: json!({"text": "test"}).to_string(),
+            input: json!({"text": "test"}),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::ToolUse));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Echoed the text");
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::EndTurn));
+    fake_model.end_last_completion_stream();
+    assert_eq!(
+        stop_events(events.collect().await),
+        vec![acp::StopReason::EndTurn]
+    );
+    let recorded_markdown = thread.read_with(cx, |thread, _| thread.to_markdown());
+
+    // Replaying the same turn in a new thread serves every request from the cassette, even
+    // though the thread and prompt ids differ.
+    fake_model.forbid_requests();
+    let player =
+        CassetteLanguageModel::new(model.clone(), CassetteMode::Replay, &cassette_path).unwrap();
+    let context_server_registry =
+        cx.new(|cx| ContextServerRegistry::new(context_server_store.clone(), cx));
+    let replay_thread = cx.new(|cx| {
+        let mut thread = Thread::new(
+            thread.read(cx).project().clone(),
+            project_context.clone(),
+            context_server_registry,
+            Templates::new(),
+            Some(Arc::new(player)),
+            cx,
+        );
+        thread.add_tool(EchoTool);
+        thread
+    });
+    let events = replay_thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Use the echo tool"], cx)
+        })
+        .unwrap();
+    assert_eq!(
+        stop_events(events.collect().await),
+        vec![acp::StopReason::EndTurn]
+    );
+    assert_eq!(
+        replay_thread.read_with(cx, |thread, _| thread.to_markdown()),
+        recorded_markdown
+    );
+    assert!(fake_model.pending_completions().is_empty());
+}
+
 #[gpui::test]
 async fn test_terminal_tool_timeout_kills_handle(cx: &mut TestAppContext) {
     init_test(cx);
@@ -305,7 +393,11 @@ async fn test_terminal_tool_timeout_kills_handle(cx: &mut TestAppContext) {
     let handle = environment.terminal_handle.clone().unwrap();
 
     #[allow(clippy::arc_with_non_send_sync)]
-    let tool = Arc::new(crate::TerminalTool::new(project, environment));
+    let tool = Arc::new(crate::TerminalTool::new(
+        WeakEntity::new_invalid(),
+        project,
+        environment,
+    ));
     let (event_stream, mut rx) = crate::ToolCallEventStream::test();
 
     let task = cx.update(|cx| {
@@ -372,7 +464,11 @@ async fn test_terminal_tool_without_timeout_does_not_kill_handle(cx: &mut TestAp
     let handle = environment.terminal_handle.clone().unwrap();
 
     #[allow(clippy::arc_with_non_send_sync)]
-    let tool = Arc::new(crate::TerminalTool::new(project, environment));
+    let tool = Arc::new(crate::TerminalTool::new(
+        WeakEntity::new_invalid(),
+        project,
+        environment,
+    ));
     let (event_stream, mut rx) = crate::ToolCallEventStream::test();
 
     let _task = cx.update(|cx| {
@@ -1955,6 +2051,7 @@ async fn test_terminal_tool_cancellation_captures_output(cx: &mut TestAppContext
     let mut events = thread
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
+                cx.weak_entity(),
                 thread.project().clone(),
                 environment,
             ));
@@ -2238,6 +2335,7 @@ async fn test_truncate_while_terminal_tool_running(cx: &mut TestAppContext) {
     let mut events = thread
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
+                cx.weak_entity(),
                 thread.project().clone(),
                 environment,
             ));
@@ -2302,6 +2400,7 @@ async fn test_cancel_multiple_concurrent_terminal_tools(cx: &mut TestAppContext)
     let mut events = thread
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
+                cx.weak_entity(),
                 thread.project().clone(),
                 environment.clone(),
             ));
@@ -2415,6 +2514,7 @@ async fn test_terminal_tool_stopped_via_terminal_card_button(cx: &mut TestAppCon
     let mut events = thread
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
+                cx.weak_entity(),
                 thread.project().clone(),
                 environment,
             ));
@@ -2509,6 +2609,7 @@ async fn test_terminal_tool_timeout_expires(cx: &mut TestAppContext) {
     let mut events = thread
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
+                cx.weak_entity(),
                 thread.project().clone(),
                 environment,
             ));
@@ -2943,6 +3044,292 @@ async fn test_truncate_second_message(cx: &mut TestAppContext) {
     assert_first_message_state(cx);
 }
 
+#[gpui::test]
+async fn test_context_compaction(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let first_message_id = UserMessageId::new();
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(first_message_id.clone(), ["Message 1"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Message 1 response");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 850_000,
+            output_tokens: 50_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The first turn used more than 80% of the context window, so the next turn starts by
+    // summarizing it.
+    let second_message_id = UserMessageId::new();
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(second_message_id.clone(), ["Message 2"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.intent,
+        Some(CompletionIntent::ThreadContextSummarization)
+    );
+    assert_eq!(
+        completion.messages.last().unwrap().content,
+        vec![agent_settings::COMPACT_THREAD_PROMPT.into()]
+    );
+    fake_model.send_last_completion_stream_text_chunk("Summary of message 1");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.messages[1..],
+        vec![
+            LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![
+                    "The earlier part of this conversation was summarized to fit in the context window:\n\nSummary of message 1".into()
+                ],
+                cache: false,
+                reasoning_details: None,
+            },
+            LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Message 2".into()],
+                cache: true,
+                reasoning_details: None,
+            }
+        ]
+    );
+    fake_model.send_last_completion_stream_text_chunk("Message 2 response");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(
+            thread.to_markdown(),
+            indoc! {"
+                ## Summary
+
+                Summary of message 1
+
+                ## User
+
+                Message 2
+
+                ## Assistant
+
+                Message 2 response
+            "}
+        );
+    });
+
+    // Replaying the thread still shows the summarized messages.
+    let events = thread
+        .update(cx, |thread, cx| thread.replay(cx))
+        .collect::<Vec<_>>()
+        .await;
+    let events = events
+        .into_iter()
+        .filter_map(|event| match event.unwrap() {
+            ThreadEvent::UserMessage(message) => Some(format!("user {}", message.to_markdown())),
+            ThreadEvent::AgentText(text) => Some(format!("agent {text}")),
+            ThreadEvent::ContextCompacted(id) => {
+                assert_eq!(id, second_message_id);
+                Some("compacted".into())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            "user Message 1\n",
+            "agent Message 1 response",
+            "user Message 2\n",
+            "compacted",
+            "agent Message 2 response",
+        ]
+    );
+
+    // Truncating at a summarized message puts the summarized messages back first.
+    thread
+        .update(cx, |thread, cx| {
+            thread.truncate(second_message_id.clone(), cx)
+        })
+        .unwrap();
+    thread.read_with(cx, |thread, _| {
+        assert!(thread.to_markdown().starts_with("## Summary"));
+    });
+    thread
+        .update(cx, |thread, cx| thread.truncate(first_message_id, cx))
+        .unwrap();
+    thread.read_with(cx, |thread, _| assert_eq!(thread.to_markdown(), ""));
+}
+
+#[gpui::test]
+async fn test_context_compaction_between_tool_calls(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Message 1"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Message 1 response");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    thread.update(cx, |thread, _| thread.add_tool(EchoTool));
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Use the echo tool"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: EchoTool::NAME.into(),
+            raw_input: json!({"text": "test"}).to_string(),
+            input: json!({"text": "test"}),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 850_000,
+            output_tokens: 50_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The request that called the tool filled the context window, so the earlier turns are
+    // summarized before the tool's result is sent.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.intent,
+        Some(CompletionIntent::ThreadContextSummarization)
+    );
+    fake_model.send_last_completion_stream_text_chunk("Summary of message 1");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(completion.intent, Some(CompletionIntent::ToolResults));
+    assert_eq!(
+        completion.messages[1].content,
+        vec![
+            "The earlier part of this conversation was summarized to fit in the context window:\n\nSummary of message 1".into()
+        ]
+    );
+    assert_eq!(
+        completion.messages[2].content,
+        vec!["Use the echo tool".into()]
+    );
+    fake_model.send_last_completion_stream_text_chunk("Echoed the text");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    thread.read_with(cx, |thread, _| {
+        assert!(
+            thread
+                .to_markdown()
+                .starts_with("## Summary\n\nSummary of message 1")
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_model_token_usage(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    // Providers report the usage of the request so far, so only the difference is accumulated.
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 100,
+            output_tokens: 10,
+            cache_creation_input_tokens: 20,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model.send_last_completion_stream_text_chunk("Hey!");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 100,
+            output_tokens: 50,
+            cache_creation_input_tokens: 20,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::EndTurn));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["How are you?"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 10,
+            output_tokens: 30,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 120,
+        },
+    ));
+    fake_model.send_last_completion_stream_text_chunk("Good!");
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::EndTurn));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let expected_usage = language_model::TokenUsage {
+        input_tokens: 110,
+        output_tokens: 80,
+        cache_creation_input_tokens: 20,
+        cache_read_input_tokens: 120,
+    };
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.cumulative_token_usage(), expected_usage);
+        assert_eq!(
+            thread.model_token_usage(),
+            &[ModelTokenUsage {
+                provider: fake_model.provider_id().0,
+                model: fake_model.id().0,
+                usage: expected_usage,
+                cost: None,
+            }]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_title_generation(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -2989,6 +3376,59 @@ async fn test_title_generation(cx: &mut TestAppContext) {
     thread.read_with(cx, |thread, _| assert_eq!(thread.title(), "Hello world"));
 }
 
+#[gpui::test]
+async fn test_title_generation_token_usage(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let summary_model = Arc::new(FakeLanguageModel::default());
+    thread.update(cx, |thread, cx| {
+        thread.set_summarization_model(Some(summary_model.clone()), cx)
+    });
+
+    let send = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 100,
+            output_tokens: 10,
+            ..Default::default()
+        },
+    ));
+    fake_model.send_last_completion_stream_text_chunk("Hey!");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The request that generates the title is counted too, even though it isn't part of a turn.
+    summary_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 120,
+            output_tokens: 5,
+            ..Default::default()
+        },
+    ));
+    summary_model.send_last_completion_stream_text_chunk("Greeting");
+    summary_model.end_last_completion_stream();
+    send.collect::<Vec<_>>().await;
+    cx.run_until_parked();
+
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.title(), "Greeting");
+        assert_eq!(
+            thread.cumulative_token_usage(),
+            language_model::TokenUsage {
+                input_tokens: 220,
+                output_tokens: 15,
+                ..Default::default()
+            }
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_building_request_with_pending_tools(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -3986,7 +4426,11 @@ async fn test_terminal_tool_permission_rules(cx: &mut TestAppContext) {
         });
 
         #[allow(clippy::arc_with_non_send_sync)]
-        let tool = Arc::new(crate::TerminalTool::new(project.clone(), environment));
+        let tool = Arc::new(crate::TerminalTool::new(
+            WeakEntity::new_invalid(),
+            project.clone(),
+            environment,
+        ));
         let (event_stream, _rx) = crate::ToolCallEventStream::test();
 
         let task = cx.update(|cx| {
@@ -4038,7 +4482,11 @@ async fn test_terminal_tool_permission_rules(cx: &mut TestAppContext) {
         });
 
         #[allow(clippy::arc_with_non_send_sync)]
-        let tool = Arc::new(crate::TerminalTool::new(project.clone(), environment));
+        let tool = Arc::new(crate::TerminalTool::new(
+            WeakEntity::new_invalid(),
+            project.clone(),
+            environment,
+        ));
         let (event_stream, mut rx) = crate::ToolCallEventStream::test();
 
         let task = cx.update(|cx| {
@@ -4096,7 +4544,11 @@ async fn test_terminal_tool_permission_rules(cx: &mut TestAppContext) {
         });
 
         #[allow(clippy::arc_with_non_send_sync)]
-        let tool = Arc::new(crate::TerminalTool::new(project.clone(), environment));
+        let tool = Arc::new(crate::TerminalTool::new(
+            WeakEntity::new_invalid(),
+            project.clone(),
+            environment,
+        ));
         let (event_stream, mut rx) = crate::ToolCallEventStream::test();
 
         let _task = cx.update(|cx| {
@@ -4143,7 +4595,11 @@ async fn test_terminal_tool_permission_rules(cx: &mut TestAppContext) {
         });
 
         #[allow(clippy::arc_with_non_send_sync)]
-        let tool = Arc::new(crate::TerminalTool::new(project.clone(), environment));
+        let tool = Arc::new(crate::TerminalTool::new(
+            WeakEntity::new_invalid(),
+            project.clone(),
+            environment,
+        ));
         let (event_stream, _rx) = crate::ToolCallEventStream::test();
 
         let task = cx.update(|cx| {
@@ -4233,6 +4689,7 @@ async fn test_subagent_tool_call_end_to_end(cx: &mut TestAppContext) {
         label: "label".to_string(),
         message: "subagent task prompt".to_string(),
         session_id: None,
+        paths: None,
     };
     let subagent_tool_use = LanguageModelToolUse {
         id: "subagent_1".into(),
@@ -4266,11 +4723,31 @@ async fn test_subagent_tool_call_end_to_end(cx: &mut TestAppContext) {
             .clone()
     });
 
+    model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 42,
+            ..Default::default()
+        },
+    ));
     model.send_last_completion_stream_text_chunk("subagent task response");
     model.end_last_completion_stream();
 
     cx.run_until_parked();
 
+    // The subagent's usage is recorded for today, and counts towards the parent thread.
+    thread_store.read_with(cx, |store, _| assert_eq!(store.tokens_today(), 42));
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(
+            thread
+                .model_token_usage()
+                .iter()
+                .map(|usage| usage.usage.input_tokens)
+                .sum::<u64>(),
+            42
+        );
+        assert_eq!(thread.cumulative_token_usage(), Default::default());
+    });
+
     assert_eq!(
         subagent_thread.read_with(cx, |thread, cx| thread.to_markdown(cx)),
         indoc! {"
@@ -4315,6 +4792,149 @@ async fn test_subagent_tool_call_end_to_end(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_subagents_with_path_scopes(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        LanguageModelRegistry::test(cx);
+    });
+    cx.update(|cx| {
+        cx.update_flags(true, vec!["subagents".to_string()]);
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/",
+        json!({
+            "a": {
+                "ui": {
+                    "button.rs": "fn button() {}"
+                }
+            }
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/a").as_ref()], cx).await;
+    let thread_store = cx.new(|cx| ThreadStore::new(cx));
+    let agent = NativeAgent::new(
+        project.clone(),
+        thread_store.clone(),
+        Templates::new(),
+        None,
+        fs.clone(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    let connection = Rc::new(NativeAgentConnection(agent.clone()));
+
+    let acp_thread = cx
+        .update(|cx| {
+            connection
+                .clone()
+                .new_session(project.clone(), Path::new(""), cx)
+        })
+        .await
+        .unwrap();
+    let session_id = acp_thread.read_with(cx, |thread, _| thread.session_id().clone());
+    let thread = agent.read_with(cx, |agent, _| {
+        agent.sessions.get(&session_id).unwrap().thread.clone()
+    });
+    let model = Arc::new(FakeLanguageModel::default());
+    thread.update(cx, |thread, cx| {
+        thread.set_model(model.clone(), cx);
+    });
+    cx.run_until_parked();
+
+    let send = acp_thread.update(cx, |thread, cx| thread.send_raw("Prompt", cx));
+    cx.run_until_parked();
+    for (id, label, path) in [
+        ("subagent_1", "UI", "a/ui"),
+        ("subagent_2", "Button", "a/ui/button.rs"),
+    ] {
+        let input = SpawnAgentToolInput {
+            label: label.to_string(),
+            message: format!("{label} task prompt"),
+            session_id: None,
+            paths: Some(vec![path.to_string()]),
+        };
+        model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+            LanguageModelToolUse {
+                id: id.into(),
+                name: SpawnAgentTool::NAME.into(),
+                raw_input: serde_json::to_string(&input).unwrap(),
+                input: serde_json::to_value(&input).unwrap(),
+                is_input_complete: true,
+                thought_signature: None,
+            },
+        ));
+    }
+    model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // Agents that run in parallel can't be assigned overlapping paths.
+    let subagent_session_ids = thread.read_with(cx, |thread, cx| thread.running_subagent_ids(cx));
+    assert_eq!(subagent_session_ids.len(), 1);
+    let subagent_thread = agent.read_with(cx, |agent, _cx| {
+        agent
+            .sessions
+            .get(&subagent_session_ids[0])
+            .expect("subagent session should exist")
+            .thread
+            .clone()
+    });
+    let path_scope = subagent_thread
+        .read_with(cx, |thread, _| thread.path_scope().cloned())
+        .unwrap();
+    assert!(
+        path_scope == PathScope::new(["a/ui"]) || path_scope == PathScope::new(["a/ui/button.rs"])
+    );
+
+    let project_path = project
+        .read_with(cx, |project, cx| {
+            project.find_project_path("a/ui/button.rs", cx)
+        })
+        .unwrap();
+    let buffer = project
+        .update(cx, |project, cx| project.open_buffer(project_path, cx))
+        .await
+        .unwrap();
+    subagent_thread.update(cx, |thread, cx| {
+        thread.action_log().update(cx, |action_log, cx| {
+            action_log.buffer_read(buffer.clone(), cx);
+            buffer.update(cx, |buffer, cx| {
+                buffer.set_text("fn button() -> Button {}", cx)
+            });
+            action_log.buffer_edited(buffer.clone(), cx);
+        });
+    });
+    cx.run_until_parked();
+
+    model.send_last_completion_stream_text_chunk("Subtask response");
+    model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The edits of agents restricted to some paths are reviewed in the parent thread.
+    let changed_buffers = thread.read_with(cx, |thread, cx| {
+        thread
+            .action_log()
+            .read(cx)
+            .changed_buffers(cx)
+            .into_keys()
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(changed_buffers, vec![buffer]);
+
+    model.send_last_completion_stream_text_chunk("Response");
+    model.end_last_completion_stream();
+    send.await.unwrap();
+
+    let markdown = acp_thread.read_with(cx, |thread, cx| thread.to_markdown(cx));
+    assert!(markdown.contains("Status: Completed"));
+    assert!(markdown.contains("Status: Failed"));
+    assert!(markdown.contains("overlap"));
+}
+
 #[gpui::test]
 async fn test_subagent_tool_call_cancellation_during_task_prompt(cx: &mut TestAppContext) {
     init_test(cx);
@@ -4376,6 +4996,7 @@ async fn test_subagent_tool_call_cancellation_during_task_prompt(cx: &mut TestAp
         label: "label".to_string(),
         message: "subagent task prompt".to_string(),
         session_id: None,
+        paths: None,
     };
     let subagent_tool_use = LanguageModelToolUse {
         id: "subagent_1".into(),
@@ -4513,6 +5134,7 @@ async fn test_subagent_tool_resume_session(cx: &mut TestAppContext) {
         label: "initial task".to_string(),
         message: "do the first task".to_string(),
         session_id: None,
+        paths: None,
     };
     let subagent_tool_use = LanguageModelToolUse {
         id: "subagent_1".into(),
@@ -4574,6 +5196,7 @@ async fn test_subagent_tool_resume_session(cx: &mut TestAppContext) {
         label: "follow-up task".to_string(),
         message: "do the follow-up task".to_string(),
         session_id: Some(subagent_session_id.clone()),
+        paths: None,
     };
     let resume_tool_use = LanguageModelToolUse {
         id: "subagent_2".into(),
@@ -4756,6 +5379,7 @@ async fn test_max_subagent_depth_prevents_tool_registration(cx: &mut TestAppCont
         thread.set_subagent_context(SubagentContext {
             parent_thread_id: agent_client_protocol::SessionId::new("parent-id"),
             depth: MAX_SUBAGENT_DEPTH - 1,
+            path_scope: None,
         });
         thread
     });
@@ -4892,6 +5516,7 @@ async fn test_subagent_context_window_warning(cx: &mut TestAppContext) {
         label: "label".to_string(),
         message: "subagent task prompt".to_string(),
         session_id: None,
+        paths: None,
     };
     let subagent_tool_use = LanguageModelToolUse {
         id: "subagent_1".into(),
@@ -5025,6 +5650,7 @@ async fn test_subagent_no_context_window_warning_when_already_at_warning(cx: &mu
         label: "initial task".to_string(),
         message: "do the first task".to_string(),
         session_id: None,
+        paths: None,
     };
     let subagent_tool_use = LanguageModelToolUse {
         id: "subagent_1".into(),
@@ -5091,6 +5717,7 @@ async fn test_subagent_no_context_window_warning_when_already_at_warning(cx: &mu
         label: "follow-up task".to_string(),
         message: "do the follow-up task".to_string(),
         session_id: Some(subagent_session_id.clone()),
+        paths: None,
     };
     let resume_tool_use = LanguageModelToolUse {
         id: "subagent_2".into(),
@@ -5206,6 +5833,7 @@ async fn test_subagent_error_propagation(cx: &mut TestAppContext) {
         label: "label".to_string(),
         message: "subagent task prompt".to_string(),
         session_id: None,
+        paths: None,
     };
     let subagent_tool_use = LanguageModelToolUse {
         id: "subagent_1".into(),
@@ -1,9 +1,10 @@
 use crate::{
     ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
     DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool,
-    ListDirectoryTool, MovePathTool, NowTool, OpenTool, ProjectSnapshot, ReadFileTool,
-    RestoreFileFromDiskTool, SaveFileTool, SpawnAgentTool, StreamingEditFileTool,
-    SystemPromptTemplate, Template, Templates, TerminalTool, ToolPermissionDecision, WebSearchTool,
+    ListDirectoryTool, ModelTokenUsage, MovePathTool, NowTool, OpenTool, PathScope,
+    ProjectSnapshot, ReadFileTool, RepoMapTool, RestoreFileFromDiskTool, SaveFileTool,
+    SemanticSearchTool, SpawnAgentTool, StreamingEditFileTool, SystemPromptTemplate, Template,
+    Templates, TerminalTool, ToolPermissionDecision, WebSearchTool,
     decide_permission_from_settings,
 };
 use acp_thread::{MentionUri, UserMessageId};
@@ -14,8 +15,8 @@ use feature_flags::{
 
 use agent_client_protocol as acp;
 use agent_settings::{
-    AgentProfileId, AgentProfileSettings, AgentSettings, SUMMARIZE_THREAD_DETAILED_PROMPT,
-    SUMMARIZE_THREAD_PROMPT,
+    AgentProfileId, AgentProfileSettings, AgentSettings, COMPACT_THREAD_PROMPT,
+    SUMMARIZE_THREAD_DETAILED_PROMPT, SUMMARIZE_THREAD_PROMPT,
 };
 use anyhow::{Context as _, Result, anyhow};
 use chrono::{DateTime, Utc};
@@ -42,16 +43,19 @@ use language_model::{
     LanguageModelToolResultContent, LanguageModelToolSchemaFormat, LanguageModelToolUse,
     LanguageModelToolUseId, Role, SelectedModel, StopReason, TokenUsage, ZED_CLOUD_PROVIDER_ID,
 };
-use project::Project;
+use project::{Project, ProjectPath};
 use prompt_store::ProjectContext;
 use schemars::{JsonSchema, Schema};
+use semantic_index::SemanticIndexSettings;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use settings::{LanguageModelSelection, Settings, ToolPermissionMode, update_settings_file};
 use smol::stream::StreamExt;
 use std::{
     collections::BTreeMap,
+    hash::{DefaultHasher, Hash as _, Hasher as _},
     marker::PhantomData,
+    mem,
     ops::RangeInclusive,
     path::Path,
     rc::Rc,
@@ -59,7 +63,9 @@ use std::{
     time::{Duration, Instant},
 };
 use std::{fmt::Write, path::PathBuf};
-use util::{ResultExt, debug_panic, markdown::MarkdownCodeBlock, paths::PathStyle};
+use util::{
+    ResultExt, debug_panic, markdown::MarkdownCodeBlock, paths::PathStyle, rel_path::RelPath,
+};
 use uuid::Uuid;
 
 const TOOL_CANCELED_MESSAGE: &str = "Tool canceled by user";
@@ -74,6 +80,30 @@ pub struct SubagentContext {
 
     /// Current depth level (0 = root agent, 1 = first-level subagent, etc.)
     pub depth: u8,
+
+    /// The paths the subagent is allowed to modify, if it was restricted to them
+    #[serde(default)]
+    pub path_scope: Option<PathScope>,
+}
+
+/// A scoped rules file that applied to a file the agent read or edited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppliedRulesFile {
+    pub worktree_abs_path: Arc<Path>,
+    pub path_in_worktree: Arc<RelPath>,
+    pub project_entry_id: usize,
+    /// A hash of the rules' text, so that they're included again once they change.
+    pub content_hash: u64,
+    /// The tool use whose result included the rules.
+    pub tool_use_id: LanguageModelToolUseId,
+}
+
+impl AppliedRulesFile {
+    fn is_same_file(&self, other: &Self) -> bool {
+        self.worktree_abs_path == other.worktree_abs_path
+            && self.path_in_worktree == other.path_in_worktree
+            && self.project_entry_id == other.project_entry_id
+    }
 }
 
 /// The ID of the user prompt that initiated a request.
@@ -114,6 +144,7 @@ pub enum Message {
     User(UserMessage),
     Agent(AgentMessage),
     Resume,
+    Compaction(CompactionMessage),
 }
 
 impl Message {
@@ -140,6 +171,7 @@ impl Message {
                 cache: false,
                 reasoning_details: None,
             }],
+            Message::Compaction(message) => vec![message.to_request()],
         }
     }
 
@@ -148,17 +180,58 @@ impl Message {
             Message::User(message) => message.to_markdown(),
             Message::Agent(message) => message.to_markdown(),
             Message::Resume => "[resume]\n".into(),
+            Message::Compaction(message) => message.to_markdown(),
         }
     }
 
     pub fn role(&self) -> Role {
         match self {
-            Message::User(_) | Message::Resume => Role::User,
+            Message::User(_) | Message::Resume | Message::Compaction(_) => Role::User,
             Message::Agent(_) => Role::Assistant,
         }
     }
 }
 
+/// Replaces the earlier messages of a thread with a summary, once the thread approaches the
+/// model's context window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionMessage {
+    pub summary: String,
+    /// The messages that were summarized, which are no longer sent to the model but are kept so
+    /// that the thread can still be shown in full.
+    pub messages: Vec<Message>,
+}
+
+impl CompactionMessage {
+    fn to_request(&self) -> LanguageModelRequestMessage {
+        LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![
+                format!(
+                    "The earlier part of this conversation was summarized to fit in the context window:\n\n{}",
+                    self.summary
+                )
+                .into(),
+            ],
+            cache: false,
+            reasoning_details: None,
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        format!("{}\n", self.summary)
+    }
+
+    /// Returns whether the message with the given id is among the summarized messages.
+    fn contains(&self, message_id: &UserMessageId) -> bool {
+        self.messages.iter().any(|message| match message {
+            Message::User(message) => &message.id == message_id,
+            Message::Compaction(compaction) => compaction.contains(message_id),
+            Message::Agent(_) | Message::Resume => false,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UserMessage {
     pub id: UserMessageId,
@@ -220,6 +293,7 @@ impl UserMessage {
             "<rules>\nThe user has specified the following rules that should be applied:\n";
         const OPEN_DIAGNOSTICS_TAG: &str = "<diagnostics>";
         const OPEN_DIFFS_TAG: &str = "<diffs>";
+        const OPEN_RESOURCES_TAG: &str = "<resources>";
 
         let mut file_context = OPEN_FILES_TAG.to_string();
         let mut directory_context = OPEN_DIRECTORIES_TAG.to_string();
@@ -230,6 +304,7 @@ impl UserMessage {
         let mut rules_context = OPEN_RULES_TAG.to_string();
         let mut diagnostics_context = OPEN_DIAGNOSTICS_TAG.to_string();
         let mut diffs_context = OPEN_DIFFS_TAG.to_string();
+        let mut resources_context = OPEN_RESOURCES_TAG.to_string();
 
         for chunk in &self.content {
             let chunk = match chunk {
@@ -337,6 +412,14 @@ impl UserMessage {
                             )
                             .ok();
                         }
+                        MentionUri::McpResource { server_id, uri, .. } => {
+                            write!(
+                                &mut resources_context,
+                                "\nResource {} from context server {}:\n\n{}\n",
+                                uri, server_id, content
+                            )
+                            .ok();
+                        }
                     }
 
                     language_model::MessageContent::Text(uri.as_link().to_string())
@@ -397,6 +480,13 @@ impl UserMessage {
                 .push(language_model::MessageContent::Text(fetch_context));
         }
 
+        if resources_context.len() > OPEN_RESOURCES_TAG.len() {
+            resources_context.push_str("</resources>\n");
+            message
+                .content
+                .push(language_model::MessageContent::Text(resources_context));
+        }
+
         if rules_context.len() > OPEN_RULES_TAG.len() {
             rules_context.push_str("</user_rules>\n");
             message
@@ -622,6 +712,7 @@ pub trait ThreadEnvironment {
         parent_thread: Entity<Thread>,
         label: String,
         initial_prompt: String,
+        path_scope: Option<PathScope>,
         cx: &mut App,
     ) -> Result<Rc<dyn SubagentHandle>>;
 
@@ -648,6 +739,8 @@ pub enum ThreadEvent {
     ToolCallAuthorization(ToolCallAuthorization),
     SubagentSpawned(acp::SessionId),
     Retry(acp_thread::RetryStatus),
+    /// The messages before the given user message were replaced by a summary.
+    ContextCompacted(UserMessageId),
     Stop(acp::StopReason),
 }
 
@@ -880,8 +973,12 @@ pub struct Thread {
     pending_message: Option<AgentMessage>,
     pub(crate) tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
     request_token_usage: HashMap<UserMessageId, language_model::TokenUsage>,
-    #[allow(unused)]
     cumulative_token_usage: TokenUsage,
+    /// The tokens used with each model over the lifetime of the thread.
+    model_token_usage: Vec<ModelTokenUsage>,
+    /// The latest usage reported for the completion request in flight. Providers report the
+    /// usage of a request so far, so this is needed to tell how many tokens were added.
+    in_flight_request_token_usage: TokenUsage,
     #[allow(unused)]
     initial_project_snapshot: Shared<Task<Option<Arc<ProjectSnapshot>>>>,
     pub(crate) context_server_registry: Entity<ContextServerRegistry>,
@@ -898,6 +995,8 @@ pub struct Thread {
     pub(crate) action_log: Entity<ActionLog>,
     /// Tracks the last time files were read by the agent, to detect external modifications
     pub(crate) file_read_times: HashMap<PathBuf, fs::MTime>,
+    /// Scoped rules files whose contents were already included in a tool result
+    applied_rules_files: Vec<AppliedRulesFile>,
     /// True if this thread was imported from a shared thread and can be synced.
     imported: bool,
     /// If this is a subagent thread, contains context about the parent
@@ -931,6 +1030,7 @@ impl Thread {
         thread.subagent_context = Some(SubagentContext {
             parent_thread_id: parent_thread.read(cx).id().clone(),
             depth: parent_thread.read(cx).depth() + 1,
+            path_scope: None,
         });
         thread
     }
@@ -972,6 +1072,8 @@ impl Thread {
             tools: BTreeMap::default(),
             request_token_usage: HashMap::default(),
             cumulative_token_usage: TokenUsage::default(),
+            model_token_usage: Vec::new(),
+            in_flight_request_token_usage: TokenUsage::default(),
             initial_project_snapshot: {
                 let project_snapshot = Self::project_snapshot(project.clone(), cx);
                 cx.foreground_executor()
@@ -991,6 +1093,7 @@ impl Thread {
             project,
             action_log,
             file_read_times: HashMap::default(),
+            applied_rules_files: Vec::new(),
             imported: false,
             subagent_context: None,
             running_subagents: Vec::new(),
@@ -1012,9 +1115,25 @@ impl Thread {
     ) -> mpsc::UnboundedReceiver<Result<ThreadEvent>> {
         let (tx, rx) = mpsc::unbounded();
         let stream = ThreadEventStream(tx);
-        for message in &self.messages {
+        self.replay_messages(&self.messages, &stream, cx);
+        rx
+    }
+
+    fn replay_messages(
+        &self,
+        messages: &[Message],
+        stream: &ThreadEventStream,
+        cx: &mut Context<Self>,
+    ) {
+        let mut compacted = false;
+        for message in messages {
             match message {
-                Message::User(user_message) => stream.send_user_message(user_message),
+                Message::User(user_message) => {
+                    stream.send_user_message(user_message);
+                    if mem::take(&mut compacted) {
+                        stream.send_context_compacted(user_message.id.clone());
+                    }
+                }
                 Message::Agent(assistant_message) => {
                     for content in &assistant_message.content {
                         match content {
@@ -1035,9 +1154,12 @@ impl Thread {
                     }
                 }
                 Message::Resume => {}
+                Message::Compaction(compaction) => {
+                    self.replay_messages(&compaction.messages, stream, cx);
+                    compacted = true;
+                }
             }
         }
-        rx
     }
 
     fn replay_tool_call(
@@ -1114,6 +1236,7 @@ impl Thread {
                 tool_use.id.clone(),
                 stream.clone(),
                 Some(self.project.read(cx).fs().clone()),
+                self.path_scope().cloned(),
                 cancellation_rx,
             );
             tool.replay(tool_use.input.clone(), output, tool_event_stream, cx)
@@ -1200,6 +1323,8 @@ impl Thread {
             tools: BTreeMap::default(),
             request_token_usage: db_thread.request_token_usage.clone(),
             cumulative_token_usage: db_thread.cumulative_token_usage,
+            model_token_usage: db_thread.model_token_usage,
+            in_flight_request_token_usage: TokenUsage::default(),
             initial_project_snapshot: Task::ready(db_thread.initial_project_snapshot).shared(),
             context_server_registry,
             profile_id,
@@ -1215,6 +1340,7 @@ impl Thread {
             prompt_capabilities_tx,
             prompt_capabilities_rx,
             file_read_times: HashMap::default(),
+            applied_rules_files: Vec::new(),
             imported: db_thread.imported,
             subagent_context: db_thread.subagent_context,
             running_subagents: Vec::new(),
@@ -1231,6 +1357,7 @@ impl Thread {
             initial_project_snapshot: None,
             cumulative_token_usage: self.cumulative_token_usage,
             request_token_usage: self.request_token_usage.clone(),
+            model_token_usage: self.model_token_usage.clone(),
             model: self.model.as_ref().map(|model| DbLanguageModel {
                 provider: model.provider_id().to_string(),
                 model: model.id().0.to_string(),
@@ -1271,6 +1398,73 @@ impl Thread {
         &self.project
     }
 
+    pub fn applied_rules_files(&self) -> &[AppliedRulesFile] {
+        &self.applied_rules_files
+    }
+
+    /// Returns the scoped rules that apply to the given path and haven't been
+    /// included in a tool result yet, or have changed since, formatted to be
+    /// appended to the result of the given tool use.
+    pub(crate) fn take_scoped_rules_for_path(
+        &mut self,
+        project_path: &ProjectPath,
+        tool_use_id: &LanguageModelToolUseId,
+        cx: &mut Context<Self>,
+    ) -> Option<String> {
+        let worktree = self
+            .project
+            .read(cx)
+            .worktree_for_id(project_path.worktree_id, cx)?;
+        let worktree_abs_path = worktree.read(cx).abs_path();
+        let project_context = self.project_context.read(cx);
+        let worktree_context = project_context
+            .worktrees
+            .iter()
+            .find(|worktree| worktree.abs_path == worktree_abs_path)?;
+
+        let mut output = String::new();
+        for rules_file in &worktree_context.scoped_rules_files {
+            if !rules_file.applies_to_path(&project_path.path) {
+                continue;
+            }
+            let mut hasher = DefaultHasher::new();
+            rules_file.text.hash(&mut hasher);
+            let applied_rules_file = AppliedRulesFile {
+                worktree_abs_path: worktree_abs_path.clone(),
+                path_in_worktree: rules_file.path_in_worktree.clone(),
+                project_entry_id: rules_file.project_entry_id,
+                content_hash: hasher.finish(),
+                tool_use_id: tool_use_id.clone(),
+            };
+            if let Some(ix) = self
+                .applied_rules_files
+                .iter()
+                .position(|applied| applied.is_same_file(&applied_rules_file))
+            {
+                if self.applied_rules_files[ix].content_hash == applied_rules_file.content_hash {
+                    continue;
+                }
+                self.applied_rules_files.remove(ix);
+            }
+            write!(
+                output,
+                "\n\nThe project rules in `{}/{}` apply to this file, and should be followed from now on:\n``````\n{}\n``````",
+                worktree_context.root_name,
+                rules_file.path_in_worktree.as_unix_str(),
+                rules_file.text
+            )
+            .ok();
+            self.applied_rules_files.push(applied_rules_file);
+        }
+
+        if output.is_empty() {
+            None
+        } else {
+            cx.notify();
+            Some(output)
+        }
+    }
+
     pub fn action_log(&self) -> &Entity<ActionLog> {
         &self.action_log
     }
@@ -1370,11 +1564,20 @@ impl Thread {
             self.project.clone(),
             self.action_log.clone(),
         ));
+        self.add_tool(RepoMapTool::new(self.project.clone(), cx));
         self.add_tool(SaveFileTool::new(self.project.clone()));
         self.add_tool(RestoreFileFromDiskTool::new(self.project.clone()));
-        self.add_tool(TerminalTool::new(self.project.clone(), environment.clone()));
+        self.add_tool(TerminalTool::new(
+            cx.weak_entity(),
+            self.project.clone(),
+            environment.clone(),
+        ));
         self.add_tool(WebSearchTool);
 
+        if SemanticIndexSettings::get_global(cx).enabled && self.project.read(cx).is_local() {
+            self.add_tool(SemanticSearchTool::new(self.project.clone()));
+        }
+
         if cx.has_flag::<SubagentsFeatureFlag>() && self.depth() < MAX_SUBAGENT_DEPTH {
             self.add_tool(SpawnAgentTool::new(cx.weak_entity(), environment));
         }
@@ -1452,11 +1655,74 @@ impl Thread {
         cx.notify();
     }
 
+    /// Adds the tokens used since the previous usage update of the in-flight request to the
+    /// thread's totals.
+    fn accumulate_token_usage(
+        &mut self,
+        model: &dyn LanguageModel,
+        update: language_model::TokenUsage,
+        cx: &mut Context<Self>,
+    ) {
+        let delta = update.saturating_sub(self.in_flight_request_token_usage);
+        self.in_flight_request_token_usage = update;
+        self.record_token_usage(model, delta, cx);
+    }
+
+    /// Adds the tokens used by a request to the thread's totals. Requests made on behalf of the
+    /// thread outside of its turns, such as generating its title, are recorded here too.
+    pub(crate) fn record_token_usage(
+        &mut self,
+        model: &dyn LanguageModel,
+        usage: language_model::TokenUsage,
+        cx: &mut Context<Self>,
+    ) {
+        if usage == TokenUsage::default() {
+            return;
+        }
+
+        self.cumulative_token_usage = self.cumulative_token_usage + usage;
+        let usage = ModelTokenUsage::new(model, usage);
+        self.add_model_token_usage(usage.clone());
+        cx.emit(ModelTokenUsageRecorded(usage));
+    }
+
+    /// Adds the tokens used by one of the thread's subagents to its per-model totals. The
+    /// subagent reports them to the thread store itself.
+    pub(crate) fn add_subagent_token_usage(
+        &mut self,
+        usage: ModelTokenUsage,
+        cx: &mut Context<Self>,
+    ) {
+        self.add_model_token_usage(usage);
+        cx.notify();
+    }
+
+    fn add_model_token_usage(&mut self, usage: ModelTokenUsage) {
+        if let Some(existing) = self
+            .model_token_usage
+            .iter_mut()
+            .find(|existing| existing.is_same_model(&usage))
+        {
+            existing.add(&usage);
+        } else {
+            self.model_token_usage.push(usage);
+        }
+    }
+
+    pub fn cumulative_token_usage(&self) -> TokenUsage {
+        self.cumulative_token_usage
+    }
+
+    pub fn model_token_usage(&self) -> &[ModelTokenUsage] {
+        &self.model_token_usage
+    }
+
     pub fn truncate(&mut self, message_id: UserMessageId, cx: &mut Context<Self>) -> Result<()> {
         self.cancel(cx).detach();
         // Clear pending message since cancel will try to flush it asynchronously,
         // and we don't want that content to be added after we truncate
         self.pending_message.take();
+        self.expand_compaction_containing(&message_id);
         let Some(position) = self.messages.iter().position(
             |msg| matches!(msg, Message::User(UserMessage { id, .. }) if id == &message_id),
         ) else {
@@ -1468,20 +1734,67 @@ impl Thread {
                 Message::User(message) => {
                     self.request_token_usage.remove(&message.id);
                 }
-                Message::Agent(_) | Message::Resume => {}
+                Message::Agent(_) | Message::Resume | Message::Compaction(_) => {}
             }
         }
+        self.retain_applied_rules_files();
         self.clear_summary();
         cx.notify();
         Ok(())
     }
 
+    /// Forgets the scoped rules files whose tool results are no longer sent to
+    /// the model, because they were truncated or compacted, so that they're
+    /// included again the next time they apply.
+    fn retain_applied_rules_files(&mut self) {
+        let tool_use_ids = self
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::Agent(message) => Some(message),
+                Message::User(_) | Message::Resume | Message::Compaction(_) => None,
+            })
+            .chain(&self.pending_message)
+            .flat_map(|message| message.tool_results.keys())
+            .collect::<HashSet<_>>();
+        self.applied_rules_files
+            .retain(|rules_file| tool_use_ids.contains(&rules_file.tool_use_id));
+    }
+
+    /// Puts back the summarized messages of the compaction that contains the given message, so that
+    /// the thread can be truncated at it.
+    fn expand_compaction_containing(&mut self, message_id: &UserMessageId) {
+        while let Some(position) = self.messages.iter().position(|message| {
+            matches!(message, Message::Compaction(compaction) if compaction.contains(message_id))
+        }) {
+            let Message::Compaction(compaction) = self.messages.remove(position) else {
+                unreachable!()
+            };
+            self.messages
+                .splice(position..position, compaction.messages);
+        }
+    }
+
     pub fn latest_request_token_usage(&self) -> Option<language_model::TokenUsage> {
         let last_user_message = self.last_user_message()?;
         let tokens = self.request_token_usage.get(&last_user_message.id)?;
         Some(*tokens)
     }
 
+    /// Returns the tokens used by the latest request that received a response, ignoring requests
+    /// whose messages were compacted since.
+    fn context_tokens(&self) -> Option<u64> {
+        self.messages
+            .iter()
+            .rev()
+            .filter_map(|message| match message {
+                Message::User(message) => Some(&message.id),
+                _ => None,
+            })
+            .find_map(|id| self.request_token_usage.get(id))
+            .map(|usage| usage.total_tokens())
+    }
+
     pub fn latest_token_usage(&self) -> Option<acp_thread::TokenUsage> {
         let usage = self.latest_request_token_usage()?;
         let model = self.model.clone()?;
@@ -1653,8 +1966,8 @@ impl Thread {
             .context("Profile not found")?;
         let (events_tx, events_rx) = mpsc::unbounded::<Result<ThreadEvent>>();
         let event_stream = ThreadEventStream(events_tx);
-        let message_ix = self.messages.len().saturating_sub(1);
         self.clear_summary();
+        let compaction = self.compact_if_needed(&model, cx);
         let (cancellation_tx, mut cancellation_rx) = watch::channel(false);
         self.running_turn = Some(RunningTurn {
             event_stream: event_stream.clone(),
@@ -1662,6 +1975,22 @@ impl Thread {
             cancellation_tx,
             streaming_tool_inputs: HashMap::default(),
             _task: cx.spawn(async move |this, cx| {
+                if let Some(compaction) = compaction {
+                    log::debug!("Compacting thread before starting agent turn");
+                    let mut cancellation_rx = cancellation_rx.clone();
+                    if !Self::wait_for_compaction(compaction, &event_stream, &mut cancellation_rx)
+                        .await
+                    {
+                        log::debug!("Turn was cancelled while compacting thread");
+                        return;
+                    }
+                }
+                let Ok(message_ix) =
+                    this.read_with(cx, |this, _| this.messages.len().saturating_sub(1))
+                else {
+                    return;
+                };
+
                 log::debug!("Starting agent turn execution");
 
                 let turn_result = Self::run_turn_internal(
@@ -1721,8 +2050,10 @@ impl Thread {
         let mut attempt = 0;
         let mut intent = CompletionIntent::UserPrompt;
         loop {
-            let request =
-                this.update(cx, |this, cx| this.build_completion_request(intent, cx))??;
+            let request = this.update(cx, |this, cx| {
+                this.in_flight_request_token_usage = TokenUsage::default();
+                this.build_completion_request(intent, cx)
+            })??;
 
             telemetry::event!(
                 "Agent Thread Completion",
@@ -1872,10 +2203,41 @@ impl Thread {
                 }
                 intent = CompletionIntent::ToolResults;
                 attempt = 0;
+
+                // Tool results can fill the context window within a single turn.
+                let compaction = this.update(cx, |this, cx| this.compact_if_needed(&model, cx))?;
+                if let Some(compaction) = compaction {
+                    log::debug!("Compacting thread before sending tool results");
+                    if !Self::wait_for_compaction(compaction, event_stream, &mut cancellation_rx)
+                        .await
+                    {
+                        log::debug!("Turn was cancelled while compacting thread");
+                        return Ok(());
+                    }
+                }
             }
         }
     }
 
+    /// Waits for the thread to be compacted, and reports it to the event stream.
+    /// Returns `false` if the turn was cancelled in the meantime.
+    async fn wait_for_compaction(
+        compaction: Task<Result<UserMessageId>>,
+        event_stream: &ThreadEventStream,
+        cancellation_rx: &mut watch::Receiver<bool>,
+    ) -> bool {
+        futures::select! {
+            result = compaction.fuse() => {
+                match result {
+                    Ok(compacted_before) => event_stream.send_context_compacted(compacted_before),
+                    Err(error) => log::error!("Failed to compact thread: {error:#}"),
+                }
+                true
+            }
+            _ = cancellation_rx.changed().fuse() => false,
+        }
+    }
+
     fn handle_completion_error(
         &mut self,
         error: LanguageModelCompletionError,
@@ -1993,6 +2355,9 @@ impl Thread {
                     cache_creation_input_tokens = usage.cache_creation_input_tokens,
                     cache_read_input_tokens = usage.cache_read_input_tokens,
                 );
+                if let Some(model) = self.model.clone() {
+                    self.accumulate_token_usage(model.as_ref(), usage, cx);
+                }
                 self.update_token_usage(usage, cx);
             }
             Stop(StopReason::Refusal) => return Err(CompletionError::Refusal.into()),
@@ -2144,6 +2509,7 @@ impl Thread {
             tool_use_id.clone(),
             event_stream.clone(),
             Some(fs),
+            self.path_scope().cloned(),
             cancellation_rx,
         );
         tool_event_stream.update_fields(
@@ -2299,17 +2665,30 @@ impl Thread {
         let task = cx
             .spawn(async move |this, cx| {
                 let mut summary = String::new();
-                let mut messages = model.stream_completion(request, cx).await.log_err()?;
-                while let Some(event) = messages.next().await {
-                    let event = event.log_err()?;
-                    let text = match event {
-                        LanguageModelCompletionEvent::Text(text) => text,
-                        _ => continue,
-                    };
+                let mut token_usage = TokenUsage::default();
+                let generate = async {
+                    let mut messages = model.stream_completion(request, cx).await?;
+                    while let Some(event) = messages.next().await {
+                        let text = match event? {
+                            LanguageModelCompletionEvent::Text(text) => text,
+                            LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                                token_usage = usage;
+                                continue;
+                            }
+                            _ => continue,
+                        };
 
-                    let mut lines = text.lines();
-                    summary.extend(lines.next());
-                }
+                        let mut lines = text.lines();
+                        summary.extend(lines.next());
+                    }
+                    anyhow::Ok(())
+                };
+                let result = generate.await;
+                this.update(cx, |this, cx| {
+                    this.record_token_usage(model.as_ref(), token_usage, cx)
+                })
+                .ok()?;
+                result.log_err()?;
 
                 log::debug!("Setting summary: {}", summary);
                 let summary = SharedString::from(summary);
@@ -2328,6 +2707,109 @@ impl Thread {
         task
     }
 
+    /// Summarizes the messages before the last user message, if the latest request filled enough
+    /// of the model's context window. Resolves to the id of that user message.
+    fn compact_if_needed(
+        &mut self,
+        model: &Arc<dyn LanguageModel>,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<Result<UserMessageId>>> {
+        // Subagents report to their parent when they run out of context instead.
+        if self.is_subagent() {
+            return None;
+        }
+        let threshold = AgentSettings::get_global(cx).auto_compact_threshold;
+        let used_tokens = self.context_tokens()?;
+        if threshold >= 1.
+            || (used_tokens as f64) < threshold as f64 * model.max_token_count() as f64
+        {
+            return None;
+        }
+
+        let (position, kept_message_id) =
+            self.messages
+                .iter()
+                .enumerate()
+                .rev()
+                .find_map(|(ix, message)| match message {
+                    Message::User(message) => Some((ix, message.id.clone())),
+                    _ => None,
+                })?;
+        // Summarizing a single summary again wouldn't save much context.
+        if position == 0 || (position == 1 && matches!(self.messages[0], Message::Compaction(_))) {
+            return None;
+        }
+
+        let summarization_model = self
+            .summarization_model
+            .clone()
+            .unwrap_or_else(|| model.clone());
+        let mut request = LanguageModelRequest {
+            intent: Some(CompletionIntent::ThreadContextSummarization),
+            temperature: AgentSettings::temperature_for_model(&summarization_model, cx),
+            ..Default::default()
+        };
+        for message in &self.messages[..position] {
+            request.messages.extend(message.to_request());
+        }
+        request.messages.push(LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![COMPACT_THREAD_PROMPT.into()],
+            cache: false,
+            reasoning_details: None,
+        });
+
+        Some(cx.spawn(async move |this, cx| {
+            let mut summary = String::new();
+            let mut token_usage = TokenUsage::default();
+            let generate = async {
+                let mut events = summarization_model.stream_completion(request, cx).await?;
+                while let Some(event) = events.next().await {
+                    match event? {
+                        LanguageModelCompletionEvent::Text(text) => summary.push_str(&text),
+                        LanguageModelCompletionEvent::UsageUpdate(usage) => token_usage = usage,
+                        _ => {}
+                    }
+                }
+                anyhow::Ok(())
+            };
+            let result = generate.await;
+            this.update(cx, |this, cx| {
+                this.record_token_usage(summarization_model.as_ref(), token_usage, cx)
+            })?;
+            result?;
+            let summary = summary.trim();
+            anyhow::ensure!(
+                !summary.is_empty(),
+                "summarization model returned no summary"
+            );
+
+            this.update(cx, |this, cx| {
+                anyhow::ensure!(
+                    matches!(
+                        this.messages.get(position),
+                        Some(Message::User(message)) if message.id == kept_message_id
+                    ),
+                    "thread changed while it was being compacted"
+                );
+                let messages = this.messages.drain(..position).collect();
+                this.messages.insert(
+                    0,
+                    Message::Compaction(CompactionMessage {
+                        summary: summary.to_string(),
+                        messages,
+                    }),
+                );
+                // The usage of earlier requests no longer reflects what is sent to the model.
+                this.request_token_usage.remove(&kept_message_id);
+                this.retain_applied_rules_files();
+                cx.emit(TokenUsageUpdated(this.latest_token_usage()));
+                cx.notify();
+                Ok(kept_message_id)
+            })?
+        }))
+    }
+
     pub fn generate_title(&mut self, cx: &mut Context<Self>) {
         let Some(model) = self.summarization_model.clone() else {
             return;
@@ -2355,6 +2837,7 @@ impl Thread {
         });
         self.pending_title_generation = Some(cx.spawn(async move |this, cx| {
             let mut title = String::new();
+            let mut token_usage = TokenUsage::default();
 
             let generate = async {
                 let mut messages = model.stream_completion(request, cx).await?;
@@ -2362,6 +2845,10 @@ impl Thread {
                     let event = event?;
                     let text = match event {
                         LanguageModelCompletionEvent::Text(text) => text,
+                        LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                            token_usage = usage;
+                            continue;
+                        }
                         _ => continue,
                     };
 
@@ -2376,7 +2863,11 @@ impl Thread {
                 anyhow::Ok(())
             };
 
-            if generate.await.context("failed to generate title").is_ok() {
+            let result = generate.await;
+            _ = this.update(cx, |this, cx| {
+                this.record_token_usage(model.as_ref(), token_usage, cx)
+            });
+            if result.context("failed to generate title").is_ok() {
                 _ = this.update(cx, |this, cx| this.set_title(title.into(), cx));
             }
             _ = this.update(cx, |this, _| this.pending_title_generation = None);
@@ -2405,6 +2896,7 @@ impl Thread {
                 Message::User(user_message) => Some(user_message),
                 Message::Agent(_) => None,
                 Message::Resume => None,
+                Message::Compaction(_) => None,
             })
     }
 
@@ -2493,6 +2985,7 @@ impl Thread {
             temperature: AgentSettings::temperature_for_model(model, cx),
             thinking_allowed: self.thinking_enabled,
             thinking_effort: self.thinking_effort.clone(),
+            max_tokens: None,
         };
 
         log::debug!("Completion request built successfully");
@@ -2618,6 +3111,24 @@ impl Thread {
         });
     }
 
+    /// Returns a running subagent whose path scope overlaps the given one. Subagents without a
+    /// path scope may modify any file, so they overlap every scope.
+    pub(crate) fn running_subagent_overlapping(
+        &self,
+        path_scope: &PathScope,
+        cx: &App,
+    ) -> Option<Entity<Thread>> {
+        self.running_subagents
+            .iter()
+            .filter_map(|subagent| subagent.upgrade())
+            .find(|subagent| {
+                subagent
+                    .read(cx)
+                    .path_scope()
+                    .is_none_or(|scope| scope.overlaps(path_scope))
+            })
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn running_subagent_ids(&self, cx: &App) -> Vec<acp::SessionId> {
         self.running_subagents
@@ -2640,6 +3151,19 @@ impl Thread {
         self.subagent_context.as_ref().map(|c| c.depth).unwrap_or(0)
     }
 
+    /// Returns the paths this thread is allowed to modify, if it's a subagent restricted to them.
+    pub fn path_scope(&self) -> Option<&PathScope> {
+        self.subagent_context
+            .as_ref()
+            .and_then(|c| c.path_scope.as_ref())
+    }
+
+    pub(crate) fn set_path_scope(&mut self, path_scope: Option<PathScope>) {
+        if let Some(context) = self.subagent_context.as_mut() {
+            context.path_scope = path_scope;
+        }
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn set_subagent_context(&mut self, context: SubagentContext) {
         self.subagent_context = Some(context);
@@ -2698,6 +3222,7 @@ impl Thread {
                 Message::User(_) => markdown.push_str("## User\n\n"),
                 Message::Agent(_) => markdown.push_str("## Assistant\n\n"),
                 Message::Resume => {}
+                Message::Compaction(_) => markdown.push_str("## Summary\n\n"),
             }
             markdown.push_str(&message.to_markdown());
         }
@@ -2850,6 +3375,11 @@ pub struct TokenUsageUpdated(pub Option<acp_thread::TokenUsage>);
 
 impl EventEmitter<TokenUsageUpdated> for Thread {}
 
+/// Emitted with the tokens, and their cost, that were used since the previous event.
+pub struct ModelTokenUsageRecorded(pub ModelTokenUsage);
+
+impl EventEmitter<ModelTokenUsageRecorded> for Thread {}
+
 pub struct TitleUpdated;
 
 impl EventEmitter<TitleUpdated> for Thread {}
@@ -3219,6 +3749,12 @@ impl ThreadEventStream {
             .ok();
     }
 
+    fn send_context_compacted(&self, compacted_before: UserMessageId) {
+        self.0
+            .unbounded_send(Ok(ThreadEvent::ContextCompacted(compacted_before)))
+            .ok();
+    }
+
     fn send_retry(&self, status: acp_thread::RetryStatus) {
         self.0.unbounded_send(Ok(ThreadEvent::Retry(status))).ok();
     }
@@ -3243,6 +3779,7 @@ pub struct ToolCallEventStream {
     tool_use_id: LanguageModelToolUseId,
     stream: ThreadEventStream,
     fs: Option<Arc<dyn Fs>>,
+    path_scope: Option<PathScope>,
     cancellation_rx: watch::Receiver<bool>,
 }
 
@@ -3262,6 +3799,7 @@ impl ToolCallEventStream {
             "test_id".into(),
             ThreadEventStream(events_tx),
             None,
+            None,
             cancellation_rx,
         );
 
@@ -3282,16 +3820,23 @@ impl ToolCallEventStream {
         tool_use_id: LanguageModelToolUseId,
         stream: ThreadEventStream,
         fs: Option<Arc<dyn Fs>>,
+        path_scope: Option<PathScope>,
         cancellation_rx: watch::Receiver<bool>,
     ) -> Self {
         Self {
             tool_use_id,
             stream,
             fs,
+            path_scope,
             cancellation_rx,
         }
     }
 
+    /// Returns the paths the tool is allowed to modify, if the thread is restricted to them.
+    pub fn path_scope(&self) -> Option<&PathScope> {
+        self.path_scope.as_ref()
+    }
+
     /// Returns a future that resolves when the user cancels the tool call.
     /// Tools should select on this alongside their main work to detect user cancellation.
     pub fn cancelled_by_user(&self) -> impl std::future::Future<Output = ()> + '_ {
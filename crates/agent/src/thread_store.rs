@@ -1,14 +1,19 @@
-use crate::{DbThread, DbThreadMetadata, ThreadsDatabase};
+use crate::{DailyTokenUsage, DbThread, DbThreadMetadata, ModelTokenUsage, ThreadsDatabase};
 use agent_client_protocol as acp;
 use anyhow::{Result, anyhow};
+use chrono::{Days, Local, NaiveDate};
 use gpui::{App, Context, Entity, Global, Task, prelude::*};
 
+/// The number of days, including today, for which token usage is loaded.
+pub const TOKEN_USAGE_HISTORY_DAYS: u64 = 30;
+
 struct GlobalThreadStore(Entity<ThreadStore>);
 
 impl Global for GlobalThreadStore {}
 
 pub struct ThreadStore {
     threads: Vec<DbThreadMetadata>,
+    daily_token_usage: Vec<DailyTokenUsage>,
 }
 
 impl ThreadStore {
@@ -24,8 +29,10 @@ impl ThreadStore {
     pub fn new(cx: &mut Context<Self>) -> Self {
         let this = Self {
             threads: Vec::new(),
+            daily_token_usage: Vec::new(),
         };
         this.reload(cx);
+        this.load_daily_token_usage(cx);
         this
     }
 
@@ -99,6 +106,84 @@ impl ThreadStore {
         .detach_and_log_err(cx);
     }
 
+    fn load_daily_token_usage(&self, cx: &mut Context<Self>) {
+        let database_connection = ThreadsDatabase::connect(cx);
+        let since = Local::now()
+            .date_naive()
+            .checked_sub_days(Days::new(TOKEN_USAGE_HISTORY_DAYS - 1))
+            .unwrap_or(NaiveDate::MIN);
+        cx.spawn(async move |this, cx| {
+            let database = database_connection.await.map_err(|err| anyhow!(err))?;
+            let usage = database.daily_token_usage(since).await?;
+            this.update(cx, |this, cx| {
+                this.daily_token_usage = usage;
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Adds the given usage to today's totals.
+    pub fn record_token_usage(&mut self, usage: ModelTokenUsage, cx: &mut Context<Self>) {
+        let usage = DailyTokenUsage {
+            day: Local::now().date_naive(),
+            usage,
+        };
+        self.add_daily_token_usage(usage.clone());
+        cx.notify();
+
+        let database_connection = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_connection.await.map_err(|err| anyhow!(err))?;
+            database.record_token_usage(usage).await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn add_daily_token_usage(&mut self, usage: DailyTokenUsage) {
+        if let Some(existing) = self.daily_token_usage.iter_mut().find(|existing| {
+            existing.day == usage.day && existing.usage.is_same_model(&usage.usage)
+        }) {
+            existing.usage.add(&usage.usage);
+        } else {
+            self.daily_token_usage.push(usage);
+        }
+    }
+
+    /// Returns the token usage per model and day, for the last [`TOKEN_USAGE_HISTORY_DAYS`] days.
+    pub fn daily_token_usage(&self) -> &[DailyTokenUsage] {
+        &self.daily_token_usage
+    }
+
+    /// Returns the estimated cost in USD of today's requests whose pricing is known.
+    pub fn cost_today(&self) -> f64 {
+        self.usage_today().filter_map(|usage| usage.cost).sum()
+    }
+
+    /// Returns the number of tokens used today, by all models.
+    pub fn tokens_today(&self) -> u64 {
+        self.usage_today()
+            .map(|usage| usage.usage.total_tokens())
+            .sum()
+    }
+
+    /// Returns the number of tokens used today by models whose pricing isn't known, which
+    /// [`Self::cost_today`] doesn't account for.
+    pub fn unpriced_tokens_today(&self) -> u64 {
+        self.usage_today()
+            .filter(|usage| usage.cost.is_none())
+            .map(|usage| usage.usage.total_tokens())
+            .sum()
+    }
+
+    fn usage_today(&self) -> impl Iterator<Item = &ModelTokenUsage> {
+        let today = Local::now().date_naive();
+        self.daily_token_usage
+            .iter()
+            .filter(move |usage| usage.day == today)
+            .map(|usage| &usage.usage)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.threads.is_empty()
     }
@@ -129,6 +214,7 @@ mod tests {
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
             request_token_usage: HashMap::default(),
+            model_token_usage: Vec::new(),
             model: None,
             profile: None,
             imported: false,
@@ -275,4 +361,33 @@ mod tests {
         assert_eq!(entries[0].id, first_id);
         assert_eq!(entries[1].id, second_id);
     }
+
+    #[gpui::test]
+    async fn test_usage_today_includes_unpriced_models(cx: &mut TestAppContext) {
+        let thread_store = cx.new(|cx| ThreadStore::new(cx));
+        cx.run_until_parked();
+
+        let usage = |model: &str, input_tokens, cost| ModelTokenUsage {
+            provider: "provider".into(),
+            model: model.to_string().into(),
+            usage: language_model::TokenUsage {
+                input_tokens,
+                output_tokens: 10,
+                ..Default::default()
+            },
+            cost,
+        };
+        thread_store.update(cx, |store, cx| {
+            store.record_token_usage(usage("priced", 90, Some(0.5)), cx);
+            store.record_token_usage(usage("unpriced", 190, None), cx);
+            store.record_token_usage(usage("unpriced", 290, None), cx);
+        });
+        cx.run_until_parked();
+
+        thread_store.read_with(cx, |store, _| {
+            assert_eq!(store.cost_today(), 0.5);
+            assert_eq!(store.tokens_today(), 600);
+            assert_eq!(store.unpriced_tokens_today(), 500);
+        });
+    }
 }
@@ -1,6 +1,9 @@
 use crate::AgentTool;
 use crate::tools::TerminalTool;
 use agent_settings::{AgentSettings, CompiledRegex, ToolPermissions, ToolRules};
+use gpui::App;
+use project::Project;
+use serde::{Deserialize, Serialize};
 use settings::ToolPermissionMode;
 use shell_command_parser::extract_commands;
 use std::path::{Component, Path};
@@ -520,6 +523,132 @@ pub fn most_restrictive(
     }
 }
 
+/// The paths that a subagent is allowed to modify, so that several subagents can work on
+/// disjoint parts of the project in parallel.
+///
+/// Paths are compared after normalization, as relative paths starting with the name of a
+/// project's root directory. Absolute paths within the project are resolved to that form with
+/// [`PathScope::resolve`] and [`resolve_scope_path`], and others are compared as they are.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathScope {
+    paths: Vec<String>,
+}
+
+impl PathScope {
+    pub fn new(paths: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let paths = paths
+            .into_iter()
+            .map(|path| normalize_path(path.as_ref()))
+            .filter(|path| !path.is_empty())
+            .collect();
+        Self { paths }
+    }
+
+    /// Creates a scope whose paths are resolved within the project, like [`resolve_scope_path`].
+    pub fn resolve(
+        paths: impl IntoIterator<Item = impl AsRef<str>>,
+        project: &Project,
+        cx: &App,
+    ) -> Self {
+        Self::new(
+            paths
+                .into_iter()
+                .map(|path| resolve_scope_path(path.as_ref(), project, cx)),
+        )
+    }
+
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    /// Returns whether the path is one of the scope's paths, or inside one of them.
+    pub fn contains(&self, path: &str) -> bool {
+        let path = normalize_path(path);
+        self.paths
+            .iter()
+            .any(|scope_path| is_same_or_descendant(&path, scope_path))
+    }
+
+    /// Returns whether a path could be in both scopes.
+    pub fn overlaps(&self, other: &PathScope) -> bool {
+        self.paths.iter().any(|path| {
+            other.paths.iter().any(|other_path| {
+                is_same_or_descendant(path, other_path) || is_same_or_descendant(other_path, path)
+            })
+        })
+    }
+}
+
+/// Resolves a path that a tool was given the same way the tools do, and returns it relative to
+/// the project, starting with the name of its worktree's root directory, so that absolute and
+/// relative paths to the same file compare equal. Paths outside of the project are returned as is.
+pub fn resolve_scope_path(raw: &str, project: &Project, cx: &App) -> String {
+    let Some(project_path) = project.find_project_path(raw, cx) else {
+        return raw.to_string();
+    };
+    let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+        return raw.to_string();
+    };
+    let root_name = worktree.read(cx).root_name_str().to_string();
+    if project_path.path.is_empty() {
+        root_name
+    } else {
+        format!("{root_name}/{}", project_path.path.as_unix_str())
+    }
+}
+
+fn is_same_or_descendant(path: &str, ancestor: &str) -> bool {
+    path.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || ancestor.ends_with('/'))
+}
+
+/// Like [`decide_permission_for_paths`], but denies paths outside of the scope of the thread, if
+/// it has one.
+pub fn decide_permission_for_scoped_paths(
+    tool_name: &str,
+    raw_paths: &[String],
+    scope: Option<&PathScope>,
+    settings: &AgentSettings,
+    project: &Project,
+    cx: &App,
+) -> ToolPermissionDecision {
+    if let Some(scope) = scope
+        && let Some(path) = raw_paths
+            .iter()
+            .find(|path| !scope.contains(&resolve_scope_path(path, project, cx)))
+    {
+        return ToolPermissionDecision::Deny(format!(
+            "`{path}` is outside of the paths assigned to this agent ({}). \
+            Only modify files within them, and report any other changes that are needed instead.",
+            scope
+                .paths()
+                .iter()
+                .map(|path| format!("`{path}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    decide_permission_for_paths(tool_name, raw_paths, settings)
+}
+
+pub fn decide_permission_for_scoped_path(
+    tool_name: &str,
+    raw_path: &str,
+    scope: Option<&PathScope>,
+    settings: &AgentSettings,
+    project: &Project,
+    cx: &App,
+) -> ToolPermissionDecision {
+    decide_permission_for_scoped_paths(
+        tool_name,
+        &[raw_path.to_string()],
+        scope,
+        settings,
+        project,
+        cx,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -560,6 +689,9 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions,
             show_turn_stats: false,
+            auto_compact_threshold: 0.8,
+            daily_budget: None,
+            daily_token_budget: None,
         }
     }
 
@@ -2265,4 +2397,87 @@ mod tests {
         let decision = path_perm(EditFileTool::NAME, "src/../.env", &["^\\.env"], &[], &[]);
         assert!(matches!(decision, ToolPermissionDecision::Deny(_)));
     }
+
+    #[test]
+    fn path_scope_contains() {
+        let scope = PathScope::new(["project/src/ui", "project/Cargo.toml"]);
+        assert!(scope.contains("project/src/ui"));
+        assert!(scope.contains("project/src/ui/button.rs"));
+        assert!(scope.contains("project/Cargo.toml"));
+        assert!(!scope.contains("project/src/ui_kit/button.rs"));
+        assert!(!scope.contains("project/src/main.rs"));
+        assert!(!scope.contains("project/src/ui/../main.rs"));
+    }
+
+    #[test]
+    fn path_scope_overlaps() {
+        let scope = PathScope::new(["project/src/ui"]);
+        assert!(scope.overlaps(&PathScope::new(["project/src"])));
+        assert!(scope.overlaps(&PathScope::new(["project/src/ui/button.rs"])));
+        assert!(!scope.overlaps(&PathScope::new(["project/src/ui_kit", "project/tests"])));
+    }
+
+    #[gpui::test]
+    async fn decide_permission_for_scoped_path_denies_paths_outside_scope(
+        cx: &mut gpui::TestAppContext,
+    ) {
+        cx.update(|cx| {
+            let settings_store = settings::SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+        let fs = project::FakeFs::new(cx.executor());
+        fs.insert_tree(
+            util::path!("/root/project"),
+            serde_json::json!({ "src": { "ui": { "button.rs": "" }, "main.rs": "" } }),
+        )
+        .await;
+        let project = Project::test(fs, [util::path!("/root/project").as_ref()], cx).await;
+
+        let settings = test_agent_settings(ToolPermissions {
+            default: ToolPermissionMode::Allow,
+            tools: Default::default(),
+        });
+        project.read_with(cx, |project, cx| {
+            let scope = PathScope::new(["project/src/ui"]);
+            let decide = |path: &str, scope: Option<&PathScope>| {
+                decide_permission_for_scoped_path(
+                    EditFileTool::NAME,
+                    path,
+                    scope,
+                    &settings,
+                    project,
+                    cx,
+                )
+            };
+            assert_eq!(
+                decide("project/src/ui/button.rs", Some(&scope)),
+                ToolPermissionDecision::Allow
+            );
+            assert!(matches!(
+                decide("project/src/main.rs", Some(&scope)),
+                ToolPermissionDecision::Deny(_)
+            ));
+            assert_eq!(
+                decide("project/src/main.rs", None),
+                ToolPermissionDecision::Allow
+            );
+
+            // Absolute paths are resolved within the project, both in the scope and in tool inputs.
+            assert_eq!(
+                decide(util::path!("/root/project/src/ui/button.rs"), Some(&scope)),
+                ToolPermissionDecision::Allow
+            );
+            assert!(matches!(
+                decide(util::path!("/root/project/src/main.rs"), Some(&scope)),
+                ToolPermissionDecision::Deny(_)
+            ));
+            let absolute_scope =
+                PathScope::resolve([util::path!("/root/project/src/ui")], project, cx);
+            assert_eq!(absolute_scope, scope);
+            assert_eq!(
+                decide("project/src/ui/button.rs", Some(&absolute_scope)),
+                ToolPermissionDecision::Allow
+            );
+        });
+    }
 }
@@ -12,8 +12,10 @@ mod move_path_tool;
 mod now_tool;
 mod open_tool;
 mod read_file_tool;
+mod repo_map_tool;
 mod restore_file_from_disk_tool;
 mod save_file_tool;
+mod semantic_search_tool;
 mod spawn_agent_tool;
 mod streaming_edit_file_tool;
 mod terminal_tool;
@@ -37,8 +39,10 @@ pub use move_path_tool::*;
 pub use now_tool::*;
 pub use open_tool::*;
 pub use read_file_tool::*;
+pub use repo_map_tool::*;
 pub use restore_file_from_disk_tool::*;
 pub use save_file_tool::*;
+pub use semantic_search_tool::*;
 pub use spawn_agent_tool::*;
 pub use streaming_edit_file_tool::*;
 pub use terminal_tool::*;
@@ -127,8 +131,10 @@ tools! {
     NowTool,
     OpenTool,
     ReadFileTool,
+    RepoMapTool,
     RestoreFileFromDiskTool,
     SaveFileTool,
+    SemanticSearchTool,
     SpawnAgentTool,
     TerminalTool,
     WebSearchTool,
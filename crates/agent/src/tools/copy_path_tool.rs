@@ -4,6 +4,7 @@ use super::tool_permissions::{
 };
 use crate::{
     AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision, decide_permission_for_paths,
+    decide_permission_for_scoped_path, most_restrictive,
 };
 use agent_client_protocol::ToolKind;
 use agent_settings::AgentSettings;
@@ -93,7 +94,18 @@ impl AgentTool for CopyPathTool {
                 .map_err(|e| format!("Failed to receive tool input: {e}"))?;
             let paths = vec![input.source_path.clone(), input.destination_path.clone()];
             let decision = cx.update(|cx| {
-                decide_permission_for_paths(Self::NAME, &paths, &AgentSettings::get_global(cx))
+                let settings = AgentSettings::get_global(cx);
+                most_restrictive(
+                    decide_permission_for_paths(Self::NAME, &paths, settings),
+                    decide_permission_for_scoped_path(
+                        Self::NAME,
+                        &input.destination_path,
+                        event_stream.path_scope(),
+                        settings,
+                        project.read(cx),
+                        cx,
+                    ),
+                )
             });
             if let ToolPermissionDecision::Deny(reason) = decision {
                 return Err(reason);
@@ -14,7 +14,8 @@ use std::sync::Arc;
 use util::markdown::MarkdownInlineCode;
 
 use crate::{
-    AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision, decide_permission_for_path,
+    AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision,
+    decide_permission_for_scoped_path,
 };
 use std::path::Path;
 
@@ -81,7 +82,14 @@ impl AgentTool for CreateDirectoryTool {
                 .await
                 .map_err(|e| format!("Failed to receive tool input: {e}"))?;
             let decision = cx.update(|cx| {
-                decide_permission_for_path(Self::NAME, &input.path, AgentSettings::get_global(cx))
+                decide_permission_for_scoped_path(
+                    Self::NAME,
+                    &input.path,
+                    event_stream.path_scope(),
+                    AgentSettings::get_global(cx),
+                    project.read(cx),
+                    cx,
+                )
             });
 
             if let ToolPermissionDecision::Deny(reason) = decision {
@@ -3,7 +3,8 @@ use super::tool_permissions::{
     detect_symlink_escape, sensitive_settings_kind,
 };
 use crate::{
-    AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision, decide_permission_for_path,
+    AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision,
+    decide_permission_for_scoped_path,
 };
 use action_log::ActionLog;
 use agent_client_protocol::ToolKind;
@@ -87,7 +88,14 @@ impl AgentTool for DeletePathTool {
             let path = input.path;
 
             let decision = cx.update(|cx| {
-                decide_permission_for_path(Self::NAME, &path, AgentSettings::get_global(cx))
+                decide_permission_for_scoped_path(
+                    Self::NAME,
+                    &path,
+                    event_stream.path_scope(),
+                    AgentSettings::get_global(cx),
+                    project.read(cx),
+                    cx,
+                )
             });
 
             if let ToolPermissionDecision::Deny(reason) = decision {
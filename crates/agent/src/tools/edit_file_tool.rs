@@ -104,6 +104,9 @@ pub enum EditFileToolOutput {
         old_text: Arc<String>,
         #[serde(default)]
         diff: String,
+        /// Scoped project rules that apply to the file and were included in the output.
+        #[serde(default)]
+        project_rules: Option<String>,
         #[serde(alias = "raw_output")]
         edit_agent_output: EditAgentOutput,
     },
@@ -116,17 +119,24 @@ impl std::fmt::Display for EditFileToolOutput {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             EditFileToolOutput::Success {
-                diff, input_path, ..
+                diff,
+                input_path,
+                project_rules,
+                ..
             } => {
                 if diff.is_empty() {
-                    write!(f, "No edits were made.")
+                    write!(f, "No edits were made.")?;
                 } else {
                     write!(
                         f,
                         "Edited {}:\n\n```diff\n{diff}\n```",
                         input_path.display()
-                    )
+                    )?;
+                }
+                if let Some(project_rules) = project_rules {
+                    write!(f, "{project_rules}")?;
                 }
+                Ok(())
             }
             EditFileToolOutput::Error { error } => write!(f, "{error}"),
         }
@@ -287,7 +297,7 @@ impl AgentTool for EditFileTool {
 
                 let edit_format = EditFormat::from_model(model.clone())?;
                 let edit_agent = EditAgent::new(
-                    model,
+                    model.clone(),
                     project.clone(),
                     action_log.clone(),
                     self.templates.clone(),
@@ -433,6 +443,9 @@ impl AgentTool for EditFileTool {
                 }
 
                 let edit_agent_output = output.await?;
+                self.thread.update(cx, |thread, cx| {
+                    thread.record_token_usage(model.as_ref(), edit_agent_output.token_usage, cx)
+                })?;
 
                 let format_on_save_enabled = buffer.read_with(cx, |buffer, cx| {
                     let settings = language_settings::language_settings(
@@ -519,11 +532,16 @@ impl AgentTool for EditFileTool {
                     );
                 }
 
+                let project_rules = self.thread.update(cx, |thread, cx| {
+                    thread.take_scoped_rules_for_path(&project_path, event_stream.tool_use_id(), cx)
+                })?;
+
                 anyhow::Ok(EditFileToolOutput::Success {
                     input_path: input.path,
                     new_text,
                     old_text,
                     diff: unified_diff,
+                    project_rules,
                     edit_agent_output,
                 })
             }.await;
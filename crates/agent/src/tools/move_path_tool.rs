@@ -3,7 +3,8 @@ use super::tool_permissions::{
     collect_symlink_escapes, sensitive_settings_kind,
 };
 use crate::{
-    AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision, decide_permission_for_paths,
+    AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision,
+    decide_permission_for_scoped_paths,
 };
 use agent_client_protocol::ToolKind;
 use agent_settings::AgentSettings;
@@ -106,7 +107,14 @@ impl AgentTool for MovePathTool {
                 .map_err(|e| format!("Failed to receive tool input: {e}"))?;
             let paths = vec![input.source_path.clone(), input.destination_path.clone()];
             let decision = cx.update(|cx| {
-                decide_permission_for_paths(Self::NAME, &paths, AgentSettings::get_global(cx))
+                decide_permission_for_scoped_paths(
+                    Self::NAME,
+                    &paths,
+                    event_stream.path_scope(),
+                    AgentSettings::get_global(cx),
+                    project.read(cx),
+                    cx,
+                )
             });
             if let ToolPermissionDecision::Deny(reason) = decision {
                 return Err(reason);
@@ -350,6 +350,17 @@ impl AgentTool for ReadFileTool {
                 }
             });
 
+            if let Ok(LanguageModelToolResultContent::Text(text)) = &result
+                && let Some(rules) = thread
+                    .update(cx, |thread, cx| {
+                        thread.take_scoped_rules_for_path(&project_path, event_stream.tool_use_id(), cx)
+                    })
+                    .ok()
+                    .flatten()
+            {
+                return Ok(format!("{text}{rules}").into());
+            }
+
             result
         })
     }
@@ -364,12 +375,12 @@ mod test {
     use gpui::{AppContext, TestAppContext, UpdateGlobal as _};
     use language_model::fake_provider::FakeLanguageModel;
     use project::{FakeFs, Project};
-    use prompt_store::ProjectContext;
+    use prompt_store::{ProjectContext, ScopedRulesFileContext, WorktreeContext};
     use serde_json::json;
     use settings::SettingsStore;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use std::sync::Arc;
-    use util::path;
+    use util::{path, rel_path::rel_path};
 
     #[gpui::test]
     async fn test_read_nonexistent_file(cx: &mut TestAppContext) {
@@ -456,6 +467,117 @@ mod test {
         assert_eq!(result.unwrap(), "This is a small file content".into());
     }
 
+    #[gpui::test]
+    async fn test_read_file_includes_scoped_rules(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "db": {
+                    "AGENTS.md": "Use snake_case for column names.",
+                    "README.md": "Database docs",
+                    "schema.sql": "CREATE TABLE users;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let context_server_registry =
+            cx.new(|cx| ContextServerRegistry::new(project.read(cx).context_server_store(), cx));
+        let project_context = ProjectContext::new(
+            vec![WorktreeContext {
+                root_name: "root".into(),
+                abs_path: Path::new(path!("/root")).into(),
+                rules_file: None,
+                scoped_rules_files: vec![
+                    ScopedRulesFileContext::new(
+                        rel_path("db/AGENTS.md").into(),
+                        vec!["**/*.sql".into()],
+                        "Use snake_case for column names.".into(),
+                        0,
+                    )
+                    .unwrap(),
+                ],
+            }],
+            Vec::new(),
+        );
+        let project_context = cx.new(|_cx| project_context);
+        let model = Arc::new(FakeLanguageModel::default());
+        let thread = cx.new(|cx| {
+            Thread::new(
+                project.clone(),
+                project_context.clone(),
+                context_server_registry,
+                Templates::new(),
+                Some(model),
+                cx,
+            )
+        });
+        let tool = Arc::new(ReadFileTool::new(thread.downgrade(), project, action_log));
+        let read = |path: &str, cx: &mut TestAppContext| {
+            let input = ReadFileToolInput {
+                path: path.into(),
+                start_line: None,
+                end_line: None,
+            };
+            cx.update(|cx| {
+                tool.clone().run(
+                    ToolInput::resolved(input),
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+        };
+
+        // The rules only apply to files matching their globs.
+        let result = read("root/db/README.md", cx).await;
+        assert_eq!(result.unwrap(), "Database docs".into());
+
+        let result = read("root/db/schema.sql", cx).await;
+        assert_eq!(
+            result.unwrap(),
+            indoc::indoc! {"
+                CREATE TABLE users;
+
+                The project rules in `root/db/AGENTS.md` apply to this file, and should be followed from now on:
+                ``````
+                Use snake_case for column names.
+                ``````"}
+            .into()
+        );
+        thread.read_with(cx, |thread, _| {
+            assert_eq!(thread.applied_rules_files().len(), 1);
+        });
+
+        // Rules are only included the first time they apply.
+        let result = read("root/db/schema.sql", cx).await;
+        assert_eq!(result.unwrap(), "CREATE TABLE users;".into());
+
+        // Rules are included again once they change.
+        project_context.update(cx, |project_context, _| {
+            project_context.worktrees[0].scoped_rules_files[0].text =
+                "Use snake_case for table names.".into();
+        });
+        let result = read("root/db/schema.sql", cx).await;
+        assert_eq!(
+            result.unwrap(),
+            indoc::indoc! {"
+                CREATE TABLE users;
+
+                The project rules in `root/db/AGENTS.md` apply to this file, and should be followed from now on:
+                ``````
+                Use snake_case for table names.
+                ``````"}
+            .into()
+        );
+        thread.read_with(cx, |thread, _| {
+            assert_eq!(thread.applied_rules_files().len(), 1);
+        });
+    }
+
     #[gpui::test]
     async fn test_read_large_file(cx: &mut TestAppContext) {
         init_test(cx);
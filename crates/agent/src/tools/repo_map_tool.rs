@@ -0,0 +1,407 @@
+use crate::{AgentTool, ToolCallEventStream, ToolInput, repo_map::RepoMap};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, AsyncApp, Entity, Task};
+use language::{Location, Point, ToPoint as _};
+use project::{Project, ProjectPath, lsp_store::SymbolLocation};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use ui::SharedString;
+use util::markdown::MarkdownInlineCode;
+
+const DEFAULT_MAX_TOKENS: usize = 1024;
+const MAX_MAX_TOKENS: usize = 8192;
+const MAX_LOCATIONS: usize = 100;
+
+/// Returns a map of the most important symbols in the project, or finds where a symbol is defined or referenced.
+///
+/// With no arguments, lists the project's files along with their key types, functions and other definitions, ranked by how widely they are used across the project and trimmed to a token budget. Use this to orient yourself in an unfamiliar codebase before reading individual files.
+///
+/// Pass `definitions_of` to find where a symbol is defined, or `references_to` to find the places that use it. These are answered by the language server when one is running, and otherwise fall back to the symbol index.
+///
+/// <example>
+/// To get an overview of the project:
+/// {}
+///
+/// To see the key symbols in one directory with a larger budget:
+/// {
+///     "path": "backend/src/api",
+///     "max_tokens": 4096
+/// }
+///
+/// To find who uses a type:
+/// {
+///     "references_to": "SessionStore"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RepoMapToolInput {
+    /// Only include symbols from files under this path.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// The exact name of a symbol whose definitions should be returned instead of the map.
+    #[serde(default)]
+    pub definitions_of: Option<String>,
+    /// The exact name of a symbol whose references should be returned instead of the map.
+    #[serde(default)]
+    pub references_to: Option<String>,
+    /// Approximate number of tokens the map may use. Defaults to 1024.
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+}
+
+pub struct RepoMapTool {
+    project: Entity<Project>,
+    repo_map: Entity<RepoMap>,
+}
+
+impl RepoMapTool {
+    pub fn new(project: Entity<Project>, cx: &mut App) -> Self {
+        let repo_map = RepoMap::for_project(&project, cx);
+        Self { project, repo_map }
+    }
+}
+
+impl AgentTool for RepoMapTool {
+    type Input = RepoMapToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "repo_map";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(RepoMapToolInput {
+                definitions_of: Some(name),
+                ..
+            }) => format!("Find definitions of {}", MarkdownInlineCode(&name)).into(),
+            Ok(RepoMapToolInput {
+                references_to: Some(name),
+                ..
+            }) => format!("Find references to {}", MarkdownInlineCode(&name)).into(),
+            Ok(RepoMapToolInput {
+                path: Some(path), ..
+            }) if !path.is_empty() => {
+                format!("Map symbols in {}", MarkdownInlineCode(&path)).into()
+            }
+            _ => "Map repository symbols".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let refresh = self.repo_map.update(cx, |repo_map, cx| repo_map.refresh(cx));
+            futures::select! {
+                _ = refresh.fuse() => {}
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Repository map cancelled by user".to_string());
+                }
+            }
+
+            if let Some(name) = input.definitions_of {
+                self.definitions(&name, cx).await
+            } else if let Some(name) = input.references_to {
+                self.references(&name, cx).await
+            } else {
+                let path_prefix = match input.path {
+                    Some(path) if !path.is_empty() => Some(
+                        self.project
+                            .read_with(cx, |project, cx| project.find_project_path(&path, cx))
+                            .ok_or_else(|| format!("Could not find path {path} in project"))?,
+                    ),
+                    _ => None,
+                };
+                let max_tokens = input
+                    .max_tokens
+                    .unwrap_or(DEFAULT_MAX_TOKENS)
+                    .min(MAX_MAX_TOKENS);
+                let output = self.repo_map.read_with(cx, |repo_map, cx| {
+                    repo_map.render(path_prefix.as_ref(), max_tokens, cx)
+                });
+                if output.is_empty() {
+                    Ok("No symbols found. The project may be empty, or its languages may not support outlines.".into())
+                } else {
+                    Ok(output)
+                }
+            }
+        })
+    }
+}
+
+impl RepoMapTool {
+    async fn definitions(&self, name: &str, cx: &mut AsyncApp) -> Result<String, String> {
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.symbols(name, cx))
+            .await
+            .unwrap_or_default();
+
+        let mut output = String::new();
+        let mut count = 0;
+        self.project.read_with(cx, |project, cx| {
+            for symbol in symbols.iter().filter(|symbol| symbol.name == name) {
+                let SymbolLocation::InProject(project_path) = &symbol.path else {
+                    continue;
+                };
+                let Some(full_path) = display_path(project, project_path, cx) else {
+                    continue;
+                };
+                count += 1;
+                writeln!(
+                    output,
+                    "{full_path}:{} ({:?})",
+                    symbol.range.start.0.row + 1,
+                    symbol.kind
+                )
+                .ok();
+            }
+        });
+        if count > 0 {
+            return Ok(format!("Found {count} definitions of `{name}`:\n{output}"));
+        }
+
+        let definitions = self
+            .repo_map
+            .read_with(cx, |repo_map, _| repo_map.definitions(name));
+        self.project.read_with(cx, |project, cx| {
+            for definition in &definitions {
+                let Some(full_path) = display_path(project, &definition.path, cx) else {
+                    continue;
+                };
+                count += 1;
+                writeln!(
+                    output,
+                    "{full_path}:{}: {}",
+                    definition.symbol.row + 1,
+                    definition.symbol.text
+                )
+                .ok();
+            }
+        });
+        if count > 0 {
+            Ok(format!("Found {count} definitions of `{name}`:\n{output}"))
+        } else {
+            Ok(format!("No definitions of `{name}` found."))
+        }
+    }
+
+    async fn references(&self, name: &str, cx: &mut AsyncApp) -> Result<String, String> {
+        let definition = self
+            .repo_map
+            .read_with(cx, |repo_map, _| repo_map.definitions(name))
+            .into_iter()
+            .next();
+
+        if let Some(definition) = definition
+            && let Some(locations) = self
+                .lsp_references(
+                    definition.path,
+                    Point::new(definition.symbol.row, definition.symbol.column),
+                    cx,
+                )
+                .await
+            && !locations.is_empty()
+        {
+            let mut output = format!("Found {} references to `{name}`:\n", locations.len());
+            cx.update(|cx| {
+                for location in locations.iter().take(MAX_LOCATIONS) {
+                    let buffer = location.buffer.read(cx);
+                    let Some(file) = buffer.file() else {
+                        continue;
+                    };
+                    let full_path = file.full_path(cx);
+                    let row = location.range.start.to_point(buffer).row;
+                    let line = buffer
+                        .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+                        .collect::<String>();
+                    writeln!(
+                        output,
+                        "{}:{}: {}",
+                        full_path.display(),
+                        row + 1,
+                        line.trim()
+                    )
+                    .ok();
+                }
+            });
+            if locations.len() > MAX_LOCATIONS {
+                writeln!(
+                    output,
+                    "\n{} more references were omitted.",
+                    locations.len() - MAX_LOCATIONS
+                )
+                .ok();
+            }
+            return Ok(output);
+        }
+
+        // Without a language server, fall back to the files that mention the name.
+        let paths = self
+            .repo_map
+            .read_with(cx, |repo_map, _| repo_map.files_mentioning(name));
+        if paths.is_empty() {
+            return Ok(format!("No references to `{name}` found."));
+        }
+        let mut output = format!(
+            "No language server could resolve `{name}`. These {} files mention it:\n",
+            paths.len()
+        );
+        self.project.read_with(cx, |project, cx| {
+            for path in paths.iter().take(MAX_LOCATIONS) {
+                if let Some(full_path) = display_path(project, path, cx) {
+                    writeln!(output, "{full_path}").ok();
+                }
+            }
+        });
+        Ok(output)
+    }
+
+    async fn lsp_references(
+        &self,
+        project_path: ProjectPath,
+        position: Point,
+        cx: &mut AsyncApp,
+    ) -> Option<Vec<Location>> {
+        let buffer = self
+            .project
+            .update(cx, |project, cx| project.open_buffer(project_path, cx))
+            .await
+            .ok()?;
+        self.project
+            .update(cx, |project, cx| project.references(&buffer, position, cx))
+            .await
+            .ok()
+            .flatten()
+    }
+}
+
+fn display_path(project: &Project, project_path: &ProjectPath, cx: &App) -> Option<String> {
+    let worktree = project.worktree_for_id(project_path.worktree_id, cx)?;
+    Some(
+        worktree
+            .read(cx)
+            .root_name()
+            .join(&project_path.path)
+            .display(project.path_style(cx))
+            .into_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_repo_map_tool(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "src": {
+                    "lib.rs": "pub struct Widget;\n\npub fn helper() {}\n",
+                    "main.rs": "fn main() {\n    let widget = Widget;\n}\n",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        project.update(cx, |project, _| {
+            project.languages().add(language::rust_lang())
+        });
+        let tool = Arc::new(cx.update(|cx| RepoMapTool::new(project.clone(), cx)));
+
+        let run = |input: RepoMapToolInput, cx: &mut TestAppContext| {
+            let tool = tool.clone();
+            cx.update(|cx| {
+                tool.run(
+                    ToolInput::resolved(input),
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+        };
+
+        let output = run(
+            RepoMapToolInput {
+                path: None,
+                definitions_of: None,
+                references_to: None,
+                max_tokens: None,
+            },
+            cx,
+        )
+        .await
+        .unwrap()
+        .replace('\\', "/");
+        assert_eq!(
+            output,
+            "root/src/lib.rs\n  pub struct Widget [L1]\n  pub fn helper [L3]\nroot/src/main.rs\n  fn main [L1]\n"
+        );
+
+        let output = run(
+            RepoMapToolInput {
+                path: None,
+                definitions_of: Some("helper".into()),
+                references_to: None,
+                max_tokens: None,
+            },
+            cx,
+        )
+        .await
+        .unwrap()
+        .replace('\\', "/");
+        assert_eq!(
+            output,
+            "Found 1 definitions of `helper`:\nroot/src/lib.rs:3: pub fn helper\n"
+        );
+
+        let output = run(
+            RepoMapToolInput {
+                path: None,
+                definitions_of: None,
+                references_to: Some("Widget".into()),
+                max_tokens: None,
+            },
+            cx,
+        )
+        .await
+        .unwrap()
+        .replace('\\', "/");
+        assert_eq!(
+            output,
+            "No language server could resolve `Widget`. These 2 files mention it:\nroot/src/lib.rs\nroot/src/main.rs\n"
+        );
+    }
+}
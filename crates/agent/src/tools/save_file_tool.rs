@@ -19,6 +19,7 @@ use super::tool_permissions::{
 };
 use crate::{
     AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision, decide_permission_for_path,
+    decide_permission_for_scoped_path,
 };
 
 /// Saves files that have unsaved changes.
@@ -81,7 +82,14 @@ impl AgentTool for SaveFileTool {
             for path in &input.paths {
                 let path_str = path.to_string_lossy();
                 let decision = cx.update(|cx| {
-                    decide_permission_for_path(Self::NAME, &path_str, AgentSettings::get_global(cx))
+                    decide_permission_for_scoped_path(
+                        Self::NAME,
+                        &path_str,
+                        event_stream.path_scope(),
+                        AgentSettings::get_global(cx),
+                        project.read(cx),
+                        cx,
+                    )
                 });
                 if let ToolPermissionDecision::Deny(reason) = decision {
                     return Err(reason);
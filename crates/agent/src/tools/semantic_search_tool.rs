@@ -0,0 +1,151 @@
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, Task};
+use language::{Bias, ToPoint as _};
+use project::{Project, ProjectPath};
+use schemars::JsonSchema;
+use semantic_index::{SemanticDb, Status};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use ui::SharedString;
+use util::markdown::{MarkdownCodeBlock, MarkdownInlineCode};
+
+const DEFAULT_LIMIT: usize = 8;
+const MAX_LIMIT: usize = 32;
+
+/// Searches the project for code related to a natural language description, using a local embeddings index.
+///
+/// Unlike `grep`, this finds code by meaning rather than by exact text, so it works even when you don't know the names used in the code. Results are chunks of files, such as whole functions or type definitions, ordered from most to least relevant.
+///
+/// Prefer `grep` when you know an identifier or string that appears in the code you're looking for.
+///
+/// <example>
+/// To find where authentication is handled:
+/// {
+///     "query": "check a user's password and start a session"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SemanticSearchToolInput {
+    /// A description of the code to look for.
+    pub query: String,
+    /// The maximum number of chunks to return. Defaults to 8.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+pub struct SemanticSearchTool {
+    project: Entity<Project>,
+}
+
+impl SemanticSearchTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for SemanticSearchTool {
+    type Input = SemanticSearchToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "semantic_search";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Semantic search for {}", MarkdownInlineCode(&input.query)).into(),
+            Err(_) => "Semantic search".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let index = cx
+                .update(|cx| SemanticDb::project_index(&self.project, cx))
+                .ok_or_else(|| {
+                    "Semantic search is not available for this project. Use grep instead."
+                        .to_string()
+                })?;
+            let limit = input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+            let (status, search) = index.read_with(cx, |index, cx| {
+                (index.status(), index.search(input.query, limit, cx))
+            });
+            let results = futures::select! {
+                results = search.fuse() => results.map_err(|e| format!("Semantic search failed: {e:#}"))?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Semantic search cancelled by user".to_string());
+                }
+            };
+
+            let mut output = String::new();
+            if status != Status::Idle {
+                writeln!(
+                    output,
+                    "The project is still being indexed, so some results may be missing.\n"
+                )
+                .ok();
+            }
+            if results.is_empty() {
+                output.push_str("No results found.");
+                return Ok(output);
+            }
+
+            for result in results {
+                let project_path = ProjectPath {
+                    worktree_id: result.worktree_id,
+                    path: result.path,
+                };
+                let Ok(buffer) = self
+                    .project
+                    .update(cx, |project, cx| project.open_buffer(project_path, cx))
+                    .await
+                else {
+                    continue;
+                };
+                buffer.read_with(cx, |buffer, cx| {
+                    // The file may have changed since it was indexed.
+                    let start = buffer.clip_offset(result.range.start, Bias::Left);
+                    let end = buffer.clip_offset(result.range.end, Bias::Right);
+                    let start_row = start.to_point(buffer).row;
+                    let end_row = end.to_point(buffer).row;
+                    let text = buffer.text_for_range(start..end).collect::<String>();
+                    let full_path = buffer
+                        .file()
+                        .map(|file| file.full_path(cx).display().to_string())
+                        .unwrap_or_default();
+                    writeln!(
+                        output,
+                        "## {full_path} (lines {}-{})\n{}",
+                        start_row + 1,
+                        end_row + 1,
+                        MarkdownCodeBlock {
+                            tag: &full_path,
+                            text: text.trim_end(),
+                        }
+                    )
+                    .ok();
+                });
+            }
+            Ok(output)
+        })
+    }
+}
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 use std::sync::Arc;
 
-use crate::{AgentTool, Thread, ThreadEnvironment, ToolCallEventStream, ToolInput};
+use crate::{AgentTool, PathScope, Thread, ThreadEnvironment, ToolCallEventStream, ToolInput};
 
 /// Spawns an agent to perform a delegated task.
 ///
@@ -22,7 +22,9 @@ use crate::{AgentTool, Thread, ThreadEnvironment, ToolCallEventStream, ToolInput
 ///
 /// **Follow-up** (with session_id): Sends a follow-up to an existing agent session. The agent already has full context, so send only a short, direct message — do NOT repeat the original task or context. Examples: "Also update the tests", "Fix the compile error in foo.rs", "Retry".
 ///
-/// - If spawning multiple agents that might write to the filesystem, provide guidance on how to avoid conflicts (e.g. assign each to different directories).
+/// **Parallel subtasks**: To split a task into independent subtasks, spawn one agent per subtask in parallel and assign each the `paths` it may modify. Agents can't modify files outside of their paths, and agents that run at the same time must be assigned disjoint paths. Their edits are added to yours for the user to review, and files that were edited by more than one agent are flagged as conflicts.
+///
+/// - If spawning multiple agents that might write to the filesystem without assigning them paths, provide guidance on how to avoid conflicts (e.g. assign each to different directories).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SpawnAgentToolInput {
     /// Short label displayed in the UI while the agent runs (e.g., "Researching alternatives")
@@ -32,6 +34,9 @@ pub struct SpawnAgentToolInput {
     /// Session ID of an existing agent session to continue instead of creating a new one.
     #[serde(default)]
     pub session_id: Option<acp::SessionId>,
+    /// Files or directories the new agent is restricted to modifying, starting with one of the project's root directories. Omit to let the agent modify any file. Ignored for follow-ups, which keep the paths of the session.
+    #[serde(default)]
+    pub paths: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -123,10 +128,15 @@ impl AgentTool for SpawnAgentTool {
                         cx,
                     )
                 } else {
+                    let path_scope = input.paths.map(|paths| {
+                        let project = parent_thread_entity.read(cx).project().clone();
+                        PathScope::resolve(paths, project.read(cx), cx)
+                    });
                     self.environment.create_subagent(
                         parent_thread_entity,
                         input.label,
                         input.message,
+                        path_scope,
                         cx,
                     )
                 };
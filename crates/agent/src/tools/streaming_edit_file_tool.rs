@@ -297,11 +297,25 @@ impl StreamingEditState {
                 })
                 .await;
 
+            let project_path = buffer.read_with(cx, |buffer, cx| {
+                buffer.file().map(|file| ProjectPath {
+                    worktree_id: file.worktree_id(cx),
+                    path: file.path().clone(),
+                })
+            });
+            let project_rules = match project_path {
+                Some(project_path) => tool.thread.update(cx, |thread, cx| {
+                    thread.take_scoped_rules_for_path(&project_path, event_stream.tool_use_id(), cx)
+                })?,
+                None => None,
+            };
+
             let output = StreamingEditFileToolOutput::Success {
                 input_path: PathBuf::from(input.path),
                 new_text,
                 old_text: old_text.clone(),
                 diff: unified_diff,
+                project_rules,
             };
             Ok(output)
         }
@@ -603,6 +617,9 @@ pub enum StreamingEditFileToolOutput {
         old_text: Arc<String>,
         #[serde(default)]
         diff: String,
+        /// Scoped project rules that apply to the file and were included in the output.
+        #[serde(default)]
+        project_rules: Option<String>,
     },
     Error {
         error: String,
@@ -613,17 +630,24 @@ impl std::fmt::Display for StreamingEditFileToolOutput {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             StreamingEditFileToolOutput::Success {
-                diff, input_path, ..
+                diff,
+                input_path,
+                project_rules,
+                ..
             } => {
                 if diff.is_empty() {
-                    write!(f, "No edits were made.")
+                    write!(f, "No edits were made.")?;
                 } else {
                     write!(
                         f,
                         "Edited {}:\n\n```diff\n{diff}\n```",
                         input_path.display()
-                    )
+                    )?;
+                }
+                if let Some(project_rules) = project_rules {
+                    write!(f, "{project_rules}")?;
                 }
+                Ok(())
             }
             StreamingEditFileToolOutput::Error { error } => write!(f, "{error}"),
         }
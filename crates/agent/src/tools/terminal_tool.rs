@@ -2,7 +2,7 @@ use agent_client_protocol as acp;
 use agent_settings::AgentSettings;
 use anyhow::Result;
 use futures::FutureExt as _;
-use gpui::{App, Entity, SharedString, Task};
+use gpui::{App, Entity, SharedString, Task, WeakEntity};
 use project::Project;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -15,8 +15,9 @@ use std::{
 };
 
 use crate::{
-    AgentTool, ThreadEnvironment, ToolCallEventStream, ToolInput, ToolPermissionDecision,
-    decide_permission_from_settings,
+    AgentTool, Thread, ThreadEnvironment, ToolCallEventStream, ToolInput, ToolPermissionDecision,
+    decide_permission_from_settings, most_restrictive,
+    terminal_sandbox::{self, TerminalSandbox},
 };
 
 const COMMAND_OUTPUT_LIMIT: u64 = 16 * 1024;
@@ -48,17 +49,39 @@ pub struct TerminalToolInput {
 }
 
 pub struct TerminalTool {
+    thread: WeakEntity<Thread>,
     project: Entity<Project>,
     environment: Rc<dyn ThreadEnvironment>,
 }
 
 impl TerminalTool {
-    pub fn new(project: Entity<Project>, environment: Rc<dyn ThreadEnvironment>) -> Self {
+    pub fn new(
+        thread: WeakEntity<Thread>,
+        project: Entity<Project>,
+        environment: Rc<dyn ThreadEnvironment>,
+    ) -> Self {
         Self {
+            thread,
             project,
             environment,
         }
     }
+
+    /// Returns the sandbox that the thread's profile runs commands in, if it enables one.
+    fn sandbox(&self, cx: &App) -> Result<Option<TerminalSandbox>> {
+        let Some(thread) = self.thread.upgrade() else {
+            return Ok(None);
+        };
+        let settings = AgentSettings::get_global(cx);
+        match settings.profiles.get(thread.read(cx).profile()) {
+            Some(profile) if profile.terminal_sandbox.enabled => Ok(Some(TerminalSandbox::new(
+                &profile.terminal_sandbox,
+                &self.project,
+                cx,
+            )?)),
+            _ => Ok(None),
+        }
+    }
 }
 
 impl AgentTool for TerminalTool {
@@ -95,15 +118,21 @@ impl AgentTool for TerminalTool {
                 .await
                 .map_err(|e| format!("Failed to receive tool input: {e}"))?;
 
-            let (working_dir, authorize) = cx.update(|cx| {
+            let (working_dir, sandbox, authorize) = cx.update(|cx| {
                 let working_dir =
                     working_dir(&input, &self.project, cx).map_err(|err| err.to_string())?;
+                let sandbox = self.sandbox(cx).map_err(|err| err.to_string())?;
 
-                let decision = decide_permission_from_settings(
+                let mut decision = decide_permission_from_settings(
                     Self::NAME,
                     std::slice::from_ref(&input.command),
                     AgentSettings::get_global(cx),
                 );
+                // Commands can modify any file, so agents restricted to some paths need the
+                // user to confirm them.
+                if event_stream.path_scope().is_some() {
+                    decision = most_restrictive(decision, ToolPermissionDecision::Confirm);
+                }
 
                 let authorize = match decision {
                     ToolPermissionDecision::Allow => None,
@@ -122,20 +151,21 @@ impl AgentTool for TerminalTool {
                         ))
                     }
                 };
-                Ok((working_dir, authorize))
+                Ok((working_dir, sandbox, authorize))
             })?;
             if let Some(authorize) = authorize {
                 authorize.await.map_err(|e| e.to_string())?;
             }
 
+            let command = match &sandbox {
+                Some(sandbox) => sandbox
+                    .wrap_command(&input.command, working_dir.as_deref())
+                    .map_err(|e| e.to_string())?,
+                None => input.command.clone(),
+            };
             let terminal = self
                 .environment
-                .create_terminal(
-                    input.command.clone(),
-                    working_dir,
-                    Some(COMMAND_OUTPUT_LIMIT),
-                    cx,
-                )
+                .create_terminal(command, working_dir, Some(COMMAND_OUTPUT_LIMIT), cx)
                 .await
                 .map_err(|e| e.to_string())?;
 
@@ -191,13 +221,19 @@ impl AgentTool for TerminalTool {
             let user_stopped = user_stopped_via_signal || user_stopped_via_terminal;
 
             let output = terminal.current_output(cx).map_err(|e| e.to_string())?;
-
-            Ok(process_content(
-                output,
-                &input.command,
-                timed_out,
-                user_stopped,
-            ))
+            let denial = output
+                .exit_status
+                .as_ref()
+                .filter(|_| sandbox.is_some() && !user_stopped)
+                .and_then(|status| {
+                    terminal_sandbox::denied_operation(status.exit_code, &output.output)
+                });
+
+            let content = process_content(output, &input.command, timed_out, user_stopped);
+            match denial {
+                Some(denial) => Err(format!("{content}\n\n{denial}")),
+                None => Ok(content),
+            }
         })
     }
 }
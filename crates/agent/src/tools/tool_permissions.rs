@@ -1,6 +1,6 @@
 use crate::{
     Thread, ToolCallEventStream, ToolPermissionContext, ToolPermissionDecision,
-    decide_permission_for_path,
+    decide_permission_for_scoped_path,
 };
 use anyhow::{Result, anyhow};
 use fs::Fs;
@@ -370,8 +370,21 @@ pub fn authorize_file_edit(
 ) -> Task<Result<()>> {
     let path_str = path.to_string_lossy();
 
+    let Some(project) = thread
+        .upgrade()
+        .map(|thread| thread.read(cx).project().clone())
+    else {
+        return Task::ready(Err(anyhow!("thread was dropped")));
+    };
     let settings = agent_settings::AgentSettings::get_global(cx);
-    let decision = decide_permission_for_path(tool_name, &path_str, settings);
+    let decision = decide_permission_for_scoped_path(
+        tool_name,
+        &path_str,
+        event_stream.path_scope(),
+        settings,
+        project.read(cx),
+        cx,
+    );
 
     if let ToolPermissionDecision::Deny(reason) = decision {
         return Task::ready(Err(anyhow!("{}", reason)));
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{Result, bail};
 use collections::IndexMap;
@@ -7,7 +7,7 @@ use fs::Fs;
 use gpui::{App, SharedString};
 use settings::{
     AgentProfileContent, ContextServerPresetContent, LanguageModelSelection, Settings as _,
-    SettingsContent, update_settings_file,
+    SettingsContent, TerminalSandboxContent, update_settings_file,
 };
 use util::ResultExt as _;
 
@@ -70,6 +70,10 @@ impl AgentProfile {
         let default_model = base_profile
             .as_ref()
             .and_then(|profile| profile.default_model.clone());
+        let terminal_sandbox = base_profile
+            .as_ref()
+            .map(|profile| profile.terminal_sandbox.clone())
+            .unwrap_or_default();
 
         let profile_settings = AgentProfileSettings {
             name: name.into(),
@@ -77,6 +81,7 @@ impl AgentProfile {
             enable_all_context_servers,
             context_servers,
             default_model,
+            terminal_sandbox,
         };
 
         update_settings_file(fs, cx, {
@@ -109,6 +114,7 @@ pub struct AgentProfileSettings {
     pub context_servers: IndexMap<Arc<str>, ContextServerPreset>,
     /// Default language model to apply when this profile becomes active.
     pub default_model: Option<LanguageModelSelection>,
+    pub terminal_sandbox: TerminalSandboxSettings,
 }
 
 impl AgentProfileSettings {
@@ -158,6 +164,7 @@ impl AgentProfileSettings {
                     })
                     .collect(),
                 default_model: self.default_model.clone(),
+                terminal_sandbox: self.terminal_sandbox.to_content(),
             },
         );
 
@@ -173,6 +180,7 @@ impl From<AgentProfileContent> for AgentProfileSettings {
             enable_all_context_servers,
             context_servers,
             default_model,
+            terminal_sandbox,
         } = content;
 
         Self {
@@ -184,6 +192,7 @@ impl From<AgentProfileContent> for AgentProfileSettings {
                 .map(|(server_id, preset)| (server_id, preset.into()))
                 .collect(),
             default_model,
+            terminal_sandbox: terminal_sandbox.map(Into::into).unwrap_or_default(),
         }
     }
 }
@@ -200,3 +209,36 @@ impl From<settings::ContextServerPresetContent> for ContextServerPreset {
         }
     }
 }
+
+/// Restricts what the commands run by the terminal tool can access.
+#[derive(Debug, Clone, Default)]
+pub struct TerminalSandboxSettings {
+    pub enabled: bool,
+    pub allow_network: bool,
+    /// Paths that commands can write to, besides the project's worktrees.
+    pub writable_paths: Vec<PathBuf>,
+}
+
+impl TerminalSandboxSettings {
+    /// Returns the settings content for these settings, or `None` if they are the defaults.
+    pub fn to_content(&self) -> Option<TerminalSandboxContent> {
+        if !self.enabled && !self.allow_network && self.writable_paths.is_empty() {
+            return None;
+        }
+        Some(TerminalSandboxContent {
+            enabled: Some(self.enabled),
+            allow_network: Some(self.allow_network),
+            writable_paths: Some(self.writable_paths.clone()),
+        })
+    }
+}
+
+impl From<TerminalSandboxContent> for TerminalSandboxSettings {
+    fn from(content: TerminalSandboxContent) -> Self {
+        Self {
+            enabled: content.enabled.unwrap_or_default(),
+            allow_network: content.allow_network.unwrap_or_default(),
+            writable_paths: content.writable_paths.unwrap_or_default(),
+        }
+    }
+}
@@ -20,6 +20,7 @@ pub use crate::agent_profile::*;
 pub const SUMMARIZE_THREAD_PROMPT: &str = include_str!("prompts/summarize_thread_prompt.txt");
 pub const SUMMARIZE_THREAD_DETAILED_PROMPT: &str =
     include_str!("prompts/summarize_thread_detailed_prompt.txt");
+pub const COMPACT_THREAD_PROMPT: &str = include_str!("prompts/compact_thread_prompt.txt");
 
 #[derive(Clone, Debug, RegisterSetting)]
 pub struct AgentSettings {
@@ -50,6 +51,9 @@ pub struct AgentSettings {
     pub use_modifier_to_send: bool,
     pub message_editor_min_lines: usize,
     pub show_turn_stats: bool,
+    pub auto_compact_threshold: f32,
+    pub daily_budget: Option<f64>,
+    pub daily_token_budget: Option<u64>,
     pub tool_permissions: ToolPermissions,
 }
 
@@ -437,6 +441,9 @@ impl Settings for AgentSettings {
             use_modifier_to_send: agent.use_modifier_to_send.unwrap(),
             message_editor_min_lines: agent.message_editor_min_lines.unwrap(),
             show_turn_stats: agent.show_turn_stats.unwrap(),
+            auto_compact_threshold: agent.auto_compact_threshold.unwrap(),
+            daily_budget: agent.daily_budget,
+            daily_token_budget: agent.daily_token_budget,
             tool_permissions: compile_tool_permissions(agent.tool_permissions),
         }
     }
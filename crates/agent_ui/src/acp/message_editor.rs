@@ -79,6 +79,7 @@ impl PromptCompletionProviderDelegate for Entity<MessageEditor> {
                 PromptContextType::Diagnostics,
                 PromptContextType::Fetch,
                 PromptContextType::Rules,
+                PromptContextType::McpResource,
             ]);
         }
         supported
@@ -463,7 +464,9 @@ impl MessageEditor {
                                     mention_image.format.mime_type(),
                                 )
                                 .uri(match uri {
-                                    MentionUri::File { .. } => Some(uri.to_uri().to_string()),
+                                    MentionUri::File { .. } | MentionUri::McpResource { .. } => {
+                                        Some(uri.to_uri().to_string())
+                                    }
                                     MentionUri::PastedImage => None,
                                     other => {
                                         debug_panic!(
@@ -931,6 +934,52 @@ impl MessageEditor {
         .detach();
     }
 
+    /// Appends a mention of a context server resource to the message.
+    pub fn insert_mcp_resource(
+        &mut self,
+        mention_uri: MentionUri,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let buffer = self.editor.read(cx).buffer().clone();
+        let Some(buffer) = buffer.read(cx).as_singleton() else {
+            return;
+        };
+
+        let new_text = format!("{} ", mention_uri.as_link());
+        let content_len = new_text.len() - 1;
+        let anchor = buffer.update(cx, |buffer, _cx| buffer.anchor_before(buffer.len()));
+        self.editor.update(cx, |message_editor, cx| {
+            message_editor.edit(
+                [(
+                    multi_buffer::Anchor::max()..multi_buffer::Anchor::max(),
+                    new_text,
+                )],
+                cx,
+            );
+        });
+
+        let supports_images = self.prompt_capabilities.borrow().image;
+        self.mention_set
+            .update(cx, |mention_set, cx| {
+                mention_set.confirm_mention_completion(
+                    mention_uri.name().into(),
+                    anchor,
+                    content_len,
+                    mention_uri,
+                    supports_images,
+                    self.editor.clone(),
+                    &workspace,
+                    window,
+                    cx,
+                )
+            })
+            .detach();
+    }
+
     /// Inserts code snippets as creases into the editor.
     /// Each tuple contains (code_text, crease_title).
     pub fn insert_code_creases(
@@ -208,6 +208,10 @@ pub struct AcpThreadView {
     pub(super) thread_error: Option<ThreadError>,
     pub thread_error_markdown: Option<Entity<Markdown>>,
     pub token_limit_callout_dismissed: bool,
+    daily_budget_callout_visible: bool,
+    /// Whether the user chose to keep sending messages in this thread after reaching the daily
+    /// budget.
+    daily_budget_acknowledged: bool,
     pub last_token_limit_telemetry: Option<acp_thread::TokenUsageRatio>,
     thread_feedback: ThreadFeedbackState,
     pub list_state: ListState,
@@ -409,6 +413,8 @@ impl AcpThreadView {
             thread_error: None,
             thread_error_markdown: None,
             token_limit_callout_dismissed: false,
+            daily_budget_callout_visible: false,
+            daily_budget_acknowledged: false,
             last_token_limit_telemetry: None,
             thread_feedback: Default::default(),
             expanded_tool_calls: HashSet::default(),
@@ -689,9 +695,31 @@ impl AcpThreadView {
             }
         }
 
+        if self.is_over_daily_budget(cx) {
+            self.daily_budget_callout_visible = true;
+            cx.notify();
+            return;
+        }
+
         self.send_impl(message_editor, window, cx)
     }
 
+    /// Returns whether today's requests reached the daily budget in US dollars or in tokens,
+    /// unless the user already chose to keep sending in this thread.
+    fn is_over_daily_budget(&self, cx: &App) -> bool {
+        if self.daily_budget_acknowledged || self.as_native_thread(cx).is_none() {
+            return false;
+        }
+        let settings = AgentSettings::get_global(cx);
+        let thread_store = ThreadStore::global(cx).read(cx);
+        settings
+            .daily_budget
+            .is_some_and(|budget| thread_store.cost_today() >= budget)
+            || settings
+                .daily_token_budget
+                .is_some_and(|budget| thread_store.tokens_today() >= budget)
+    }
+
     pub fn send_impl(
         &mut self,
         message_editor: Entity<MessageEditor>,
@@ -1585,6 +1613,40 @@ impl AcpThreadView {
             .detach_and_log_err(cx);
     }
 
+    /// Opens a diff for every file touched by the agent that changed since the checkpoint.
+    pub fn view_checkpoint_changes(
+        &mut self,
+        checkpoint: FileCheckpoint,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let changes = self
+            .thread
+            .read(cx)
+            .action_log()
+            .update(cx, |action_log, cx| {
+                action_log.changes_since_checkpoint(checkpoint, cx)
+            });
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            for change in changes.await? {
+                workspace
+                    .update_in(cx, |workspace, window, cx| {
+                        TextDiffView::open_checkpoint_diff(
+                            change.checkpoint_text,
+                            change.buffer,
+                            workspace,
+                            window,
+                            cx,
+                        )
+                    })?
+                    .await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub fn clear_thread_error(&mut self, cx: &mut Context<Self>) {
         self.thread_error = None;
         self.thread_error_markdown = None;
@@ -1673,13 +1735,21 @@ impl AcpThreadView {
         let Some(thread) = self.as_native_thread(cx) else {
             return;
         };
-        let project_context = thread.read(cx).project_context().read(cx);
+        let thread = thread.read(cx);
+        let project_context = thread.project_context().read(cx);
 
         let project_entry_ids = project_context
             .worktrees
             .iter()
             .flat_map(|worktree| worktree.rules_file.as_ref())
-            .map(|rules_file| ProjectEntryId::from_usize(rules_file.project_entry_id))
+            .map(|rules_file| rules_file.project_entry_id)
+            .chain(
+                thread
+                    .applied_rules_files()
+                    .iter()
+                    .map(|rules_file| rules_file.project_entry_id),
+            )
+            .map(ProjectEntryId::from_usize)
             .collect::<Vec<_>>();
 
         self.workspace
@@ -2875,13 +2945,15 @@ impl AcpThreadView {
             let (user_rules_count, project_rules_count) = self
                 .as_native_thread(cx)
                 .map(|thread| {
-                    let project_context = thread.read(cx).project_context().read(cx);
+                    let thread = thread.read(cx);
+                    let project_context = thread.project_context().read(cx);
                     let user_rules = project_context.user_rules.len();
                     let project_rules = project_context
                         .worktrees
                         .iter()
                         .filter(|wt| wt.rules_file.is_some())
-                        .count();
+                        .count()
+                        + thread.applied_rules_files().len();
                     (user_rules, project_rules)
                 })
                 .unwrap_or((0, 0));
@@ -3358,6 +3430,15 @@ impl AcpThreadView {
                             }
                         }),
                 )
+                .item(
+                    ContextMenuEntry::new("MCP Resources")
+                        .icon(IconName::ToolHammer)
+                        .icon_color(Color::Muted)
+                        .icon_size(IconSize::XSmall)
+                        .handler(move |window, cx| {
+                            window.dispatch_action(crate::BrowseMcpResources.boxed_clone(), cx);
+                        }),
+                )
                 .item(
                     ContextMenuEntry::new("Image")
                         .icon(IconName::Image)
@@ -3492,6 +3573,11 @@ impl AcpThreadView {
                     .as_ref()
                     .is_some_and(|checkpoint| checkpoint.show);
 
+                let is_compaction_boundary = message
+                    .id
+                    .as_ref()
+                    .is_some_and(|id| self.thread.read(cx).compacted_before() == Some(id));
+
                 let agent_name = self.agent_name.clone();
                 let is_subagent = self.is_subagent();
 
@@ -3520,8 +3606,26 @@ impl AcpThreadView {
                     .gap_1p5()
                     .w_full()
                     .children(rules_item)
+                    .when(is_compaction_boundary, |this| {
+                        this.child(
+                            h_flex()
+                                .id("compaction-boundary")
+                                .px_3()
+                                .gap_2()
+                                .child(Divider::horizontal())
+                                .child(
+                                    Label::new("Earlier messages were summarized")
+                                        .size(LabelSize::XSmall)
+                                        .color(Color::Muted),
+                                )
+                                .child(Divider::horizontal())
+                                .tooltip(Tooltip::text("The messages above were replaced by a summary to fit the model's context window. They are kept here, but the agent only sees their summary.")),
+                        )
+                    })
                     .children(message.id.clone().and_then(|message_id| {
-                        message.checkpoint.as_ref()?.show.then(|| {
+                        let checkpoint = message.checkpoint.as_ref()?;
+                        let file_checkpoint = checkpoint.file_checkpoint();
+                        checkpoint.show.then(|| {
                             h_flex()
                                 .px_3()
                                 .gap_2()
@@ -3539,6 +3643,19 @@ impl AcpThreadView {
                                             this.restore_checkpoint(&message_id, cx);
                                         }))
                                 )
+                                .child(
+                                    Button::new("view-checkpoint-changes", "View Changes")
+                                        .icon(IconName::Diff)
+                                        .icon_size(IconSize::XSmall)
+                                        .icon_position(IconPosition::Start)
+                                        .label_size(LabelSize::XSmall)
+                                        .icon_color(Color::Muted)
+                                        .color(Color::Muted)
+                                        .tooltip(Tooltip::text("Shows how the files edited by the agent changed since this point in the conversation."))
+                                        .on_click(cx.listener(move |this, _, window, cx| {
+                                            this.view_checkpoint_changes(file_checkpoint, window, cx);
+                                        }))
+                                )
                                 .child(Divider::horizontal())
                         })
                     }))
@@ -6540,20 +6657,37 @@ impl AcpThreadView {
             .first()
             .map(|user_rules| user_rules.uuid.0);
 
+        let applied_rules_files = self
+            .as_native_thread(cx)?
+            .read(cx)
+            .applied_rules_files()
+            .iter()
+            .map(|rules_file| &rules_file.path_in_worktree);
         let rules_files = project_context
             .worktrees
             .iter()
             .filter_map(|worktree| worktree.rules_file.as_ref())
+            .map(|rules_file| &rules_file.path_in_worktree)
+            .chain(applied_rules_files)
             .collect::<Vec<_>>();
 
         let rules_file_text = match rules_files.as_slice() {
             &[] => None,
-            &[rules_file] => Some(format!(
-                "Using project {:?} file",
-                rules_file.path_in_worktree
-            )),
+            &[path_in_worktree] => Some(format!("Using project {:?} file", path_in_worktree)),
             rules_files => Some(format!("Using {} project rules files", rules_files.len())),
         };
+        let rules_file_tooltip = if rules_files.len() > 1 {
+            format!(
+                "View Project Rules:\n{}",
+                rules_files
+                    .iter()
+                    .map(|path_in_worktree| path_in_worktree.as_unix_str())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        } else {
+            "View Project Rules".to_string()
+        };
 
         if user_rules_text.is_none() && rules_file_text.is_none() {
             return None;
@@ -6611,7 +6745,7 @@ impl AcpThreadView {
                                     .color(Color::Muted),
                             )
                             .hover(|s| s.bg(cx.theme().colors().element_hover))
-                            .tooltip(Tooltip::text("View Project Rules"))
+                            .tooltip(Tooltip::text(rules_file_tooltip))
                             .on_click(cx.listener(Self::handle_open_rules)),
                     )
                 })
@@ -7071,6 +7205,72 @@ impl AcpThreadView {
         )
     }
 
+    fn render_daily_budget_callout(&self, cx: &mut Context<Self>) -> Option<Callout> {
+        if !self.daily_budget_callout_visible {
+            return None;
+        }
+
+        let settings = AgentSettings::get_global(cx);
+        let thread_store = ThreadStore::global(cx).read(cx);
+        let cost_today = thread_store.cost_today();
+        let tokens_today = thread_store.tokens_today();
+        let unpriced_tokens_today = thread_store.unpriced_tokens_today();
+        let mut description = match (settings.daily_budget, settings.daily_token_budget) {
+            (Some(budget), _) if cost_today >= budget => format!(
+                "Today's requests cost an estimated ${cost_today:.2}, reaching your daily budget of ${budget:.2}."
+            ),
+            (_, Some(budget)) if tokens_today >= budget => format!(
+                "Today's requests used {} tokens, reaching your daily budget of {} tokens.",
+                crate::text_thread_editor::humanize_token_count(tokens_today),
+                crate::text_thread_editor::humanize_token_count(budget),
+            ),
+            _ => return None,
+        };
+        if unpriced_tokens_today > 0 {
+            description.push_str(&format!(
+                " {} tokens went to models whose pricing is unknown.",
+                crate::text_thread_editor::humanize_token_count(unpriced_tokens_today)
+            ));
+        }
+
+        Some(
+            Callout::new()
+                .severity(Severity::Warning)
+                .icon(IconName::Warning)
+                .title("Daily budget reached")
+                .description(description)
+                .actions_slot(
+                    h_flex()
+                        .gap_0p5()
+                        .child(
+                            Button::new("view-usage", "View Usage")
+                                .label_size(LabelSize::Small)
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(OpenUsageDashboard.boxed_clone(), cx);
+                                }),
+                        )
+                        .child(
+                            Button::new("send-over-budget", "Send Anyway")
+                                .label_size(LabelSize::Small)
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.daily_budget_acknowledged = true;
+                                    this.daily_budget_callout_visible = false;
+                                    this.send(window, cx);
+                                })),
+                        ),
+                )
+                .dismiss_action(
+                    IconButton::new("dismiss-daily-budget", IconName::Close)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text("Dismiss"))
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.daily_budget_callout_visible = false;
+                            cx.notify();
+                        })),
+                ),
+        )
+    }
+
     fn open_permission_dropdown(
         &mut self,
         _: &crate::OpenPermissionDropdown,
@@ -7325,6 +7525,7 @@ impl Render for AcpThreadView {
                 |this, version| this.child(self.render_new_version_callout(&version, cx)),
             )
             .children(self.render_token_limit_callout(cx))
+            .children(self.render_daily_budget_callout(cx))
             .child(self.render_message_editor(window, cx))
     }
 }
@@ -7450,6 +7651,7 @@ pub(crate) fn open_link(
             MentionUri::Diagnostics { .. } => {}
             MentionUri::TerminalSelection { .. } => {}
             MentionUri::GitDiff { .. } => {}
+            MentionUri::McpResource { .. } => {}
         })
     } else {
         cx.open_url(&url);
@@ -32,7 +32,10 @@ use project::{
         AgentServerStore, CLAUDE_AGENT_NAME, CODEX_NAME, ExternalAgentServerName,
         ExternalAgentSource, GEMINI_NAME,
     },
-    context_server_store::{ContextServerConfiguration, ContextServerStatus, ContextServerStore},
+    context_server_store::{
+        ContextServerConfiguration, ContextServerStatus, ContextServerStore,
+        ServerStatusChangedEvent,
+    },
 };
 use settings::{Settings, SettingsStore, update_settings_file};
 use ui::{
@@ -97,8 +100,11 @@ impl AgentConfiguration {
             },
         );
 
-        cx.subscribe(&context_server_store, |_, _, _, cx| cx.notify())
-            .detach();
+        cx.subscribe(
+            &context_server_store,
+            |_, _, _: &ServerStatusChangedEvent, cx| cx.notify(),
+        )
+        .detach();
 
         let mut this = Self {
             fs,
@@ -885,34 +885,38 @@ fn wait_for_context_server(
     let tx = Arc::new(Mutex::new(Some(tx)));
 
     let context_server_id_for_timeout = context_server_id.clone();
-    let subscription = cx.subscribe(context_server_store, move |_, event, _cx| {
-        let project::context_server_store::ServerStatusChangedEvent { server_id, status } = event;
-
-        match status {
-            ContextServerStatus::Running => {
-                if server_id == &context_server_id
-                    && let Some(tx) = tx.lock().unwrap().take()
-                {
-                    let _ = tx.send(Ok(()));
+    let subscription = cx.subscribe(
+        context_server_store,
+        move |_, event: &project::context_server_store::ServerStatusChangedEvent, _cx| {
+            let project::context_server_store::ServerStatusChangedEvent { server_id, status } =
+                event;
+
+            match status {
+                ContextServerStatus::Running => {
+                    if server_id == &context_server_id
+                        && let Some(tx) = tx.lock().unwrap().take()
+                    {
+                        let _ = tx.send(Ok(()));
+                    }
                 }
-            }
-            ContextServerStatus::Stopped => {
-                if server_id == &context_server_id
-                    && let Some(tx) = tx.lock().unwrap().take()
-                {
-                    let _ = tx.send(Err("Context server stopped running".into()));
+                ContextServerStatus::Stopped => {
+                    if server_id == &context_server_id
+                        && let Some(tx) = tx.lock().unwrap().take()
+                    {
+                        let _ = tx.send(Err("Context server stopped running".into()));
+                    }
                 }
-            }
-            ContextServerStatus::Error(error) => {
-                if server_id == &context_server_id
-                    && let Some(tx) = tx.lock().unwrap().take()
-                {
-                    let _ = tx.send(Err(error.clone()));
+                ContextServerStatus::Error(error) => {
+                    if server_id == &context_server_id
+                        && let Some(tx) = tx.lock().unwrap().take()
+                    {
+                        let _ = tx.send(Err(error.clone()));
+                    }
                 }
+                _ => {}
             }
-            _ => {}
-        }
-    });
+        },
+    );
 
     cx.spawn(async move |cx| {
         let timeout = cx.background_executor().timer(WAIT_TIMEOUT);
@@ -315,6 +315,7 @@ impl PickerDelegate for ToolPickerDelegate {
                             })
                             .collect(),
                         default_model: default_profile.default_model.clone(),
+                        terminal_sandbox: default_profile.terminal_sandbox.to_content(),
                     });
 
                 if let Some(server_id) = server_id {
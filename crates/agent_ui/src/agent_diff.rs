@@ -28,7 +28,10 @@ use std::{
     ops::Range,
     sync::Arc,
 };
-use ui::{CommonAnimationExt, IconButtonShape, KeyBinding, Tooltip, prelude::*, vertical_divider};
+use ui::{
+    Callout, CommonAnimationExt, IconButtonShape, KeyBinding, Severity, Tooltip, prelude::*,
+    vertical_divider,
+};
 use util::ResultExt;
 use workspace::{
     Item, ItemHandle, ItemNavHistory, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView,
@@ -107,7 +110,8 @@ impl AgentDiffPane {
         let mut this = Self {
             _subscriptions: vec![
                 cx.observe_in(&action_log, window, |this, _action_log, window, cx| {
-                    this.update_excerpts(window, cx)
+                    this.update_excerpts(window, cx);
+                    cx.notify();
                 }),
                 cx.subscribe(&thread, |this, _thread, event, cx| {
                     this.handle_acp_thread_event(event, cx)
@@ -265,6 +269,68 @@ impl AgentDiffPane {
         }
     }
 
+    /// Renders the files that were edited by more than one agent, e.g. subagents that ran in
+    /// parallel, whose edits might not fit together.
+    fn render_edit_conflicts(&self, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let action_log = self.thread.read(cx).action_log().clone();
+        action_log
+            .read(cx)
+            .edit_conflicts(cx)
+            .into_iter()
+            .enumerate()
+            .map(|(ix, conflict)| {
+                let buffer = conflict.buffer;
+                let path = buffer
+                    .read(cx)
+                    .file()
+                    .map(|file| file.full_path(cx).to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "untitled".into());
+                let mut sources = conflict
+                    .sources
+                    .iter()
+                    .map(|source| format!("\"{source}\""))
+                    .collect::<Vec<_>>();
+                if conflict.includes_own_edits {
+                    sources.insert(0, "this thread".into());
+                }
+                let path_key = PathKey::for_buffer(&buffer, cx);
+
+                Callout::new()
+                    .severity(Severity::Warning)
+                    .icon(IconName::Warning)
+                    .title(format!("Conflicting Edits in {path}"))
+                    .description(format!(
+                        "Edited by {}. Review the file as a whole, as the edits might not fit together.",
+                        sources.join(", ")
+                    ))
+                    .actions_slot(
+                        h_flex()
+                            .gap_0p5()
+                            .child(
+                                Button::new(("go-to-conflict", ix), "Go to File")
+                                    .label_size(LabelSize::Small)
+                                    .on_click(cx.listener(move |this, _, window, cx| {
+                                        this.move_to_path(path_key.clone(), window, cx)
+                                    })),
+                            )
+                            .child(
+                                Button::new(("resolve-conflict", ix), "Mark Resolved")
+                                    .label_size(LabelSize::Small)
+                                    .on_click({
+                                        let action_log = action_log.clone();
+                                        move |_, _, cx| {
+                                            action_log.update(cx, |action_log, cx| {
+                                                action_log.resolve_edit_conflict(&buffer, cx)
+                                            })
+                                        }
+                                    }),
+                            ),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+
     fn keep(&mut self, _: &Keep, window: &mut Window, cx: &mut Context<Self>) {
         self.editor.update(cx, |editor, cx| {
             let snapshot = editor.buffer().read(cx).snapshot(cx);
@@ -702,7 +768,14 @@ impl Render for AgentDiffPane {
                         ),
                 )
             })
-            .when(!is_empty, |el| el.child(self.editor.clone()))
+            .when(!is_empty, |el| {
+                el.child(
+                    v_flex()
+                        .size_full()
+                        .children(self.render_edit_conflicts(cx))
+                        .child(div().flex_1().min_h_0().w_full().child(self.editor.clone())),
+                )
+            })
     }
 }
 
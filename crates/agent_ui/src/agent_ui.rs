@@ -12,6 +12,8 @@ mod favorite_models;
 mod inline_assistant;
 mod inline_prompt_editor;
 mod language_model_selector;
+mod mcp_resource_picker;
+mod mcp_server_requests;
 mod mention_set;
 mod profile_selector;
 mod slash_command;
@@ -21,6 +23,7 @@ mod terminal_inline_assistant;
 mod text_thread_editor;
 mod text_thread_history;
 mod ui;
+mod usage_dashboard;
 
 use std::rc::Rc;
 use std::sync::Arc;
@@ -100,6 +103,8 @@ actions!(
         OpenActiveThreadAsMarkdown,
         /// Opens the agent diff view to review changes.
         OpenAgentDiff,
+        /// Opens a dashboard with the tokens used by agent threads and their estimated cost.
+        OpenUsageDashboard,
         /// Copies the current thread to the clipboard as JSON for debugging.
         CopyThreadToClipboard,
         /// Loads a thread from the clipboard JSON for debugging.
@@ -128,6 +133,8 @@ actions!(
         ResetTrialEndUpsell,
         /// Opens the "Add Context" menu in the message editor.
         OpenAddContextMenu,
+        /// Opens a picker to browse and attach resources provided by context servers.
+        BrowseMcpResources,
         /// Continues the current thread.
         ContinueThread,
         /// Interrupts the current generation and sends the message immediately.
@@ -287,6 +294,7 @@ pub fn init(
     assistant_slash_command::init(cx);
     agent_panel::init(cx);
     context_server_configuration::init(language_registry.clone(), fs.clone(), cx);
+    mcp_server_requests::init(cx);
     TextThreadEditor::init(cx);
 
     register_slash_commands(cx);
@@ -576,6 +584,9 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions: Default::default(),
             show_turn_stats: false,
+            auto_compact_threshold: 0.8,
+            daily_budget: None,
+            daily_token_budget: None,
         };
 
         cx.update(|cx| {
@@ -398,6 +398,10 @@ impl CodegenAlternative {
         &self.last_equal_ranges
     }
 
+    pub fn transformation_transaction_id(&self) -> Option<TransactionId> {
+        self.transformation_transaction_id
+    }
+
     pub fn use_streaming_tools(model: &dyn LanguageModel, cx: &App) -> bool {
         model.supports_streaming_tools()
             && AgentSettings::get_global(cx).inline_assistant_use_streaming_tools
@@ -547,6 +551,7 @@ impl CodegenAlternative {
                 messages,
                 thinking_allowed: false,
                 thinking_effort: None,
+                max_tokens: None,
             }
         }))
     }
@@ -626,6 +631,7 @@ impl CodegenAlternative {
                 messages: vec![request_message],
                 thinking_allowed: false,
                 thinking_effort: None,
+                max_tokens: None,
             }
         }))
     }
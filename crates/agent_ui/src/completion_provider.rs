@@ -7,6 +7,7 @@ use std::sync::atomic::AtomicBool;
 use crate::acp::AcpThreadHistory;
 use acp_thread::{AgentSessionInfo, MentionUri};
 use anyhow::Result;
+use context_server::ContextServerId;
 use editor::{
     CompletionProvider, Editor, ExcerptId, code_context_menus::COMPLETION_MENU_MAX_WIDTH,
 };
@@ -63,6 +64,7 @@ pub(crate) enum PromptContextType {
     Thread,
     Rules,
     Diagnostics,
+    McpResource,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -101,6 +103,7 @@ impl TryFrom<&str> for PromptContextType {
             "thread" => Ok(Self::Thread),
             "rule" => Ok(Self::Rules),
             "diagnostics" => Ok(Self::Diagnostics),
+            "resource" => Ok(Self::McpResource),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -115,6 +118,7 @@ impl PromptContextType {
             Self::Thread => "thread",
             Self::Rules => "rule",
             Self::Diagnostics => "diagnostics",
+            Self::McpResource => "resource",
         }
     }
 
@@ -126,6 +130,7 @@ impl PromptContextType {
             Self::Thread => "Threads",
             Self::Rules => "Rules",
             Self::Diagnostics => "Diagnostics",
+            Self::McpResource => "MCP Resources",
         }
     }
 
@@ -137,6 +142,7 @@ impl PromptContextType {
             Self::Thread => IconName::Thread,
             Self::Rules => IconName::Reader,
             Self::Diagnostics => IconName::Warning,
+            Self::McpResource => IconName::ToolHammer,
         }
     }
 }
@@ -148,6 +154,7 @@ pub(crate) enum Match {
     RecentThread(AgentSessionInfo),
     Fetch(SharedString),
     Rules(RulesContextEntry),
+    McpResource(McpResourceMatch),
     Entry(EntryMatch),
 }
 
@@ -161,6 +168,7 @@ impl Match {
             Match::Symbol(_) => 1.,
             Match::Rules(_) => 1.,
             Match::Fetch(_) => 1.,
+            Match::McpResource(resource) => resource.score,
         }
     }
 }
@@ -523,6 +531,60 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
         })
     }
 
+    fn completion_for_mcp_resource(
+        resource: McpResourceMatch,
+        source_range: Range<Anchor>,
+        source: Arc<T>,
+        editor: WeakEntity<Editor>,
+        mention_set: WeakEntity<MentionSet>,
+        workspace: Entity<Workspace>,
+        cx: &mut App,
+    ) -> Completion {
+        let uri = MentionUri::McpResource {
+            server_id: resource.server_id.0.to_string(),
+            uri: resource.resource.uri.to_string(),
+            name: resource.resource.name.clone(),
+        };
+        let new_text = format!("{} ", uri.as_link());
+        let new_text_len = new_text.len();
+        let icon_path = uri.icon_path(cx);
+        let title: SharedString = resource.resource.name.clone().into();
+        let variable_highlight_id = cx
+            .theme()
+            .syntax()
+            .highlight_id("variable")
+            .map(HighlightId);
+        let mut label = CodeLabelBuilder::default();
+        label.push_str(&resource.resource.name, None);
+        label.push_str(" ", None);
+        label.push_str(&resource.server_id.0, variable_highlight_id);
+        Completion {
+            replace_range: source_range.clone(),
+            new_text,
+            label: label.build(),
+            documentation: resource
+                .resource
+                .description
+                .clone()
+                .map(|description| CompletionDocumentation::SingleLine(description.into())),
+            insert_text_mode: None,
+            source: project::CompletionSource::Custom,
+            match_start: None,
+            snippet_deduplication_key: None,
+            icon_path: Some(icon_path),
+            confirm: Some(confirm_completion_callback(
+                title,
+                source_range.start,
+                new_text_len - 1,
+                uri,
+                source,
+                editor,
+                mention_set,
+                workspace,
+            )),
+        }
+    }
+
     pub(crate) fn completion_for_action(
         action: PromptContextAction,
         source_range: Range<Anchor>,
@@ -877,6 +939,18 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
 
             Some(PromptContextType::Diagnostics) => Task::ready(Vec::new()),
 
+            Some(PromptContextType::McpResource) => {
+                let project = workspace.read(cx).project().clone();
+                let search_task = search_mcp_resources(query, cancellation_flag, &project, cx);
+                cx.background_spawn(async move {
+                    search_task
+                        .await
+                        .into_iter()
+                        .map(Match::McpResource)
+                        .collect()
+                })
+            }
+
             None if query.is_empty() => {
                 let recent_task = self.recent_context_picker_entries(&workspace, cx);
                 let entries = self
@@ -1075,6 +1149,22 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
             entries.push(PromptContextEntry::Mode(PromptContextType::Fetch));
         }
 
+        if self
+            .source
+            .supports_context(PromptContextType::McpResource, cx)
+            && workspace
+                .read(cx)
+                .project()
+                .read(cx)
+                .context_server_store()
+                .read(cx)
+                .resources()
+                .next()
+                .is_some()
+        {
+            entries.push(PromptContextEntry::Mode(PromptContextType::McpResource));
+        }
+
         if self
             .source
             .supports_context(PromptContextType::Diagnostics, cx)
@@ -1325,6 +1415,17 @@ impl<T: PromptCompletionProviderDelegate> CompletionProvider for PromptCompletio
                                     workspace.clone(),
                                     cx,
                                 )),
+                                Match::McpResource(resource) => {
+                                    Some(Self::completion_for_mcp_resource(
+                                        resource,
+                                        source_range.clone(),
+                                        source.clone(),
+                                        editor.clone(),
+                                        mention_set.clone(),
+                                        workspace.clone(),
+                                        cx,
+                                    ))
+                                }
                                 Match::Fetch(url) => Self::completion_for_fetch(
                                     source_range.clone(),
                                     url,
@@ -1944,6 +2045,68 @@ pub(crate) fn search_rules(
     })
 }
 
+pub(crate) fn search_mcp_resources(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    project: &Entity<Project>,
+    cx: &mut App,
+) -> Task<Vec<McpResourceMatch>> {
+    let resources = project
+        .read(cx)
+        .context_server_store()
+        .read(cx)
+        .resources()
+        .map(|(server_id, resource)| (server_id.clone(), resource.clone()))
+        .collect::<Vec<_>>();
+    if query.is_empty() {
+        return Task::ready(
+            resources
+                .into_iter()
+                .map(|(server_id, resource)| McpResourceMatch {
+                    server_id,
+                    resource,
+                    score: 1.,
+                })
+                .collect(),
+        );
+    }
+
+    let candidates = resources
+        .iter()
+        .enumerate()
+        .map(|(ix, (_, resource))| StringMatchCandidate::new(ix, &resource.name))
+        .collect::<Vec<_>>();
+    let executor = cx.background_executor().clone();
+    cx.background_spawn(async move {
+        fuzzy::match_strings(
+            &candidates,
+            &query,
+            false,
+            true,
+            100,
+            &cancellation_flag,
+            executor,
+        )
+        .await
+        .into_iter()
+        .map(|mat| {
+            let (server_id, resource) = resources[mat.candidate_id].clone();
+            McpResourceMatch {
+                server_id,
+                resource,
+                score: mat.score,
+            }
+        })
+        .collect()
+    })
+}
+
+pub struct McpResourceMatch {
+    pub server_id: ContextServerId,
+    pub resource: context_server::types::Resource,
+    pub score: f64,
+}
+
 pub struct SymbolMatch {
     pub symbol: Symbol,
 }
@@ -40,7 +40,7 @@ use gpui::{
     App, Context, Entity, Focusable, Global, HighlightStyle, Subscription, Task, UpdateGlobal,
     WeakEntity, Window, point,
 };
-use language::{Buffer, Point, Selection, TransactionId};
+use language::{Buffer, BufferSnapshot, Point, Selection, TransactionId};
 use language_model::{ConfigurationError, ConfiguredModel, LanguageModelRegistry};
 use multi_buffer::MultiBufferRow;
 use parking_lot::Mutex;
@@ -81,6 +81,7 @@ pub fn init(fs: Arc<dyn Fs>, prompt_builder: Arc<PromptBuilder>, cx: &mut App) {
 }
 
 const PROMPT_HISTORY_MAX_LEN: usize = 20;
+const MAX_EXCERPT_ASSISTS: usize = 64;
 
 enum InlineAssistTarget {
     Editor(Entity<Editor>),
@@ -252,6 +253,25 @@ impl InlineAssistant {
         action: &zed_actions::assistant::InlineAssist,
         window: &mut Window,
         cx: &mut Context<Workspace>,
+    ) {
+        Self::deploy(workspace, action.prompt.clone(), false, window, cx);
+    }
+
+    pub fn inline_assist_excerpts(
+        workspace: &mut Workspace,
+        action: &zed_actions::assistant::InlineAssistExcerpts,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        Self::deploy(workspace, action.prompt.clone(), true, window, cx);
+    }
+
+    fn deploy(
+        workspace: &mut Workspace,
+        prompt: Option<String>,
+        all_excerpts: bool,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
     ) {
         if !AgentSettings::get_global(cx).enabled(cx) {
             return;
@@ -284,14 +304,20 @@ impl InlineAssistant {
             |window: &mut Window, cx: &mut Context<Workspace>| match inline_assist_target {
                 InlineAssistTarget::Editor(active_editor) => {
                     InlineAssistant::update_global(cx, |assistant, cx| {
-                        assistant.assist(
+                        let assist = if all_excerpts {
+                            InlineAssistant::assist_excerpts
+                        } else {
+                            InlineAssistant::assist
+                        };
+                        assist(
+                            assistant,
                             &active_editor,
                             cx.entity().downgrade(),
                             workspace.project().downgrade(),
                             thread_store,
                             prompt_store,
                             history,
-                            action.prompt.clone(),
+                            prompt.clone(),
                             window,
                             cx,
                         );
@@ -306,7 +332,7 @@ impl InlineAssistant {
                             thread_store,
                             prompt_store,
                             history,
-                            action.prompt.clone(),
+                            prompt.clone(),
                             window,
                             cx,
                         );
@@ -453,31 +479,72 @@ impl InlineAssistant {
             );
 
             codegen_ranges.push(anchor_range);
+            Self::report_assist_invoked(buffer, cx);
+        }
 
-            if let Some(model) = LanguageModelRegistry::read_global(cx).inline_assistant_model() {
-                telemetry::event!(
-                    "Assistant Invoked",
-                    kind = "inline",
-                    phase = "invoked",
-                    model = model.model.telemetry_id(),
-                    model_provider = model.provider.id().to_string(),
-                    language_name = buffer.language().map(|language| language.name().to_proto())
-                );
+        Some((codegen_ranges, newest_selection))
+    }
 
-                report_anthropic_event(
-                    &model.model,
-                    AnthropicEventData {
-                        completion_type: language_model::AnthropicCompletionType::Editor,
-                        event: language_model::AnthropicEventType::Invoked,
-                        language_name: buffer.language().map(|language| language.name().to_proto()),
-                        message_id: None,
-                    },
-                    cx,
-                );
+    /// Returns a range for each excerpt of the editor's multibuffer that
+    /// doesn't already contain an inline assist.
+    fn excerpt_codegen_ranges(
+        &self,
+        editor: &Entity<Editor>,
+        snapshot: &EditorSnapshot,
+        cx: &mut App,
+    ) -> Vec<Range<Anchor>> {
+        let snapshot = snapshot.buffer_snapshot();
+        let existing_ranges = self
+            .assists_by_editor
+            .get(&editor.downgrade())
+            .into_iter()
+            .flat_map(|editor_assists| &editor_assists.assist_ids)
+            .map(|assist_id| self.assists[assist_id].range.to_offset(snapshot))
+            .collect::<Vec<_>>();
+
+        let mut codegen_ranges = Vec::new();
+        for (excerpt_id, buffer, excerpt_range) in snapshot.excerpts() {
+            let buffer_range = excerpt_range.context.to_offset(buffer);
+            let anchor_range = Anchor::range_in_buffer(
+                excerpt_id,
+                buffer.anchor_before(buffer_range.start)..buffer.anchor_after(buffer_range.end),
+            );
+            let offset_range = anchor_range.to_offset(snapshot);
+            if existing_ranges
+                .iter()
+                .any(|range| range.overlaps(&offset_range))
+            {
+                continue;
             }
+
+            codegen_ranges.push(anchor_range);
+            Self::report_assist_invoked(buffer, cx);
         }
+        codegen_ranges
+    }
 
-        Some((codegen_ranges, newest_selection))
+    fn report_assist_invoked(buffer: &BufferSnapshot, cx: &App) {
+        if let Some(model) = LanguageModelRegistry::read_global(cx).inline_assistant_model() {
+            telemetry::event!(
+                "Assistant Invoked",
+                kind = "inline",
+                phase = "invoked",
+                model = model.model.telemetry_id(),
+                model_provider = model.provider.id().to_string(),
+                language_name = buffer.language().map(|language| language.name().to_proto())
+            );
+
+            report_anthropic_event(
+                &model.model,
+                AnthropicEventData {
+                    completion_type: language_model::AnthropicCompletionType::Editor,
+                    event: language_model::AnthropicEventType::Invoked,
+                    language_name: buffer.language().map(|language| language.name().to_proto()),
+                    message_id: None,
+                },
+                cx,
+            );
+        }
     }
 
     fn batch_assist(
@@ -661,6 +728,79 @@ impl InlineAssistant {
         assist_to_focus
     }
 
+    /// Deploys a linked inline assist on every excerpt of the editor's
+    /// multibuffer. Once started, each excerpt is transformed concurrently and
+    /// can be accepted or rejected on its own.
+    pub fn assist_excerpts(
+        &mut self,
+        editor: &Entity<Editor>,
+        workspace: WeakEntity<Workspace>,
+        project: WeakEntity<Project>,
+        thread_store: Entity<ThreadStore>,
+        prompt_store: Option<Entity<PromptStore>>,
+        history: WeakEntity<AcpThreadHistory>,
+        initial_prompt: Option<String>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<InlineAssistId> {
+        let snapshot = editor.update(cx, |editor, cx| editor.snapshot(window, cx));
+        let mut codegen_ranges = self.excerpt_codegen_ranges(editor, &snapshot, cx);
+        if codegen_ranges.is_empty() {
+            return self.assist(
+                editor,
+                workspace,
+                project,
+                thread_store,
+                prompt_store,
+                history,
+                initial_prompt,
+                window,
+                cx,
+            );
+        }
+
+        if codegen_ranges.len() > MAX_EXCERPT_ASSISTS {
+            codegen_ranges.truncate(MAX_EXCERPT_ASSISTS);
+            workspace
+                .update(cx, |workspace, cx| {
+                    struct TooManyExcerpts;
+
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<TooManyExcerpts>(),
+                            format!(
+                                "Inline assist was limited to the first {MAX_EXCERPT_ASSISTS} excerpts."
+                            ),
+                        )
+                        .autohide(),
+                        cx,
+                    );
+                })
+                .ok();
+        }
+
+        let assist_to_focus = self.batch_assist(
+            editor,
+            workspace,
+            project,
+            thread_store,
+            prompt_store,
+            history,
+            initial_prompt,
+            window,
+            &codegen_ranges,
+            None,
+            None,
+            cx,
+        );
+
+        if let Some(assist_id) = assist_to_focus {
+            self.focus_assist(assist_id, window, cx);
+        }
+
+        assist_to_focus
+    }
+
     pub fn suggest_assist(
         &mut self,
         editor: &Entity<Editor>,
@@ -1060,8 +1200,25 @@ impl InlineAssistant {
         self.dismiss_assist(assist_id, window, cx);
 
         if let Some(assist) = self.assists.remove(&assist_id) {
+            let active_alternative = assist.codegen.read(cx).active_alternative().clone();
             if let hash_map::Entry::Occupied(mut entry) = self.assist_groups.entry(assist.group_id)
             {
+                if !undo
+                    && let Some(transaction_id) =
+                        active_alternative.read(cx).transformation_transaction_id()
+                    && let Some(editor) = assist.editor.upgrade()
+                {
+                    // Group the accepted edits of all the group's assists, so they're undone together.
+                    if let Some(group_transaction_id) = entry.get().transaction_id {
+                        let buffer = editor.read(cx).buffer().clone();
+                        buffer.update(cx, |buffer, cx| {
+                            buffer.merge_transactions(transaction_id, group_transaction_id, cx)
+                        });
+                    } else {
+                        entry.get_mut().transaction_id = Some(transaction_id);
+                    }
+                }
+
                 entry.get_mut().assist_ids.retain(|id| *id != assist_id);
                 if entry.get().assist_ids.is_empty() {
                     entry.remove();
@@ -1082,7 +1239,6 @@ impl InlineAssistant {
                 }
             }
 
-            let active_alternative = assist.codegen.read(cx).active_alternative().clone();
             if let Some(model) = LanguageModelRegistry::read_global(cx).inline_assistant_model() {
                 let language_name = assist.editor.upgrade().and_then(|editor| {
                     let multibuffer = editor.read(cx).buffer().read(cx);
@@ -1711,6 +1867,8 @@ struct InlineAssistGroup {
     assist_ids: Vec<InlineAssistId>,
     linked: bool,
     active_assist_id: Option<InlineAssistId>,
+    /// The transaction that the group's accepted edits are merged into.
+    transaction_id: Option<TransactionId>,
 }
 
 impl InlineAssistGroup {
@@ -1719,6 +1877,7 @@ impl InlineAssistGroup {
             assist_ids: Vec::new(),
             linked: true,
             active_assist_id: None,
+            transaction_id: None,
         }
     }
 }
@@ -2429,3 +2588,172 @@ pub mod evals {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language::Capability;
+    use project::FakeFs;
+    use serde_json::json;
+    use util::path;
+    use workspace::MultiWorkspace;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+            release_channel::init(semver::Version::new(0, 0, 0), cx);
+            prompt_store::init(cx);
+            LanguageModelRegistry::test(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_assist_excerpts(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({})).await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+        cx.update(|_, cx| {
+            let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+            cx.set_global(InlineAssistant::new(fs.clone(), prompt_builder));
+        });
+
+        let buffer_a = cx.new(|cx| Buffer::local("first\nmiddle\nsecond\n", cx));
+        let buffer_b = cx.new(|cx| Buffer::local("third\n", cx));
+        let editor = cx.update(|window, cx| {
+            let multibuffer = cx.new(|cx| {
+                let mut multibuffer = MultiBuffer::new(Capability::ReadWrite);
+                multibuffer.push_excerpts(
+                    buffer_a.clone(),
+                    [
+                        ExcerptRange::new(Point::new(0, 0)..Point::new(0, 5)),
+                        ExcerptRange::new(Point::new(2, 0)..Point::new(2, 6)),
+                    ],
+                    cx,
+                );
+                multibuffer.push_excerpts(
+                    buffer_b.clone(),
+                    [ExcerptRange::new(Point::new(0, 0)..Point::new(0, 5))],
+                    cx,
+                );
+                multibuffer
+            });
+            let editor = cx.new(|cx| Editor::for_multibuffer(multibuffer, None, window, cx));
+            workspace.update(cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, window, cx);
+            });
+            editor
+        });
+
+        let assist_ids = cx.update(|window, cx| {
+            let thread_store = cx.new(|cx| ThreadStore::new(cx));
+            let history = cx.new(|cx| AcpThreadHistory::new(None, window, cx));
+            InlineAssistant::update_global(cx, |inline_assistant, cx| {
+                let assist_id = inline_assistant
+                    .assist_excerpts(
+                        &editor,
+                        workspace.downgrade(),
+                        project.downgrade(),
+                        thread_store,
+                        None,
+                        history.downgrade(),
+                        Some("Uppercase it".to_string()),
+                        window,
+                        cx,
+                    )
+                    .unwrap();
+                inline_assistant.start_assist(assist_id, window, cx);
+                inline_assistant.assists_by_editor[&editor.downgrade()]
+                    .assist_ids
+                    .clone()
+            })
+        });
+        cx.run_until_parked();
+
+        // Each excerpt gets its own codegen, which is started with the excerpt's text.
+        let excerpt_texts = cx.update(|_, cx| {
+            let inline_assistant = cx.global::<InlineAssistant>();
+            let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+            assist_ids
+                .iter()
+                .map(|assist_id| {
+                    let range = inline_assistant.assists[assist_id].range.clone();
+                    snapshot.text_for_range(range).collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(excerpt_texts, ["first", "second", "third"]);
+        let model = cx.update(|_, cx| LanguageModelRegistry::read_global(cx).fake_model());
+        let model = model.as_fake();
+        assert_eq!(model.pending_completions().len(), 3);
+        for excerpt_text in &excerpt_texts {
+            let rewrite_section = format!("<rewrite_this>\n{excerpt_text}\n</rewrite_this>");
+            let request = model
+                .pending_completions()
+                .into_iter()
+                .find(|request| {
+                    request
+                        .messages
+                        .iter()
+                        .any(|message| message.string_contents().contains(&rewrite_section))
+                })
+                .unwrap();
+            model.send_completion_stream_text_chunk(&request, excerpt_text.to_uppercase());
+            model.end_completion_stream(&request);
+        }
+        cx.run_until_parked();
+        assert_eq!(
+            buffer_a.read_with(cx, |buffer, _| buffer.text()),
+            "FIRST\nmiddle\nSECOND\n"
+        );
+        assert_eq!(buffer_b.read_with(cx, |buffer, _| buffer.text()), "THIRD\n");
+
+        // Rejecting an excerpt's edit leaves the other excerpts alone.
+        cx.update(|window, cx| {
+            InlineAssistant::update_global(cx, |inline_assistant, cx| {
+                inline_assistant.finish_assist(assist_ids[1], true, window, cx);
+                assert!(inline_assistant.assists.contains_key(&assist_ids[0]));
+                assert!(inline_assistant.assists.contains_key(&assist_ids[2]));
+            })
+        });
+        assert_eq!(
+            buffer_a.read_with(cx, |buffer, _| buffer.text()),
+            "FIRST\nmiddle\nsecond\n"
+        );
+        assert_eq!(buffer_b.read_with(cx, |buffer, _| buffer.text()), "THIRD\n");
+
+        // Accepting an excerpt's edit leaves the other excerpts pending.
+        cx.update(|window, cx| {
+            InlineAssistant::update_global(cx, |inline_assistant, cx| {
+                inline_assistant.finish_assist(assist_ids[0], false, window, cx);
+                assert!(!inline_assistant.assists.contains_key(&assist_ids[0]));
+                assert!(inline_assistant.assists.contains_key(&assist_ids[2]));
+                inline_assistant.finish_assist(assist_ids[2], false, window, cx);
+                assert!(inline_assistant.assists.is_empty());
+            })
+        });
+        assert_eq!(
+            buffer_a.read_with(cx, |buffer, _| buffer.text()),
+            "FIRST\nmiddle\nsecond\n"
+        );
+        assert_eq!(buffer_b.read_with(cx, |buffer, _| buffer.text()), "THIRD\n");
+
+        // A single undo reverts the accepted edits across buffers.
+        editor.update_in(cx, |editor, window, cx| {
+            editor.undo(&editor::actions::Undo, window, cx)
+        });
+        assert_eq!(
+            buffer_a.read_with(cx, |buffer, _| buffer.text()),
+            "first\nmiddle\nsecond\n"
+        );
+        assert_eq!(buffer_b.read_with(cx, |buffer, _| buffer.text()), "third\n");
+    }
+}
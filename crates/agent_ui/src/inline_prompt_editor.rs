@@ -1200,6 +1200,7 @@ impl PromptCompletionProviderDelegate for PromptEditorCompletionProviderDelegate
             PromptContextType::Thread,
             PromptContextType::Fetch,
             PromptContextType::Rules,
+            PromptContextType::McpResource,
         ]
     }
 
@@ -0,0 +1,410 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use acp_thread::MentionUri;
+use collections::HashMap;
+use context_server::{ContextServerId, types::Resource, types::ResourceTemplate};
+use gpui::{
+    DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ImageFormat, Task, WeakEntity,
+};
+use picker::{Picker, PickerDelegate};
+use project::{Project, context_server_store::ContextServerStore};
+use ui::{DocumentationAside, DocumentationSide, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+use crate::AgentPanel;
+use crate::completion_provider::{McpResourceMatch, search_mcp_resources};
+
+/// Maximum number of characters of a text resource shown in the preview.
+const PREVIEW_MAX_CHARS: usize = 2000;
+
+pub struct McpResourcePicker {
+    picker: Entity<Picker<McpResourcePickerDelegate>>,
+}
+
+impl McpResourcePicker {
+    pub fn toggle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        if workspace.panel::<AgentPanel>(cx).is_none() {
+            return;
+        }
+        let project = workspace.project().clone();
+        let weak_workspace = cx.weak_entity();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            Self::new(project, weak_workspace, window, cx)
+        });
+    }
+
+    fn new(
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = McpResourcePickerDelegate {
+            picker: cx.entity().downgrade(),
+            project,
+            workspace,
+            entries: Vec::new(),
+            query: String::new(),
+            selected_index: 0,
+            previews: HashMap::default(),
+            load_preview: Task::ready(()),
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl Render for McpResourcePicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for McpResourcePicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for McpResourcePicker {}
+impl ModalView for McpResourcePicker {}
+
+enum ResourceEntry {
+    Resource(McpResourceMatch),
+    Template {
+        server_id: ContextServerId,
+        template: ResourceTemplate,
+    },
+}
+
+enum ResourcePreview {
+    Loading,
+    Text(SharedString),
+    Error(SharedString),
+}
+
+struct McpResourcePickerDelegate {
+    picker: WeakEntity<McpResourcePicker>,
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    entries: Vec<ResourceEntry>,
+    query: String,
+    selected_index: usize,
+    previews: HashMap<String, ResourcePreview>,
+    load_preview: Task<()>,
+}
+
+impl McpResourcePickerDelegate {
+    fn is_text_resource(resource: &Resource) -> bool {
+        resource.mime_type.as_deref().is_none_or(|mime_type| {
+            mime_type.starts_with("text/")
+                || mime_type.ends_with("json")
+                || mime_type.ends_with("xml")
+                || mime_type.ends_with("yaml")
+        })
+    }
+
+    /// Fills in the variables of a resource template with the whitespace-separated
+    /// words of the query, in order.
+    fn expand_template(&self, template: &ResourceTemplate) -> Option<url::Url> {
+        let variables = ContextServerStore::resource_template_variables(&template.uri_template);
+        let values = self.query.split_whitespace().collect::<Vec<_>>();
+        if values.len() < variables.len() {
+            return None;
+        }
+        let arguments = variables
+            .into_iter()
+            .zip(values)
+            .map(|(variable, value)| (variable.to_string(), value.to_string()))
+            .collect();
+        ContextServerStore::expand_resource_template(&template.uri_template, &arguments).log_err()
+    }
+
+    fn mention_uri_for_entry(&self, entry: &ResourceEntry) -> Option<MentionUri> {
+        match entry {
+            ResourceEntry::Resource(mat) => Some(MentionUri::McpResource {
+                server_id: mat.server_id.0.to_string(),
+                uri: mat.resource.uri.to_string(),
+                name: mat.resource.name.clone(),
+            }),
+            ResourceEntry::Template {
+                server_id,
+                template,
+            } => {
+                let uri = self.expand_template(template)?;
+                Some(MentionUri::McpResource {
+                    server_id: server_id.0.to_string(),
+                    name: format!("{} ({})", template.name, self.query.trim()),
+                    uri: uri.to_string(),
+                })
+            }
+        }
+    }
+
+    fn load_preview(&mut self, cx: &mut Context<Picker<Self>>) {
+        let Some(ResourceEntry::Resource(mat)) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        if !Self::is_text_resource(&mat.resource) {
+            return;
+        }
+        let uri = mat.resource.uri.clone();
+        if matches!(
+            self.previews.get(uri.as_str()),
+            Some(ResourcePreview::Text(_) | ResourcePreview::Error(_))
+        ) {
+            return;
+        }
+
+        let read_task = self
+            .project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .read_resource(&mat.server_id, uri.clone(), cx);
+        self.previews
+            .insert(uri.to_string(), ResourcePreview::Loading);
+        self.load_preview = cx.spawn(async move |picker, cx| {
+            let preview = match read_task.await {
+                Ok(response) => {
+                    let text = response
+                        .contents
+                        .into_iter()
+                        .filter_map(|contents| match contents {
+                            context_server::types::ResourceContentsType::Text(contents) => {
+                                Some(contents.text)
+                            }
+                            context_server::types::ResourceContentsType::Blob(_) => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    let text = util::truncate_and_trailoff(&text, PREVIEW_MAX_CHARS);
+                    ResourcePreview::Text(text.into())
+                }
+                Err(error) => ResourcePreview::Error(error.to_string().into()),
+            };
+            picker
+                .update(cx, |picker, cx| {
+                    picker.delegate.previews.insert(uri.to_string(), preview);
+                    cx.notify();
+                })
+                .ok();
+        });
+    }
+}
+
+impl PickerDelegate for McpResourcePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search resources, or type template arguments…".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No resources found".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+        self.load_preview(cx);
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let templates = self
+            .project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .resource_templates()
+            .map(|(server_id, template)| ResourceEntry::Template {
+                server_id: server_id.clone(),
+                template: template.clone(),
+            })
+            .collect::<Vec<_>>();
+        let search_task = search_mcp_resources(
+            query.clone(),
+            Arc::new(AtomicBool::default()),
+            &self.project,
+            cx,
+        );
+
+        cx.spawn(async move |picker, cx| {
+            let resources = search_task.await;
+            picker
+                .update(cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.query = query;
+                    delegate.entries = resources
+                        .into_iter()
+                        .map(ResourceEntry::Resource)
+                        .chain(templates)
+                        .collect();
+                    delegate.selected_index = 0;
+                    delegate.load_preview(cx);
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(mention_uri) = self
+            .entries
+            .get(self.selected_index)
+            .and_then(|entry| self.mention_uri_for_entry(entry))
+        else {
+            return;
+        };
+
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let Some(panel) = workspace.panel::<AgentPanel>(cx) else {
+                    return;
+                };
+                workspace.focus_panel::<AgentPanel>(window, cx);
+                panel.update(cx, |panel, cx| {
+                    if let Some(thread_view) = panel.active_thread_view() {
+                        thread_view.update(cx, |thread_view, cx| {
+                            thread_view.insert_mcp_resource(mention_uri, window, cx);
+                        });
+                    }
+                });
+            })
+            .log_err();
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let (name, server_id, detail, icon) = match self.entries.get(ix)? {
+            ResourceEntry::Resource(mat) => (
+                mat.resource.name.clone(),
+                mat.server_id.clone(),
+                mat.resource.uri.to_string(),
+                if Self::is_text_resource(&mat.resource) {
+                    IconName::File
+                } else {
+                    IconName::Image
+                },
+            ),
+            ResourceEntry::Template {
+                server_id,
+                template,
+            } => (
+                template.name.clone(),
+                server_id.clone(),
+                template.uri_template.clone(),
+                IconName::ToolHammer,
+            ),
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+                .child(
+                    v_flex()
+                        .child(
+                            h_flex().gap_1p5().child(Label::new(name)).child(
+                                Label::new(server_id.0.to_string())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                        )
+                        .child(
+                            Label::new(detail)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        ),
+                ),
+        )
+    }
+
+    fn documentation_aside(
+        &self,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<DocumentationAside> {
+        let message: SharedString = match self.entries.get(self.selected_index)? {
+            ResourceEntry::Resource(mat) => {
+                if Self::is_text_resource(&mat.resource) {
+                    match self.previews.get(mat.resource.uri.as_str())? {
+                        ResourcePreview::Loading => "Loading…".into(),
+                        ResourcePreview::Text(text) => text.clone(),
+                        ResourcePreview::Error(error) => error.clone(),
+                    }
+                } else {
+                    let mime_type = mat
+                        .resource
+                        .mime_type
+                        .as_deref()
+                        .unwrap_or("Binary resource");
+                    if ImageFormat::from_mime_type(mime_type).is_some() {
+                        format!("{mime_type} will be attached to the message as an image.").into()
+                    } else {
+                        format!(
+                            "{mime_type} will be attached to the message if it contains text. \
+                            Otherwise only a link to it is added."
+                        )
+                        .into()
+                    }
+                }
+            }
+            ResourceEntry::Template { template, .. } => {
+                let variables =
+                    ContextServerStore::resource_template_variables(&template.uri_template);
+                let description = template.description.clone().unwrap_or_default();
+                format!(
+                    "{description}\n\nType values for {} to fill in this template.",
+                    variables.join(", ")
+                )
+                .trim()
+                .to_string()
+                .into()
+            }
+        };
+
+        Some(DocumentationAside {
+            side: DocumentationSide::Right,
+            render: Rc::new(move |_| Label::new(message.clone()).into_any_element()),
+        })
+    }
+
+    fn documentation_aside_index(&self) -> Option<usize> {
+        Some(self.selected_index)
+    }
+}
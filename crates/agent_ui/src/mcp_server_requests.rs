@@ -0,0 +1,934 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use context_server::{
+    ContextServerClientDelegate, ContextServerId,
+    types::{
+        CreateMessageRequest, CreateMessageResult, ElicitAction, ElicitRequest, ElicitResult,
+        MessageContent, ModelPreferences, PrimitiveSchemaDefinition, Role as McpRole,
+    },
+};
+use futures::{StreamExt as _, channel::oneshot, lock::Mutex};
+use gpui::{
+    AnyWindowHandle, AsyncApp, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Image,
+    ImageFormat, PromptLevel, ScrollHandle, Task, WeakEntity,
+};
+use language_model::{
+    ConfiguredModel, LanguageModelCompletionEvent, LanguageModelImage, LanguageModelRegistry,
+    LanguageModelRequest, LanguageModelRequestMessage, Role, StopReason,
+};
+use serde_json::Value;
+use ui::{
+    Banner, Checkbox, KeyBinding, Modal, ModalFooter, ModalHeader, Section, ToggleState,
+    WithScrollbar, prelude::*,
+};
+use ui_input::InputField;
+use workspace::{ModalView, Workspace};
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        let delegate = Arc::new(WorkspaceContextServerDelegate {
+            workspace: cx.weak_entity(),
+            window: window.window_handle(),
+            elicitation_queue: Arc::default(),
+        });
+        workspace
+            .project()
+            .read(cx)
+            .context_server_store()
+            .update(cx, |store, cx| store.set_client_delegate(delegate, cx));
+    })
+    .detach();
+}
+
+/// Serves sampling and elicitation requests from the context servers of a workspace,
+/// asking the user in that workspace's window.
+struct WorkspaceContextServerDelegate {
+    workspace: WeakEntity<Workspace>,
+    window: AnyWindowHandle,
+    /// Held while an elicitation modal is open, so that further requests wait their turn.
+    elicitation_queue: Arc<Mutex<()>>,
+}
+
+impl ContextServerClientDelegate for WorkspaceContextServerDelegate {
+    fn create_message(
+        &self,
+        server_id: ContextServerId,
+        request: CreateMessageRequest,
+        cx: AsyncApp,
+    ) -> Task<Result<CreateMessageResult>> {
+        let window = self.window;
+        cx.spawn(async move |cx| {
+            let configured_model = cx
+                .update(|cx| sampling_model(request.model_preferences.as_ref(), cx))
+                .context("no language model is configured")?;
+            let model_name = configured_model.model.name().0;
+
+            let message = format!("{server_id} wants to send a request to {model_name}");
+            let detail = sampling_prompt_detail(&request);
+            let answer = window.update(cx, |_, window, cx| {
+                window.prompt(
+                    PromptLevel::Info,
+                    &message,
+                    Some(&detail),
+                    &["Allow", "Deny"],
+                    cx,
+                )
+            })?;
+            anyhow::ensure!(answer.await? == 0, "the user rejected the sampling request");
+
+            let (text, stop_reason) =
+                complete_sampling_request(&configured_model, request, cx).await?;
+            Ok(CreateMessageResult {
+                role: McpRole::Assistant,
+                content: MessageContent::Text {
+                    text,
+                    annotations: None,
+                },
+                model: configured_model.model.id().0.to_string(),
+                stop_reason: Some(stop_reason.to_string()),
+            })
+        })
+    }
+
+    fn elicit(
+        &self,
+        server_id: ContextServerId,
+        request: ElicitRequest,
+        cx: AsyncApp,
+    ) -> Task<Result<ElicitResult>> {
+        let window = self.window;
+        let workspace = self.workspace.clone();
+        let elicitation_queue = self.elicitation_queue.clone();
+        cx.spawn(async move |cx| {
+            let _turn = elicitation_queue.lock().await;
+            let (response_tx, response_rx) = oneshot::channel();
+            window.update(cx, |_, window, cx| {
+                workspace.update(cx, |workspace, cx| {
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        McpElicitationModal::new(server_id, request, response_tx, window, cx)
+                    });
+                })
+            })??;
+
+            // The modal drops the sender without answering when it is dismissed.
+            Ok(response_rx.await.unwrap_or(ElicitResult {
+                action: ElicitAction::Cancel,
+                content: None,
+            }))
+        })
+    }
+}
+
+fn sampling_prompt_detail(request: &CreateMessageRequest) -> String {
+    const MAX_DETAIL_CHARS: usize = 1000;
+
+    let prompt = request
+        .system_prompt
+        .iter()
+        .map(|system_prompt| format!("System prompt: {system_prompt}"))
+        .chain(
+            request
+                .messages
+                .iter()
+                .filter_map(|message| match &message.content {
+                    MessageContent::Text { text, .. } => Some(text.clone()),
+                    _ => None,
+                }),
+        )
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    util::truncate_and_trailoff(&prompt, MAX_DETAIL_CHARS)
+}
+
+/// Picks the model that serves a sampling request: the first available model matching
+/// one of the server's hints, in order of preference, or the default model otherwise.
+fn sampling_model(preferences: Option<&ModelPreferences>, cx: &App) -> Option<ConfiguredModel> {
+    let registry = LanguageModelRegistry::read_global(cx);
+    let hints = preferences
+        .and_then(|preferences| preferences.hints.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|hint| hint.name.as_deref());
+    for hint in hints {
+        let hint = hint.to_lowercase();
+        let model = registry.available_models(cx).find(|model| {
+            model.id().0.to_lowercase().contains(&hint)
+                || model.name().0.to_lowercase().contains(&hint)
+        });
+        if let Some(model) = model
+            && let Some(provider) = registry.provider(&model.provider_id())
+        {
+            return Some(ConfiguredModel { provider, model });
+        }
+    }
+    registry.default_model()
+}
+
+/// Completes a sampling request, returning the response text and the reason it stopped.
+async fn complete_sampling_request(
+    configured_model: &ConfiguredModel,
+    request: CreateMessageRequest,
+    cx: &mut AsyncApp,
+) -> Result<(String, &'static str)> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = request.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![system_prompt.into()],
+            cache: false,
+            reasoning_details: None,
+        });
+    }
+    for message in request.messages {
+        let content = match message.content {
+            MessageContent::Text { text, .. } => text.into(),
+            MessageContent::Image {
+                data, mime_type, ..
+            } => {
+                let Some(format) = ImageFormat::from_mime_type(&mime_type) else {
+                    log::warn!("unsupported sampling image type: {mime_type}");
+                    continue;
+                };
+                let image = if format == LanguageModelImage::FORMAT {
+                    LanguageModelImage {
+                        source: data.into(),
+                        size: None,
+                    }
+                } else {
+                    let bytes =
+                        base64::Engine::decode(&base64::prelude::BASE64_STANDARD, data.as_bytes())
+                            .context("invalid base64 in sampling image")?;
+                    let image = Arc::new(Image::from_bytes(format, bytes));
+                    cx.update(|cx| LanguageModelImage::from_image(image, cx))
+                        .await
+                        .with_context(|| format!("failed to convert {mime_type} sampling image"))?
+                };
+                language_model::MessageContent::Image(image)
+            }
+            content => {
+                log::warn!("unsupported sampling message content: {content:?}");
+                continue;
+            }
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                McpRole::User => Role::User,
+                McpRole::Assistant => Role::Assistant,
+            },
+            content: vec![content],
+            cache: false,
+            reasoning_details: None,
+        });
+    }
+
+    let request = LanguageModelRequest {
+        messages,
+        stop: request.stop_sequences.unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+        max_tokens: Some(request.max_tokens as u64),
+        ..Default::default()
+    };
+    let mut events = configured_model
+        .model
+        .stream_completion(request, cx)
+        .await?;
+    let mut text = String::new();
+    let mut stop_reason = "endTurn";
+    while let Some(event) = events.next().await {
+        match event? {
+            LanguageModelCompletionEvent::Text(chunk) => text.push_str(&chunk),
+            LanguageModelCompletionEvent::Stop(StopReason::MaxTokens) => stop_reason = "maxTokens",
+            _ => {}
+        }
+    }
+    Ok((text, stop_reason))
+}
+
+enum ElicitationInput {
+    Text(Entity<InputField>),
+    Boolean(ToggleState),
+    Choice {
+        options: Vec<(String, SharedString)>,
+        selected: Option<usize>,
+    },
+}
+
+struct ElicitationField {
+    name: String,
+    schema: PrimitiveSchemaDefinition,
+    required: bool,
+    input: ElicitationInput,
+}
+
+impl ElicitationField {
+    fn title(&self) -> &str {
+        self.schema.title().unwrap_or(&self.name)
+    }
+
+    fn label(&self) -> SharedString {
+        let label = self.title();
+        if self.required {
+            format!("{label} *").into()
+        } else {
+            label.to_string().into()
+        }
+    }
+}
+
+/// Asks the user for the structured input requested by a context server.
+pub struct McpElicitationModal {
+    server_id: ContextServerId,
+    message: SharedString,
+    fields: Vec<ElicitationField>,
+    response_tx: Option<oneshot::Sender<ElicitResult>>,
+    last_error: Option<SharedString>,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+}
+
+impl McpElicitationModal {
+    fn new(
+        server_id: ContextServerId,
+        request: ElicitRequest,
+        response_tx: oneshot::Sender<ElicitResult>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let required = request.requested_schema.required;
+        let fields = request
+            .requested_schema
+            .properties
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (name, schema))| {
+                let input = match &schema {
+                    PrimitiveSchemaDefinition::String {
+                        enum_values: Some(values),
+                        enum_names,
+                        default,
+                        ..
+                    } => ElicitationInput::Choice {
+                        options: values
+                            .iter()
+                            .enumerate()
+                            .map(|(ix, value)| {
+                                let label = enum_names
+                                    .as_ref()
+                                    .and_then(|names| names.get(ix))
+                                    .unwrap_or(value);
+                                (value.clone(), label.clone().into())
+                            })
+                            .collect(),
+                        selected: default
+                            .as_ref()
+                            .and_then(|default| values.iter().position(|value| value == default)),
+                    },
+                    PrimitiveSchemaDefinition::Boolean { default, .. } => {
+                        ElicitationInput::Boolean(default.unwrap_or(false).into())
+                    }
+                    PrimitiveSchemaDefinition::String { default, .. } => {
+                        Self::text_input(&schema, default.clone(), ix, window, cx)
+                    }
+                    PrimitiveSchemaDefinition::Number { default, .. } => {
+                        Self::text_input(&schema, default.map(|n| n.to_string()), ix, window, cx)
+                    }
+                    PrimitiveSchemaDefinition::Integer { default, .. } => {
+                        Self::text_input(&schema, default.map(|n| n.to_string()), ix, window, cx)
+                    }
+                };
+                ElicitationField {
+                    required: required.contains(&name),
+                    name,
+                    schema,
+                    input,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let focus_handle = fields
+            .iter()
+            .find_map(|field| match &field.input {
+                ElicitationInput::Text(input) => Some(input.focus_handle(cx)),
+                _ => None,
+            })
+            .unwrap_or_else(|| cx.focus_handle());
+
+        Self {
+            server_id,
+            message: request.message.into(),
+            fields,
+            response_tx: Some(response_tx),
+            last_error: None,
+            focus_handle,
+            scroll_handle: ScrollHandle::new(),
+        }
+    }
+
+    fn text_input(
+        schema: &PrimitiveSchemaDefinition,
+        default: Option<String>,
+        tab_index: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> ElicitationInput {
+        let placeholder = schema.description().unwrap_or_default().to_string();
+        ElicitationInput::Text(cx.new(|cx| {
+            let input = InputField::new(window, cx, &placeholder)
+                .tab_index(tab_index as isize)
+                .tab_stop(true);
+            if let Some(default) = default {
+                input.set_text(&default, window, cx);
+            }
+            input
+        }))
+    }
+
+    fn respond(&mut self, result: ElicitResult, cx: &mut Context<Self>) {
+        if let Some(response_tx) = self.response_tx.take() {
+            response_tx.send(result).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn accept(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        let mut content = serde_json::Map::new();
+        for field in &self.fields {
+            let value = match &field.input {
+                ElicitationInput::Text(input) => {
+                    parse_field_value(&field.schema, &input.read(cx).text(cx))
+                }
+                ElicitationInput::Boolean(state) => Ok(Some(Value::Bool(state.selected()))),
+                ElicitationInput::Choice { options, selected } => Ok(selected
+                    .and_then(|ix| options.get(ix))
+                    .map(|(value, _)| Value::String(value.clone()))),
+            };
+            match value {
+                Ok(Some(value)) => {
+                    content.insert(field.name.clone(), value);
+                }
+                Ok(None) if field.required => {
+                    self.last_error = Some(format!("{} is required", field.title()).into());
+                    cx.notify();
+                    return;
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    self.last_error = Some(error);
+                    cx.notify();
+                    return;
+                }
+            }
+        }
+
+        self.respond(
+            ElicitResult {
+                action: ElicitAction::Accept,
+                content: Some(content),
+            },
+            cx,
+        );
+    }
+
+    fn decline(&mut self, cx: &mut Context<Self>) {
+        self.respond(
+            ElicitResult {
+                action: ElicitAction::Decline,
+                content: None,
+            },
+            cx,
+        );
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        self.respond(
+            ElicitResult {
+                action: ElicitAction::Cancel,
+                content: None,
+            },
+            cx,
+        );
+    }
+
+    fn on_tab(&mut self, _: &menu::SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        window.focus_next(cx);
+    }
+
+    fn on_tab_prev(
+        &mut self,
+        _: &menu::SelectPrevious,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus_prev(cx);
+    }
+
+    fn render_field(
+        &self,
+        ix: usize,
+        field: &ElicitationField,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let description = field
+            .schema
+            .description()
+            .map(|description| description.to_string());
+        match &field.input {
+            ElicitationInput::Text(input) => v_flex()
+                .gap_1()
+                .child(Label::new(field.label()).size(LabelSize::Small))
+                .child(input.clone())
+                .into_any_element(),
+            ElicitationInput::Boolean(state) => v_flex()
+                .gap_0p5()
+                .child(
+                    Checkbox::new(("elicitation-checkbox", ix), *state)
+                        .label(field.label())
+                        .on_click(cx.listener(move |this, checked: &ToggleState, _, cx| {
+                            if let Some(ElicitationInput::Boolean(state)) =
+                                this.fields.get_mut(ix).map(|field| &mut field.input)
+                            {
+                                *state = *checked;
+                            }
+                            cx.notify();
+                        })),
+                )
+                .children(description.map(|description| {
+                    Label::new(description)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                }))
+                .into_any_element(),
+            ElicitationInput::Choice { options, selected } => v_flex()
+                .gap_1()
+                .child(Label::new(field.label()).size(LabelSize::Small))
+                .children(description.map(|description| {
+                    Label::new(description)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                }))
+                .child(
+                    h_flex()
+                        .flex_wrap()
+                        .gap_1()
+                        .children(options.iter().enumerate().map(|(option_ix, (_, label))| {
+                            Button::new(
+                                SharedString::from(format!("elicitation-choice-{ix}-{option_ix}")),
+                                label.clone(),
+                            )
+                            .style(ButtonStyle::Outlined)
+                            .label_size(LabelSize::Small)
+                            .toggle_state(*selected == Some(option_ix))
+                            .on_click(cx.listener(
+                                move |this, _, _, cx| {
+                                    if let Some(ElicitationInput::Choice { selected, .. }) =
+                                        this.fields.get_mut(ix).map(|field| &mut field.input)
+                                    {
+                                        *selected = Some(option_ix);
+                                    }
+                                    cx.notify();
+                                },
+                            ))
+                        })),
+                )
+                .into_any_element(),
+        }
+    }
+}
+
+/// Parses the text entered for a field, returning `None` when it was left empty.
+fn parse_field_value(
+    schema: &PrimitiveSchemaDefinition,
+    text: &str,
+) -> Result<Option<Value>, SharedString> {
+    let name = schema.title().unwrap_or("Value");
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    match schema {
+        PrimitiveSchemaDefinition::String {
+            min_length,
+            max_length,
+            ..
+        } => {
+            let length = text.chars().count();
+            if let Some(min_length) = min_length
+                && length < *min_length
+            {
+                return Err(format!("{name} must be at least {min_length} characters").into());
+            }
+            if let Some(max_length) = max_length
+                && length > *max_length
+            {
+                return Err(format!("{name} must be at most {max_length} characters").into());
+            }
+            Ok(Some(Value::String(text.to_string())))
+        }
+        PrimitiveSchemaDefinition::Number {
+            minimum, maximum, ..
+        } => {
+            let number = text
+                .parse::<f64>()
+                .map_err(|_| SharedString::from(format!("{name} must be a number")))?;
+            if minimum.is_some_and(|minimum| number < minimum)
+                || maximum.is_some_and(|maximum| number > maximum)
+            {
+                return Err(format!("{name} is out of range").into());
+            }
+            Ok(serde_json::Number::from_f64(number).map(Value::Number))
+        }
+        PrimitiveSchemaDefinition::Integer {
+            minimum, maximum, ..
+        } => {
+            let number = text
+                .parse::<i64>()
+                .map_err(|_| SharedString::from(format!("{name} must be a whole number")))?;
+            if minimum.is_some_and(|minimum| number < minimum)
+                || maximum.is_some_and(|maximum| number > maximum)
+            {
+                return Err(format!("{name} is out of range").into());
+            }
+            Ok(Some(Value::from(number)))
+        }
+        PrimitiveSchemaDefinition::Boolean { .. } => match text {
+            "true" => Ok(Some(Value::Bool(true))),
+            "false" => Ok(Some(Value::Bool(false))),
+            _ => Err(format!("{name} must be true or false").into()),
+        },
+    }
+}
+
+impl EventEmitter<DismissEvent> for McpElicitationModal {}
+
+impl Focusable for McpElicitationModal {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl ModalView for McpElicitationModal {}
+
+impl Render for McpElicitationModal {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        let fields = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(ix, field)| self.render_field(ix, field, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("mcp-elicitation-modal")
+            .key_context("McpElicitationModal")
+            .w(rems(34.))
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::accept))
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::on_tab))
+            .on_action(cx.listener(Self::on_tab_prev))
+            .child(
+                Modal::new("mcp-elicitation", None)
+                    .header(
+                        ModalHeader::new()
+                            .headline(format!("{} Requests Input", self.server_id))
+                            .description(self.message.clone()),
+                    )
+                    .when_some(self.last_error.clone(), |this, error| {
+                        this.section(
+                            Section::new().child(
+                                Banner::new()
+                                    .severity(Severity::Warning)
+                                    .child(div().text_xs().child(error)),
+                            ),
+                        )
+                    })
+                    .child(
+                        div()
+                            .size_full()
+                            .vertical_scrollbar_for(&self.scroll_handle, window, cx)
+                            .child(
+                                v_flex()
+                                    .id("modal_content")
+                                    .size_full()
+                                    .tab_group()
+                                    .max_h(rems_from_px(450.))
+                                    .pl_3()
+                                    .pr_4()
+                                    .pb_2()
+                                    .gap_2()
+                                    .overflow_y_scroll()
+                                    .track_scroll(&self.scroll_handle)
+                                    .children(fields),
+                            ),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Button::new("decline", "Decline")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Cancel,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _event, _window, cx| {
+                                            this.decline(cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("submit", "Submit")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Confirm,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _event, window, cx| {
+                                            this.accept(&menu::Confirm, window, cx)
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language_model::{
+        LanguageModel, LanguageModelProviderId, LanguageModelProviderName,
+        fake_provider::{FakeLanguageModel, FakeLanguageModelProvider},
+    };
+    use project::Project;
+    use serde_json::json;
+    use util::path;
+    use workspace::{AppState, MultiWorkspace};
+
+    async fn init_test(
+        cx: &mut TestAppContext,
+    ) -> (WorkspaceContextServerDelegate, &mut VisualTestContext) {
+        let app_state = cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+            LanguageModelRegistry::test(cx);
+            app_state
+        });
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/project"), json!({}))
+            .await;
+        let project = Project::test(app_state.fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project, window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+        let delegate = WorkspaceContextServerDelegate {
+            workspace: workspace.downgrade(),
+            window: cx.update(|window, _| window.window_handle()),
+        };
+        (delegate, cx)
+    }
+
+    #[gpui::test]
+    async fn test_sampling_request(cx: &mut TestAppContext) {
+        let (delegate, cx) = init_test(cx).await;
+        let hinted_model = Arc::new(FakeLanguageModel::with_id_and_thinking(
+            "hinted",
+            "small-model",
+            "Small Model",
+            false,
+        ));
+        cx.update(|_, cx| {
+            let provider = FakeLanguageModelProvider::new(
+                LanguageModelProviderId::from("hinted".to_string()),
+                LanguageModelProviderName::from("Hinted".to_string()),
+            )
+            .with_models(vec![hinted_model.clone() as Arc<dyn LanguageModel>]);
+            LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+                registry.register_provider(Arc::new(provider), cx)
+            });
+        });
+
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(2, 2))
+            .write_to(&mut jpeg, image::ImageFormat::Jpeg)
+            .unwrap();
+        let request = |max_tokens: u32| -> CreateMessageRequest {
+            serde_json::from_value(json!({
+                "messages": [
+                    {"role": "user", "content": {"type": "text", "text": "Describe this image"}},
+                    {
+                        "role": "user",
+                        "content": {
+                            "type": "image",
+                            "data": base64::Engine::encode(
+                                &base64::prelude::BASE64_STANDARD,
+                                jpeg.get_ref(),
+                            ),
+                            "mimeType": "image/jpeg",
+                        },
+                    },
+                ],
+                "modelPreferences": {"hints": [{"name": "unavailable"}, {"name": "small"}]},
+                "systemPrompt": "Be brief",
+                "maxTokens": max_tokens,
+            }))
+            .unwrap()
+        };
+        let server_id = ContextServerId("mcp".into());
+
+        let task = cx
+            .update(|_, cx| delegate.create_message(server_id.clone(), request(4), cx.to_async()));
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer("Allow");
+        cx.run_until_parked();
+
+        let completions = hinted_model.pending_completions();
+        let [completion] = completions.as_slice() else {
+            panic!("expected a completion with the hinted model, got {completions:?}");
+        };
+        let roles = completion
+            .messages
+            .iter()
+            .map(|message| message.role)
+            .collect::<Vec<_>>();
+        assert_eq!(roles, [Role::System, Role::User, Role::User]);
+        let [language_model::MessageContent::Image(image)] =
+            completion.messages[2].content.as_slice()
+        else {
+            panic!("expected the JPEG image to be forwarded");
+        };
+        assert!(image.size.is_some());
+
+        hinted_model.send_last_completion_stream_text_chunk("A black square");
+        let result = task.await.unwrap();
+        assert_eq!(result.model, "small-model");
+        assert_eq!(result.stop_reason.as_deref(), Some("maxTokens"));
+        let MessageContent::Text { text, .. } = result.content else {
+            panic!("expected a text response");
+        };
+        assert_eq!(text, "A black squa");
+
+        let task = cx.update(|_, cx| {
+            delegate.create_message(server_id.clone(), request(100), cx.to_async())
+        });
+        cx.run_until_parked();
+        cx.simulate_prompt_answer("Deny");
+        assert!(task.await.is_err());
+        assert_eq!(hinted_model.pending_completions().len(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_elicitation_request(cx: &mut TestAppContext) {
+        let (delegate, cx) = init_test(cx).await;
+        let request = || -> ElicitRequest {
+            serde_json::from_value(json!({
+                "message": "Who are you?",
+                "requestedSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "title": "Name"},
+                        "subscribe": {"type": "boolean", "default": true},
+                    },
+                    "required": ["name"],
+                },
+            }))
+            .unwrap()
+        };
+        let server_id = ContextServerId("mcp".into());
+        let active_modal = |cx: &mut VisualTestContext| {
+            delegate
+                .workspace
+                .upgrade()
+                .unwrap()
+                .read_with(cx, |workspace, cx| {
+                    workspace.active_modal::<McpElicitationModal>(cx)
+                })
+        };
+
+        let task = cx.update(|_, cx| delegate.elicit(server_id.clone(), request(), cx.to_async()));
+        cx.run_until_parked();
+        let modal = active_modal(cx).expect("the elicitation modal should be open");
+        modal.update_in(cx, |modal, window, cx| {
+            modal.accept(&menu::Confirm, window, cx);
+            assert_eq!(modal.last_error.as_deref(), Some("Name is required"));
+            let ElicitationInput::Text(input) = &modal.fields[0].input else {
+                panic!("expected a text input for the name");
+            };
+            input.update(cx, |input, cx| input.set_text("Ada", window, cx));
+            modal.accept(&menu::Confirm, window, cx);
+        });
+        let result = task.await.unwrap();
+        assert_eq!(result.action, ElicitAction::Accept);
+        assert_eq!(
+            result.content.map(Value::Object),
+            Some(json!({"name": "Ada", "subscribe": true}))
+        );
+        cx.run_until_parked();
+        assert!(active_modal(cx).is_none());
+
+        let task = cx.update(|_, cx| delegate.elicit(server_id.clone(), request(), cx.to_async()));
+        cx.run_until_parked();
+        let modal = active_modal(cx).expect("the elicitation modal should be open");
+        modal.update(cx, |modal, cx| modal.decline(cx));
+        let result = task.await.unwrap();
+        assert_eq!(result.action, ElicitAction::Decline);
+        assert_eq!(result.content, None);
+    }
+
+    #[test]
+    fn test_parse_field_value() {
+        let string = PrimitiveSchemaDefinition::String {
+            title: Some("Name".into()),
+            description: None,
+            min_length: Some(2),
+            max_length: Some(4),
+            format: None,
+            enum_values: None,
+            enum_names: None,
+            default: None,
+        };
+        assert_eq!(parse_field_value(&string, "  "), Ok(None));
+        assert_eq!(
+            parse_field_value(&string, "abc"),
+            Ok(Some(Value::String("abc".into())))
+        );
+        assert_eq!(
+            parse_field_value(&string, "a"),
+            Err("Name must be at least 2 characters".into())
+        );
+
+        let integer = PrimitiveSchemaDefinition::Integer {
+            title: None,
+            description: None,
+            minimum: Some(1),
+            maximum: Some(10),
+            default: None,
+        };
+        assert_eq!(parse_field_value(&integer, "7"), Ok(Some(Value::from(7))));
+        assert_eq!(
+            parse_field_value(&integer, "1.5"),
+            Err("Value must be a whole number".into())
+        );
+        assert_eq!(
+            parse_field_value(&integer, "11"),
+            Err("Value is out of range".into())
+        );
+    }
+}
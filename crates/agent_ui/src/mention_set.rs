@@ -5,6 +5,7 @@ use agent_servers::{AgentServer, AgentServerDelegate};
 use anyhow::{Context as _, Result, anyhow};
 use assistant_slash_commands::{codeblock_fence_for_path, collect_diagnostics_output};
 use collections::{HashMap, HashSet};
+use context_server::ContextServerId;
 use editor::{
     Anchor, Editor, EditorSnapshot, ExcerptId, FoldPlaceholder, ToOffset,
     display_map::{Crease, CreaseId, CreaseMetadata, FoldId},
@@ -13,7 +14,7 @@ use editor::{
 use futures::{AsyncReadExt as _, FutureExt as _, future::Shared};
 use gpui::{
     AppContext, ClipboardEntry, Context, Empty, Entity, EntityId, Image, ImageFormat, Img,
-    SharedString, Task, WeakEntity,
+    SharedString, Subscription, Task, WeakEntity,
 };
 use http_client::{AsyncBody, HttpClientWithUrl};
 use itertools::Either;
@@ -21,7 +22,10 @@ use language::Buffer;
 use language_model::LanguageModelImage;
 use multi_buffer::MultiBufferRow;
 use postage::stream::Stream as _;
-use project::{Project, ProjectItem, ProjectPath, Worktree};
+use project::{
+    Project, ProjectItem, ProjectPath, Worktree,
+    context_server_store::{ContextServerResourceEvent, ContextServerStore},
+};
 use prompt_store::{PromptId, PromptStore};
 use rope::Point;
 use std::{
@@ -63,6 +67,8 @@ pub struct MentionSet {
     thread_store: Option<Entity<ThreadStore>>,
     prompt_store: Option<Entity<PromptStore>>,
     mentions: HashMap<CreaseId, (MentionUri, MentionTask)>,
+    /// Reloads the mentions of MCP resources when their servers report changes.
+    resource_updates: Option<Subscription>,
 }
 
 impl MentionSet {
@@ -76,6 +82,7 @@ impl MentionSet {
             thread_store,
             prompt_store,
             mentions: HashMap::default(),
+            resource_updates: None,
         }
     }
 
@@ -147,6 +154,9 @@ impl MentionSet {
                 include_errors,
                 include_warnings,
             } => self.confirm_mention_for_diagnostics(include_errors, include_warnings, cx),
+            MentionUri::McpResource { server_id, uri, .. } => {
+                self.confirm_mention_for_mcp_resource(server_id, uri, supports_images, cx)
+            }
             MentionUri::PastedImage
             | MentionUri::Selection { .. }
             | MentionUri::TerminalSelection { .. }
@@ -277,6 +287,9 @@ impl MentionSet {
                 include_errors,
                 include_warnings,
             } => self.confirm_mention_for_diagnostics(include_errors, include_warnings, cx),
+            MentionUri::McpResource { server_id, uri, .. } => {
+                self.confirm_mention_for_mcp_resource(server_id, uri, supports_images, cx)
+            }
             MentionUri::PastedImage => {
                 debug_panic!("pasted image URI should not be included in completions");
                 Task::ready(Err(anyhow!(
@@ -394,6 +407,101 @@ impl MentionSet {
         })
     }
 
+    fn confirm_mention_for_mcp_resource(
+        &mut self,
+        server_id: String,
+        uri: String,
+        supports_images: bool,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Mention>> {
+        let Some(project) = self.project.upgrade() else {
+            return Task::ready(Err(anyhow!("project not found")));
+        };
+        let uri = match url::Url::parse(&uri) {
+            Ok(uri) => uri,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        let server_id = ContextServerId(server_id.into());
+        let store = project.read(cx).context_server_store();
+        self.watch_mcp_resource(&store, &server_id, &uri, cx);
+        let read_task = store.read(cx).read_resource(&server_id, uri, cx);
+        cx.background_spawn(async move {
+            let response = read_task.await?;
+            mention_for_resource_contents(response.contents, supports_images)
+        })
+    }
+
+    /// Subscribes to changes of a mentioned MCP resource, so that the mention is
+    /// read again when its server reports that it was updated.
+    fn watch_mcp_resource(
+        &mut self,
+        store: &Entity<ContextServerStore>,
+        server_id: &ContextServerId,
+        uri: &url::Url,
+        cx: &mut Context<Self>,
+    ) {
+        if self.resource_updates.is_none() {
+            self.resource_updates = Some(cx.subscribe(
+                store,
+                |this, _, event: &ContextServerResourceEvent, cx| {
+                    if let ContextServerResourceEvent::ResourceUpdated { server_id, uri } = event {
+                        this.reload_mcp_resource_mentions(server_id, uri, cx);
+                    }
+                },
+            ));
+        }
+        if store.read(cx).is_subscribed_to_resource(server_id, uri) {
+            return;
+        }
+        let subscribe = store.update(cx, |store, cx| {
+            store.subscribe_to_resource(server_id, uri.clone(), cx)
+        });
+        cx.background_spawn(async move {
+            // Servers don't have to support subscriptions, in which case the
+            // mention keeps the contents that were read when it was inserted.
+            if let Err(error) = subscribe.await {
+                log::debug!("Not watching mentioned resource for changes: {error:#}");
+            }
+        })
+        .detach();
+    }
+
+    fn reload_mcp_resource_mentions(
+        &mut self,
+        server_id: &ContextServerId,
+        uri: &str,
+        cx: &mut Context<Self>,
+    ) {
+        let updated_mentions = self
+            .mentions
+            .iter()
+            .filter(|(_, (mention_uri, _))| {
+                matches!(
+                    mention_uri,
+                    MentionUri::McpResource { server_id: mention_server_id, uri: resource_uri, .. }
+                        if mention_server_id.as_str() == server_id.0.as_ref() && resource_uri == uri
+                )
+            })
+            .map(|(crease_id, (mention_uri, task))| {
+                // Images were only attached if the model supports them.
+                let supports_images = matches!(task.peek(), Some(Ok(Mention::Image(_))));
+                (*crease_id, mention_uri.clone(), supports_images)
+            })
+            .collect::<Vec<_>>();
+        for (crease_id, mention_uri, supports_images) in updated_mentions {
+            let task = self.confirm_mention_for_mcp_resource(
+                server_id.0.to_string(),
+                uri.to_string(),
+                supports_images,
+                cx,
+            );
+            let task = cx
+                .spawn(async move |_, _| task.await.map_err(|e| e.to_string()))
+                .shared();
+            self.mentions.insert(crease_id, (mention_uri, task));
+        }
+    }
+
     fn confirm_mention_for_symbol(
         &self,
         abs_path: PathBuf,
@@ -620,6 +728,37 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_mention_for_resource_blob() {
+        use context_server::types::{BlobResourceContents, ResourceContentsType};
+
+        let blob = |mime_type: &str, bytes: &[u8]| {
+            vec![ResourceContentsType::Blob(BlobResourceContents {
+                uri: url::Url::parse("docs://guide").unwrap(),
+                mime_type: Some(mime_type.to_string()),
+                blob: base64::Engine::encode(&base64::prelude::BASE64_STANDARD, bytes),
+            })]
+        };
+
+        let Mention::Text { content, .. } =
+            mention_for_resource_contents(blob("application/x-yaml", b"key: value"), false)
+                .unwrap()
+        else {
+            panic!("expected a blob of text to be embedded");
+        };
+        assert_eq!(content, "key: value");
+
+        assert!(matches!(
+            mention_for_resource_contents(blob("application/octet-stream", &[0xff, 0xfe]), false),
+            Ok(Mention::Link)
+        ));
+        assert!(matches!(
+            mention_for_resource_contents(blob("image/png", &[0x89, b'P']), true),
+            Ok(Mention::Image(_))
+        ));
+        assert!(mention_for_resource_contents(blob("image/png", &[0x89, b'P']), false).is_err());
+    }
+
     #[gpui::test]
     async fn test_thread_mentions_disabled(cx: &mut TestAppContext) {
         init_test(cx);
@@ -1110,6 +1249,64 @@ impl Render for ImageHover {
     }
 }
 
+/// Converts the contents of an MCP resource into a mention. Text contents, and
+/// blobs whose bytes are text, are embedded. Image blobs are attached as images,
+/// and any other blob can only be attached as a link to the resource.
+fn mention_for_resource_contents(
+    contents: Vec<context_server::types::ResourceContentsType>,
+    supports_images: bool,
+) -> Result<Mention> {
+    let mut text = String::new();
+    let mut has_blob = false;
+    for contents in contents {
+        let contents_text = match contents {
+            context_server::types::ResourceContentsType::Text(contents) => contents.text,
+            context_server::types::ResourceContentsType::Blob(contents) => {
+                let format = contents
+                    .mime_type
+                    .as_deref()
+                    .and_then(ImageFormat::from_mime_type);
+                if let Some(format) = format {
+                    if !supports_images {
+                        return Err(anyhow!("This model does not support images yet"));
+                    }
+                    return Ok(Mention::Image(MentionImage {
+                        data: contents.blob.into(),
+                        format,
+                    }));
+                }
+                let bytes = base64::Engine::decode(
+                    &base64::prelude::BASE64_STANDARD,
+                    contents.blob.as_bytes(),
+                )
+                .context("invalid base64 in resource blob")?;
+                match String::from_utf8(bytes) {
+                    Ok(contents_text) => contents_text,
+                    Err(_) => {
+                        has_blob = true;
+                        continue;
+                    }
+                }
+            }
+        };
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&contents_text);
+    }
+
+    if !text.is_empty() {
+        Ok(Mention::Text {
+            content: text,
+            tracked_buffers: Vec::new(),
+        })
+    } else if has_blob {
+        Ok(Mention::Link)
+    } else {
+        Err(anyhow!("Resource has no contents"))
+    }
+}
+
 async fn fetch_url_content(http_client: Arc<HttpClientWithUrl>, url: String) -> Result<String> {
     #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
     enum ContentType {
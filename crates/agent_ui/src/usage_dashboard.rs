@@ -0,0 +1,245 @@
+use agent::{ModelTokenUsage, TOKEN_USAGE_HISTORY_DAYS, ThreadStore};
+use agent_settings::AgentSettings;
+use chrono::Local;
+use gpui::{
+    DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle, Subscription, Window,
+    prelude::*,
+};
+use language_model::{LanguageModelProviderId, LanguageModelRegistry};
+use settings::Settings as _;
+use ui::{Modal, ModalHeader, Section, SectionHeader, prelude::*};
+use workspace::{ModalView, Workspace};
+
+use crate::agent_panel::AgentPanel;
+use crate::text_thread_editor::humanize_token_count;
+
+/// Shows the tokens used by native agent threads and their estimated cost, per provider and model.
+pub struct UsageDashboard {
+    focus_handle: FocusHandle,
+    thread_store: Entity<ThreadStore>,
+    thread: Option<Entity<agent::Thread>>,
+    scroll_handle: ScrollHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl UsageDashboard {
+    pub fn toggle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        let thread = workspace
+            .panel::<AgentPanel>(cx)
+            .and_then(|panel| panel.read(cx).active_native_agent_thread(cx));
+        workspace.toggle_modal(window, cx, |_window, cx| Self::new(thread, cx));
+    }
+
+    fn new(thread: Option<Entity<agent::Thread>>, cx: &mut Context<Self>) -> Self {
+        let thread_store = ThreadStore::global(cx);
+        let mut subscriptions = vec![cx.observe(&thread_store, |_, _, cx| cx.notify())];
+        if let Some(thread) = &thread {
+            subscriptions.push(cx.observe(thread, |_, _, cx| cx.notify()));
+        }
+        Self {
+            focus_handle: cx.focus_handle(),
+            thread_store,
+            thread,
+            scroll_handle: ScrollHandle::new(),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent)
+    }
+
+    fn render_section(
+        &self,
+        label: impl Into<SharedString>,
+        usage: Vec<ModelTokenUsage>,
+        cx: &App,
+    ) -> Section {
+        let section = Section::new().header(
+            SectionHeader::new(label)
+                .end_slot(Label::new(format_total_cost(&usage)).color(Color::Muted)),
+        );
+        if usage.is_empty() {
+            return section.child(
+                Label::new("No usage recorded.")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            );
+        }
+
+        let mut providers = Vec::<(SharedString, Vec<ModelTokenUsage>)>::new();
+        for usage in usage {
+            match providers
+                .iter_mut()
+                .find(|(provider, _)| *provider == usage.provider)
+            {
+                Some((_, models)) => models.push(usage),
+                None => providers.push((usage.provider.clone(), vec![usage])),
+            }
+        }
+
+        let registry = LanguageModelRegistry::read_global(cx);
+        section.children(providers.into_iter().map(|(provider, models)| {
+            let provider_name = registry
+                .provider(&LanguageModelProviderId(provider.clone()))
+                .map_or(provider, |provider| provider.name().0);
+            v_flex()
+                .gap_0p5()
+                .child(
+                    h_flex()
+                        .justify_between()
+                        .child(Label::new(provider_name).size(LabelSize::Small))
+                        .child(Label::new(format_total_cost(&models)).size(LabelSize::Small)),
+                )
+                .children(models.into_iter().map(|model| {
+                    let usage = model.usage;
+                    h_flex()
+                        .pl_2()
+                        .gap_2()
+                        .justify_between()
+                        .child(
+                            Label::new(model.model)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .flex_none()
+                                .child(
+                                    Label::new(format!(
+                                        "{} in · {} out · {} cache write · {} cache read",
+                                        humanize_token_count(usage.input_tokens),
+                                        humanize_token_count(usage.output_tokens),
+                                        humanize_token_count(usage.cache_creation_input_tokens),
+                                        humanize_token_count(usage.cache_read_input_tokens),
+                                    ))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                )
+                                .child(
+                                    Label::new(
+                                        model.cost.map_or("unknown cost".into(), format_cost),
+                                    )
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                ),
+                        )
+                }))
+        }))
+    }
+}
+
+/// Formats the total cost of the given usage, noting when some of it has no known pricing.
+fn format_total_cost(usage: &[ModelTokenUsage]) -> String {
+    let cost = format_cost(usage.iter().filter_map(|usage| usage.cost).sum());
+    if usage.iter().any(|usage| usage.cost.is_none()) {
+        format!("{cost} + unknown cost")
+    } else {
+        cost
+    }
+}
+
+fn format_cost(cost: f64) -> String {
+    format!("${cost:.2}")
+}
+
+/// Merges the usage of the same model.
+fn merge_usage(usage: impl IntoIterator<Item = ModelTokenUsage>) -> Vec<ModelTokenUsage> {
+    let mut merged = Vec::<ModelTokenUsage>::new();
+    for usage in usage {
+        match merged
+            .iter_mut()
+            .find(|merged| merged.is_same_model(&usage))
+        {
+            Some(merged) => merged.add(&usage),
+            None => merged.push(usage),
+        }
+    }
+    merged
+}
+
+impl ModalView for UsageDashboard {}
+
+impl Focusable for UsageDashboard {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<DismissEvent> for UsageDashboard {}
+
+impl Render for UsageDashboard {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let today = Local::now().date_naive();
+        let daily_usage = self.thread_store.read(cx).daily_token_usage();
+        let today_usage = merge_usage(
+            daily_usage
+                .iter()
+                .filter(|usage| usage.day == today)
+                .map(|usage| usage.usage.clone()),
+        );
+        let history_usage = merge_usage(daily_usage.iter().map(|usage| usage.usage.clone()));
+
+        let settings = AgentSettings::get_global(cx);
+        let budget = settings
+            .daily_budget
+            .map(|budget| {
+                format!(
+                    "{} of the {} daily budget spent today.",
+                    format_total_cost(&today_usage),
+                    format_cost(budget)
+                )
+            })
+            .into_iter()
+            .chain(settings.daily_token_budget.map(|budget| {
+                format!(
+                    "{} of the {} daily token budget used today.",
+                    humanize_token_count(
+                        today_usage
+                            .iter()
+                            .map(|usage| usage.usage.total_tokens())
+                            .sum()
+                    ),
+                    humanize_token_count(budget)
+                )
+            }))
+            .collect::<Vec<_>>();
+
+        let mut modal = Modal::new("usage-dashboard", Some(self.scroll_handle.clone())).header(
+            ModalHeader::new()
+                .headline("Token Usage")
+                .description(if budget.is_empty() {
+                    "Costs are estimated from the providers' list prices.".into()
+                } else {
+                    budget.join(" ")
+                })
+                .show_dismiss_button(true),
+        );
+        if let Some(thread) = &self.thread {
+            modal = modal.section(self.render_section(
+                "This Thread",
+                thread.read(cx).model_token_usage().to_vec(),
+                cx,
+            ));
+        }
+        modal = modal
+            .section(self.render_section("Today", today_usage, cx))
+            .section(self.render_section(
+                format!("Last {TOKEN_USAGE_HISTORY_DAYS} Days"),
+                history_usage,
+                cx,
+            ));
+
+        div()
+            .key_context("UsageDashboard")
+            .occlude()
+            .elevation_3(cx)
+            .w(rems(40.))
+            .max_h(rems(40.))
+            .on_action(cx.listener(Self::cancel))
+            .track_focus(&self.focus_handle)
+            .child(modal)
+    }
+}
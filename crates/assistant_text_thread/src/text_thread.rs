@@ -2275,6 +2275,7 @@ impl TextThread {
             temperature: model.and_then(|model| AgentSettings::temperature_for_model(model, cx)),
             thinking_allowed: true,
             thinking_effort: None,
+            max_tokens: None,
         };
         for message in self.messages(cx) {
             if message.status != MessageStatus::Done {
@@ -0,0 +1,123 @@
+use crate::TestServer;
+use call::ActiveCall;
+use collab_ui::{
+    session_playback::SessionPlayback,
+    session_recording::{SessionEvent, SessionRecorder},
+};
+use gpui::{AppContext as _, BackgroundExecutor, TestAppContext};
+use serde_json::json;
+use std::time::Duration;
+use util::{path, rel_path::rel_path};
+
+#[gpui::test]
+async fn test_recording_and_replaying_session(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            path!("/dir"),
+            json!({ "a.txt": "one two three", "b.txt": "five" }),
+        )
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project(path!("/dir"), cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let recorder = cx_a.new(|cx| SessionRecorder::new(project_a.clone(), project_id, cx));
+
+    // The guest opens and edits a buffer, one second apart.
+    executor.advance_clock(Duration::from_secs(1));
+    let buffer_b = project_b
+        .update(cx_b, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("a.txt")), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    executor.advance_clock(Duration::from_secs(1));
+    buffer_b.update(cx_b, |buffer, cx| buffer.edit([(0..3, "ONE")], None, cx));
+    executor.run_until_parked();
+
+    executor.advance_clock(Duration::from_secs(1));
+    buffer_b.update(cx_b, |buffer, cx| {
+        buffer.edit([(13..13, " four")], None, cx)
+    });
+    executor.run_until_parked();
+
+    // The host edits a buffer as soon as it opens it, before its state is recorded.
+    let buffer_a = project_a
+        .update(cx_a, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("b.txt")), cx)
+        })
+        .await
+        .unwrap();
+    buffer_a.update(cx_a, |buffer, cx| buffer.edit([(0..0, "four ")], None, cx));
+    executor.run_until_parked();
+
+    let recording = recorder.update(cx_a, |recorder, _| recorder.finish());
+    let buffer_a_id = buffer_a.read_with(cx_a, |buffer, _| buffer.remote_id().to_proto());
+    let buffer_a_events = recording
+        .events
+        .iter()
+        .filter_map(|event| match &event.event {
+            SessionEvent::BufferOpened { buffer_id, .. } if *buffer_id == buffer_a_id => {
+                Some("opened")
+            }
+            SessionEvent::BufferOperation { buffer_id, .. } if *buffer_id == buffer_a_id => {
+                Some("operation")
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(buffer_a_events.first(), Some(&"opened"));
+    assert!(buffer_a_events.contains(&"operation"));
+    let logins = recording
+        .events
+        .iter()
+        .filter_map(|event| match &event.event {
+            SessionEvent::ParticipantJoined { participant } => {
+                Some(participant.github_login.clone())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(logins, ["user_a", "user_b"]);
+
+    // Round-trip the recording through its file format before replaying it.
+    let recording = serde_json::from_str(&serde_json::to_string(&recording).unwrap()).unwrap();
+    let languages = client_a.language_registry().clone();
+    let playback = cx_a.new(|_| SessionPlayback::new(recording, languages));
+    let text_at = |position_ms: u64, cx: &mut TestAppContext| {
+        playback.update(cx, |playback, cx| {
+            playback.seek(position_ms, cx);
+            playback
+                .buffers()
+                .first()
+                .map(|buffer| buffer.buffer.read(cx).text())
+        })
+    };
+
+    assert_eq!(text_at(500, cx_a), None);
+    assert_eq!(text_at(3500, cx_a).as_deref(), Some("ONE two three four"));
+    assert_eq!(text_at(2500, cx_a).as_deref(), Some("ONE two three"));
+    assert_eq!(text_at(1500, cx_a).as_deref(), Some("one two three"));
+    playback.read_with(cx_a, |playback, _| {
+        assert!(playback.buffers()[0].path.ends_with("a.txt"));
+    });
+}
@@ -3,6 +3,8 @@ pub mod collab_panel;
 pub mod notification_panel;
 pub mod notifications;
 mod panel_settings;
+pub mod session_playback;
+pub mod session_recording;
 
 use std::{rc::Rc, sync::Arc};
 
@@ -22,6 +24,7 @@ pub fn init(app_state: &Arc<AppState>, cx: &mut App) {
     collab_panel::init(cx);
     notification_panel::init(cx);
     notifications::init(app_state, cx);
+    session_recording::init(cx);
     title_bar::init(cx);
 }
 
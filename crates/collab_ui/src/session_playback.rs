@@ -0,0 +1,552 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use client::{Collaborator, ParticipantIndex};
+use collections::HashMap;
+use editor::{CollaborationHub, Editor};
+use gpui::{
+    App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable, Render,
+    SharedString, Subscription, Task, Window,
+};
+use language::{Buffer, Capability, LanguageRegistry, ReplicaId};
+use rpc::proto::{self, PeerId};
+use ui::{Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::item::{Item, ItemEvent};
+
+use crate::session_recording::{RecordedParticipant, SessionEvent, SessionRecording};
+
+const PLAYBACK_TICK: Duration = Duration::from_millis(100);
+const TIMELINE_SEGMENTS: usize = 100;
+
+pub struct PlaybackBuffer {
+    pub buffer_id: u64,
+    pub path: SharedString,
+    pub buffer: Entity<Buffer>,
+}
+
+/// Replays a [`SessionRecording`] into read-only buffers up to a position in time.
+pub struct SessionPlayback {
+    recording: Arc<SessionRecording>,
+    languages: Arc<LanguageRegistry>,
+    position_ms: u64,
+    next_event_ix: usize,
+    buffers: Vec<PlaybackBuffer>,
+    pending_operations: HashMap<u64, Vec<proto::Operation>>,
+    participants: HashMap<PeerId, RecordedParticipant>,
+    collaborators: HashMap<PeerId, Collaborator>,
+    participant_indices: HashMap<u64, ParticipantIndex>,
+    follows: Vec<(PeerId, PeerId)>,
+    last_active_buffer_id: Option<u64>,
+}
+
+pub enum SessionPlaybackEvent {
+    /// The buffers were recreated to replay the recording from its start.
+    Rewound,
+    Advanced,
+}
+
+impl EventEmitter<SessionPlaybackEvent> for SessionPlayback {}
+
+impl SessionPlayback {
+    pub fn new(recording: SessionRecording, languages: Arc<LanguageRegistry>) -> Self {
+        Self {
+            recording: Arc::new(recording),
+            languages,
+            position_ms: 0,
+            next_event_ix: 0,
+            buffers: Vec::new(),
+            pending_operations: HashMap::default(),
+            participants: HashMap::default(),
+            collaborators: HashMap::default(),
+            participant_indices: HashMap::default(),
+            follows: Vec::new(),
+            last_active_buffer_id: None,
+        }
+    }
+
+    pub fn recording(&self) -> &SessionRecording {
+        &self.recording
+    }
+
+    pub fn position_ms(&self) -> u64 {
+        self.position_ms
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.recording.duration_ms()
+    }
+
+    pub fn buffers(&self) -> &[PlaybackBuffer] {
+        &self.buffers
+    }
+
+    pub fn buffer(&self, buffer_id: u64) -> Option<&PlaybackBuffer> {
+        self.buffers
+            .iter()
+            .find(|buffer| buffer.buffer_id == buffer_id)
+    }
+
+    /// The buffer that most recently changed, which the playback follows by default.
+    pub fn last_active_buffer_id(&self) -> Option<u64> {
+        self.last_active_buffer_id
+    }
+
+    /// Who follows whom at the current position, as `(leader, follower)` logins.
+    pub fn follows(&self) -> Vec<(SharedString, SharedString)> {
+        self.follows
+            .iter()
+            .map(|(leader, follower)| (self.login(*leader), self.login(*follower)))
+            .collect()
+    }
+
+    fn login(&self, peer_id: PeerId) -> SharedString {
+        self.participants
+            .get(&peer_id)
+            .map(|participant| SharedString::from(participant.github_login.clone()))
+            .unwrap_or_else(|| "unknown".into())
+    }
+
+    /// Replays the recording up to `position_ms`, rewinding first if it lies before the current position.
+    pub fn seek(&mut self, position_ms: u64, cx: &mut Context<Self>) {
+        let position_ms = position_ms.min(self.duration_ms());
+        let rewound = position_ms < self.position_ms;
+        if rewound {
+            self.position_ms = 0;
+            self.next_event_ix = 0;
+            self.buffers.clear();
+            self.pending_operations.clear();
+            self.participants.clear();
+            self.collaborators.clear();
+            self.participant_indices.clear();
+            self.follows.clear();
+            self.last_active_buffer_id = None;
+        }
+
+        let recording = self.recording.clone();
+        while let Some(event) = recording.events.get(self.next_event_ix)
+            && event.offset_ms <= position_ms
+        {
+            self.apply(event.event.clone(), cx);
+            self.next_event_ix += 1;
+        }
+        self.position_ms = position_ms;
+
+        cx.emit(if rewound {
+            SessionPlaybackEvent::Rewound
+        } else {
+            SessionPlaybackEvent::Advanced
+        });
+        cx.notify();
+    }
+
+    fn apply(&mut self, event: SessionEvent, cx: &mut Context<Self>) {
+        match event {
+            SessionEvent::ParticipantJoined { participant } => {
+                let next_index = self.participant_indices.len() as u32;
+                self.participant_indices
+                    .entry(participant.user_id)
+                    .or_insert(ParticipantIndex(next_index));
+                self.collaborators.insert(
+                    participant.peer_id,
+                    Collaborator {
+                        peer_id: participant.peer_id,
+                        replica_id: ReplicaId::new(participant.replica_id),
+                        user_id: participant.user_id,
+                        is_host: participant.is_host,
+                        committer_name: None,
+                        committer_email: None,
+                    },
+                );
+                self.participants.insert(participant.peer_id, participant);
+            }
+            SessionEvent::ParticipantLeft { peer_id } => {
+                self.collaborators.remove(&peer_id);
+                self.follows
+                    .retain(|(leader, follower)| *leader != peer_id && *follower != peer_id);
+            }
+            SessionEvent::BufferOpened {
+                buffer_id,
+                path,
+                state,
+                mut operations,
+            } => {
+                if self.buffer(buffer_id).is_some() {
+                    return;
+                }
+                // Replay as a replica no recorded participant uses, so everyone's selections are shown.
+                let replica_id = ReplicaId::new(u16::MAX);
+                let Some(buffer) =
+                    Buffer::from_proto(replica_id, Capability::ReadOnly, state, None).log_err()
+                else {
+                    return;
+                };
+                operations.extend(
+                    self.pending_operations
+                        .remove(&buffer_id)
+                        .unwrap_or_default(),
+                );
+                let buffer = cx.new(|cx| {
+                    let mut buffer = buffer;
+                    buffer.apply_ops(
+                        operations.into_iter().filter_map(|operation| {
+                            language::proto::deserialize_operation(operation).log_err()
+                        }),
+                        cx,
+                    );
+                    buffer
+                });
+                let path = path.unwrap_or_else(|| format!("untitled-{buffer_id}"));
+                self.detect_language(&buffer, PathBuf::from(&path), cx);
+                self.buffers.push(PlaybackBuffer {
+                    buffer_id,
+                    path: path.into(),
+                    buffer,
+                });
+                self.last_active_buffer_id.get_or_insert(buffer_id);
+            }
+            SessionEvent::BufferOperation {
+                buffer_id,
+                operation,
+            } => {
+                let Some(playback_buffer) = self.buffer(buffer_id) else {
+                    self.pending_operations
+                        .entry(buffer_id)
+                        .or_default()
+                        .push(operation);
+                    return;
+                };
+                let buffer = playback_buffer.buffer.clone();
+                if let Some(operation) = language::proto::deserialize_operation(operation).log_err()
+                {
+                    buffer.update(cx, |buffer, cx| buffer.apply_ops([operation], cx));
+                }
+                self.last_active_buffer_id = Some(buffer_id);
+            }
+            SessionEvent::FollowStarted { leader, follower } => {
+                self.follows.push((leader, follower));
+            }
+            SessionEvent::FollowStopped { leader, follower } => {
+                self.follows.retain(|follow| *follow != (leader, follower));
+            }
+        }
+    }
+
+    fn detect_language(&self, buffer: &Entity<Buffer>, path: PathBuf, cx: &mut Context<Self>) {
+        let languages = self.languages.clone();
+        let buffer = buffer.downgrade();
+        cx.spawn(async move |_, cx| {
+            let language = languages.load_language_for_file_path(&path).await.ok()?;
+            buffer
+                .update(cx, |buffer, cx| buffer.set_language(Some(language), cx))
+                .ok()
+        })
+        .detach();
+    }
+}
+
+struct PlaybackCollaborationHub(Entity<SessionPlayback>);
+
+impl CollaborationHub for PlaybackCollaborationHub {
+    fn collaborators<'a>(&self, cx: &'a App) -> &'a HashMap<PeerId, Collaborator> {
+        &self.0.read(cx).collaborators
+    }
+
+    fn user_participant_indices<'a>(&self, cx: &'a App) -> &'a HashMap<u64, ParticipantIndex> {
+        &self.0.read(cx).participant_indices
+    }
+
+    fn user_names(&self, cx: &App) -> HashMap<u64, SharedString> {
+        self.0
+            .read(cx)
+            .participants
+            .values()
+            .map(|participant| {
+                (
+                    participant.user_id,
+                    SharedString::from(participant.github_login.clone()),
+                )
+            })
+            .collect()
+    }
+}
+
+/// A read-only view replaying a recorded collaboration session, with a timeline to scrub through it.
+pub struct SessionPlaybackView {
+    playback: Entity<SessionPlayback>,
+    active_buffer_id: Option<u64>,
+    follow_activity: bool,
+    editor: Option<Entity<Editor>>,
+    playing: Option<Task<()>>,
+    focus_handle: FocusHandle,
+    _subscription: Subscription,
+}
+
+impl SessionPlaybackView {
+    pub fn new(
+        recording: SessionRecording,
+        languages: Arc<LanguageRegistry>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let playback = cx.new(|_| SessionPlayback::new(recording, languages));
+        let subscription = cx.subscribe_in(&playback, window, |this, _, event, window, cx| {
+            if let SessionPlaybackEvent::Rewound = event {
+                this.editor = None;
+            }
+            this.update_editor(window, cx);
+        });
+        let mut this = Self {
+            playback,
+            active_buffer_id: None,
+            follow_activity: true,
+            editor: None,
+            playing: None,
+            focus_handle: cx.focus_handle(),
+            _subscription: subscription,
+        };
+        this.playback
+            .update(cx, |playback, cx| playback.seek(0, cx));
+        this.update_editor(window, cx);
+        this
+    }
+
+    fn update_editor(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let playback = self.playback.read(cx);
+        if self.follow_activity {
+            self.active_buffer_id = playback.last_active_buffer_id();
+        }
+        let buffer = self
+            .active_buffer_id
+            .and_then(|buffer_id| playback.buffer(buffer_id))
+            .map(|playback_buffer| playback_buffer.buffer.clone());
+        let Some(buffer) = buffer else {
+            self.editor = None;
+            cx.notify();
+            return;
+        };
+        let is_current = self.editor.as_ref().is_some_and(|editor| {
+            editor.read(cx).buffer().read(cx).as_singleton().as_ref() == Some(&buffer)
+        });
+        if !is_current {
+            let hub = PlaybackCollaborationHub(self.playback.clone());
+            self.editor = Some(cx.new(|cx| {
+                let mut editor = Editor::for_buffer(buffer, None, window, cx);
+                editor.set_read_only(true);
+                editor.set_collaboration_hub(Box::new(hub));
+                editor
+            }));
+        }
+        cx.notify();
+    }
+
+    fn select_buffer(&mut self, buffer_id: u64, window: &mut Window, cx: &mut Context<Self>) {
+        self.follow_activity = false;
+        self.active_buffer_id = Some(buffer_id);
+        self.update_editor(window, cx);
+    }
+
+    fn toggle_follow_activity(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.follow_activity = !self.follow_activity;
+        self.update_editor(window, cx);
+    }
+
+    fn seek(&mut self, position_ms: u64, cx: &mut Context<Self>) {
+        self.playback
+            .update(cx, |playback, cx| playback.seek(position_ms, cx));
+    }
+
+    fn toggle_playing(&mut self, cx: &mut Context<Self>) {
+        if self.playing.take().is_some() {
+            cx.notify();
+            return;
+        }
+        let playback = self.playback.clone();
+        if playback.read(cx).position_ms() >= playback.read(cx).duration_ms() {
+            self.seek(0, cx);
+        }
+        self.playing = Some(cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(PLAYBACK_TICK).await;
+                let finished = playback.update(cx, |playback, cx| {
+                    playback.seek(
+                        playback.position_ms() + PLAYBACK_TICK.as_millis() as u64,
+                        cx,
+                    );
+                    playback.position_ms() >= playback.duration_ms()
+                });
+                if finished {
+                    break;
+                }
+            }
+            this.update(cx, |this, cx| {
+                this.playing = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn render_timeline(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let playback = self.playback.read(cx);
+        let duration_ms = playback.duration_ms().max(1);
+        let position_ms = playback.position_ms();
+        let mut has_events = [false; TIMELINE_SEGMENTS];
+        for event in &playback.recording().events {
+            let segment = (event.offset_ms * TIMELINE_SEGMENTS as u64 / duration_ms) as usize;
+            has_events[segment.min(TIMELINE_SEGMENTS - 1)] = true;
+        }
+
+        h_flex()
+            .flex_1()
+            .h_4()
+            .children((0..TIMELINE_SEGMENTS).map(|segment| {
+                let segment_start_ms = segment as u64 * duration_ms / TIMELINE_SEGMENTS as u64;
+                let played = segment_start_ms <= position_ms;
+                let color = match (played, has_events[segment]) {
+                    (true, true) => cx.theme().colors().text_accent,
+                    (true, false) => cx.theme().colors().element_selected,
+                    (false, true) => cx.theme().colors().text_muted,
+                    (false, false) => cx.theme().colors().element_background,
+                };
+                div()
+                    .id(segment)
+                    .flex_1()
+                    .h_full()
+                    .bg(color)
+                    .cursor_pointer()
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.seek(segment_start_ms, cx);
+                    }))
+            }))
+    }
+}
+
+fn format_offset(offset_ms: u64) -> String {
+    let seconds = offset_ms / 1000;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl EventEmitter<()> for SessionPlaybackView {}
+
+impl Focusable for SessionPlaybackView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for SessionPlaybackView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let playback = self.playback.read(cx);
+        let position = format_offset(playback.position_ms());
+        let duration = format_offset(playback.duration_ms());
+        let follows = playback
+            .follows()
+            .into_iter()
+            .map(|(leader, follower)| format!("{follower} is following {leader}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let tabs = playback
+            .buffers()
+            .iter()
+            .map(|buffer| (buffer.buffer_id, buffer.path.clone()))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .key_context("SessionPlayback")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .p_1()
+                    .gap_1()
+                    .flex_wrap()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        Button::new("follow-activity", "Follow Activity")
+                            .toggle_state(self.follow_activity)
+                            .tooltip(Tooltip::text("Show the buffer that changed most recently"))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_follow_activity(window, cx)
+                            })),
+                    )
+                    .children(tabs.into_iter().map(|(buffer_id, path)| {
+                        Button::new(SharedString::from(format!("buffer-{buffer_id}")), path)
+                            .toggle_state(self.active_buffer_id == Some(buffer_id))
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.select_buffer(buffer_id, window, cx)
+                            }))
+                    })),
+            )
+            .child(div().flex_1().min_h_0().map(|this| {
+                match &self.editor {
+                    Some(editor) => this.child(editor.clone()),
+                    None => this.child(
+                        h_flex()
+                            .size_full()
+                            .justify_center()
+                            .child(Label::new("No buffers were opened yet").color(Color::Muted)),
+                    ),
+                }
+            }))
+            .child(
+                h_flex()
+                    .p_1()
+                    .gap_2()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        IconButton::new(
+                            "toggle-playing",
+                            if self.playing.is_some() {
+                                IconName::DebugPause
+                            } else {
+                                IconName::PlayFilled
+                            },
+                        )
+                        .tooltip(Tooltip::text(if self.playing.is_some() {
+                            "Pause"
+                        } else {
+                            "Play"
+                        }))
+                        .on_click(cx.listener(|this, _, _, cx| this.toggle_playing(cx))),
+                    )
+                    .child(
+                        Label::new(format!("{position} / {duration}"))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(self.render_timeline(cx)),
+            )
+            .when(!follows.is_empty(), |this| {
+                this.child(
+                    h_flex().px_2().pb_1().child(
+                        Label::new(follows)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+                )
+            })
+    }
+}
+
+impl Item for SessionPlaybackView {
+    type Event = ();
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::PlayOutlined))
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        let started_at = self.playback.read(cx).recording().started_at;
+        format!("Session {}", started_at.format("%Y-%m-%d %H:%M")).into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn to_item_events(_event: &Self::Event, _f: &mut dyn FnMut(ItemEvent)) {}
+}
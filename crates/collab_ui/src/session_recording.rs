@@ -0,0 +1,466 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use anyhow::{Context as _, Result};
+use call::{ActiveCall, Room};
+use chrono::{DateTime, Utc};
+use collections::{HashMap, HashSet};
+use gpui::{
+    App, AppContext as _, BackgroundExecutor, Context, Entity, Global, PathPromptOptions,
+    Subscription, Window, actions,
+};
+use language::{Buffer, BufferEvent, BufferId, File as _};
+use project::{Fs, Project, buffer_store::BufferStoreEvent};
+use rpc::proto::{self, PeerId};
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+use workspace::{AppState, OpenOptions, Toast, Workspace, notifications::NotificationId};
+
+use crate::session_playback::SessionPlaybackView;
+
+actions!(
+    collab,
+    [
+        /// Starts recording the collaboration session on the current shared project,
+        /// or stops the recording in progress and saves it.
+        ToggleSessionRecording,
+        /// Opens a recorded collaboration session for playback.
+        OpenSessionRecording,
+    ]
+);
+
+const RECORDING_VERSION: u32 = 1;
+
+pub fn init(cx: &mut App) {
+    cx.set_global(ActiveSessionRecording(None));
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleSessionRecording, _, cx| {
+            toggle_session_recording(workspace, cx);
+        });
+        workspace.register_action(|workspace, _: &OpenSessionRecording, window, cx| {
+            prompt_for_session_recording(workspace, window, cx);
+        });
+    })
+    .detach();
+}
+
+/// A recorded collaboration session on a shared project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecording {
+    pub version: u32,
+    pub project_id: u64,
+    pub started_at: DateTime<Utc>,
+    pub events: Vec<RecordedEvent>,
+}
+
+impl SessionRecording {
+    /// The time between the start of the recording and its last event, in milliseconds.
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map_or(0, |event| event.offset_ms)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Milliseconds since the start of the recording.
+    pub offset_ms: u64,
+    pub event: SessionEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedParticipant {
+    pub peer_id: PeerId,
+    pub replica_id: u16,
+    pub user_id: u64,
+    pub github_login: String,
+    pub is_host: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionEvent {
+    ParticipantJoined {
+        participant: RecordedParticipant,
+    },
+    ParticipantLeft {
+        peer_id: PeerId,
+    },
+    /// A buffer became part of the session, along with the operations that produced its contents.
+    BufferOpened {
+        buffer_id: u64,
+        path: Option<String>,
+        state: proto::BufferState,
+        operations: Vec<proto::Operation>,
+    },
+    /// An edit, selection change or other operation on a buffer, made by any participant.
+    BufferOperation {
+        buffer_id: u64,
+        operation: proto::Operation,
+    },
+    FollowStarted {
+        leader: PeerId,
+        follower: PeerId,
+    },
+    FollowStopped {
+        leader: PeerId,
+        follower: PeerId,
+    },
+}
+
+struct ActiveSessionRecording(Option<Entity<SessionRecorder>>);
+
+impl Global for ActiveSessionRecording {}
+
+/// Records the buffer operations, participants and follows of a shared project.
+pub struct SessionRecorder {
+    project: Entity<Project>,
+    project_id: u64,
+    started_at: DateTime<Utc>,
+    executor: BackgroundExecutor,
+    start: Instant,
+    events: Vec<RecordedEvent>,
+    recorded_buffers: HashSet<BufferId>,
+    /// Operations on buffers whose initial state is still being serialized, which are
+    /// recorded after it so that they're replayed in order.
+    opening_buffers: HashMap<BufferId, Vec<proto::Operation>>,
+    participants: HashSet<PeerId>,
+    follows: HashSet<(PeerId, PeerId)>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SessionRecorder {
+    pub fn new(project: Entity<Project>, project_id: u64, cx: &mut Context<Self>) -> Self {
+        let buffer_store = project.read(cx).buffer_store().clone();
+        let room = ActiveCall::global(cx).read(cx).room().cloned();
+        let mut subscriptions = vec![
+            cx.subscribe(&project, Self::handle_project_event),
+            cx.subscribe(&buffer_store, |this, _, event, cx| {
+                if let BufferStoreEvent::BufferAdded(buffer) = event {
+                    this.record_buffer(buffer.clone(), cx);
+                }
+            }),
+        ];
+        if let Some(room) = &room {
+            subscriptions.push(cx.observe(room, |this, room, cx| this.record_follows(&room, cx)));
+        }
+
+        let mut this = Self {
+            project,
+            project_id,
+            started_at: Utc::now(),
+            executor: cx.background_executor().clone(),
+            start: cx.background_executor().now(),
+            events: Vec::new(),
+            recorded_buffers: HashSet::default(),
+            opening_buffers: HashMap::default(),
+            participants: HashSet::default(),
+            follows: HashSet::default(),
+            _subscriptions: subscriptions,
+        };
+        this.record_participants(cx);
+        for buffer in buffer_store.read(cx).buffers().collect::<Vec<_>>() {
+            this.record_buffer(buffer, cx);
+        }
+        if let Some(room) = room {
+            this.record_follows(&room, cx);
+        }
+        this
+    }
+
+    /// Ends the recording, returning everything recorded so far.
+    pub fn finish(&mut self) -> SessionRecording {
+        SessionRecording {
+            version: RECORDING_VERSION,
+            project_id: self.project_id,
+            started_at: self.started_at,
+            events: std::mem::take(&mut self.events),
+        }
+    }
+
+    fn push(&mut self, event: SessionEvent) {
+        self.events.push(RecordedEvent {
+            offset_ms: (self.executor.now() - self.start).as_millis() as u64,
+            event,
+        });
+    }
+
+    fn handle_project_event(
+        &mut self,
+        _: Entity<Project>,
+        event: &project::Event,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            project::Event::CollaboratorJoined(peer_id) => {
+                self.record_collaborator(*peer_id, cx);
+            }
+            project::Event::CollaboratorLeft(peer_id) => {
+                self.record_participant_left(*peer_id);
+            }
+            project::Event::CollaboratorUpdated {
+                old_peer_id,
+                new_peer_id,
+            } => {
+                self.record_participant_left(*old_peer_id);
+                self.record_collaborator(*new_peer_id, cx);
+            }
+            _ => {}
+        }
+    }
+
+    fn record_participants(&mut self, cx: &mut Context<Self>) {
+        let project = self.project.read(cx);
+        let client = project.client();
+        if let Some(peer_id) = client.peer_id()
+            && let Some(user) = project.user_store().read(cx).current_user()
+        {
+            let participant = RecordedParticipant {
+                peer_id,
+                replica_id: project.replica_id().as_u16(),
+                user_id: user.id,
+                github_login: user.github_login.to_string(),
+                is_host: !project.is_via_collab(),
+            };
+            self.participants.insert(peer_id);
+            self.push(SessionEvent::ParticipantJoined { participant });
+        }
+
+        let peer_ids = project.collaborators().keys().copied().collect::<Vec<_>>();
+        for peer_id in peer_ids {
+            self.record_collaborator(peer_id, cx);
+        }
+    }
+
+    fn record_collaborator(&mut self, peer_id: PeerId, cx: &mut Context<Self>) {
+        let project = self.project.read(cx);
+        let Some(collaborator) = project.collaborators().get(&peer_id) else {
+            return;
+        };
+        let github_login = project
+            .user_store()
+            .read(cx)
+            .get_cached_user(collaborator.user_id)
+            .map(|user| user.github_login.to_string())
+            .unwrap_or_default();
+        let participant = RecordedParticipant {
+            peer_id,
+            replica_id: collaborator.replica_id.as_u16(),
+            user_id: collaborator.user_id,
+            github_login,
+            is_host: collaborator.is_host,
+        };
+        if self.participants.insert(peer_id) {
+            self.push(SessionEvent::ParticipantJoined { participant });
+        }
+    }
+
+    fn record_participant_left(&mut self, peer_id: PeerId) {
+        if self.participants.remove(&peer_id) {
+            self.push(SessionEvent::ParticipantLeft { peer_id });
+        }
+    }
+
+    fn record_buffer(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if !self.recorded_buffers.insert(buffer_id) {
+            return;
+        }
+
+        self._subscriptions
+            .push(cx.subscribe(&buffer, move |this, _, event, _| {
+                if let BufferEvent::Operation { operation, .. } = event {
+                    let operation = language::proto::serialize_operation(operation);
+                    if let Some(operations) = this.opening_buffers.get_mut(&buffer_id) {
+                        operations.push(operation);
+                    } else {
+                        this.push(SessionEvent::BufferOperation {
+                            buffer_id: buffer_id.to_proto(),
+                            operation,
+                        });
+                    }
+                }
+            }));
+        self.opening_buffers.insert(buffer_id, Vec::new());
+
+        let (path, state, operations) = buffer.update(cx, |buffer, cx| {
+            let path = buffer
+                .file()
+                .map(|file| file.full_path(cx).to_string_lossy().into_owned());
+            (path, buffer.to_proto(cx), buffer.serialize_ops(None, cx))
+        });
+        cx.spawn(async move |this, cx| {
+            let operations = operations.await;
+            this.update(cx, |this, _| {
+                this.push(SessionEvent::BufferOpened {
+                    buffer_id: buffer_id.to_proto(),
+                    path,
+                    state,
+                    operations,
+                });
+                for operation in this.opening_buffers.remove(&buffer_id).unwrap_or_default() {
+                    this.push(SessionEvent::BufferOperation {
+                        buffer_id: buffer_id.to_proto(),
+                        operation,
+                    });
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn record_follows(&mut self, room: &Entity<Room>, cx: &mut Context<Self>) {
+        let room = room.read(cx);
+        let follows = self
+            .participants
+            .iter()
+            .flat_map(|&leader| {
+                room.followers_for(leader, self.project_id)
+                    .iter()
+                    .map(move |&follower| (leader, follower))
+            })
+            .collect::<HashSet<_>>();
+        let started = follows
+            .difference(&self.follows)
+            .copied()
+            .collect::<Vec<_>>();
+        let stopped = self
+            .follows
+            .difference(&follows)
+            .copied()
+            .collect::<Vec<_>>();
+        for (leader, follower) in started {
+            self.push(SessionEvent::FollowStarted { leader, follower });
+        }
+        for (leader, follower) in stopped {
+            self.push(SessionEvent::FollowStopped { leader, follower });
+        }
+        self.follows = follows;
+    }
+}
+
+fn toggle_session_recording(workspace: &mut Workspace, cx: &mut Context<Workspace>) {
+    let notification_id = NotificationId::unique::<SessionRecorder>();
+    if let Some(recorder) = cx.global_mut::<ActiveSessionRecording>().0.take() {
+        let recording = recorder.update(cx, |recorder, _| recorder.finish());
+        let app_state = workspace.app_state().clone();
+        cx.spawn(async move |workspace, cx| {
+            let path = save_session_recording(&recording, app_state.fs.as_ref()).await?;
+            workspace.update(cx, |workspace, cx| {
+                workspace.show_toast(
+                    Toast::new(
+                        notification_id,
+                        format!("Session recording saved to {}", path.display()),
+                    )
+                    .on_click("Play Recording", move |_, cx| {
+                        open_session_recording(path.clone(), app_state.clone(), cx)
+                            .detach_and_log_err(cx);
+                    }),
+                    cx,
+                )
+            })
+        })
+        .detach_and_log_err(cx);
+        return;
+    }
+
+    let project = workspace.project().clone();
+    let Some(project_id) = project.read(cx).remote_id() else {
+        workspace.show_toast(
+            Toast::new(
+                notification_id,
+                "Share or join a project to record a collaboration session.",
+            )
+            .autohide(),
+            cx,
+        );
+        return;
+    };
+    let recorder = cx.new(|cx| SessionRecorder::new(project, project_id, cx));
+    cx.global_mut::<ActiveSessionRecording>().0 = Some(recorder);
+    workspace.show_toast(
+        Toast::new(notification_id, "Recording the collaboration session.").autohide(),
+        cx,
+    );
+}
+
+async fn save_session_recording(recording: &SessionRecording, fs: &dyn Fs) -> Result<PathBuf> {
+    let dir = paths::session_recordings_dir();
+    fs.create_dir(dir).await?;
+    let mut path = dir.join(format!(
+        "{}.json",
+        recording.started_at.format("%Y-%m-%d-%H-%M-%S")
+    ));
+    let mut suffix = 1;
+    while fs.is_file(&path).await {
+        suffix += 1;
+        path = dir.join(format!(
+            "{}-{suffix}.json",
+            recording.started_at.format("%Y-%m-%d-%H-%M-%S")
+        ));
+    }
+    fs.atomic_write(path.clone(), serde_json::to_string(recording)?)
+        .await?;
+    Ok(path)
+}
+
+async fn load_session_recording(path: &Path, fs: &dyn Fs) -> Result<SessionRecording> {
+    let content = fs.load(path).await?;
+    let recording = serde_json::from_str::<SessionRecording>(&content)
+        .with_context(|| format!("parsing session recording {path:?}"))?;
+    anyhow::ensure!(
+        recording.version <= RECORDING_VERSION,
+        "session recording {path:?} was made by a newer version of Zed"
+    );
+    Ok(recording)
+}
+
+fn prompt_for_session_recording(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let paths = cx.prompt_for_paths(PathPromptOptions {
+        files: true,
+        directories: false,
+        multiple: false,
+        prompt: Some("Play Recording".into()),
+    });
+    let app_state = workspace.app_state().clone();
+    cx.spawn_in(window, async move |_, cx| {
+        let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+            return Ok(());
+        };
+        cx.update(|_, cx| open_session_recording(path, app_state, cx))?
+            .await
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Opens the session recording at `path` for playback in a new window.
+pub fn open_session_recording(
+    path: PathBuf,
+    app_state: Arc<AppState>,
+    cx: &mut App,
+) -> gpui::Task<Result<()>> {
+    cx.spawn(async move |cx| {
+        let recording = load_session_recording(&path, app_state.fs.as_ref()).await?;
+        let languages = app_state.languages.clone();
+        cx.update(|cx| {
+            workspace::open_new(
+                OpenOptions::default(),
+                app_state,
+                cx,
+                move |workspace, window, cx| {
+                    let view =
+                        cx.new(|cx| SessionPlaybackView::new(recording, languages, window, cx));
+                    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+                },
+            )
+        })
+        .await
+    })
+}
@@ -53,6 +53,7 @@ pub(crate) struct Client {
     name: Arc<str>,
     subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
     io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
@@ -101,7 +102,6 @@ struct AnyResponse<'a> {
 }
 
 #[derive(Serialize, Deserialize)]
-#[allow(dead_code)]
 pub(crate) struct Response<T> {
     pub jsonrpc: &'static str,
     pub id: RequestId,
@@ -114,7 +114,6 @@ pub(crate) struct Response<T> {
 pub(crate) enum CspResult<T> {
     #[serde(rename = "result")]
     Ok(Option<T>),
-    #[allow(dead_code)]
     Error(Option<Error>),
 }
 
@@ -202,12 +201,14 @@ impl Client {
             let response_handlers = response_handlers.clone();
             let request_handlers = request_handlers.clone();
             let transport = transport.clone();
+            let outbound_tx = outbound_tx.clone();
             async move |cx| {
                 Self::handle_input(
                     transport,
                     subscription_set,
                     request_handlers,
                     response_handlers,
+                    outbound_tx,
                     cx,
                 )
                 .log_err()
@@ -238,6 +239,7 @@ impl Client {
             server_id,
             subscription_set,
             response_handlers,
+            request_handlers,
             name: server_name,
             next_id: Default::default(),
             outbound_tx,
@@ -252,14 +254,15 @@ impl Client {
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches
+    /// them to the appropriate handlers. Requests from the server are answered by the
+    /// handlers registered with [`Client::on_request`], or rejected when no handler exists.
     async fn handle_input(
         transport: Arc<dyn Transport>,
         subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
         request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: &mut AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
@@ -274,6 +277,17 @@ impl Client {
                         request.params.unwrap_or(RawValue::NULL),
                         cx.clone(),
                     );
+                } else {
+                    log::debug!("unhandled context server request: {}", request.method);
+                    send_response::<()>(
+                        &outbound_tx,
+                        request.id,
+                        CspResult::Error(Some(Error {
+                            message: format!("method not found: {}", request.method),
+                            code: METHOD_NOT_FOUND,
+                        })),
+                    )
+                    .log_err();
                 }
             } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut()
@@ -452,6 +466,55 @@ impl Client {
         Ok(())
     }
 
+    /// Registers a handler for requests sent by the context server.
+    ///
+    /// The handler's result is sent back to the server as the response to the
+    /// request. Registering a handler for a method replaces any previous handler.
+    pub fn on_request<P, R>(
+        &self,
+        method: &'static str,
+        mut f: impl 'static + Send + FnMut(P, AsyncApp) -> Task<Result<R>>,
+    ) where
+        P: DeserializeOwned,
+        R: Serialize + 'static,
+    {
+        let outbound_tx = self.outbound_tx.clone();
+        self.request_handlers.lock().insert(
+            method,
+            Box::new(move |id, params, cx| {
+                let params = match serde_json::from_str::<P>(params.get()) {
+                    Ok(params) => params,
+                    Err(error) => {
+                        send_response::<()>(
+                            &outbound_tx,
+                            id,
+                            CspResult::Error(Some(Error {
+                                message: error.to_string(),
+                                code: INVALID_PARAMS,
+                            })),
+                        )
+                        .log_err();
+                        return;
+                    }
+                };
+
+                let response = f(params, cx.clone());
+                let outbound_tx = outbound_tx.clone();
+                cx.spawn(async move |_| {
+                    let result = match response.await {
+                        Ok(response) => CspResult::Ok(Some(response)),
+                        Err(error) => CspResult::Error(Some(Error {
+                            message: error.to_string(),
+                            code: INTERNAL_ERROR,
+                        })),
+                    };
+                    send_response(&outbound_tx, id, result).log_err();
+                })
+                .detach();
+            }),
+        );
+    }
+
     #[must_use]
     pub fn on_notification(
         &self,
@@ -467,6 +530,21 @@ impl Client {
     }
 }
 
+fn send_response<T: Serialize>(
+    outbound_tx: &channel::Sender<String>,
+    id: RequestId,
+    value: CspResult<T>,
+) -> Result<()> {
+    let response = serde_json::to_string(&Response {
+        jsonrpc: JSON_RPC_VERSION,
+        id,
+        value,
+    })?;
+    outbound_tx
+        .try_send(response)
+        .context("failed to write to context server's stdin")
+}
+
 #[derive(Debug)]
 pub struct RequestCanceled;
 
@@ -562,3 +640,50 @@ impl Drop for NotificationSubscription {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test::FakeTransport,
+        types::{ListRootsResponse, Request as _, Root, requests::ListRoots},
+    };
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_requests_from_server(cx: &mut TestAppContext) {
+        let transport = Arc::new(FakeTransport::new(cx.executor()));
+        let client = Client::new(
+            ContextServerId("test".into()),
+            "test".into(),
+            transport.clone(),
+            None,
+            cx.to_async(),
+        )
+        .unwrap();
+
+        transport.send_request::<ListRoots>(1, ()).unwrap();
+        let response = transport.next_response().await.unwrap();
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+        assert!(response.get("result").is_none());
+
+        client.on_request::<(), ListRootsResponse>(ListRoots::METHOD, |_, cx| {
+            cx.background_spawn(async {
+                Ok(ListRootsResponse {
+                    roots: vec![Root {
+                        uri: url::Url::parse("file:///project").unwrap(),
+                        name: Some("project".into()),
+                    }],
+                    meta: None,
+                })
+            })
+        });
+        transport.send_request::<ListRoots>(2, ()).unwrap();
+        let response = transport.next_response().await.unwrap();
+        assert_eq!(response["id"], json!(2));
+        assert_eq!(response["result"]["roots"][0]["name"], json!("project"));
+        assert!(response.get("error").is_none());
+    }
+}
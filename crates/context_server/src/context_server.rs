@@ -15,7 +15,7 @@ use std::{fmt::Display, path::PathBuf};
 
 use anyhow::Result;
 use client::Client;
-use gpui::AsyncApp;
+use gpui::{AsyncApp, Task};
 use parking_lot::RwLock;
 pub use settings::ContextServerCommand;
 use url::Url;
@@ -31,6 +31,27 @@ impl Display for ContextServerId {
     }
 }
 
+/// Serves the requests that a context server can send to the client.
+///
+/// Sampling lets a server ask for a language model completion, and elicitation
+/// lets it ask the user for structured input. Both are only advertised to
+/// servers when a delegate has been set.
+pub trait ContextServerClientDelegate: Send + Sync {
+    fn create_message(
+        &self,
+        server_id: ContextServerId,
+        request: types::CreateMessageRequest,
+        cx: AsyncApp,
+    ) -> Task<Result<types::CreateMessageResult>>;
+
+    fn elicit(
+        &self,
+        server_id: ContextServerId,
+        request: types::ElicitRequest,
+        cx: AsyncApp,
+    ) -> Task<Result<types::ElicitResult>>;
+}
+
 enum ContextServerTransport {
     Stdio(ContextServerCommand, Option<PathBuf>),
     Custom(Arc<dyn crate::transport::Transport>),
@@ -41,6 +62,7 @@ pub struct ContextServer {
     client: RwLock<Option<Arc<crate::protocol::InitializedContextServerProtocol>>>,
     configuration: ContextServerTransport,
     request_timeout: Option<Duration>,
+    client_delegate: RwLock<Option<Arc<dyn ContextServerClientDelegate>>>,
 }
 
 impl ContextServer {
@@ -57,6 +79,7 @@ impl ContextServer {
                 working_directory.map(|directory| directory.to_path_buf()),
             ),
             request_timeout: None,
+            client_delegate: RwLock::new(None),
         }
     }
 
@@ -94,6 +117,7 @@ impl ContextServer {
             client: RwLock::new(None),
             configuration: ContextServerTransport::Custom(transport),
             request_timeout,
+            client_delegate: RwLock::new(None),
         }
     }
 
@@ -105,6 +129,13 @@ impl ContextServer {
         self.client.read().clone()
     }
 
+    /// Sets the delegate that serves sampling and elicitation requests from the server.
+    /// Takes effect the next time the server is started, since support for sampling and
+    /// elicitation is advertised when the server is initialized.
+    pub fn set_client_delegate(&self, delegate: Option<Arc<dyn ContextServerClientDelegate>>) {
+        *self.client_delegate.write() = delegate;
+    }
+
     pub async fn start(&self, cx: &AsyncApp) -> Result<()> {
         self.initialize(self.new_client(cx)?).await
     }
@@ -139,7 +170,29 @@ impl ContextServer {
             name: "Zed".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
-        let initialized_protocol = protocol.initialize(client_info).await?;
+        let mut capabilities = types::ClientCapabilities {
+            experimental: None,
+            sampling: None,
+            elicitation: None,
+            roots: None,
+        };
+
+        let delegate = self.client_delegate.read().clone();
+        if let Some(delegate) = delegate {
+            protocol.on_request::<types::requests::CreateMessage>({
+                let delegate = delegate.clone();
+                let server_id = self.id.clone();
+                move |request, cx| delegate.create_message(server_id.clone(), request, cx)
+            });
+            protocol.on_request::<types::requests::ElicitationCreate>({
+                let server_id = self.id.clone();
+                move |request, cx| delegate.elicit(server_id.clone(), request, cx)
+            });
+            capabilities.sampling = Some(serde_json::json!({}));
+            capabilities.elicitation = Some(serde_json::json!({}));
+        }
+
+        let initialized_protocol = protocol.initialize(client_info, capabilities).await?;
 
         log::debug!(
             "context server {} initialized: {:?}",
@@ -41,20 +41,47 @@ pub struct FakeTransport {
     >,
     tx: futures::channel::mpsc::UnboundedSender<String>,
     rx: Arc<Mutex<futures::channel::mpsc::UnboundedReceiver<String>>>,
+    responses_tx: futures::channel::mpsc::UnboundedSender<serde_json::Value>,
+    responses_rx: Mutex<futures::channel::mpsc::UnboundedReceiver<serde_json::Value>>,
     executor: BackgroundExecutor,
 }
 
 impl FakeTransport {
     pub fn new(executor: BackgroundExecutor) -> Self {
         let (tx, rx) = futures::channel::mpsc::unbounded();
+        let (responses_tx, responses_rx) = futures::channel::mpsc::unbounded();
         Self {
             request_handlers: Default::default(),
             tx,
             rx: Arc::new(Mutex::new(rx)),
+            responses_tx,
+            responses_rx: Mutex::new(responses_rx),
             executor,
         }
     }
 
+    /// Sends a request from the server to the client.
+    pub fn send_request<T: crate::types::Request>(
+        &self,
+        id: u64,
+        params: T::Params,
+    ) -> anyhow::Result<()> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": T::METHOD,
+            "params": params,
+        });
+        self.tx
+            .unbounded_send(request.to_string())
+            .context("sending a request")
+    }
+
+    /// Waits for the next response the client sent to a request of the server.
+    pub async fn next_response(&self) -> Option<serde_json::Value> {
+        self.responses_rx.lock().await.next().await
+    }
+
     pub fn on_request<T, Fut>(
         mut self,
         handler: impl 'static + Send + Sync + Fn(T::Params) -> Fut,
@@ -101,6 +128,10 @@ impl Transport for FakeTransport {
                 } else {
                     log::debug!("No handler registered for MCP request '{method}'");
                 }
+            } else if msg.get("id").is_some() {
+                self.responses_tx
+                    .unbounded_send(msg)
+                    .context("recording a response")?;
             }
         }
         Ok(())
@@ -1,4 +1,4 @@
-use collections::HashMap;
+use collections::{HashMap, IndexMap};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -48,7 +48,12 @@ pub mod requests {
         ResourcesReadParams,
         ResourcesReadResponse
     );
-    request!("resources/list", ResourcesList, (), ResourcesListResponse);
+    request!(
+        "resources/list",
+        ResourcesList,
+        PaginatedParams,
+        ResourcesListResponse
+    );
     request!(
         "logging/setLevel",
         LoggingSetLevel,
@@ -73,10 +78,22 @@ pub mod requests {
     request!(
         "resources/templates/list",
         ListResourceTemplates,
-        (),
+        PaginatedParams,
         ListResourceTemplatesResponse
     );
     request!("roots/list", ListRoots, (), ListRootsResponse);
+    request!(
+        "sampling/createMessage",
+        CreateMessage,
+        CreateMessageRequest,
+        CreateMessageResult
+    );
+    request!(
+        "elicitation/create",
+        ElicitationCreate,
+        ElicitRequest,
+        ElicitResult
+    );
 }
 
 pub trait Request {
@@ -177,6 +194,16 @@ pub struct ResourcesSubscribeParams {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// Requests a page of a list, starting at the `next_cursor` returned for the previous page.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourcesReadParams {
@@ -270,7 +297,7 @@ pub struct ResourcesReadResponse {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ResourceContentsType {
     Text(TextResourceContents),
@@ -313,7 +340,7 @@ pub struct CreateMessageRequest {
     pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateMessageResult {
     pub role: Role,
@@ -323,6 +350,125 @@ pub struct CreateMessageResult {
     pub stop_reason: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitRequest {
+    pub message: String,
+    pub requested_schema: ElicitationSchema,
+}
+
+/// A restricted JSON schema describing a flat object of primitive values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitationSchema {
+    #[serde(rename = "type", default = "ElicitationSchema::object_type")]
+    pub schema_type: String,
+    pub properties: IndexMap<String, PrimitiveSchemaDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+}
+
+impl ElicitationSchema {
+    fn object_type() -> String {
+        "object".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PrimitiveSchemaDefinition {
+    #[serde(rename_all = "camelCase")]
+    String {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min_length: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_length: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+        enum_values: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        enum_names: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Number {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        minimum: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        maximum: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        default: Option<f64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Integer {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        minimum: Option<i64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        maximum: Option<i64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        default: Option<i64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Boolean {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        default: Option<bool>,
+    },
+}
+
+impl PrimitiveSchemaDefinition {
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            Self::String { title, .. }
+            | Self::Number { title, .. }
+            | Self::Integer { title, .. }
+            | Self::Boolean { title, .. } => title.as_deref(),
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            Self::String { description, .. }
+            | Self::Number { description, .. }
+            | Self::Integer { description, .. }
+            | Self::Boolean { description, .. } => description.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PromptMessage {
@@ -445,6 +591,8 @@ pub struct ClientCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sampling: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub elicitation: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub roots: Option<RootsCapabilities>,
 }
 
@@ -535,7 +683,7 @@ pub struct Implementation {
     pub version: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Resource {
     pub uri: Url,
@@ -554,7 +702,7 @@ pub struct ResourceContents {
     pub mime_type: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TextResourceContents {
     pub uri: Url,
@@ -563,7 +711,7 @@ pub struct TextResourceContents {
     pub text: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BlobResourceContents {
     pub uri: Url,
@@ -572,7 +720,7 @@ pub struct BlobResourceContents {
     pub blob: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceTemplate {
     pub uri_template: String,
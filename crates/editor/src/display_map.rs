@@ -163,6 +163,7 @@ pub enum HighlightKey {
     InlineAssist,
     InputComposition,
     MatchingBracket,
+    MergeViewWordDiff,
     PendingInput,
     ProjectSearchView,
     Rename,
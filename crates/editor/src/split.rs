@@ -38,7 +38,7 @@ use crate::{
     actions::{DisableBreakpoint, EditLogBreakpoint, EnableBreakpoint, ToggleBreakpoint},
     display_map::Companion,
 };
-use zed_actions::assistant::InlineAssist;
+use zed_actions::assistant::{InlineAssist, InlineAssistExcerpts};
 
 pub(crate) fn convert_lhs_rows_to_rhs(
     lhs_excerpt_to_rhs_excerpt: &HashMap<ExcerptId, ExcerptId>,
@@ -913,6 +913,19 @@ impl SplittableEditor {
         }
     }
 
+    fn intercept_inline_assist_excerpts(
+        &mut self,
+        _: &InlineAssistExcerpts,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.lhs.is_some() {
+            cx.stop_propagation();
+        } else {
+            cx.propagate();
+        }
+    }
+
     fn toggle_soft_wrap(
         &mut self,
         _: &ToggleSoftWrap,
@@ -2046,6 +2059,7 @@ impl Render for SplittableEditor {
             .on_action(cx.listener(Self::intercept_disable_breakpoint))
             .on_action(cx.listener(Self::intercept_edit_log_breakpoint))
             .on_action(cx.listener(Self::intercept_inline_assist))
+            .on_action(cx.listener(Self::intercept_inline_assist_excerpts))
             .capture_action(cx.listener(Self::toggle_soft_wrap))
             .size_full()
             .child(inner)
@@ -21,7 +21,10 @@ use gpui::http_client::read_proxy_from_env;
 use gpui::{App, AppContext, AsyncApp, Entity, UpdateGlobal};
 use gpui_tokio::Tokio;
 use language::LanguageRegistry;
-use language_model::{ConfiguredModel, LanguageModel, LanguageModelRegistry, SelectedModel};
+use language_model::{
+    ConfiguredModel, LanguageModel, LanguageModelRegistry, SelectedModel,
+    cassette_provider::{CassetteLanguageModel, CassetteMode},
+};
 use node_runtime::{NodeBinaryOptions, NodeRuntime};
 use project::project_settings::ProjectSettings;
 use prompt_store::PromptBuilder;
@@ -60,6 +63,13 @@ struct Args {
     /// Maximum number of examples to run concurrently.
     #[arg(long, default_value = "4")]
     concurrency: usize,
+    /// Records the completions of the agent and judge models to cassettes in this directory.
+    /// The models must still be configured when replaying, since their metadata isn't recorded.
+    #[arg(long)]
+    cassette_dir: Option<PathBuf>,
+    /// Replays the completions recorded in `--cassette-dir` instead of calling the models.
+    #[arg(long, requires = "cassette_dir")]
+    replay: bool,
     /// Output current environment variables as JSON to stdout
     #[arg(long, hide = true)]
     printenv: bool,
@@ -144,8 +154,31 @@ fn main() {
         cx.spawn(async move |cx| {
             future::join_all(tasks).await;
             let judge_model = cx.update(|cx| {
-                let agent_model = load_model(&args.model, cx).unwrap();
-                let judge_model = load_model(&args.judge_model, cx).unwrap();
+                let mut agent_model = load_model(&args.model, cx).unwrap();
+                let mut judge_model = load_model(&args.judge_model, cx).unwrap();
+                if let Some(cassette_dir) = &args.cassette_dir {
+                    let mode = if args.replay {
+                        CassetteMode::Replay
+                    } else {
+                        CassetteMode::Record
+                    };
+                    agent_model.model = Arc::new(
+                        CassetteLanguageModel::new(
+                            agent_model.model,
+                            mode,
+                            cassette_dir.join("agent.json"),
+                        )
+                        .unwrap(),
+                    );
+                    judge_model.model = Arc::new(
+                        CassetteLanguageModel::new(
+                            judge_model.model,
+                            mode,
+                            cassette_dir.join("judge.json"),
+                        )
+                        .unwrap(),
+                    );
+                }
                 LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
                     registry.set_default_model(Some(agent_model.clone()), cx);
                 });
@@ -334,6 +334,9 @@ impl ExampleContext {
                     ThreadEvent::Retry(status) => {
                         println!("{log_prefix} Got retry: {status:?}");
                     }
+                    ThreadEvent::ContextCompacted(_) => {
+                        println!("{log_prefix} Thread was compacted");
+                    }
                     ThreadEvent::Stop(stop_reason) => match stop_reason {
                         acp::StopReason::EndTurn => {}
                         acp::StopReason::MaxTokens => {
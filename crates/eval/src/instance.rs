@@ -302,6 +302,7 @@ impl ExampleInstance {
                         root_name,
                         abs_path,
                         rules_file: None,
+                        scoped_rules_files: Vec::new(),
                     }
                 }).collect::<Vec<_>>();
                 let project_context = cx.new(|_cx| ProjectContext::new(worktrees, vec![]));
@@ -564,6 +565,7 @@ impl ExampleInstance {
                 stop: Vec::new(),
                 thinking_allowed: true,
                 thinking_effort: None,
+                max_tokens: None,
             };
 
             let model = model.clone();
@@ -685,6 +687,7 @@ impl agent::ThreadEnvironment for EvalThreadEnvironment {
         _parent_thread: Entity<agent::Thread>,
         _label: String,
         _initial_prompt: String,
+        _path_scope: Option<agent::PathScope>,
         _cx: &mut App,
     ) -> Result<Rc<dyn agent::SubagentHandle>> {
         unimplemented!()
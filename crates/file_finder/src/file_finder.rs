@@ -1,25 +1,34 @@
 #[cfg(test)]
 mod file_finder_tests;
+mod frecency;
+mod persistence;
 
-use futures::future::join_all;
+use futures::{
+    FutureExt as _, StreamExt as _,
+    channel::mpsc,
+    future::{Shared, join_all},
+};
 pub use open_path_prompt::OpenPathDelegate;
 
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use editor::Editor;
 use file_icons::FileIcons;
+use frecency::{EDIT_WEIGHT, Frecency, FrecencyScores, OPEN_WEIGHT};
 use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
 use gpui::{
     Action, AnyElement, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
-    KeyContext, Modifiers, ModifiersChangedEvent, ParentElement, Render, Styled, Task, WeakEntity,
-    Window, actions, rems,
+    KeyContext, Modifiers, ModifiersChangedEvent, ParentElement, Render, Styled, Subscription,
+    Task, WeakEntity, Window, actions, rems,
 };
 use open_path_prompt::{
     OpenPathPrompt,
     file_finder_settings::{FileFinderSettings, FileFinderWidth},
 };
+use persistence::FILE_FINDER_DB;
 use picker::{Picker, PickerDelegate};
 use project::{
-    PathMatchCandidateSet, Project, ProjectPath, WorktreeId, worktree_store::WorktreeStore,
+    PathMatchCandidateSet, Project, ProjectPath, Worktree, WorktreeId,
+    worktree_store::WorktreeStore,
 };
 use project_panel::project_panel_settings::ProjectPanelSettings;
 use settings::Settings;
@@ -98,12 +107,17 @@ impl FileFinder {
     fn register(
         workspace: &mut Workspace,
         _window: Option<&mut Window>,
-        _: &mut Context<Workspace>,
+        cx: &mut Context<Workspace>,
     ) {
+        let frecency = cx.new(|cx| Frecency::new(workspace.database_id(), cx));
+        Self::record_file_uses(frecency.clone(), cx);
+        Self::index_worktree_paths(workspace, cx);
+
         workspace.register_action(
-            |workspace, action: &workspace::ToggleFileFinder, window, cx| {
+            move |workspace, action: &workspace::ToggleFileFinder, window, cx| {
                 let Some(file_finder) = workspace.active_modal::<Self>(cx) else {
-                    Self::open(workspace, action.separate_history, window, cx).detach();
+                    let frecency = frecency.read(cx).scores();
+                    Self::open(workspace, action.separate_history, frecency, window, cx).detach();
                     return;
                 };
 
@@ -117,9 +131,144 @@ impl FileFinder {
         );
     }
 
+    /// Feeds the files opened and saved in the workspace into its frecency.
+    fn record_file_uses(frecency: Entity<Frecency>, cx: &mut Context<Workspace>) {
+        cx.subscribe_self(move |workspace, event: &workspace::Event, cx| {
+            let (project_path, weight) = match event {
+                workspace::Event::ItemAdded { item } => (item.project_path(cx), OPEN_WEIGHT),
+                workspace::Event::UserSavedItem { item, .. } => (
+                    item.upgrade().and_then(|item| item.project_path(cx)),
+                    EDIT_WEIGHT,
+                ),
+                _ => return,
+            };
+            let Some(abs_path) = project_path.and_then(|project_path| {
+                workspace
+                    .project()
+                    .read(cx)
+                    .absolute_path(&project_path, cx)
+            }) else {
+                return;
+            };
+            let workspace_id = workspace.database_id();
+            frecency.update(cx, |frecency, cx| {
+                frecency.record(abs_path, weight, workspace_id, cx)
+            });
+        })
+        .detach();
+    }
+
+    /// Persists the paths of each local worktree once it's scanned, and keeps them up to date as
+    /// its files change, so that the first search in the next session doesn't have to wait for
+    /// the worktree to be scanned again.
+    fn index_worktree_paths(workspace: &Workspace, cx: &mut Context<Workspace>) {
+        let project = workspace.project().clone();
+        for worktree in project.read(cx).worktrees(cx).collect::<Vec<_>>() {
+            Self::index_paths(workspace, worktree, cx);
+        }
+        cx.subscribe(&project, |workspace, project, event, cx| {
+            if let project::Event::WorktreeAdded(worktree_id) = event
+                && let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx)
+            {
+                Self::index_paths(workspace, worktree, cx);
+            }
+        })
+        .detach();
+    }
+
+    fn index_paths(workspace: &Workspace, worktree: Entity<Worktree>, cx: &mut Context<Workspace>) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        let worktree_ref = worktree.read(cx);
+        if !worktree_ref.is_visible() || worktree_ref.is_single_file() {
+            return;
+        }
+        let Some(scan_complete) = worktree_ref
+            .as_local()
+            .map(|worktree| worktree.scan_complete())
+        else {
+            return;
+        };
+        let worktree = worktree.downgrade();
+        cx.spawn(async move |workspace, cx| {
+            scan_complete.await;
+            // Take the snapshot and subscribe to its changes at once, so that no change is missed.
+            let (changes_tx, mut changes_rx) = mpsc::unbounded();
+            let Some((abs_path, snapshot, _subscription)) = workspace
+                .update(cx, |_, cx| {
+                    let worktree = worktree.upgrade()?;
+                    let subscription = cx.subscribe(&worktree, move |_, _, event, _| {
+                        if let worktree::Event::UpdatedEntries(changes) = event {
+                            changes_tx.unbounded_send(changes.clone()).ok();
+                        }
+                    });
+                    let worktree = worktree.read(cx);
+                    Some((worktree.abs_path(), worktree.snapshot(), subscription))
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+
+            let index_abs_path = abs_path.to_path_buf();
+            cx.background_spawn(async move {
+                let mut removed = FILE_FINDER_DB
+                    .path_index(workspace_id, index_abs_path.clone())?
+                    .into_iter()
+                    .collect::<HashSet<_>>();
+                let mut added = Vec::new();
+                for entry in snapshot.files(false, 0) {
+                    let path = entry.path.as_unix_str();
+                    if !removed.remove(path) {
+                        added.push(path.to_string());
+                    }
+                }
+                FILE_FINDER_DB
+                    .update_path_index(
+                        workspace_id,
+                        index_abs_path,
+                        added,
+                        removed.into_iter().collect(),
+                    )
+                    .await
+            })
+            .await
+            .log_err();
+
+            while let Some(changes) = changes_rx.next().await {
+                let mut changes = vec![changes];
+                while let Ok(Some(more_changes)) = changes_rx.try_next() {
+                    changes.push(more_changes);
+                }
+                let Ok(snapshot) = worktree.read_with(cx, |worktree, _| worktree.snapshot()) else {
+                    break;
+                };
+                let mut added = Vec::new();
+                let mut removed = Vec::new();
+                for (path, _, _) in changes.iter().flat_map(|changes| changes.iter()) {
+                    match snapshot.entry_for_path(path) {
+                        Some(entry) if entry.is_dir() => {}
+                        Some(entry) if !entry.is_ignored => {
+                            added.push(path.as_unix_str().to_string())
+                        }
+                        _ => removed.push(path.as_unix_str().to_string()),
+                    }
+                }
+                FILE_FINDER_DB
+                    .update_path_index(workspace_id, abs_path.to_path_buf(), added, removed)
+                    .await
+                    .log_err();
+            }
+        })
+        .detach();
+    }
+
     fn open(
         workspace: &mut Workspace,
         separate_history: bool,
+        frecency: FrecencyScores,
         window: &mut Window,
         cx: &mut Context<Workspace>,
     ) -> Task<()> {
@@ -172,6 +321,7 @@ impl FileFinder {
                             currently_opened_path,
                             history_items.collect(),
                             separate_history,
+                            frecency,
                             window,
                             cx,
                         );
@@ -403,12 +553,26 @@ pub struct FileFinderDelegate {
     cancel_flag: Arc<AtomicBool>,
     history_items: Vec<FoundPath>,
     separate_history: bool,
+    frecency: FrecencyScores,
     first_update: bool,
     filter_popover_menu_handle: PopoverMenuHandle<ContextMenu>,
     split_popover_menu_handle: PopoverMenuHandle<ContextMenu>,
     focus_handle: FocusHandle,
     include_ignored: Option<bool>,
     include_ignored_refresh: Task<()>,
+    /// Files in the unscanned directories of lazily-scanned worktrees, keyed by worktree and
+    /// whether ignored files were included.
+    unloaded_files: HashMap<(WorktreeId, bool), UnloadedFiles>,
+    /// Files of worktrees whose initial scan is still running, as indexed in a previous session.
+    indexed_files: HashMap<WorktreeId, Shared<Task<Arc<Vec<Arc<RelPath>>>>>>,
+}
+
+/// The files found so far by a walk of a worktree's unscanned directories. Dropped when files
+/// change inside those directories, so that the next search walks them again.
+struct UnloadedFiles {
+    files: Arc<Vec<Arc<RelPath>>>,
+    _walk: Task<()>,
+    _subscription: Subscription,
 }
 
 /// Use a custom ordering for file finder: the regular one
@@ -537,6 +701,7 @@ impl Matches {
         cx: &'a App,
         history_items: impl IntoIterator<Item = &'a FoundPath> + Clone,
         currently_opened: Option<&'a FoundPath>,
+        frecency: &FrecencyScores,
         query: Option<&FileSearchQuery>,
         new_search_matches: impl Iterator<Item = ProjectPanelOrdMatch>,
         extend_old_matches: bool,
@@ -570,6 +735,7 @@ impl Matches {
             history_items,
             currently_opened,
             worktree_name_by_id,
+            frecency,
             query,
             path_style,
         );
@@ -580,7 +746,16 @@ impl Matches {
                     worktree_id: WorktreeId::from_usize(path_match.0.worktree_id),
                 })
             })
-            .map(Match::Search)
+            .map(|mut path_match| {
+                let worktree_id = WorktreeId::from_usize(path_match.0.worktree_id);
+                if !frecency.is_empty()
+                    && let Some(worktree) = worktree_store.read(cx).worktree_for_id(worktree_id, cx)
+                {
+                    let abs_path = worktree.read(cx).absolutize(&path_match.0.path);
+                    path_match.0.score = frecency.boost(&abs_path, path_match.0.score);
+                }
+                Match::Search(path_match)
+            })
             .collect();
 
         if extend_old_matches {
@@ -710,6 +885,7 @@ fn matching_history_items<'a>(
     history_items: impl IntoIterator<Item = &'a FoundPath>,
     currently_opened: Option<&'a FoundPath>,
     worktree_name_by_id: Option<HashMap<WorktreeId, Arc<RelPath>>>,
+    frecency: &FrecencyScores,
     query: &FileSearchQuery,
     path_style: PathStyle,
 ) -> HashMap<ProjectPath, Match> {
@@ -765,13 +941,14 @@ fn matching_history_items<'a>(
                 path_style,
             )
             .into_iter()
-            .filter_map(|path_match| {
+            .filter_map(|mut path_match| {
                 candidates_paths
                     .remove_entry(&ProjectPath {
                         worktree_id: WorktreeId::from_usize(path_match.worktree_id),
                         path: Arc::clone(&path_match.path),
                     })
                     .map(|(project_path, found_path)| {
+                        path_match.score = frecency.boost(&found_path.absolute, path_match.score);
                         (
                             project_path.clone(),
                             Match::History {
@@ -829,6 +1006,7 @@ impl FileFinderDelegate {
         currently_opened_path: Option<FoundPath>,
         history_items: Vec<FoundPath>,
         separate_history: bool,
+        frecency: FrecencyScores,
         window: &mut Window,
         cx: &mut Context<FileFinder>,
     ) -> Self {
@@ -848,12 +1026,15 @@ impl FileFinderDelegate {
             cancel_flag: Arc::new(AtomicBool::new(false)),
             history_items,
             separate_history,
+            frecency,
             first_update: true,
             filter_popover_menu_handle: PopoverMenuHandle::default(),
             split_popover_menu_handle: PopoverMenuHandle::default(),
             focus_handle: cx.focus_handle(),
             include_ignored: FileFinderSettings::get_global(cx).include_ignored,
             include_ignored_refresh: Task::ready(()),
+            unloaded_files: HashMap::default(),
+            indexed_files: HashMap::default(),
         }
     }
 
@@ -894,7 +1075,7 @@ impl FileFinderDelegate {
             .collect::<Vec<_>>();
         let include_root_name = worktrees.len() > 1;
         let candidate_sets = worktrees
-            .into_iter()
+            .iter()
             .map(|worktree| {
                 let worktree = worktree.read(cx);
                 PathMatchCandidateSet {
@@ -907,13 +1088,23 @@ impl FileFinderDelegate {
                 }
             })
             .collect::<Vec<_>>();
+        let unloaded_file_sets = worktrees
+            .iter()
+            .zip(&candidate_sets)
+            .filter_map(|(worktree, candidate_set)| {
+                let files =
+                    self.unloaded_files(worktree, candidate_set.include_ignored, window, cx)?;
+                Some((candidate_set.snapshot.clone(), files))
+            })
+            .collect::<Vec<_>>();
+        let path_style = self.project.read(cx).path_style(cx);
 
         let search_id = util::post_inc(&mut self.search_count);
         self.cancel_flag.store(true, atomic::Ordering::Release);
         self.cancel_flag = Arc::new(AtomicBool::new(false));
         let cancel_flag = self.cancel_flag.clone();
         cx.spawn_in(window, async move |picker, cx| {
-            let matches = fuzzy::match_path_sets(
+            let mut matches = fuzzy::match_path_sets(
                 candidate_sets.as_slice(),
                 query.path_query(),
                 &relative_to,
@@ -922,9 +1113,37 @@ impl FileFinderDelegate {
                 &cancel_flag,
                 cx.background_executor().clone(),
             )
-            .await
-            .into_iter()
-            .map(ProjectPanelOrdMatch);
+            .await;
+            for (snapshot, files) in unloaded_file_sets {
+                let files = files.await;
+                let path_query = query.path_query().to_owned();
+                let unloaded_matches = cx
+                    .background_spawn(async move {
+                        let candidates = files
+                            .iter()
+                            .filter(|path| snapshot.entry_for_path(path).is_none())
+                            .map(|path| PathMatchCandidate {
+                                is_dir: false,
+                                path,
+                                char_bag: CharBag::from_iter(
+                                    path.as_unix_str().to_lowercase().chars(),
+                                ),
+                            })
+                            .collect();
+                        fuzzy::match_fixed_path_set(
+                            candidates,
+                            snapshot.id().to_usize(),
+                            include_root_name.then(|| snapshot.root_name().into()),
+                            &path_query,
+                            false,
+                            100,
+                            path_style,
+                        )
+                    })
+                    .await;
+                matches.extend(unloaded_matches);
+            }
+            let matches = matches.into_iter().map(ProjectPanelOrdMatch);
             let did_cancel = cancel_flag.load(atomic::Ordering::Acquire);
             picker
                 .update(cx, |picker, cx| {
@@ -936,6 +1155,95 @@ impl FileFinderDelegate {
         })
     }
 
+    /// Returns the files of a worktree that aren't in its snapshot yet, so that they can still be
+    /// found: the files indexed in a previous session while the initial scan is running, or the
+    /// files in the unscanned directories of a lazily-scanned worktree found so far. The latter are
+    /// walked in the background, refreshing the matches as they're found.
+    fn unloaded_files(
+        &mut self,
+        worktree: &Entity<Worktree>,
+        include_ignored: bool,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Shared<Task<Arc<Vec<Arc<RelPath>>>>>> {
+        let worktree_ref = worktree.read(cx);
+        let local_worktree = worktree_ref.as_local()?;
+        if worktree_ref.completed_scan_id() == 0 {
+            return self.indexed_files(worktree, cx);
+        }
+        if !local_worktree.settings().lazy_file_scan {
+            return None;
+        }
+        let key = (worktree_ref.id(), include_ignored);
+        if let Some(unloaded) = self.unloaded_files.get(&key) {
+            return Some(Task::ready(unloaded.files.clone()).shared());
+        }
+        let mut batches = worktree.update(cx, |worktree, cx| {
+            worktree
+                .as_local()
+                .map(|worktree| worktree.walk_unloaded_files(include_ignored, cx))
+        })?;
+        let walk = cx.spawn_in(window, async move |picker, cx| {
+            while let Some(batch) = batches.next().await {
+                let updated = picker.update_in(cx, |picker, window, cx| {
+                    if let Some(unloaded) = picker.delegate.unloaded_files.get_mut(&key) {
+                        Arc::make_mut(&mut unloaded.files).extend(batch);
+                        picker.refresh(window, cx);
+                    }
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        });
+        let subscription =
+            cx.subscribe_in(worktree, window, move |picker, _, event, window, cx| {
+                if let worktree::Event::UnloadedDirectoriesChanged = event
+                    && picker.delegate.unloaded_files.remove(&key).is_some()
+                {
+                    picker.refresh(window, cx);
+                }
+            });
+        self.unloaded_files.insert(
+            key,
+            UnloadedFiles {
+                files: Arc::default(),
+                _walk: walk,
+                _subscription: subscription,
+            },
+        );
+        None
+    }
+
+    fn indexed_files(
+        &mut self,
+        worktree: &Entity<Worktree>,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Shared<Task<Arc<Vec<Arc<RelPath>>>>>> {
+        let workspace_id = self.workspace.upgrade()?.read(cx).database_id()?;
+        let worktree = worktree.read(cx);
+        if let Some(files) = self.indexed_files.get(&worktree.id()) {
+            return Some(files.clone());
+        }
+        let abs_path = worktree.abs_path().to_path_buf();
+        let files = cx
+            .background_spawn(async move {
+                let paths = FILE_FINDER_DB
+                    .path_index(workspace_id, abs_path)
+                    .log_err()
+                    .unwrap_or_default();
+                Arc::new(
+                    paths
+                        .iter()
+                        .filter_map(|path| RelPath::unix(path).log_err().map(RelPath::into_arc))
+                        .collect(),
+                )
+            })
+            .shared();
+        self.indexed_files.insert(worktree.id(), files.clone());
+        Some(files)
+    }
+
     fn set_search_matches(
         &mut self,
         search_id: usize,
@@ -965,6 +1273,7 @@ impl FileFinderDelegate {
                 cx,
                 &self.history_items,
                 self.currently_opened_path.as_ref(),
+                &self.frecency,
                 Some(&query),
                 matches.into_iter(),
                 extend_old_matches,
@@ -1414,6 +1723,7 @@ impl PickerDelegate for FileFinderDelegate {
                             || project.is_via_remote_server()
                     }),
                     self.currently_opened_path.as_ref(),
+                    &self.frecency,
                     None,
                     None.into_iter(),
                     false,
@@ -2832,6 +2832,43 @@ async fn test_nonexistent_history_items_not_shown(cx: &mut gpui::TestAppContext)
     });
 }
 
+#[gpui::test]
+async fn test_frecency_breaks_ties_between_history_items(cx: &mut gpui::TestAppContext) {
+    let app_state = init_test(cx);
+
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            path!("/src"),
+            json!({
+                "a": { "main.rs": "// First main file" },
+                "b": { "main.rs": "// Second main file" },
+                "other.rs": "// Unrelated file",
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), [path!("/src").as_ref()], cx).await;
+    let (multi_workspace, cx) =
+        cx.add_window_view(|window, cx| MultiWorkspace::test_new(project, window, cx));
+    let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+    open_close_queried_buffer("b/main", 1, "main.rs", &workspace, cx).await;
+    open_close_queried_buffer("b/main", 1, "main.rs", &workspace, cx).await;
+    open_close_queried_buffer("a/main", 1, "main.rs", &workspace, cx).await;
+
+    let picker = open_file_picker(&workspace, cx);
+    cx.simulate_input("main.rs");
+
+    picker.update(cx, |picker, _| {
+        assert_eq!(
+            collect_search_matches(picker).history,
+            vec![rel_path("b/main.rs").into(), rel_path("a/main.rs").into()],
+            "Equally good matches should be ranked by how often and how recently they were opened"
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_search_results_refreshed_on_worktree_updates(cx: &mut gpui::TestAppContext) {
     let app_state = init_test(cx);
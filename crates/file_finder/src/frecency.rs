@@ -0,0 +1,152 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use collections::HashMap;
+use gpui::{Context, Task};
+use util::ResultExt as _;
+use workspace::WorkspaceId;
+
+use crate::persistence::FILE_FINDER_DB;
+
+/// The number of seconds after which a use of a file counts half as much.
+const HALF_LIFE_SECS: f64 = 7. * 24. * 60. * 60.;
+/// How much opening a file adds to its frecency.
+pub(crate) const OPEN_WEIGHT: f64 = 1.;
+/// How much saving a file adds to its frecency. Lower than opening, since autosave can save a
+/// file many times while it's edited.
+pub(crate) const EDIT_WEIGHT: f64 = 0.25;
+/// The number of seconds after which an unused file is forgotten, by which point its score has
+/// decayed to less than a thousandth.
+const MAX_AGE_SECS: i64 = 10 * HALF_LIFE_SECS as i64;
+/// The number of most recently used files that are remembered per workspace.
+const MAX_ENTRIES: i64 = 5_000;
+/// The largest fraction by which frecency can raise a match's score, so that it reorders matches
+/// of similar quality without outranking much better ones.
+const MAX_BOOST: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy)]
+struct FrecencyEntry {
+    score: f64,
+    last_used: i64,
+}
+
+impl FrecencyEntry {
+    fn decayed(&self, now: i64) -> f64 {
+        let elapsed = now.saturating_sub(self.last_used).max(0) as f64;
+        self.score * 0.5f64.powf(elapsed / HALF_LIFE_SECS)
+    }
+}
+
+/// Tracks how frequently and how recently the files of a workspace are opened and edited.
+///
+/// Every use adds to a file's score, and scores decay exponentially over time, so a file that is
+/// used often but not lately ranks close to one that was just used a few times.
+pub(crate) struct Frecency {
+    entries: HashMap<PathBuf, FrecencyEntry>,
+    _load: Task<()>,
+}
+
+impl Frecency {
+    pub fn new(workspace_id: Option<WorkspaceId>, cx: &mut Context<Self>) -> Self {
+        let load = match workspace_id {
+            Some(workspace_id) => cx.spawn(async move |this, cx| {
+                let entries = cx
+                    .background_spawn(async move {
+                        FILE_FINDER_DB
+                            .prune_frecency(workspace_id, now() - MAX_AGE_SECS, MAX_ENTRIES)
+                            .await
+                            .log_err();
+                        FILE_FINDER_DB.frecency(workspace_id)
+                    })
+                    .await
+                    .log_err()
+                    .unwrap_or_default();
+                this.update(cx, |this, _| {
+                    for (abs_path, score, last_used) in entries {
+                        let persisted = FrecencyEntry { score, last_used };
+                        this.entries
+                            .entry(abs_path)
+                            .and_modify(|entry| entry.score += persisted.decayed(entry.last_used))
+                            .or_insert(persisted);
+                    }
+                })
+                .ok();
+            }),
+            None => Task::ready(()),
+        };
+        Self {
+            entries: HashMap::default(),
+            _load: load,
+        }
+    }
+
+    /// Records a use of the file at `abs_path`, persisting it if the workspace is persisted.
+    pub fn record(
+        &mut self,
+        abs_path: PathBuf,
+        weight: f64,
+        workspace_id: Option<WorkspaceId>,
+        cx: &mut Context<Self>,
+    ) {
+        let now = now();
+        let entry = self
+            .entries
+            .entry(abs_path.clone())
+            .or_insert(FrecencyEntry {
+                score: 0.,
+                last_used: now,
+            });
+        entry.score = entry.decayed(now) + weight;
+        entry.last_used = now;
+
+        if let Some(workspace_id) = workspace_id {
+            let FrecencyEntry { score, last_used } = *entry;
+            cx.background_spawn(async move {
+                FILE_FINDER_DB
+                    .save_frecency(workspace_id, abs_path, score, last_used)
+                    .await
+                    .log_err();
+            })
+            .detach();
+        }
+    }
+
+    /// Returns the current, decayed, score of every file.
+    pub fn scores(&self) -> FrecencyScores {
+        let now = now();
+        FrecencyScores(
+            self.entries
+                .iter()
+                .map(|(abs_path, entry)| (abs_path.clone(), entry.decayed(now)))
+                .collect(),
+        )
+    }
+}
+
+/// A snapshot of the frecency scores, taken when the file finder is opened.
+#[derive(Debug, Default)]
+pub(crate) struct FrecencyScores(HashMap<PathBuf, f64>);
+
+impl FrecencyScores {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Blends the frecency of the file at `abs_path` into its fuzzy match score.
+    pub fn boost(&self, abs_path: &Path, match_score: f64) -> f64 {
+        match self.0.get(abs_path) {
+            Some(&frecency) if frecency > 0. => {
+                match_score * (1. + MAX_BOOST * frecency / (frecency + 1.))
+            }
+            _ => match_score,
+        }
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+pub struct FileFinderDb(ThreadSafeConnection);
+
+impl Domain for FileFinderDb {
+    const NAME: &str = stringify!(FileFinderDb);
+
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE file_finder_frecency(
+            workspace_id INTEGER NOT NULL,
+            abs_path TEXT NOT NULL,
+            score REAL NOT NULL,
+            last_used INTEGER NOT NULL,
+            PRIMARY KEY(workspace_id, abs_path),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+        ) STRICT;
+
+        CREATE TABLE file_finder_path_index(
+            workspace_id INTEGER NOT NULL,
+            worktree_abs_path TEXT NOT NULL,
+            path TEXT NOT NULL,
+            PRIMARY KEY(workspace_id, worktree_abs_path, path),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(FILE_FINDER_DB, FileFinderDb, [WorkspaceDb]);
+
+impl FileFinderDb {
+    query! {
+        pub fn frecency(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, f64, i64)>> {
+            SELECT abs_path, score, last_used
+            FROM file_finder_frecency
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_frecency(
+            workspace_id: WorkspaceId,
+            abs_path: PathBuf,
+            score: f64,
+            last_used: i64
+        ) -> Result<()> {
+            INSERT OR REPLACE INTO file_finder_frecency(workspace_id, abs_path, score, last_used)
+            VALUES (?, ?, ?, ?)
+        }
+    }
+
+    /// Removes the files that were last used before `used_before`, and all but the
+    /// `max_entries` most recently used ones.
+    pub async fn prune_frecency(
+        &self,
+        workspace_id: WorkspaceId,
+        used_before: i64,
+        max_entries: i64,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM file_finder_frecency
+                WHERE workspace_id = ?1 AND (
+                    last_used < ?2 OR abs_path NOT IN (
+                        SELECT abs_path
+                        FROM file_finder_frecency
+                        WHERE workspace_id = ?1
+                        ORDER BY last_used DESC
+                        LIMIT ?3
+                    )
+                );
+            ))?((workspace_id, used_before, max_entries))
+        })
+        .await
+    }
+
+    query! {
+        pub fn path_index(workspace_id: WorkspaceId, worktree_abs_path: PathBuf) -> Result<Vec<String>> {
+            SELECT path
+            FROM file_finder_path_index
+            WHERE workspace_id = ? AND worktree_abs_path = ?
+        }
+    }
+
+    /// Adds the `added` paths to the index of the worktree at `worktree_abs_path`, and removes
+    /// the `removed` ones from it.
+    pub async fn update_path_index(
+        &self,
+        workspace_id: WorkspaceId,
+        worktree_abs_path: PathBuf,
+        added: Vec<String>,
+        removed: Vec<String>,
+    ) -> Result<()> {
+        if added.is_empty() && removed.is_empty() {
+            return Ok(());
+        }
+        self.write(move |conn| {
+            conn.with_savepoint("update_path_index", || {
+                let mut insert = conn.exec_bound::<(WorkspaceId, &Path, &str)>(sql!(
+                    INSERT OR IGNORE INTO file_finder_path_index(workspace_id, worktree_abs_path, path)
+                    VALUES (?, ?, ?)
+                ))?;
+                for path in &added {
+                    insert((workspace_id, &worktree_abs_path, path))?;
+                }
+                let mut delete = conn.exec_bound::<(WorkspaceId, &Path, &str)>(sql!(
+                    DELETE FROM file_finder_path_index
+                    WHERE workspace_id = ? AND worktree_abs_path = ? AND path = ?
+                ))?;
+                for path in &removed {
+                    delete((workspace_id, &worktree_abs_path, path))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_update_path_index() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let worktree_abs_path = PathBuf::from("/root");
+        let index = || {
+            let mut paths = FILE_FINDER_DB
+                .path_index(workspace_id, worktree_abs_path.clone())
+                .unwrap();
+            paths.sort();
+            paths
+        };
+
+        FILE_FINDER_DB
+            .update_path_index(
+                workspace_id,
+                worktree_abs_path.clone(),
+                vec!["a.rs".into(), "src/b.rs".into()],
+                Vec::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(index(), ["a.rs", "src/b.rs"]);
+
+        FILE_FINDER_DB
+            .update_path_index(
+                workspace_id,
+                worktree_abs_path.clone(),
+                vec!["a.rs".into(), "src/c.rs".into()],
+                vec!["src/b.rs".into()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(index(), ["a.rs", "src/c.rs"]);
+    }
+
+    #[gpui::test]
+    async fn test_prune_frecency() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        for (abs_path, last_used) in [("/old.rs", 10), ("/recent.rs", 100), ("/latest.rs", 200)] {
+            FILE_FINDER_DB
+                .save_frecency(workspace_id, PathBuf::from(abs_path), 1., last_used)
+                .await
+                .unwrap();
+        }
+
+        FILE_FINDER_DB
+            .prune_frecency(workspace_id, 50, 10)
+            .await
+            .unwrap();
+        let mut paths = FILE_FINDER_DB
+            .frecency(workspace_id)
+            .unwrap()
+            .into_iter()
+            .map(|(abs_path, _, _)| abs_path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [PathBuf::from("/latest.rs"), PathBuf::from("/recent.rs")]
+        );
+
+        FILE_FINDER_DB
+            .prune_frecency(workspace_id, 50, 1)
+            .await
+            .unwrap();
+        let paths = FILE_FINDER_DB
+            .frecency(workspace_id)
+            .unwrap()
+            .into_iter()
+            .map(|(abs_path, _, _)| abs_path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [PathBuf::from("/latest.rs")]);
+    }
+}
@@ -0,0 +1,646 @@
+//! Read-only access to the contents of `.zip`, `.jar`, `.tar.gz` and `.crate` archives.
+//!
+//! Entries are addressed by appending `!` to the archive's file name: `/deps/foo.jar!` is the
+//! root directory of `/deps/foo.jar`, and `/deps/foo.jar!/a/B.java` is a file inside it. These
+//! virtual directories never show up when listing the directory containing the archive, so
+//! archives are only browsed when something explicitly opens a path inside them.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    ffi::OsString,
+    hash::{DefaultHasher, Hash as _, Hasher as _},
+    io,
+    path::{Component, Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use async_compression::futures::bufread::GzipDecoder;
+use async_tar::Archive;
+use futures::{AsyncRead, AsyncReadExt as _, Stream, StreamExt as _, io::Cursor, stream};
+use git::repository::GitRepository;
+use gpui::BackgroundExecutor;
+use parking_lot::Mutex;
+use rope::Rope;
+use text::LineEnding;
+use util::post_inc;
+
+use crate::{
+    CopyOptions, CreateOptions, FileHandle, Fs, JobEventReceiver, MTime, Metadata, PathEvent,
+    RemoveOptions, RenameOptions, Watcher,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// `.zip` and `.jar` files.
+    Zip,
+    /// Gzipped tarballs, including `.crate` files.
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") || name.ends_with(".jar") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") || name.ends_with(".crate") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns whether the file at this path can be browsed with [`ArchiveFs`].
+pub fn is_archive_path(path: &Path) -> bool {
+    ArchiveFormat::for_path(path).is_some()
+}
+
+/// Returns the path of the read-only directory exposing the contents of an archive.
+pub fn archive_root_path(archive_path: &Path) -> PathBuf {
+    let mut root = archive_path.as_os_str().to_owned();
+    root.push("!");
+    root.into()
+}
+
+/// Splits a path into the archive containing it and the path of the entry within that archive.
+///
+/// Returns `None` for paths that are not inside an archive. This only looks at the path, so it
+/// can't tell an archive from a directory whose name ends in an archive's extension.
+pub fn split_archive_path(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let mut archive_path = PathBuf::new();
+    let mut components = path.components();
+    while let Some(component) = components.next() {
+        if let Component::Normal(name) = component
+            && let Some(archive_name) = name.to_str().and_then(|name| name.strip_suffix('!'))
+        {
+            let candidate = archive_path.join(archive_name);
+            if is_archive_path(&candidate) {
+                return Some((candidate, components.as_path().to_path_buf()));
+            }
+        }
+        archive_path.push(component);
+    }
+    None
+}
+
+/// Returns whether the path refers to something inside an archive, judging only by the path.
+pub fn is_path_in_archive(path: &Path) -> bool {
+    split_archive_path(path).is_some()
+}
+
+/// An [`Fs`] that serves the contents of archives as read-only directories and forwards every
+/// other path to the wrapped filesystem.
+///
+/// Archives are decoded into memory the first time a path inside them is accessed, and decoded
+/// again only if the archive's mtime changes. The least recently used archives are dropped from
+/// memory once the decoded archives take up more than [`MAX_CACHED_ARCHIVES_SIZE`] bytes. Paths
+/// inside archives are never reported by [`Fs::watch`].
+pub struct ArchiveFs {
+    fs: Arc<dyn Fs>,
+    executor: BackgroundExecutor,
+    archives: Mutex<ArchiveCache>,
+}
+
+/// The total size of the decoded archives that [`ArchiveFs`] keeps in memory.
+pub const MAX_CACHED_ARCHIVES_SIZE: usize = 256 * 1024 * 1024;
+
+#[derive(Default)]
+struct ArchiveCache {
+    archives: HashMap<PathBuf, CachedArchive>,
+    size: usize,
+    next_use: usize,
+}
+
+struct CachedArchive {
+    index: Arc<ArchiveIndex>,
+    last_use: usize,
+}
+
+impl ArchiveCache {
+    fn get(&mut self, archive_path: &Path, mtime: MTime) -> Option<Arc<ArchiveIndex>> {
+        let archive = self.archives.get_mut(archive_path)?;
+        if archive.index.mtime != mtime {
+            return None;
+        }
+        archive.last_use = post_inc(&mut self.next_use);
+        Some(archive.index.clone())
+    }
+
+    fn insert(&mut self, archive_path: PathBuf, index: Arc<ArchiveIndex>) {
+        self.size += index.size;
+        let archive = CachedArchive {
+            index,
+            last_use: post_inc(&mut self.next_use),
+        };
+        if let Some(replaced) = self.archives.insert(archive_path.clone(), archive) {
+            self.size -= replaced.index.size;
+        }
+        while self.size > MAX_CACHED_ARCHIVES_SIZE {
+            let Some(least_recently_used) = self
+                .archives
+                .iter()
+                .filter(|(path, _)| **path != archive_path)
+                .min_by_key(|(_, archive)| archive.last_use)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            if let Some(evicted) = self.archives.remove(&least_recently_used) {
+                self.size -= evicted.index.size;
+            }
+        }
+    }
+}
+
+impl ArchiveFs {
+    pub fn new(fs: Arc<dyn Fs>, executor: BackgroundExecutor) -> Self {
+        Self {
+            fs,
+            executor,
+            archives: Mutex::default(),
+        }
+    }
+
+    /// Splits a path inside an archive like [`split_archive_path`], returning `None` unless the
+    /// archive exists and is a file, so that other paths are forwarded to the wrapped filesystem.
+    async fn split_archive_path(&self, path: &Path) -> Option<(PathBuf, Metadata, PathBuf)> {
+        let (archive_path, entry_path) = split_archive_path(path)?;
+        let metadata = self.fs.metadata(&archive_path).await.ok()??;
+        (!metadata.is_dir).then_some((archive_path, metadata, entry_path))
+    }
+
+    async fn ensure_writable(&self, path: &Path) -> Result<()> {
+        anyhow::ensure!(
+            self.split_archive_path(path).await.is_none(),
+            "{path:?} is inside a read-only archive"
+        );
+        Ok(())
+    }
+
+    async fn index(&self, archive_path: &Path, metadata: &Metadata) -> Result<Arc<ArchiveIndex>> {
+        if let Some(index) = self.archives.lock().get(archive_path, metadata.mtime) {
+            return Ok(index);
+        }
+
+        let format = ArchiveFormat::for_path(archive_path)
+            .with_context(|| format!("unsupported archive {archive_path:?}"))?;
+        let bytes = self.fs.load_bytes(archive_path).await?;
+        let index = self
+            .executor
+            .spawn(ArchiveIndex::read(format, bytes, metadata.mtime))
+            .await
+            .with_context(|| format!("reading archive {archive_path:?}"))?;
+        let index = Arc::new(index);
+        self.archives
+            .lock()
+            .insert(archive_path.to_path_buf(), index.clone());
+        Ok(index)
+    }
+
+    /// Looks up a path inside an archive, returning `None` if the path is not inside an archive
+    /// and `Some(None)` if the archive does not contain it.
+    async fn entry(&self, path: &Path) -> Option<Result<Option<(Arc<ArchiveIndex>, PathBuf)>>> {
+        let (archive_path, metadata, entry_path) = self.split_archive_path(path).await?;
+        Some(self.index(&archive_path, &metadata).await.map(|index| {
+            index
+                .entries
+                .contains_key(&entry_path)
+                .then_some((index, entry_path))
+        }))
+    }
+
+    async fn file_contents(&self, path: &Path) -> Option<Result<Arc<[u8]>>> {
+        let entry = self.entry(path).await?;
+        Some(entry.and_then(|entry| {
+            let (index, entry_path) =
+                entry.with_context(|| format!("{path:?} not found in archive"))?;
+            match &index.entries[&entry_path] {
+                ArchiveEntry::File(contents) => Ok(contents.clone()),
+                ArchiveEntry::Dir(_) => Err(anyhow!("{path:?} is a directory")),
+            }
+        }))
+    }
+}
+
+struct ArchiveIndex {
+    mtime: MTime,
+    entries: HashMap<PathBuf, ArchiveEntry>,
+    /// The total size of the archive's files.
+    size: usize,
+}
+
+enum ArchiveEntry {
+    Dir(BTreeSet<OsString>),
+    File(Arc<[u8]>),
+}
+
+impl ArchiveIndex {
+    async fn read(format: ArchiveFormat, bytes: Vec<u8>, mtime: MTime) -> Result<Self> {
+        let mut index = Self {
+            mtime,
+            entries: HashMap::from_iter([(PathBuf::new(), ArchiveEntry::Dir(BTreeSet::new()))]),
+            size: 0,
+        };
+        match format {
+            ArchiveFormat::Zip => {
+                let mut reader =
+                    async_zip::base::read::stream::ZipFileReader::new(Cursor::new(bytes));
+                while let Some(mut item) = reader.next_with_entry().await? {
+                    let entry_reader = item.reader_mut();
+                    let entry = entry_reader.entry();
+                    let name = entry
+                        .filename()
+                        .as_str()
+                        .context("reading zip entry file name")?
+                        .to_owned();
+                    if entry.dir()? {
+                        index.insert_dir(Path::new(&name));
+                    } else {
+                        let mut contents = Vec::new();
+                        entry_reader
+                            .read_to_end(&mut contents)
+                            .await
+                            .with_context(|| format!("reading zip entry {name:?}"))?;
+                        index.insert_file(Path::new(&name), contents);
+                    }
+                    reader = item.skip().await.context("reading next zip entry")?;
+                }
+            }
+            ArchiveFormat::TarGz => {
+                let archive = Archive::new(GzipDecoder::new(Cursor::new(bytes)));
+                let mut entries = archive.entries()?;
+                while let Some(entry) = entries.next().await {
+                    let mut entry = entry?;
+                    let path = entry.path()?.into_owned();
+                    let entry_type = entry.header().entry_type();
+                    if entry_type.is_dir() {
+                        index.insert_dir(&path);
+                    } else if entry_type.is_file() {
+                        let mut contents = Vec::new();
+                        entry
+                            .read_to_end(&mut contents)
+                            .await
+                            .with_context(|| format!("reading tar entry {path:?}"))?;
+                        index.insert_file(&path, contents);
+                    }
+                }
+            }
+        }
+        Ok(index)
+    }
+
+    /// Normalizes a path stored in an archive, rejecting entries that would escape its root.
+    fn normalize(path: &Path) -> Option<PathBuf> {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => normalized.push(name),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        (!normalized.as_os_str().is_empty()).then_some(normalized)
+    }
+
+    fn insert_dir(&mut self, path: &Path) {
+        let Some(path) = Self::normalize(path) else {
+            return;
+        };
+        self.insert_parents(&path);
+        self.entries
+            .entry(path)
+            .or_insert_with(|| ArchiveEntry::Dir(BTreeSet::new()));
+    }
+
+    fn insert_file(&mut self, path: &Path, contents: Vec<u8>) {
+        let Some(path) = Self::normalize(path) else {
+            return;
+        };
+        self.insert_parents(&path);
+        self.size += contents.len();
+        if let Some(ArchiveEntry::File(replaced)) = self
+            .entries
+            .insert(path, ArchiveEntry::File(contents.into()))
+        {
+            self.size -= replaced.len();
+        }
+    }
+
+    fn insert_parents(&mut self, path: &Path) {
+        let mut child = path;
+        while let Some(parent) = child.parent() {
+            let entry = self
+                .entries
+                .entry(parent.to_path_buf())
+                .or_insert_with(|| ArchiveEntry::Dir(BTreeSet::new()));
+            if let ArchiveEntry::Dir(children) = entry
+                && let Some(name) = child.file_name()
+            {
+                children.insert(name.to_owned());
+            }
+            child = parent;
+        }
+    }
+
+    fn metadata(&self, abs_path: &Path, entry_path: &Path) -> Metadata {
+        let mut hasher = DefaultHasher::new();
+        abs_path.hash(&mut hasher);
+        let (is_dir, len) = match &self.entries[entry_path] {
+            ArchiveEntry::Dir(_) => (true, 0),
+            ArchiveEntry::File(contents) => (false, contents.len() as u64),
+        };
+        Metadata {
+            inode: hasher.finish(),
+            mtime: self.mtime,
+            is_symlink: false,
+            is_dir,
+            len,
+            is_fifo: false,
+            is_executable: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ArchiveFileHandle {
+    path: PathBuf,
+}
+
+impl FileHandle for ArchiveFileHandle {
+    fn current_path(&self, _: &Arc<dyn Fs>) -> Result<PathBuf> {
+        Ok(self.path.clone())
+    }
+}
+
+struct ArchiveWatcher;
+
+impl Watcher for ArchiveWatcher {
+    fn add(&self, _: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    fn remove(&self, _: &Path) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Fs for ArchiveFs {
+    async fn create_dir(&self, path: &Path) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.create_dir(path).await
+    }
+
+    async fn create_symlink(&self, path: &Path, target: PathBuf) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.create_symlink(path, target).await
+    }
+
+    async fn create_file(&self, path: &Path, options: CreateOptions) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.create_file(path, options).await
+    }
+
+    async fn create_file_with(
+        &self,
+        path: &Path,
+        content: Pin<&mut (dyn AsyncRead + Send)>,
+    ) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.create_file_with(path, content).await
+    }
+
+    async fn extract_tar_file(
+        &self,
+        path: &Path,
+        content: Archive<Pin<&mut (dyn AsyncRead + Send)>>,
+    ) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.extract_tar_file(path, content).await
+    }
+
+    async fn copy_file(&self, source: &Path, target: &Path, options: CopyOptions) -> Result<()> {
+        self.ensure_writable(target).await?;
+        match self.file_contents(source).await {
+            Some(contents) => {
+                let contents = contents?;
+                if self.fs.metadata(target).await?.is_some() {
+                    if options.ignore_if_exists {
+                        return Ok(());
+                    }
+                    anyhow::ensure!(options.overwrite, "{target:?} already exists");
+                }
+                self.fs.write(target, &contents).await
+            }
+            None => self.fs.copy_file(source, target, options).await,
+        }
+    }
+
+    async fn rename(&self, source: &Path, target: &Path, options: RenameOptions) -> Result<()> {
+        self.ensure_writable(source).await?;
+        self.ensure_writable(target).await?;
+        self.fs.rename(source, target, options).await
+    }
+
+    async fn remove_dir(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.remove_dir(path, options).await
+    }
+
+    async fn trash_dir(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.trash_dir(path, options).await
+    }
+
+    async fn remove_file(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.remove_file(path, options).await
+    }
+
+    async fn trash_file(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.trash_file(path, options).await
+    }
+
+    async fn open_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>> {
+        match self.entry(path).await {
+            Some(entry) => {
+                entry?.with_context(|| format!("{path:?} not found in archive"))?;
+                Ok(Arc::new(ArchiveFileHandle {
+                    path: path.to_path_buf(),
+                }))
+            }
+            None => self.fs.open_handle(path).await,
+        }
+    }
+
+    async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read + Send + Sync>> {
+        match self.file_contents(path).await {
+            Some(contents) => Ok(Box::new(io::Cursor::new(contents?.to_vec()))),
+            None => self.fs.open_sync(path).await,
+        }
+    }
+
+    async fn load(&self, path: &Path) -> Result<String> {
+        match self.file_contents(path).await {
+            Some(contents) => Ok(String::from_utf8(contents?.to_vec())?),
+            None => self.fs.load(path).await,
+        }
+    }
+
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        match self.file_contents(path).await {
+            Some(contents) => Ok(contents?.to_vec()),
+            None => self.fs.load_bytes(path).await,
+        }
+    }
+
+    async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()> {
+        self.ensure_writable(&path).await?;
+        self.fs.atomic_write(path, text).await
+    }
+
+    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.save(path, text, line_ending).await
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> Result<()> {
+        self.ensure_writable(path).await?;
+        self.fs.write(path, content).await
+    }
+
+    async fn canonicalize(&self, path: &Path) -> Result<PathBuf> {
+        match self.split_archive_path(path).await {
+            Some((archive_path, metadata, entry_path)) => {
+                let index = self.index(&archive_path, &metadata).await?;
+                anyhow::ensure!(
+                    index.entries.contains_key(&entry_path),
+                    "{path:?} not found in archive"
+                );
+                let archive_path = self.fs.canonicalize(&archive_path).await?;
+                let root = archive_root_path(&archive_path);
+                Ok(if entry_path.as_os_str().is_empty() {
+                    root
+                } else {
+                    root.join(entry_path)
+                })
+            }
+            None => self.fs.canonicalize(path).await,
+        }
+    }
+
+    async fn is_file(&self, path: &Path) -> bool {
+        match self.entry(path).await {
+            Some(Ok(Some((index, entry_path)))) => {
+                matches!(index.entries[&entry_path], ArchiveEntry::File(_))
+            }
+            Some(_) => false,
+            None => self.fs.is_file(path).await,
+        }
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        match self.entry(path).await {
+            Some(Ok(Some((index, entry_path)))) => {
+                matches!(index.entries[&entry_path], ArchiveEntry::Dir(_))
+            }
+            Some(_) => false,
+            None => self.fs.is_dir(path).await,
+        }
+    }
+
+    async fn metadata(&self, path: &Path) -> Result<Option<Metadata>> {
+        match self.entry(path).await {
+            Some(entry) => Ok(entry?.map(|(index, entry_path)| index.metadata(path, &entry_path))),
+            None => self.fs.metadata(path).await,
+        }
+    }
+
+    async fn read_link(&self, path: &Path) -> Result<PathBuf> {
+        if self.split_archive_path(path).await.is_some() {
+            anyhow::bail!("{path:?} is not a symlink");
+        }
+        self.fs.read_link(path).await
+    }
+
+    async fn read_dir(
+        &self,
+        path: &Path,
+    ) -> Result<Pin<Box<dyn Send + Stream<Item = Result<PathBuf>>>>> {
+        let Some(entry) = self.entry(path).await else {
+            return self.fs.read_dir(path).await;
+        };
+        let (index, entry_path) =
+            entry?.with_context(|| format!("{path:?} not found in archive"))?;
+        let ArchiveEntry::Dir(children) = &index.entries[&entry_path] else {
+            anyhow::bail!("{path:?} is not a directory");
+        };
+        let children = children
+            .iter()
+            .map(|name| Ok(path.join(name)))
+            .collect::<Vec<_>>();
+        Ok(Box::pin(stream::iter(children)))
+    }
+
+    async fn watch(
+        &self,
+        path: &Path,
+        latency: Duration,
+    ) -> (
+        Pin<Box<dyn Send + Stream<Item = Vec<PathEvent>>>>,
+        Arc<dyn Watcher>,
+    ) {
+        if self.split_archive_path(path).await.is_some() {
+            return (
+                Box::pin(stream::pending::<Vec<PathEvent>>()),
+                Arc::new(ArchiveWatcher),
+            );
+        }
+        self.fs.watch(path, latency).await
+    }
+
+    fn open_repo(
+        &self,
+        abs_dot_git: &Path,
+        system_git_binary_path: Option<&Path>,
+    ) -> Option<Arc<dyn GitRepository>> {
+        self.fs.open_repo(abs_dot_git, system_git_binary_path)
+    }
+
+    async fn git_init(
+        &self,
+        abs_work_directory: &Path,
+        fallback_branch_name: String,
+    ) -> Result<()> {
+        self.ensure_writable(abs_work_directory).await?;
+        self.fs
+            .git_init(abs_work_directory, fallback_branch_name)
+            .await
+    }
+
+    async fn git_clone(&self, repo_url: &str, abs_work_directory: &Path) -> Result<()> {
+        self.ensure_writable(abs_work_directory).await?;
+        self.fs.git_clone(repo_url, abs_work_directory).await
+    }
+
+    fn is_fake(&self) -> bool {
+        self.fs.is_fake()
+    }
+
+    async fn is_case_sensitive(&self) -> bool {
+        self.fs.is_case_sensitive().await
+    }
+
+    fn subscribe_to_jobs(&self) -> JobEventReceiver {
+        self.fs.subscribe_to_jobs()
+    }
+
+    #[cfg(feature = "test-support")]
+    fn as_fake(&self) -> Arc<crate::FakeFs> {
+        self.fs.as_fake()
+    }
+}
@@ -625,6 +625,8 @@ impl GitRepository for FakeGitRepository {
             let contents = join_all(contents).await;
             self.with_state_async(true, move |state| {
                 for (path, content) in contents {
+                    // Like `git add`, staging a conflicted file marks it as resolved.
+                    state.unmerged_paths.remove(&path);
                     if let Some(content) = content {
                         state.index_contents.insert(path, content);
                     } else {
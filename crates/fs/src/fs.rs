@@ -1,3 +1,4 @@
+pub mod archive_fs;
 pub mod fs_watcher;
 
 use parking_lot::Mutex;
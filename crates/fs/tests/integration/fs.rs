@@ -3,7 +3,9 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use fs::*;
+use async_zip::{ZipEntryBuilder, base::write::ZipFileWriter};
+use fs::{archive_fs::ArchiveFs, *};
+use futures::StreamExt as _;
 use gpui::BackgroundExecutor;
 use serde_json::json;
 use tempfile::TempDir;
@@ -562,3 +564,104 @@ async fn test_realfs_symlink_loop_metadata(executor: BackgroundExecutor) {
     assert!(!metadata.is_executable);
     // don't care about len or mtime on symlinks?
 }
+
+#[gpui::test]
+async fn test_archive_fs(executor: BackgroundExecutor) {
+    let fake_fs = FakeFs::new(executor.clone());
+    fake_fs.insert_tree(path!("/deps"), json!({})).await;
+
+    let mut archive = futures::io::Cursor::new(Vec::new());
+    let mut writer = ZipFileWriter::new(&mut archive);
+    for (name, contents) in [
+        ("com/example/Lib.java", "class Lib {}"),
+        ("META-INF/MANIFEST.MF", "Manifest-Version: 1.0"),
+    ] {
+        let builder = ZipEntryBuilder::new(name.into(), async_zip::Compression::Stored);
+        writer
+            .write_entry_whole(builder, contents.as_bytes())
+            .await
+            .unwrap();
+    }
+    writer.close().await.unwrap();
+    fake_fs
+        .insert_file(path!("/deps/lib.jar"), archive.into_inner())
+        .await;
+
+    let fs = ArchiveFs::new(fake_fs.clone(), executor);
+    let read_dir = async |path: &str| {
+        let mut paths = fs
+            .read_dir(Path::new(path))
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        paths.sort();
+        paths
+    };
+
+    // Archives are not expanded when listing the directory that contains them.
+    assert_eq!(
+        read_dir(path!("/deps")).await,
+        [PathBuf::from(path!("/deps/lib.jar"))]
+    );
+    assert!(fs.is_dir(Path::new(path!("/deps/lib.jar!"))).await);
+    assert_eq!(
+        read_dir(path!("/deps/lib.jar!")).await,
+        [
+            PathBuf::from(path!("/deps/lib.jar!/META-INF")),
+            PathBuf::from(path!("/deps/lib.jar!/com")),
+        ]
+    );
+    assert_eq!(
+        read_dir(path!("/deps/lib.jar!/com/example")).await,
+        [PathBuf::from(path!("/deps/lib.jar!/com/example/Lib.java"))]
+    );
+
+    let file_path = Path::new(path!("/deps/lib.jar!/com/example/Lib.java"));
+    assert_eq!(fs.load(file_path).await.unwrap(), "class Lib {}");
+    let metadata = fs.metadata(file_path).await.unwrap().unwrap();
+    assert!(!metadata.is_dir);
+    assert_eq!(metadata.len, "class Lib {}".len() as u64);
+    assert_eq!(fs.canonicalize(file_path).await.unwrap(), file_path);
+    assert!(
+        fs.metadata(Path::new(path!("/deps/lib.jar!/missing.java")))
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Archives are read-only, but their files can be copied out.
+    assert!(fs.write(file_path, b"class Lib { int x; }").await.is_err());
+    assert!(
+        fs.remove_file(file_path, RemoveOptions::default())
+            .await
+            .is_err()
+    );
+    fs.copy_file(
+        file_path,
+        Path::new(path!("/deps/Lib.java")),
+        CopyOptions::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        fake_fs
+            .load(Path::new(path!("/deps/Lib.java")))
+            .await
+            .unwrap(),
+        "class Lib {}"
+    );
+
+    // Paths are only treated as being inside an archive when the archive is a file.
+    fake_fs
+        .insert_tree(
+            path!("/deps"),
+            json!({ "dir.zip": {}, "dir.zip!": { "a.txt": "a" } }),
+        )
+        .await;
+    let file_path = Path::new(path!("/deps/dir.zip!/a.txt"));
+    assert_eq!(fs.load(file_path).await.unwrap(), "a");
+    fs.write(file_path, b"b").await.unwrap();
+    assert_eq!(fake_fs.load(file_path).await.unwrap(), "b");
+}
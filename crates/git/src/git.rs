@@ -50,6 +50,10 @@ actions!(
         StageFile,
         /// Unstages the current file.
         UnstageFile,
+        /// Opens the three-way merge editor for the current file's conflicts.
+        OpenMergeEditor,
+        /// Stages the file in the merge editor, marking its conflicts as resolved.
+        MarkResolvedAndStage,
         // repo-wide
         /// Stages all changes in the repository.
         StageAll,
@@ -93,6 +97,8 @@ actions!(
         ExpandCommitEditor,
         /// Generates a commit message using AI.
         GenerateCommitMessage,
+        /// Drafts a description for a pull request of the current branch using AI.
+        DraftPullRequestDescription,
         /// Initializes a new git repository.
         Init,
         /// Opens all modified files in the editor.
@@ -0,0 +1,237 @@
+use anyhow::{Context as _, Result};
+use regex::Regex;
+use std::fmt::Write as _;
+
+/// The path, relative to the repository root, of the file describing the
+/// project's commit message conventions.
+pub const COMMIT_MESSAGE_TEMPLATE_PATH: &str = ".zed/commit_message_template.md";
+
+/// The paths, relative to the repository root, where a pull request template
+/// is looked up, in order of priority.
+pub const PULL_REQUEST_TEMPLATE_PATHS: &[&str] = &[
+    ".github/pull_request_template.md",
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+];
+
+/// Matches ticket numbers like `ABC-123`.
+const DEFAULT_TICKET_PATTERN: &str = r"[A-Z][A-Z0-9]+-[0-9]+";
+
+/// Directories that group a project's packages, and so don't name a useful scope.
+const GROUPING_DIRECTORIES: &[&str] = &[
+    "apps", "crates", "lib", "libs", "modules", "packages", "services", "src",
+];
+
+/// A project's commit message conventions.
+///
+/// The template file starts with an optional frontmatter block delimited by
+/// `---` lines, where `conventional_commits: true` requests Conventional
+/// Commits subjects and `ticket_pattern` overrides the regex used to extract
+/// ticket numbers from branch names. The rest of the file is included in the
+/// prompt as-is, so it can hold a template or further instructions.
+#[derive(Debug)]
+pub struct CommitMessageTemplate {
+    pub conventional_commits: bool,
+    pub ticket_pattern: Regex,
+    pub instructions: String,
+}
+
+impl CommitMessageTemplate {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut conventional_commits = false;
+        let mut ticket_pattern = None;
+        let mut instructions = text;
+
+        if let Some((frontmatter, body)) = util::markdown::split_frontmatter(text) {
+            instructions = body;
+            for line in frontmatter.lines() {
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+                let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                match key.trim() {
+                    "conventional_commits" => conventional_commits = value == "true",
+                    "ticket_pattern" => {
+                        ticket_pattern = Some(
+                            Regex::new(value)
+                                .with_context(|| format!("invalid ticket_pattern {value:?}"))?,
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Self {
+            conventional_commits,
+            ticket_pattern: match ticket_pattern {
+                Some(ticket_pattern) => ticket_pattern,
+                None => Regex::new(DEFAULT_TICKET_PATTERN)?,
+            },
+            instructions: instructions.trim().to_string(),
+        })
+    }
+
+    /// Returns the ticket number referenced by the branch name, if any.
+    pub fn ticket_for_branch<'a>(&self, branch: &'a str) -> Option<&'a str> {
+        self.ticket_pattern
+            .find(branch)
+            .map(|ticket| ticket.as_str())
+    }
+
+    /// Describes the conventions to the model, given the current branch and
+    /// the paths changed by the commit.
+    pub fn prompt_section<'a>(
+        &self,
+        branch: Option<&str>,
+        changed_paths: impl IntoIterator<Item = &'a str>,
+    ) -> String {
+        let mut section = String::from(
+            "\n\nThis project has the following commit message conventions, which take precedence over the guidelines above:\n",
+        );
+
+        if self.conventional_commits {
+            section.push_str(
+                "- Use the Conventional Commits format for the subject line: `<type>(<scope>): <description>`, where the type is one of feat, fix, docs, style, refactor, perf, test, build, ci, chore or revert, and the description starts with a lowercase letter\n",
+            );
+            match detect_scope(changed_paths) {
+                Some(scope) => {
+                    writeln!(
+                        section,
+                        "- All the changes are in `{scope}`, so use `{scope}` as the scope"
+                    )
+                    .ok();
+                }
+                None => section.push_str(
+                    "- Omit the scope unless the changes clearly belong to a single area of the project\n",
+                ),
+            }
+        }
+
+        if let Some(branch) = branch
+            && let Some(ticket) = self.ticket_for_branch(branch)
+        {
+            writeln!(
+                section,
+                "- The current branch `{branch}` is for ticket `{ticket}`, so reference it in the commit message, where the template below places it if it says so"
+            )
+            .ok();
+        }
+
+        if !self.instructions.is_empty() {
+            writeln!(
+                section,
+                "\nFollow this commit message template:\n<commit_message_template>\n{}\n</commit_message_template>",
+                self.instructions
+            )
+            .ok();
+        }
+
+        section
+    }
+}
+
+/// Returns the paths changed by a unified diff, as they are after the change.
+pub fn changed_paths(diff: &str) -> Vec<&str> {
+    diff.lines()
+        .filter_map(|line| line.strip_prefix("diff --git "))
+        .filter_map(|paths| {
+            let (_, new_path) = paths.split_once(" b/")?;
+            Some(new_path)
+        })
+        .collect()
+}
+
+/// Detects the area of the project that all the given paths belong to, such
+/// as `editor` for paths under `crates/editor`.
+pub fn detect_scope<'a>(paths: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let mut common_directories: Option<Vec<&str>> = None;
+    for path in paths {
+        let mut directories = path.split('/').collect::<Vec<_>>();
+        // Drop the file name.
+        directories.pop();
+        common_directories = Some(match common_directories {
+            None => directories,
+            Some(common) => common
+                .into_iter()
+                .zip(directories)
+                .take_while(|(common, directory)| common == directory)
+                .map(|(common, _)| common)
+                .collect(),
+        });
+    }
+
+    common_directories?
+        .into_iter()
+        .find(|directory| !GROUPING_DIRECTORIES.contains(directory))
+        .map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_template() {
+        let template = CommitMessageTemplate::parse(
+            "---\nconventional_commits: true\nticket_pattern: \"#[0-9]+\"\n---\n\n<type>: <subject>\n\nRefs: <ticket>\n",
+        )
+        .unwrap();
+        assert!(template.conventional_commits);
+        assert_eq!(template.ticket_for_branch("fix/#42-crash"), Some("#42"));
+        assert_eq!(template.instructions, "<type>: <subject>\n\nRefs: <ticket>");
+
+        let template = CommitMessageTemplate::parse("Mention the ticket in the body.").unwrap();
+        assert!(!template.conventional_commits);
+        assert_eq!(
+            template.ticket_for_branch("feature/PROJ-1234-add-login"),
+            Some("PROJ-1234")
+        );
+        assert_eq!(template.ticket_for_branch("main"), None);
+        assert_eq!(template.instructions, "Mention the ticket in the body.");
+
+        let template = CommitMessageTemplate::parse(
+            "---\r\nconventional_commits: true\r\n---\r\nKeep subjects short.\r\n",
+        )
+        .unwrap();
+        assert!(template.conventional_commits);
+        assert_eq!(template.instructions, "Keep subjects short.");
+
+        assert!(CommitMessageTemplate::parse("---\nticket_pattern: (\n---\n").is_err());
+    }
+
+    #[test]
+    fn test_changed_paths() {
+        let diff = "diff --git a/crates/editor/src/editor.rs b/crates/editor/src/editor.rs\n\
+            index 1234567..89abcde 100644\n\
+            --- a/crates/editor/src/editor.rs\n\
+            +++ b/crates/editor/src/editor.rs\n\
+            @@ -1 +1 @@\n\
+            -old\n\
+            +new\n\
+            diff --git a/old_name.md b/docs/new_name.md\n";
+        assert_eq!(
+            changed_paths(diff),
+            vec!["crates/editor/src/editor.rs", "docs/new_name.md"]
+        );
+    }
+
+    #[test]
+    fn test_detect_scope() {
+        assert_eq!(
+            detect_scope(["crates/editor/src/editor.rs", "crates/editor/Cargo.toml"]),
+            Some("editor".to_string())
+        );
+        assert_eq!(
+            detect_scope(["docs/src/ai/rules.md", "docs/src/SUMMARY.md"]),
+            Some("docs".to_string())
+        );
+        assert_eq!(
+            detect_scope(["crates/editor/src/editor.rs", "crates/git_ui/src/git_ui.rs"]),
+            None
+        );
+        assert_eq!(detect_scope(["README.md"]), None);
+        assert_eq!(detect_scope(Vec::<&str>::new()), None);
+    }
+}
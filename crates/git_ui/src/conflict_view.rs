@@ -14,6 +14,8 @@ use std::{ops::Range, sync::Arc};
 use ui::{ActiveTheme, Element as _, Styled, Window, prelude::*};
 use util::{ResultExt as _, debug_panic, maybe};
 
+use crate::merge_view::MergeView;
+
 pub(crate) struct ConflictAddon {
     buffers: HashMap<BufferId, BufferConflicts>,
 }
@@ -419,6 +421,7 @@ fn render_conflict_buttons(
             Button::new("both", "Use Both")
                 .label_size(LabelSize::Small)
                 .on_click({
+                    let editor = editor.clone();
                     let conflict = conflict.clone();
                     let ours = conflict.ours.clone();
                     let theirs = conflict.theirs.clone();
@@ -435,6 +438,29 @@ fn render_conflict_buttons(
                     }
                 }),
         )
+        .child(
+            Button::new("merge-editor", "Open Merge Editor")
+                .label_size(LabelSize::Small)
+                .on_click({
+                    let buffer_id = conflict.range.start.buffer_id;
+                    move |_, window, cx| {
+                        let Some((workspace, buffer)) = editor
+                            .read_with(cx, |editor, cx| {
+                                let workspace = editor.workspace()?;
+                                let buffer = editor.buffer().read(cx).buffer(buffer_id?)?;
+                                Some((workspace, buffer))
+                            })
+                            .ok()
+                            .flatten()
+                        else {
+                            return;
+                        };
+                        workspace.update(cx, |workspace, cx| {
+                            MergeView::deploy(buffer, workspace, window, cx)
+                        });
+                    }
+                }),
+        )
         .into_any()
 }
 
@@ -1,4 +1,7 @@
 use crate::askpass_modal::AskPassModal;
+use crate::commit_message_template::{
+    COMMIT_MESSAGE_TEMPLATE_PATH, CommitMessageTemplate, PULL_REQUEST_TEMPLATE_PATHS, changed_paths,
+};
 use crate::commit_modal::CommitModal;
 use crate::commit_tooltip::CommitTooltip;
 use crate::commit_view::CommitView;
@@ -77,7 +80,9 @@ use workspace::SERIALIZATION_THROTTLE_TIME;
 use workspace::{
     Workspace,
     dock::{DockPosition, Panel, PanelEvent},
-    notifications::{DetachAndPromptErr, ErrorMessagePrompt, NotificationId, NotifyResultExt},
+    notifications::{
+        DetachAndPromptErr, ErrorMessagePrompt, NotificationId, NotifyResultExt, NotifyTaskExt,
+    },
 };
 
 actions!(
@@ -2624,7 +2629,17 @@ impl GitPanel {
         repo_work_dir: &Arc<Path>,
         cx: &mut AsyncApp,
     ) -> Option<String> {
-        let rules_path = cx.update(|cx| {
+        Self::load_repository_file(project, repo_work_dir, RULES_FILE_NAMES, cx).await
+    }
+
+    /// Loads the first of the given files that exists in the repository, if it isn't empty.
+    async fn load_repository_file(
+        project: &Entity<Project>,
+        repo_work_dir: &Arc<Path>,
+        paths: &[&str],
+        cx: &mut AsyncApp,
+    ) -> Option<String> {
+        let file_path = cx.update(|cx| {
             for worktree in project.read(cx).worktrees(cx) {
                 let worktree_abs_path = worktree.read(cx).abs_path();
                 if !worktree_abs_path.starts_with(&repo_work_dir) {
@@ -2632,8 +2647,8 @@ impl GitPanel {
                 }
 
                 let worktree_snapshot = worktree.read(cx).snapshot();
-                for rules_name in RULES_FILE_NAMES {
-                    if let Ok(rel_path) = RelPath::unix(rules_name) {
+                for path in paths {
+                    if let Ok(rel_path) = RelPath::unix(path) {
                         if let Some(entry) = worktree_snapshot.entry_for_path(rel_path) {
                             if entry.is_file() {
                                 return Some(ProjectPath {
@@ -2649,7 +2664,7 @@ impl GitPanel {
         })?;
 
         let buffer = project
-            .update(cx, |project, cx| project.open_buffer(rules_path, cx))
+            .update(cx, |project, cx| project.open_buffer(file_path, cx))
             .await
             .ok()?;
 
@@ -2665,18 +2680,16 @@ impl GitPanel {
         }
     }
 
-    async fn load_commit_message_prompt(cx: &mut AsyncApp) -> String {
+    async fn load_built_in_prompt(prompt: BuiltInPrompt, cx: &mut AsyncApp) -> String {
         let load = async {
             let store = cx.update(|cx| PromptStore::global(cx)).await.ok()?;
             store
-                .update(cx, |s, cx| {
-                    s.load(PromptId::BuiltIn(BuiltInPrompt::CommitMessage), cx)
-                })
+                .update(cx, |s, cx| s.load(PromptId::BuiltIn(prompt), cx))
                 .await
                 .ok()
         };
         load.await
-            .unwrap_or_else(|| BuiltInPrompt::CommitMessage.default_content().to_string())
+            .unwrap_or_else(|| prompt.default_content().to_string())
     }
 
     /// Generates a commit message using an LLM.
@@ -2708,6 +2721,11 @@ impl GitPanel {
         let temperature = AgentSettings::temperature_for_model(&model, cx);
         let project = self.project.clone();
         let repo_work_dir = repo.read(cx).work_directory_abs_path.clone();
+        let branch_name = repo
+            .read(cx)
+            .branch
+            .as_ref()
+            .map(|branch| branch.name().to_string());
 
         self.generate_commit_message_task = Some(cx.spawn(async move |this, mut cx| {
              async move {
@@ -2739,12 +2757,30 @@ impl GitPanel {
                     }
                 };
 
+                let template_section = match Self::load_repository_file(
+                    &project,
+                    &repo_work_dir,
+                    &[COMMIT_MESSAGE_TEMPLATE_PATH],
+                    &mut cx,
+                )
+                .await
+                .map(|template| CommitMessageTemplate::parse(&template))
+                {
+                    Some(Ok(template)) => template
+                        .prompt_section(branch_name.as_deref(), changed_paths(&diff_text)),
+                    Some(Err(e)) => {
+                        Self::show_commit_message_error(&this, &e, cx);
+                        return anyhow::Ok(());
+                    }
+                    None => String::new(),
+                };
+
                 const MAX_DIFF_BYTES: usize = 20_000;
                 diff_text = Self::compress_commit_diff(&diff_text, MAX_DIFF_BYTES);
 
                 let rules_content = Self::load_project_rules(&project, &repo_work_dir, &mut cx).await;
 
-                let prompt = Self::load_commit_message_prompt(&mut cx).await;
+                let prompt = Self::load_built_in_prompt(BuiltInPrompt::CommitMessage, &mut cx).await;
 
                 let subject = this.update(cx, |this, cx| {
                     this.commit_editor.read(cx).text(cx).lines().next().map(ToOwned::to_owned).unwrap_or_default()
@@ -2767,7 +2803,7 @@ impl GitPanel {
                 };
 
                 let content = format!(
-                    "{prompt}{rules_section}{subject_section}\nHere are the changes in this commit:\n{diff_text}"
+                    "{prompt}{rules_section}{template_section}{subject_section}\nHere are the changes in this commit:\n{diff_text}"
                 );
 
                 let request = LanguageModelRequest {
@@ -2786,6 +2822,7 @@ impl GitPanel {
                     temperature,
                     thinking_allowed: false,
                     thinking_effort: None,
+                    max_tokens: None,
                 };
 
                 let stream = model.stream_completion_text(request, cx);
@@ -2828,6 +2865,165 @@ impl GitPanel {
         }));
     }
 
+    /// Drafts a description for a pull request of the current branch into a
+    /// new Markdown buffer, using an LLM to summarize the changes since the
+    /// default branch.
+    pub fn draft_pull_request_description(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !AgentSettings::get_global(cx).enabled(cx) {
+            return;
+        }
+
+        let Some(ConfiguredModel { provider, model }) =
+            LanguageModelRegistry::read_global(cx).commit_message_model()
+        else {
+            return;
+        };
+
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+
+        telemetry::event!("Git Pull Request Description Drafted");
+
+        let temperature = AgentSettings::temperature_for_model(&model, cx);
+        let project = self.project.clone();
+        let workspace = self.workspace.clone();
+        let repo_work_dir = repo.read(cx).work_directory_abs_path.clone();
+        let branch_name = repo
+            .read(cx)
+            .branch
+            .as_ref()
+            .map(|branch| branch.name().to_string());
+        let default_branch = repo.update(cx, |repo, _| repo.default_branch(true));
+        let markdown = project.read(cx).languages().language_for_name("Markdown");
+
+        cx.spawn_in(window, async move |_, cx| {
+            if let Some(task) = cx.update(|_, cx| {
+                if !provider.is_authenticated(cx) {
+                    Some(provider.authenticate(cx))
+                } else {
+                    None
+                }
+            })? {
+                task.await.log_err();
+            }
+
+            let default_branch = default_branch
+                .await??
+                .context("Could not determine the default branch")?;
+            let diff_text = repo
+                .update(cx, |repo, cx| {
+                    repo.diff(
+                        DiffType::MergeBase {
+                            base_ref: default_branch.clone(),
+                        },
+                        cx,
+                    )
+                })
+                .await??;
+            anyhow::ensure!(
+                !diff_text.trim().is_empty(),
+                "There are no changes since {default_branch}"
+            );
+
+            const MAX_DIFF_BYTES: usize = 40_000;
+            let diff_text = Self::compress_commit_diff(&diff_text, MAX_DIFF_BYTES);
+
+            let prompt = Self::load_built_in_prompt(BuiltInPrompt::PullRequestDescription, cx).await;
+            let rules_section = match Self::load_project_rules(&project, &repo_work_dir, cx).await {
+                Some(rules) => format!(
+                    "\n\nThe user has provided the following project rules that you should follow when writing the description:\n\
+                    <project_rules>\n{rules}\n</project_rules>\n"
+                ),
+                None => String::new(),
+            };
+            let template_section = match Self::load_repository_file(
+                &project,
+                &repo_work_dir,
+                PULL_REQUEST_TEMPLATE_PATHS,
+                cx,
+            )
+            .await
+            {
+                Some(template) => format!(
+                    "\n\nFill in this project's pull request template, and skip any sections that don't apply to the changes:\n\
+                    <pull_request_template>\n{template}\n</pull_request_template>\n"
+                ),
+                None => String::new(),
+            };
+            let ticket_section = Self::load_repository_file(
+                &project,
+                &repo_work_dir,
+                &[COMMIT_MESSAGE_TEMPLATE_PATH],
+                cx,
+            )
+            .await
+            .and_then(|template| CommitMessageTemplate::parse(&template).log_err())
+            .zip(branch_name.as_deref())
+            .and_then(|(template, branch)| {
+                let ticket = template.ticket_for_branch(branch)?;
+                Some(format!(
+                    "\nThe branch is for ticket `{ticket}`, so reference it in the description.\n"
+                ))
+            })
+            .unwrap_or_default();
+            let branch_section = match &branch_name {
+                Some(branch) => format!("\nThe pull request merges `{branch}` into `{default_branch}`."),
+                None => format!("\nThe pull request merges into `{default_branch}`."),
+            };
+
+            let content = format!(
+                "{prompt}{rules_section}{template_section}{ticket_section}{branch_section}\nHere are the changes:\n{diff_text}"
+            );
+
+            let request = LanguageModelRequest {
+                thread_id: None,
+                prompt_id: None,
+                intent: Some(CompletionIntent::GenerateGitCommitMessage),
+                messages: vec![LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: vec![content.into()],
+                    cache: false,
+                    reasoning_details: None,
+                }],
+                tools: Vec::new(),
+                tool_choice: None,
+                stop: Vec::new(),
+                temperature,
+                thinking_allowed: false,
+                thinking_effort: None,
+                max_tokens: None,
+            };
+
+            let markdown = markdown.await.log_err();
+            let buffer = project
+                .update(cx, |project, cx| project.create_buffer(markdown, false, cx))
+                .await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let multibuffer = cx.new(|cx| {
+                    MultiBuffer::singleton(buffer.clone(), cx)
+                        .with_title("Pull Request Description".to_string())
+                });
+                let editor = cx.new(|cx| {
+                    Editor::for_multibuffer(multibuffer, Some(project.clone()), window, cx)
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+            })?;
+
+            let mut messages = model.stream_completion_text(request, cx).await?;
+            while let Some(text) = messages.stream.next().await {
+                let text = text?;
+                buffer.update(cx, |buffer, cx| {
+                    let insert_position = buffer.len();
+                    buffer.edit([(insert_position..insert_position, text)], None, cx);
+                });
+            }
+
+            anyhow::Ok(())
+        })
+        .detach_and_notify_err(self.workspace.clone(), window, cx);
+    }
+
     fn get_fetch_options(
         &self,
         window: &mut Window,
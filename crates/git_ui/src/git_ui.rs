@@ -29,6 +29,7 @@ use crate::{git_panel::GitPanel, text_diff_view::TextDiffView};
 
 mod askpass_modal;
 pub mod branch_picker;
+mod commit_message_template;
 mod commit_modal;
 pub mod commit_tooltip;
 pub mod commit_view;
@@ -38,6 +39,7 @@ pub mod file_history_view;
 pub mod git_panel;
 mod git_panel_settings;
 pub mod git_picker;
+pub mod merge_view;
 pub mod multi_diff_view;
 pub mod picker_prompt;
 pub mod project_diff;
@@ -67,6 +69,7 @@ pub fn init(cx: &mut App) {
         if project.is_read_only(cx) {
             return;
         }
+        merge_view::MergeView::register(workspace);
         if !project.is_via_collab() {
             workspace.register_action(
                 |workspace, _: &zed_actions::git::CreatePullRequest, window, cx| {
@@ -77,6 +80,15 @@ pub fn init(cx: &mut App) {
                     }
                 },
             );
+            workspace.register_action(
+                |workspace, _: &git::DraftPullRequestDescription, window, cx| {
+                    if let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.draft_pull_request_description(window, cx);
+                        });
+                    }
+                },
+            );
             workspace.register_action(|workspace, _: &git::Fetch, window, cx| {
                 let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
                     return;
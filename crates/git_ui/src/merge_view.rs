@@ -0,0 +1,984 @@
+//! MergeView shows the base, ours and theirs versions of a conflicted file side by side,
+//! above the merge result.
+
+use anyhow::Result;
+use editor::{
+    Bias, DisplayPoint, Editor, EditorEvent, HighlightKey, RowHighlightOptions,
+    display_map::{BlockPlacement, BlockProperties, BlockStyle, DisplayRow},
+};
+use git::MarkResolvedAndStage;
+use gpui::{
+    AnyElement, App, AppContext as _, Context, Empty, Entity, EntityId, EventEmitter, FocusHandle,
+    Focusable, IntoElement, PromptLevel, Render, Subscription, Task, WeakEntity, Window, point,
+};
+use language::{
+    Anchor, Buffer, BufferEvent, BufferSnapshot, DiffOptions, Point, ToOffset as _, ToPoint as _,
+    word_diff_ranges,
+};
+use project::{ConflictRegion, ConflictSet, Project, ProjectItem as _};
+use std::{any::TypeId, ops::Range, sync::Arc};
+use ui::{Divider, prelude::*};
+use util::paths::PathExt as _;
+use workspace::{
+    Item, ItemHandle as _, Toast, Workspace,
+    item::{ItemEvent, SaveOptions},
+    notifications::{DetachAndPromptErr, NotificationId},
+};
+
+/// One of the versions of a file that were merged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MergeSide {
+    Base,
+    Ours,
+    Theirs,
+}
+
+impl MergeSide {
+    const ALL: [Self; 3] = [Self::Base, Self::Ours, Self::Theirs];
+
+    fn range(self, conflict: &ConflictRegion) -> Option<Range<Anchor>> {
+        match self {
+            Self::Base => conflict.base.clone(),
+            Self::Ours => Some(conflict.ours.clone()),
+            Self::Theirs => Some(conflict.theirs.clone()),
+        }
+    }
+}
+
+/// A conflict of the file, as it was when the merge view was opened.
+struct MergeHunk {
+    /// The conflict's range in the merge result, which shrinks to the resolved text
+    /// once the conflict is resolved.
+    result_range: Range<Anchor>,
+    /// The rows of the conflict in the base, ours and theirs editors.
+    side_rows: [Range<u32>; 3],
+}
+
+enum MergeHunkHighlight {}
+
+pub struct MergeView {
+    buffer: Entity<Buffer>,
+    project: Entity<Project>,
+    result_editor: Entity<Editor>,
+    side_editors: [Entity<Editor>; 3],
+    hunks: Vec<MergeHunk>,
+    conflicts: Arc<[ConflictRegion]>,
+    ours_branch_name: SharedString,
+    theirs_branch_name: SharedString,
+    /// The scroll positions that editors were scrolled to in order to follow another
+    /// editor, to tell them apart from scrolling by the user.
+    expected_scroll_tops: Vec<(EntityId, f64)>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl MergeView {
+    pub fn register(workspace: &mut Workspace) {
+        workspace.register_action(|workspace, _: &git::OpenMergeEditor, window, cx| {
+            let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+                return;
+            };
+            let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+                return;
+            };
+            Self::deploy(buffer, workspace, window, cx);
+        });
+    }
+
+    /// Opens the merge view for a conflicted buffer, or activates the one that is already open.
+    pub fn deploy(
+        buffer: Entity<Buffer>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let existing = workspace
+            .items_of_type::<Self>(cx)
+            .find(|view| view.read(cx).buffer.read(cx).remote_id() == buffer_id);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let conflicts = ConflictSet::parse(&buffer.read(cx).text_snapshot()).conflicts;
+        if conflicts.is_empty() {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<Self>(),
+                    "This file has no merge conflicts",
+                )
+                .autohide(),
+                cx,
+            );
+            return;
+        }
+
+        let project = workspace.project().clone();
+        let view = cx.new(|cx| Self::new(buffer, conflicts, project, window, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+    }
+
+    fn new(
+        buffer: Entity<Buffer>,
+        conflicts: Arc<[ConflictRegion]>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let snapshot = buffer.read(cx).snapshot();
+        let language = snapshot.language().cloned();
+        let result_editor =
+            cx.new(|cx| Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx));
+
+        let mut hunks = conflicts
+            .iter()
+            .map(|conflict| MergeHunk {
+                result_range: conflict.range.clone(),
+                side_rows: Default::default(),
+            })
+            .collect::<Vec<_>>();
+        let side_editors = MergeSide::ALL.map(|side| {
+            let (text, hunk_offsets) = side_text(&snapshot, &conflicts, side);
+            let side_buffer = cx.new(|cx| {
+                let mut buffer = Buffer::local(text, cx);
+                buffer.set_language(language.clone(), cx);
+                buffer
+            });
+            let side_snapshot = side_buffer.read(cx).snapshot();
+            for (hunk, offsets) in hunks.iter_mut().zip(&hunk_offsets) {
+                hunk.side_rows[side as usize] = side_snapshot.offset_to_point(offsets.start).row
+                    ..side_snapshot.offset_to_point(offsets.end).row;
+            }
+            cx.new(|cx| {
+                let mut editor = Editor::for_buffer(side_buffer, None, window, cx);
+                editor.set_read_only(true);
+                editor
+            })
+        });
+
+        let mut subscriptions = vec![cx.subscribe(&buffer, |this, buffer, event, cx| {
+            if let BufferEvent::Edited = event {
+                this.conflicts = ConflictSet::parse(&buffer.read(cx).text_snapshot()).conflicts;
+                cx.notify();
+            }
+        })];
+        for editor in side_editors.iter().chain([&result_editor]) {
+            subscriptions.push(cx.subscribe_in(
+                editor,
+                window,
+                |this, editor, event, window, cx| {
+                    if let EditorEvent::ScrollPositionChanged { local: true, .. } = event {
+                        this.sync_scroll(editor, window, cx);
+                    }
+                },
+            ));
+        }
+
+        let first_conflict = conflicts.first();
+        let mut this = Self {
+            ours_branch_name: first_conflict
+                .map(|conflict| conflict.ours_branch_name.clone())
+                .unwrap_or_else(|| "Ours".into()),
+            theirs_branch_name: first_conflict
+                .map(|conflict| conflict.theirs_branch_name.clone())
+                .unwrap_or_else(|| "Theirs".into()),
+            buffer,
+            project,
+            result_editor,
+            side_editors,
+            hunks,
+            conflicts: conflicts.clone(),
+            expected_scroll_tops: Vec::new(),
+            _subscriptions: subscriptions,
+        };
+        this.highlight_side_hunks(&snapshot, &conflicts, cx);
+        this
+    }
+
+    /// Highlights the conflicts in the side editors, along with the words that differ
+    /// between the sides, and adds buttons for accepting them above each conflict.
+    fn highlight_side_hunks(
+        &mut self,
+        snapshot: &BufferSnapshot,
+        conflicts: &[ConflictRegion],
+        cx: &mut Context<Self>,
+    ) {
+        let hunk_texts = conflicts
+            .iter()
+            .map(|conflict| {
+                MergeSide::ALL.map(|side| {
+                    side.range(conflict)
+                        .map(|range| snapshot.text_for_range(range).collect::<String>())
+                        .unwrap_or_default()
+                })
+            })
+            .collect::<Vec<_>>();
+        let this = cx.weak_entity();
+
+        for side in MergeSide::ALL {
+            let color = match side {
+                MergeSide::Base => cx.theme().colors().editor_active_line_background,
+                MergeSide::Ours => cx.theme().colors().version_control_conflict_marker_ours,
+                MergeSide::Theirs => cx.theme().colors().version_control_conflict_marker_theirs,
+            };
+            let editor = self.side_editors[side as usize].clone();
+            editor.update(cx, |editor, cx| {
+                let multibuffer = editor.buffer().read(cx).snapshot(cx);
+                let mut word_ranges = Vec::new();
+                let mut blocks = Vec::new();
+                for (ix, (hunk, [base, ours, theirs])) in
+                    self.hunks.iter().zip(&hunk_texts).enumerate()
+                {
+                    let rows = &hunk.side_rows[side as usize];
+                    let start = multibuffer.anchor_after(Point::new(rows.start, 0));
+                    let end = multibuffer.anchor_before(Point::new(rows.end, 0));
+                    editor.highlight_rows::<MergeHunkHighlight>(
+                        start..end,
+                        color,
+                        RowHighlightOptions {
+                            include_gutter: true,
+                            ..Default::default()
+                        },
+                        cx,
+                    );
+
+                    let changed_ranges = match side {
+                        MergeSide::Base => {
+                            let mut ranges = word_diff_ranges(base, ours, DiffOptions::default()).0;
+                            ranges.extend(word_diff_ranges(base, theirs, DiffOptions::default()).0);
+                            ranges
+                        }
+                        MergeSide::Ours => word_diff_ranges(ours, theirs, DiffOptions::default()).0,
+                        MergeSide::Theirs => {
+                            word_diff_ranges(ours, theirs, DiffOptions::default()).1
+                        }
+                    };
+                    let hunk_start = multibuffer.point_to_offset(Point::new(rows.start, 0));
+                    word_ranges.extend(changed_ranges.into_iter().map(|range| {
+                        multibuffer.anchor_after(hunk_start + range.start)
+                            ..multibuffer.anchor_before(hunk_start + range.end)
+                    }));
+
+                    let this = this.clone();
+                    blocks.push(BlockProperties {
+                        placement: BlockPlacement::Above(start),
+                        height: Some(1),
+                        style: BlockStyle::Fixed,
+                        render: Arc::new(move |cx| render_accept_buttons(&this, ix, side, cx)),
+                        priority: 0,
+                    });
+                }
+
+                editor.highlight_background(
+                    HighlightKey::MergeViewWordDiff,
+                    &word_ranges,
+                    move |_, theme| match side {
+                        MergeSide::Base => theme.colors().version_control_word_deleted,
+                        MergeSide::Ours | MergeSide::Theirs => {
+                            theme.colors().version_control_word_added
+                        }
+                    },
+                    cx,
+                );
+                editor.insert_blocks(blocks, None, cx);
+            });
+        }
+    }
+
+    /// Returns the conflict of a hunk in the merge result, unless it has been resolved.
+    fn unresolved_conflict(&self, hunk_ix: usize, cx: &App) -> Option<ConflictRegion> {
+        let hunk = self.hunks.get(hunk_ix)?;
+        let snapshot = self.buffer.read(cx).snapshot();
+        let start = hunk.result_range.start.to_offset(&snapshot);
+        self.conflicts
+            .iter()
+            .find(|conflict| conflict.range.start.to_offset(&snapshot) == start)
+            .cloned()
+    }
+
+    /// Resolves a hunk's conflict by keeping the given sides, in order.
+    fn accept(&mut self, hunk_ix: usize, sides: &[MergeSide], cx: &mut Context<Self>) {
+        let Some(conflict) = self.unresolved_conflict(hunk_ix, cx) else {
+            return;
+        };
+        let mut ranges = sides.iter().filter_map(|side| side.range(&conflict));
+        let Some(first) = ranges.next() else {
+            conflict.resolve(self.buffer.clone(), &[], cx);
+            return;
+        };
+        // Resolving only removes text, so the other sides are appended after the first
+        // one, which keeps their order even when it isn't their order in the file.
+        let snapshot = self.buffer.read(cx).snapshot();
+        let appended = ranges
+            .flat_map(|range| snapshot.text_for_range(range))
+            .collect::<String>();
+        self.buffer
+            .update(cx, |buffer, _| buffer.start_transaction());
+        conflict.resolve(self.buffer.clone(), std::slice::from_ref(&first), cx);
+        self.buffer.update(cx, |buffer, cx| {
+            if !appended.is_empty() {
+                buffer.edit([(first.end..first.end, appended)], None, cx);
+            }
+            buffer.end_transaction(cx);
+        });
+    }
+
+    fn mark_resolved_and_stage(
+        &mut self,
+        _: &MarkResolvedAndStage,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer_id = self.buffer.read(cx).remote_id();
+        let Some((repository, repo_path)) = self
+            .project
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer_id, cx)
+        else {
+            return;
+        };
+        let confirmation = match self.conflicts.len() {
+            0 => None,
+            remaining => Some(window.prompt(
+                PromptLevel::Warning,
+                &if remaining == 1 {
+                    "1 conflict is not resolved yet".to_string()
+                } else {
+                    format!("{remaining} conflicts are not resolved yet")
+                },
+                Some("Their conflict markers will be staged along with the rest of the file."),
+                &["Stage Anyway", "Cancel"],
+                cx,
+            )),
+        };
+        cx.spawn_in(window, async move |_, cx| {
+            if let Some(confirmation) = confirmation
+                && confirmation.await? != 0
+            {
+                return Ok(());
+            }
+            repository
+                .update(cx, |repository, cx| {
+                    repository.stage_entries(vec![repo_path], cx)
+                })
+                .await
+        })
+        .detach_and_prompt_err("Failed to stage file", window, cx, |_, _, _| None);
+    }
+
+    fn editors(&self) -> impl Iterator<Item = &Entity<Editor>> {
+        [&self.result_editor].into_iter().chain(&self.side_editors)
+    }
+
+    /// Scrolls the other editors to the rows that correspond to the top row of `source`.
+    fn sync_scroll(
+        &mut self,
+        source: &Entity<Editor>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let source_top = source.update(cx, |editor, cx| editor.scroll_position(cx).y);
+        if let Some(ix) = self
+            .expected_scroll_tops
+            .iter()
+            .position(|(id, _)| *id == source.entity_id())
+        {
+            let (_, expected_top) = self.expected_scroll_tops.swap_remove(ix);
+            if (expected_top - source_top).abs() < 0.01 {
+                return;
+            }
+        }
+
+        let result_snapshot = self.buffer.read(cx).snapshot();
+        let result_rows = self
+            .hunks
+            .iter()
+            .map(|hunk| {
+                hunk.result_range.start.to_point(&result_snapshot).row
+                    ..hunk.result_range.end.to_point(&result_snapshot).row
+            })
+            .collect::<Vec<_>>();
+        let rows = [result_rows].into_iter().chain(MergeSide::ALL.map(|side| {
+            self.hunks
+                .iter()
+                .map(|hunk| hunk.side_rows[side as usize].clone())
+                .collect::<Vec<_>>()
+        }));
+        let editors = self.editors().cloned().zip(rows).collect::<Vec<_>>();
+        let Some((_, source_rows)) = editors.iter().find(|(editor, _)| editor == source) else {
+            return;
+        };
+        let source_row = source.update(cx, |editor, cx| {
+            let snapshot = editor.display_snapshot(cx);
+            let display_row = source_top.floor();
+            let point = snapshot.display_point_to_point(
+                DisplayPoint::new(DisplayRow(display_row as u32), 0),
+                Bias::Left,
+            );
+            point.row as f64 + (source_top - display_row)
+        });
+
+        for (editor, target_rows) in &editors {
+            if editor == source {
+                continue;
+            }
+            let row = map_row(source_row, source_rows, target_rows);
+            let top = editor.update(cx, |editor, cx| {
+                let snapshot = editor.display_snapshot(cx);
+                let buffer_row = row.floor();
+                let max_row = snapshot.buffer_snapshot().max_point().row;
+                let display_point = snapshot.point_to_display_point(
+                    Point::new((buffer_row as u32).min(max_row), 0),
+                    Bias::Left,
+                );
+                let x = editor.scroll_position(cx).x;
+                editor.set_scroll_position(
+                    point(x, display_point.row().0 as f64 + (row - buffer_row)),
+                    window,
+                    cx,
+                );
+                editor.scroll_position(cx).y
+            });
+            self.expected_scroll_tops.push((editor.entity_id(), top));
+        }
+    }
+
+    fn render_pane(&self, title: SharedString, editor: &Entity<Editor>, cx: &App) -> AnyElement {
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .min_h_0()
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(title).size(LabelSize::Small).color(Color::Muted)),
+            )
+            .child(div().flex_1().min_h_0().child(editor.clone()))
+            .into_any_element()
+    }
+}
+
+/// Reconstructs one side of a conflicted file from its conflict markers, returning its
+/// text and the offsets of each conflict's hunk in it.
+fn side_text(
+    buffer: &BufferSnapshot,
+    conflicts: &[ConflictRegion],
+    side: MergeSide,
+) -> (String, Vec<Range<usize>>) {
+    let mut text = String::new();
+    let mut hunk_offsets = Vec::with_capacity(conflicts.len());
+    let mut offset = 0;
+    for conflict in conflicts {
+        let range = conflict.range.start.to_offset(buffer)..conflict.range.end.to_offset(buffer);
+        text.extend(buffer.text_for_range(offset..range.start));
+        let hunk_start = text.len();
+        if let Some(side_range) = side.range(conflict) {
+            text.extend(buffer.text_for_range(side_range));
+        }
+        hunk_offsets.push(hunk_start..text.len());
+        offset = range.end;
+    }
+    text.extend(buffer.text_for_range(offset..buffer.len()));
+    (text, hunk_offsets)
+}
+
+/// Maps a row from one editor to another, given the rows of the same hunks in both.
+/// Rows between hunks are the same text in both editors, and rows within a hunk are
+/// mapped proportionally.
+fn map_row(row: f64, from_hunks: &[Range<u32>], to_hunks: &[Range<u32>]) -> f64 {
+    let mut from_offset = 0.;
+    let mut to_offset = 0.;
+    for (from, to) in from_hunks.iter().zip(to_hunks) {
+        if row < from.start as f64 {
+            break;
+        }
+        if row < from.end as f64 {
+            let progress = (row - from.start as f64) / from.len() as f64;
+            return to.start as f64 + progress * to.len() as f64;
+        }
+        from_offset = from.end as f64;
+        to_offset = to.end as f64;
+    }
+    to_offset + (row - from_offset)
+}
+
+fn render_accept_buttons(
+    view: &WeakEntity<MergeView>,
+    hunk_ix: usize,
+    side: MergeSide,
+    cx: &mut editor::display_map::BlockContext,
+) -> AnyElement {
+    let Some(merge_view) = view.upgrade() else {
+        return Empty.into_any_element();
+    };
+    let merge_view = merge_view.read(cx);
+    let conflict = merge_view.unresolved_conflict(hunk_ix, cx);
+    let resolved = conflict.is_none();
+    let has_base = conflict.is_some_and(|conflict| conflict.base.is_some());
+    let (ours, theirs) = (
+        merge_view.ours_branch_name.clone(),
+        merge_view.theirs_branch_name.clone(),
+    );
+
+    let button = |id: &'static str, label: String, sides: &'static [MergeSide]| {
+        let view = view.clone();
+        Button::new(id, label)
+            .label_size(LabelSize::Small)
+            .disabled(resolved)
+            .on_click(move |_, _, cx| {
+                view.update(cx, |view, cx| view.accept(hunk_ix, sides, cx))
+                    .ok();
+            })
+    };
+    let buttons = match side {
+        MergeSide::Base => vec![
+            button("accept-base", "Accept Base".into(), &[MergeSide::Base]).disabled(!has_base),
+        ],
+        MergeSide::Ours => vec![
+            button("accept-ours", format!("Accept {ours}"), &[MergeSide::Ours]),
+            button(
+                "accept-ours-then-theirs",
+                format!("Accept {ours}, then {theirs}"),
+                &[MergeSide::Ours, MergeSide::Theirs],
+            ),
+        ],
+        MergeSide::Theirs => vec![
+            button(
+                "accept-theirs",
+                format!("Accept {theirs}"),
+                &[MergeSide::Theirs],
+            ),
+            button(
+                "accept-theirs-then-ours",
+                format!("Accept {theirs}, then {ours}"),
+                &[MergeSide::Theirs, MergeSide::Ours],
+            ),
+        ],
+    };
+
+    h_flex()
+        .id(cx.block_id)
+        .h(cx.line_height)
+        .ml(cx.margins.gutter.width)
+        .items_end()
+        .gap_1()
+        .bg(cx.theme().colors().editor_background)
+        .children(buttons)
+        .when(resolved, |this| {
+            this.child(
+                Label::new("Resolved")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+        })
+        .into_any()
+}
+
+impl EventEmitter<ItemEvent> for MergeView {}
+
+impl Focusable for MergeView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.result_editor.focus_handle(cx)
+    }
+}
+
+impl Item for MergeView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, f: &mut dyn FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        let file_name = self
+            .buffer
+            .read(cx)
+            .file()
+            .and_then(|file| {
+                Some(
+                    file.full_path(cx)
+                        .file_name()?
+                        .to_string_lossy()
+                        .to_string(),
+                )
+            })
+            .unwrap_or_else(|| "untitled".into());
+        format!("Merge {file_name}").into()
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let file = self.buffer.read(cx).file()?;
+        Some(
+            format!(
+                "Merge conflicts in {}",
+                file.full_path(cx).compact().to_string_lossy()
+            )
+            .into(),
+        )
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Merge View Opened")
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.result_editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        self.result_editor.for_each_project_item(cx, f)
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.result_editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        options: SaveOptions,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.result_editor
+            .update(cx, |editor, cx| editor.save(options, project, window, cx))
+    }
+}
+
+impl Render for MergeView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let path = self
+            .buffer
+            .read(cx)
+            .project_path(cx)
+            .map(|path| {
+                path.path
+                    .display(self.project.read(cx).path_style(cx))
+                    .to_string()
+            })
+            .unwrap_or_default();
+        let remaining = match self.conflicts.len() {
+            0 => "All conflicts resolved".to_string(),
+            1 => "1 conflict remaining".to_string(),
+            count => format!("{count} conflicts remaining"),
+        };
+        let [base, ours, theirs] = &self.side_editors;
+
+        v_flex()
+            .key_context("MergeView")
+            .on_action(cx.listener(Self::mark_resolved_and_stage))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .h(rems_from_px(41.))
+                    .pl_3()
+                    .pr_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(path).color(Color::Muted).buffer_font(cx))
+                    .child(
+                        h_flex()
+                            .gap_1p5()
+                            .child(
+                                Label::new(remaining)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(Divider::vertical())
+                            .child(
+                                Button::new("mark-resolved-and-stage", "Mark Resolved and Stage")
+                                    .label_size(LabelSize::Small)
+                                    .icon(IconName::Check)
+                                    .icon_size(IconSize::Small)
+                                    .icon_color(Color::Muted)
+                                    .icon_position(IconPosition::Start)
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.mark_resolved_and_stage(
+                                            &MarkResolvedAndStage,
+                                            window,
+                                            cx,
+                                        );
+                                    })),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(self.render_pane("Base".into(), base, cx))
+                    .child(Divider::vertical())
+                    .child(self.render_pane(self.ours_branch_name.clone(), ours, cx))
+                    .child(Divider::vertical())
+                    .child(self.render_pane(self.theirs_branch_name.clone(), theirs, cx)),
+            )
+            .child(self.render_pane("Result".into(), &self.result_editor, cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::{
+        repository::RepoPath,
+        status::{UnmergedStatus, UnmergedStatusCode},
+    };
+    use gpui::{TestAppContext, VisualTestContext};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::{path, rel_path::rel_path};
+    use workspace::MultiWorkspace;
+
+    const CONFLICTED_TEXT: &str = indoc! {"
+        one
+        <<<<<<< HEAD
+        two
+        =======
+        zwei
+        >>>>>>> feature
+        three
+        <<<<<<< HEAD
+        four
+        =======
+        vier
+        >>>>>>> feature
+        five
+        <<<<<<< HEAD
+        six
+        =======
+        sechs
+        >>>>>>> feature
+    "};
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+        });
+    }
+
+    /// Opens the merge view for `/project/foo`, which git reports as conflicted.
+    async fn open_merge_view<'a>(
+        text: &str,
+        cx: &'a mut TestAppContext,
+    ) -> (
+        Arc<FakeFs>,
+        Entity<Project>,
+        Entity<MergeView>,
+        &'a mut VisualTestContext,
+    ) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({ ".git": {}, "foo": text }))
+            .await;
+        fs.set_status_for_repo(
+            Path::new(path!("/project/.git")),
+            &[(
+                "foo",
+                UnmergedStatus {
+                    first_head: UnmergedStatusCode::Updated,
+                    second_head: UnmergedStatusCode::Updated,
+                }
+                .into(),
+            )],
+        );
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/project/foo"), cx)
+            })
+            .await
+            .unwrap();
+        let (_, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let view = cx.new_window_entity(|window, cx| {
+            let conflicts = ConflictSet::parse(&buffer.read(cx).text_snapshot()).conflicts;
+            MergeView::new(buffer, conflicts, project.clone(), window, cx)
+        });
+        cx.run_until_parked();
+        (fs, project, view, cx)
+    }
+
+    fn result_text(view: &Entity<MergeView>, cx: &mut VisualTestContext) -> String {
+        view.read_with(cx, |view, cx| view.buffer.read(cx).text())
+    }
+
+    #[test]
+    fn test_map_row() {
+        let from = [2..5, 10..10];
+        let to = [2..3, 8..12];
+        // Before the first hunk.
+        assert_eq!(map_row(1., &from, &to), 1.);
+        // Within a hunk.
+        assert_eq!(map_row(2., &from, &to), 2.);
+        assert_eq!(map_row(3.5, &from, &to), 2.5);
+        // Between hunks.
+        assert_eq!(map_row(5., &from, &to), 3.);
+        assert_eq!(map_row(9., &from, &to), 7.);
+        // After an empty hunk.
+        assert_eq!(map_row(10., &from, &to), 12.);
+        assert_eq!(map_row(12.5, &from, &to), 14.5);
+        // Mapping back is the inverse.
+        assert_eq!(map_row(2.5, &to, &from), 3.5);
+        assert_eq!(map_row(14.5, &to, &from), 12.5);
+    }
+
+    #[gpui::test]
+    fn test_side_text(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+        let text = indoc! {"
+            one
+            <<<<<<< HEAD
+            two
+            ||||||| base
+            deux
+            =======
+            zwei
+            drei
+            >>>>>>> feature
+            four
+        "};
+        let buffer = cx.new(|cx| Buffer::local(text, cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let conflicts = ConflictSet::parse(&snapshot.text).conflicts;
+        assert_eq!(conflicts.len(), 1);
+
+        let (base, base_hunks) = side_text(&snapshot, &conflicts, MergeSide::Base);
+        assert_eq!(base, "one\ndeux\nfour\n");
+        assert_eq!(&base[base_hunks[0].clone()], "deux\n");
+
+        let (ours, ours_hunks) = side_text(&snapshot, &conflicts, MergeSide::Ours);
+        assert_eq!(ours, "one\ntwo\nfour\n");
+        assert_eq!(&ours[ours_hunks[0].clone()], "two\n");
+
+        let (theirs, theirs_hunks) = side_text(&snapshot, &conflicts, MergeSide::Theirs);
+        assert_eq!(theirs, "one\nzwei\ndrei\nfour\n");
+        assert_eq!(&theirs[theirs_hunks[0].clone()], "zwei\ndrei\n");
+    }
+
+    #[gpui::test]
+    async fn test_accept_sides(cx: &mut TestAppContext) {
+        let (_, _, view, cx) = open_merge_view(CONFLICTED_TEXT, cx).await;
+        assert_eq!(view.read_with(cx, |view, _| view.conflicts.len()), 3);
+
+        view.update(cx, |view, cx| {
+            view.accept(0, &[MergeSide::Ours], cx);
+            view.accept(1, &[MergeSide::Theirs], cx);
+            view.accept(2, &[MergeSide::Theirs, MergeSide::Ours], cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            result_text(&view, cx),
+            "one\ntwo\nthree\nvier\nfive\nsechs\nsix\n"
+        );
+        assert_eq!(view.read_with(cx, |view, _| view.conflicts.len()), 0);
+
+        // Accepting a resolved hunk again does nothing.
+        view.update(cx, |view, cx| view.accept(0, &[MergeSide::Theirs], cx));
+        cx.run_until_parked();
+        assert_eq!(
+            result_text(&view, cx),
+            "one\ntwo\nthree\nvier\nfive\nsechs\nsix\n"
+        );
+
+        // Accepting both sides is undone at once, which brings the conflict back.
+        view.update(cx, |view, cx| {
+            view.buffer.update(cx, |buffer, cx| buffer.undo(cx));
+        });
+        cx.run_until_parked();
+        assert_eq!(view.read_with(cx, |view, _| view.conflicts.len()), 1);
+        view.update(cx, |view, cx| {
+            view.accept(2, &[MergeSide::Ours, MergeSide::Theirs], cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            result_text(&view, cx),
+            "one\ntwo\nthree\nvier\nfive\nsix\nsechs\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_mark_resolved_and_stage(cx: &mut TestAppContext) {
+        let (fs, project, view, cx) = open_merge_view(CONFLICTED_TEXT, cx).await;
+        let foo = RepoPath::from_rel_path(rel_path("foo"));
+        let is_conflicted = |cx: &mut VisualTestContext| {
+            project.read_with(cx, |project, cx| {
+                let repository = project.active_repository(cx).unwrap();
+                let status = repository.read(cx).status_for_path(&foo).unwrap().status;
+                status.is_conflicted()
+            })
+        };
+        assert!(is_conflicted(cx));
+
+        view.update(cx, |view, cx| {
+            for hunk_ix in 0..3 {
+                view.accept(hunk_ix, &[MergeSide::Ours], cx);
+            }
+        });
+        let buffer = view.read_with(cx, |view, _| view.buffer.clone());
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer, cx))
+            .await
+            .unwrap();
+        view.update_in(cx, |view, window, cx| {
+            view.mark_resolved_and_stage(&MarkResolvedAndStage, window, cx)
+        });
+        cx.run_until_parked();
+
+        let index_text = fs
+            .with_git_state(Path::new(path!("/project/.git")), false, |state| {
+                state.index_contents.get(&foo).cloned()
+            })
+            .unwrap();
+        assert_eq!(
+            index_text.as_deref(),
+            Some("one\ntwo\nthree\nfour\nfive\nsix\n")
+        );
+        assert!(!is_conflicted(cx));
+    }
+}
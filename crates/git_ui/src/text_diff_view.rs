@@ -1,4 +1,5 @@
-//! TextDiffView currently provides a UI for displaying differences between the clipboard and selected text.
+//! TextDiffView provides a UI for displaying differences between the clipboard and selected text,
+//! and between an agent checkpoint and the current contents of a file.
 
 use anyhow::Result;
 use buffer_diff::BufferDiff;
@@ -8,7 +9,7 @@ use gpui::{
     AnyElement, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, FocusHandle,
     Focusable, IntoElement, Render, Task, Window,
 };
-use language::{self, Buffer, Point};
+use language::{self, Buffer, Point, Rope};
 use project::Project;
 use std::{
     any::{Any, TypeId},
@@ -132,6 +133,69 @@ impl TextDiffView {
         Some(task)
     }
 
+    /// Opens a diff between the contents a file had at an agent checkpoint, or no contents if it
+    /// didn't exist yet, and its current contents.
+    pub fn open_checkpoint_diff(
+        checkpoint_text: Option<Rope>,
+        source_buffer: Entity<Buffer>,
+        workspace: &Workspace,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let source_buffer_snapshot = source_buffer.read(cx).snapshot();
+        let checkpoint_buffer = cx.new(|cx| {
+            let text = checkpoint_text
+                .map(|text| text.to_string())
+                .unwrap_or_default();
+            let mut buffer = language::Buffer::local(text, cx);
+            buffer.set_language(source_buffer.read(cx).language().cloned(), cx);
+            buffer
+        });
+        let diff_buffer = cx.new(|cx| BufferDiff::new(&source_buffer_snapshot.text, cx));
+
+        let path = source_buffer
+            .read(cx)
+            .file()
+            .map(|file| file.full_path(cx).compact().to_string_lossy().into_owned())
+            .unwrap_or("untitled".into());
+        let title = source_buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string())
+            .unwrap_or("untitled".into());
+
+        let workspace = workspace.weak_handle();
+        window.spawn(cx, async move |cx| {
+            let project = workspace.update(cx, |workspace, _| workspace.project().clone())?;
+
+            update_diff_buffer(&diff_buffer, &source_buffer, &checkpoint_buffer, cx).await?;
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                let diff_view = cx.new(|cx| {
+                    let full_range = Point::zero()..source_buffer.read(cx).max_point();
+                    TextDiffView::new_internal(
+                        checkpoint_buffer,
+                        source_buffer,
+                        full_range,
+                        diff_buffer,
+                        project,
+                        format!("Checkpoint ↔ {title}").into(),
+                        format!("Checkpoint ↔ {path}").into(),
+                        window,
+                        cx,
+                    )
+                });
+
+                let pane = workspace.active_pane();
+                pane.update(cx, |pane, cx| {
+                    pane.add_item(Box::new(diff_view.clone()), true, true, None, window, cx);
+                });
+
+                diff_view
+            })
+        })
+    }
+
     pub fn new(
         clipboard_buffer: Entity<Buffer>,
         source_editor: Entity<Editor>,
@@ -141,6 +205,53 @@ impl TextDiffView {
         project: Entity<Project>,
         window: &mut Window,
         cx: &mut Context<Self>,
+    ) -> Self {
+        let editor = source_editor.read(cx);
+        let title = editor.buffer().read(cx).title(cx).to_string();
+        let selection_location_text = selection_location_text(editor, cx);
+        let selection_location_title = selection_location_text
+            .as_ref()
+            .map(|text| format!("{} @ {}", title, text))
+            .unwrap_or(title);
+
+        let path = editor
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .and_then(|b| {
+                b.read(cx)
+                    .file()
+                    .map(|f| f.full_path(cx).compact().to_string_lossy().into_owned())
+            })
+            .unwrap_or("untitled".into());
+
+        let selection_location_path = selection_location_text
+            .map(|text| format!("{} @ {}", path, text))
+            .unwrap_or(path);
+
+        Self::new_internal(
+            clipboard_buffer,
+            source_buffer,
+            source_range,
+            diff_buffer,
+            project,
+            format!("Clipboard ↔ {selection_location_title}").into(),
+            format!("Clipboard ↔ {selection_location_path}").into(),
+            window,
+            cx,
+        )
+    }
+
+    fn new_internal(
+        base_buffer: Entity<Buffer>,
+        source_buffer: Entity<Buffer>,
+        source_range: Range<Point>,
+        diff_buffer: Entity<BufferDiff>,
+        project: Entity<Project>,
+        title: SharedString,
+        path: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Self {
         let multibuffer = cx.new(|cx| {
             let mut multibuffer = MultiBuffer::new(language::Capability::ReadWrite);
@@ -178,33 +289,10 @@ impl TextDiffView {
         })
         .detach();
 
-        let editor = source_editor.read(cx);
-        let title = editor.buffer().read(cx).title(cx).to_string();
-        let selection_location_text = selection_location_text(editor, cx);
-        let selection_location_title = selection_location_text
-            .as_ref()
-            .map(|text| format!("{} @ {}", title, text))
-            .unwrap_or(title);
-
-        let path = editor
-            .buffer()
-            .read(cx)
-            .as_singleton()
-            .and_then(|b| {
-                b.read(cx)
-                    .file()
-                    .map(|f| f.full_path(cx).compact().to_string_lossy().into_owned())
-            })
-            .unwrap_or("untitled".into());
-
-        let selection_location_path = selection_location_text
-            .map(|text| format!("{} @ {}", path, text))
-            .unwrap_or(path);
-
         Self {
             diff_editor,
-            title: format!("Clipboard ↔ {selection_location_title}").into(),
-            path: Some(format!("Clipboard ↔ {selection_location_path}").into()),
+            title,
+            path: Some(path),
             buffer_changes_tx,
             _recalculate_diff_task: cx.spawn(async move |_, cx| {
                 while buffer_changes_rx.recv().await.is_ok() {
@@ -221,7 +309,7 @@ impl TextDiffView {
                     }
 
                     log::trace!("start recalculating");
-                    update_diff_buffer(&diff_buffer, &source_buffer, &clipboard_buffer, cx).await?;
+                    update_diff_buffer(&diff_buffer, &source_buffer, &base_buffer, cx).await?;
                     log::trace!("finish recalculating");
                 }
                 Ok(())
@@ -253,14 +341,14 @@ fn build_clipboard_buffer(
 async fn update_diff_buffer(
     diff: &Entity<BufferDiff>,
     source_buffer: &Entity<Buffer>,
-    clipboard_buffer: &Entity<Buffer>,
+    base_buffer: &Entity<Buffer>,
     cx: &mut AsyncApp,
 ) -> Result<()> {
     let source_buffer_snapshot = source_buffer.read_with(cx, |buffer, _| buffer.snapshot());
     let language = source_buffer_snapshot.language().cloned();
     let language_registry = source_buffer.read_with(cx, |buffer, _| buffer.language_registry());
 
-    let base_buffer_snapshot = clipboard_buffer.read_with(cx, |buffer, _| buffer.snapshot());
+    let base_buffer_snapshot = base_buffer.read_with(cx, |buffer, _| buffer.snapshot());
     let base_text = base_buffer_snapshot.text();
 
     let update = diff
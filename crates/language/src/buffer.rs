@@ -355,7 +355,13 @@ pub enum Operation {
 #[derive(Clone, Debug, PartialEq)]
 pub enum BufferEvent {
     /// The buffer was changed in a way that must be
-    /// propagated to its other replicas.
+    /// propagated to its other replicas, or applied an
+    /// operation received from another replica.
+    ///
+    /// Remote text operations are emitted as they're
+    /// received, since applying them orders them anyway.
+    /// Other remote operations that can't be applied yet
+    /// are deferred, and only emitted once they're applied.
     Operation {
         operation: Operation,
         is_local: bool,
@@ -3005,6 +3011,10 @@ impl Buffer {
     }
 
     fn apply_op(&mut self, operation: Operation, cx: &mut Context<Self>) {
+        cx.emit(BufferEvent::Operation {
+            operation: operation.clone(),
+            is_local: false,
+        });
         match operation {
             Operation::Buffer(_) => {
                 unreachable!("buffer operations should never be applied at this layer")
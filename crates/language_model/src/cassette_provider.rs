@@ -0,0 +1,688 @@
+//! A language model that records completions of another model to a cassette file, and replays
+//! them later without network access.
+//!
+//! Requests are matched by the hash of their normalized JSON, so replaying only works when the
+//! code under test builds the same requests as when recording. Fields that vary between runs,
+//! like the thread and prompt ids, are removed before hashing; use
+//! [`CassetteLanguageModel::with_normalizer`] to remove others, such as temporary paths.
+//!
+//! This only covers code that talks to models through [`LanguageModel`], like the agent tests and
+//! the `eval` crate's `--cassette-dir` flag. `edit_prediction_cli` calls the provider APIs directly
+//! and caches their responses itself, so it can't be recorded this way.
+
+use crate::{
+    LanguageModel, LanguageModelCacheConfiguration, LanguageModelCompletionError,
+    LanguageModelCompletionEvent, LanguageModelCostInfo, LanguageModelEffortLevel, LanguageModelId,
+    LanguageModelName, LanguageModelProviderId, LanguageModelProviderName, LanguageModelRequest,
+    LanguageModelToolChoice, LanguageModelToolSchemaFormat,
+};
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use futures::{FutureExt, StreamExt, future::BoxFuture, stream::BoxStream};
+use gpui::{App, AsyncApp};
+use http_client::StatusCode;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Forwards requests to the wrapped model and appends its responses to the cassette.
+    Record,
+    /// Serves responses from the cassette, failing requests that weren't recorded.
+    Replay,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Interaction {
+    request_hash: String,
+    /// The normalized request, stored to make cassettes reviewable.
+    request: serde_json::Value,
+    events: Vec<RecordedEvent>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedEvent {
+    Event(LanguageModelCompletionEvent),
+    Error(RecordedError),
+}
+
+/// A [`LanguageModelCompletionError`] in a form that can be stored, so that replayed errors are
+/// retried and reported like the recorded ones. Errors wrapping a source keep only its message.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedError {
+    PromptTooLarge {
+        tokens: Option<u64>,
+    },
+    NoApiKey {
+        provider: String,
+    },
+    RateLimitExceeded {
+        provider: String,
+        retry_after: Option<Duration>,
+    },
+    ServerOverloaded {
+        provider: String,
+        retry_after: Option<Duration>,
+    },
+    ApiInternalServerError {
+        provider: String,
+        message: String,
+    },
+    UpstreamProviderError {
+        message: String,
+        status: u16,
+        retry_after: Option<Duration>,
+    },
+    HttpResponseError {
+        provider: String,
+        status_code: u16,
+        message: String,
+    },
+    BadRequestFormat {
+        provider: String,
+        message: String,
+    },
+    AuthenticationError {
+        provider: String,
+        message: String,
+    },
+    PermissionError {
+        provider: String,
+        message: String,
+    },
+    ApiEndpointNotFound {
+        provider: String,
+    },
+    ApiReadResponseError {
+        provider: String,
+        message: String,
+    },
+    SerializeRequest {
+        provider: String,
+        message: String,
+    },
+    HttpSend {
+        provider: String,
+        message: String,
+    },
+    DeserializeResponse {
+        provider: String,
+        message: String,
+    },
+    StreamEndedUnexpectedly {
+        provider: String,
+    },
+    Other(String),
+}
+
+impl From<&LanguageModelCompletionError> for RecordedError {
+    fn from(error: &LanguageModelCompletionError) -> Self {
+        use LanguageModelCompletionError as E;
+
+        let name = |provider: &LanguageModelProviderName| provider.0.to_string();
+        match error {
+            E::PromptTooLarge { tokens } => Self::PromptTooLarge { tokens: *tokens },
+            E::NoApiKey { provider } => Self::NoApiKey {
+                provider: name(provider),
+            },
+            E::RateLimitExceeded {
+                provider,
+                retry_after,
+            } => Self::RateLimitExceeded {
+                provider: name(provider),
+                retry_after: *retry_after,
+            },
+            E::ServerOverloaded {
+                provider,
+                retry_after,
+            } => Self::ServerOverloaded {
+                provider: name(provider),
+                retry_after: *retry_after,
+            },
+            E::ApiInternalServerError { provider, message } => Self::ApiInternalServerError {
+                provider: name(provider),
+                message: message.clone(),
+            },
+            E::UpstreamProviderError {
+                message,
+                status,
+                retry_after,
+            } => Self::UpstreamProviderError {
+                message: message.clone(),
+                status: status.as_u16(),
+                retry_after: *retry_after,
+            },
+            E::HttpResponseError {
+                provider,
+                status_code,
+                message,
+            } => Self::HttpResponseError {
+                provider: name(provider),
+                status_code: status_code.as_u16(),
+                message: message.clone(),
+            },
+            E::BadRequestFormat { provider, message } => Self::BadRequestFormat {
+                provider: name(provider),
+                message: message.clone(),
+            },
+            E::AuthenticationError { provider, message } => Self::AuthenticationError {
+                provider: name(provider),
+                message: message.clone(),
+            },
+            E::PermissionError { provider, message } => Self::PermissionError {
+                provider: name(provider),
+                message: message.clone(),
+            },
+            E::ApiEndpointNotFound { provider } => Self::ApiEndpointNotFound {
+                provider: name(provider),
+            },
+            E::ApiReadResponseError { provider, error } => Self::ApiReadResponseError {
+                provider: name(provider),
+                message: error.to_string(),
+            },
+            E::SerializeRequest { provider, error } => Self::SerializeRequest {
+                provider: name(provider),
+                message: error.to_string(),
+            },
+            E::HttpSend { provider, error } => Self::HttpSend {
+                provider: name(provider),
+                message: format!("{error:#}"),
+            },
+            E::DeserializeResponse { provider, error } => Self::DeserializeResponse {
+                provider: name(provider),
+                message: error.to_string(),
+            },
+            E::StreamEndedUnexpectedly { provider } => Self::StreamEndedUnexpectedly {
+                provider: name(provider),
+            },
+            E::BuildRequestBody { .. } => Self::Other(error.to_string()),
+            E::Other(error) => Self::Other(format!("{error:#}")),
+        }
+    }
+}
+
+impl From<RecordedError> for LanguageModelCompletionError {
+    fn from(error: RecordedError) -> Self {
+        use RecordedError as E;
+
+        let status =
+            |status: u16| StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        match error {
+            E::PromptTooLarge { tokens } => Self::PromptTooLarge { tokens },
+            E::NoApiKey { provider } => Self::NoApiKey {
+                provider: provider.into(),
+            },
+            E::RateLimitExceeded {
+                provider,
+                retry_after,
+            } => Self::RateLimitExceeded {
+                provider: provider.into(),
+                retry_after,
+            },
+            E::ServerOverloaded {
+                provider,
+                retry_after,
+            } => Self::ServerOverloaded {
+                provider: provider.into(),
+                retry_after,
+            },
+            E::ApiInternalServerError { provider, message } => Self::ApiInternalServerError {
+                provider: provider.into(),
+                message,
+            },
+            E::UpstreamProviderError {
+                message,
+                status: status_code,
+                retry_after,
+            } => Self::UpstreamProviderError {
+                message,
+                status: status(status_code),
+                retry_after,
+            },
+            E::HttpResponseError {
+                provider,
+                status_code,
+                message,
+            } => Self::HttpResponseError {
+                provider: provider.into(),
+                status_code: status(status_code),
+                message,
+            },
+            E::BadRequestFormat { provider, message } => Self::BadRequestFormat {
+                provider: provider.into(),
+                message,
+            },
+            E::AuthenticationError { provider, message } => Self::AuthenticationError {
+                provider: provider.into(),
+                message,
+            },
+            E::PermissionError { provider, message } => Self::PermissionError {
+                provider: provider.into(),
+                message,
+            },
+            E::ApiEndpointNotFound { provider } => Self::ApiEndpointNotFound {
+                provider: provider.into(),
+            },
+            E::ApiReadResponseError { provider, message } => Self::ApiReadResponseError {
+                provider: provider.into(),
+                error: io::Error::other(message),
+            },
+            E::SerializeRequest { provider, message } => Self::SerializeRequest {
+                provider: provider.into(),
+                error: serde::ser::Error::custom(message),
+            },
+            E::HttpSend { provider, message } => Self::HttpSend {
+                provider: provider.into(),
+                error: anyhow!(message),
+            },
+            E::DeserializeResponse { provider, message } => Self::DeserializeResponse {
+                provider: provider.into(),
+                error: serde::de::Error::custom(message),
+            },
+            E::StreamEndedUnexpectedly { provider } => Self::StreamEndedUnexpectedly {
+                provider: provider.into(),
+            },
+            E::Other(message) => Self::Other(anyhow!(message)),
+        }
+    }
+}
+
+type Normalizer = Arc<dyn Fn(&mut serde_json::Value) + Send + Sync>;
+
+pub struct CassetteLanguageModel {
+    model: Arc<dyn LanguageModel>,
+    mode: CassetteMode,
+    path: PathBuf,
+    normalizer: Option<Normalizer>,
+    state: Arc<Mutex<CassetteState>>,
+}
+
+#[derive(Default)]
+struct CassetteState {
+    cassette: Cassette,
+    /// How many interactions were replayed for each request hash, so that identical requests
+    /// get their responses in the order they were recorded.
+    replay_counts: HashMap<String, usize>,
+}
+
+impl CassetteLanguageModel {
+    /// Wraps `model`, recording to or replaying from the cassette at `path`.
+    ///
+    /// In record mode, the cassette is created or overwritten. The wrapped model is only used for
+    /// its metadata in replay mode.
+    pub fn new(
+        model: Arc<dyn LanguageModel>,
+        mode: CassetteMode,
+        path: impl Into<PathBuf>,
+    ) -> Result<Self> {
+        let path = path.into();
+        let cassette = match mode {
+            CassetteMode::Record => Cassette::default(),
+            CassetteMode::Replay => {
+                let json = std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read cassette {path:?}"))?;
+                serde_json::from_str(&json)
+                    .with_context(|| format!("failed to parse cassette {path:?}"))?
+            }
+        };
+        Ok(Self {
+            model,
+            mode,
+            path,
+            normalizer: None,
+            state: Arc::new(Mutex::new(CassetteState {
+                cassette,
+                replay_counts: HashMap::default(),
+            })),
+        })
+    }
+
+    /// Sets a function that removes run-specific values from requests before they are hashed.
+    pub fn with_normalizer(
+        mut self,
+        normalizer: impl Fn(&mut serde_json::Value) + Send + Sync + 'static,
+    ) -> Self {
+        self.normalizer = Some(Arc::new(normalizer));
+        self
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn normalize(&self, request: &LanguageModelRequest) -> Result<(String, serde_json::Value)> {
+        let mut request = serde_json::to_value(request)?;
+        if let Some(request) = request.as_object_mut() {
+            request.remove("thread_id");
+            request.remove("prompt_id");
+        }
+        if let Some(normalizer) = &self.normalizer {
+            normalizer(&mut request);
+        }
+        let hash = Sha256::digest(serde_json::to_string(&request)?.as_bytes());
+        let hash = hash.iter().map(|byte| format!("{byte:02x}")).collect();
+        Ok((hash, request))
+    }
+
+    fn replay(
+        &self,
+        request_hash: &str,
+    ) -> Result<Vec<Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>> {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+        let recorded = state
+            .cassette
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.request_hash == request_hash)
+            .collect::<Vec<_>>();
+        let replay_count = state
+            .replay_counts
+            .entry(request_hash.to_string())
+            .or_default();
+        // Keep serving the last recorded response if a request is made more often than recorded.
+        let interaction = recorded
+            .get(*replay_count)
+            .or(recorded.last())
+            .with_context(|| {
+                format!(
+                    "no recorded response for request {request_hash} in cassette {:?}",
+                    self.path
+                )
+            })?;
+        *replay_count += 1;
+
+        Ok(interaction
+            .events
+            .iter()
+            .map(|event| match event {
+                RecordedEvent::Event(event) => Ok(event.clone()),
+                RecordedEvent::Error(error) => Err(error.clone().into()),
+            })
+            .collect())
+    }
+}
+
+fn save_interaction(
+    state: &Mutex<CassetteState>,
+    path: &Path,
+    interaction: Interaction,
+) -> Result<()> {
+    let mut state = state.lock();
+    state.cassette.interactions.push(interaction);
+    let json = serde_json::to_string_pretty(&state.cassette)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, json).with_context(|| format!("failed to write cassette {path:?}"))
+}
+
+impl LanguageModel for CassetteLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.model.id()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        self.model.name()
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.model.provider_id()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.model.provider_name()
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.model.telemetry_id()
+    }
+
+    fn model_cost_info(&self) -> Option<LanguageModelCostInfo> {
+        self.model.model_cost_info()
+    }
+
+    fn supports_thinking(&self) -> bool {
+        self.model.supports_thinking()
+    }
+
+    fn supported_effort_levels(&self) -> Vec<LanguageModelEffortLevel> {
+        self.model.supported_effort_levels()
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.supports_images()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.model.supports_tools()
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.model.supports_tool_choice(choice)
+    }
+
+    fn supports_streaming_tools(&self) -> bool {
+        self.model.supports_streaming_tools()
+    }
+
+    fn supports_split_token_display(&self) -> bool {
+        self.model.supports_split_token_display()
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        self.model.tool_input_format()
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.model.max_token_count()
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.model.max_output_tokens()
+    }
+
+    fn cache_configuration(&self) -> Option<LanguageModelCacheConfiguration> {
+        self.model.cache_configuration()
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &App,
+    ) -> BoxFuture<'static, Result<u64>> {
+        match self.mode {
+            CassetteMode::Record => self.model.count_tokens(request, cx),
+            CassetteMode::Replay => futures::future::ready(Ok(0)).boxed(),
+        }
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let (request_hash, normalized_request) = match self.normalize(&request) {
+            Ok(normalized) => normalized,
+            Err(error) => return futures::future::ready(Err(error.into())).boxed(),
+        };
+
+        match self.mode {
+            CassetteMode::Replay => {
+                let events = self
+                    .replay(&request_hash)
+                    .map(|events| futures::stream::iter(events).boxed())
+                    .map_err(LanguageModelCompletionError::from);
+                futures::future::ready(events).boxed()
+            }
+            CassetteMode::Record => {
+                let completion = self.model.stream_completion(request, cx);
+                let state = self.state.clone();
+                let path = self.path.clone();
+                async move {
+                    let events = completion.await?;
+                    let recorded_events = Arc::new(Mutex::new(Vec::new()));
+                    let events = events.inspect({
+                        let recorded_events = recorded_events.clone();
+                        move |event| {
+                            recorded_events.lock().push(match event {
+                                Ok(event) => RecordedEvent::Event(event.clone()),
+                                Err(error) => RecordedEvent::Error(error.into()),
+                            })
+                        }
+                    });
+                    // Save the interaction once the stream ends.
+                    let save = futures::stream::once(async move {
+                        let interaction = Interaction {
+                            request_hash,
+                            request: normalized_request,
+                            events: std::mem::take(&mut *recorded_events.lock()),
+                        };
+                        if let Err(error) = save_interaction(&state, &path, interaction) {
+                            log::error!("{error:#}");
+                        }
+                    })
+                    .filter_map(|()| futures::future::ready(None));
+                    Ok(events.chain(save).boxed())
+                }
+                .boxed()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        LanguageModelRequestMessage, MessageContent, Role, StopReason,
+        fake_provider::FakeLanguageModel,
+    };
+    use futures::TryStreamExt as _;
+    use gpui::TestAppContext;
+
+    fn request(text: &str, thread_id: &str) -> LanguageModelRequest {
+        LanguageModelRequest {
+            thread_id: Some(thread_id.to_string()),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![MessageContent::Text(text.to_string())],
+                cache: false,
+                reasoning_details: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[gpui::test]
+    async fn test_record_and_replay(cx: &mut TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassettes/hello.json");
+
+        let fake_model = Arc::new(FakeLanguageModel::default());
+        let recorder =
+            CassetteLanguageModel::new(fake_model.clone(), CassetteMode::Record, &path).unwrap();
+        let events = cx
+            .update(|cx| recorder.stream_completion(request("Hello", "thread-1"), &cx.to_async()));
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_text_chunk("Hi!");
+        fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(
+            StopReason::EndTurn,
+        ));
+        fake_model.end_last_completion_stream();
+        let recorded_events = events.await.unwrap().try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(
+            recorded_events,
+            vec![
+                LanguageModelCompletionEvent::Text("Hi!".into()),
+                LanguageModelCompletionEvent::Stop(StopReason::EndTurn),
+            ]
+        );
+
+        fake_model.forbid_requests();
+        let player =
+            CassetteLanguageModel::new(fake_model.clone(), CassetteMode::Replay, &path).unwrap();
+
+        // The thread id differs between runs, so it's not part of the request hash.
+        let replayed_events = cx
+            .update(|cx| player.stream_completion(request("Hello", "thread-2"), &cx.to_async()))
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(replayed_events, recorded_events);
+
+        let error = cx
+            .update(|cx| player.stream_completion(request("Bye", "thread-2"), &cx.to_async()))
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("no recorded response"));
+    }
+
+    #[gpui::test]
+    async fn test_replay_error(cx: &mut TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("error.json");
+
+        let fake_model = Arc::new(FakeLanguageModel::default());
+        let recorder =
+            CassetteLanguageModel::new(fake_model.clone(), CassetteMode::Record, &path).unwrap();
+        let events = cx
+            .update(|cx| recorder.stream_completion(request("Hello", "thread-1"), &cx.to_async()));
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_error(
+            LanguageModelCompletionError::RateLimitExceeded {
+                provider: LanguageModelProviderName::new("fake"),
+                retry_after: Some(Duration::from_secs(3)),
+            },
+        );
+        fake_model.end_last_completion_stream();
+        events.await.unwrap().collect::<Vec<_>>().await;
+
+        let player =
+            CassetteLanguageModel::new(fake_model.clone(), CassetteMode::Replay, &path).unwrap();
+        let replayed_events = cx
+            .update(|cx| player.stream_completion(request("Hello", "thread-2"), &cx.to_async()))
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        let [
+            Err(LanguageModelCompletionError::RateLimitExceeded {
+                provider,
+                retry_after,
+            }),
+        ] = replayed_events.as_slice()
+        else {
+            panic!("expected a rate limit error, got {replayed_events:?}");
+        };
+        assert_eq!(*provider, LanguageModelProviderName::new("fake"));
+        assert_eq!(*retry_after, Some(Duration::from_secs(3)));
+    }
+}
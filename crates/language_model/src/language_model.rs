@@ -1,4 +1,5 @@
 mod api_key;
+pub mod cassette_provider;
 mod model;
 mod rate_limiter;
 mod registry;
@@ -496,6 +497,20 @@ impl TokenUsage {
             + self.cache_read_input_tokens
             + self.cache_creation_input_tokens
     }
+
+    /// Subtracts `other` from each count, stopping at zero instead of underflowing.
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            cache_creation_input_tokens: self
+                .cache_creation_input_tokens
+                .saturating_sub(other.cache_creation_input_tokens),
+            cache_read_input_tokens: self
+                .cache_read_input_tokens
+                .saturating_sub(other.cache_read_input_tokens),
+        }
+    }
 }
 
 impl Add<TokenUsage> for TokenUsage {
@@ -893,10 +908,12 @@ pub struct LanguageModelProviderName(pub SharedString);
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum LanguageModelCostInfo {
-    /// Cost per 1,000 input and output tokens
+    /// Cost in USD per 1,000,000 tokens of each kind
     TokenCost {
         input_token_cost_per_1m: f64,
         output_token_cost_per_1m: f64,
+        cache_creation_input_token_cost_per_1m: f64,
+        cache_read_input_token_cost_per_1m: f64,
     },
     /// Cost per request
     RequestCost { cost_per_request: f64 },
@@ -912,6 +929,7 @@ impl LanguageModelCostInfo {
             LanguageModelCostInfo::TokenCost {
                 input_token_cost_per_1m,
                 output_token_cost_per_1m,
+                ..
             } => {
                 let input_cost = Self::cost_value_to_string(input_token_cost_per_1m);
                 let output_cost = Self::cost_value_to_string(output_token_cost_per_1m);
@@ -920,6 +938,26 @@ impl LanguageModelCostInfo {
         }
     }
 
+    /// Returns the cost in USD of the given token usage, if this model is priced per token.
+    pub fn cost(&self, usage: &TokenUsage) -> Option<f64> {
+        match self {
+            LanguageModelCostInfo::RequestCost { .. } => None,
+            LanguageModelCostInfo::TokenCost {
+                input_token_cost_per_1m,
+                output_token_cost_per_1m,
+                cache_creation_input_token_cost_per_1m,
+                cache_read_input_token_cost_per_1m,
+            } => Some(
+                (usage.input_tokens as f64 * input_token_cost_per_1m
+                    + usage.output_tokens as f64 * output_token_cost_per_1m
+                    + usage.cache_creation_input_tokens as f64
+                        * cache_creation_input_token_cost_per_1m
+                    + usage.cache_read_input_tokens as f64 * cache_read_input_token_cost_per_1m)
+                    / 1_000_000.,
+            ),
+        }
+    }
+
     fn cost_value_to_string(cost: &f64) -> SharedString {
         if (cost.fract() - 0.0).abs() < std::f64::EPSILON {
             SharedString::from(format!("{:.0}", cost))
@@ -1169,4 +1207,27 @@ mod tests {
         assert_eq!(deserialized.name, original.name);
         assert_eq!(deserialized.thought_signature, None);
     }
+
+    #[test]
+    fn test_token_cost() {
+        let cost_info = LanguageModelCostInfo::TokenCost {
+            input_token_cost_per_1m: 3.,
+            output_token_cost_per_1m: 15.,
+            cache_creation_input_token_cost_per_1m: 3.75,
+            cache_read_input_token_cost_per_1m: 0.3,
+        };
+        let usage = TokenUsage {
+            input_tokens: 2_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 400_000,
+            cache_read_input_tokens: 1_000_000,
+        };
+        let cost = cost_info.cost(&usage).unwrap();
+        assert!((cost - (6. + 1.5 + 1.5 + 0.3)).abs() < 1e-9);
+
+        let cost_info = LanguageModelCostInfo::RequestCost {
+            cost_per_request: 1.,
+        };
+        assert_eq!(cost_info.cost(&usage), None);
+    }
 }
@@ -453,6 +453,8 @@ pub struct LanguageModelRequest {
     pub temperature: Option<f32>,
     pub thinking_allowed: bool,
     pub thinking_effort: Option<String>,
+    /// Limits the number of tokens generated below the model's own maximum.
+    pub max_tokens: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
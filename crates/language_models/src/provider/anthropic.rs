@@ -10,10 +10,10 @@ use http_client::HttpClient;
 use language_model::{
     ApiKeyState, AuthenticateError, ConfigurationViewTargetAgent, EnvVar, IconOrSvg, LanguageModel,
     LanguageModelCacheConfiguration, LanguageModelCompletionError, LanguageModelCompletionEvent,
-    LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
-    LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolUse, MessageContent,
-    RateLimiter, Role, StopReason, env_var,
+    LanguageModelCostInfo, LanguageModelId, LanguageModelName, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRequest, LanguageModelToolChoice, LanguageModelToolResultContent,
+    LanguageModelToolUse, MessageContent, RateLimiter, Role, StopReason, env_var,
 };
 use settings::{Settings, SettingsStore};
 use std::pin::Pin;
@@ -479,6 +479,44 @@ impl AnthropicModel {
     }
 }
 
+/// Returns Anthropic's list prices for the model. Cache writes cost 1.25 times and cache reads 0.1
+/// times the input price.
+fn cost_info(model: &anthropic::Model) -> Option<LanguageModelCostInfo> {
+    use anthropic::Model;
+
+    let (input, output) = match model {
+        Model::ClaudeOpus4
+        | Model::ClaudeOpus4Thinking
+        | Model::ClaudeOpus4_1
+        | Model::ClaudeOpus4_1Thinking => (15., 75.),
+        Model::ClaudeOpus4_5
+        | Model::ClaudeOpus4_5Thinking
+        | Model::ClaudeOpus4_6
+        | Model::ClaudeOpus4_6Thinking
+        | Model::ClaudeOpus4_6_1mContext
+        | Model::ClaudeOpus4_6_1mContextThinking => (5., 25.),
+        Model::ClaudeSonnet4
+        | Model::ClaudeSonnet4Thinking
+        | Model::ClaudeSonnet4_5
+        | Model::ClaudeSonnet4_5Thinking
+        | Model::ClaudeSonnet4_5_1mContext
+        | Model::ClaudeSonnet4_5_1mContextThinking
+        | Model::ClaudeSonnet4_6
+        | Model::ClaudeSonnet4_6Thinking
+        | Model::ClaudeSonnet4_6_1mContext
+        | Model::ClaudeSonnet4_6_1mContextThinking => (3., 15.),
+        Model::ClaudeHaiku4_5 | Model::ClaudeHaiku4_5Thinking => (1., 5.),
+        Model::Claude3Haiku => (0.25, 1.25),
+        Model::Custom { .. } => return None,
+    };
+    Some(LanguageModelCostInfo::TokenCost {
+        input_token_cost_per_1m: input,
+        output_token_cost_per_1m: output,
+        cache_creation_input_token_cost_per_1m: input * 1.25,
+        cache_read_input_token_cost_per_1m: input * 0.1,
+    })
+}
+
 impl LanguageModel for AnthropicModel {
     fn id(&self) -> LanguageModelId {
         self.id.clone()
@@ -531,6 +569,10 @@ impl LanguageModel for AnthropicModel {
         })
     }
 
+    fn model_cost_info(&self) -> Option<LanguageModelCostInfo> {
+        cost_info(&self.model)
+    }
+
     fn max_token_count(&self) -> u64 {
         self.model.max_token_count()
     }
@@ -694,7 +736,9 @@ pub fn into_anthropic(
     anthropic::Request {
         model,
         messages: new_messages,
-        max_tokens: max_output_tokens,
+        max_tokens: request.max_tokens.map_or(max_output_tokens, |max_tokens| {
+            max_tokens.min(max_output_tokens)
+        }),
         system: if system_message.is_empty() {
             None
         } else {
@@ -1105,6 +1149,7 @@ mod tests {
             tool_choice: None,
             thinking_allowed: true,
             thinking_effort: None,
+            max_tokens: None,
         };
 
         let anthropic_request = into_anthropic(
@@ -1159,6 +1204,7 @@ mod tests {
                 reasoning_details: None,
             }],
             thinking_effort: None,
+            max_tokens: None,
             thread_id: None,
             prompt_id: None,
             intent: None,
@@ -910,6 +910,7 @@ fn into_copilot_chat(
             LanguageModelToolChoice::Any => ToolChoice::Any,
             LanguageModelToolChoice::None => ToolChoice::None,
         }),
+        max_tokens: request.max_tokens,
     })
 }
 
@@ -930,6 +931,7 @@ fn into_copilot_responses(
         temperature,
         thinking_allowed: _,
         thinking_effort: _,
+        max_tokens,
     } = request;
 
     let mut input_items: Vec<responses::ResponseInputItem> = Vec::new();
@@ -1110,6 +1112,7 @@ fn into_copilot_responses(
         include: Some(vec![
             copilot_responses::ResponseIncludable::ReasoningEncryptedContent,
         ]),
+        max_output_tokens: max_tokens,
     }
 }
 
@@ -385,7 +385,11 @@ pub fn into_deepseek(
         model: model.id().to_string(),
         messages,
         stream: true,
-        max_tokens: max_output_tokens,
+        max_tokens: request
+            .max_tokens
+            .into_iter()
+            .chain(max_output_tokens)
+            .min(),
         temperature: if is_reasoner {
             None
         } else {
@@ -508,7 +508,11 @@ pub fn into_mistral(
             model: model.id().to_string(),
             messages,
             stream,
-            max_tokens: max_output_tokens,
+            max_tokens: request
+                .max_tokens
+                .into_iter()
+                .chain(max_output_tokens)
+                .min(),
             temperature: request.temperature,
             response_format: None,
             tool_choice: match request.tool_choice {
@@ -883,6 +887,7 @@ mod tests {
             stop: vec![],
             thinking_allowed: true,
             thinking_effort: None,
+            max_tokens: None,
         };
 
         let (mistral_request, affinity) =
@@ -919,6 +924,7 @@ mod tests {
             stop: vec![],
             thinking_allowed: true,
             thinking_effort: None,
+            max_tokens: None,
         };
 
         let (mistral_request, _) = into_mistral(request, mistral::Model::Pixtral12BLatest, None);
@@ -6,8 +6,8 @@ use gpui::{AnyView, App, AsyncApp, Context, Entity, SharedString, Task, Window};
 use http_client::HttpClient;
 use language_model::{
     ApiKeyState, AuthenticateError, EnvVar, IconOrSvg, LanguageModel, LanguageModelCompletionError,
-    LanguageModelCompletionEvent, LanguageModelId, LanguageModelImage, LanguageModelName,
-    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelCompletionEvent, LanguageModelCostInfo, LanguageModelId, LanguageModelImage,
+    LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, LanguageModelRequestMessage,
     LanguageModelToolChoice, LanguageModelToolResult, LanguageModelToolResultContent,
     LanguageModelToolUse, LanguageModelToolUseId, MessageContent, RateLimiter, Role, StopReason,
@@ -276,6 +276,32 @@ impl OpenAiLanguageModel {
     }
 }
 
+/// Returns OpenAI's list prices for the model as (input, output, cached input). OpenAI doesn't
+/// charge extra for writing to the prompt cache.
+fn cost_info(model: &Model) -> Option<LanguageModelCostInfo> {
+    let (input, output, cached_input) = match model {
+        Model::ThreePointFiveTurbo => (0.5, 1.5, 0.5),
+        Model::Four => (30., 60., 30.),
+        Model::FourTurbo => (10., 30., 10.),
+        Model::FourOmniMini => (0.15, 0.6, 0.075),
+        Model::FourPointOneNano => (0.1, 0.4, 0.025),
+        Model::O1 => (15., 60., 7.5),
+        Model::O3Mini => (1.1, 4.4, 0.55),
+        Model::O3 => (2., 8., 0.5),
+        Model::Five | Model::FiveCodex | Model::FivePointOne => (1.25, 10., 0.125),
+        Model::FiveMini => (0.25, 2., 0.025),
+        Model::FiveNano => (0.05, 0.4, 0.005),
+        Model::FivePointTwo | Model::FivePointTwoCodex => (1.75, 14., 0.175),
+        Model::FivePointThreeCodex | Model::Custom { .. } => return None,
+    };
+    Some(LanguageModelCostInfo::TokenCost {
+        input_token_cost_per_1m: input,
+        output_token_cost_per_1m: output,
+        cache_creation_input_token_cost_per_1m: input,
+        cache_read_input_token_cost_per_1m: cached_input,
+    })
+}
+
 impl LanguageModel for OpenAiLanguageModel {
     fn id(&self) -> LanguageModelId {
         self.id.clone()
@@ -340,6 +366,10 @@ impl LanguageModel for OpenAiLanguageModel {
         format!("openai/{}", self.model.id())
     }
 
+    fn model_cost_info(&self) -> Option<LanguageModelCostInfo> {
+        cost_info(&self.model)
+    }
+
     fn max_token_count(&self) -> u64 {
         self.model.max_token_count()
     }
@@ -502,7 +532,11 @@ pub fn into_open_ai(
         stream,
         stop: request.stop,
         temperature: request.temperature.or(Some(1.0)),
-        max_completion_tokens: max_output_tokens,
+        max_completion_tokens: request
+            .max_tokens
+            .into_iter()
+            .chain(max_output_tokens)
+            .min(),
         parallel_tool_calls: if supports_parallel_tool_calls && !request.tools.is_empty() {
             // Disable parallel tool calls, as the Agent currently expects a maximum of one per turn.
             Some(false)
@@ -555,6 +589,7 @@ pub fn into_open_ai_response(
         temperature,
         thinking_allowed: _,
         thinking_effort: _,
+        max_tokens,
     } = request;
 
     let mut input_items = Vec::new();
@@ -578,7 +613,7 @@ pub fn into_open_ai_response(
         stream,
         temperature,
         top_p: None,
-        max_output_tokens,
+        max_output_tokens: max_tokens.into_iter().chain(max_output_tokens).min(),
         parallel_tool_calls: if tools.is_empty() {
             None
         } else {
@@ -779,11 +814,15 @@ impl OpenAiEventMapper {
     ) -> Vec<Result<LanguageModelCompletionEvent, LanguageModelCompletionError>> {
         let mut events = Vec::new();
         if let Some(usage) = event.usage {
+            let cached_tokens = usage
+                .prompt_tokens_details
+                .as_ref()
+                .map_or(0, |details| details.cached_tokens);
             events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
-                input_tokens: usage.prompt_tokens,
+                input_tokens: usage.prompt_tokens.saturating_sub(cached_tokens),
                 output_tokens: usage.completion_tokens,
                 cache_creation_input_tokens: 0,
-                cache_read_input_tokens: 0,
+                cache_read_input_tokens: cached_tokens,
             })));
         }
 
@@ -1119,11 +1158,18 @@ impl OpenAiResponseEventMapper {
 }
 
 fn token_usage_from_response_usage(usage: &ResponsesUsage) -> TokenUsage {
+    let cached_tokens = usage
+        .input_tokens_details
+        .as_ref()
+        .map_or(0, |details| details.cached_tokens);
     TokenUsage {
-        input_tokens: usage.input_tokens.unwrap_or_default(),
+        input_tokens: usage
+            .input_tokens
+            .unwrap_or_default()
+            .saturating_sub(cached_tokens),
         output_tokens: usage.output_tokens.unwrap_or_default(),
         cache_creation_input_tokens: 0,
-        cache_read_input_tokens: 0,
+        cache_read_input_tokens: cached_tokens,
     }
 }
 
@@ -1435,6 +1481,7 @@ mod tests {
             temperature: None,
             thinking_allowed: true,
             thinking_effort: None,
+            max_tokens: None,
         };
 
         // Validate that all models are supported by tiktoken-rs
@@ -1471,6 +1518,7 @@ mod tests {
                         input_tokens: Some(5),
                         output_tokens: Some(3),
                         total_tokens: Some(8),
+                        input_tokens_details: None,
                     }),
                     ..Default::default()
                 },
@@ -1573,6 +1621,7 @@ mod tests {
             temperature: None,
             thinking_allowed: false,
             thinking_effort: None,
+            max_tokens: None,
         };
 
         let response = into_open_ai_response(
@@ -1697,6 +1746,7 @@ mod tests {
                     input_tokens: Some(10),
                     output_tokens: Some(20),
                     total_tokens: Some(30),
+                    input_tokens_details: None,
                 }),
                 ..Default::default()
             },
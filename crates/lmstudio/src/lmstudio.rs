@@ -431,6 +431,49 @@ pub async fn stream_chat_completion(
     }
 }
 
+#[derive(Serialize, Debug)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub input: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EmbeddingsResponse {
+    pub data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EmbeddingData {
+    pub embedding: Vec<f32>,
+    pub index: usize,
+}
+
+pub async fn embeddings(
+    client: &dyn HttpClient,
+    api_url: &str,
+    request: EmbeddingsRequest,
+) -> Result<EmbeddingsResponse> {
+    let uri = format!("{api_url}/embeddings");
+    let request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    anyhow::ensure!(
+        response.status().is_success(),
+        "Failed to connect to LM Studio API: {} {}",
+        response.status(),
+        body,
+    );
+    serde_json::from_str(&body).context("Unable to parse LM Studio embeddings response")
+}
+
 pub async fn get_models(
     client: &dyn HttpClient,
     api_url: &str,
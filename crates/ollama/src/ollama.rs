@@ -326,6 +326,47 @@ pub async fn get_models(
     Ok(response.models)
 }
 
+#[derive(Serialize, Debug)]
+pub struct EmbedRequest {
+    pub model: String,
+    pub input: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EmbedResponse {
+    pub embeddings: Vec<Vec<f32>>,
+}
+
+/// Compute embeddings for a batch of inputs with an embedding model, such as `nomic-embed-text`.
+pub async fn embed(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    request: EmbedRequest,
+) -> Result<EmbedResponse> {
+    let uri = format!("{api_url}/api/embed");
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .when_some(api_key, |builder, api_key| {
+            builder.header("Authorization", format!("Bearer {api_key}"))
+        })
+        .body(AsyncBody::from(serde_json::to_string(&request)?))?;
+
+    let mut response = client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    anyhow::ensure!(
+        response.status().is_success(),
+        "Failed to connect to Ollama API: {} {}",
+        response.status(),
+        body,
+    );
+    serde_json::from_str(&body).context("Unable to parse Ollama embed response")
+}
+
 /// Fetch details of a model, used to determine model capabilities
 pub async fn show_model(
     client: &dyn HttpClient,
@@ -465,6 +465,15 @@ pub struct Usage {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub total_tokens: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct PromptTokensDetails {
+    /// The number of prompt tokens that were read from the prompt cache.
+    #[serde(default)]
+    pub cached_tokens: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
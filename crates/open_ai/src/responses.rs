@@ -212,6 +212,15 @@ pub struct ResponseUsage {
     pub output_tokens: Option<u64>,
     #[serde(default)]
     pub total_tokens: Option<u64>,
+    #[serde(default)]
+    pub input_tokens_details: Option<ResponseInputTokensDetails>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct ResponseInputTokensDetails {
+    /// The number of input tokens that were read from the prompt cache.
+    #[serde(default)]
+    pub cached_tokens: u64,
 }
 
 #[derive(Deserialize, Debug, Clone)]
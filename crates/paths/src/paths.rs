@@ -437,6 +437,18 @@ pub fn remote_servers_dir() -> &'static PathBuf {
     REMOTE_SERVERS_DIR.get_or_init(|| data_dir().join("remote_servers"))
 }
 
+/// Returns the path to the directory where changes made to remote files while offline are queued.
+pub fn remote_write_behind_dir() -> &'static PathBuf {
+    static REMOTE_WRITE_BEHIND_DIR: OnceLock<PathBuf> = OnceLock::new();
+    REMOTE_WRITE_BEHIND_DIR.get_or_init(|| data_dir().join("remote_write_behind"))
+}
+
+/// Returns the path to the directory where recorded collaboration sessions are stored.
+pub fn session_recordings_dir() -> &'static PathBuf {
+    static SESSION_RECORDINGS_DIR: OnceLock<PathBuf> = OnceLock::new();
+    SESSION_RECORDINGS_DIR.get_or_init(|| data_dir().join("session_recordings"))
+}
+
 /// Returns the path to the directory where the devcontainer CLI is installed.
 pub fn devcontainer_dir() -> &'static PathBuf {
     static DEVCONTAINER_DIR: OnceLock<PathBuf> = OnceLock::new();
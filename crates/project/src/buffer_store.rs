@@ -6,6 +6,7 @@ use crate::{
 use anyhow::{Context as _, Result, anyhow};
 use client::Client;
 use collections::{HashMap, HashSet, hash_map};
+use fs::archive_fs::is_path_in_archive;
 use futures::{Future, FutureExt as _, channel::oneshot, future::Shared};
 use gpui::{
     App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity,
@@ -28,6 +29,12 @@ use text::{BufferId, ReplicaId};
 use util::{ResultExt as _, TryFutureExt, debug_panic, maybe, rel_path::RelPath};
 use worktree::{File, PathChange, ProjectEntryId, Worktree, WorktreeId, WorktreeSettings};
 
+mod write_behind;
+
+use write_behind::WriteBehind;
+pub use write_behind::WriteBehindResolution;
+pub(crate) use write_behind::write_behind_queue_path;
+
 /// A set of open buffers.
 pub struct BufferStore {
     state: BufferStoreState,
@@ -71,6 +78,7 @@ struct RemoteBufferStore {
     remote_buffer_listeners:
         HashMap<BufferId, Vec<oneshot::Sender<anyhow::Result<Entity<Buffer>>>>>,
     worktree_store: Entity<WorktreeStore>,
+    write_behind: Option<WriteBehind>,
 }
 
 struct LocalBufferStore {
@@ -92,6 +100,10 @@ pub enum BufferStoreEvent {
         buffer: Entity<Buffer>,
         old_file: Option<Arc<dyn language::File>>,
     },
+    /// Changes made to this buffer while offline conflict with changes made to the remote file.
+    WriteBehindConflict {
+        buffer: Entity<Buffer>,
+    },
 }
 
 #[derive(Default, Debug, Clone)]
@@ -692,7 +704,11 @@ impl LocalBufferStore {
                     } else {
                         settings.is_path_read_only(&project_path.path)
                     };
-                    if is_read_only {
+                    if is_path_in_archive(&file.worktree.read(cx).abs_path()) {
+                        buffer.update(cx, |buffer, cx| {
+                            buffer.set_capability(Capability::ReadOnly, cx);
+                        });
+                    } else if is_read_only {
                         buffer.update(cx, |buffer, cx| {
                             buffer.set_capability(Capability::Read, cx);
                         });
@@ -808,6 +824,7 @@ impl BufferStore {
                 project_id: remote_id,
                 upstream_client,
                 worktree_store: worktree_store.clone(),
+                write_behind: None,
             }),
             downstream_client: None,
             opened_buffers: Default::default(),
@@ -914,6 +931,9 @@ impl BufferStore {
         buffer: Entity<Buffer>,
         cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
+        if self.is_working_offline() {
+            return self.save_buffer_offline(buffer, cx);
+        }
         match &mut self.state {
             BufferStoreState::Local(this) => this.save_buffer(buffer, cx),
             BufferStoreState::Remote(this) => this.save_remote_buffer(buffer, None, cx),
@@ -926,6 +946,11 @@ impl BufferStore {
         path: ProjectPath,
         cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
+        if self.is_working_offline() {
+            return Task::ready(Err(anyhow!(
+                "cannot save to a new path while disconnected from the remote host"
+            )));
+        }
         let old_file = buffer.read(cx).file().cloned();
         let task = match &self.state {
             BufferStoreState::Local(this) => this.save_buffer_as(buffer.clone(), path, cx),
@@ -963,8 +988,10 @@ impl BufferStore {
         let handle = cx.entity().downgrade();
         buffer_entity.update(cx, move |_, cx| {
             cx.on_release(move |buffer, cx| {
+                let location = write_behind::buffer_location(buffer, cx);
                 handle
-                    .update(cx, |_, cx| {
+                    .update(cx, |this, cx| {
+                        this.write_behind_buffer_released(buffer.remote_id(), location, cx);
                         cx.emit(BufferStoreEvent::BufferDropped(buffer.remote_id()))
                     })
                     .ok();
@@ -998,6 +1025,7 @@ impl BufferStore {
         }
 
         cx.subscribe(&buffer_entity, Self::on_buffer_event).detach();
+        self.record_synced_state(&buffer_entity, cx);
         cx.emit(BufferStoreEvent::BufferAdded(buffer_entity));
         Ok(())
     }
@@ -1073,6 +1101,23 @@ impl BufferStore {
         (buffers, incomplete_buffer_ids)
     }
 
+    /// Called when the connection to the remote server is lost for good.
+    ///
+    /// If write-behind is enabled, buffers stay editable and changes are queued locally,
+    /// otherwise they become read-only as when disconnected from a collaboration host.
+    pub fn disconnected_from_remote_server(&mut self, cx: &mut Context<Self>) {
+        if !self.start_working_offline(cx) {
+            self.disconnected_from_host(cx);
+            return;
+        }
+        for buffer in self.buffers() {
+            buffer.update(cx, |buffer, _| buffer.give_up_waiting());
+        }
+        if let Some(remote) = self.as_remote_mut() {
+            remote.remote_buffer_listeners.clear()
+        }
+    }
+
     pub fn disconnected_from_host(&mut self, cx: &mut App) {
         for open_buffer in self.opened_buffers.values_mut() {
             if let Some(buffer) = open_buffer.upgrade() {
@@ -1146,7 +1191,14 @@ impl BufferStore {
             BufferEvent::FileHandleChanged => {
                 self.buffer_changed_file(buffer, cx);
             }
+            BufferEvent::Edited | BufferEvent::DirtyChanged => {
+                self.schedule_offline_mirror(cx);
+            }
+            BufferEvent::Saved => {
+                self.record_synced_state(&buffer, cx);
+            }
             BufferEvent::Reloaded => {
+                self.record_synced_state(&buffer, cx);
                 let Some((downstream_client, project_id)) = self.downstream_client.as_ref() else {
                     return;
                 };
@@ -0,0 +1,642 @@
+//! Keeps remote buffers editable while the connection to a remote server is lost.
+//!
+//! While offline, unsaved edits and saves are mirrored to a queue file on this machine that is
+//! shared by all projects on the same host. Once the connection is re-established, or a project
+//! for the same host opens the buffer's worktree again, the queued saves are written to the remote
+//! host and unsaved edits are restored, unless the remote file changed in the meantime, in which
+//! case the user is asked how to resolve the conflict.
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use fs::{Fs, MTime, RemoveOptions};
+use futures::{FutureExt as _, future::Shared};
+use gpui::{App, AppContext as _, Context, Entity, Global, Subscription, Task, WeakEntity};
+use language::Buffer;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use text::BufferId;
+use util::{ResultExt as _, rel_path::RelPath};
+use worktree::{File, Worktree};
+
+use crate::{
+    ProjectPath,
+    buffer_store::{BufferStore, BufferStoreEvent},
+    trusted_worktrees::RemoteHostLocation,
+    worktree_store::WorktreeStoreEvent,
+};
+
+const MIRROR_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// How to resolve a conflict between changes made offline and the remote file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteBehindResolution {
+    /// Write the offline changes over the remote file.
+    Overwrite,
+    /// Drop the offline changes and keep the remote file.
+    Discard,
+    /// Keep the offline changes queued until the next time the project connects.
+    Defer,
+}
+
+/// Changes made to a remote file while offline.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(super) struct PendingBuffer {
+    /// Absolute path of the worktree's root on the remote host.
+    worktree_root: String,
+    path: String,
+    /// The remote file's modification time when the buffer was last in sync with it.
+    base_mtime: Option<MTime>,
+    /// A digest of the remote file's contents when the buffer was last in sync with it.
+    base_digest: Option<String>,
+    /// Contents saved while offline, to be written to the remote file.
+    saved_text: Option<String>,
+    /// Unsaved contents, to be restored into the buffer.
+    unsaved_text: Option<String>,
+}
+
+impl PendingBuffer {
+    fn matches(&self, worktree_root: &str, path: &str) -> bool {
+        self.worktree_root == worktree_root && self.path == path
+    }
+}
+
+#[derive(Clone, Default)]
+struct SyncedState {
+    mtime: Option<MTime>,
+    digest: Option<String>,
+}
+
+pub(super) struct WriteBehind {
+    queue: Entity<WriteBehindQueue>,
+    offline: bool,
+    /// The remote state each open buffer was last in sync with.
+    synced: HashMap<BufferId, SyncedState>,
+    mirror_task: Option<Task<()>>,
+    _worktree_store_subscription: Subscription,
+}
+
+/// The changes queued for one host.
+///
+/// Every project connected to the host shares the same queue, so that they don't overwrite each
+/// other's changes when writing the queue file.
+struct WriteBehindQueue {
+    fs: Arc<dyn Fs>,
+    queue_path: PathBuf,
+    pending: Vec<PendingBuffer>,
+    /// Pending entries whose buffers are being written or awaiting a conflict resolution.
+    in_flight: HashSet<(String, String)>,
+    loaded: Shared<Task<()>>,
+    persist_task: Option<Task<()>>,
+}
+
+#[derive(Default)]
+struct WriteBehindQueues(HashMap<PathBuf, WeakEntity<WriteBehindQueue>>);
+
+impl Global for WriteBehindQueues {}
+
+/// The file changes made offline to projects on `host` are queued in.
+pub(crate) fn write_behind_queue_path(host: &RemoteHostLocation) -> PathBuf {
+    let host = match &host.user_name {
+        Some(user_name) => format!("{user_name}@{}", host.host_identifier),
+        None => host.host_identifier.to_string(),
+    };
+    paths::remote_write_behind_dir().join(format!("{}.json", digest(&host)))
+}
+
+fn digest(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+pub(super) fn buffer_location(buffer: &Buffer, cx: &App) -> Option<(String, String)> {
+    let file = File::from_dyn(buffer.file())?;
+    Some((
+        file.worktree
+            .read(cx)
+            .abs_path()
+            .to_string_lossy()
+            .into_owned(),
+        file.path.as_unix_str().to_string(),
+    ))
+}
+
+impl BufferStore {
+    /// Keeps this remote buffer store's buffers editable when the connection is lost,
+    /// queueing offline changes in the file at `queue_path`.
+    ///
+    /// Changes already queued in that file are replayed as the worktrees they belong to are added.
+    pub fn enable_write_behind(
+        &mut self,
+        fs: Arc<dyn Fs>,
+        queue_path: PathBuf,
+        cx: &mut Context<Self>,
+    ) {
+        if self.as_remote().is_none() {
+            return;
+        }
+        let subscription = cx.subscribe(&self.worktree_store, |this, _, event, cx| {
+            if let WorktreeStoreEvent::WorktreeAdded(worktree) = event {
+                this.replay_write_behind(worktree.clone(), cx);
+            }
+        });
+        let queue = WriteBehindQueue::for_path(fs, queue_path, cx);
+        let loaded = queue.read(cx).loaded.clone();
+        if let Some(remote) = self.as_remote_mut() {
+            remote.write_behind = Some(WriteBehind {
+                queue,
+                offline: false,
+                synced: HashMap::default(),
+                mirror_task: None,
+                _worktree_store_subscription: subscription,
+            });
+        }
+        for buffer in self.buffers().collect::<Vec<_>>() {
+            self.record_synced_state(&buffer, cx);
+        }
+
+        cx.spawn(async move |this, cx| {
+            loaded.await;
+            this.update(cx, |this, cx| {
+                let worktrees = this.worktree_store.read(cx).worktrees().collect::<Vec<_>>();
+                for worktree in worktrees {
+                    this.replay_write_behind(worktree, cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Whether the connection is lost and changes are being queued locally.
+    pub fn is_working_offline(&self) -> bool {
+        self.write_behind()
+            .is_some_and(|write_behind| write_behind.offline)
+    }
+
+    fn write_behind(&self) -> Option<&WriteBehind> {
+        self.as_remote()?.write_behind.as_ref()
+    }
+
+    fn write_behind_mut(&mut self) -> Option<&mut WriteBehind> {
+        self.as_remote_mut()?.write_behind.as_mut()
+    }
+
+    /// Starts queueing changes locally, returning `false` if write-behind isn't enabled.
+    pub(super) fn start_working_offline(&mut self, cx: &mut Context<Self>) -> bool {
+        let Some(write_behind) = self.write_behind_mut() else {
+            return false;
+        };
+        write_behind.offline = true;
+        self.mirror_offline_buffers(cx);
+        true
+    }
+
+    /// Called when the connection to the remote server dropped and is being re-established.
+    ///
+    /// Changes are queued locally in the meantime, so that saving keeps working.
+    pub fn reconnecting_to_remote_server(&mut self, cx: &mut Context<Self>) {
+        self.start_working_offline(cx);
+    }
+
+    /// Called when the connection to the remote server was re-established, writing the changes
+    /// queued while it was lost to the remote host.
+    pub fn reconnected_to_remote_server(&mut self, cx: &mut Context<Self>) {
+        if !self.is_working_offline() {
+            return;
+        }
+        // Queue the latest edits first, so that replaying doesn't restore older ones.
+        self.mirror_offline_buffers(cx);
+        if let Some(write_behind) = self.write_behind_mut() {
+            write_behind.offline = false;
+            write_behind.mirror_task = None;
+        }
+        let worktrees = self.worktree_store.read(cx).worktrees().collect::<Vec<_>>();
+        for worktree in worktrees {
+            self.replay_write_behind(worktree, cx);
+        }
+    }
+
+    pub(super) fn record_synced_state(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(write_behind) = self.write_behind() else {
+            return;
+        };
+        if write_behind.offline {
+            return;
+        }
+        let buffer = buffer.read(cx);
+        let buffer_id = buffer.remote_id();
+        let mtime = buffer.saved_mtime();
+        if buffer.is_dirty() {
+            // The buffer's contents don't match the remote file, so only its modification time
+            // is known. A digest taken while the file was in the same state still applies.
+            let digest = write_behind
+                .synced
+                .get(&buffer_id)
+                .filter(|synced| synced.mtime == mtime)
+                .and_then(|synced| synced.digest.clone());
+            if let Some(write_behind) = self.write_behind_mut() {
+                write_behind
+                    .synced
+                    .insert(buffer_id, SyncedState { mtime, digest });
+            }
+            return;
+        }
+        let text = buffer.as_rope().clone();
+        cx.spawn(async move |this, cx| {
+            let digest = cx
+                .background_spawn(async move { digest(&text.to_string()) })
+                .await;
+            this.update(cx, |this, _| {
+                if let Some(write_behind) = this.write_behind_mut() {
+                    write_behind.synced.insert(
+                        buffer_id,
+                        SyncedState {
+                            mtime,
+                            digest: Some(digest),
+                        },
+                    );
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Forgets a released buffer, dropping any unsaved changes mirrored while offline.
+    pub(super) fn write_behind_buffer_released(
+        &mut self,
+        buffer_id: BufferId,
+        location: Option<(String, String)>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(write_behind) = self.write_behind_mut() else {
+            return;
+        };
+        let synced = write_behind.synced.remove(&buffer_id).unwrap_or_default();
+        if let Some((worktree_root, path)) = location
+            && write_behind.offline
+        {
+            write_behind.queue.update(cx, |queue, cx| {
+                queue.upsert(synced, worktree_root, path, |pending| {
+                    pending.unsaved_text = None
+                });
+                queue.persist(cx);
+            });
+        }
+    }
+
+    /// Mirrors the buffers' contents shortly after they were edited offline.
+    pub(super) fn schedule_offline_mirror(&mut self, cx: &mut Context<Self>) {
+        let Some(write_behind) = self.write_behind_mut() else {
+            return;
+        };
+        if !write_behind.offline || write_behind.mirror_task.is_some() {
+            return;
+        }
+        write_behind.mirror_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(MIRROR_DEBOUNCE).await;
+            this.update(cx, |this, cx| {
+                if let Some(write_behind) = this.write_behind_mut() {
+                    write_behind.mirror_task = None;
+                }
+                this.mirror_offline_buffers(cx);
+            })
+            .ok();
+        }));
+    }
+
+    fn mirror_offline_buffers(&mut self, cx: &mut Context<Self>) {
+        let Some(write_behind) = self.write_behind() else {
+            return;
+        };
+        let mut mirrored = Vec::new();
+        for buffer in self.buffers() {
+            let buffer = buffer.read(cx);
+            let Some(location) = buffer_location(buffer, cx) else {
+                continue;
+            };
+            let synced = write_behind
+                .synced
+                .get(&buffer.remote_id())
+                .cloned()
+                .unwrap_or_default();
+            let unsaved_text = buffer.is_dirty().then(|| buffer.text());
+            mirrored.push((synced, location, unsaved_text));
+        }
+        write_behind.queue.update(cx, |queue, cx| {
+            for (synced, (worktree_root, path), unsaved_text) in mirrored {
+                queue.upsert(synced, worktree_root, path, |pending| {
+                    pending.unsaved_text = unsaved_text
+                });
+            }
+            queue.persist(cx);
+        });
+    }
+
+    /// Queues a save made while offline and marks the buffer as saved.
+    pub(super) fn save_buffer_offline(
+        &mut self,
+        buffer: Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let (buffer_id, location, text, version, mtime) = {
+            let buffer = buffer.read(cx);
+            (
+                buffer.remote_id(),
+                buffer_location(buffer, cx),
+                buffer.text(),
+                buffer.version(),
+                buffer.saved_mtime(),
+            )
+        };
+        let Some((worktree_root, path)) = location else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "cannot save a new file while disconnected from the remote host"
+            )));
+        };
+        let Some(write_behind) = self.write_behind() else {
+            return Task::ready(Err(anyhow::anyhow!("not working offline")));
+        };
+        let synced = write_behind
+            .synced
+            .get(&buffer_id)
+            .cloned()
+            .unwrap_or_default();
+        write_behind.queue.update(cx, |queue, cx| {
+            queue.upsert(synced, worktree_root, path, |pending| {
+                pending.saved_text = Some(text);
+                pending.unsaved_text = None;
+            });
+            queue.persist(cx);
+        });
+        buffer.update(cx, |buffer, cx| buffer.did_save(version, mtime, cx));
+        Task::ready(Ok(()))
+    }
+
+    /// Writes the changes queued for files in `worktree` to the remote host.
+    fn replay_write_behind(&mut self, worktree: Entity<Worktree>, cx: &mut Context<Self>) {
+        let worktree_root = worktree.read(cx).abs_path().to_string_lossy().into_owned();
+        let worktree_id = worktree.read(cx).id();
+        let Some(write_behind) = self.write_behind() else {
+            return;
+        };
+        if write_behind.offline {
+            return;
+        }
+        let entries = write_behind
+            .queue
+            .update(cx, |queue, _| queue.claim(&worktree_root));
+        for pending in entries {
+            let Some(path) = RelPath::unix(&pending.path).log_err() else {
+                continue;
+            };
+            let open = self.open_buffer(
+                ProjectPath {
+                    worktree_id,
+                    path: path.into_arc(),
+                },
+                cx,
+            );
+            cx.spawn(async move |this, cx| {
+                let buffer = open.await?;
+                let (mtime, text) = buffer.read_with(cx, |buffer, _| {
+                    let mtime = buffer.file().and_then(|file| file.disk_state().mtime());
+                    (mtime, buffer.as_rope().clone())
+                });
+                let remote_digest = cx
+                    .background_spawn(async move { digest(&text.to_string()) })
+                    .await;
+                let remote_changed = mtime != pending.base_mtime
+                    && pending.base_digest.as_ref() != Some(&remote_digest);
+                let task = this.update(cx, |this, cx| {
+                    if remote_changed {
+                        cx.emit(BufferStoreEvent::WriteBehindConflict { buffer });
+                        Task::ready(Ok(()))
+                    } else {
+                        this.apply_pending_buffer(buffer, pending, cx)
+                    }
+                })?;
+                task.await
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    /// Resolves a conflict reported through [`BufferStoreEvent::WriteBehindConflict`].
+    pub fn resolve_write_behind_conflict(
+        &mut self,
+        buffer: Entity<Buffer>,
+        resolution: WriteBehindResolution,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some((worktree_root, path)) = buffer_location(buffer.read(cx), cx) else {
+            return Task::ready(Ok(()));
+        };
+        let Some(queue) = self
+            .write_behind()
+            .map(|write_behind| write_behind.queue.clone())
+        else {
+            return Task::ready(Ok(()));
+        };
+        let Some(pending) = queue
+            .read(cx)
+            .pending
+            .iter()
+            .find(|pending| pending.matches(&worktree_root, &path))
+            .cloned()
+        else {
+            return Task::ready(Ok(()));
+        };
+        match resolution {
+            WriteBehindResolution::Overwrite => self.apply_pending_buffer(buffer, pending, cx),
+            WriteBehindResolution::Discard => {
+                queue.update(cx, |queue, cx| {
+                    queue.remove(&worktree_root, &path);
+                    queue.persist(cx);
+                });
+                Task::ready(Ok(()))
+            }
+            WriteBehindResolution::Defer => {
+                queue.update(cx, |queue, _| {
+                    queue.in_flight.remove(&(worktree_root, path));
+                });
+                Task::ready(Ok(()))
+            }
+        }
+    }
+
+    fn apply_pending_buffer(
+        &mut self,
+        buffer: Entity<Buffer>,
+        pending: PendingBuffer,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let queue = self
+            .write_behind()
+            .map(|write_behind| write_behind.queue.clone());
+        cx.spawn(async move |this, cx| {
+            if let Some(saved_text) = pending.saved_text.clone() {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_text(saved_text, cx);
+                });
+                this.update(cx, |this, cx| this.save_buffer(buffer.clone(), cx))?
+                    .await?;
+            }
+            if let Some(unsaved_text) = pending.unsaved_text.clone() {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_text(unsaved_text, cx);
+                });
+            }
+            if let Some(queue) = queue {
+                queue.update(cx, |queue, cx| {
+                    queue.remove(&pending.worktree_root, &pending.path);
+                    queue.persist(cx);
+                });
+            }
+            Ok(())
+        })
+    }
+}
+
+impl WriteBehindQueue {
+    /// Returns the queue stored at `queue_path`, loading it if no project uses it yet.
+    fn for_path(fs: Arc<dyn Fs>, queue_path: PathBuf, cx: &mut App) -> Entity<Self> {
+        if let Some(queue) = cx
+            .default_global::<WriteBehindQueues>()
+            .0
+            .get(&queue_path)
+            .and_then(|queue| queue.upgrade())
+        {
+            return queue;
+        }
+
+        let queue = cx.new(|cx| {
+            let load = {
+                let fs = fs.clone();
+                let queue_path = queue_path.clone();
+                cx.background_spawn(async move {
+                    if !fs.is_file(&queue_path).await {
+                        return Ok(Vec::new());
+                    }
+                    let content = fs.load(&queue_path).await?;
+                    serde_json::from_str::<Vec<PendingBuffer>>(&content)
+                        .with_context(|| format!("parsing write-behind queue {queue_path:?}"))
+                })
+            };
+            let loaded = cx
+                .spawn(async move |this, cx| {
+                    let Some(pending) = load.await.log_err() else {
+                        return;
+                    };
+                    this.update(cx, |this, _| this.pending.extend(pending)).ok();
+                })
+                .shared();
+            Self {
+                fs,
+                queue_path: queue_path.clone(),
+                pending: Vec::new(),
+                in_flight: HashSet::default(),
+                loaded,
+                persist_task: None,
+            }
+        });
+        cx.default_global::<WriteBehindQueues>()
+            .0
+            .insert(queue_path, queue.downgrade());
+        queue
+    }
+
+    /// Returns the entries for files in the worktree at `worktree_root` that aren't being
+    /// written already, marking them as in flight.
+    fn claim(&mut self, worktree_root: &str) -> Vec<PendingBuffer> {
+        let entries = self
+            .pending
+            .iter()
+            .filter(|pending| pending.worktree_root == worktree_root)
+            .filter(|pending| {
+                !self
+                    .in_flight
+                    .contains(&(pending.worktree_root.clone(), pending.path.clone()))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for pending in &entries {
+            self.in_flight
+                .insert((pending.worktree_root.clone(), pending.path.clone()));
+        }
+        entries
+    }
+
+    fn upsert(
+        &mut self,
+        synced: SyncedState,
+        worktree_root: String,
+        path: String,
+        update: impl FnOnce(&mut PendingBuffer),
+    ) {
+        let index = match self
+            .pending
+            .iter()
+            .position(|pending| pending.matches(&worktree_root, &path))
+        {
+            Some(index) => index,
+            None => {
+                self.pending.push(PendingBuffer {
+                    worktree_root,
+                    path,
+                    base_mtime: synced.mtime,
+                    base_digest: synced.digest,
+                    saved_text: None,
+                    unsaved_text: None,
+                });
+                self.pending.len() - 1
+            }
+        };
+        update(&mut self.pending[index]);
+        let pending = &self.pending[index];
+        if pending.saved_text.is_none() && pending.unsaved_text.is_none() {
+            self.pending.remove(index);
+        }
+    }
+
+    fn remove(&mut self, worktree_root: &str, path: &str) {
+        self.pending
+            .retain(|pending| !pending.matches(worktree_root, path));
+        self.in_flight
+            .remove(&(worktree_root.to_string(), path.to_string()));
+    }
+
+    fn persist(&mut self, cx: &mut Context<Self>) {
+        let fs = self.fs.clone();
+        let queue_path = self.queue_path.clone();
+        let content = if self.pending.is_empty() {
+            None
+        } else {
+            serde_json::to_string_pretty(&self.pending).log_err()
+        };
+        self.persist_task = Some(cx.background_spawn(async move {
+            let result = async {
+                match content {
+                    Some(content) => {
+                        if let Some(parent) = queue_path.parent() {
+                            fs.create_dir(parent).await?;
+                        }
+                        fs.atomic_write(queue_path, content).await
+                    }
+                    None => {
+                        fs.remove_file(
+                            &queue_path,
+                            RemoveOptions {
+                                recursive: false,
+                                ignore_if_not_exists: true,
+                            },
+                        )
+                        .await
+                    }
+                }
+            };
+            result.await.log_err();
+        }));
+    }
+}
@@ -7,7 +7,12 @@ use std::time::Duration;
 
 use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet};
-use context_server::{ContextServer, ContextServerCommand, ContextServerId};
+use context_server::{
+    ContextServer, ContextServerClientDelegate, ContextServerCommand, ContextServerId,
+    client::NotificationSubscription,
+    protocol::{InitializedContextServerProtocol, ServerCapability},
+    types::{self as mcp, Notification as _},
+};
 use futures::{FutureExt as _, future::Either, future::join_all};
 use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity, actions};
 use itertools::Itertools;
@@ -15,6 +20,7 @@ use registry::ContextServerDescriptorRegistry;
 use remote::RemoteClient;
 use rpc::{AnyProtoClient, TypedEnvelope, proto};
 use settings::{Settings as _, SettingsStore};
+use url::Url;
 use util::{ResultExt as _, rel_path::RelPath};
 
 use crate::{
@@ -221,10 +227,21 @@ pub struct ContextServerStore {
     registry: Entity<ContextServerDescriptorRegistry>,
     update_servers_task: Option<Task<Result<()>>>,
     context_server_factory: Option<ContextServerFactory>,
+    client_delegate: Option<Arc<dyn ContextServerClientDelegate>>,
     needs_server_update: bool,
+    resources: HashMap<ContextServerId, ServerResources>,
     _subscriptions: Vec<Subscription>,
 }
 
+/// The resources and resource templates advertised by a running context server.
+struct ServerResources {
+    resources: Vec<mcp::Resource>,
+    templates: Vec<mcp::ResourceTemplate>,
+    subscribed_uris: HashSet<Url>,
+    load_task: Task<()>,
+    _notification_subscriptions: Vec<NotificationSubscription>,
+}
+
 pub struct ServerStatusChangedEvent {
     pub server_id: ContextServerId,
     pub status: ContextServerStatus,
@@ -232,6 +249,18 @@ pub struct ServerStatusChangedEvent {
 
 impl EventEmitter<ServerStatusChangedEvent> for ContextServerStore {}
 
+pub enum ContextServerResourceEvent {
+    /// The list of resources or resource templates of a server changed.
+    ResourcesChanged { server_id: ContextServerId },
+    /// The contents of a subscribed resource changed.
+    ResourceUpdated {
+        server_id: ContextServerId,
+        uri: String,
+    },
+}
+
+impl EventEmitter<ContextServerResourceEvent> for ContextServerStore {}
+
 impl ContextServerStore {
     pub fn local(
         worktree_store: Entity<WorktreeStore>,
@@ -287,6 +316,38 @@ impl ContextServerStore {
         }
     }
 
+    /// Sets the delegate that serves sampling and elicitation requests from
+    /// context servers.
+    ///
+    /// Servers advertise sampling and elicitation support when they are
+    /// initialized, so the servers that were started without a delegate are
+    /// restarted to pick it up.
+    pub fn set_client_delegate(
+        &mut self,
+        delegate: Arc<dyn ContextServerClientDelegate>,
+        cx: &mut Context<Self>,
+    ) {
+        let had_delegate = self.client_delegate.replace(delegate).is_some();
+        if had_delegate {
+            return;
+        }
+
+        let servers_to_restart = self
+            .servers
+            .values()
+            .filter(|state| {
+                matches!(
+                    state,
+                    ContextServerState::Starting { .. } | ContextServerState::Running { .. }
+                )
+            })
+            .map(|state| (state.server(), state.configuration()))
+            .collect::<Vec<_>>();
+        for (server, configuration) in servers_to_restart {
+            self.run_server(server, configuration, cx);
+        }
+    }
+
     pub fn is_remote_project(&self) -> bool {
         matches!(self.state, ContextServerStoreState::Remote { .. })
     }
@@ -405,9 +466,11 @@ impl ContextServerStore {
             registry,
             needs_server_update: false,
             servers: HashMap::default(),
+            resources: HashMap::default(),
             server_ids: Default::default(),
             update_servers_task: None,
             context_server_factory,
+            client_delegate: None,
         };
         if maintain_server_loop {
             this.available_context_servers_changed(cx);
@@ -573,6 +636,7 @@ impl ContextServerStore {
         ) {
             self.stop_server(&id, cx).log_err();
         }
+        server.set_client_delegate(self.client_delegate.clone());
         let task = cx.spawn({
             let id = server.id();
             let server = server.clone();
@@ -848,12 +912,324 @@ impl ContextServerStore {
     ) {
         let status = ContextServerStatus::from_state(&state);
         self.servers.insert(id.clone(), state);
+        if status == ContextServerStatus::Running {
+            self.reload_resources(&id, cx);
+        } else {
+            self.clear_resources(&id, cx);
+        }
         cx.emit(ServerStatusChangedEvent {
             server_id: id,
             status,
         });
     }
 
+    /// Returns the resources advertised by all running context servers.
+    pub fn resources(&self) -> impl Iterator<Item = (&ContextServerId, &mcp::Resource)> {
+        self.resources
+            .iter()
+            .flat_map(|(server_id, server_resources)| {
+                server_resources
+                    .resources
+                    .iter()
+                    .map(move |resource| (server_id, resource))
+            })
+    }
+
+    /// Returns the resource templates advertised by all running context servers.
+    pub fn resource_templates(
+        &self,
+    ) -> impl Iterator<Item = (&ContextServerId, &mcp::ResourceTemplate)> {
+        self.resources
+            .iter()
+            .flat_map(|(server_id, server_resources)| {
+                server_resources
+                    .templates
+                    .iter()
+                    .map(move |template| (server_id, template))
+            })
+    }
+
+    pub fn is_subscribed_to_resource(&self, server_id: &ContextServerId, uri: &Url) -> bool {
+        self.resources
+            .get(server_id)
+            .is_some_and(|server_resources| server_resources.subscribed_uris.contains(uri))
+    }
+
+    pub fn read_resource(
+        &self,
+        server_id: &ContextServerId,
+        uri: Url,
+        cx: &App,
+    ) -> Task<Result<mcp::ResourcesReadResponse>> {
+        let client = match self.resource_client(server_id) {
+            Ok(client) => client,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        cx.background_spawn(async move {
+            client
+                .request::<mcp::requests::ResourcesRead>(mcp::ResourcesReadParams {
+                    uri,
+                    meta: None,
+                })
+                .await
+        })
+    }
+
+    /// Subscribes to changes of a resource. Updates are reported via
+    /// [`ContextServerResourceEvent::ResourceUpdated`].
+    pub fn subscribe_to_resource(
+        &mut self,
+        server_id: &ContextServerId,
+        uri: Url,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let client = match self.resource_client(server_id) {
+            Ok(client) => client,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let supports_subscriptions = client
+            .initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false);
+        if !supports_subscriptions {
+            return Task::ready(Err(anyhow::anyhow!(
+                "Context server {server_id} does not support resource subscriptions"
+            )));
+        }
+
+        let server_id = server_id.clone();
+        cx.spawn(async move |this, cx| {
+            client
+                .request::<mcp::requests::ResourcesSubscribe>(mcp::ResourcesSubscribeParams {
+                    uri: uri.clone(),
+                    meta: None,
+                })
+                .await?;
+            this.update(cx, |this, _| {
+                if let Some(server_resources) = this.resources.get_mut(&server_id) {
+                    server_resources.subscribed_uris.insert(uri);
+                }
+            })
+        })
+    }
+
+    pub fn unsubscribe_from_resource(
+        &mut self,
+        server_id: &ContextServerId,
+        uri: Url,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(server_resources) = self.resources.get_mut(server_id) else {
+            return Task::ready(Ok(()));
+        };
+        if !server_resources.subscribed_uris.remove(&uri) {
+            return Task::ready(Ok(()));
+        }
+        let client = match self.resource_client(server_id) {
+            Ok(client) => client,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        cx.background_spawn(async move {
+            client
+                .request::<mcp::requests::ResourcesUnsubscribe>(mcp::ResourcesUnsubscribeParams {
+                    uri,
+                    meta: None,
+                })
+                .await
+        })
+    }
+
+    fn resource_client(
+        &self,
+        server_id: &ContextServerId,
+    ) -> Result<Arc<InitializedContextServerProtocol>> {
+        let server = self
+            .get_running_server(server_id)
+            .with_context(|| format!("Context server {server_id} is not running"))?;
+        let client = server
+            .client()
+            .with_context(|| format!("Context server {server_id} is not initialized"))?;
+        anyhow::ensure!(
+            client.capable(ServerCapability::Resources),
+            "Context server {server_id} does not provide resources"
+        );
+        Ok(client)
+    }
+
+    fn reload_resources(&mut self, server_id: &ContextServerId, cx: &mut Context<Self>) {
+        let Ok(client) = self.resource_client(server_id) else {
+            self.clear_resources(server_id, cx);
+            return;
+        };
+
+        let server_resources =
+            self.resources
+                .entry(server_id.clone())
+                .or_insert_with(|| ServerResources {
+                    resources: Vec::new(),
+                    templates: Vec::new(),
+                    subscribed_uris: HashSet::default(),
+                    load_task: Task::ready(()),
+                    _notification_subscriptions: Self::subscribe_to_resource_notifications(
+                        server_id, &client, cx,
+                    ),
+                });
+
+        let server_id = server_id.clone();
+        server_resources.load_task = cx.spawn(async move |this, cx| {
+            let mut resources = Vec::new();
+            let mut cursor = None;
+            loop {
+                let params = mcp::PaginatedParams { cursor, meta: None };
+                let Some(response) = client
+                    .request::<mcp::requests::ResourcesList>(params)
+                    .await
+                    .log_err()
+                else {
+                    break;
+                };
+                resources.extend(response.resources);
+                cursor = response.next_cursor;
+                if cursor.is_none() {
+                    break;
+                }
+            }
+
+            let mut templates = Vec::new();
+            let mut cursor = None;
+            loop {
+                let params = mcp::PaginatedParams { cursor, meta: None };
+                // Resource templates are optional, so servers may not implement this request.
+                let Ok(response) = client
+                    .request::<mcp::requests::ListResourceTemplates>(params)
+                    .await
+                else {
+                    break;
+                };
+                templates.extend(response.resource_templates);
+                cursor = response.next_cursor;
+                if cursor.is_none() {
+                    break;
+                }
+            }
+
+            this.update(cx, |this, cx| {
+                let Some(server_resources) = this.resources.get_mut(&server_id) else {
+                    return;
+                };
+                server_resources.resources = resources;
+                server_resources.templates = templates;
+                cx.emit(ContextServerResourceEvent::ResourcesChanged { server_id });
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn clear_resources(&mut self, server_id: &ContextServerId, cx: &mut Context<Self>) {
+        if let Some(server_resources) = self.resources.remove(server_id)
+            && (!server_resources.resources.is_empty() || !server_resources.templates.is_empty())
+        {
+            cx.emit(ContextServerResourceEvent::ResourcesChanged {
+                server_id: server_id.clone(),
+            });
+        }
+    }
+
+    fn subscribe_to_resource_notifications(
+        server_id: &ContextServerId,
+        client: &InitializedContextServerProtocol,
+        cx: &mut Context<Self>,
+    ) -> Vec<NotificationSubscription> {
+        let list_changed =
+            client.on_notification(mcp::notifications::ResourcesListChanged::METHOD, {
+                let server_id = server_id.clone();
+                let this = cx.entity().downgrade();
+                Box::new(move |_params, cx: AsyncApp| {
+                    let server_id = server_id.clone();
+                    let this = this.clone();
+                    cx.spawn(async move |cx| {
+                        this.update(cx, |this, cx| this.reload_resources(&server_id, cx))
+                    })
+                    .detach();
+                })
+            });
+
+        let updated = client.on_notification(mcp::notifications::ResourcesUpdated::METHOD, {
+            let server_id = server_id.clone();
+            let this = cx.entity().downgrade();
+            Box::new(move |params, cx: AsyncApp| {
+                let Some(params) =
+                    serde_json::from_value::<mcp::ResourcesUpdatedParams>(params).log_err()
+                else {
+                    return;
+                };
+                let server_id = server_id.clone();
+                let this = this.clone();
+                cx.spawn(async move |cx| {
+                    this.update(cx, |_, cx| {
+                        cx.emit(ContextServerResourceEvent::ResourceUpdated {
+                            server_id,
+                            uri: params.uri,
+                        })
+                    })
+                })
+                .detach();
+            })
+        });
+
+        vec![list_changed, updated]
+    }
+
+    /// Expands a [level 1](https://www.rfc-editor.org/rfc/rfc6570#section-1.2) URI template,
+    /// replacing each `{variable}` with its percent-encoded value.
+    ///
+    /// Templates using the operators or modifiers of higher levels, like `{+path}` or
+    /// `{?query*}`, are rejected.
+    pub fn expand_resource_template(
+        uri_template: &str,
+        arguments: &HashMap<String, String>,
+    ) -> Result<Url> {
+        let mut expanded = String::with_capacity(uri_template.len());
+        let mut rest = uri_template;
+        while let Some(start) = rest.find('{') {
+            expanded.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .with_context(|| format!("Unterminated expression in {uri_template:?}"))?
+                + start;
+            let variable = &rest[start + 1..end];
+            anyhow::ensure!(
+                !variable.is_empty()
+                    && !variable.starts_with('.')
+                    && variable
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '%')),
+                "Unsupported expression {{{variable}}} in {uri_template:?}, \
+                only level 1 URI templates are supported"
+            );
+            let value = arguments
+                .get(variable)
+                .with_context(|| format!("Missing value for {variable:?}"))?;
+            expanded.push_str(&urlencoding::encode(value));
+            rest = &rest[end + 1..];
+        }
+        expanded.push_str(rest);
+        Ok(Url::parse(&expanded)?)
+    }
+
+    /// Returns the variable names used in a level 1 URI template, in order of appearance.
+    pub fn resource_template_variables(uri_template: &str) -> Vec<&str> {
+        uri_template
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(variable, _)| variable))
+            .collect()
+    }
+
     fn available_context_servers_changed(&mut self, cx: &mut Context<Self>) {
         if self.update_servers_task.is_some() {
             self.needs_server_update = true;
@@ -1630,6 +1630,7 @@ impl GitStore {
                     .detach();
                 }
             }
+            BufferStoreEvent::WriteBehindConflict { .. } => {}
         }
     }
 
@@ -56,6 +56,7 @@ use async_trait::async_trait;
 use client::{TypedEnvelope, proto};
 use clock::Global;
 use collections::{BTreeMap, BTreeSet, HashMap, HashSet, btree_map};
+use fs::archive_fs::archive_root_path;
 use futures::{
     AsyncWriteExt, Future, FutureExt, StreamExt,
     future::{Either, Shared, join_all, pending, select},
@@ -303,6 +304,8 @@ pub struct LocalLspStore {
     language_server_dynamic_registrations: HashMap<LanguageServerId, DynamicRegistrations>,
     supplementary_language_servers:
         HashMap<LanguageServerId, (LanguageServerName, Arc<LanguageServer>)>,
+    /// Sources of diagnostics that are not language servers, such as the problem matchers of tasks.
+    non_lsp_diagnostic_sources: HashMap<LanguageServerId, LanguageServerName>,
     prettier_store: Entity<PrettierStore>,
     next_diagnostic_group_id: usize,
     diagnostics: HashMap<
@@ -4183,6 +4186,7 @@ impl LspStore {
                 worktree_store: worktree_store.clone(),
 
                 supplementary_language_servers: Default::default(),
+                non_lsp_diagnostic_sources: Default::default(),
                 languages: languages.clone(),
                 language_server_ids: Default::default(),
                 language_servers: Default::default(),
@@ -4351,7 +4355,8 @@ impl LspStore {
                         this.update_local_worktree_language_servers(&worktree, changes, cx);
                     }
                     worktree::Event::UpdatedGitRepositories(_)
-                    | worktree::Event::DeletedEntry(_) => {}
+                    | worktree::Event::DeletedEntry(_)
+                    | worktree::Event::UnloadedDirectoriesChanged => {}
                 })
                 .detach()
             }
@@ -8215,6 +8220,62 @@ impl LspStore {
         Ok(())
     }
 
+    /// Registers a source of diagnostics that is not a language server, e.g. a task's
+    /// problem matchers, and returns the id to report its diagnostics under.
+    pub fn register_non_lsp_diagnostic_source(
+        &mut self,
+        name: LanguageServerName,
+    ) -> anyhow::Result<LanguageServerId> {
+        let server_id = self.languages.next_language_server_id();
+        let local = self
+            .as_local_mut()
+            .context("diagnostic sources can only be registered in local projects")?;
+        local.non_lsp_diagnostic_sources.insert(server_id, name);
+        Ok(server_id)
+    }
+
+    /// The name of a source registered with [`Self::register_non_lsp_diagnostic_source`].
+    pub fn non_lsp_diagnostic_source_name(
+        &self,
+        server_id: LanguageServerId,
+    ) -> Option<&LanguageServerName> {
+        self.as_local()?.non_lsp_diagnostic_sources.get(&server_id)
+    }
+
+    /// Replaces the diagnostics reported under a source registered with
+    /// [`Self::register_non_lsp_diagnostic_source`] for the given paths.
+    /// Paths outside of the project's worktrees are skipped.
+    pub fn update_non_lsp_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        diagnostics: HashMap<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>,
+        cx: &mut Context<Self>,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.non_lsp_diagnostic_source_name(server_id).is_some(),
+            "no diagnostic source registered with id {server_id}"
+        );
+        let worktree_store = self.worktree_store.read(cx);
+        let updates = diagnostics
+            .into_iter()
+            .filter(|(abs_path, _)| worktree_store.find_worktree(abs_path, cx).is_some())
+            .map(
+                |(document_abs_path, diagnostics)| DocumentDiagnosticsUpdate {
+                    diagnostics: DocumentDiagnostics {
+                        diagnostics,
+                        document_abs_path,
+                        version: None,
+                    },
+                    result_id: None,
+                    registration_id: None,
+                    server_id,
+                    disk_based_sources: Cow::Borrowed(&[]),
+                },
+            )
+            .collect();
+        self.merge_diagnostic_entries(updates, |_, _, _| false, cx)
+    }
+
     pub fn merge_diagnostic_entries<'a>(
         &mut self,
         diagnostic_updates: Vec<DocumentDiagnosticsUpdate<'a, DocumentDiagnostics>>,
@@ -8486,9 +8547,12 @@ impl LspStore {
             let current_scheme = abs_path.scheme().to_owned();
             // Uri is immutable, so we can't modify the scheme
 
-            let abs_path = abs_path
-                .to_file_path_ext(path_style)
-                .map_err(|()| anyhow!("can't convert URI to path"))?;
+            let abs_path = match archive_uri_to_path(&abs_path, path_style) {
+                Some(abs_path) => abs_path,
+                None => abs_path
+                    .to_file_path_ext(path_style)
+                    .map_err(|()| anyhow!("can't convert URI to path"))?,
+            };
             let p = abs_path.clone();
             let yarn_worktree = lsp_store
                 .update(cx, move |lsp_store, cx| match lsp_store.as_local() {
@@ -13282,6 +13346,23 @@ fn workspace_diagnostic_identifier(
     }
 }
 
+/// Converts `jar:` and `zip:` URIs, as used by JVM language servers for library sources, into
+/// paths served by [`fs::archive_fs::ArchiveFs`].
+fn archive_uri_to_path(uri: &lsp::Uri, path_style: PathStyle) -> Option<PathBuf> {
+    let uri = uri.as_str();
+    let archive_uri = uri
+        .strip_prefix("jar:")
+        .or_else(|| uri.strip_prefix("zip:"))?;
+    let (archive_uri, entry_path) = archive_uri.split_once("!/")?;
+    let archive_path = archive_uri
+        .parse::<lsp::Uri>()
+        .ok()?
+        .to_file_path_ext(path_style)
+        .ok()?;
+    let entry_path = urlencoding::decode(entry_path).ok()?;
+    Some(archive_root_path(&archive_path).join(entry_path.as_ref()))
+}
+
 fn resolve_word_completion(snapshot: &BufferSnapshot, completion: &mut Completion) {
     let CompletionSource::BufferWord {
         word_range,
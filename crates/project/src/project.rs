@@ -54,7 +54,7 @@ pub use manifest_tree::ManifestTree;
 pub use project_search::{Search, SearchResults};
 
 use anyhow::{Context as _, Result, anyhow};
-use buffer_store::{BufferStore, BufferStoreEvent};
+use buffer_store::{BufferStore, BufferStoreEvent, write_behind_queue_path};
 use client::{
     Client, Collaborator, PendingEntitySubscription, ProjectId, TypedEnvelope, UserStore, proto,
 };
@@ -154,7 +154,7 @@ pub use task_inventory::{
     TaskSourceKind,
 };
 
-pub use buffer_store::ProjectTransaction;
+pub use buffer_store::{ProjectTransaction, WriteBehindResolution};
 pub use lsp_store::{
     DiagnosticSummary, InvalidationStrategy, LanguageServerLogType, LanguageServerProgress,
     LanguageServerPromptRequest, LanguageServerStatus, LanguageServerToQuery, LspStore,
@@ -371,6 +371,10 @@ pub enum Event {
     DisconnectedFromRemote {
         server_not_running: bool,
     },
+    /// Changes made to a buffer while disconnected from the remote server conflict with the remote file.
+    WriteBehindConflict {
+        buffer: Entity<Buffer>,
+    },
     Closed,
     DeletedEntry(WorktreeId, ProjectEntryId),
     CollaboratorUpdated {
@@ -1319,6 +1323,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1375,7 +1380,7 @@ impl Project {
             if init_worktree_trust {
                 trusted_worktrees::track_worktree_trust(
                     worktree_store.clone(),
-                    Some(RemoteHostLocation::from(connection_options)),
+                    Some(RemoteHostLocation::from(connection_options.clone())),
                     None,
                     Some((remote_proto.clone(), ProjectId(REMOTE_SERVER_PROJECT_ID))),
                     cx,
@@ -1385,12 +1390,18 @@ impl Project {
             let weak_self = cx.weak_entity();
 
             let buffer_store = cx.new(|cx| {
-                BufferStore::remote(
+                let mut buffer_store = BufferStore::remote(
                     worktree_store.clone(),
                     remote.read(cx).proto_client(),
                     REMOTE_SERVER_PROJECT_ID,
                     cx,
-                )
+                );
+                buffer_store.enable_write_behind(
+                    fs.clone(),
+                    write_behind_queue_path(&RemoteHostLocation::from(connection_options)),
+                    cx,
+                );
+                buffer_store
             });
             let image_store = cx.new(|cx| {
                 ImageStore::remote(
@@ -1551,6 +1562,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1829,6 +1841,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -2772,6 +2785,12 @@ impl Project {
         }
     }
 
+    /// Whether this remote project lost its connection and keeps its buffers editable,
+    /// queueing changes until it's reopened.
+    pub fn is_working_offline(&self, cx: &App) -> bool {
+        self.buffer_store.read(cx).is_working_offline()
+    }
+
     #[inline]
     fn remote_client_is_disconnected(&self, cx: &App) -> bool {
         self.remote_client
@@ -3309,6 +3328,11 @@ impl Project {
                         .log_err();
                 }
             }
+            BufferStoreEvent::WriteBehindConflict { buffer } => {
+                cx.emit(Event::WriteBehindConflict {
+                    buffer: buffer.clone(),
+                });
+            }
             _ => {}
         }
     }
@@ -3506,13 +3530,23 @@ impl Project {
                     store.disconnected_from_host(cx);
                 });
                 self.buffer_store.update(cx, |buffer_store, cx| {
-                    buffer_store.disconnected_from_host(cx)
+                    buffer_store.disconnected_from_remote_server(cx)
                 });
                 self.lsp_store.update(cx, |lsp_store, _cx| {
                     lsp_store.disconnected_from_ssh_remote()
                 });
                 cx.emit(Event::DisconnectedFromRemote { server_not_running });
             }
+            remote::RemoteClientEvent::Reconnecting => {
+                self.buffer_store.update(cx, |buffer_store, cx| {
+                    buffer_store.reconnecting_to_remote_server(cx)
+                });
+            }
+            remote::RemoteClientEvent::Reconnected => {
+                self.buffer_store.update(cx, |buffer_store, cx| {
+                    buffer_store.reconnected_to_remote_server(cx)
+                });
+            }
         }
     }
 
@@ -410,7 +410,7 @@ impl Search {
     ) -> impl AsyncFnOnce(&mut AsyncApp) {
         async move |cx| {
             _ = maybe!(async move {
-                let gitignored_tracker = PathInclusionMatcher::new(query.clone());
+                let unloaded_dirs_tracker = PathInclusionMatcher::new(query.clone());
                 let include_ignored = query.include_ignored();
                 for worktree in worktrees {
                     let (mut snapshot, worktree_settings) = worktree
@@ -418,17 +418,24 @@ impl Search {
                             Some((this.snapshot(), this.as_local()?.settings()))
                         })
                         .context("The worktree is not local")?;
-                    if query.include_ignored() {
+                    // The directories that a lazily-scanned worktree didn't scan are walked
+                    // without adding them to the worktree.
+                    let mut unloaded_dirs_to_walk = vec![];
+                    if query.include_ignored() || worktree_settings.lazy_file_scan {
                         // Pre-fetch all of the ignored directories as they're going to be searched.
                         let mut entries_to_refresh = vec![];
 
                         for entry in snapshot.entries(query.include_ignored(), 0) {
-                            if gitignored_tracker.should_scan_gitignored_dir(
+                            if unloaded_dirs_tracker.should_scan_unloaded_dir(
                                 entry,
                                 &snapshot,
                                 &worktree_settings,
                             ) {
-                                entries_to_refresh.push(entry.path.clone());
+                                if entry.is_ignored {
+                                    entries_to_refresh.push(entry.path.clone());
+                                } else {
+                                    unloaded_dirs_to_walk.push(entry.path.clone());
+                                }
                             }
                         }
                         let barrier = worktree.update(cx, |this, _| {
@@ -444,27 +451,51 @@ impl Search {
                         }
                         snapshot = worktree.read_with(cx, |this, _| this.snapshot());
                     }
+                    let mut unloaded_files = (!unloaded_dirs_to_walk.is_empty())
+                        .then(|| {
+                            worktree.update(cx, |this, cx| {
+                                Some(this.as_local()?.walk_unloaded_dirs(
+                                    unloaded_dirs_to_walk,
+                                    include_ignored,
+                                    cx,
+                                ))
+                            })
+                        })
+                        .flatten();
                     let tx = tx.clone();
                     let results = results.clone();
 
                     cx.background_executor()
                         .spawn(async move {
-                            for entry in snapshot.files(include_ignored, 0) {
-                                let (should_scan_tx, should_scan_rx) = oneshot::channel();
-
-                                let Ok(_) = tx
-                                    .send(InputPath {
-                                        entry: entry.clone(),
+                            let send =
+                                async |path: Arc<RelPath>, entry_id: Option<ProjectEntryId>| {
+                                    let (should_scan_tx, should_scan_rx) = oneshot::channel();
+                                    tx.send(InputPath {
+                                        path,
+                                        entry_id,
                                         snapshot: snapshot.clone(),
                                         should_scan_tx,
                                     })
                                     .await
-                                else {
-                                    return;
+                                    .ok()?;
+                                    results.send(should_scan_rx).await.ok()
                                 };
-                                if results.send(should_scan_rx).await.is_err() {
+                            for entry in snapshot.files(include_ignored, 0) {
+                                if entry.is_fifo {
+                                    continue;
+                                }
+                                if send(entry.path.clone(), Some(entry.id)).await.is_none() {
                                     return;
-                                };
+                                }
+                            }
+                            if let Some(unloaded_files) = &mut unloaded_files {
+                                while let Some(paths) = unloaded_files.next().await {
+                                    for path in paths {
+                                        if send(path, None).await.is_none() {
+                                            return;
+                                        }
+                                    }
+                                }
                             }
                         })
                         .await;
@@ -768,35 +799,32 @@ impl RequestHandler<'_> {
     async fn handle_scan_path(&self, req: InputPath) {
         _ = maybe!(async move {
             let InputPath {
-                entry,
+                path,
+                entry_id,
                 snapshot,
                 mut should_scan_tx,
             } = req;
 
-            if entry.is_fifo || !entry.is_file() {
-                return Ok(());
-            }
-
             if self.query.filters_path() {
                 let matched_path = if self.query.match_full_paths() {
                     let mut full_path = snapshot.root_name().to_owned();
-                    full_path.push(&entry.path);
+                    full_path.push(&path);
                     self.query.match_path(&full_path)
                 } else {
-                    self.query.match_path(&entry.path)
+                    self.query.match_path(&path)
                 };
                 if !matched_path {
                     return Ok(());
                 }
             }
 
-            if self.open_entries.contains(&entry.id) {
+            if entry_id.is_some_and(|entry_id| self.open_entries.contains(&entry_id)) {
                 // The buffer is already in memory and that's the version we want to scan;
                 // hence skip the dilly-dally and look for all matches straight away.
                 should_scan_tx
                     .send(ProjectPath {
                         worktree_id: snapshot.id(),
-                        path: entry.path.clone(),
+                        path,
                     })
                     .await?;
             } else {
@@ -806,7 +834,7 @@ impl RequestHandler<'_> {
                         worktree_root: snapshot.abs_path().clone(),
                         path: ProjectPath {
                             worktree_id: snapshot.id(),
-                            path: entry.path.clone(),
+                            path,
                         },
                     })
                     .await?;
@@ -819,7 +847,9 @@ impl RequestHandler<'_> {
 }
 
 struct InputPath {
-    entry: Entry,
+    path: Arc<RelPath>,
+    /// The worktree entry of the file, unless it's in a directory that wasn't scanned.
+    entry_id: Option<ProjectEntryId>,
     snapshot: Snapshot,
     should_scan_tx: oneshot::Sender<ProjectPath>,
 }
@@ -830,8 +860,8 @@ struct MatchingEntry {
     should_scan_tx: oneshot::Sender<ProjectPath>,
 }
 
-/// This struct encapsulates the logic to decide whether a given gitignored directory should be
-/// scanned based on include/exclude patterns of a search query (as include/exclude parameters may match paths inside it).
+/// This struct encapsulates the logic to decide whether a given unloaded directory (a gitignored one, or any directory of
+/// a lazily-scanned worktree) should be scanned based on include/exclude patterns of a search query (as include/exclude parameters may match paths inside it).
 /// It is kind-of doing an inverse of glob. Given a glob pattern like `src/**/` and a parent path like `src`, we need to decide whether the parent
 /// may contain glob hits.
 pub struct PathInclusionMatcher {
@@ -856,16 +886,20 @@ impl PathInclusionMatcher {
         Self { included, query }
     }
 
-    pub fn should_scan_gitignored_dir(
+    pub fn should_scan_unloaded_dir(
         &self,
         entry: &Entry,
         snapshot: &Snapshot,
         worktree_settings: &WorktreeSettings,
     ) -> bool {
-        if !entry.is_ignored || !entry.kind.is_unloaded() {
+        if !entry.kind.is_unloaded() {
             return false;
         }
-        if !self.query.include_ignored() {
+        if entry.is_ignored {
+            if !self.query.include_ignored() {
+                return false;
+            }
+        } else if !worktree_settings.lazy_file_scan {
             return false;
         }
         if worktree_settings.is_path_excluded(&entry.path) {
@@ -1,28 +1,47 @@
-use anyhow::Result;
-use collections::HashMap;
+use anyhow::{Result, anyhow};
+use collections::{HashMap, hash_map};
 use gpui::{App, AppContext as _, Context, Entity, Task, WeakEntity};
 
 use futures::{FutureExt, future::Shared};
 use itertools::Itertools as _;
-use language::LanguageName;
+use language::{
+    Diagnostic, DiagnosticEntry, DiagnosticSeverity, DiagnosticSourceKind, LanguageName,
+    LanguageServerId, LanguageServerName, PointUtf16, Unclipped,
+};
+use lsp::NumberOrString;
 use remote::RemoteClient;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
+    mem,
     path::{Path, PathBuf},
     sync::Arc,
 };
-use task::{Shell, ShellBuilder, ShellKind, SpawnInTerminal};
+use task::{
+    ProblemMatcher, ProblemSeverity, Shell, ShellBuilder, ShellKind, SpawnInTerminal, TaskId,
+};
 use terminal::{
-    TaskState, TaskStatus, Terminal, TerminalBuilder, insert_zed_terminal_env,
+    OutputCursor, TaskState, TaskStatus, Terminal, TerminalBuilder, insert_zed_terminal_env,
     terminal_settings::TerminalSettings,
 };
-use util::{command::new_std_command, get_default_system_shell, maybe, rel_path::RelPath};
+use util::{
+    ResultExt as _, command::new_std_command, get_default_system_shell, maybe, rel_path::RelPath,
+};
 
 use crate::{Project, ProjectPath};
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
+    /// Diagnostics reported by the problem matchers of tasks, by the id of the task.
+    pub(crate) task_diagnostics: HashMap<TaskId, TaskDiagnostics>,
+}
+
+pub(crate) struct TaskDiagnostics {
+    /// The diagnostic source that the task's diagnostics are reported under, to keep them
+    /// apart from language servers' diagnostics and from other tasks' diagnostics.
+    server_id: LanguageServerId,
+    /// The paths that diagnostics were last reported for.
+    paths: Vec<PathBuf>,
 }
 
 impl Project {
@@ -92,6 +111,19 @@ impl Project {
         let (completion_tx, completion_rx) = bounded(1);
 
         let local_path = if is_via_remote { None } else { path.clone() };
+        if is_via_remote && !spawn_task.problem_matchers.is_empty() {
+            return Task::ready(Err(anyhow!(
+                "task `{}` has problem matchers, which are not supported in remote projects",
+                spawn_task.label
+            )));
+        }
+        let problem_matchers = spawn_task.problem_matchers.clone();
+        if !problem_matchers.is_empty() {
+            self.clear_task_diagnostics(&spawn_task.id, cx);
+        }
+        let task_id = spawn_task.id.clone();
+        let task_label = spawn_task.label.clone();
+        let task_cwd = local_path.clone();
         let task_state = Some(TaskState {
             spawned_task: spawn_task.clone(),
             status: TaskStatus::Running,
@@ -280,11 +312,156 @@ impl Project {
                 })
                 .detach();
 
+                if !problem_matchers.is_empty() {
+                    // Other waiters, such as the terminal panel and the debugger, consume the
+                    // task's completion channel, so follow the terminal's events instead.
+                    // The output is collected as it is printed, before it can scroll out of
+                    // the terminal or be cleared by the task.
+                    let mut output_cursor = OutputCursor::default();
+                    let mut output = String::new();
+                    cx.subscribe(&terminal_handle, move |project, terminal, event, cx| {
+                        let task_finished = match event {
+                            terminal::Event::Wakeup => false,
+                            terminal::Event::TaskFinished => true,
+                            _ => return,
+                        };
+                        for line in terminal
+                            .read(cx)
+                            .read_output(&mut output_cursor, task_finished)
+                        {
+                            output.push_str(&line);
+                            output.push('\n');
+                        }
+                        if task_finished {
+                            project.report_task_diagnostics(
+                                task_id.clone(),
+                                &task_label,
+                                &problem_matchers,
+                                &mem::take(&mut output),
+                                task_cwd.as_deref(),
+                                cx,
+                            );
+                        }
+                    })
+                    .detach();
+                }
+
                 terminal_handle
             })
         })
     }
 
+    /// Removes the diagnostics that the problem matchers of a task reported on its previous run.
+    fn clear_task_diagnostics(&mut self, task_id: &TaskId, cx: &mut Context<Self>) {
+        let Some(task_diagnostics) = self.terminals.task_diagnostics.get_mut(task_id) else {
+            return;
+        };
+        let server_id = task_diagnostics.server_id;
+        let cleared = task_diagnostics
+            .paths
+            .drain(..)
+            .map(|path| (path, Vec::new()))
+            .collect();
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_non_lsp_diagnostics(server_id, cleared, cx)
+                .log_err();
+        });
+    }
+
+    /// Finds problems in a finished task's output and reports them as diagnostics.
+    fn report_task_diagnostics(
+        &mut self,
+        task_id: TaskId,
+        task_label: &str,
+        problem_matchers: &[ProblemMatcher],
+        output: &str,
+        cwd: Option<&Path>,
+        cx: &mut Context<Self>,
+    ) {
+        let cwd = cwd
+            .map(Arc::from)
+            .or_else(|| self.active_project_directory(cx));
+        let mut diagnostics = HashMap::<PathBuf, Vec<_>>::default();
+        let problems = problem_matchers
+            .iter()
+            .filter_map(|matcher| matcher.problems(output).log_err())
+            .flatten();
+        for (group_id, problem) in problems.enumerate() {
+            let path = Path::new(&problem.path);
+            let abs_path = match &cwd {
+                Some(cwd) if path.is_relative() => cwd.join(path),
+                _ => path.to_path_buf(),
+            };
+            // Drop `.` components, as in `./src/main.go`.
+            let abs_path = abs_path.components().collect::<PathBuf>();
+            if !abs_path.is_absolute() {
+                continue;
+            }
+
+            let start = PointUtf16::new(
+                problem.line - 1,
+                problem.column.unwrap_or(1).saturating_sub(1),
+            );
+            diagnostics
+                .entry(abs_path)
+                .or_default()
+                .push(DiagnosticEntry {
+                    range: Unclipped(start)..Unclipped(start),
+                    diagnostic: Diagnostic {
+                        source: Some(task_label.to_string()),
+                        code: problem.code.map(NumberOrString::String),
+                        severity: match problem.severity {
+                            ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                            ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                            ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                        },
+                        message: problem.message,
+                        group_id,
+                        is_primary: true,
+                        is_disk_based: true,
+                        source_kind: DiagnosticSourceKind::Other,
+                        ..Diagnostic::default()
+                    },
+                });
+        }
+
+        let task_diagnostics = match self.terminals.task_diagnostics.entry(task_id) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => {
+                let Some(server_id) = self
+                    .lsp_store
+                    .update(cx, |lsp_store, _| {
+                        lsp_store.register_non_lsp_diagnostic_source(LanguageServerName::from(
+                            task_label,
+                        ))
+                    })
+                    .log_err()
+                else {
+                    return;
+                };
+                entry.insert(TaskDiagnostics {
+                    server_id,
+                    paths: Vec::new(),
+                })
+            }
+        };
+        let server_id = task_diagnostics.server_id;
+        // Clear the diagnostics of a run that finished after this one was started.
+        let mut updates = task_diagnostics
+            .paths
+            .drain(..)
+            .map(|path| (path, Vec::new()))
+            .collect::<HashMap<_, _>>();
+        task_diagnostics.paths = diagnostics.keys().cloned().collect();
+        updates.extend(diagnostics);
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_non_lsp_diagnostics(server_id, updates, cx)
+                .log_err();
+        });
+    }
+
     pub fn create_terminal_shell(
         &mut self,
         cwd: Option<PathBuf>,
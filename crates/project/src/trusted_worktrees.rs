@@ -160,6 +160,12 @@ impl From<RemoteConnectionOptions> for RemoteHostLocation {
                 Some(SharedString::new(docker_connection_options.name)),
                 SharedString::new(docker_connection_options.container_id),
             ),
+            RemoteConnectionOptions::Socket(socket) => {
+                (None, SharedString::new(socket.address.to_string()))
+            }
+            RemoteConnectionOptions::Command(command) => {
+                (None, SharedString::new(command.exec.join(" ")))
+            }
             #[cfg(feature = "test-support")]
             RemoteConnectionOptions::Mock(mock) => {
                 (None, SharedString::new(format!("mock-{}", mock.id)))
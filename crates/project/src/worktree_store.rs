@@ -735,6 +735,7 @@ impl WorktreeStore {
                 worktree::Event::DeletedEntry(id) => {
                     cx.emit(WorktreeStoreEvent::WorktreeDeletedEntry(worktree_id, *id))
                 }
+                worktree::Event::UnloadedDirectoriesChanged => {}
             }
         })
         .detach();
@@ -1,6 +1,6 @@
 use anyhow::Result;
-use context_server::test::create_fake_transport;
-use context_server::{ContextServer, ContextServerId};
+use context_server::test::{FakeTransport, create_fake_transport};
+use context_server::{ContextServer, ContextServerClientDelegate, ContextServerId, types as mcp};
 use gpui::{AppContext, AsyncApp, Entity, Subscription, Task, TestAppContext, UpdateGlobal as _};
 use http_client::{FakeHttpClient, Response};
 use project::context_server_store::registry::ContextServerDescriptorRegistry;
@@ -13,8 +13,9 @@ use project::{
 };
 use serde_json::json;
 use settings::{ContextServerCommand, Settings, SettingsStore};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use url::Url;
 use util::path;
 
 #[gpui::test]
@@ -871,6 +872,259 @@ async fn test_context_server_stdio_timeout(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_context_server_resources(cx: &mut TestAppContext) {
+    const SERVER_ID: &str = "mcp-resources";
+
+    let (_fs, project) = setup_context_server_test(cx, json!({"code.rs": ""}), vec![]).await;
+
+    let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+    let store = cx.new(|cx| {
+        ContextServerStore::test(
+            registry.clone(),
+            project.read(cx).worktree_store(),
+            Some(project.downgrade()),
+            cx,
+        )
+    });
+
+    let transport = create_fake_transport(SERVER_ID, cx.executor())
+        .on_request::<mcp::requests::Initialize, _>(|_| async {
+            mcp::InitializeResponse {
+                protocol_version: mcp::ProtocolVersion(mcp::LATEST_PROTOCOL_VERSION.to_string()),
+                server_info: mcp::Implementation {
+                    name: SERVER_ID.to_string(),
+                    version: "1.0.0".to_string(),
+                },
+                capabilities: mcp::ServerCapabilities {
+                    resources: Some(mcp::ResourcesCapabilities {
+                        subscribe: Some(true),
+                        list_changed: Some(true),
+                    }),
+                    ..Default::default()
+                },
+                meta: None,
+            }
+        })
+        .on_request::<mcp::requests::ResourcesList, _>(|params| async move {
+            let (name, next_cursor) = match params.cursor.as_deref() {
+                None => ("notes.md", Some("page-2".to_string())),
+                Some(_) => ("todo.md", None),
+            };
+            mcp::ResourcesListResponse {
+                resources: vec![mcp::Resource {
+                    uri: Url::parse(&format!("file:///{name}")).unwrap(),
+                    name: name.to_string(),
+                    description: None,
+                    mime_type: Some("text/markdown".to_string()),
+                }],
+                next_cursor,
+                meta: None,
+            }
+        })
+        .on_request::<mcp::requests::ListResourceTemplates, _>(|_| async {
+            mcp::ListResourceTemplatesResponse {
+                resource_templates: vec![mcp::ResourceTemplate {
+                    uri_template: "issue://{repo}/{number}".to_string(),
+                    name: "Issue".to_string(),
+                    description: None,
+                    mime_type: None,
+                }],
+                next_cursor: None,
+                meta: None,
+            }
+        })
+        .on_request::<mcp::requests::ResourcesRead, _>(|params| async move {
+            mcp::ResourcesReadResponse {
+                contents: vec![mcp::ResourceContentsType::Text(mcp::TextResourceContents {
+                    uri: params.uri,
+                    mime_type: Some("text/markdown".to_string()),
+                    text: "# Notes".to_string(),
+                })],
+                meta: None,
+            }
+        })
+        .on_request::<mcp::requests::ResourcesSubscribe, _>(|_| async {});
+
+    let server_id = ContextServerId(SERVER_ID.into());
+    let server = Arc::new(ContextServer::new(server_id.clone(), Arc::new(transport)));
+    store.update(cx, |store, cx| store.test_start_server(server, cx));
+    cx.run_until_parked();
+
+    store.read_with(cx, |store, _| {
+        let resources = store
+            .resources()
+            .map(|(id, resource)| (id.clone(), resource.name.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            resources,
+            vec![
+                (server_id.clone(), "notes.md".to_string()),
+                (server_id.clone(), "todo.md".to_string()),
+            ]
+        );
+        let templates = store
+            .resource_templates()
+            .map(|(_, template)| template.uri_template.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(templates, vec!["issue://{repo}/{number}".to_string()]);
+    });
+
+    let uri = Url::parse("file:///notes.md").unwrap();
+    let response = store
+        .read_with(cx, |store, cx| {
+            store.read_resource(&server_id, uri.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let [mcp::ResourceContentsType::Text(contents)] = response.contents.as_slice() else {
+        panic!(
+            "expected a single text resource, got {:?}",
+            response.contents
+        );
+    };
+    assert_eq!(contents.text, "# Notes");
+
+    store
+        .update(cx, |store, cx| {
+            store.subscribe_to_resource(&server_id, uri.clone(), cx)
+        })
+        .await
+        .unwrap();
+    store.read_with(cx, |store, _| {
+        assert!(store.is_subscribed_to_resource(&server_id, &uri));
+    });
+
+    store
+        .update(cx, |store, cx| store.stop_server(&server_id, cx))
+        .unwrap();
+    store.read_with(cx, |store, _| {
+        assert_eq!(store.resources().count(), 0);
+        assert!(!store.is_subscribed_to_resource(&server_id, &uri));
+    });
+}
+
+#[gpui::test]
+async fn test_context_server_client_delegate(cx: &mut TestAppContext) {
+    const SERVER_ID: &str = "mcp-sampling";
+
+    struct FakeClientDelegate;
+
+    impl ContextServerClientDelegate for FakeClientDelegate {
+        fn create_message(
+            &self,
+            _: ContextServerId,
+            _: mcp::CreateMessageRequest,
+            _: AsyncApp,
+        ) -> Task<Result<mcp::CreateMessageResult>> {
+            Task::ready(Err(anyhow::anyhow!("sampling is not supported")))
+        }
+
+        fn elicit(
+            &self,
+            _: ContextServerId,
+            _: mcp::ElicitRequest,
+            _: AsyncApp,
+        ) -> Task<Result<mcp::ElicitResult>> {
+            Task::ready(Err(anyhow::anyhow!("elicitation is not supported")))
+        }
+    }
+
+    let (_fs, project) = setup_context_server_test(cx, json!({"code.rs": ""}), vec![]).await;
+
+    let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+    let store = cx.new(|cx| {
+        ContextServerStore::test(
+            registry.clone(),
+            project.read(cx).worktree_store(),
+            Some(project.downgrade()),
+            cx,
+        )
+    });
+
+    let advertised_sampling = Arc::new(Mutex::new(Vec::new()));
+    let transport = FakeTransport::new(cx.executor()).on_request::<mcp::requests::Initialize, _>({
+        let advertised_sampling = advertised_sampling.clone();
+        move |params| {
+            advertised_sampling
+                .lock()
+                .unwrap()
+                .push(params.capabilities.sampling.is_some());
+            async {
+                mcp::InitializeResponse {
+                    protocol_version: mcp::ProtocolVersion(
+                        mcp::LATEST_PROTOCOL_VERSION.to_string(),
+                    ),
+                    server_info: mcp::Implementation {
+                        name: SERVER_ID.to_string(),
+                        version: "1.0.0".to_string(),
+                    },
+                    capabilities: mcp::ServerCapabilities::default(),
+                    meta: None,
+                }
+            }
+        }
+    });
+
+    let server_id = ContextServerId(SERVER_ID.into());
+    let server = Arc::new(ContextServer::new(server_id.clone(), Arc::new(transport)));
+    store.update(cx, |store, cx| store.test_start_server(server, cx));
+    cx.run_until_parked();
+    assert_eq!(*advertised_sampling.lock().unwrap(), [false]);
+
+    // Servers started before the delegate was set are restarted to advertise sampling.
+    store.update(cx, |store, cx| {
+        store.set_client_delegate(Arc::new(FakeClientDelegate), cx)
+    });
+    cx.run_until_parked();
+    assert_eq!(*advertised_sampling.lock().unwrap(), [false, true]);
+    cx.update(|cx| {
+        assert_eq!(
+            store.read(cx).status_for_server(&server_id),
+            Some(ContextServerStatus::Running)
+        );
+    });
+
+    // Replacing the delegate doesn't restart servers that already advertise sampling.
+    store.update(cx, |store, cx| {
+        store.set_client_delegate(Arc::new(FakeClientDelegate), cx)
+    });
+    cx.run_until_parked();
+    assert_eq!(*advertised_sampling.lock().unwrap(), [false, true]);
+}
+
+#[test]
+fn test_expand_resource_template() {
+    let arguments = [
+        ("repo".to_string(), "zed".to_string()),
+        ("number".to_string(), "1 2".to_string()),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        ContextServerStore::expand_resource_template("issue://{repo}/{number}", &arguments)
+            .unwrap()
+            .as_str(),
+        "issue://zed/1%202"
+    );
+    assert_eq!(
+        ContextServerStore::resource_template_variables("issue://{repo}/{number}"),
+        vec!["repo", "number"]
+    );
+    assert!(
+        ContextServerStore::expand_resource_template("issue://{repo}/{missing}", &arguments)
+            .is_err()
+    );
+    assert!(
+        ContextServerStore::expand_resource_template("issue://{+repo}/{number}", &arguments)
+            .is_err()
+    );
+    assert!(
+        ContextServerStore::expand_resource_template("issue://{repo}{?number}", &arguments)
+            .is_err()
+    );
+}
+
 fn assert_server_events(
     store: &Entity<ContextServerStore>,
     expected_events: Vec<(ContextServerId, ContextServerStatus)>,
@@ -882,7 +1136,7 @@ fn assert_server_events(
         let expected_event_count = expected_events.len();
         let subscription = cx.subscribe(store, {
             let received_event_count = received_event_count.clone();
-            move |_, event, _| {
+            move |_, event: &ServerStatusChangedEvent, _| {
                 let ServerStatusChangedEvent {
                     server_id: actual_server_id,
                     status: actual_status,
@@ -81,11 +81,7 @@ async fn test_path_inclusion_matcher(cx: &mut gpui::TestAppContext) {
     .unwrap();
 
     let path_matcher = PathInclusionMatcher::new(Arc::new(search_query));
-    assert!(path_matcher.should_scan_gitignored_dir(
-        &entry,
-        &worktree_snapshot,
-        &worktree_settings
-    ));
+    assert!(path_matcher.should_scan_unloaded_dir(&entry, &worktree_snapshot, &worktree_settings));
 
     // 2. Test searching for `field`, including ignored files but updating
     // `files_to_include` to only include files under `src/lib`.
@@ -106,9 +102,5 @@ async fn test_path_inclusion_matcher(cx: &mut gpui::TestAppContext) {
     .unwrap();
 
     let path_matcher = PathInclusionMatcher::new(Arc::new(search_query));
-    assert!(!path_matcher.should_scan_gitignored_dir(
-        &entry,
-        &worktree_snapshot,
-        &worktree_settings
-    ));
+    assert!(!path_matcher.should_scan_unloaded_dir(&entry, &worktree_snapshot, &worktree_settings));
 }
@@ -81,7 +81,7 @@ use std::{
     time::Duration,
 };
 use sum_tree::SumTree;
-use task::{ResolvedTask, ShellKind, TaskContext};
+use task::{ProblemMatcher, ResolvedTask, ShellKind, SpawnInTerminal, TaskContext, TaskId};
 use text::{Anchor, PointUtf16, ReplicaId, ToOffset, Unclipped};
 use unindent::Unindent as _;
 use util::{
@@ -2630,6 +2630,69 @@ async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_task_problem_matcher_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let dir = TempTree::new(json!({
+        "src": {
+            "main.c": "int main() {\n    return y;\n}\n",
+        },
+    }));
+    let path = dir.path();
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree_from_real_fs(path, path).await;
+    let project = Project::test(fs, [path], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+
+    let spawn_task = SpawnInTerminal {
+        id: TaskId("make".to_string()),
+        label: "make".to_string(),
+        command: Some("printf".to_string()),
+        args: vec!["src/main.c:2:12: error: 'y' undeclared\\n".to_string()],
+        cwd: Some(path.to_path_buf()),
+        problem_matchers: vec![ProblemMatcher::BuiltIn("$gcc".to_string())],
+        ..SpawnInTerminal::default()
+    };
+    let terminal = project
+        .update(cx, |project, cx| {
+            project.create_terminal_task(spawn_task, cx)
+        })
+        .await
+        .unwrap();
+
+    // Another waiter, like the terminal panel or the debugger, must still be told
+    // that the task has completed.
+    let exit_status = terminal
+        .read_with(cx, |terminal, cx| terminal.wait_for_completed_task(cx))
+        .await;
+    assert!(exit_status.is_some_and(|status| status.success()));
+    cx.executor().run_until_parked();
+
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project
+                .diagnostic_summaries(false, cx)
+                .map(|(path, _, summary)| (path, summary))
+                .collect::<Vec<_>>(),
+            vec![(
+                ProjectPath {
+                    worktree_id,
+                    path: rel_path("src/main.c").into(),
+                },
+                DiagnosticSummary {
+                    error_count: 1,
+                    warning_count: 0,
+                }
+            )]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -7115,6 +7178,78 @@ async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_in_lazily_scanned_worktree(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings.project.worktree.lazy_file_scan = Some(true);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "package.json": r#"{ "main_key": "main value" }"#,
+            "docs": {
+                "guide": {
+                    "intro.md": "no matches here",
+                },
+            },
+            "web": {
+                "components": {
+                    "button.tsx": "const button_key = 'button'",
+                },
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::text(
+                "key",
+                false,
+                false,
+                false,
+                Default::default(),
+                Default::default(),
+                false,
+                None,
+            )
+            .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            (path!("dir/package.json").to_string(), vec![8..11]),
+            (
+                path!("dir/web/components/button.tsx").to_string(),
+                vec![13..16]
+            ),
+        ]),
+    );
+
+    // Directories without matches are walked without being added to the worktree.
+    let worktree = project.read_with(cx, |project, cx| project.worktrees(cx).next().unwrap());
+    worktree.read_with(cx, |worktree, _| {
+        assert!(
+            worktree
+                .entry_for_path(rel_path("docs"))
+                .unwrap()
+                .kind
+                .is_unloaded()
+        );
+        assert!(worktree.entry_for_path(rel_path("docs/guide")).is_none());
+    });
+}
+
 #[gpui::test]
 async fn test_search_with_unicode(cx: &mut gpui::TestAppContext) {
     init_test(cx);
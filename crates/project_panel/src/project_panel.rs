@@ -34,6 +34,7 @@ use notifications::status_toast::{StatusToast, ToastIcon};
 use project::{
     Entry, EntryKind, Fs, GitEntry, GitEntryRef, GitTraversal, Project, ProjectEntryId,
     ProjectPath, Worktree, WorktreeId,
+    archive_fs::{archive_root_path, is_archive_path},
     git_store::{GitStoreEvent, RepositoryEvent, git_traversal::ChildEntriesGitIter},
     project_settings::GoToDiagnosticSeverityFilter,
 };
@@ -393,6 +394,8 @@ actions!(
         SelectPrevDirectory,
         /// Opens a diff view to compare two marked files.
         CompareMarkedFiles,
+        /// Adds the selected archive to the project as a read-only folder.
+        BrowseArchive,
     ]
 );
 
@@ -1171,6 +1174,12 @@ impl ProjectPanel {
                 && (cfg!(target_os = "windows")
                     || (settings.hide_root && visible_worktrees_count == 1));
             let should_show_compare = !is_dir && self.file_abs_paths_to_diff(cx).is_some();
+            let is_archive = !is_dir
+                && project.is_local()
+                && entry
+                    .path
+                    .file_name()
+                    .is_some_and(|name| is_archive_path(Path::new(name)));
 
             let has_git_repo = !is_dir && {
                 let project_path = project::ProjectPath {
@@ -1210,6 +1219,9 @@ impl ProjectPanel {
                             .when(is_local, |menu| {
                                 menu.action("Open in Default App", Box::new(OpenWithSystem))
                             })
+                            .when(is_archive, |menu| {
+                                menu.action("Browse Archive", Box::new(BrowseArchive))
+                            })
                             .action("Open in Terminal", Box::new(OpenInTerminal))
                             .when(is_dir, |menu| {
                                 menu.separator()
@@ -3459,6 +3471,18 @@ impl ProjectPanel {
         }
     }
 
+    fn browse_archive(&mut self, _: &BrowseArchive, _: &mut Window, cx: &mut Context<Self>) {
+        let Some((worktree, entry)) = self.selected_entry(cx) else {
+            return;
+        };
+        let root_path = archive_root_path(&worktree.absolutize(&entry.path));
+        self.project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree(root_path, true, cx)
+            })
+            .detach_and_log_err(cx);
+    }
+
     fn open_system(&mut self, _: &OpenWithSystem, _: &mut Window, cx: &mut Context<Self>) {
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             let abs_path = worktree.absolutize(&entry.path);
@@ -6460,6 +6484,7 @@ impl Render for ProjectPanel {
                 .when(project.is_local(), |el| {
                     el.on_action(cx.listener(Self::reveal_in_finder))
                         .on_action(cx.listener(Self::open_system))
+                        .on_action(cx.listener(Self::browse_archive))
                         .on_action(cx.listener(Self::open_in_terminal))
                 })
                 .when(project.is_via_remote_server(), |el| {
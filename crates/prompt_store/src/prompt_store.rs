@@ -67,12 +67,14 @@ impl PromptMetadata {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter)]
 pub enum BuiltInPrompt {
     CommitMessage,
+    PullRequestDescription,
 }
 
 impl BuiltInPrompt {
     pub fn title(&self) -> &'static str {
         match self {
             Self::CommitMessage => "Commit message",
+            Self::PullRequestDescription => "Pull request description",
         }
     }
 
@@ -80,6 +82,9 @@ impl BuiltInPrompt {
     pub fn default_content(&self) -> &'static str {
         match self {
             Self::CommitMessage => include_str!("../../git_ui/src/commit_message_prompt.txt"),
+            Self::PullRequestDescription => {
+                include_str!("../../git_ui/src/pull_request_description_prompt.txt")
+            }
         }
     }
 }
@@ -88,6 +93,7 @@ impl std::fmt::Display for BuiltInPrompt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::CommitMessage => write!(f, "Commit message"),
+            Self::PullRequestDescription => write!(f, "Pull request description"),
         }
     }
 }
@@ -126,7 +132,7 @@ impl PromptId {
         match self {
             Self::User { .. } => true,
             Self::BuiltIn(builtin) => match builtin {
-                BuiltInPrompt::CommitMessage => true,
+                BuiltInPrompt::CommitMessage | BuiltInPrompt::PullRequestDescription => true,
             },
         }
     }
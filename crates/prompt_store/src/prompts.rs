@@ -15,7 +15,11 @@ use std::{
 };
 use text::LineEnding;
 use util::{
-    ResultExt, get_default_system_shell_preferring_bash, rel_path::RelPath, shell::ShellKind,
+    ResultExt, get_default_system_shell_preferring_bash,
+    markdown::split_frontmatter,
+    paths::{PathMatcher, PathStyle},
+    rel_path::RelPath,
+    shell::ShellKind,
 };
 
 use crate::UserPromptId;
@@ -37,6 +41,8 @@ pub struct ProjectContext {
     pub worktrees: Vec<WorktreeContext>,
     /// Whether any worktree has a rules_file. Provided as a field because handlebars can't do this.
     pub has_rules: bool,
+    /// Whether any worktree has scoped_rules_files. Provided as a field because handlebars can't do this.
+    pub has_scoped_rules: bool,
     pub user_rules: Vec<UserRulesContext>,
     /// `!user_rules.is_empty()` - provided as a field because handlebars can't do this.
     pub has_user_rules: bool,
@@ -50,9 +56,13 @@ impl ProjectContext {
         let has_rules = worktrees
             .iter()
             .any(|worktree| worktree.rules_file.is_some());
+        let has_scoped_rules = worktrees
+            .iter()
+            .any(|worktree| !worktree.scoped_rules_files.is_empty());
         Self {
             worktrees,
             has_rules,
+            has_scoped_rules,
             has_user_rules: !default_user_rules.is_empty(),
             user_rules: default_user_rules,
             os: std::env::consts::OS.to_string(),
@@ -75,6 +85,9 @@ pub struct WorktreeContext {
     pub root_name: String,
     pub abs_path: Arc<Path>,
     pub rules_file: Option<RulesFileContext>,
+    /// Rules files that only apply to some of the worktree's files, such as
+    /// rules files in subdirectories.
+    pub scoped_rules_files: Vec<ScopedRulesFileContext>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize)]
@@ -87,6 +100,105 @@ pub struct RulesFileContext {
     pub project_entry_id: usize,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ScopedRulesFileContext {
+    pub path_in_worktree: Arc<RelPath>,
+    /// The globs from the file's `applies_to` frontmatter, if any.
+    pub applies_to: Vec<String>,
+    pub text: String,
+    #[serde(skip)]
+    pub project_entry_id: usize,
+    #[serde(skip)]
+    pub matcher: PathMatcher,
+}
+
+impl ScopedRulesFileContext {
+    pub fn new(
+        path_in_worktree: Arc<RelPath>,
+        applies_to: Vec<String>,
+        text: String,
+        project_entry_id: usize,
+    ) -> Result<Self> {
+        let matcher = PathMatcher::new(&applies_to, PathStyle::Posix)?;
+        Ok(Self {
+            path_in_worktree,
+            applies_to,
+            text,
+            project_entry_id,
+            matcher,
+        })
+    }
+
+    /// The directory whose files these rules apply to.
+    pub fn directory(&self) -> &RelPath {
+        let directory = self.path_in_worktree.parent().unwrap_or(RelPath::empty());
+        // `.github/copilot-instructions.md` applies to the directory containing `.github`.
+        if directory.file_name() == Some(".github") {
+            directory.parent().unwrap_or(RelPath::empty())
+        } else {
+            directory
+        }
+    }
+
+    /// Whether these rules apply to the file at the given path in the worktree.
+    ///
+    /// Rules apply to the files below the directory containing the rules file,
+    /// and `applies_to` globs are matched against paths relative to that directory.
+    pub fn applies_to_path(&self, path: &RelPath) -> bool {
+        let Ok(relative_path) = path.strip_prefix(self.directory()) else {
+            return false;
+        };
+        self.applies_to.is_empty() || self.matcher.is_match(relative_path)
+    }
+}
+
+/// Splits a rules file into the globs of its `applies_to` frontmatter and its
+/// remaining text.
+///
+/// The frontmatter is a block delimited by `---` lines at the start of the
+/// file. Its `applies_to` (or `globs`) key may hold a single glob, a
+/// comma-separated or bracketed list of globs, or a list of `- glob` lines.
+pub fn parse_rules_file(text: &str) -> (Vec<String>, &str) {
+    let text = text.trim_start_matches('\u{feff}');
+    let Some((frontmatter, body)) = split_frontmatter(text) else {
+        return (Vec::new(), text);
+    };
+
+    let unquote = |glob: &str| {
+        glob.trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .trim()
+            .to_string()
+    };
+    let mut globs = Vec::new();
+    let mut in_globs_list = false;
+    for line in frontmatter.lines() {
+        if in_globs_list && let Some(item) = line.trim_start().strip_prefix("- ") {
+            globs.push(unquote(item));
+            continue;
+        }
+        in_globs_list = false;
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        if !matches!(key.trim(), "applies_to" | "globs") {
+            continue;
+        }
+        let value = value.trim();
+        if value.is_empty() {
+            in_globs_list = true;
+        } else {
+            let value = value
+                .strip_prefix('[')
+                .and_then(|value| value.strip_suffix(']'))
+                .unwrap_or(value);
+            globs.extend(value.split(',').map(unquote));
+        }
+    }
+    globs.retain(|glob| !glob.is_empty());
+    (globs, body)
+}
+
 #[derive(Serialize)]
 pub struct ContentPromptDiagnosticContext {
     pub line_number: usize,
@@ -476,3 +588,73 @@ impl PromptBuilder {
             .render("terminal_assistant_prompt", &context)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::rel_path::rel_path;
+
+    #[test]
+    fn test_parse_rules_file() {
+        assert_eq!(parse_rules_file("Use tabs.\n"), (Vec::new(), "Use tabs.\n"));
+        assert_eq!(
+            parse_rules_file("---\napplies_to: \"**/*.sql\"\n---\nUse snake_case.\n"),
+            (vec!["**/*.sql".to_string()], "Use snake_case.\n")
+        );
+        assert_eq!(
+            parse_rules_file("---\nglobs: [\"*.ts\", '*.tsx']\ndescription: Frontend\n---\nBody"),
+            (vec!["*.ts".to_string(), "*.tsx".to_string()], "Body")
+        );
+        assert_eq!(
+            parse_rules_file("---\napplies_to:\n  - src/**/*.rs\n  - build.rs\n---\nBody"),
+            (
+                vec!["src/**/*.rs".to_string(), "build.rs".to_string()],
+                "Body"
+            )
+        );
+        assert_eq!(
+            parse_rules_file("---\r\napplies_to: \"*.py\"\r\n---\r\nBody\r\n"),
+            (vec!["*.py".to_string()], "Body\r\n")
+        );
+        // An unterminated frontmatter block is part of the text.
+        assert_eq!(
+            parse_rules_file("---\napplies_to: *.rs\nBody"),
+            (Vec::new(), "---\napplies_to: *.rs\nBody")
+        );
+    }
+
+    #[test]
+    fn test_scoped_rules_applies_to_path() {
+        let rules = ScopedRulesFileContext::new(
+            rel_path("backend/AGENTS.md").into(),
+            Vec::new(),
+            String::new(),
+            0,
+        )
+        .unwrap();
+        assert!(rules.applies_to_path(rel_path("backend/src/main.rs")));
+        assert!(!rules.applies_to_path(rel_path("frontend/src/main.ts")));
+
+        let rules = ScopedRulesFileContext::new(
+            rel_path("backend/.rules").into(),
+            vec!["**/*.sql".to_string()],
+            String::new(),
+            0,
+        )
+        .unwrap();
+        assert!(rules.applies_to_path(rel_path("backend/migrations/001.sql")));
+        assert!(!rules.applies_to_path(rel_path("backend/src/main.rs")));
+        assert!(!rules.applies_to_path(rel_path("schema.sql")));
+
+        let rules = ScopedRulesFileContext::new(
+            rel_path("web/.github/copilot-instructions.md").into(),
+            vec!["*.ts".to_string()],
+            String::new(),
+            0,
+        )
+        .unwrap();
+        assert!(rules.applies_to_path(rel_path("web/index.ts")));
+        assert!(!rules.applies_to_path(rel_path("web/README.md")));
+        assert!(!rules.applies_to_path(rel_path("index.ts")));
+    }
+}
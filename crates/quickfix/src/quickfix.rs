@@ -0,0 +1,457 @@
+mod quickfix_panel;
+mod quickfix_panel_settings;
+
+use anyhow::{Context as _, Result, anyhow};
+use editor::{Editor, SelectionEffects, scroll::Autoscroll};
+use gpui::{App, Context, Entity, SharedString, Task, Window, actions};
+use language::{BufferSnapshot, DiagnosticSeverity, Point, ToPoint as _};
+use project::{Project, ProjectPath};
+use search::ProjectSearchView;
+use task::{ProblemMatcher, ProblemSeverity};
+use workspace::{
+    Toast, Workspace,
+    notifications::NotificationId,
+    quickfix::{QuickfixEntry, QuickfixList, QuickfixLists},
+};
+
+pub use quickfix_panel::QuickfixPanel;
+
+actions!(
+    quickfix,
+    [
+        /// Toggles focus on the quickfix panel.
+        ToggleFocus,
+        /// Opens and focuses the quickfix panel.
+        Open,
+        /// Closes the quickfix panel.
+        Close,
+        /// Goes to the next entry in the quickfix list.
+        Next,
+        /// Goes to the previous entry in the quickfix list.
+        Previous,
+        /// Goes to the first entry in the quickfix list.
+        First,
+        /// Goes to the last entry in the quickfix list.
+        Last,
+        /// Switches to the previous quickfix list in the history.
+        Older,
+        /// Switches to the next quickfix list in the history.
+        Newer,
+        /// Creates a quickfix list from the results of the open project search.
+        FromProjectSearch,
+        /// Creates a quickfix list from the errors and warnings in the project.
+        FromDiagnostics,
+        /// Creates a quickfix list from the references to the symbol under the cursor.
+        FromReferences,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, window, cx| {
+                workspace.toggle_panel_focus::<QuickfixPanel>(window, cx);
+            })
+            .register_action(|workspace, _: &Open, window, cx| {
+                workspace.focus_panel::<QuickfixPanel>(window, cx);
+            })
+            .register_action(|workspace, _: &Close, window, cx| {
+                workspace.close_panel::<QuickfixPanel>(window, cx);
+            })
+            .register_action(|workspace, _: &Next, window, cx| {
+                go_to_relative(workspace, 1, window, cx);
+            })
+            .register_action(|workspace, _: &Previous, window, cx| {
+                go_to_relative(workspace, -1, window, cx);
+            })
+            .register_action(|workspace, _: &First, window, cx| {
+                go_to_entry(workspace, |lists, cx| lists.select(0, cx), window, cx);
+            })
+            .register_action(|workspace, _: &Last, window, cx| {
+                go_to_entry(
+                    workspace,
+                    |lists, cx| {
+                        let len = lists.current().map_or(0, |list| list.entries.len());
+                        lists.select(len.saturating_sub(1), cx)
+                    },
+                    window,
+                    cx,
+                );
+            })
+            .register_action(|workspace, _: &Older, _, cx| {
+                let result = workspace
+                    .quickfix_lists()
+                    .update(cx, |lists, cx| lists.older(1, cx));
+                if let Err(error) = result {
+                    show_error(workspace, error, cx);
+                }
+            })
+            .register_action(|workspace, _: &Newer, _, cx| {
+                let result = workspace
+                    .quickfix_lists()
+                    .update(cx, |lists, cx| lists.newer(1, cx));
+                if let Err(error) = result {
+                    show_error(workspace, error, cx);
+                }
+            })
+            .register_action(|workspace, _: &FromProjectSearch, window, cx| {
+                let search = workspace.items_of_type::<ProjectSearchView>(cx).next();
+                match search {
+                    Some(search) => {
+                        let list = search_list(&search, cx);
+                        push_list(workspace, list, window, cx);
+                    }
+                    None => show_error(workspace, anyhow!("No project search is open"), cx),
+                }
+            })
+            .register_action(|workspace, _: &FromDiagnostics, window, cx| {
+                let list = diagnostics_list(workspace.project().clone(), cx);
+                cx.spawn_in(window, async move |workspace, cx| {
+                    let list = list.await?;
+                    workspace.update_in(cx, |workspace, window, cx| {
+                        push_list(workspace, list, window, cx)
+                    })
+                })
+                .detach_and_log_err(cx);
+            })
+            .register_action(|workspace, _: &FromReferences, window, cx| {
+                let list = references_list(workspace, cx);
+                cx.spawn_in(window, async move |workspace, cx| {
+                    let list = list.await;
+                    workspace.update_in(cx, |workspace, window, cx| match list {
+                        Ok(list) => push_list(workspace, list, window, cx),
+                        Err(error) => show_error(workspace, error, cx),
+                    })
+                })
+                .detach_and_log_err(cx);
+            });
+    })
+    .detach();
+}
+
+struct QuickfixToast;
+
+fn show_error(workspace: &mut Workspace, error: anyhow::Error, cx: &mut Context<Workspace>) {
+    workspace.show_toast(
+        Toast::new(NotificationId::unique::<QuickfixToast>(), error.to_string()).autohide(),
+        cx,
+    );
+}
+
+/// Makes `list` the current quickfix list and reveals it in the quickfix panel.
+pub fn push_list(
+    workspace: &mut Workspace,
+    list: QuickfixList,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let is_empty = list.entries.is_empty();
+    workspace
+        .quickfix_lists()
+        .update(cx, |lists, cx| lists.push(list, cx));
+    if is_empty {
+        show_error(workspace, anyhow!("No errors"), cx);
+    } else {
+        workspace.open_panel::<QuickfixPanel>(window, cx);
+    }
+}
+
+/// Selects an entry of the current quickfix list with `select` and opens it.
+pub fn go_to_entry(
+    workspace: &mut Workspace,
+    select: impl FnOnce(&mut QuickfixLists, &mut Context<QuickfixLists>) -> Result<QuickfixEntry>,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    match workspace.quickfix_lists().clone().update(cx, select) {
+        Ok(entry) => open_entry(workspace, entry, window, cx).detach_and_log_err(cx),
+        Err(error) => show_error(workspace, error, cx),
+    }
+}
+
+/// Moves `delta` entries through the current quickfix list. When there is no
+/// list yet, one is created from the open project search or the diagnostics.
+fn go_to_relative(
+    workspace: &mut Workspace,
+    delta: isize,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if workspace.quickfix_lists().read(cx).current().is_some() {
+        go_to_entry(
+            workspace,
+            |lists, cx| lists.select_relative(delta, cx),
+            window,
+            cx,
+        );
+        return;
+    }
+
+    let list = default_list(workspace, cx);
+    cx.spawn_in(window, async move |workspace, cx| {
+        let list = list.await?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace
+                .quickfix_lists()
+                .update(cx, |lists, cx| lists.push(list, cx));
+            go_to_entry(workspace, |lists, cx| lists.select(0, cx), window, cx);
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Opens the file of `entry` and moves the cursor to its position.
+pub fn open_entry(
+    workspace: &mut Workspace,
+    entry: QuickfixEntry,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let open = workspace.open_path(entry.path, None, true, window, cx);
+    cx.spawn_in(window, async move |_, cx| {
+        let item = open.await?;
+        let Some(editor) = cx.update(|_, cx| item.act_as::<Editor>(cx))? else {
+            return Ok(());
+        };
+        editor.update_in(cx, |editor, window, cx| {
+            let position = entry.position;
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::center()),
+                window,
+                cx,
+                |selections| selections.select_ranges([position..position]),
+            );
+        })
+    })
+}
+
+/// The list `:cnext` and friends use when no list was created yet: the
+/// results of the open project search, or the project's diagnostics.
+pub fn default_list(workspace: &Workspace, cx: &mut App) -> Task<Result<QuickfixList>> {
+    let search = workspace.items_of_type::<ProjectSearchView>(cx).next();
+    match search {
+        Some(search) => Task::ready(Ok(search_list(&search, cx))),
+        None => diagnostics_list(workspace.project().clone(), cx),
+    }
+}
+
+pub fn search_list(search: &Entity<ProjectSearchView>, cx: &App) -> QuickfixList {
+    let search = search.read(cx);
+    let multibuffer = search.results_editor().read(cx).buffer().read(cx);
+    let entries = search
+        .get_matches(cx)
+        .into_iter()
+        .filter_map(|range| {
+            let buffer = multibuffer.buffer_for_anchor(range.start, cx)?;
+            let buffer = buffer.read(cx);
+            let snapshot = buffer.snapshot();
+            let position = range.start.text_anchor.to_point(&snapshot);
+            Some(QuickfixEntry {
+                path: ProjectPath::from_file(buffer.file()?.as_ref(), cx),
+                position,
+                text: line_text(&snapshot, position.row),
+                severity: None,
+            })
+        })
+        .collect();
+    QuickfixList::new(format!("Search: {}", search.search_query_text(cx)), entries)
+}
+
+pub fn diagnostics_list(project: Entity<Project>, cx: &mut App) -> Task<Result<QuickfixList>> {
+    let mut paths = project
+        .read(cx)
+        .diagnostic_summaries(false, cx)
+        .filter(|(_, _, summary)| summary.error_count + summary.warning_count > 0)
+        .map(|(path, _, _)| path)
+        .collect::<Vec<_>>();
+    paths.sort_by(|a, b| a.path.cmp(&b.path));
+    paths.dedup();
+    cx.spawn(async move |cx| {
+        let mut entries = Vec::new();
+        for path in paths {
+            let buffer = project
+                .update(cx, |project, cx| project.open_buffer(path.clone(), cx))
+                .await?;
+            let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+            for entry in snapshot.diagnostics_in_range::<_, Point>(0..snapshot.len(), false) {
+                let diagnostic = entry.diagnostic;
+                if diagnostic.is_primary && diagnostic.severity <= DiagnosticSeverity::WARNING {
+                    entries.push(QuickfixEntry {
+                        path: path.clone(),
+                        position: entry.range.start,
+                        text: diagnostic.message.lines().next().unwrap_or_default().into(),
+                        severity: Some(diagnostic.severity),
+                    });
+                }
+            }
+        }
+        Ok(QuickfixList::new("Diagnostics", entries))
+    })
+}
+
+pub fn references_list(workspace: &Workspace, cx: &mut App) -> Task<Result<QuickfixList>> {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return Task::ready(Err(anyhow!("No active editor")));
+    };
+    let editor = editor.read(cx);
+    let head = editor.selections.newest_anchor().head();
+    let Some((buffer, position)) = editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    else {
+        return Task::ready(Err(anyhow!("No buffer under the cursor")));
+    };
+    let references = workspace
+        .project()
+        .update(cx, |project, cx| project.references(&buffer, position, cx));
+    cx.spawn(async move |cx| {
+        let locations = references
+            .await?
+            .context("Language server does not support references")?;
+        let entries = cx.update(|cx| {
+            locations
+                .into_iter()
+                .filter_map(|location| {
+                    let buffer = location.buffer.read(cx);
+                    let snapshot = buffer.snapshot();
+                    let position = location.range.start.to_point(&snapshot);
+                    Some(QuickfixEntry {
+                        path: ProjectPath::from_file(buffer.file()?.as_ref(), cx),
+                        position,
+                        text: line_text(&snapshot, position.row),
+                        severity: None,
+                    })
+                })
+                .collect()
+        });
+        Ok(QuickfixList::new("References", entries))
+    })
+}
+
+/// Creates a quickfix list from the output of a command such as `make` or
+/// `grep -n`, finding its locations with the given problem matchers. Locations
+/// that aren't in the project are skipped.
+pub fn output_list(
+    title: impl Into<SharedString>,
+    output: &str,
+    problem_matchers: &[ProblemMatcher],
+    project: &Entity<Project>,
+    cx: &App,
+) -> QuickfixList {
+    let project = project.read(cx);
+    let entries = parse_output(output, problem_matchers)
+        .into_iter()
+        .filter_map(|location| {
+            Some(QuickfixEntry {
+                path: project.find_project_path(&location.path, cx)?,
+                position: location.position,
+                text: location.message.into(),
+                severity: location.severity,
+            })
+        })
+        .collect();
+    QuickfixList::new(title, entries)
+}
+
+#[derive(Debug, PartialEq)]
+struct OutputLocation {
+    path: String,
+    position: Point,
+    message: String,
+    severity: Option<DiagnosticSeverity>,
+}
+
+/// Finds the locations in a command's output. The lines matched by `$grep` are
+/// search results rather than problems, so they have no severity.
+fn parse_output(output: &str, problem_matchers: &[ProblemMatcher]) -> Vec<OutputLocation> {
+    let mut locations = Vec::new();
+    for matcher in problem_matchers {
+        let is_search = matches!(matcher, ProblemMatcher::BuiltIn(name) if name == "$grep");
+        for problem in matcher.problems(output).into_iter().flatten() {
+            locations.push(OutputLocation {
+                position: Point::new(
+                    problem.line - 1,
+                    problem.column.unwrap_or(1).saturating_sub(1),
+                ),
+                severity: (!is_search).then(|| match problem.severity {
+                    ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                    ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                    ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                }),
+                path: problem.path,
+                message: problem.message,
+            });
+        }
+    }
+    locations
+}
+
+fn line_text(snapshot: &BufferSnapshot, row: u32) -> SharedString {
+    let range = Point::new(row, 0)..Point::new(row, snapshot.line_len(row));
+    snapshot
+        .text_for_range(range)
+        .collect::<String>()
+        .trim()
+        .to_string()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let make_output = "\
+src/main.c:3:5: error: use of undeclared identifier 'x'
+src/lib.ts:10:2 - error TS2304: Cannot find name 'x'.
+error[E0425]: cannot find value `y` in this scope
+ --> src/main.rs:2:13
+  |
+warning: unused variable: `z`
+  --> src/lib.rs:7:9
+make: *** [Makefile:4: all] Error 1
+";
+        let matchers = ["$rustc", "$gcc", "$tsc"].map(|name| ProblemMatcher::BuiltIn(name.into()));
+        assert_eq!(
+            parse_output(make_output, &matchers),
+            vec![
+                OutputLocation {
+                    path: "src/main.rs".into(),
+                    position: Point::new(1, 12),
+                    message: "cannot find value `y` in this scope".into(),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                },
+                OutputLocation {
+                    path: "src/lib.rs".into(),
+                    position: Point::new(6, 8),
+                    message: "unused variable: `z`".into(),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                },
+                OutputLocation {
+                    path: "src/main.c".into(),
+                    position: Point::new(2, 4),
+                    message: "use of undeclared identifier 'x'".into(),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                },
+                OutputLocation {
+                    path: "src/lib.ts".into(),
+                    position: Point::new(9, 1),
+                    message: "Cannot find name 'x'.".into(),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                },
+            ]
+        );
+
+        assert_eq!(
+            parse_output(
+                "README.md:12:some matching line\n",
+                &[ProblemMatcher::BuiltIn("$grep".into())]
+            ),
+            vec![OutputLocation {
+                path: "README.md".into(),
+                position: Point::new(11, 0),
+                message: "some matching line".into(),
+                severity: None,
+            }]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Result;
+use gpui::{
+    Action, App, AsyncWindowContext, Context, Entity, EventEmitter, FocusHandle, Focusable, Pixels,
+    ScrollStrategy, Subscription, Task, UniformListScrollHandle, WeakEntity, Window, uniform_list,
+};
+use language::DiagnosticSeverity;
+use project::{Fs, Project};
+use settings::Settings;
+use ui::{ListItem, ListItemSpacing, prelude::*};
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+    quickfix::{QuickfixEntry, QuickfixEvent, QuickfixLists},
+};
+
+use crate::{ToggleFocus, go_to_entry, quickfix_panel_settings::QuickfixPanelSettings};
+
+const QUICKFIX_PANEL_KEY: &str = "QuickfixPanel";
+
+/// Lists the entries of the workspace's current quickfix list.
+pub struct QuickfixPanel {
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    lists: Entity<QuickfixLists>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+    _subscription: Subscription,
+}
+
+impl QuickfixPanel {
+    pub fn load(
+        workspace: WeakEntity<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<Entity<Self>>> {
+        cx.spawn(async move |cx| {
+            workspace.update_in(cx, |workspace, window, cx| Self::new(workspace, window, cx))
+        })
+    }
+
+    fn new(workspace: &mut Workspace, _: &mut Window, cx: &mut Context<Workspace>) -> Entity<Self> {
+        let lists = workspace.quickfix_lists().clone();
+        cx.new(|cx| {
+            let subscription =
+                cx.subscribe(&lists, |this: &mut Self, lists, _: &QuickfixEvent, cx| {
+                    if let Some(list) = lists.read(cx).current() {
+                        this.scroll_handle
+                            .scroll_to_item(list.selected_index(), ScrollStrategy::Center);
+                    }
+                    cx.notify();
+                });
+            Self {
+                workspace: workspace.weak_handle(),
+                project: workspace.project().clone(),
+                lists,
+                fs: workspace.app_state().fs.clone(),
+                focus_handle: cx.focus_handle(),
+                scroll_handle: UniformListScrollHandle::new(),
+                width: None,
+                height: None,
+                _subscription: subscription,
+            }
+        })
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        self.lists
+            .update(cx, |lists, cx| lists.select_relative(1, cx))
+            .ok();
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.lists
+            .update(cx, |lists, cx| lists.select_relative(-1, cx))
+            .ok();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self
+            .lists
+            .read(cx)
+            .current()
+            .map(|list| list.selected_index())
+        {
+            self.open_entry(ix, window, cx);
+        }
+    }
+
+    fn open_entry(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                go_to_entry(workspace, |lists, cx| lists.select(ix, cx), window, cx);
+            })
+            .ok();
+    }
+
+    fn render_entry(
+        &self,
+        ix: usize,
+        entry: &QuickfixEntry,
+        selected: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let path_style = self.project.read(cx).path_style(cx);
+        let location = format!(
+            "{}:{}:{}",
+            entry.path.path.display(path_style),
+            entry.position.row + 1,
+            entry.position.column + 1
+        );
+        let icon = entry.severity.map(|severity| match severity {
+            DiagnosticSeverity::ERROR => Icon::new(IconName::XCircle).color(Color::Error),
+            DiagnosticSeverity::WARNING => Icon::new(IconName::Warning).color(Color::Warning),
+            _ => Icon::new(IconName::Info).color(Color::Muted),
+        });
+
+        ListItem::new(ix)
+            .spacing(ListItemSpacing::Dense)
+            .toggle_state(selected)
+            .start_slot::<Icon>(icon)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(location).color(Color::Muted))
+                    .child(Label::new(entry.text.clone()).truncate()),
+            )
+            .on_click(cx.listener(move |this, _, window, cx| this.open_entry(ix, window, cx)))
+    }
+}
+
+impl Focusable for QuickfixPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for QuickfixPanel {}
+
+impl Render for QuickfixPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let lists = self.lists.read(cx);
+        let history = format!("{} of {}", lists.current_index() + 1, lists.lists().len());
+        let current = lists.current().map(|list| {
+            (
+                list.title.clone(),
+                list.entries.len(),
+                list.selected_index(),
+            )
+        });
+
+        v_flex()
+            .key_context("QuickfixPanel")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .map(|this| match current {
+                Some((title, entry_count, selected_index)) => this
+                    .child(
+                        h_flex()
+                            .px_2()
+                            .py_1()
+                            .gap_2()
+                            .border_b_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(Label::new(title))
+                            .child(Label::new(history).color(Color::Muted)),
+                    )
+                    .child(
+                        uniform_list(
+                            "quickfix-entries",
+                            entry_count,
+                            cx.processor(move |this, range: Range<usize>, _, cx| {
+                                let lists = this.lists.read(cx);
+                                let Some(list) = lists.current() else {
+                                    return Vec::new();
+                                };
+                                let entries = list.entries[range.clone()].to_vec();
+                                range
+                                    .zip(entries)
+                                    .map(|(ix, entry)| {
+                                        this.render_entry(ix, &entry, ix == selected_index, cx)
+                                            .into_any_element()
+                                    })
+                                    .collect()
+                            }),
+                        )
+                        .track_scroll(&self.scroll_handle)
+                        .flex_1(),
+                    ),
+                None => this
+                    .justify_center()
+                    .items_center()
+                    .child(Label::new("No quickfix list").color(Color::Muted)),
+            })
+    }
+}
+
+impl Panel for QuickfixPanel {
+    fn persistent_name() -> &'static str {
+        "QuickfixPanel"
+    }
+
+    fn panel_key() -> &'static str {
+        QUICKFIX_PANEL_KEY
+    }
+
+    fn position(&self, _: &Window, cx: &App) -> DockPosition {
+        QuickfixPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        settings::update_settings_file(self.fs.clone(), cx, move |settings, _| {
+            settings.quickfix_panel.get_or_insert_default().dock = Some(position.into())
+        });
+    }
+
+    fn size(&self, window: &Window, cx: &App) -> Pixels {
+        let settings = QuickfixPanelSettings::get_global(cx);
+        match self.position(window, cx) {
+            DockPosition::Left | DockPosition::Right => {
+                self.width.unwrap_or(settings.default_width)
+            }
+            DockPosition::Bottom => self.height.unwrap_or(settings.default_height),
+        }
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        match self.position(window, cx) {
+            DockPosition::Left | DockPosition::Right => self.width = size,
+            DockPosition::Bottom => self.height = size,
+        }
+        cx.notify();
+    }
+
+    fn icon(&self, _: &Window, cx: &App) -> Option<IconName> {
+        QuickfixPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::ListTodo)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Quickfix Panel")
+    }
+
+    fn icon_label(&self, _: &Window, cx: &App) -> Option<String> {
+        let count = self.lists.read(cx).current()?.entries.len();
+        (count > 0).then(|| count.to_string())
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        10
+    }
+}
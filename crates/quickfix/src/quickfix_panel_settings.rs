@@ -0,0 +1,24 @@
+use gpui::Pixels;
+use settings::{RegisterSetting, Settings};
+use ui::px;
+use workspace::dock::DockPosition;
+
+#[derive(Debug, RegisterSetting)]
+pub struct QuickfixPanelSettings {
+    pub button: bool,
+    pub dock: DockPosition,
+    pub default_width: Pixels,
+    pub default_height: Pixels,
+}
+
+impl Settings for QuickfixPanelSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let panel = content.quickfix_panel.as_ref().unwrap();
+        Self {
+            button: panel.button.unwrap(),
+            dock: panel.dock.unwrap().into(),
+            default_width: panel.default_width.map(px).unwrap(),
+            default_height: panel.default_height.map(px).unwrap(),
+        }
+    }
+}
@@ -23,6 +23,7 @@ pub struct DisconnectedOverlay {
     host: Host,
     focus_handle: FocusHandle,
     finished: bool,
+    can_work_offline: bool,
 }
 
 impl EventEmitter<DismissEvent> for DisconnectedOverlay {}
@@ -81,8 +82,10 @@ impl DisconnectedOverlay {
                     Host::CollabGuestProject
                 };
 
+                let can_work_offline = project.read(cx).is_working_offline(cx);
                 workspace.toggle_modal(window, cx, |_, cx| DisconnectedOverlay {
                     finished: false,
+                    can_work_offline,
                     workspace: handle,
                     host,
                     focus_handle: cx.focus_handle(),
@@ -140,6 +143,11 @@ impl DisconnectedOverlay {
         .detach_and_prompt_err("Failed to reconnect", window, cx, |_, _, _| None);
     }
 
+    fn handle_work_offline(&mut self, _: &ClickEvent, _: &mut Window, cx: &mut Context<Self>) {
+        self.finished = true;
+        cx.emit(DismissEvent);
+    }
+
     fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
         self.finished = true;
         cx.emit(DismissEvent)
@@ -155,7 +163,9 @@ impl Render for DisconnectedOverlay {
                 "Your connection to the remote project has been lost.".to_string()
             }
             Host::RemoteServerProject(options, server_not_running) => {
-                let autosave = if ProjectSettings::get_global(cx)
+                let autosave = if self.can_work_offline {
+                    "\nYou can keep editing offline. Saved changes are written to the server once you reconnect."
+                } else if ProjectSettings::get_global(cx)
                     .session
                     .restore_unsaved_buffers
                 {
@@ -202,6 +212,14 @@ impl Render for DisconnectedOverlay {
                                             window.remove_window();
                                         })),
                                 )
+                                .when(self.can_work_offline, |el| {
+                                    el.child(
+                                        Button::new("work-offline", "Work Offline")
+                                            .style(ButtonStyle::Filled)
+                                            .layer(ElevationIndex::ModalSurface)
+                                            .on_click(cx.listener(Self::handle_work_offline)),
+                                    )
+                                })
                                 .when(can_reconnect, |el| {
                                     el.child(
                                         Button::new("reconnect", "Reconnect")
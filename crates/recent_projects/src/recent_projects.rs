@@ -1394,6 +1394,8 @@ fn icon_for_remote_connection(options: Option<&RemoteConnectionOptions>) -> Icon
             RemoteConnectionOptions::Ssh(_) => IconName::Server,
             RemoteConnectionOptions::Wsl(_) => IconName::Linux,
             RemoteConnectionOptions::Docker(_) => IconName::Box,
+            RemoteConnectionOptions::Socket(_) => IconName::Server,
+            RemoteConnectionOptions::Command(_) => IconName::Server,
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(_) => IconName::Server,
         },
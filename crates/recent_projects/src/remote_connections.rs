@@ -13,11 +13,14 @@ use gpui::{AppContext, AsyncApp, PromptLevel, WindowHandle};
 use language::Point;
 use project::trusted_worktrees;
 use remote::{
-    DockerConnectionOptions, Interactive, RemoteConnection, RemoteConnectionOptions,
-    SshConnectionOptions,
+    CommandConnectionOptions, DockerConnectionOptions, Interactive, RemoteConnection,
+    RemoteConnectionOptions, SocketConnectionOptions, SshConnectionOptions,
 };
 pub use settings::SshConnection;
-use settings::{DevContainerConnection, ExtendingVec, RegisterSetting, Settings, WslConnection};
+use settings::{
+    CommandConnection, DevContainerConnection, ExtendingVec, RegisterSetting, Settings,
+    SocketConnection, WslConnection,
+};
 use util::paths::PathWithPosition;
 use workspace::{
     AppState, MultiWorkspace, OpenOptions, SerializedWorkspaceLocation, Workspace,
@@ -33,6 +36,8 @@ pub use remote_connection::{
 pub struct RemoteSettings {
     pub ssh_connections: ExtendingVec<SshConnection>,
     pub wsl_connections: ExtendingVec<WslConnection>,
+    pub socket_connections: ExtendingVec<SocketConnection>,
+    pub command_connections: ExtendingVec<CommandConnection>,
     /// Whether to read ~/.ssh/config for ssh connection sources.
     pub read_ssh_config: bool,
 }
@@ -46,6 +51,14 @@ impl RemoteSettings {
         self.wsl_connections.clone().0.into_iter()
     }
 
+    pub fn socket_connections(&self) -> impl Iterator<Item = SocketConnection> + use<> {
+        self.socket_connections.clone().0.into_iter()
+    }
+
+    pub fn command_connections(&self) -> impl Iterator<Item = CommandConnection> + use<> {
+        self.command_connections.clone().0.into_iter()
+    }
+
     pub fn fill_connection_options_from_settings(&self, options: &mut SshConnectionOptions) {
         for conn in self.ssh_connections() {
             if conn.host == options.host.to_string()
@@ -83,6 +96,8 @@ pub enum Connection {
     Ssh(SshConnection),
     Wsl(WslConnection),
     DevContainer(DevContainerConnection),
+    Socket(SocketConnectionOptions),
+    Command(CommandConnectionOptions),
 }
 
 impl From<Connection> for RemoteConnectionOptions {
@@ -99,6 +114,8 @@ impl From<Connection> for RemoteConnectionOptions {
                     use_podman: conn.use_podman,
                 })
             }
+            Connection::Socket(options) => RemoteConnectionOptions::Socket(options),
+            Connection::Command(options) => RemoteConnectionOptions::Command(options),
         }
     }
 }
@@ -121,6 +138,12 @@ impl Settings for RemoteSettings {
         Self {
             ssh_connections: remote.ssh_connections.clone().unwrap_or_default().into(),
             wsl_connections: remote.wsl_connections.clone().unwrap_or_default().into(),
+            socket_connections: remote.socket_connections.clone().unwrap_or_default().into(),
+            command_connections: remote
+                .command_connections
+                .clone()
+                .unwrap_or_default()
+                .into(),
             read_ssh_config: remote.read_ssh_config.unwrap(),
         }
     }
@@ -320,6 +343,10 @@ pub async fn open_remote_project(
                                 RemoteConnectionOptions::Docker(_) => {
                                     "Failed to connect to Dev Container"
                                 }
+                                RemoteConnectionOptions::Socket(_)
+                                | RemoteConnectionOptions::Command(_) => {
+                                    "Failed to connect to remote server"
+                                }
                                 #[cfg(any(test, feature = "test-support"))]
                                 RemoteConnectionOptions::Mock(_) => {
                                     "Failed to connect to mock server"
@@ -381,6 +408,10 @@ pub async fn open_remote_project(
                                 RemoteConnectionOptions::Docker(_) => {
                                     "Failed to connect to Dev Container"
                                 }
+                                RemoteConnectionOptions::Socket(_)
+                                | RemoteConnectionOptions::Command(_) => {
+                                    "Failed to connect to remote server"
+                                }
                                 #[cfg(any(test, feature = "test-support"))]
                                 RemoteConnectionOptions::Mock(_) => {
                                     "Failed to connect to mock server"
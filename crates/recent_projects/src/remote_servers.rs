@@ -5,6 +5,7 @@ use crate::{
     },
     ssh_config::parse_ssh_config_hosts,
 };
+use anyhow::Context as _;
 use dev_container::{
     DevContainerConfig, DevContainerContext, find_devcontainer_configs,
     start_dev_container_with_config,
@@ -24,8 +25,8 @@ use paths::{global_ssh_config_file, user_ssh_config_file};
 use picker::{Picker, PickerDelegate};
 use project::{Fs, Project};
 use remote::{
-    RemoteClient, RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions,
-    remote_client::ConnectionIdentifier,
+    CommandConnectionOptions, RemoteClient, RemoteConnectionOptions, SocketConnectionOptions,
+    SshConnectionOptions, WslConnectionOptions, remote_client::ConnectionIdentifier,
 };
 use settings::{
     RemoteProject, RemoteSettingsContent, Settings as _, SettingsStore, update_settings_file,
@@ -413,6 +414,14 @@ impl ProjectPicker {
                 connection_string: "".into(),
                 nickname: None,
             },
+            RemoteConnectionOptions::Socket(connection) => ProjectPickerData::Ssh {
+                connection_string: connection.connection_string().into(),
+                nickname: connection.nickname.clone().map(|nick| nick.into()),
+            },
+            RemoteConnectionOptions::Command(connection) => ProjectPickerData::Ssh {
+                connection_string: connection.exec.join(" ").into(),
+                nickname: Some(connection.name.clone().into()),
+            },
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(options) => ProjectPickerData::Ssh {
                 connection_string: format!("mock-{}", options.id).into(),
@@ -481,6 +490,26 @@ impl ProjectPicker {
                                         server.projects.insert(RemoteProject { paths });
                                     };
                                 }
+                                ServerIndex::Socket(index) => {
+                                    if let Some(server) = settings
+                                        .remote
+                                        .socket_connections
+                                        .as_mut()
+                                        .and_then(|connections| connections.get_mut(index.0))
+                                    {
+                                        server.projects.insert(RemoteProject { paths });
+                                    };
+                                }
+                                ServerIndex::Command(index) => {
+                                    if let Some(server) = settings
+                                        .remote
+                                        .command_connections
+                                        .as_mut()
+                                        .and_then(|connections| connections.get_mut(index.0))
+                                    {
+                                        server.projects.insert(RemoteProject { paths });
+                                    };
+                                }
                             }
                         });
                     })
@@ -598,10 +627,30 @@ impl std::fmt::Display for WslServerIndex {
     }
 }
 
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct SocketServerIndex(usize);
+impl std::fmt::Display for SocketServerIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct CommandServerIndex(usize);
+impl std::fmt::Display for CommandServerIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum ServerIndex {
     Ssh(SshServerIndex),
     Wsl(WslServerIndex),
+    Socket(SocketServerIndex),
+    Command(CommandServerIndex),
 }
 impl From<SshServerIndex> for ServerIndex {
     fn from(index: SshServerIndex) -> Self {
@@ -613,6 +662,16 @@ impl From<WslServerIndex> for ServerIndex {
         Self::Wsl(index)
     }
 }
+impl From<SocketServerIndex> for ServerIndex {
+    fn from(index: SocketServerIndex) -> Self {
+        Self::Socket(index)
+    }
+}
+impl From<CommandServerIndex> for ServerIndex {
+    fn from(index: CommandServerIndex) -> Self {
+        Self::Command(index)
+    }
+}
 
 #[derive(Clone)]
 enum RemoteEntry {
@@ -707,7 +766,63 @@ impl DefaultState {
                 }
             });
 
-        let mut servers = ssh_servers.chain(wsl_servers).collect::<Vec<RemoteEntry>>();
+        let socket_servers =
+            ssh_settings
+                .socket_connections()
+                .enumerate()
+                .filter_map(|(index, connection)| {
+                    let options = SocketConnectionOptions::try_from(&connection)
+                        .with_context(|| {
+                            format!("invalid socket connection {:?}", connection.address)
+                        })
+                        .log_err()?;
+                    let open_folder = NavigableEntry::new(&handle, cx);
+                    let configure = NavigableEntry::new(&handle, cx);
+                    let projects = connection
+                        .projects
+                        .iter()
+                        .map(|project| (NavigableEntry::new(&handle, cx), project.clone()))
+                        .collect();
+                    Some(RemoteEntry::Project {
+                        open_folder,
+                        configure,
+                        projects,
+                        index: ServerIndex::Socket(SocketServerIndex(index)),
+                        connection: Connection::Socket(options),
+                    })
+                });
+
+        let command_servers =
+            ssh_settings
+                .command_connections()
+                .enumerate()
+                .filter_map(|(index, connection)| {
+                    let options = CommandConnectionOptions::try_from(&connection)
+                        .with_context(|| {
+                            format!("invalid command connection {:?}", connection.name)
+                        })
+                        .log_err()?;
+                    let open_folder = NavigableEntry::new(&handle, cx);
+                    let configure = NavigableEntry::new(&handle, cx);
+                    let projects = connection
+                        .projects
+                        .iter()
+                        .map(|project| (NavigableEntry::new(&handle, cx), project.clone()))
+                        .collect();
+                    Some(RemoteEntry::Project {
+                        open_folder,
+                        configure,
+                        projects,
+                        index: ServerIndex::Command(CommandServerIndex(index)),
+                        connection: Connection::Command(options),
+                    })
+                });
+
+        let mut servers = ssh_servers
+            .chain(wsl_servers)
+            .chain(socket_servers)
+            .chain(command_servers)
+            .collect::<Vec<RemoteEntry>>();
 
         if read_ssh_config {
             let mut extra_servers_from_config = ssh_config_servers.clone();
@@ -750,6 +865,16 @@ enum ViewServerOptionsState {
         server_index: WslServerIndex,
         entries: [NavigableEntry; 2],
     },
+    Socket {
+        connection: SocketConnectionOptions,
+        server_index: SocketServerIndex,
+        entries: [NavigableEntry; 2],
+    },
+    Command {
+        connection: CommandConnectionOptions,
+        server_index: CommandServerIndex,
+        entries: [NavigableEntry; 2],
+    },
 }
 
 impl ViewServerOptionsState {
@@ -757,6 +882,8 @@ impl ViewServerOptionsState {
         match self {
             Self::Ssh { entries, .. } => entries,
             Self::Wsl { entries, .. } => entries,
+            Self::Socket { entries, .. } => entries,
+            Self::Command { entries, .. } => entries,
         }
     }
 }
@@ -1124,6 +1251,20 @@ impl RemoteServerProjects {
                     entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
                 }
             }
+            (ServerIndex::Socket(server_index), RemoteConnectionOptions::Socket(connection)) => {
+                ViewServerOptionsState::Socket {
+                    connection,
+                    server_index,
+                    entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
+                }
+            }
+            (ServerIndex::Command(server_index), RemoteConnectionOptions::Command(connection)) => {
+                ViewServerOptionsState::Command {
+                    connection,
+                    server_index,
+                    entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
+                }
+            }
             _ => {
                 log::error!("server index and connection options mismatch");
                 self.mode = Mode::default_mode(&BTreeSet::default(), cx);
@@ -1336,6 +1477,19 @@ impl RemoteServerProjects {
             Connection::DevContainer(dev_container_options) => {
                 (dev_container_options.name.clone(), None, false)
             }
+            Connection::Socket(socket_options) => {
+                let address = socket_options.connection_string();
+                if let Some(nickname) = socket_options.nickname.clone() {
+                    let aux_label = SharedString::from(format!("({address})"));
+                    (nickname, Some(aux_label), false)
+                } else {
+                    (address, None, false)
+                }
+            }
+            Connection::Command(command_options) => {
+                let aux_label = SharedString::from(format!("({})", command_options.exec.join(" ")));
+                (command_options.name.clone(), Some(aux_label), false)
+            }
         };
         v_flex()
             .w_full()
@@ -1529,6 +1683,8 @@ impl RemoteServerProjects {
             match server_ix {
                 ServerIndex::Ssh(index) => format!("ssh-{index}"),
                 ServerIndex::Wsl(index) => format!("wsl-{index}"),
+                ServerIndex::Socket(index) => format!("socket-{index}"),
+                ServerIndex::Command(index) => format!("command-{index}"),
             }
         ));
         let container_element_id_base =
@@ -1676,6 +1832,12 @@ impl RemoteServerProjects {
             ServerIndex::Wsl(server) => {
                 self.delete_wsl_project(server, project, cx);
             }
+            ServerIndex::Socket(server) => {
+                self.delete_socket_project(server, project, cx);
+            }
+            ServerIndex::Command(server) => {
+                self.delete_command_project(server, project, cx);
+            }
         }
     }
 
@@ -1723,6 +1885,58 @@ impl RemoteServerProjects {
         });
     }
 
+    fn delete_socket_project(
+        &mut self,
+        server: SocketServerIndex,
+        project: &RemoteProject,
+        cx: &mut Context<Self>,
+    ) {
+        let project = project.clone();
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(server) = setting
+                .socket_connections
+                .as_mut()
+                .and_then(|connections| connections.get_mut(server.0))
+            {
+                server.projects.remove(&project);
+            }
+        });
+    }
+
+    fn delete_socket_server(&mut self, server: SocketServerIndex, cx: &mut Context<Self>) {
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(connections) = setting.socket_connections.as_mut() {
+                connections.remove(server.0);
+            }
+        });
+    }
+
+    fn delete_command_project(
+        &mut self,
+        server: CommandServerIndex,
+        project: &RemoteProject,
+        cx: &mut Context<Self>,
+    ) {
+        let project = project.clone();
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(server) = setting
+                .command_connections
+                .as_mut()
+                .and_then(|connections| connections.get_mut(server.0))
+            {
+                server.projects.remove(&project);
+            }
+        });
+    }
+
+    fn delete_command_server(&mut self, server: CommandServerIndex, cx: &mut Context<Self>) {
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(connections) = setting.command_connections.as_mut() {
+                connections.remove(server.0);
+            }
+        });
+    }
+
     fn add_ssh_server(
         &mut self,
         connection_options: remote::SshConnectionOptions,
@@ -2184,6 +2398,24 @@ impl RemoteServerProjects {
                     }
                     .render(window, cx)
                     .into_any_element(),
+                    ViewServerOptionsState::Socket { connection, .. } => SshConnectionHeader {
+                        connection_string: connection.connection_string().into(),
+                        paths: Default::default(),
+                        nickname: connection.nickname.clone().map(|s| s.into()),
+                        is_wsl: false,
+                        is_devcontainer: false,
+                    }
+                    .render(window, cx)
+                    .into_any_element(),
+                    ViewServerOptionsState::Command { connection, .. } => SshConnectionHeader {
+                        connection_string: connection.exec.join(" ").into(),
+                        paths: Default::default(),
+                        nickname: Some(connection.name.clone().into()),
+                        is_wsl: false,
+                        is_devcontainer: false,
+                    }
+                    .render(window, cx)
+                    .into_any_element(),
                 })
                 .child(
                     v_flex()
@@ -2212,6 +2444,28 @@ impl RemoteServerProjects {
                                 window,
                                 cx,
                             )),
+                            ViewServerOptionsState::Socket {
+                                connection,
+                                entries,
+                                server_index,
+                            } => this.child(self.render_edit_socket(
+                                connection,
+                                *server_index,
+                                entries,
+                                window,
+                                cx,
+                            )),
+                            ViewServerOptionsState::Command {
+                                connection,
+                                entries,
+                                server_index,
+                            } => this.child(self.render_edit_command(
+                                connection,
+                                *server_index,
+                                entries,
+                                window,
+                                cx,
+                            )),
                         })
                         .child(ListSeparator)
                         .child({
@@ -2320,6 +2574,157 @@ impl RemoteServerProjects {
         })
     }
 
+    fn render_edit_socket(
+        &self,
+        connection: &SocketConnectionOptions,
+        index: SocketServerIndex,
+        entries: &[NavigableEntry],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let server_name = SharedString::new(
+            connection
+                .nickname
+                .clone()
+                .unwrap_or_else(|| connection.connection_string()),
+        );
+
+        v_flex().child({
+            fn remove_socket_server(
+                remote_servers: Entity<RemoteServerProjects>,
+                index: SocketServerIndex,
+                server_name: SharedString,
+                window: &mut Window,
+                cx: &mut App,
+            ) {
+                let prompt_message = format!("Remove server `{}`?", server_name);
+
+                let confirmation = window.prompt(
+                    PromptLevel::Warning,
+                    &prompt_message,
+                    None,
+                    &["Yes, remove it", "No, keep it"],
+                    cx,
+                );
+
+                cx.spawn(async move |cx| {
+                    if confirmation.await.ok() == Some(0) {
+                        remote_servers.update(cx, |this, cx| {
+                            this.delete_socket_server(index, cx);
+                        });
+                        remote_servers.update(cx, |this, cx| {
+                            this.mode = Mode::default_mode(&this.ssh_config_servers, cx);
+                            cx.notify();
+                        });
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+            div()
+                .id("socket-options-remove-server")
+                .track_focus(&entries[0].focus_handle)
+                .on_action(cx.listener({
+                    let server_name = server_name.clone();
+                    move |_, _: &menu::Confirm, window, cx| {
+                        remove_socket_server(cx.entity(), index, server_name.clone(), window, cx);
+                        cx.focus_self(window);
+                    }
+                }))
+                .child(
+                    ListItem::new("remove-server")
+                        .toggle_state(entries[0].focus_handle.contains_focused(window, cx))
+                        .inset(true)
+                        .spacing(ui::ListItemSpacing::Sparse)
+                        .start_slot(Icon::new(IconName::Trash).color(Color::Error))
+                        .child(Label::new("Remove Server").color(Color::Error))
+                        .on_click(cx.listener(move |_, _, window, cx| {
+                            remove_socket_server(
+                                cx.entity(),
+                                index,
+                                server_name.clone(),
+                                window,
+                                cx,
+                            );
+                            cx.focus_self(window);
+                        })),
+                )
+        })
+    }
+
+    fn render_edit_command(
+        &self,
+        connection: &CommandConnectionOptions,
+        index: CommandServerIndex,
+        entries: &[NavigableEntry],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let server_name = SharedString::new(connection.display_name());
+
+        v_flex().child({
+            fn remove_command_server(
+                remote_servers: Entity<RemoteServerProjects>,
+                index: CommandServerIndex,
+                server_name: SharedString,
+                window: &mut Window,
+                cx: &mut App,
+            ) {
+                let prompt_message = format!("Remove server `{}`?", server_name);
+
+                let confirmation = window.prompt(
+                    PromptLevel::Warning,
+                    &prompt_message,
+                    None,
+                    &["Yes, remove it", "No, keep it"],
+                    cx,
+                );
+
+                cx.spawn(async move |cx| {
+                    if confirmation.await.ok() == Some(0) {
+                        remote_servers.update(cx, |this, cx| {
+                            this.delete_command_server(index, cx);
+                        });
+                        remote_servers.update(cx, |this, cx| {
+                            this.mode = Mode::default_mode(&this.ssh_config_servers, cx);
+                            cx.notify();
+                        });
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+            div()
+                .id("command-options-remove-server")
+                .track_focus(&entries[0].focus_handle)
+                .on_action(cx.listener({
+                    let server_name = server_name.clone();
+                    move |_, _: &menu::Confirm, window, cx| {
+                        remove_command_server(cx.entity(), index, server_name.clone(), window, cx);
+                        cx.focus_self(window);
+                    }
+                }))
+                .child(
+                    ListItem::new("remove-server")
+                        .toggle_state(entries[0].focus_handle.contains_focused(window, cx))
+                        .inset(true)
+                        .spacing(ui::ListItemSpacing::Sparse)
+                        .start_slot(Icon::new(IconName::Trash).color(Color::Error))
+                        .child(Label::new("Remove Server").color(Color::Error))
+                        .on_click(cx.listener(move |_, _, window, cx| {
+                            remove_command_server(
+                                cx.entity(),
+                                index,
+                                server_name.clone(),
+                                window,
+                                cx,
+                            );
+                            cx.focus_self(window);
+                        })),
+                )
+        })
+    }
+
     fn render_edit_ssh(
         &self,
         connection: &SshConnectionOptions,
@@ -2555,7 +2960,53 @@ impl RemoteServerProjects {
                 _ => None,
             }));
 
-        if ssh_connections_changed || wsl_connections_changed {
+        let socket_connections_changed = ssh_settings
+            .socket_connections()
+            .filter_map(|connection| {
+                let options = SocketConnectionOptions::try_from(&connection).ok()?;
+                Some((options, connection.projects))
+            })
+            .ne(state.servers.iter().filter_map(|server| match server {
+                RemoteEntry::Project {
+                    connection: Connection::Socket(connection),
+                    projects,
+                    ..
+                } => Some((
+                    connection.clone(),
+                    projects
+                        .iter()
+                        .map(|(_, project)| project.clone())
+                        .collect(),
+                )),
+                _ => None,
+            }));
+
+        let command_connections_changed = ssh_settings
+            .command_connections()
+            .filter_map(|connection| {
+                let options = CommandConnectionOptions::try_from(&connection).ok()?;
+                Some((options, connection.projects))
+            })
+            .ne(state.servers.iter().filter_map(|server| match server {
+                RemoteEntry::Project {
+                    connection: Connection::Command(connection),
+                    projects,
+                    ..
+                } => Some((
+                    connection.clone(),
+                    projects
+                        .iter()
+                        .map(|(_, project)| project.clone())
+                        .collect(),
+                )),
+                _ => None,
+            }));
+
+        if ssh_connections_changed
+            || wsl_connections_changed
+            || socket_connections_changed
+            || command_connections_changed
+        {
             should_rebuild = true;
         };
 
@@ -2,6 +2,7 @@ use anyhow::Result;
 use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use prost::Message as _;
 use rpc::proto::Envelope;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct MessageId(pub u32);
@@ -74,3 +75,55 @@ pub async fn read_message_raw<S: AsyncRead + Unpin>(
 
     Ok(())
 }
+
+/// Sent by the client as the first frame on a socket connection to a `remote_server listen`
+/// process, before any [`Envelope`] is exchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketHandshake {
+    pub token: String,
+    pub identifier: String,
+    pub reconnect: bool,
+}
+
+/// The server's reply to a [`SocketHandshake`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketHandshakeResponse {
+    Accepted,
+    InvalidToken,
+    /// The client asked to reconnect to a session this server process does not know about.
+    ServerNotRunning,
+}
+
+pub async fn write_json_message<S: AsyncWrite + Unpin, T: Serialize>(
+    stream: &mut S,
+    buffer: &mut Vec<u8>,
+    message: &T,
+) -> Result<()> {
+    buffer.clear();
+    serde_json::to_writer(&mut *buffer, message)?;
+    write_size_prefixed_buffer(stream, buffer).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Reads a size-prefixed JSON message, refusing messages larger than `max_len` so that an
+/// unauthenticated peer cannot make us allocate arbitrary amounts of memory.
+pub async fn read_json_message<S: AsyncRead + Unpin, T: DeserializeOwned>(
+    stream: &mut S,
+    buffer: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<T> {
+    buffer.resize(MESSAGE_LEN_SIZE, 0);
+    stream.read_exact(buffer).await?;
+
+    let message_len = message_len_from_buffer(buffer) as usize;
+    anyhow::ensure!(
+        message_len <= max_len,
+        "message of {message_len} bytes exceeds the limit of {max_len} bytes"
+    );
+    buffer.resize(message_len, 0);
+    stream.read_exact(buffer).await?;
+
+    Ok(serde_json::from_slice(buffer)?)
+}
@@ -11,7 +11,11 @@ pub use remote_client::{
     RemoteClientDelegate, RemoteClientEvent, RemoteConnection, RemoteConnectionOptions, RemoteOs,
     RemotePlatform, connect,
 };
+pub use transport::command::CommandConnectionOptions;
 pub use transport::docker::DockerConnectionOptions;
+pub use transport::socket::{
+    SocketAddress, SocketConnectionOptions, SocketTokenSource, TOKEN_ENV_VAR,
+};
 pub use transport::ssh::{SshConnectionOptions, SshPortForwardOption};
 pub use transport::wsl::WslConnectionOptions;
 #[cfg(target_os = "windows")]
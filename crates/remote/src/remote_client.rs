@@ -5,7 +5,9 @@ use crate::{
     protocol::MessageId,
     proxy::ProxyLaunchError,
     transport::{
-        docker::{DockerConnectionOptions, DockerExecConnection},
+        command::{CommandConnectionOptions, CommandExecConnection},
+        docker::DockerConnectionOptions,
+        socket::{SocketConnectionOptions, SocketRemoteConnection},
         ssh::SshRemoteConnection,
         wsl::{WslConnectionOptions, WslRemoteConnection},
     },
@@ -225,6 +227,10 @@ impl State {
         }
     }
 
+    fn is_connected(&self) -> bool {
+        matches!(self, Self::Connected { .. })
+    }
+
     fn is_reconnect_failed(&self) -> bool {
         matches!(self, Self::ReconnectFailed { .. })
     }
@@ -324,7 +330,13 @@ pub struct RemoteClient {
 
 #[derive(Debug)]
 pub enum RemoteClientEvent {
-    Disconnected { server_not_running: bool },
+    Disconnected {
+        server_not_running: bool,
+    },
+    /// The connection was lost and is being re-established.
+    Reconnecting,
+    /// The connection was re-established after it had been lost.
+    Reconnected,
 }
 
 impl EventEmitter<RemoteClientEvent> for RemoteClient {}
@@ -599,6 +611,9 @@ impl RemoteClient {
         }
 
         self.set_state(State::Reconnecting, cx);
+        if attempts == 1 {
+            cx.emit(RemoteClientEvent::Reconnecting);
+        }
 
         log::info!(
             "Trying to reconnect to remote server... Attempt {}",
@@ -712,6 +727,9 @@ impl RemoteClient {
                     this.reconnect(cx)
                 } else if this.state_is(State::is_reconnect_exhausted) {
                     Ok(())
+                } else if this.state_is(State::is_connected) {
+                    cx.emit(RemoteClientEvent::Reconnected);
+                    Ok(())
                 } else {
                     log::debug!("State has transition from Reconnecting into new state while attempting reconnect.");
                     Ok(())
@@ -1226,8 +1244,24 @@ impl ConnectionPool {
                                 .await
                                 .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
                         }
-                        RemoteConnectionOptions::Docker(opts) => {
-                            DockerExecConnection::new(opts, delegate, cx)
+                        RemoteConnectionOptions::Docker(opts) => CommandExecConnection::new(
+                            opts.command_options(),
+                            RemoteConnectionOptions::Docker(opts),
+                            delegate,
+                            cx,
+                        )
+                        .await
+                        .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>),
+                        RemoteConnectionOptions::Command(opts) => CommandExecConnection::new(
+                            opts.clone(),
+                            RemoteConnectionOptions::Command(opts),
+                            delegate,
+                            cx,
+                        )
+                        .await
+                        .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>),
+                        RemoteConnectionOptions::Socket(opts) => {
+                            SocketRemoteConnection::new(opts, delegate, cx)
                                 .await
                                 .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
                         }
@@ -1278,6 +1312,8 @@ pub enum RemoteConnectionOptions {
     Ssh(SshConnectionOptions),
     Wsl(WslConnectionOptions),
     Docker(DockerConnectionOptions),
+    Socket(SocketConnectionOptions),
+    Command(CommandConnectionOptions),
     #[cfg(any(test, feature = "test-support"))]
     Mock(crate::transport::mock::MockConnectionOptions),
 }
@@ -1294,6 +1330,8 @@ impl RemoteConnectionOptions {
                     opts.name.clone()
                 }
             }
+            RemoteConnectionOptions::Socket(opts) => opts.connection_string(),
+            RemoteConnectionOptions::Command(opts) => opts.display_name(),
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(opts) => format!("mock-{}", opts.id),
         }
@@ -1312,6 +1350,18 @@ impl From<WslConnectionOptions> for RemoteConnectionOptions {
     }
 }
 
+impl From<SocketConnectionOptions> for RemoteConnectionOptions {
+    fn from(opts: SocketConnectionOptions) -> Self {
+        RemoteConnectionOptions::Socket(opts)
+    }
+}
+
+impl From<CommandConnectionOptions> for RemoteConnectionOptions {
+    fn from(opts: CommandConnectionOptions) -> Self {
+        RemoteConnectionOptions::Command(opts)
+    }
+}
+
 #[cfg(any(test, feature = "test-support"))]
 impl From<crate::transport::mock::MockConnectionOptions> for RemoteConnectionOptions {
     fn from(opts: crate::transport::mock::MockConnectionOptions) -> Self {
@@ -12,9 +12,11 @@ use gpui::{AppContext as _, AsyncApp, Task};
 use rpc::proto::Envelope;
 use util::command::Child;
 
+pub mod command;
 pub mod docker;
 #[cfg(any(test, feature = "test-support"))]
 pub mod mock;
+pub mod socket;
 pub mod ssh;
 pub mod wsl;
 
@@ -0,0 +1,978 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{AsyncWriteExt as _, future};
+use parking_lot::Mutex;
+use release_channel::{AppCommitSha, AppVersion, ReleaseChannel};
+use semver::Version as SemanticVersion;
+use std::time::Instant;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt;
+use util::command::Stdio;
+use util::shell::ShellKind;
+use util::{
+    paths::{PathStyle, RemotePathBuf},
+    rel_path::RelPath,
+};
+
+use futures::channel::mpsc::{Sender, UnboundedReceiver, UnboundedSender};
+use gpui::{App, AppContext, AsyncApp, Task};
+use rpc::proto::Envelope;
+
+use crate::{
+    RemoteClientDelegate, RemoteConnection, RemoteConnectionOptions, RemoteOs, RemotePlatform,
+    remote_client::{CommandTemplate, Interactive},
+    transport::parse_platform,
+};
+
+const SOURCE_PLACEHOLDER: &str = "{src}";
+const DESTINATION_PLACEHOLDER: &str = "{dst}";
+
+/// Options for a remote reachable through a local command that runs a program
+/// inside of it, such as `kubectl exec` or `docker exec`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct CommandConnectionOptions {
+    pub name: String,
+    /// Local command line that runs the arguments appended to it on the remote,
+    /// e.g. `["kubectl", "exec", "-i", "my-pod", "--"]`. Trailing arguments
+    /// must be passed through as-is, without going through a shell.
+    pub exec: Vec<String>,
+    /// Like `exec`, but allocating a TTY. Used for terminals, and falls back
+    /// to `exec` when not set.
+    pub exec_interactive: Option<Vec<String>>,
+    /// Local command lines that copy the `{src}` path to the `{dst}` path on
+    /// the remote, run in order. When empty, files are streamed through `exec`.
+    pub upload: Vec<Vec<String>>,
+    /// Whether to upload the server binary from this machine instead of
+    /// downloading it on the remote.
+    pub upload_binary_over_exec: bool,
+}
+
+impl CommandConnectionOptions {
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            self.exec.join(" ")
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Builds the local command that runs `program` on the remote.
+    pub(crate) fn exec_command(
+        &self,
+        program: &str,
+        args: &[impl AsRef<str>],
+        working_dir: Option<&str>,
+        env: &HashMap<String, String>,
+        interactive: Interactive,
+    ) -> Result<CommandTemplate> {
+        let template = match interactive {
+            Interactive::Yes => self.exec_interactive.as_ref().unwrap_or(&self.exec),
+            Interactive::No => &self.exec,
+        };
+        let (exec_program, exec_args) = template
+            .split_first()
+            .context("exec command for remote is empty")?;
+
+        let mut args_out = exec_args.to_vec();
+        if let Some(working_dir) = working_dir {
+            // Paths relative to the home directory are resolved by the remote shell,
+            // since `~` is not expanded when passed as a plain argument.
+            let relative_to_home = if working_dir == "~" {
+                Some("")
+            } else {
+                working_dir.strip_prefix("~/")
+            };
+            match relative_to_home {
+                Some(relative) => {
+                    args_out.extend([
+                        "sh".to_string(),
+                        "-c".to_string(),
+                        "cd && cd -- \"$0\" && exec \"$@\"".to_string(),
+                        if relative.is_empty() {
+                            ".".to_string()
+                        } else {
+                            relative.to_string()
+                        },
+                    ]);
+                }
+                None => {
+                    args_out.extend([
+                        "sh".to_string(),
+                        "-c".to_string(),
+                        "cd -- \"$0\" && exec \"$@\"".to_string(),
+                        working_dir.to_string(),
+                    ]);
+                }
+            }
+        }
+        if !env.is_empty() {
+            let mut env = env.iter().collect::<Vec<_>>();
+            env.sort();
+            args_out.push("env".to_string());
+            args_out.extend(env.into_iter().map(|(k, v)| format!("{k}={v}")));
+        }
+        args_out.push(program.to_string());
+        args_out.extend(args.iter().map(|arg| arg.as_ref().to_string()));
+
+        Ok(CommandTemplate {
+            program: exec_program.clone(),
+            args: args_out,
+            env: Default::default(),
+        })
+    }
+
+    /// Runs `program` on the remote and returns its standard output.
+    pub(crate) async fn run_exec(
+        &self,
+        program: &str,
+        working_dir: Option<&str>,
+        env: &HashMap<String, String>,
+        args: &[impl AsRef<str>],
+    ) -> Result<String> {
+        let template = self.exec_command(program, args, working_dir, env, Interactive::No)?;
+        let mut command = util::command::new_command(&template.program);
+        command.args(&template.args);
+        let output = command.output().await?;
+        log::debug!("{:?}: {:?}", command, output);
+        anyhow::ensure!(
+            output.status.success(),
+            "failed to run command {command:?}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Runs `program` on the remote, writing `input` to its standard input.
+    async fn run_exec_with_input(
+        &self,
+        program: &str,
+        args: &[impl AsRef<str>],
+        input: Vec<u8>,
+    ) -> Result<()> {
+        let template =
+            self.exec_command(program, args, None, &Default::default(), Interactive::No)?;
+        let mut command = util::command::new_command(&template.program);
+        command
+            .args(&template.args)
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        let mut child = command.spawn()?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("failed to get stdin of exec command")?;
+        let write_input = async move {
+            stdin.write_all(&input).await?;
+            stdin.close().await?;
+            anyhow::Ok(())
+        };
+        let (write_result, output) = future::join(write_input, child.output()).await;
+        let output = output?;
+        anyhow::ensure!(
+            output.status.success(),
+            "failed to run command {command:?}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        write_result
+    }
+
+    /// Copies a local file or directory to `dst_path` on the remote.
+    pub(crate) async fn upload(&self, src_path: &Path, dst_path: &str) -> Result<()> {
+        log::debug!("uploading {:?} to {:?}", src_path, dst_path);
+        if self.upload.is_empty() {
+            return self.stream_upload(src_path, dst_path).await;
+        }
+
+        let src_path = src_path.to_string_lossy();
+        for template in &self.upload {
+            let args = template
+                .iter()
+                .map(|arg| {
+                    arg.replace(SOURCE_PLACEHOLDER, &src_path)
+                        .replace(DESTINATION_PLACEHOLDER, dst_path)
+                })
+                .collect::<Vec<_>>();
+            let (program, args) = args.split_first().context("upload command is empty")?;
+            let mut command = util::command::new_command(program);
+            command.args(args);
+            let output = command.output().await?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                log::debug!("failed to upload {src_path} -> {dst_path}: {stderr}");
+                anyhow::bail!(
+                    "failed to upload via {command:?} {} -> {}: {}",
+                    src_path,
+                    dst_path,
+                    stderr,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn stream_upload(&self, src_path: &Path, dst_path: &str) -> Result<()> {
+        if smol::fs::metadata(src_path).await?.is_dir() {
+            let mut tar = util::command::new_command("tar");
+            tar.arg("-cf").arg("-").arg("-C").arg(src_path).arg(".");
+            let archive = tar.output().await?;
+            anyhow::ensure!(
+                archive.status.success(),
+                "failed to archive {src_path:?}: {}",
+                String::from_utf8_lossy(&archive.stderr)
+            );
+            self.run_exec_with_input(
+                "sh",
+                &["-c", "mkdir -p -- \"$0\" && tar -xf - -C \"$0\"", dst_path],
+                archive.stdout,
+            )
+            .await
+        } else {
+            let contents = smol::fs::read(src_path).await?;
+            self.run_exec_with_input("sh", &["-c", "cat > \"$0\"", dst_path], contents)
+                .await
+        }
+    }
+}
+
+impl TryFrom<&settings::CommandConnection> for CommandConnectionOptions {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &settings::CommandConnection) -> Result<Self> {
+        anyhow::ensure!(
+            !val.exec.is_empty(),
+            "exec command for {:?} is empty",
+            val.name
+        );
+        Ok(CommandConnectionOptions {
+            name: val.name.clone(),
+            exec: val.exec.clone(),
+            exec_interactive: val.exec_interactive.clone(),
+            upload: val.upload.clone().into_iter().collect(),
+            upload_binary_over_exec: val.upload_binary_over_exec.unwrap_or(false),
+        })
+    }
+}
+
+pub(crate) struct CommandExecConnection {
+    proxy_process: Mutex<Option<u32>>,
+    remote_dir_for_server: String,
+    remote_binary_relpath: Option<Arc<RelPath>>,
+    options: CommandConnectionOptions,
+    connection_options: RemoteConnectionOptions,
+    remote_platform: Option<RemotePlatform>,
+    path_style: Option<PathStyle>,
+    shell: String,
+}
+
+impl CommandExecConnection {
+    /// Connects using `options`, reporting `connection_options` as the
+    /// options this connection was created from.
+    pub async fn new(
+        options: CommandConnectionOptions,
+        connection_options: RemoteConnectionOptions,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        let mut this = Self {
+            proxy_process: Mutex::new(None),
+            remote_dir_for_server: "/".to_string(),
+            remote_binary_relpath: None,
+            options,
+            connection_options,
+            remote_platform: None,
+            path_style: None,
+            shell: "sh".to_owned(),
+        };
+        let (release_channel, version, commit) = cx.update(|cx| {
+            (
+                ReleaseChannel::global(cx),
+                AppVersion::global(cx),
+                AppCommitSha::try_global(cx),
+            )
+        });
+        let remote_platform = this.check_remote_platform().await?;
+
+        this.path_style = match remote_platform.os {
+            RemoteOs::Windows => Some(PathStyle::Windows),
+            _ => Some(PathStyle::Posix),
+        };
+
+        this.remote_platform = Some(remote_platform);
+        log::info!("Remote platform discovered: {:?}", this.remote_platform);
+
+        this.shell = this.discover_shell().await;
+        log::info!("Remote shell discovered: {}", this.shell);
+
+        this.remote_dir_for_server = this.user_home_dir().await?.trim().to_string();
+
+        this.remote_binary_relpath = Some(
+            this.ensure_server_binary(
+                &delegate,
+                release_channel,
+                version,
+                &this.remote_dir_for_server,
+                commit,
+                cx,
+            )
+            .await?,
+        );
+
+        Ok(this)
+    }
+
+    async fn run_exec(
+        &self,
+        program: &str,
+        working_dir: Option<&str>,
+        env: &HashMap<String, String>,
+        args: &[impl AsRef<str>],
+    ) -> Result<String> {
+        self.options.run_exec(program, working_dir, env, args).await
+    }
+
+    async fn discover_shell(&self) -> String {
+        let default_shell = "sh";
+        match self
+            .run_exec("sh", None, &Default::default(), &["-c", "echo $SHELL"])
+            .await
+        {
+            Ok(shell) => match shell.trim() {
+                "" => {
+                    log::info!("$SHELL is not set, checking passwd for user");
+                }
+                shell => {
+                    return shell.to_owned();
+                }
+            },
+            Err(e) => {
+                log::error!("Failed to get $SHELL: {e}. Checking passwd for user");
+            }
+        }
+
+        match self
+            .run_exec(
+                "sh",
+                None,
+                &Default::default(),
+                &["-c", "getent passwd \"$(id -un)\" | cut -d: -f7"],
+            )
+            .await
+        {
+            Ok(shell) => match shell.trim() {
+                "" => {
+                    log::info!("No shell found in passwd, falling back to {default_shell}");
+                }
+                shell => {
+                    return shell.to_owned();
+                }
+            },
+            Err(e) => {
+                log::info!("Error getting shell from passwd: {e}. Falling back to {default_shell}");
+            }
+        }
+        default_shell.to_owned()
+    }
+
+    async fn check_remote_platform(&self) -> Result<RemotePlatform> {
+        let uname = self
+            .run_exec("uname", None, &Default::default(), &["-sm"])
+            .await?;
+        parse_platform(&uname)
+    }
+
+    async fn ensure_server_binary(
+        &self,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        release_channel: ReleaseChannel,
+        version: SemanticVersion,
+        remote_dir_for_server: &str,
+        commit: Option<AppCommitSha>,
+        cx: &mut AsyncApp,
+    ) -> Result<Arc<RelPath>> {
+        let remote_platform = self
+            .remote_platform
+            .context("No remote platform defined; cannot proceed.")?;
+
+        let version_str = match release_channel {
+            ReleaseChannel::Nightly => {
+                let commit = commit.map(|s| s.full()).unwrap_or_default();
+                format!("{}-{}", version, commit)
+            }
+            ReleaseChannel::Dev => "build".to_string(),
+            _ => version.to_string(),
+        };
+        let binary_name = format!(
+            "zed-remote-server-{}-{}",
+            release_channel.dev_name(),
+            version_str
+        );
+        let dst_path =
+            paths::remote_server_dir_relative().join(RelPath::unix(&binary_name).unwrap());
+
+        let binary_exists_on_server = self
+            .run_exec(
+                &dst_path.display(self.path_style()),
+                Some(&remote_dir_for_server),
+                &Default::default(),
+                &["version"],
+            )
+            .await
+            .is_ok();
+        #[cfg(any(debug_assertions, feature = "build-remote-server-binary"))]
+        if let Some(remote_server_path) = super::build_remote_server_from_source(
+            &remote_platform,
+            delegate.as_ref(),
+            binary_exists_on_server,
+            cx,
+        )
+        .await?
+        {
+            let tmp_path = paths::remote_server_dir_relative().join(
+                RelPath::unix(&format!(
+                    "download-{}-{}",
+                    std::process::id(),
+                    remote_server_path.file_name().unwrap().to_string_lossy()
+                ))
+                .unwrap(),
+            );
+            self.upload_local_server_binary(
+                &remote_server_path,
+                &tmp_path,
+                &remote_dir_for_server,
+                delegate,
+                cx,
+            )
+            .await?;
+            self.extract_server_binary(&dst_path, &tmp_path, &remote_dir_for_server, delegate, cx)
+                .await?;
+            return Ok(dst_path);
+        }
+
+        if binary_exists_on_server {
+            return Ok(dst_path);
+        }
+
+        let wanted_version = cx.update(|cx| match release_channel {
+            ReleaseChannel::Nightly => Ok(None),
+            ReleaseChannel::Dev => {
+                anyhow::bail!(
+                    "ZED_BUILD_REMOTE_SERVER is not set and no remote server exists at ({:?})",
+                    dst_path
+                )
+            }
+            _ => Ok(Some(AppVersion::global(cx))),
+        })?;
+
+        let tmp_path_gz = paths::remote_server_dir_relative().join(
+            RelPath::unix(&format!(
+                "{}-download-{}.gz",
+                binary_name,
+                std::process::id()
+            ))
+            .unwrap(),
+        );
+        if !self.options.upload_binary_over_exec
+            && let Some(url) = delegate
+                .get_download_url(remote_platform, release_channel, wanted_version.clone(), cx)
+                .await?
+        {
+            match self
+                .download_binary_on_server(&url, &tmp_path_gz, &remote_dir_for_server, delegate, cx)
+                .await
+            {
+                Ok(_) => {
+                    self.extract_server_binary(
+                        &dst_path,
+                        &tmp_path_gz,
+                        &remote_dir_for_server,
+                        delegate,
+                        cx,
+                    )
+                    .await
+                    .context("extracting server binary")?;
+                    return Ok(dst_path);
+                }
+                Err(e) => {
+                    log::error!(
+                        "Failed to download binary on server, attempting to download locally and then upload it the server: {e:#}",
+                    )
+                }
+            }
+        }
+
+        let src_path = delegate
+            .download_server_binary_locally(remote_platform, release_channel, wanted_version, cx)
+            .await
+            .context("downloading server binary locally")?;
+        self.upload_local_server_binary(
+            &src_path,
+            &tmp_path_gz,
+            &remote_dir_for_server,
+            delegate,
+            cx,
+        )
+        .await
+        .context("uploading server binary")?;
+        self.extract_server_binary(
+            &dst_path,
+            &tmp_path_gz,
+            &remote_dir_for_server,
+            delegate,
+            cx,
+        )
+        .await
+        .context("extracting server binary")?;
+        Ok(dst_path)
+    }
+
+    async fn user_home_dir(&self) -> Result<String> {
+        let inner_program = self.shell();
+        self.run_exec(
+            &inner_program,
+            None,
+            &Default::default(),
+            &["-c", "echo $HOME"],
+        )
+        .await
+    }
+
+    async fn extract_server_binary(
+        &self,
+        dst_path: &RelPath,
+        tmp_path: &RelPath,
+        remote_dir_for_server: &str,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        delegate.set_status(Some("Extracting remote development server"), cx);
+        let server_mode = 0o755;
+
+        let shell_kind = ShellKind::Posix;
+        let orig_tmp_path = tmp_path.display(self.path_style());
+        let server_mode = format!("{:o}", server_mode);
+        let server_mode = shell_kind
+            .try_quote(&server_mode)
+            .context("shell quoting")?;
+        let dst_path = dst_path.display(self.path_style());
+        let dst_path = shell_kind.try_quote(&dst_path).context("shell quoting")?;
+        let script = if let Some(tmp_path) = orig_tmp_path.strip_suffix(".gz") {
+            let orig_tmp_path = shell_kind
+                .try_quote(&orig_tmp_path)
+                .context("shell quoting")?;
+            let tmp_path = shell_kind.try_quote(&tmp_path).context("shell quoting")?;
+            format!(
+                "gunzip -f {orig_tmp_path} && chmod {server_mode} {tmp_path} && mv {tmp_path} {dst_path}",
+            )
+        } else {
+            let orig_tmp_path = shell_kind
+                .try_quote(&orig_tmp_path)
+                .context("shell quoting")?;
+            format!("chmod {server_mode} {orig_tmp_path} && mv {orig_tmp_path} {dst_path}",)
+        };
+        let args = shell_kind.args_for_shell(false, script.to_string());
+        self.run_exec(
+            "sh",
+            Some(&remote_dir_for_server),
+            &Default::default(),
+            &args,
+        )
+        .await
+        .log_err();
+        Ok(())
+    }
+
+    async fn upload_local_server_binary(
+        &self,
+        src_path: &Path,
+        tmp_path_gz: &RelPath,
+        remote_dir_for_server: &str,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        if let Some(parent) = tmp_path_gz.parent() {
+            self.run_exec(
+                "mkdir",
+                Some(remote_dir_for_server),
+                &Default::default(),
+                &["-p", parent.display(self.path_style()).as_ref()],
+            )
+            .await?;
+        }
+
+        let src_stat = smol::fs::metadata(&src_path).await?;
+        let size = src_stat.len();
+
+        let t0 = Instant::now();
+        delegate.set_status(Some("Uploading remote development server"), cx);
+        log::info!(
+            "uploading remote development server to {:?} ({}kb)",
+            tmp_path_gz,
+            size / 1024
+        );
+        let full_server_path = format!(
+            "{}/{}",
+            remote_dir_for_server,
+            tmp_path_gz.display(self.path_style())
+        );
+        self.options
+            .upload(src_path, &full_server_path)
+            .await
+            .context("failed to upload server binary")?;
+        log::info!("uploaded remote development server in {:?}", t0.elapsed());
+        Ok(())
+    }
+
+    async fn download_binary_on_server(
+        &self,
+        url: &str,
+        tmp_path_gz: &RelPath,
+        remote_dir_for_server: &str,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        if let Some(parent) = tmp_path_gz.parent() {
+            self.run_exec(
+                "mkdir",
+                Some(remote_dir_for_server),
+                &Default::default(),
+                &["-p", parent.display(self.path_style()).as_ref()],
+            )
+            .await?;
+        }
+
+        delegate.set_status(Some("Downloading remote development server on host"), cx);
+
+        match self
+            .run_exec(
+                "curl",
+                Some(remote_dir_for_server),
+                &Default::default(),
+                &[
+                    "-f",
+                    "-L",
+                    url,
+                    "-o",
+                    &tmp_path_gz.display(self.path_style()),
+                ],
+            )
+            .await
+        {
+            Ok(_) => {}
+            Err(e) => {
+                if self
+                    .run_exec("which", None, &Default::default(), &["curl"])
+                    .await
+                    .is_ok()
+                {
+                    return Err(e);
+                }
+
+                log::info!("curl is not available, trying wget");
+                match self
+                    .run_exec(
+                        "wget",
+                        Some(remote_dir_for_server),
+                        &Default::default(),
+                        &[url, "-O", &tmp_path_gz.display(self.path_style())],
+                    )
+                    .await
+                {
+                    Ok(_) => {}
+                    Err(e) => {
+                        if self
+                            .run_exec("which", None, &Default::default(), &["wget"])
+                            .await
+                            .is_ok()
+                        {
+                            return Err(e);
+                        } else {
+                            anyhow::bail!("Neither curl nor wget is available");
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn kill_inner(&self) -> Result<()> {
+        if let Some(pid) = self.proxy_process.lock().take() {
+            if let Ok(_) = util::command::new_command("kill")
+                .arg(pid.to_string())
+                .spawn()
+            {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("Failed to kill process"))
+            }
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteConnection for CommandExecConnection {
+    fn has_wsl_interop(&self) -> bool {
+        false
+    }
+    fn start_proxy(
+        &self,
+        unique_identifier: String,
+        reconnect: bool,
+        incoming_tx: UnboundedSender<Envelope>,
+        outgoing_rx: UnboundedReceiver<Envelope>,
+        connection_activity_tx: Sender<()>,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<i32>> {
+        // We'll try connecting anew every time we open a connection, so proactively try to kill any old connections.
+        if !self.has_been_killed() {
+            if let Err(e) = self.kill_inner() {
+                return Task::ready(Err(e));
+            };
+        }
+
+        delegate.set_status(Some("Starting proxy"), cx);
+
+        let Some(remote_binary_relpath) = self.remote_binary_relpath.clone() else {
+            return Task::ready(Err(anyhow!("Remote binary path not set")));
+        };
+
+        let mut env = HashMap::default();
+        for env_var in ["RUST_LOG", "RUST_BACKTRACE", "ZED_GENERATE_MINIDUMPS"] {
+            if let Some(value) = std::env::var(env_var).ok() {
+                env.insert(env_var.to_string(), value);
+            }
+        }
+
+        let mut proxy_args = vec![
+            "proxy".to_string(),
+            "--identifier".to_string(),
+            unique_identifier,
+        ];
+        if reconnect {
+            proxy_args.push("--reconnect".to_string());
+        }
+        let template = match self.options.exec_command(
+            &remote_binary_relpath.display(self.path_style()),
+            &proxy_args,
+            Some(&self.remote_dir_for_server),
+            &env,
+            Interactive::No,
+        ) {
+            Ok(template) => template,
+            Err(e) => return Task::ready(Err(e)),
+        };
+
+        let mut command = util::command::new_command(&template.program);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(template.args);
+
+        let Ok(child) = command.spawn() else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "Failed to start remote server process"
+            )));
+        };
+
+        let mut proxy_process = self.proxy_process.lock();
+        *proxy_process = Some(child.id());
+
+        cx.spawn(async move |cx| {
+            super::handle_rpc_messages_over_child_process_stdio(
+                child,
+                incoming_tx,
+                outgoing_rx,
+                connection_activity_tx,
+                cx,
+            )
+            .await
+            .and_then(|status| {
+                if status != 0 {
+                    anyhow::bail!("Remote server exited with status {status}");
+                }
+                Ok(0)
+            })
+        })
+    }
+
+    fn upload_directory(
+        &self,
+        src_path: PathBuf,
+        dest_path: RemotePathBuf,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        let options = self.options.clone();
+        let dest_path = dest_path.to_string();
+        cx.background_spawn(async move { options.upload(&src_path, &dest_path).await })
+    }
+
+    async fn kill(&self) -> Result<()> {
+        self.kill_inner()
+    }
+
+    fn has_been_killed(&self) -> bool {
+        self.proxy_process.lock().is_none()
+    }
+
+    fn build_command(
+        &self,
+        program: Option<String>,
+        args: &[String],
+        env: &HashMap<String, String>,
+        working_dir: Option<String>,
+        _port_forward: Option<(u16, String, u16)>,
+        interactive: Interactive,
+    ) -> Result<CommandTemplate> {
+        let working_dir =
+            working_dir.map(|dir| RemotePathBuf::new(dir, self.path_style()).to_string());
+        let (program, args) = match program {
+            Some(program) => (program, args.to_vec()),
+            None => (self.shell(), vec!["-l".to_string()]),
+        };
+        self.options
+            .exec_command(&program, &args, working_dir.as_deref(), env, interactive)
+    }
+
+    fn build_forward_ports_command(
+        &self,
+        _forwards: Vec<(u16, String, u16)>,
+    ) -> Result<CommandTemplate> {
+        Err(anyhow::anyhow!(
+            "Port forwarding is not supported for command connections"
+        ))
+    }
+
+    fn connection_options(&self) -> RemoteConnectionOptions {
+        self.connection_options.clone()
+    }
+
+    fn path_style(&self) -> PathStyle {
+        self.path_style.unwrap_or(PathStyle::Posix)
+    }
+
+    fn shell(&self) -> String {
+        self.shell.clone()
+    }
+
+    fn default_system_shell(&self) -> String {
+        String::from("/bin/sh")
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Runs the remote side of every command on this machine.
+    fn local_options() -> CommandConnectionOptions {
+        CommandConnectionOptions {
+            name: "local".to_string(),
+            exec: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "exec \"$@\"".to_string(),
+                "sh".to_string(),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_exec_command() {
+        let options = local_options();
+        let mut env = HashMap::default();
+        env.insert("B".to_string(), "2".to_string());
+        env.insert("A".to_string(), "1".to_string());
+
+        let command = options
+            .exec_command("ls", &["-la"], Some("~/work"), &env, Interactive::Yes)
+            .unwrap();
+        assert_eq!(command.program, "sh");
+        assert_eq!(
+            command.args,
+            [
+                "-c",
+                "exec \"$@\"",
+                "sh",
+                "sh",
+                "-c",
+                "cd && cd -- \"$0\" && exec \"$@\"",
+                "work",
+                "env",
+                "A=1",
+                "B=2",
+                "ls",
+                "-la",
+            ]
+        );
+
+        let command = options
+            .exec_command("ls", &[] as &[&str], None, &env, Interactive::No)
+            .unwrap();
+        assert_eq!(
+            command.args,
+            ["-c", "exec \"$@\"", "sh", "env", "A=1", "B=2", "ls"]
+        );
+    }
+
+    #[test]
+    fn test_run_exec_and_upload_with_local_shell() {
+        let options = local_options();
+        let local = tempfile::tempdir().unwrap();
+        let remote = tempfile::tempdir().unwrap();
+        let remote_dir = remote.path().to_string_lossy().to_string();
+
+        smol::block_on(async {
+            let output = options
+                .run_exec(
+                    "sh",
+                    Some(&remote_dir),
+                    &HashMap::from_iter([("GREETING".to_string(), "hello".to_string())]),
+                    &["-c", "echo \"$GREETING from $(pwd -P)\""],
+                )
+                .await
+                .unwrap();
+            let remote_dir = std::fs::canonicalize(remote.path()).unwrap();
+            assert_eq!(
+                output.trim(),
+                format!("hello from {}", remote_dir.display())
+            );
+
+            let file = local.path().join("server.gz");
+            std::fs::write(&file, b"binary contents").unwrap();
+            let dst = remote.path().join("uploaded.gz");
+            options.upload(&file, &dst.to_string_lossy()).await.unwrap();
+            assert_eq!(std::fs::read(&dst).unwrap(), b"binary contents");
+
+            let dir = local.path().join("extension");
+            std::fs::create_dir_all(dir.join("src")).unwrap();
+            std::fs::write(dir.join("src/lib.rs"), "fn main() {}").unwrap();
+            let dst = remote.path().join("extensions/extension");
+            options.upload(&dir, &dst.to_string_lossy()).await.unwrap();
+            assert_eq!(
+                std::fs::read_to_string(dst.join("src/lib.rs")).unwrap(),
+                "fn main() {}"
+            );
+
+            assert!(
+                options
+                    .run_exec("sh", None, &Default::default(), &["-c", "exit 3"])
+                    .await
+                    .is_err()
+            );
+        });
+    }
+}
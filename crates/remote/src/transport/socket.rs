@@ -0,0 +1,435 @@
+//! Transport that connects to an already running `remote_server listen` process over TCP or a
+//! Unix domain socket.
+//!
+//! Unlike the other transports, nothing is spawned on the remote machine: the server is expected
+//! to have been started out of band, and the client authenticates with a shared token. Because
+//! there is no command channel, terminals and port forwarding are unavailable.
+
+use crate::{
+    RemoteClientDelegate,
+    protocol::{
+        SocketHandshake, SocketHandshakeResponse, read_json_message, read_message,
+        write_json_message, write_message,
+    },
+    proxy::ProxyLaunchError,
+    remote_client::{CommandTemplate, Interactive, RemoteConnection, RemoteConnectionOptions},
+};
+use anyhow::{Context as _, Result, anyhow, bail};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{
+    AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _, FutureExt as _, StreamExt as _,
+    channel::{
+        mpsc::{Sender, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+};
+use gpui::{App, AppContext as _, AsyncApp, BackgroundExecutor, FutureExt as _, Task};
+use parking_lot::Mutex;
+use rpc::proto::Envelope;
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use util::paths::{PathStyle, RemotePathBuf};
+
+/// The environment variable both `remote_server listen` and the client read the token from by
+/// default.
+pub const TOKEN_ENV_VAR: &str = "ZED_REMOTE_SERVER_TOKEN";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_HANDSHAKE_RESPONSE_LEN: usize = 1024;
+
+/// Where a `remote_server listen` process accepts connections.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SocketAddress {
+    Tcp { host: String, port: u16 },
+    Unix(PathBuf),
+}
+
+impl SocketAddress {
+    /// Parses `host:port`, `[ipv6]:port`, or `unix:/path/to/socket`.
+    pub fn parse(address: &str) -> Result<Self> {
+        let address = address.trim();
+        if let Some(path) = address.strip_prefix("unix:") {
+            if path.is_empty() {
+                bail!("missing socket path in {address:?}");
+            }
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+
+        let (host, port) = address
+            .rsplit_once(':')
+            .with_context(|| format!("expected host:port or unix:/path, got {address:?}"))?;
+        let host = host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host);
+        if host.is_empty() {
+            bail!("missing host in {address:?}");
+        }
+        let port = port
+            .parse()
+            .with_context(|| format!("invalid port in {address:?}"))?;
+        Ok(Self::Tcp {
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
+impl fmt::Display for SocketAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp { host, port } if host.contains(':') => write!(f, "[{host}]:{port}"),
+            Self::Tcp { host, port } => write!(f, "{host}:{port}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Where the client reads the authentication token from. The token itself is never part of the
+/// connection options, so that they can be persisted alongside recent projects.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SocketTokenSource {
+    Env(String),
+    File(PathBuf),
+}
+
+impl Default for SocketTokenSource {
+    fn default() -> Self {
+        Self::Env(TOKEN_ENV_VAR.to_string())
+    }
+}
+
+impl SocketTokenSource {
+    /// Parses `env:NAME` or `file:/path/to/token`.
+    pub fn parse(source: &str) -> Result<Self> {
+        if let Some(name) = source.strip_prefix("env:") {
+            Ok(Self::Env(name.to_string()))
+        } else if let Some(path) = source.strip_prefix("file:") {
+            Ok(Self::File(PathBuf::from(path)))
+        } else {
+            bail!("expected env:NAME or file:/path, got {source:?}")
+        }
+    }
+
+    fn load(&self) -> Result<String> {
+        let token = match self {
+            Self::Env(name) => std::env::var(name)
+                .with_context(|| format!("environment variable {name} is not set"))?,
+            Self::File(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read token file {path:?}"))?,
+        };
+        let token = token.trim();
+        if token.is_empty() {
+            bail!("the authentication token from {self} is empty");
+        }
+        Ok(token.to_string())
+    }
+}
+
+impl fmt::Display for SocketTokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(name) => write!(f, "env:{name}"),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SocketConnectionOptions {
+    pub address: SocketAddress,
+    pub token_source: SocketTokenSource,
+    pub nickname: Option<String>,
+}
+
+impl SocketConnectionOptions {
+    pub fn connection_string(&self) -> String {
+        self.address.to_string()
+    }
+}
+
+impl TryFrom<&settings::SocketConnection> for SocketConnectionOptions {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &settings::SocketConnection) -> Result<Self> {
+        Ok(SocketConnectionOptions {
+            address: SocketAddress::parse(&val.address)?,
+            token_source: val
+                .token_source
+                .as_deref()
+                .map(SocketTokenSource::parse)
+                .transpose()?
+                .unwrap_or_default(),
+            nickname: val.nickname.clone(),
+        })
+    }
+}
+
+type BoxedStream = (
+    Box<dyn AsyncRead + Send + Unpin>,
+    Box<dyn AsyncWrite + Send + Unpin>,
+);
+
+pub(crate) struct SocketRemoteConnection {
+    connection_options: SocketConnectionOptions,
+    token: String,
+    /// The stream opened by [`SocketRemoteConnection::new`], used by the first `start_proxy`.
+    /// Every later proxy, e.g. for another window sharing this connection, opens its own stream.
+    stream: Mutex<Option<BoxedStream>>,
+    kill_txs: Mutex<Vec<oneshot::Sender<()>>>,
+    killed: AtomicBool,
+}
+
+impl SocketRemoteConnection {
+    pub(crate) async fn new(
+        connection_options: SocketConnectionOptions,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        let token = connection_options.token_source.load()?;
+        delegate.set_status(
+            Some(&format!("Connecting to {}", connection_options.address)),
+            cx,
+        );
+        let stream = connect(&connection_options.address, cx.background_executor()).await?;
+
+        Ok(Self {
+            connection_options,
+            token,
+            stream: Mutex::new(Some(stream)),
+            kill_txs: Mutex::new(Vec::new()),
+            killed: AtomicBool::new(false),
+        })
+    }
+}
+
+async fn connect(address: &SocketAddress, executor: &BackgroundExecutor) -> Result<BoxedStream> {
+    open_stream(address)
+        .with_timeout(CONNECT_TIMEOUT, executor)
+        .await
+        .map_err(|_| anyhow!("timed out connecting to {address}"))?
+        .with_context(|| format!("failed to connect to {address}"))
+}
+
+async fn open_stream(address: &SocketAddress) -> Result<BoxedStream> {
+    match address {
+        SocketAddress::Tcp { host, port } => {
+            let stream = smol::net::TcpStream::connect((host.as_str(), *port)).await?;
+            stream.set_nodelay(true).ok();
+            let (reader, writer) = stream.split();
+            Ok((Box::new(reader), Box::new(writer)))
+        }
+        SocketAddress::Unix(path) => {
+            let stream = net::async_net::UnixStream::connect(path).await?;
+            let (reader, writer) = stream.split();
+            Ok((Box::new(reader), Box::new(writer)))
+        }
+    }
+}
+
+async fn write_envelopes(
+    mut writer: impl AsyncWrite + Unpin,
+    mut outgoing_rx: UnboundedReceiver<Envelope>,
+) -> Result<()> {
+    let mut buffer = Vec::new();
+    while let Some(outgoing) = outgoing_rx.next().await {
+        write_message(&mut writer, &mut buffer, outgoing).await?;
+        writer.flush().await?;
+    }
+    Ok(())
+}
+
+async fn read_envelopes(
+    mut reader: impl AsyncRead + Unpin,
+    incoming_tx: UnboundedSender<Envelope>,
+    mut connection_activity_tx: Sender<()>,
+) -> Result<()> {
+    let mut buffer = Vec::new();
+    loop {
+        let envelope = read_message(&mut reader, &mut buffer).await?;
+        connection_activity_tx.try_send(()).ok();
+        incoming_tx.unbounded_send(envelope).ok();
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteConnection for SocketRemoteConnection {
+    fn start_proxy(
+        &self,
+        unique_identifier: String,
+        reconnect: bool,
+        incoming_tx: UnboundedSender<Envelope>,
+        outgoing_rx: UnboundedReceiver<Envelope>,
+        connection_activity_tx: Sender<()>,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<i32>> {
+        let stream = self.stream.lock().take();
+        let (kill_tx, kill_rx) = oneshot::channel();
+        let mut kill_txs = self.kill_txs.lock();
+        kill_txs.retain(|kill_tx| !kill_tx.is_canceled());
+        kill_txs.push(kill_tx);
+        drop(kill_txs);
+
+        delegate.set_status(Some("Authenticating"), cx);
+
+        let address = self.connection_options.address.clone();
+        let handshake = SocketHandshake {
+            token: self.token.clone(),
+            identifier: unique_identifier,
+            reconnect,
+        };
+        let executor = cx.background_executor().clone();
+        cx.background_spawn(async move {
+            let (mut reader, mut writer) = match stream {
+                Some(stream) => stream,
+                None => connect(&address, &executor).await?,
+            };
+            let mut buffer = Vec::new();
+            write_json_message(&mut writer, &mut buffer, &handshake)
+                .await
+                .context("sending handshake")?;
+            let response: SocketHandshakeResponse =
+                read_json_message(&mut reader, &mut buffer, MAX_HANDSHAKE_RESPONSE_LEN)
+                    .await
+                    .context("reading handshake response")?;
+            match response {
+                SocketHandshakeResponse::Accepted => {}
+                SocketHandshakeResponse::InvalidToken => {
+                    bail!("the remote server rejected the authentication token")
+                }
+                SocketHandshakeResponse::ServerNotRunning => {
+                    log::info!(
+                        "remote server does not know session {}",
+                        handshake.identifier
+                    );
+                    return Ok(ProxyLaunchError::ServerNotRunning.to_exit_code());
+                }
+            }
+
+            futures::select! {
+                result = write_envelopes(writer, outgoing_rx).fuse() => {
+                    result.context("writing to socket")?
+                }
+                result = read_envelopes(reader, incoming_tx, connection_activity_tx).fuse() => {
+                    result.context("reading from socket")?
+                }
+                _ = kill_rx.fuse() => {}
+            }
+            Ok(0)
+        })
+    }
+
+    fn upload_directory(
+        &self,
+        _src_path: PathBuf,
+        _dest_path: RemotePathBuf,
+        _cx: &App,
+    ) -> Task<Result<()>> {
+        Task::ready(Err(anyhow!(
+            "Uploading files is not supported over socket connections"
+        )))
+    }
+
+    async fn kill(&self) -> Result<()> {
+        self.killed.store(true, Ordering::SeqCst);
+        self.stream.lock().take();
+        for kill_tx in self.kill_txs.lock().drain(..) {
+            kill_tx.send(()).ok();
+        }
+        Ok(())
+    }
+
+    fn has_been_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    fn build_command(
+        &self,
+        _program: Option<String>,
+        _args: &[String],
+        _env: &HashMap<String, String>,
+        _working_dir: Option<String>,
+        _port_forward: Option<(u16, String, u16)>,
+        _interactive: Interactive,
+    ) -> Result<CommandTemplate> {
+        Err(anyhow!(
+            "Running commands is not supported over socket connections"
+        ))
+    }
+
+    fn build_forward_ports_command(
+        &self,
+        _forwards: Vec<(u16, String, u16)>,
+    ) -> Result<CommandTemplate> {
+        Err(anyhow!(
+            "Port forwarding is not supported over socket connections"
+        ))
+    }
+
+    fn connection_options(&self) -> RemoteConnectionOptions {
+        RemoteConnectionOptions::Socket(self.connection_options.clone())
+    }
+
+    fn path_style(&self) -> PathStyle {
+        PathStyle::Posix
+    }
+
+    fn shell(&self) -> String {
+        "sh".to_string()
+    }
+
+    fn default_system_shell(&self) -> String {
+        String::from("/bin/sh")
+    }
+
+    fn has_wsl_interop(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_socket_address() {
+        assert_eq!(
+            SocketAddress::parse("devbox:7001").unwrap(),
+            SocketAddress::Tcp {
+                host: "devbox".into(),
+                port: 7001
+            }
+        );
+        assert_eq!(
+            SocketAddress::parse("[::1]:7001").unwrap(),
+            SocketAddress::Tcp {
+                host: "::1".into(),
+                port: 7001
+            }
+        );
+        assert_eq!(
+            SocketAddress::parse("unix:/run/zed/remote.sock").unwrap(),
+            SocketAddress::Unix(PathBuf::from("/run/zed/remote.sock"))
+        );
+        assert!(SocketAddress::parse("devbox").is_err());
+        assert!(SocketAddress::parse("devbox:port").is_err());
+        assert!(SocketAddress::parse("unix:").is_err());
+        assert_eq!(
+            SocketTokenSource::parse("file:/etc/zed/token").unwrap(),
+            SocketTokenSource::File(PathBuf::from("/etc/zed/token"))
+        );
+        assert!(SocketTokenSource::parse("hunter2").is_err());
+
+        for address in ["devbox:7001", "[::1]:7001", "unix:/tmp/zed.sock"] {
+            assert_eq!(SocketAddress::parse(address).unwrap().to_string(), address);
+        }
+    }
+}
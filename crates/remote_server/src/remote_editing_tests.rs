@@ -26,7 +26,7 @@ use lsp::{
 };
 use node_runtime::NodeRuntime;
 use project::{
-    ProgressToken, Project,
+    ProgressToken, Project, WriteBehindResolution,
     agent_server_store::AgentServerCommand,
     search::{SearchQuery, SearchResult},
 };
@@ -37,6 +37,7 @@ use smol::stream::StreamExt;
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 use unindent::Unindent as _;
 use util::{path, paths::PathMatcher, rel_path::rel_path};
@@ -1348,6 +1349,397 @@ async fn test_reconnect(cx: &mut TestAppContext, server_cx: &mut TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_remote_write_behind_while_offline(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        path!("/code"),
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }"
+                }
+            },
+        }),
+    )
+    .await;
+
+    let (project, _headless) = init_test(&fs, cx, server_cx).await;
+    let client_fs = project.read_with(cx, |project, _| project.fs().clone());
+    let queue_path = Path::new(path!("/write_behind/queue.json"));
+    project.update(cx, |project, cx| {
+        project.buffer_store().update(cx, |buffer_store, cx| {
+            buffer_store.enable_write_behind(client_fs.clone(), queue_path.to_path_buf(), cx)
+        })
+    });
+
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    let client = cx.read(|cx| project.read(cx).remote_client().unwrap());
+    client.update(cx, |client, cx| client.force_server_not_running(cx));
+    cx.run_until_parked();
+
+    assert!(project.read_with(cx, |project, cx| project.is_working_offline(cx)));
+    buffer.update(cx, |buffer, cx| {
+        assert!(buffer.capability().editable());
+        let ix = buffer.text().find('1').unwrap();
+        buffer.edit([(ix..ix + 1, "100")], None, cx);
+    });
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    assert!(!buffer.read_with(cx, |buffer, _| buffer.is_dirty()));
+    assert_eq!(
+        fs.load(path!("/code/project1/src/lib.rs").as_ref())
+            .await
+            .unwrap(),
+        "fn one() -> usize { 1 }"
+    );
+    let queue: serde_json::Value =
+        serde_json::from_str(&client_fs.load(queue_path).await.unwrap()).unwrap();
+    assert_eq!(queue[0]["path"], "src/lib.rs");
+    assert_eq!(queue[0]["saved_text"], "fn one() -> usize { 100 }");
+    assert_eq!(queue[0]["unsaved_text"], serde_json::Value::Null);
+}
+
+#[gpui::test]
+async fn test_remote_write_behind_replay(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        path!("/code"),
+        json!({
+            "project1": {
+                "unchanged.txt": "one",
+                "changed.txt": "two, changed remotely",
+            },
+        }),
+    )
+    .await;
+    let unchanged_mtime = fs
+        .metadata(path!("/code/project1/unchanged.txt").as_ref())
+        .await
+        .unwrap()
+        .unwrap()
+        .mtime;
+
+    let (project, _headless) = init_test(&fs, cx, server_cx).await;
+    let client_fs = project.read_with(cx, |project, _| project.fs().clone());
+    let queue_path = Path::new(path!("/write_behind/queue.json"));
+    client_fs
+        .as_fake()
+        .insert_file(
+            queue_path,
+            json!([
+                {
+                    "worktree_root": path!("/code/project1"),
+                    "path": "unchanged.txt",
+                    "base_mtime": unchanged_mtime,
+                    "base_digest": null,
+                    "saved_text": "one, saved offline",
+                    "unsaved_text": "one, edited offline",
+                },
+                {
+                    "worktree_root": path!("/code/project1"),
+                    "path": "changed.txt",
+                    "base_mtime": null,
+                    "base_digest": "stale",
+                    "saved_text": "two, saved offline",
+                    "unsaved_text": null,
+                },
+            ])
+            .to_string()
+            .into_bytes(),
+        )
+        .await;
+
+    let conflicts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    cx.update(|cx| {
+        let conflicts = conflicts.clone();
+        cx.subscribe(&project, move |_, event, _| {
+            if let project::Event::WriteBehindConflict { buffer } = event {
+                conflicts.lock().unwrap().push(buffer.clone());
+            }
+        })
+        .detach();
+    });
+    project.update(cx, |project, cx| {
+        project.buffer_store().update(cx, |buffer_store, cx| {
+            buffer_store.enable_write_behind(client_fs.clone(), queue_path.to_path_buf(), cx)
+        })
+    });
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    cx.run_until_parked();
+
+    assert_eq!(
+        fs.load(path!("/code/project1/unchanged.txt").as_ref())
+            .await
+            .unwrap(),
+        "one, saved offline"
+    );
+    let unchanged = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("unchanged.txt")), cx)
+        })
+        .await
+        .unwrap();
+    unchanged.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "one, edited offline");
+        assert!(buffer.is_dirty());
+    });
+
+    assert_eq!(
+        fs.load(path!("/code/project1/changed.txt").as_ref())
+            .await
+            .unwrap(),
+        "two, changed remotely"
+    );
+    let conflict = {
+        let conflicts = conflicts.lock().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        conflicts[0].clone()
+    };
+    project
+        .update(cx, |project, cx| {
+            project.buffer_store().update(cx, |buffer_store, cx| {
+                buffer_store.resolve_write_behind_conflict(
+                    conflict,
+                    WriteBehindResolution::Overwrite,
+                    cx,
+                )
+            })
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    assert_eq!(
+        fs.load(path!("/code/project1/changed.txt").as_ref())
+            .await
+            .unwrap(),
+        "two, saved offline"
+    );
+    assert!(!client_fs.is_file(queue_path).await);
+}
+
+#[gpui::test]
+async fn test_remote_write_behind_two_projects_on_one_host(
+    cx: &mut TestAppContext,
+    server_cx_a: &mut TestAppContext,
+    server_cx_b: &mut TestAppContext,
+) {
+    let fs_a = FakeFs::new(server_cx_a.executor());
+    fs_a.insert_tree(path!("/code"), json!({ "project1": { "a.txt": "a" } }))
+        .await;
+    let fs_b = FakeFs::new(server_cx_b.executor());
+    fs_b.insert_tree(path!("/code"), json!({ "project2": { "b.txt": "b" } }))
+        .await;
+
+    let (project_a, _headless_a) = init_test(&fs_a, cx, server_cx_a).await;
+    let (project_b, _headless_b) = init_test(&fs_b, cx, server_cx_b).await;
+    let client_fs = project_a.read_with(cx, |project, _| project.fs().clone());
+    let queue_path = Path::new(path!("/write_behind/queue.json"));
+
+    let mut buffers = Vec::new();
+    for (project, root, file) in [
+        (&project_a, path!("/code/project1"), "a.txt"),
+        (&project_b, path!("/code/project2"), "b.txt"),
+    ] {
+        project.update(cx, |project, cx| {
+            project.buffer_store().update(cx, |buffer_store, cx| {
+                buffer_store.enable_write_behind(client_fs.clone(), queue_path.to_path_buf(), cx)
+            })
+        });
+        let (worktree, _) = project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree(root, true, cx)
+            })
+            .await
+            .unwrap();
+        let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_buffer((worktree_id, rel_path(file)), cx)
+            })
+            .await
+            .unwrap();
+        buffers.push(buffer);
+    }
+    cx.run_until_parked();
+
+    for project in [&project_a, &project_b] {
+        let client = cx.read(|cx| project.read(cx).remote_client().unwrap());
+        client.update(cx, |client, cx| client.force_server_not_running(cx));
+    }
+    cx.run_until_parked();
+
+    for (project, buffer) in [&project_a, &project_b].into_iter().zip(&buffers) {
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(1..1, ", saved offline")], None, cx)
+        });
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+    }
+
+    // Both projects' saves are queued, neither overwrote the other's.
+    let queue: Vec<serde_json::Value> =
+        serde_json::from_str(&client_fs.load(queue_path).await.unwrap()).unwrap();
+    let mut saved = queue
+        .iter()
+        .map(|pending| {
+            (
+                pending["path"].as_str().unwrap().to_string(),
+                pending["saved_text"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    saved.sort();
+    assert_eq!(
+        saved,
+        [
+            ("a.txt".to_string(), "a, saved offline".to_string()),
+            ("b.txt".to_string(), "b, saved offline".to_string()),
+        ]
+    );
+
+    // Closing one project's buffer keeps the other project's queued save.
+    let buffer_a = buffers.remove(0);
+    drop(buffer_a);
+    cx.run_until_parked();
+    let queue: Vec<serde_json::Value> =
+        serde_json::from_str(&client_fs.load(queue_path).await.unwrap()).unwrap();
+    assert_eq!(queue.len(), 2);
+}
+
+#[gpui::test]
+async fn test_remote_write_behind_replays_on_reconnect(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        path!("/code"),
+        json!({
+            "project1": {
+                "clean.txt": "one",
+                "dirty.txt": "two",
+            },
+        }),
+    )
+    .await;
+
+    let (project, _headless) = init_test(&fs, cx, server_cx).await;
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    let clean = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("clean.txt")), cx)
+        })
+        .await
+        .unwrap();
+    let dirty = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("dirty.txt")), cx)
+        })
+        .await
+        .unwrap();
+    dirty.update(cx, |buffer, cx| {
+        buffer.edit([(3..3, ", edited before the connection dropped")], None, cx)
+    });
+    cx.run_until_parked();
+
+    // Saving while the connection is being re-established queues the save, including for a
+    // buffer that was already dirty when the connection dropped.
+    project.update(cx, |project, cx| {
+        project.buffer_store().update(cx, |buffer_store, cx| {
+            buffer_store.reconnecting_to_remote_server(cx)
+        })
+    });
+    assert!(project.read_with(cx, |project, cx| project.is_working_offline(cx)));
+    clean.update(cx, |buffer, cx| {
+        buffer.edit([(3..3, ", saved offline")], None, cx)
+    });
+    for buffer in [&clean, &dirty] {
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        fs.load(path!("/code/project1/clean.txt").as_ref())
+            .await
+            .unwrap(),
+        "one"
+    );
+
+    let conflicts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    cx.update(|cx| {
+        let conflicts = conflicts.clone();
+        cx.subscribe(&project, move |_, event, _| {
+            if let project::Event::WriteBehindConflict { buffer } = event {
+                conflicts.lock().unwrap().push(buffer.clone());
+            }
+        })
+        .detach();
+    });
+    let client = cx.read(|cx| project.read(cx).remote_client().unwrap());
+    client
+        .update(cx, |client, cx| client.simulate_disconnect(cx))
+        .detach();
+    // Miss enough heartbeats for the client to reconnect.
+    for _ in 0..10 {
+        cx.executor().advance_clock(Duration::from_secs(5));
+        cx.run_until_parked();
+    }
+
+    assert!(!project.read_with(cx, |project, cx| project.is_working_offline(cx)));
+    assert!(conflicts.lock().unwrap().is_empty());
+    assert_eq!(
+        fs.load(path!("/code/project1/clean.txt").as_ref())
+            .await
+            .unwrap(),
+        "one, saved offline"
+    );
+    assert_eq!(
+        fs.load(path!("/code/project1/dirty.txt").as_ref())
+            .await
+            .unwrap(),
+        "two, edited before the connection dropped"
+    );
+}
+
 #[gpui::test]
 async fn test_remote_root_rename(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
     let fs = FakeFs::new(server_cx.executor());
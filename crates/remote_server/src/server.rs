@@ -20,7 +20,7 @@ use futures::{
     select, select_biased,
 };
 use git::GitHostingProviderRegistry;
-use gpui::{App, AppContext as _, Context, Entity, UpdateGlobal as _};
+use gpui::{App, AppContext as _, BackgroundExecutor, Entity, UpdateGlobal as _};
 use gpui_tokio::Tokio;
 use http_client::{Url, read_proxy_from_env};
 use language::LanguageRegistry;
@@ -31,9 +31,12 @@ use project::{project_settings::ProjectSettings, trusted_worktrees};
 use proto::CrashReport;
 use release_channel::{AppCommitSha, AppVersion, RELEASE_CHANNEL, ReleaseChannel};
 use remote::{
-    RemoteClient,
+    RemoteClient, SocketAddress, TOKEN_ENV_VAR,
     json_log::LogRecord,
-    protocol::{read_message, write_message},
+    protocol::{
+        SocketHandshake, SocketHandshakeResponse, read_json_message, read_message,
+        write_json_message, write_message,
+    },
     proxy::ProxyLaunchError,
 };
 use reqwest_client::ReqwestClient;
@@ -46,15 +49,17 @@ use smol::{
     stream::StreamExt as _,
 };
 use std::{
+    cell::RefCell,
     env,
     ffi::OsStr,
     fs::File,
     io::Write,
     mem,
     path::{Path, PathBuf},
+    rc::Rc,
     str::FromStr,
     sync::{Arc, LazyLock},
-    time::Instant,
+    time::{Duration, Instant},
 };
 use thiserror::Error;
 use util::{ResultExt, command::new_command};
@@ -79,6 +84,18 @@ pub enum Commands {
         #[arg(long)]
         identifier: String,
     },
+    /// Serve sessions to clients connecting over TCP or a Unix socket.
+    Listen {
+        /// Address to listen on, either `host:port` or `unix:/path/to/socket`.
+        #[arg(long)]
+        address: String,
+        /// File containing the token clients must present. Defaults to the
+        /// `ZED_REMOTE_SERVER_TOKEN` environment variable.
+        #[arg(long)]
+        token_file: Option<PathBuf>,
+        #[arg(long)]
+        log_file: Option<PathBuf>,
+    },
     Version,
 }
 
@@ -104,6 +121,11 @@ pub fn run(command: Commands) -> anyhow::Result<()> {
             identifier,
             reconnect,
         } => execute_proxy(identifier, reconnect).context("running proxy on the remote server"),
+        Commands::Listen {
+            address,
+            token_file,
+            log_file,
+        } => execute_listen(address, token_file, log_file),
         Commands::Version => {
             let release_channel = *RELEASE_CHANNEL;
             match release_channel {
@@ -275,6 +297,18 @@ fn handle_crash_files_requests(project: &Entity<HeadlessProject>, client: &AnyPr
     );
 }
 
+/// A client attached to the server. Connections made through `remote_server proxy` use three
+/// separate Unix sockets, while `remote_server listen` multiplexes messages over a single socket
+/// and keeps its logs in the log file.
+struct ServerConnection {
+    input: BoxedReader,
+    output: BoxedWriter,
+    log_output: Option<BoxedWriter>,
+}
+
+type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
 struct ServerListeners {
     stdin: UnixListener,
     stdout: UnixListener,
@@ -289,17 +323,227 @@ impl ServerListeners {
             stderr: UnixListener::bind(stderr_path).context("failed to bind stderr socket")?,
         })
     }
+
+    async fn serve(self, connections_tx: mpsc::UnboundedSender<ServerConnection>) {
+        loop {
+            log::info!("accepting new connections");
+            let streams = futures::future::join3(
+                self.stdin.accept(),
+                self.stdout.accept(),
+                self.stderr.accept(),
+            )
+            .await;
+            let (Ok((stdin_stream, _)), Ok((stdout_stream, _)), Ok((stderr_stream, _))) = streams
+            else {
+                log::error!("failed to accept new connections");
+                break;
+            };
+            log::info!("accepted new connections");
+            let connection = ServerConnection {
+                input: Box::new(stdin_stream),
+                output: Box::new(stdout_stream),
+                log_output: Some(Box::new(stderr_stream)),
+            };
+            if connections_tx.unbounded_send(connection).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+enum SocketListener {
+    Tcp(smol::net::TcpListener),
+    Unix(UnixListener),
+}
+
+/// Accepts clients of `remote_server listen`, which must authenticate with a shared token.
+///
+/// Sessions are keyed by the identifier clients send, so that every window gets its own session.
+/// A fresh connection starts a session for a new identifier (or takes over the existing one),
+/// and a reconnect is only accepted for an identifier the server is already hosting, mirroring
+/// what `remote_server proxy --reconnect` reports when its server process is gone.
+struct SocketServer {
+    listener: SocketListener,
+    token: String,
+    sessions: std::sync::Mutex<HashMap<String, mpsc::UnboundedSender<ServerConnection>>>,
+    new_sessions_tx: mpsc::UnboundedSender<mpsc::UnboundedReceiver<ServerConnection>>,
+}
+
+impl SocketServer {
+    async fn bind(
+        address: &SocketAddress,
+        token: String,
+        new_sessions_tx: mpsc::UnboundedSender<mpsc::UnboundedReceiver<ServerConnection>>,
+    ) -> Result<Self> {
+        let listener = match address {
+            SocketAddress::Tcp { host, port } => SocketListener::Tcp(
+                smol::net::TcpListener::bind((host.as_str(), *port))
+                    .await
+                    .with_context(|| format!("failed to listen on {address}"))?,
+            ),
+            SocketAddress::Unix(path) => {
+                if path.exists() {
+                    std::fs::remove_file(path)
+                        .with_context(|| format!("failed to remove stale socket {path:?}"))?;
+                }
+                SocketListener::Unix(
+                    UnixListener::bind(path)
+                        .with_context(|| format!("failed to listen on {address}"))?,
+                )
+            }
+        };
+        Ok(Self {
+            listener,
+            token,
+            sessions: Default::default(),
+            new_sessions_tx,
+        })
+    }
+
+    async fn serve(self: Arc<Self>, executor: BackgroundExecutor) {
+        loop {
+            let (input, output, peer) = match self.accept().await {
+                Ok(accepted) => accepted,
+                Err(error) => {
+                    log::error!("failed to accept connection: {error:#}");
+                    continue;
+                }
+            };
+
+            let this = self.clone();
+            let handshake_timeout = executor.timer(SOCKET_HANDSHAKE_TIMEOUT);
+            executor
+                .spawn(async move {
+                    let identifier = select! {
+                        identifier = this.authenticate(input, output).fuse() => identifier,
+                        _ = handshake_timeout.fuse() => Err(anyhow!("handshake timed out")),
+                    };
+                    match identifier {
+                        Ok(Some(identifier)) => {
+                            log::info!("accepted connection from {peer} for session {identifier}");
+                        }
+                        Ok(None) => {}
+                        Err(error) => log::warn!("rejected connection from {peer}: {error:#}"),
+                    }
+                })
+                .detach();
+        }
+    }
+
+    async fn accept(&self) -> std::io::Result<(BoxedReader, BoxedWriter, String)> {
+        match &self.listener {
+            SocketListener::Tcp(listener) => listener.accept().await.map(|(stream, peer)| {
+                stream.set_nodelay(true).ok();
+                let (input, output) = futures::AsyncReadExt::split(stream);
+                (
+                    Box::new(input) as BoxedReader,
+                    Box::new(output) as BoxedWriter,
+                    peer.to_string(),
+                )
+            }),
+            SocketListener::Unix(listener) => listener.accept().await.map(|(stream, _)| {
+                let (input, output) = futures::AsyncReadExt::split(stream);
+                (
+                    Box::new(input) as BoxedReader,
+                    Box::new(output) as BoxedWriter,
+                    "unix socket".to_string(),
+                )
+            }),
+        }
+    }
+
+    /// Performs the handshake and hands an accepted connection to its session, returning the
+    /// session's identifier.
+    async fn authenticate(
+        &self,
+        mut input: BoxedReader,
+        mut output: BoxedWriter,
+    ) -> Result<Option<String>> {
+        let mut buffer = Vec::new();
+        let handshake: SocketHandshake =
+            read_json_message(&mut input, &mut buffer, MAX_SOCKET_HANDSHAKE_LEN).await?;
+
+        let (response, connections_tx) = if !tokens_match(&handshake.token, &self.token) {
+            (SocketHandshakeResponse::InvalidToken, None)
+        } else if let Some(connections_tx) =
+            self.session(&handshake.identifier, handshake.reconnect)
+        {
+            (SocketHandshakeResponse::Accepted, Some(connections_tx))
+        } else {
+            (SocketHandshakeResponse::ServerNotRunning, None)
+        };
+        write_json_message(&mut output, &mut buffer, &response).await?;
+
+        match connections_tx {
+            Some(connections_tx) => {
+                let connection = ServerConnection {
+                    input,
+                    output,
+                    log_output: None,
+                };
+                connections_tx
+                    .unbounded_send(connection)
+                    .map_err(|_| anyhow!("session {} has ended", handshake.identifier))?;
+                Ok(Some(handshake.identifier))
+            }
+            None if response == SocketHandshakeResponse::InvalidToken => {
+                Err(anyhow!("invalid token"))
+            }
+            None => {
+                log::info!(
+                    "refusing to reconnect unknown session {}",
+                    handshake.identifier
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns where to send connections for the session with the given identifier, starting a
+    /// new session for a fresh connection if the server isn't hosting one yet.
+    fn session(
+        &self,
+        identifier: &str,
+        reconnect: bool,
+    ) -> Option<mpsc::UnboundedSender<ServerConnection>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(connections_tx) = sessions.get(identifier)
+            && !connections_tx.is_closed()
+        {
+            return Some(connections_tx.clone());
+        }
+        if reconnect {
+            return None;
+        }
+
+        log::info!("starting session {identifier}");
+        let (connections_tx, connections_rx) = mpsc::unbounded();
+        self.new_sessions_tx.unbounded_send(connections_rx).ok()?;
+        sessions.insert(identifier.to_string(), connections_tx.clone());
+        Some(connections_tx)
+    }
+}
+
+const SOCKET_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_SOCKET_HANDSHAKE_LEN: usize = 64 * 1024;
+
+/// Compares tokens without short-circuiting on the first differing byte.
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
 }
 
 fn start_server(
-    listeners: ServerListeners,
+    mut connections_rx: mpsc::UnboundedReceiver<ServerConnection>,
+    idle_timeout: Option<Duration>,
     log_rx: Receiver<Vec<u8>>,
     cx: &mut App,
     is_wsl_interop: bool,
 ) -> AnyProtoClient {
-    // This is the server idle timeout. If no connection comes in this timeout, the server will shut down.
-    const IDLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10 * 60);
-
     let (incoming_tx, incoming_rx) = mpsc::unbounded::<Envelope>();
     let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded::<Envelope>();
     let (app_quit_tx, mut app_quit_rx) = mpsc::unbounded::<()>();
@@ -314,49 +558,55 @@ fn start_server(
     .detach();
 
     cx.spawn(async move |cx| {
+        let mut next_connection = None;
         loop {
-            let streams = futures::future::join3(
-                listeners.stdin.accept(),
-                listeners.stdout.accept(),
-                listeners.stderr.accept(),
-            );
-
-            log::info!("accepting new connections");
-            let result = select! {
-                streams = streams.fuse() => {
-                    let (Ok((stdin_stream, _)), Ok((stdout_stream, _)), Ok((stderr_stream, _))) = streams else {
-                        log::error!("failed to accept new connections");
+            let connection = if let Some(connection) = next_connection.take() {
+                connection
+            } else {
+                let executor = cx.background_executor().clone();
+                let idle_timer = async move {
+                    match idle_timeout {
+                        Some(idle_timeout) => executor.timer(idle_timeout).await,
+                        None => futures::future::pending().await,
+                    }
+                };
+                select! {
+                    connection = connections_rx.next().fuse() => {
+                        let Some(connection) = connection else {
+                            log::error!("stopped accepting new connections");
+                            break;
+                        };
+                        connection
+                    }
+                    _ = futures::FutureExt::fuse(idle_timer) => {
+                        log::warn!("timed out waiting for new connections after {:?}. exiting.", idle_timeout);
+                        cx.update(|cx| {
+                            // TODO: This is a hack, because in a headless project, shutdown isn't executed
+                            // when calling quit, but it should be.
+                            cx.shutdown();
+                            cx.quit();
+                        });
                         break;
-                    };
-                    log::info!("accepted new connections");
-                    anyhow::Ok((stdin_stream, stdout_stream, stderr_stream))
-                }
-                _ = futures::FutureExt::fuse(cx.background_executor().timer(IDLE_TIMEOUT)) => {
-                    log::warn!("timed out waiting for new connections after {:?}. exiting.", IDLE_TIMEOUT);
-                    cx.update(|cx| {
-                        // TODO: This is a hack, because in a headless project, shutdown isn't executed
-                        // when calling quit, but it should be.
-                        cx.shutdown();
-                        cx.quit();
-                    });
-                    break;
-                }
-                _ = app_quit_rx.next().fuse() => {
-                    log::info!("app quit requested");
-                    break;
+                    }
+                    _ = app_quit_rx.next().fuse() => {
+                        log::info!("app quit requested");
+                        break;
+                    }
                 }
             };
 
-            let Ok((mut stdin_stream, mut stdout_stream, mut stderr_stream)) = result else {
-                break;
-            };
+            let ServerConnection {
+                mut input,
+                mut output,
+                mut log_output,
+            } = connection;
 
             let mut input_buffer = Vec::new();
             let mut output_buffer = Vec::new();
 
             let (mut stdin_msg_tx, mut stdin_msg_rx) = mpsc::unbounded::<Envelope>();
             cx.background_spawn(async move {
-                while let Ok(msg) = read_message(&mut stdin_stream, &mut input_buffer).await {
+                while let Ok(msg) = read_message(&mut input, &mut input_buffer).await {
                     if (stdin_msg_tx.send(msg).await).is_err() {
                         break;
                     }
@@ -370,6 +620,14 @@ fn start_server(
                         return anyhow::Ok(());
                     }
 
+                    connection = connections_rx.next().fuse() => {
+                        if let Some(connection) = connection {
+                            log::info!("new connection replaces the current one");
+                            next_connection = Some(connection);
+                        }
+                        break;
+                    }
+
                     stdin_message = stdin_msg_rx.next().fuse() => {
                         let Some(message) = stdin_message else {
                             log::warn!("error reading message on stdin, dropping connection.");
@@ -388,24 +646,26 @@ fn start_server(
                         };
 
                         if let Err(error) =
-                            write_message(&mut stdout_stream, &mut output_buffer, message).await
+                            write_message(&mut output, &mut output_buffer, message).await
                         {
                             log::error!("failed to write stdout message: {:?}", error);
                             break;
                         }
-                        if let Err(error) = stdout_stream.flush().await {
+                        if let Err(error) = output.flush().await {
                             log::error!("failed to flush stdout message: {:?}", error);
                             break;
                         }
                     }
 
                     log_message = log_rx.recv().fuse() => {
-                        if let Ok(log_message) = log_message {
-                            if let Err(error) = stderr_stream.write_all(&log_message).await {
+                        if let Ok(log_message) = log_message
+                            && let Some(log_output) = log_output.as_mut()
+                        {
+                            if let Err(error) = log_output.write_all(&log_message).await {
                                 log::error!("failed to write log message to stderr: {:?}", error);
                                 break;
                             }
-                            if let Err(error) = stderr_stream.flush().await {
+                            if let Err(error) = log_output.flush().await {
                                 log::error!("failed to flush stderr stream: {:?}", error);
                                 break;
                             }
@@ -479,7 +739,88 @@ pub fn execute_run(
         .with_context(|| format!("failed to write pid file: {:?}", &pid_file))?;
 
     let listeners = ServerListeners::new(stdin_socket, stdout_socket, stderr_socket)?;
+    let (connections_tx, connections_rx) = mpsc::unbounded();
+    app.background_executor()
+        .spawn(listeners.serve(connections_tx))
+        .detach();
+    let (sessions_tx, sessions_rx) = mpsc::unbounded();
+    sessions_tx.unbounded_send(connections_rx).ok();
+
+    // This is the server idle timeout. If no connection comes in this timeout, the server will shut down.
+    const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+    run_headless_server(app, startup_time, log_rx, sessions_rx, Some(IDLE_TIMEOUT))
+}
+
+/// Runs a server that accepts authenticated clients directly over TCP or a Unix socket, instead
+/// of being spawned on demand by `remote_server proxy`.
+pub fn execute_listen(
+    address: String,
+    token_file: Option<PathBuf>,
+    log_file: Option<PathBuf>,
+) -> Result<()> {
+    init_paths()?;
+
+    let address = SocketAddress::parse(&address)?;
+    let token = match token_file {
+        Some(token_file) => std::fs::read_to_string(&token_file)
+            .with_context(|| format!("failed to read token file {token_file:?}"))?,
+        None => env::var(TOKEN_ENV_VAR).with_context(|| {
+            format!("either --token-file or the {TOKEN_ENV_VAR} environment variable must be set")
+        })?,
+    };
+    let token = token.trim().to_string();
+    anyhow::ensure!(
+        !token.is_empty(),
+        "the authentication token must not be empty"
+    );
+
+    let startup_time = Instant::now();
+    let app = gpui_platform::headless();
+    let pid = std::process::id();
+    crashes::init(
+        crashes::InitCrashHandler {
+            session_id: pid.to_string(),
+            zed_version: VERSION.to_owned(),
+            binary: "zed-remote-server".to_string(),
+            release_channel: release_channel::RELEASE_CHANNEL_NAME.clone(),
+            commit_sha: option_env!("ZED_COMMIT_SHA").unwrap_or("no_sha").to_owned(),
+        },
+        |task| {
+            app.background_executor().spawn(task).detach();
+        },
+    );
+    let log_file = log_file.unwrap_or_else(|| logs_dir().join("server-listen.log"));
+    let log_rx = init_logging_server(&log_file)?;
+    log::info!(
+        "starting up with PID {}:\nlog_file: {:?}, address: {}",
+        pid,
+        log_file,
+        address
+    );
 
+    let (sessions_tx, sessions_rx) = mpsc::unbounded();
+    let server = Arc::new(smol::block_on(SocketServer::bind(
+        &address,
+        token,
+        sessions_tx,
+    ))?);
+    app.background_executor()
+        .spawn(server.serve(app.background_executor()))
+        .detach();
+
+    run_headless_server(app, startup_time, log_rx, sessions_rx, None)
+}
+
+/// Runs the headless app, hosting a session with its own project for every receiver of
+/// connections that arrives on `sessions_rx`.
+fn run_headless_server(
+    app: gpui::Application,
+    startup_time: Instant,
+    log_rx: Receiver<Vec<u8>>,
+    mut sessions_rx: mpsc::UnboundedReceiver<mpsc::UnboundedReceiver<ServerConnection>>,
+    idle_timeout: Option<Duration>,
+) -> Result<()> {
     rayon::ThreadPoolBuilder::new()
         .num_threads(std::thread::available_parallelism().map_or(1, |n| n.get().div_ceil(2)))
         .stack_size(10 * 1024 * 1024)
@@ -523,7 +864,19 @@ pub fn execute_run(
         };
 
         log::info!("gpui app started, initializing server");
-        let session = start_server(listeners, log_rx, cx, is_wsl_interop);
+        // Sessions that are already waiting are started before the settings are loaded, so that
+        // they are told about errors in the settings file.
+        let mut sessions = Vec::new();
+        while let Ok(Some(connections_rx)) = sessions_rx.try_next() {
+            sessions.push(start_server(
+                connections_rx,
+                idle_timeout,
+                log_rx.clone(),
+                cx,
+                is_wsl_interop,
+            ));
+        }
+        let session_clients = Rc::new(RefCell::new(sessions.clone()));
         trusted_worktrees::init(HashMap::default(), cx);
 
         GitHostingProviderRegistry::set_global(git_hosting_provider_registry, cx);
@@ -535,59 +888,81 @@ pub fn execute_run(
 
         json_schema_store::init(cx);
 
-        let project = cx.new(|cx| {
-            let fs = Arc::new(RealFs::new(None, cx.background_executor().clone()));
-            let node_settings_rx = initialize_settings(session.clone(), fs.clone(), cx);
-
-            let proxy_url = read_proxy_settings(cx);
-
-            let http_client = {
-                let _guard = Tokio::handle(cx).enter();
-                Arc::new(
-                    ReqwestClient::proxy_and_user_agent(
-                        proxy_url,
-                        &format!(
-                            "Zed-Server/{} ({}; {})",
-                            env!("CARGO_PKG_VERSION"),
-                            std::env::consts::OS,
-                            std::env::consts::ARCH
-                        ),
-                    )
-                    .expect("Could not start HTTP client"),
+        let fs = Arc::new(RealFs::new(None, cx.background_executor().clone()));
+        let node_settings_rx = initialize_settings(session_clients.clone(), fs.clone(), cx);
+
+        let proxy_url = read_proxy_settings(cx);
+
+        let http_client = {
+            let _guard = Tokio::handle(cx).enter();
+            Arc::new(
+                ReqwestClient::proxy_and_user_agent(
+                    proxy_url,
+                    &format!(
+                        "Zed-Server/{} ({}; {})",
+                        env!("CARGO_PKG_VERSION"),
+                        std::env::consts::OS,
+                        std::env::consts::ARCH
+                    ),
                 )
-            };
-
-            let node_runtime =
-                NodeRuntime::new(http_client.clone(), shell_env_loaded_rx, node_settings_rx);
-
-            let mut languages = LanguageRegistry::new(cx.background_executor().clone());
-            languages.set_language_server_download_dir(paths::languages_dir().clone());
-            let languages = Arc::new(languages);
-
-            HeadlessProject::new(
-                HeadlessAppState {
-                    session: session.clone(),
-                    fs,
-                    http_client,
-                    node_runtime,
-                    languages,
-                    extension_host_proxy,
-                    startup_time,
-                },
-                true,
-                cx,
+                .expect("Could not start HTTP client"),
             )
-        });
+        };
+
+        let node_runtime =
+            NodeRuntime::new(http_client.clone(), shell_env_loaded_rx, node_settings_rx);
+
+        let start_project = move |session: AnyProtoClient, cx: &mut App| {
+            let project = cx.new(|cx| {
+                let mut languages = LanguageRegistry::new(cx.background_executor().clone());
+                languages.set_language_server_download_dir(paths::languages_dir().clone());
+                let languages = Arc::new(languages);
+
+                HeadlessProject::new(
+                    HeadlessAppState {
+                        session: session.clone(),
+                        fs: fs.clone(),
+                        http_client: http_client.clone(),
+                        node_runtime: node_runtime.clone(),
+                        languages,
+                        extension_host_proxy: extension_host_proxy.clone(),
+                        startup_time,
+                    },
+                    true,
+                    cx,
+                )
+            });
+
+            handle_crash_files_requests(&project, &session);
 
-        handle_crash_files_requests(&project, &session);
+            mem::forget(project);
+        };
+
+        for session in sessions {
+            start_project(session, cx);
+        }
+        cx.spawn(async move |cx| {
+            while let Some(connections_rx) = sessions_rx.next().await {
+                cx.update(|cx| {
+                    let session = start_server(
+                        connections_rx,
+                        idle_timeout,
+                        log_rx.clone(),
+                        cx,
+                        is_wsl_interop,
+                    );
+                    session_clients.borrow_mut().push(session.clone());
+                    start_project(session, cx);
+                });
+            }
+        })
+        .detach();
 
         cx.background_spawn(async move {
             cleanup_old_binaries_wsl();
             cleanup_old_binaries()
         })
         .detach();
-
-        mem::forget(project);
     };
     // We do not reuse any of the state after unwinding, so we don't run risk of observing broken invariants.
     let app = std::panic::AssertUnwindSafe(app);
@@ -1051,7 +1426,7 @@ where
 }
 
 fn initialize_settings(
-    session: AnyProtoClient,
+    sessions: Rc<RefCell<Vec<AnyProtoClient>>>,
     fs: Arc<dyn Fs>,
     cx: &mut App,
 ) -> watch::Receiver<Option<NodeBinaryOptions>> {
@@ -1063,24 +1438,28 @@ fn initialize_settings(
             if let Some(e) = err {
                 log::info!("Server settings failed to change: {}", e);
 
-                session
-                    .send(proto::Toast {
-                        project_id: REMOTE_SERVER_PROJECT_ID,
-                        notification_id: "server-settings-failed".to_string(),
-                        message: format!(
-                            "Error in settings on remote host {:?}: {}",
-                            paths::settings_file(),
-                            e
-                        ),
-                    })
-                    .log_err();
+                for session in sessions.borrow().iter() {
+                    session
+                        .send(proto::Toast {
+                            project_id: REMOTE_SERVER_PROJECT_ID,
+                            notification_id: "server-settings-failed".to_string(),
+                            message: format!(
+                                "Error in settings on remote host {:?}: {}",
+                                paths::settings_file(),
+                                e
+                            ),
+                        })
+                        .log_err();
+                }
             } else {
-                session
-                    .send(proto::HideToast {
-                        project_id: REMOTE_SERVER_PROJECT_ID,
-                        notification_id: "server-settings-failed".to_string(),
-                    })
-                    .log_err();
+                for session in sessions.borrow().iter() {
+                    session
+                        .send(proto::HideToast {
+                            project_id: REMOTE_SERVER_PROJECT_ID,
+                            notification_id: "server-settings-failed".to_string(),
+                        })
+                        .log_err();
+                }
             }
         }
     });
@@ -1150,7 +1529,7 @@ pub fn handle_settings_file_changes(
     .detach();
 }
 
-fn read_proxy_settings(cx: &mut Context<HeadlessProject>) -> Option<Url> {
+fn read_proxy_settings(cx: &App) -> Option<Url> {
     let proxy_str = ProxySettings::get_global(cx).proxy.to_owned();
 
     proxy_str
@@ -1219,3 +1598,88 @@ fn is_file_in_use(file_name: &OsStr) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn connect(
+        server: &SocketServer,
+        port: u16,
+        token: &str,
+        identifier: &str,
+        reconnect: bool,
+    ) -> (SocketHandshakeResponse, Result<Option<String>>) {
+        let client = async {
+            let mut stream = smol::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .unwrap();
+            let mut buffer = Vec::new();
+            let handshake = SocketHandshake {
+                token: token.to_string(),
+                identifier: identifier.to_string(),
+                reconnect,
+            };
+            write_json_message(&mut stream, &mut buffer, &handshake)
+                .await
+                .unwrap();
+            read_json_message(&mut stream, &mut buffer, MAX_SOCKET_HANDSHAKE_LEN)
+                .await
+                .unwrap()
+        };
+        let accept = async {
+            let (input, output, _) = server.accept().await.unwrap();
+            server.authenticate(input, output).await
+        };
+        futures::join!(client, accept)
+    }
+
+    #[test]
+    fn test_socket_server_sessions() {
+        smol::block_on(async {
+            let (sessions_tx, mut sessions_rx) = mpsc::unbounded();
+            let address = SocketAddress::Tcp {
+                host: "127.0.0.1".into(),
+                port: 0,
+            };
+            let server = SocketServer::bind(&address, "secret".into(), sessions_tx)
+                .await
+                .unwrap();
+            let SocketListener::Tcp(listener) = &server.listener else {
+                unreachable!()
+            };
+            let port = listener.local_addr().unwrap().port();
+
+            // Two clients on the same listener each get a session of their own.
+            let (response, identifier) = connect(&server, port, "secret", "first", false).await;
+            assert_eq!(response, SocketHandshakeResponse::Accepted);
+            assert_eq!(identifier.unwrap().as_deref(), Some("first"));
+            let (response, identifier) = connect(&server, port, "secret", "second", false).await;
+            assert_eq!(response, SocketHandshakeResponse::Accepted);
+            assert_eq!(identifier.unwrap().as_deref(), Some("second"));
+
+            let mut first_rx = sessions_rx.try_next().unwrap().unwrap();
+            let mut second_rx = sessions_rx.try_next().unwrap().unwrap();
+            assert!(first_rx.try_next().unwrap().is_some());
+            assert!(second_rx.try_next().unwrap().is_some());
+
+            // A reconnect joins the existing session with the same identifier.
+            let (response, identifier) = connect(&server, port, "secret", "first", true).await;
+            assert_eq!(response, SocketHandshakeResponse::Accepted);
+            assert_eq!(identifier.unwrap().as_deref(), Some("first"));
+            assert!(sessions_rx.try_next().is_err());
+            assert!(first_rx.try_next().unwrap().is_some());
+            assert!(second_rx.try_next().is_err());
+
+            let (response, identifier) = connect(&server, port, "secret", "third", true).await;
+            assert_eq!(response, SocketHandshakeResponse::ServerNotRunning);
+            assert_eq!(identifier.unwrap(), None);
+
+            let (response, identifier) = connect(&server, port, "wrong", "first", false).await;
+            assert_eq!(response, SocketHandshakeResponse::InvalidToken);
+            assert!(identifier.is_err());
+            assert!(sessions_rx.try_next().is_err());
+            assert!(first_rx.try_next().is_err());
+        });
+    }
+}
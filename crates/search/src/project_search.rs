@@ -1,7 +1,8 @@
 use crate::{
     BufferSearchBar, FocusSearch, HighlightKey, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll,
     ReplaceNext, SearchOption, SearchOptions, SearchSource, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleSemantic,
+    ToggleWholeWord,
     buffer_search::Deploy,
     search_bar::{
         ActionButtonState, alignment_element, input_base_styles, render_action_button,
@@ -25,13 +26,14 @@ use gpui::{
     SharedString, Styled, Subscription, Task, UpdateGlobal, WeakEntity, Window, actions, div,
 };
 use itertools::Itertools;
-use language::{Buffer, Language};
+use language::{Bias, Buffer, Language};
 use menu::Confirm;
 use project::{
     Project, ProjectPath, SearchResults,
     search::{SearchInputKind, SearchQuery},
     search_history::SearchHistoryCursor,
 };
+use semantic_index::{ProjectIndex, SemanticDb, SemanticIndexSettings};
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
@@ -68,6 +70,9 @@ actions!(
     ]
 );
 
+/// The number of chunks shown by a semantic search.
+const SEMANTIC_SEARCH_LIMIT: usize = 50;
+
 fn split_glob_patterns(text: &str) -> Vec<&str> {
     let mut patterns = Vec::new();
     let mut pattern_start = 0;
@@ -129,6 +134,12 @@ pub fn init(cx: &mut App) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, window, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, window, cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSemantic, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::SEMANTIC, window, cx);
+            },
+        );
         register_workspace_action(
             workspace,
             move |search_bar, action: &ToggleReplace, window, cx| {
@@ -236,6 +247,8 @@ pub struct ProjectSearch {
     search_id: usize,
     no_results: Option<bool>,
     limit_reached: bool,
+    /// Whether the matches are chunks ranked by the semantic index, rather than occurrences of the query.
+    semantic_results: bool,
     search_history_cursor: SearchHistoryCursor,
     search_included_history_cursor: SearchHistoryCursor,
     search_excluded_history_cursor: SearchHistoryCursor,
@@ -294,6 +307,7 @@ impl ProjectSearch {
             search_id: 0,
             no_results: None,
             limit_reached: false,
+            semantic_results: false,
             search_history_cursor: Default::default(),
             search_included_history_cursor: Default::default(),
             search_excluded_history_cursor: Default::default(),
@@ -313,6 +327,7 @@ impl ProjectSearch {
             search_id: self.search_id,
             no_results: self.no_results,
             limit_reached: self.limit_reached,
+            semantic_results: self.semantic_results,
             search_history_cursor: self.search_history_cursor.clone(),
             search_included_history_cursor: self.search_included_history_cursor.clone(),
             search_excluded_history_cursor: self.search_excluded_history_cursor.clone(),
@@ -333,8 +348,8 @@ impl ProjectSearch {
         }
     }
 
-    fn search(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
-        let search = self.project.update(cx, |project, cx| {
+    fn add_to_search_history(&mut self, query: &SearchQuery, cx: &mut Context<Self>) {
+        self.project.update(cx, |project, _| {
             project
                 .search_history_mut(SearchInputKind::Query)
                 .add(&mut self.search_history_cursor, query.as_str().to_string());
@@ -350,11 +365,18 @@ impl ProjectSearch {
                     .search_history_mut(SearchInputKind::Exclude)
                     .add(&mut self.search_excluded_history_cursor, excluded);
             }
-            project.search(query.clone(), cx)
         });
+    }
+
+    fn search(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
+        self.add_to_search_history(&query, cx);
+        let search = self
+            .project
+            .update(cx, |project, cx| project.search(query.clone(), cx));
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
         self.active_query = Some(query);
+        self.semantic_results = false;
         self.match_ranges.clear();
         self.pending_search = Some(cx.spawn(async move |project_search, cx| {
             let SearchResults { rx, _task_handle } = search;
@@ -438,6 +460,127 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+
+    /// Shows the chunks of code that the semantic index ranks as most similar to the query.
+    fn semantic_search(
+        &mut self,
+        query: SearchQuery,
+        index: Entity<ProjectIndex>,
+        cx: &mut Context<Self>,
+    ) {
+        self.add_to_search_history(&query, cx);
+        let search = index
+            .read(cx)
+            .search(query.as_str().to_string(), SEMANTIC_SEARCH_LIMIT, cx);
+        self.last_search_query_text = Some(query.as_str().to_string());
+        self.search_id += 1;
+        self.active_query = Some(query.clone());
+        self.semantic_results = true;
+        self.match_ranges.clear();
+        self.pending_search = Some(cx.spawn(async move |project_search, cx| {
+            let project = project_search
+                .update(cx, |project_search, cx| {
+                    project_search.match_ranges.clear();
+                    project_search
+                        .excerpts
+                        .update(cx, |excerpts, cx| excerpts.clear(cx));
+                    project_search.no_results = Some(true);
+                    project_search.limit_reached = false;
+                    project_search.project.clone()
+                })
+                .ok()?;
+
+            let results = search.await.log_err().unwrap_or_default();
+            // Group the chunks by file, keeping the files in order of their best chunk.
+            let mut ranges_by_path = Vec::<(ProjectPath, Vec<Range<usize>>)>::new();
+            project.read_with(cx, |project, cx| {
+                for result in results {
+                    let project_path = ProjectPath {
+                        worktree_id: result.worktree_id,
+                        path: result.path,
+                    };
+                    if query.filters_path() {
+                        let matches_filters = if query.match_full_paths() {
+                            project
+                                .worktree_for_id(project_path.worktree_id, cx)
+                                .is_some_and(|worktree| {
+                                    query.match_path(
+                                        &worktree.read(cx).root_name().join(&project_path.path),
+                                    )
+                                })
+                        } else {
+                            query.match_path(&project_path.path)
+                        };
+                        if !matches_filters {
+                            continue;
+                        }
+                    }
+                    match ranges_by_path
+                        .iter_mut()
+                        .find(|(path, _)| *path == project_path)
+                    {
+                        Some((_, ranges)) => ranges.push(result.range),
+                        None => ranges_by_path.push((project_path, vec![result.range])),
+                    }
+                }
+            });
+
+            for (rank, (project_path, mut ranges)) in ranges_by_path.into_iter().enumerate() {
+                let path = project_path.path.clone();
+                let Some(buffer) = project
+                    .update(cx, |project, cx| project.open_buffer(project_path, cx))
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+                ranges.sort_by_key(|range| range.start);
+                let new_ranges = project_search
+                    .update(cx, |project_search, cx| {
+                        // The file may have changed since it was indexed.
+                        let snapshot = buffer.read(cx).snapshot();
+                        let ranges = ranges
+                            .into_iter()
+                            .map(|range| {
+                                let start = snapshot.clip_offset(range.start, Bias::Left);
+                                let end = snapshot.clip_offset(range.end, Bias::Right);
+                                snapshot.anchor_after(start)..snapshot.anchor_before(end)
+                            })
+                            .collect();
+                        project_search.excerpts.update(cx, |excerpts, cx| {
+                            excerpts.set_anchored_excerpts_for_path(
+                                PathKey::with_sort_prefix(rank as u64, path),
+                                buffer,
+                                ranges,
+                                multibuffer_context_lines(cx),
+                                cx,
+                            )
+                        })
+                    })
+                    .ok()?;
+                let new_ranges = new_ranges.await;
+                project_search
+                    .update(cx, |project_search, cx| {
+                        project_search.match_ranges.extend(new_ranges);
+                        cx.notify();
+                    })
+                    .ok()?;
+            }
+
+            project_search
+                .update(cx, |project_search, cx| {
+                    if !project_search.match_ranges.is_empty() {
+                        project_search.no_results = Some(false);
+                    }
+                    project_search.pending_search.take();
+                    cx.notify();
+                })
+                .ok()?;
+
+            None
+        }));
+        cx.notify();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -735,6 +878,9 @@ impl ProjectSearchView {
     }
 
     fn replace_next(&mut self, _: &ReplaceNext, window: &mut Window, cx: &mut Context<Self>) {
+        if self.entity.read(cx).semantic_results {
+            return;
+        }
         if let Some(last_search_query_text) = &self.entity.read(cx).last_search_query_text
             && self.query_editor.read(cx).text(cx) != *last_search_query_text
         {
@@ -763,6 +909,9 @@ impl ProjectSearchView {
         }
     }
     fn replace_all(&mut self, _: &ReplaceAll, window: &mut Window, cx: &mut Context<Self>) {
+        if self.entity.read(cx).semantic_results {
+            return;
+        }
         if let Some(last_search_query_text) = &self.entity.read(cx).last_search_query_text
             && self.query_editor.read(cx).text(cx) != *last_search_query_text
         {
@@ -1228,9 +1377,18 @@ impl ProjectSearchView {
         } else {
             None
         };
-        if let Some(query) = self.build_search_query(cx, open_buffers) {
-            self.entity.update(cx, |model, cx| model.search(query, cx));
+        let Some(query) = self.build_search_query(cx, open_buffers) else {
+            return;
+        };
+        if self.search_options.contains(SearchOptions::SEMANTIC) {
+            let project = self.entity.read(cx).project.clone();
+            if let Some(index) = SemanticDb::project_index(&project, cx) {
+                self.entity
+                    .update(cx, |model, cx| model.semantic_search(query, index, cx));
+                return;
+            }
         }
+        self.entity.update(cx, |model, cx| model.search(query, cx));
     }
 
     pub fn search_query_text(&self, cx: &App) -> String {
@@ -2104,7 +2262,14 @@ impl Render for ProjectSearchBar {
                         search.search_options,
                         SearchSource::Project(cx),
                         focus_handle.clone(),
-                    )),
+                    ))
+                    .when(SemanticIndexSettings::get_global(cx).enabled, |this| {
+                        this.child(SearchOption::Semantic.as_button(
+                            search.search_options,
+                            SearchSource::Project(cx),
+                            focus_handle.clone(),
+                        ))
+                    }),
             );
 
         let matches_column = h_flex()
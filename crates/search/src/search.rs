@@ -37,6 +37,8 @@ actions!(
         ToggleCaseSensitive,
         /// Toggles regular expression mode.
         ToggleRegex,
+        /// Toggles searching the project by meaning, using the semantic index.
+        ToggleSemantic,
         /// Toggles the replace interface.
         ToggleReplace,
         /// Toggles searching within selection only.
@@ -71,6 +73,8 @@ bitflags! {
         const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u8;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 1 << SearchOption::Backwards as u8;
+        /// If set, project search ranks code by similarity to the query instead of matching text
+        const SEMANTIC = 1 << SearchOption::Semantic as u8;
     }
 }
 
@@ -83,6 +87,7 @@ pub enum SearchOption {
     Regex,
     OneMatchPerLine,
     Backwards,
+    Semantic,
 }
 
 pub(crate) enum SearchSource<'a, 'b> {
@@ -103,6 +108,7 @@ impl SearchOption {
             SearchOption::Regex => "Use Regular Expressions",
             SearchOption::OneMatchPerLine => "One Match Per Line",
             SearchOption::Backwards => "Search Backwards",
+            SearchOption::Semantic => "Search by Meaning",
         }
     }
 
@@ -112,6 +118,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => ui::IconName::CaseSensitive,
             SearchOption::IncludeIgnored => ui::IconName::Sliders,
             SearchOption::Regex => ui::IconName::Regex,
+            SearchOption::Semantic => ui::IconName::Sparkle,
             _ => panic!("{self:?} is not a named SearchOption"),
         }
     }
@@ -122,6 +129,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => &ToggleCaseSensitive,
             SearchOption::IncludeIgnored => &ToggleIncludeIgnored,
             SearchOption::Regex => &ToggleRegex,
+            SearchOption::Semantic => &ToggleSemantic,
             _ => panic!("{self:?} is not a toggle action"),
         }
     }
@@ -0,0 +1,152 @@
+use language::{BufferSnapshot, OffsetRangeExt as _};
+use sha2::{Digest as _, Sha256};
+use std::ops::Range;
+
+/// Chunks are kept under this size so they fit comfortably in the context of
+/// local embedding models.
+pub const MAX_CHUNK_SIZE: usize = 2048;
+/// Outline items nested deeper than this don't start new chunks.
+const MAX_BOUNDARY_DEPTH: usize = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    pub range: Range<usize>,
+    pub digest: [u8; 32],
+}
+
+/// Splits a file into chunks along the boundaries of its outline, so that a
+/// chunk usually holds whole items, such as functions or type definitions.
+///
+/// Consecutive small items are merged into a single chunk, and items larger
+/// than [`MAX_CHUNK_SIZE`] are split between lines.
+pub fn chunk_snapshot(snapshot: &BufferSnapshot) -> Vec<Chunk> {
+    let text = snapshot.text();
+    let boundaries = snapshot
+        .outline(None)
+        .items
+        .into_iter()
+        .filter(|item| item.depth <= MAX_BOUNDARY_DEPTH)
+        .map(|item| {
+            // Start chunks at the beginning of the line, so they include
+            // indentation and any annotations preceding the item's name.
+            let start = item.range.to_offset(snapshot).start;
+            text[..start].rfind('\n').map_or(0, |ix| ix + 1)
+        })
+        .collect::<Vec<_>>();
+    chunk_text(&text, &boundaries)
+}
+
+/// Splits text into chunks, preferring to break at the given offsets.
+pub fn chunk_text(text: &str, boundaries: &[usize]) -> Vec<Chunk> {
+    let mut boundaries = boundaries
+        .iter()
+        .copied()
+        .filter(|&offset| offset > 0 && offset < text.len())
+        .collect::<Vec<_>>();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+    boundaries.push(text.len());
+
+    let mut ranges = Vec::new();
+    let mut segment_start = 0;
+    let mut chunk_start = 0;
+    for segment_end in boundaries {
+        if segment_end - chunk_start <= MAX_CHUNK_SIZE {
+            segment_start = segment_end;
+            continue;
+        }
+        if segment_start > chunk_start {
+            ranges.push(chunk_start..segment_start);
+            chunk_start = segment_start;
+        }
+        while segment_end - chunk_start > MAX_CHUNK_SIZE {
+            let end = split_point(text, chunk_start, chunk_start + MAX_CHUNK_SIZE);
+            ranges.push(chunk_start..end);
+            chunk_start = end;
+        }
+        segment_start = segment_end;
+    }
+    if chunk_start < text.len() {
+        ranges.push(chunk_start..text.len());
+    }
+
+    ranges
+        .into_iter()
+        .filter(|range| !text[range.clone()].trim().is_empty())
+        .map(|range| Chunk {
+            digest: Sha256::digest(text[range.clone()].as_bytes()).into(),
+            range,
+        })
+        .collect()
+}
+
+/// Returns the offset of the last line break before `max_end`, falling back
+/// to the closest character boundary for very long lines.
+fn split_point(text: &str, start: usize, max_end: usize) -> usize {
+    if let Some(newline_ix) = text[start..max_end].rfind('\n')
+        && newline_ix > 0
+    {
+        return start + newline_ix + 1;
+    }
+    let mut end = max_end;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use language::Buffer;
+
+    #[gpui::test]
+    fn test_chunk_along_outline(cx: &mut TestAppContext) {
+        let text = "use std::fmt;\n\nfn one() {\n    1\n}\n\nfn two() {\n    2\n}\n";
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(language::rust_lang(), cx));
+        cx.run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        // Small items are merged into a single chunk.
+        let chunks = chunk_snapshot(&snapshot);
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.range.clone())
+                .collect::<Vec<_>>(),
+            vec![0..text.len()]
+        );
+
+        // Large items are split at their outline boundaries.
+        let body = "    let x = 1;\n".repeat(200);
+        let text = format!("fn one() {{\n{body}}}\n\nfn two() {{\n{body}}}\n");
+        let buffer = cx.new(|cx| Buffer::local(&text, cx).with_language(language::rust_lang(), cx));
+        cx.run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let chunks = chunk_snapshot(&snapshot);
+        let two_start = text.find("fn two").unwrap();
+        assert!(chunks.iter().any(|chunk| chunk.range.start == two_start));
+        assert!(
+            chunks
+                .iter()
+                .all(|chunk| chunk.range.len() <= MAX_CHUNK_SIZE)
+        );
+        assert_eq!(chunks.first().unwrap().range.start, 0);
+        assert_eq!(chunks.last().unwrap().range.end, text.len());
+    }
+
+    #[test]
+    fn test_chunk_long_lines() {
+        let text = format!("a{}", "é".repeat(1100));
+        let chunks = chunk_text(&text, &[]);
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.range.clone())
+                .collect::<Vec<_>>(),
+            vec![0..MAX_CHUNK_SIZE - 1, MAX_CHUNK_SIZE - 1..text.len()]
+        );
+        assert_ne!(chunks[0].digest, chunks[1].digest);
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+use std::future::Future;
+
+/// A file's row in the index: its path, the model it was embedded with, and
+/// its modification time when it was indexed.
+pub(crate) type IndexedFileRow = (String, String, Option<u64>, Option<u32>);
+
+/// A chunk's row in the index: its file's path, byte range, digest and embedding.
+pub(crate) type IndexedChunkRow = (String, usize, usize, Vec<u8>, Vec<u8>);
+
+pub struct SemanticIndexDb(ThreadSafeConnection);
+
+impl Domain for SemanticIndexDb {
+    const NAME: &str = stringify!(SemanticIndexDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE semantic_index_files(
+            worktree_path TEXT NOT NULL,
+            path TEXT NOT NULL,
+            model TEXT NOT NULL,
+            mtime_seconds INTEGER,
+            mtime_nanos INTEGER,
+            PRIMARY KEY(worktree_path, path)
+        ) STRICT;
+
+        CREATE TABLE semantic_index_chunks(
+            worktree_path TEXT NOT NULL,
+            path TEXT NOT NULL,
+            start_offset INTEGER NOT NULL,
+            end_offset INTEGER NOT NULL,
+            digest BLOB NOT NULL,
+            embedding BLOB NOT NULL,
+            FOREIGN KEY(worktree_path, path)
+                REFERENCES semantic_index_files(worktree_path, path)
+                ON DELETE CASCADE
+        ) STRICT;
+
+        CREATE INDEX semantic_index_chunks_by_file
+            ON semantic_index_chunks(worktree_path, path);
+    )];
+}
+
+db::static_connection!(SEMANTIC_INDEX_DB, SemanticIndexDb, []);
+
+impl SemanticIndexDb {
+    query! {
+        pub(crate) fn indexed_files(worktree_path: &str) -> Result<Vec<IndexedFileRow>> {
+            SELECT path, model, mtime_seconds, mtime_nanos
+            FROM semantic_index_files
+            WHERE worktree_path = (?)
+        }
+    }
+
+    query! {
+        pub(crate) fn indexed_chunks(worktree_path: &str) -> Result<Vec<IndexedChunkRow>> {
+            SELECT path, start_offset, end_offset, digest, embedding
+            FROM semantic_index_chunks
+            WHERE worktree_path = (?)
+            ORDER BY path, start_offset
+        }
+    }
+
+    /// Replaces a file's chunks. The write is queued when this is called, so
+    /// writes for the same file are applied in the order they were made.
+    pub(crate) fn save_file(
+        &self,
+        worktree_path: String,
+        path: String,
+        model: String,
+        mtime: Option<(u64, u32)>,
+        chunks: Vec<(usize, usize, Vec<u8>, Vec<u8>)>,
+    ) -> impl Future<Output = Result<()>> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_semantic_index_file", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM semantic_index_files WHERE worktree_path = (?) AND path = (?)
+                ))?((worktree_path.as_str(), path.as_str()))?;
+                conn.exec_bound(sql!(
+                    INSERT INTO semantic_index_files(worktree_path, path, model, mtime_seconds, mtime_nanos)
+                    VALUES ((?), (?), (?), (?), (?))
+                ))?((
+                    worktree_path.as_str(),
+                    path.as_str(),
+                    model.as_str(),
+                    mtime.map(|(seconds, _)| seconds),
+                    mtime.map(|(_, nanos)| nanos),
+                ))?;
+                let mut insert_chunk = conn.exec_bound(sql!(
+                    INSERT INTO semantic_index_chunks(worktree_path, path, start_offset, end_offset, digest, embedding)
+                    VALUES ((?), (?), (?), (?), (?), (?))
+                ))?;
+                for (start, end, digest, embedding) in chunks {
+                    insert_chunk((
+                        worktree_path.as_str(),
+                        path.as_str(),
+                        start,
+                        end,
+                        digest,
+                        embedding,
+                    ))?;
+                }
+                Ok(())
+            })
+        })
+    }
+
+    pub(crate) fn delete_file(
+        &self,
+        worktree_path: String,
+        path: String,
+    ) -> impl Future<Output = Result<()>> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM semantic_index_files WHERE worktree_path = (?) AND path = (?)
+            ))?((worktree_path.as_str(), path.as_str()))
+        })
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::{Context as _, Result};
+use futures::{FutureExt as _, future::BoxFuture};
+use http_client::HttpClient;
+use std::sync::Arc;
+
+/// A normalized embedding vector, so that similarity is a dot product.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Embedding(Arc<[f32]>);
+
+impl Embedding {
+    pub fn new(mut values: Vec<f32>) -> Self {
+        let norm = values.iter().map(|value| value * value).sum::<f32>().sqrt();
+        if norm > 0. {
+            for value in &mut values {
+                *value /= norm;
+            }
+        }
+        Self(values.into())
+    }
+
+    /// Returns the cosine similarity with another embedding, between -1 and 1.
+    pub fn similarity(&self, other: &Embedding) -> f32 {
+        self.0.iter().zip(other.0.iter()).map(|(a, b)| a * b).sum()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        anyhow::ensure!(
+            bytes.len() % 4 == 0,
+            "embedding has an invalid length of {} bytes",
+            bytes.len()
+        );
+        Ok(Self(
+            bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect(),
+        ))
+    }
+}
+
+/// Computes embeddings for chunks of text.
+pub trait EmbeddingProvider: Send + Sync {
+    /// An identifier for the model, stored alongside the embeddings so that
+    /// switching models re-indexes the project.
+    fn model_id(&self) -> &str;
+
+    /// The maximum number of texts to embed in a single request.
+    fn batch_size(&self) -> usize;
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>>;
+}
+
+pub struct OllamaEmbeddingProvider {
+    client: Arc<dyn HttpClient>,
+    api_url: String,
+    model: String,
+    model_id: String,
+}
+
+impl OllamaEmbeddingProvider {
+    pub fn new(client: Arc<dyn HttpClient>, api_url: String, model: String) -> Self {
+        Self {
+            client,
+            api_url,
+            model_id: format!("ollama/{model}"),
+            model,
+        }
+    }
+}
+
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn batch_size(&self) -> usize {
+        32
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        async move {
+            let response = ollama::embed(
+                self.client.as_ref(),
+                &self.api_url,
+                None,
+                ollama::EmbedRequest {
+                    model: self.model.clone(),
+                    input: texts.to_vec(),
+                },
+            )
+            .await?;
+            anyhow::ensure!(
+                response.embeddings.len() == texts.len(),
+                "Ollama returned {} embeddings for {} inputs",
+                response.embeddings.len(),
+                texts.len()
+            );
+            Ok(response
+                .embeddings
+                .into_iter()
+                .map(Embedding::new)
+                .collect())
+        }
+        .boxed()
+    }
+}
+
+pub struct LmStudioEmbeddingProvider {
+    client: Arc<dyn HttpClient>,
+    api_url: String,
+    model: String,
+    model_id: String,
+}
+
+impl LmStudioEmbeddingProvider {
+    pub fn new(client: Arc<dyn HttpClient>, api_url: String, model: String) -> Self {
+        Self {
+            client,
+            api_url,
+            model_id: format!("lmstudio/{model}"),
+            model,
+        }
+    }
+}
+
+impl EmbeddingProvider for LmStudioEmbeddingProvider {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn batch_size(&self) -> usize {
+        32
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        async move {
+            let mut response = lmstudio::embeddings(
+                self.client.as_ref(),
+                &self.api_url,
+                lmstudio::EmbeddingsRequest {
+                    model: self.model.clone(),
+                    input: texts.to_vec(),
+                },
+            )
+            .await?;
+            response.data.sort_by_key(|data| data.index);
+            anyhow::ensure!(
+                response.data.len() == texts.len(),
+                "LM Studio returned {} embeddings for {} inputs",
+                response.data.len(),
+                texts.len()
+            );
+            Ok(response
+                .data
+                .into_iter()
+                .map(|data| Embedding::new(data.embedding))
+                .collect())
+        }
+        .boxed()
+    }
+}
+
+/// An embedding provider for tests, which embeds text as a bag of words so
+/// that texts sharing words are similar.
+#[cfg(any(test, feature = "test-support"))]
+pub struct FakeEmbeddingProvider;
+
+#[cfg(any(test, feature = "test-support"))]
+impl EmbeddingProvider for FakeEmbeddingProvider {
+    fn model_id(&self) -> &str {
+        "fake"
+    }
+
+    fn batch_size(&self) -> usize {
+        4
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        use std::hash::{DefaultHasher, Hash as _, Hasher as _};
+
+        let embeddings = texts
+            .iter()
+            .map(|text| {
+                let mut values = vec![0.; 64];
+                for word in text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                {
+                    let mut hasher = DefaultHasher::new();
+                    word.to_lowercase().hash(&mut hasher);
+                    values[hasher.finish() as usize % 64] += 1.;
+                }
+                Embedding::new(values)
+            })
+            .collect();
+        futures::future::ready(Ok(embeddings)).boxed()
+    }
+}
+
+pub(crate) async fn embed_in_batches(
+    provider: &dyn EmbeddingProvider,
+    texts: &[String],
+) -> Result<Vec<Embedding>> {
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(provider.batch_size().max(1)) {
+        embeddings.extend(
+            provider
+                .embed(batch)
+                .await
+                .with_context(|| format!("failed to embed with {}", provider.model_id()))?,
+        );
+    }
+    Ok(embeddings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedding_round_trip() {
+        let embedding = Embedding::new(vec![3., 4.]);
+        assert!((embedding.similarity(&embedding) - 1.).abs() < 1e-6);
+        assert_eq!(
+            Embedding::from_bytes(&embedding.to_bytes()).unwrap(),
+            embedding
+        );
+        assert!(Embedding::from_bytes(&[0, 1, 2]).is_err());
+    }
+}
@@ -0,0 +1,592 @@
+use crate::{
+    chunking::chunk_snapshot,
+    db::SEMANTIC_INDEX_DB,
+    embedding::{Embedding, EmbeddingProvider, embed_in_batches},
+};
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use fs::MTime;
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::{App, AppContext as _, AsyncApp, Context, Entity, Subscription, Task, WeakEntity};
+use language::{Buffer, LanguageRegistry};
+use project::{Entry, PathChange, Project, Worktree, WorktreeId};
+use std::{ops::Range, path::Path, sync::Arc};
+use text::Rope;
+use util::{ResultExt as _, rel_path::RelPath};
+
+/// Files larger than this are not indexed, since they are usually generated.
+const MAX_INDEXED_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// Embeddings stored by a previous session are being loaded.
+    Loading,
+    /// Changed files are being embedded.
+    Indexing {
+        remaining_files: usize,
+    },
+    Idle,
+}
+
+/// A chunk of a file that matched a semantic search.
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub worktree_id: WorktreeId,
+    pub path: Arc<RelPath>,
+    /// The chunk's byte range in the file, as of when it was indexed.
+    pub range: Range<usize>,
+    /// The similarity between the query and the chunk, between -1 and 1.
+    pub score: f32,
+}
+
+struct IndexedFile {
+    mtime: Option<MTime>,
+    chunks: Vec<IndexedChunk>,
+}
+
+struct IndexedChunk {
+    range: Range<usize>,
+    digest: [u8; 32],
+    embedding: Embedding,
+}
+
+struct WorktreeIndex {
+    abs_path: Arc<Path>,
+    files: HashMap<Arc<RelPath>, Arc<IndexedFile>>,
+    loaded: bool,
+    queued_paths: HashSet<Arc<RelPath>>,
+    changed_paths: mpsc::UnboundedSender<Arc<RelPath>>,
+    _indexing: Task<()>,
+}
+
+/// An embeddings index over the files of a project's local worktrees.
+///
+/// Each file is split into chunks along its outline, and each chunk is
+/// embedded with the configured provider. Embeddings are persisted, so that
+/// reopening a project only re-embeds the files that changed in the meantime,
+/// and the index is kept up to date as the worktrees report changes.
+pub struct ProjectIndex {
+    project: WeakEntity<Project>,
+    languages: Arc<LanguageRegistry>,
+    provider: Arc<dyn EmbeddingProvider>,
+    worktrees: HashMap<WorktreeId, WorktreeIndex>,
+    _subscription: Subscription,
+}
+
+impl ProjectIndex {
+    pub fn new(
+        project: Entity<Project>,
+        provider: Arc<dyn EmbeddingProvider>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let languages = project.read(cx).languages().clone();
+        let subscription = cx.subscribe(&project, Self::handle_project_event);
+        let mut this = Self {
+            project: project.downgrade(),
+            languages,
+            provider,
+            worktrees: HashMap::default(),
+            _subscription: subscription,
+        };
+        for worktree in project.read(cx).visible_worktrees(cx).collect::<Vec<_>>() {
+            this.add_worktree(worktree, cx);
+        }
+        this
+    }
+
+    pub fn provider(&self) -> &Arc<dyn EmbeddingProvider> {
+        &self.provider
+    }
+
+    pub fn status(&self) -> Status {
+        if self.worktrees.values().any(|worktree| !worktree.loaded) {
+            return Status::Loading;
+        }
+        let remaining_files = self
+            .worktrees
+            .values()
+            .map(|worktree| worktree.queued_paths.len())
+            .sum();
+        if remaining_files > 0 {
+            Status::Indexing { remaining_files }
+        } else {
+            Status::Idle
+        }
+    }
+
+    /// Returns the chunks most similar to the query, best match first.
+    pub fn search(&self, query: String, limit: usize, cx: &App) -> Task<Result<Vec<SearchResult>>> {
+        let provider = self.provider.clone();
+        let files = self
+            .worktrees
+            .iter()
+            .flat_map(|(worktree_id, worktree)| {
+                worktree
+                    .files
+                    .iter()
+                    .map(|(path, file)| (*worktree_id, path.clone(), file.clone()))
+            })
+            .collect::<Vec<_>>();
+        cx.background_spawn(async move {
+            let query_embedding = provider
+                .embed(&[query])
+                .await?
+                .pop()
+                .context("no embedding returned for the query")?;
+
+            let mut results = files
+                .iter()
+                .flat_map(|(worktree_id, path, file)| {
+                    let query_embedding = &query_embedding;
+                    file.chunks.iter().map(move |chunk| SearchResult {
+                        worktree_id: *worktree_id,
+                        path: path.clone(),
+                        range: chunk.range.clone(),
+                        score: chunk.embedding.similarity(query_embedding),
+                    })
+                })
+                .collect::<Vec<_>>();
+            results.sort_by(|a, b| b.score.total_cmp(&a.score));
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+
+    fn handle_project_event(
+        &mut self,
+        project: Entity<Project>,
+        event: &project::Event,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            project::Event::WorktreeAdded(worktree_id) => {
+                if let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx) {
+                    self.add_worktree(worktree, cx);
+                }
+            }
+            project::Event::WorktreeRemoved(worktree_id) => {
+                self.worktrees.remove(worktree_id);
+                cx.notify();
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx) else {
+                    return;
+                };
+                let snapshot = worktree.read(cx).snapshot();
+                for (path, _, change) in changes.iter() {
+                    if *change == PathChange::Removed {
+                        self.remove_files(*worktree_id, path, cx);
+                    } else if snapshot
+                        .entry_for_path(path)
+                        .is_some_and(|entry| self.is_indexable(entry))
+                    {
+                        self.queue_path(*worktree_id, path.clone(), cx);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn is_indexable(&self, entry: &Entry) -> bool {
+        entry.is_file()
+            && !entry.is_ignored
+            && !entry.is_external
+            && !entry.is_private
+            && entry.size <= MAX_INDEXED_FILE_SIZE
+            && self
+                .languages
+                .language_for_file_path(entry.path.as_std_path())
+                .is_some()
+    }
+
+    fn add_worktree(&mut self, worktree: Entity<Worktree>, cx: &mut Context<Self>) {
+        let (worktree_id, abs_path) = {
+            let worktree = worktree.read(cx);
+            if !worktree.is_local() || !worktree.is_visible() {
+                return;
+            }
+            (worktree.id(), worktree.abs_path())
+        };
+        if self.worktrees.contains_key(&worktree_id) {
+            return;
+        }
+
+        let (changed_paths_tx, changed_paths_rx) = mpsc::unbounded();
+        let indexing = cx.spawn({
+            let worktree = worktree.downgrade();
+            async move |this, cx| {
+                Self::index_worktree(this, worktree_id, worktree, changed_paths_rx, cx)
+                    .await
+                    .log_err();
+            }
+        });
+        self.worktrees.insert(
+            worktree_id,
+            WorktreeIndex {
+                abs_path,
+                files: HashMap::default(),
+                loaded: false,
+                queued_paths: HashSet::default(),
+                changed_paths: changed_paths_tx,
+                _indexing: indexing,
+            },
+        );
+        cx.notify();
+    }
+
+    fn queue_path(&mut self, worktree_id: WorktreeId, path: Arc<RelPath>, cx: &mut Context<Self>) {
+        let Some(worktree) = self.worktrees.get_mut(&worktree_id) else {
+            return;
+        };
+        if worktree.queued_paths.insert(path.clone()) {
+            worktree.changed_paths.unbounded_send(path).ok();
+            cx.notify();
+        }
+    }
+
+    /// Removes the file at the given path, or every file under it if it's a directory.
+    fn remove_files(&mut self, worktree_id: WorktreeId, path: &RelPath, cx: &mut Context<Self>) {
+        let Some(worktree) = self.worktrees.get_mut(&worktree_id) else {
+            return;
+        };
+        let removed_paths = worktree
+            .files
+            .keys()
+            .filter(|file_path| file_path.starts_with(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        for removed_path in removed_paths {
+            worktree.files.remove(&removed_path);
+            let delete = SEMANTIC_INDEX_DB.delete_file(
+                worktree.abs_path.to_string_lossy().into_owned(),
+                removed_path.as_unix_str().to_string(),
+            );
+            cx.background_spawn(async move { delete.await.log_err() })
+                .detach();
+        }
+        cx.notify();
+    }
+
+    async fn index_worktree(
+        this: WeakEntity<Self>,
+        worktree_id: WorktreeId,
+        worktree: WeakEntity<Worktree>,
+        mut changed_paths: mpsc::UnboundedReceiver<Arc<RelPath>>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let (abs_path, model_id) = this.read_with(cx, |this, _| {
+            let abs_path = this.worktrees.get(&worktree_id).map(|w| w.abs_path.clone());
+            (abs_path, this.provider.model_id().to_string())
+        })?;
+        let worktree_path = abs_path
+            .context("worktree is no longer indexed")?
+            .to_string_lossy()
+            .into_owned();
+
+        let files = cx
+            .background_spawn(async move { load_indexed_files(&worktree_path, &model_id) })
+            .await?;
+
+        this.update(cx, |this, cx| {
+            let Some(worktree) = worktree.upgrade() else {
+                return;
+            };
+            let snapshot = worktree.read(cx).snapshot();
+            let mut stale_paths = Vec::new();
+            let mut removed_paths = Vec::new();
+            for (path, file) in &files {
+                match snapshot.entry_for_path(path) {
+                    Some(entry) if this.is_indexable(entry) => {
+                        if entry.mtime != file.mtime {
+                            stale_paths.push(path.clone());
+                        }
+                    }
+                    _ => removed_paths.push(path.clone()),
+                }
+            }
+            let new_paths = snapshot
+                .files(false, 0)
+                .filter(|entry| !files.contains_key(&entry.path) && this.is_indexable(entry))
+                .map(|entry| entry.path.clone())
+                .collect::<Vec<_>>();
+
+            if let Some(worktree_index) = this.worktrees.get_mut(&worktree_id) {
+                worktree_index.files = files;
+                worktree_index.loaded = true;
+            }
+            for path in removed_paths {
+                this.remove_files(worktree_id, &path, cx);
+            }
+            for path in stale_paths.into_iter().chain(new_paths) {
+                this.queue_path(worktree_id, path, cx);
+            }
+            cx.notify();
+        })?;
+
+        while let Some(path) = changed_paths.next().await {
+            let Some(worktree) = worktree.upgrade() else {
+                break;
+            };
+            Self::index_file(&this, worktree_id, worktree, path, cx).await?;
+        }
+        Ok(())
+    }
+
+    /// Chunks and embeds a single file. Only failures to compute embeddings
+    /// are returned, since they would affect every other file as well.
+    async fn index_file(
+        this: &WeakEntity<Self>,
+        worktree_id: WorktreeId,
+        worktree: Entity<Worktree>,
+        path: Arc<RelPath>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let (entry, languages, provider, previous_file) = this.update(cx, |this, cx| {
+            let worktree_index = this.worktrees.get_mut(&worktree_id);
+            let previous_file = worktree_index.and_then(|worktree_index| {
+                worktree_index.queued_paths.remove(&path);
+                worktree_index.files.get(&path).cloned()
+            });
+            cx.notify();
+            let entry = worktree
+                .read(cx)
+                .entry_for_path(&path)
+                .filter(|entry| this.is_indexable(entry))
+                .cloned();
+            (
+                entry,
+                this.languages.clone(),
+                this.provider.clone(),
+                previous_file,
+            )
+        })?;
+        let Some(entry) = entry else {
+            this.update(cx, |this, cx| this.remove_files(worktree_id, &path, cx))?;
+            return Ok(());
+        };
+
+        let loaded_file = match worktree
+            .update(cx, |worktree, cx| worktree.load_file(&path, cx))
+            .await
+        {
+            Ok(loaded_file) => loaded_file,
+            Err(error) => {
+                log::warn!("failed to load {path:?} for semantic indexing: {error:#}");
+                return Ok(());
+            }
+        };
+        let language = languages
+            .load_language_for_file_path(path.as_std_path())
+            .await
+            .ok();
+        let snapshot = cx
+            .update(|cx| {
+                Buffer::build_snapshot(
+                    Rope::from(loaded_file.text.as_str()),
+                    language,
+                    Some(languages),
+                    cx,
+                )
+            })
+            .await;
+
+        let chunks = cx
+            .background_spawn({
+                let path = path.clone();
+                async move {
+                    let text = snapshot.text();
+                    chunk_snapshot(&snapshot)
+                        .into_iter()
+                        .map(|chunk| {
+                            // Include the path, since it often says a lot about the code.
+                            let embedding_text =
+                                format!("{}\n{}", path.as_unix_str(), &text[chunk.range.clone()]);
+                            (chunk, embedding_text)
+                        })
+                        .collect::<Vec<_>>()
+                }
+            })
+            .await;
+
+        // Reuse the embeddings of chunks that didn't change since the file was last indexed.
+        let previous_embeddings = previous_file
+            .iter()
+            .flat_map(|file| &file.chunks)
+            .map(|chunk| (chunk.digest, chunk.embedding.clone()))
+            .collect::<HashMap<_, _>>();
+        let texts_to_embed = chunks
+            .iter()
+            .filter(|(chunk, _)| !previous_embeddings.contains_key(&chunk.digest))
+            .map(|(_, text)| text.clone())
+            .collect::<Vec<_>>();
+        let mut new_embeddings = embed_in_batches(provider.as_ref(), &texts_to_embed)
+            .await?
+            .into_iter();
+
+        let mut indexed_chunks = Vec::with_capacity(chunks.len());
+        for (chunk, _) in chunks {
+            let embedding = match previous_embeddings.get(&chunk.digest) {
+                Some(embedding) => embedding.clone(),
+                None => new_embeddings
+                    .next()
+                    .context("embedding provider returned too few embeddings")?,
+            };
+            indexed_chunks.push(IndexedChunk {
+                range: chunk.range,
+                digest: chunk.digest,
+                embedding,
+            });
+        }
+
+        this.update(cx, |this, cx| {
+            let Some(worktree_index) = this.worktrees.get_mut(&worktree_id) else {
+                return;
+            };
+            let save = SEMANTIC_INDEX_DB.save_file(
+                worktree_index.abs_path.to_string_lossy().into_owned(),
+                path.as_unix_str().to_string(),
+                this.provider.model_id().to_string(),
+                entry
+                    .mtime
+                    .and_then(|mtime| mtime.to_seconds_and_nanos_for_persistence()),
+                indexed_chunks
+                    .iter()
+                    .map(|chunk| {
+                        (
+                            chunk.range.start,
+                            chunk.range.end,
+                            chunk.digest.to_vec(),
+                            chunk.embedding.to_bytes(),
+                        )
+                    })
+                    .collect(),
+            );
+            cx.background_spawn(async move { save.await.log_err() })
+                .detach();
+            worktree_index.files.insert(
+                path,
+                Arc::new(IndexedFile {
+                    mtime: entry.mtime,
+                    chunks: indexed_chunks,
+                }),
+            );
+            cx.notify();
+        })?;
+        Ok(())
+    }
+}
+
+fn load_indexed_files(
+    worktree_path: &str,
+    model_id: &str,
+) -> Result<HashMap<Arc<RelPath>, Arc<IndexedFile>>> {
+    let mut files = HashMap::default();
+    for (path, model, mtime_seconds, mtime_nanos) in
+        SEMANTIC_INDEX_DB.indexed_files(worktree_path)?
+    {
+        if model != model_id {
+            continue;
+        }
+        let Some(path) = RelPath::unix(&path).log_err() else {
+            continue;
+        };
+        let mtime = mtime_seconds
+            .zip(mtime_nanos)
+            .map(|(seconds, nanos)| MTime::from_seconds_and_nanos(seconds, nanos));
+        files.insert(path.into_arc(), (mtime, Vec::new()));
+    }
+
+    for (path, start, end, digest, embedding) in SEMANTIC_INDEX_DB.indexed_chunks(worktree_path)? {
+        let Some(path) = RelPath::unix(&path).log_err() else {
+            continue;
+        };
+        let Some((_, chunks)) = files.get_mut(path) else {
+            continue;
+        };
+        let (Ok(digest), Some(embedding)) = (
+            <[u8; 32]>::try_from(digest.as_slice()),
+            Embedding::from_bytes(&embedding).log_err(),
+        ) else {
+            continue;
+        };
+        chunks.push(IndexedChunk {
+            range: start..end,
+            digest,
+            embedding,
+        });
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(path, (mtime, chunks))| (path, Arc::new(IndexedFile { mtime, chunks })))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::FakeEmbeddingProvider;
+    use gpui::{AppContext as _, TestAppContext};
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_project_index(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/semantic_index_test"),
+            json!({
+                "src": {
+                    "auth.rs": "fn check_password(user: &User, password: &str) -> bool {\n    verify_hash(password, &user.hash)\n}\n",
+                    "render.rs": "fn draw_triangle(canvas: &mut Canvas) {\n    canvas.fill_polygon(&[a, b, c]);\n}\n",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/semantic_index_test").as_ref()], cx).await;
+        project.update(cx, |project, _| {
+            project.languages().add(language::rust_lang())
+        });
+
+        let index =
+            cx.new(|cx| ProjectIndex::new(project.clone(), Arc::new(FakeEmbeddingProvider), cx));
+        cx.run_until_parked();
+        assert_eq!(index.read_with(cx, |index, _| index.status()), Status::Idle);
+
+        let search = |query: &str, cx: &mut TestAppContext| {
+            let query = query.to_string();
+            index.read_with(cx, |index, cx| index.search(query, 1, cx))
+        };
+        let results = search("check the user password", cx).await.unwrap();
+        assert_eq!(results[0].path.as_unix_str(), "src/auth.rs");
+        let results = search("draw a triangle on the canvas", cx).await.unwrap();
+        assert_eq!(results[0].path.as_unix_str(), "src/render.rs");
+
+        // Changes to the worktree are indexed incrementally.
+        fs.insert_file(
+            path!("/semantic_index_test/src/network.rs"),
+            "fn open_socket(address: &str) -> Socket {\n    Socket::connect(address)\n}\n".into(),
+        )
+        .await;
+        fs.remove_file(
+            path!("/semantic_index_test/src/render.rs").as_ref(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        cx.run_until_parked();
+
+        let results = search("open a socket to an address", cx).await.unwrap();
+        assert_eq!(results[0].path.as_unix_str(), "src/network.rs");
+        let results = search("draw a triangle on the canvas", cx).await.unwrap();
+        assert_ne!(results[0].path.as_unix_str(), "src/render.rs");
+    }
+}
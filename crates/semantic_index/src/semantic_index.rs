@@ -0,0 +1,104 @@
+mod chunking;
+mod db;
+mod embedding;
+mod project_index;
+mod semantic_index_settings;
+
+use collections::HashMap;
+use gpui::{App, AppContext as _, Entity, EntityId, Global, WeakEntity};
+use project::Project;
+use settings::{EmbeddingProviderContent, Settings as _, SettingsStore};
+use std::sync::Arc;
+
+pub use chunking::{Chunk, MAX_CHUNK_SIZE, chunk_snapshot, chunk_text};
+#[cfg(any(test, feature = "test-support"))]
+pub use embedding::FakeEmbeddingProvider;
+pub use embedding::{
+    Embedding, EmbeddingProvider, LmStudioEmbeddingProvider, OllamaEmbeddingProvider,
+};
+pub use project_index::{ProjectIndex, SearchResult, Status};
+pub use semantic_index_settings::SemanticIndexSettings;
+
+/// The semantic indices of the open projects, created when the semantic index
+/// is enabled in the settings.
+#[derive(Default)]
+pub struct SemanticDb {
+    project_indices: HashMap<EntityId, (WeakEntity<Project>, Entity<ProjectIndex>)>,
+}
+
+impl Global for SemanticDb {}
+
+impl SemanticDb {
+    /// Returns the project's semantic index, creating it if needed. Returns
+    /// `None` if the semantic index is disabled or the project isn't local.
+    pub fn project_index(project: &Entity<Project>, cx: &mut App) -> Option<Entity<ProjectIndex>> {
+        let settings = SemanticIndexSettings::get_global(cx);
+        if !settings.enabled || !project.read(cx).is_local() {
+            return None;
+        }
+        let provider: Arc<dyn EmbeddingProvider> = match settings.provider {
+            EmbeddingProviderContent::Ollama => Arc::new(OllamaEmbeddingProvider::new(
+                cx.http_client(),
+                settings.api_url().to_string(),
+                settings.model.clone(),
+            )),
+            EmbeddingProviderContent::LmStudio => Arc::new(LmStudioEmbeddingProvider::new(
+                cx.http_client(),
+                settings.api_url().to_string(),
+                settings.model.clone(),
+            )),
+        };
+
+        let this = cx.default_global::<Self>();
+        this.project_indices
+            .retain(|_, (project, _)| project.upgrade().is_some());
+        if let Some((_, index)) = this.project_indices.get(&project.entity_id()).cloned()
+            && index.read(cx).provider().model_id() == provider.model_id()
+        {
+            return Some(index);
+        }
+
+        let index = cx.new(|cx| ProjectIndex::new(project.clone(), provider, cx));
+        cx.default_global::<Self>()
+            .project_indices
+            .insert(project.entity_id(), (project.downgrade(), index.clone()));
+        Some(index)
+    }
+
+    /// Drops the indices that no longer match the settings, and recreates
+    /// them for the projects that are still open.
+    fn settings_changed(cx: &mut App) {
+        let Some(this) = cx.try_global::<Self>() else {
+            return;
+        };
+        let projects = this
+            .project_indices
+            .values()
+            .filter_map(|(project, _)| project.upgrade())
+            .collect::<Vec<_>>();
+        if !SemanticIndexSettings::get_global(cx).enabled {
+            cx.default_global::<Self>().project_indices.clear();
+            return;
+        }
+        for project in projects {
+            Self::project_index(&project, cx);
+        }
+    }
+}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|project: &mut Project, _, cx| {
+        if !project.is_local() {
+            return;
+        }
+        // Start indexing eagerly, so results are ready by the time they're needed.
+        let project = cx.entity();
+        cx.defer(move |cx| {
+            SemanticDb::project_index(&project, cx);
+        });
+    })
+    .detach();
+
+    cx.observe_global::<SettingsStore>(SemanticDb::settings_changed)
+        .detach();
+}
@@ -0,0 +1,30 @@
+use settings::{EmbeddingProviderContent, RegisterSetting, Settings};
+
+#[derive(Clone, Debug, RegisterSetting)]
+pub struct SemanticIndexSettings {
+    pub enabled: bool,
+    pub provider: EmbeddingProviderContent,
+    pub model: String,
+    pub api_url: Option<String>,
+}
+
+impl SemanticIndexSettings {
+    pub fn api_url(&self) -> &str {
+        self.api_url.as_deref().unwrap_or(match self.provider {
+            EmbeddingProviderContent::Ollama => ollama::OLLAMA_API_URL,
+            EmbeddingProviderContent::LmStudio => lmstudio::LMSTUDIO_API_URL,
+        })
+    }
+}
+
+impl Settings for SemanticIndexSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let semantic_index = content.semantic_index.as_ref().unwrap();
+        Self {
+            enabled: semantic_index.enabled.unwrap(),
+            provider: semantic_index.provider.unwrap(),
+            model: semantic_index.model.clone().unwrap(),
+            api_url: semantic_index.api_url.clone(),
+        }
+    }
+}
@@ -1027,6 +1027,8 @@ impl VsCodeSettings {
                         .collect::<Vec<_>>()
                 })
                 .filter(|r| !r.is_empty()),
+            lazy_file_scan: None,
+            file_scan_always_index: None,
             private_files: None,
             hidden_files: None,
             read_only_files: self
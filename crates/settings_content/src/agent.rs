@@ -111,6 +111,24 @@ pub struct AgentSettingsContent {
     ///
     /// Default: false
     pub show_turn_stats: Option<bool>,
+    /// The fraction of the model's context window at which the native agent summarizes older
+    /// messages of a thread, so the conversation can continue. Set to 1 or more to disable.
+    ///
+    /// Default: 0.8
+    pub auto_compact_threshold: Option<f32>,
+    /// A soft daily budget, in US dollars, for the language model requests of native agent
+    /// threads. Once the estimated cost of today's requests reaches it, the agent panel asks for
+    /// confirmation before sending a message. Requests to models whose pricing isn't known don't
+    /// count towards it; use `daily_token_budget` to limit those.
+    ///
+    /// Default: null
+    pub daily_budget: Option<f64>,
+    /// A soft daily budget, in tokens, for the language model requests of native agent threads.
+    /// Unlike `daily_budget`, it counts the tokens of every model, including those whose pricing
+    /// isn't known.
+    ///
+    /// Default: null
+    pub daily_token_budget: Option<u64>,
     /// Per-tool permission rules for granular control over which tool actions
     /// require confirmation.
     ///
@@ -208,6 +226,26 @@ pub struct AgentProfileContent {
     pub context_servers: IndexMap<Arc<str>, ContextServerPresetContent>,
     /// The default language model selected when using this profile.
     pub default_model: Option<LanguageModelSelection>,
+    /// Restrictions on what the commands run by the terminal tool can access.
+    pub terminal_sandbox: Option<TerminalSandboxContent>,
+}
+
+#[with_fallible_options]
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct TerminalSandboxContent {
+    /// Whether to run the commands of the terminal tool in a sandbox, where the file system is
+    /// read-only except for the project's worktrees. The sandbox uses namespaces only and doesn't
+    /// filter system calls. Only supported on Linux, and requires bubblewrap (`bwrap`) to be
+    /// installed.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Whether sandboxed commands can access the network.
+    ///
+    /// Default: false
+    pub allow_network: Option<bool>,
+    /// Additional paths that sandboxed commands can write to.
+    pub writable_paths: Option<Vec<PathBuf>>,
 }
 
 #[with_fallible_options]
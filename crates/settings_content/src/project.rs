@@ -125,6 +125,18 @@ pub struct WorktreeSettingsContent {
     /// ]
     pub file_scan_inclusions: Option<Vec<String>>,
 
+    /// Only scan a directory once it's expanded in the project panel, a file inside it is opened,
+    /// or it matches `file_scan_always_index`. Useful for repositories too large to scan up front.
+    /// Project search and the file finder walk the directories that haven't been scanned yet.
+    ///
+    /// Default: false
+    pub lazy_file_scan: Option<bool>,
+
+    /// Directories matching these globs are scanned up front even when `lazy_file_scan` is enabled.
+    ///
+    /// Default: []
+    pub file_scan_always_index: Option<Vec<String>>,
+
     /// Treat the files matching these globs as `.env` files.
     /// Default: ["**/.env*", "**/*.pem", "**/*.key", "**/*.cert", "**/*.crt", "**/secrets.yml"]
     pub private_files: Option<ExtendingVec<String>>,
@@ -176,6 +176,12 @@ pub struct SettingsContent {
 
     pub proxy: Option<String>,
 
+    /// Configuration for the Quickfix Panel
+    pub quickfix_panel: Option<QuickfixPanelSettingsContent>,
+
+    /// Configuration for the local semantic search index.
+    pub semantic_index: Option<SemanticIndexSettingsContent>,
+
     /// The URL of the Zed server to connect to.
     pub server_url: Option<String>,
 
@@ -673,6 +679,70 @@ pub struct NotificationPanelSettingsContent {
     pub default_width: Option<f32>,
 }
 
+#[with_fallible_options]
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, PartialEq)]
+pub struct QuickfixPanelSettingsContent {
+    /// Whether to show the panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the panel.
+    ///
+    /// Default: bottom
+    pub dock: Option<DockPosition>,
+    /// Default width of the panel in pixels, when docked to the left or right.
+    ///
+    /// Default: 400
+    #[serde(serialize_with = "crate::serialize_optional_f32_with_two_decimal_places")]
+    pub default_width: Option<f32>,
+    /// Default height of the panel in pixels, when docked to the bottom.
+    ///
+    /// Default: 240
+    #[serde(serialize_with = "crate::serialize_optional_f32_with_two_decimal_places")]
+    pub default_height: Option<f32>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, PartialEq)]
+pub struct SemanticIndexSettingsContent {
+    /// Whether to build an embeddings index of local projects for semantic search.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The local provider used to compute embeddings.
+    ///
+    /// Default: ollama
+    pub provider: Option<EmbeddingProviderContent>,
+    /// The embedding model to use. It must be available in the provider.
+    ///
+    /// Default: nomic-embed-text
+    pub model: Option<String>,
+    /// The provider's API URL. Uses the provider's default local address when unset.
+    ///
+    /// Default: null
+    pub api_url: Option<String>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    PartialEq,
+    Eq,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProviderContent {
+    Ollama,
+    #[serde(rename = "lmstudio")]
+    LmStudio,
+}
+
 #[with_fallible_options]
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, PartialEq)]
 pub struct PanelSettingsContent {
@@ -787,6 +857,13 @@ pub struct VimSettingsContent {
     /// by default. The 'g' flag then toggles this behavior.,
     pub gdefault: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
+    /// Abbreviations that are expanded when typed in insert mode or on the
+    /// command line, as if defined with `:abbreviate`.
+    pub abbreviations: Option<HashMap<String, String>>,
+    /// The command run by `:grep`, with `$*` replaced by the command's
+    /// arguments, or followed by them if it has no `$*`. Its output must list
+    /// the matches as `file:line:text`.
+    pub grepprg: Option<String>,
     pub highlight_on_yank_duration: Option<u64>,
     pub cursor_shape: Option<CursorShapeSettings>,
 }
@@ -1059,6 +1136,8 @@ pub enum ImageFileSizeUnit {
 pub struct RemoteSettingsContent {
     pub ssh_connections: Option<Vec<SshConnection>>,
     pub wsl_connections: Option<Vec<WslConnection>>,
+    pub socket_connections: Option<Vec<SocketConnection>>,
+    pub command_connections: Option<Vec<CommandConnection>>,
     pub dev_container_connections: Option<Vec<DevContainerConnection>>,
     pub read_ssh_config: Option<bool>,
     pub use_podman: Option<bool>,
@@ -1107,6 +1186,48 @@ pub struct WslConnection {
     pub projects: BTreeSet<RemoteProject>,
 }
 
+/// A `remote_server listen` process that Zed connects to directly over TCP or a Unix socket.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema, MergeFrom, Debug)]
+pub struct SocketConnection {
+    /// Address the server listens on, either `host:port` or `unix:/path/to/socket`.
+    pub address: String,
+    /// Name to use for this server in UI.
+    pub nickname: Option<String>,
+    /// Where to read the authentication token from, either `env:NAME` or `file:/path/to/token`.
+    ///
+    /// Default: `env:ZED_REMOTE_SERVER_TOKEN`
+    pub token_source: Option<String>,
+    #[serde(default)]
+    pub projects: BTreeSet<RemoteProject>,
+}
+
+/// A remote reached by running a local command that executes programs inside of it,
+/// such as `kubectl exec`.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema, MergeFrom, Debug)]
+pub struct CommandConnection {
+    /// Name to use for this remote in UI.
+    pub name: String,
+    /// Command that runs the arguments appended to it on the remote,
+    /// e.g. `["kubectl", "exec", "-i", "my-pod", "--"]`.
+    pub exec: Vec<String>,
+    /// Like `exec`, but allocating a terminal, e.g. `["kubectl", "exec", "-it", "my-pod", "--"]`.
+    ///
+    /// Default: the `exec` command
+    pub exec_interactive: Option<Vec<String>>,
+    /// Command that copies the local `{src}` path to the remote `{dst}` path,
+    /// e.g. `["kubectl", "cp", "{src}", "my-pod:{dst}"]`.
+    ///
+    /// Default: files are streamed through the `exec` command
+    pub upload: Option<Vec<String>>,
+    /// Whether to upload the remote server binary from this machine instead of
+    /// downloading it on the remote.
+    ///
+    /// Default: false
+    pub upload_binary_over_exec: Option<bool>,
+    #[serde(default)]
+    pub projects: BTreeSet<RemoteProject>,
+}
+
 #[with_fallible_options]
 #[derive(
     Clone, Debug, Default, Serialize, PartialEq, Eq, PartialOrd, Ord, Deserialize, JsonSchema,
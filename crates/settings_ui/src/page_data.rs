@@ -2436,7 +2436,7 @@ fn editor_page() -> SettingsPage {
         ]
     }
 
-    fn vim_settings_section() -> [SettingsPageItem; 12] {
+    fn vim_settings_section() -> [SettingsPageItem; 13] {
         [
             SettingsPageItem::SectionHeader("Vim"),
             SettingsPageItem::SettingItem(SettingItem {
@@ -2667,6 +2667,37 @@ fn editor_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Abbreviations",
+                description: "Abbreviations expanded in insert mode and on the command line.",
+                field: Box::new(
+                    SettingField {
+                        json_path: Some("vim.abbreviations"),
+                        pick: |settings_content| {
+                            settings_content.vim.as_ref()?.abbreviations.as_ref()
+                        },
+                        write: |settings_content, value| {
+                            settings_content.vim.get_or_insert_default().abbreviations = value;
+                        },
+                    }
+                    .unimplemented(),
+                ),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Grep Program",
+                description: "The command run by :grep, with $* replaced by its arguments.",
+                field: Box::new(SettingField {
+                    json_path: Some("vim.grepprg"),
+                    pick: |settings_content| settings_content.vim.as_ref()?.grepprg.as_ref(),
+                    write: |settings_content, value| {
+                        settings_content.vim.get_or_insert_default().grepprg = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
         ]
     }
 
@@ -3263,7 +3294,7 @@ fn search_and_files_page() -> SettingsPage {
         ]
     }
 
-    fn file_scan_section() -> [SettingsPageItem; 5] {
+    fn file_scan_section() -> [SettingsPageItem; 7] {
         [
             SettingsPageItem::SectionHeader("File Scan"),
             SettingsPageItem::SettingItem(SettingItem {
@@ -3310,6 +3341,43 @@ fn search_and_files_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Lazy File Scan",
+                description: "Only scan directories once they're expanded in the project panel, a file inside them is opened, or they match \"File Scan Always Index\". Project search and the file finder walk unscanned directories on demand",
+                field: Box::new(SettingField {
+                    json_path: Some("lazy_file_scan"),
+                    pick: |settings_content| {
+                        settings_content.project.worktree.lazy_file_scan.as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content.project.worktree.lazy_file_scan = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "File Scan Always Index",
+                description: "Globs of directories that are scanned up front even when \"Lazy File Scan\" is enabled",
+                field: Box::new(
+                    SettingField {
+                        json_path: Some("file_scan_always_index"),
+                        pick: |settings_content| {
+                            settings_content
+                                .project
+                                .worktree
+                                .file_scan_always_index
+                                .as_ref()
+                        },
+                        write: |settings_content, value| {
+                            settings_content.project.worktree.file_scan_always_index = value;
+                        },
+                    }
+                    .unimplemented(),
+                ),
+                metadata: None,
+                files: USER,
+            }),
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Restore File State",
                 description: "Restore previous file state when reopening.",
@@ -5249,6 +5317,89 @@ fn panels_page() -> SettingsPage {
         ]
     }
 
+    fn quickfix_panel_section() -> [SettingsPageItem; 5] {
+        [
+            SettingsPageItem::SectionHeader("Quickfix Panel"),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Quickfix Panel Button",
+                description: "Show the quickfix panel button in the status bar.",
+                field: Box::new(SettingField {
+                    json_path: Some("quickfix_panel.button"),
+                    pick: |settings_content| {
+                        settings_content.quickfix_panel.as_ref()?.button.as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .quickfix_panel
+                            .get_or_insert_default()
+                            .button = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Quickfix Panel Dock",
+                description: "Where to dock the quickfix panel.",
+                field: Box::new(SettingField {
+                    json_path: Some("quickfix_panel.dock"),
+                    pick: |settings_content| {
+                        settings_content.quickfix_panel.as_ref()?.dock.as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content.quickfix_panel.get_or_insert_default().dock = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Quickfix Panel Default Width",
+                description: "Default width of the quickfix panel in pixels, when docked to the left or right.",
+                field: Box::new(SettingField {
+                    json_path: Some("quickfix_panel.default_width"),
+                    pick: |settings_content| {
+                        settings_content
+                            .quickfix_panel
+                            .as_ref()?
+                            .default_width
+                            .as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .quickfix_panel
+                            .get_or_insert_default()
+                            .default_width = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Quickfix Panel Default Height",
+                description: "Default height of the quickfix panel in pixels, when docked to the bottom.",
+                field: Box::new(SettingField {
+                    json_path: Some("quickfix_panel.default_height"),
+                    pick: |settings_content| {
+                        settings_content
+                            .quickfix_panel
+                            .as_ref()?
+                            .default_height
+                            .as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .quickfix_panel
+                            .get_or_insert_default()
+                            .default_height = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+        ]
+    }
+
     fn notification_panel_section() -> [SettingsPageItem; 4] {
         [
             SettingsPageItem::SectionHeader("Notification Panel"),
@@ -5459,6 +5610,7 @@ fn panels_page() -> SettingsPage {
             outline_panel_section(),
             git_panel_section(),
             debugger_panel_section(),
+            quickfix_panel_section(),
             notification_panel_section(),
             collaboration_panel_section(),
             agent_panel_section(),
@@ -6990,7 +7142,7 @@ fn ai_page() -> SettingsPage {
         ]
     }
 
-    fn agent_configuration_section() -> [SettingsPageItem; 12] {
+    fn agent_configuration_section() -> [SettingsPageItem; 13] {
         [
             SettingsPageItem::SectionHeader("Agent Configuration"),
             SettingsPageItem::SubPageLink(SubPageLink {
@@ -7206,6 +7358,28 @@ fn ai_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Auto Compact Threshold",
+                description: "Fraction of the model's context window at which older messages of a thread are summarized. Set to 1 or more to disable.",
+                field: Box::new(SettingField {
+                    json_path: Some("agent.auto_compact_threshold"),
+                    pick: |settings_content| {
+                        settings_content
+                            .agent
+                            .as_ref()?
+                            .auto_compact_threshold
+                            .as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .agent
+                            .get_or_insert_default()
+                            .auto_compact_threshold = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
         ]
     }
 
@@ -1406,6 +1406,8 @@ mod tests {
             "now",
             "open",
             "read_file",
+            "repo_map",
+            "semantic_search",
             "thinking",
             // streaming_edit_file uses "edit_file" for permission lookups,
             // so its rules are configured under the edit_file entry.
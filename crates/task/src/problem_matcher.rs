@@ -0,0 +1,594 @@
+use anyhow::{Context as _, bail};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes how to find errors and warnings in the output of a task.
+///
+/// Uses the same format as VS Code's problem matchers, so both a built-in matcher
+/// name like `"$rustc"`, or a matcher with custom patterns can be used.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// One of the built-in matchers: `$rustc` (also matches cargo output), `$tsc`, `$gcc`
+    /// (also matches clang output), `$eslint-stylish`, `$eslint-compact`, `$go` or `$grep`
+    /// (the `path:line:text` output of `grep -n`).
+    BuiltIn(String),
+    /// A matcher defined with regular expressions.
+    Custom(CustomProblemMatcher),
+}
+
+/// A problem matcher defined with regular expressions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CustomProblemMatcher {
+    /// The pattern for a problem, or several patterns to match against consecutive
+    /// lines of output, for problems that span multiple lines.
+    pub pattern: ProblemPatterns,
+    /// The severity of the problems whose pattern does not capture a severity.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemPatterns {
+    Single(ProblemPattern),
+    Multiple(Vec<ProblemPattern>),
+}
+
+impl ProblemPatterns {
+    fn as_slice(&self) -> &[ProblemPattern] {
+        match self {
+            Self::Single(pattern) => std::slice::from_ref(pattern),
+            Self::Multiple(patterns) => patterns,
+        }
+    }
+}
+
+/// A regular expression for one line of a problem, with the indices of the
+/// capture groups that contain the problem's details.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ProblemPattern {
+    /// The regular expression to match a line of output with.
+    pub regexp: String,
+    /// The capture group of the file path, relative to the task's working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<usize>,
+    /// The capture group of the (1-based) line number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// The capture group of the (1-based) column number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// The capture group of the severity, such as `error` or `warning`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<usize>,
+    /// The capture group of the error code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<usize>,
+    /// The capture group of the message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<usize>,
+    /// Whether the last pattern may match several lines in a row, each reporting
+    /// a problem, as in ESLint's output that lists all problems below the file name.
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+}
+
+/// The severity of a problem found in a task's output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
+impl ProblemSeverity {
+    fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "error" | "fatal error" | "e" => Some(Self::Error),
+            "warning" | "warn" | "w" => Some(Self::Warning),
+            "info" | "information" | "note" | "help" | "hint" | "i" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
+
+/// An error or warning found in a task's output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskProblem {
+    /// The path of the file, as printed by the task.
+    pub path: String,
+    /// The 1-based line of the problem.
+    pub line: u32,
+    /// The 1-based column of the problem, if the task reported it.
+    pub column: Option<u32>,
+    pub severity: ProblemSeverity,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl ProblemMatcher {
+    /// Finds the problems in the output of a task.
+    pub fn problems(&self, output: &str) -> anyhow::Result<Vec<TaskProblem>> {
+        let matcher = match self {
+            Self::BuiltIn(name) => &built_in(name).with_context(|| {
+                format!("unknown problem matcher `{name}`, expected one of {BUILT_IN_NAMES:?}")
+            })?,
+            Self::Custom(matcher) => matcher,
+        };
+        let patterns = matcher.pattern.as_slice();
+        if patterns.is_empty() {
+            bail!("problem matcher has no patterns");
+        }
+        let regexes = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(&pattern.regexp)
+                    .with_context(|| format!("invalid problem pattern `{}`", pattern.regexp))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut problems = Vec::new();
+        let mut fields = ProblemFields::default();
+        // The index of the pattern the next line is expected to match.
+        let mut next_pattern = 0;
+        for line in output.lines() {
+            // A repeating last pattern keeps matching lines until one doesn't match.
+            let in_loop = next_pattern == patterns.len();
+            if in_loop {
+                let last = patterns.len() - 1;
+                if let Some(captures) = regexes[last].captures(line) {
+                    let mut fields = fields.clone();
+                    fields.capture(&patterns[last], &captures);
+                    problems.extend(fields.into_problem(matcher.severity));
+                    continue;
+                }
+                next_pattern = 0;
+            }
+
+            if next_pattern > 0 {
+                if let Some(captures) = regexes[next_pattern].captures(line) {
+                    fields.capture(&patterns[next_pattern], &captures);
+                    next_pattern += 1;
+                } else {
+                    next_pattern = 0;
+                }
+            }
+            if next_pattern == 0 {
+                let Some(captures) = regexes[0].captures(line) else {
+                    continue;
+                };
+                fields = ProblemFields::default();
+                fields.capture(&patterns[0], &captures);
+                next_pattern = 1;
+            }
+
+            if next_pattern == patterns.len() {
+                let last = &patterns[patterns.len() - 1];
+                if last.repeat && patterns.len() > 1 {
+                    // The fields captured by the earlier patterns are shared by
+                    // all the problems matched by the last one.
+                    problems.extend(fields.clone().into_problem(matcher.severity));
+                    fields.forget(last);
+                } else {
+                    problems.extend(std::mem::take(&mut fields).into_problem(matcher.severity));
+                    next_pattern = 0;
+                }
+            }
+        }
+        Ok(problems)
+    }
+}
+
+#[derive(Clone, Default)]
+struct ProblemFields {
+    path: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl ProblemFields {
+    fn capture(&mut self, pattern: &ProblemPattern, captures: &regex::Captures) {
+        let group = |index: Option<usize>| {
+            captures
+                .get(index?)
+                .map(|group| group.as_str().trim())
+                .filter(|text| !text.is_empty())
+        };
+        if let Some(path) = group(pattern.file) {
+            self.path = Some(path.to_string());
+        }
+        if let Some(line) = group(pattern.line).and_then(|line| line.parse().ok()) {
+            self.line = Some(line);
+        }
+        if let Some(column) = group(pattern.column).and_then(|column| column.parse().ok()) {
+            self.column = Some(column);
+        }
+        if let Some(severity) = group(pattern.severity).and_then(ProblemSeverity::parse) {
+            self.severity = Some(severity);
+        }
+        if let Some(code) = group(pattern.code) {
+            self.code = Some(code.to_string());
+        }
+        if let Some(message) = group(pattern.message) {
+            self.message = Some(message.to_string());
+        }
+    }
+
+    /// Clears the fields captured by `pattern`.
+    fn forget(&mut self, pattern: &ProblemPattern) {
+        if pattern.file.is_some() {
+            self.path = None;
+        }
+        if pattern.line.is_some() {
+            self.line = None;
+        }
+        if pattern.column.is_some() {
+            self.column = None;
+        }
+        if pattern.severity.is_some() {
+            self.severity = None;
+        }
+        if pattern.code.is_some() {
+            self.code = None;
+        }
+        if pattern.message.is_some() {
+            self.message = None;
+        }
+    }
+
+    fn into_problem(self, default_severity: ProblemSeverity) -> Option<TaskProblem> {
+        Some(TaskProblem {
+            path: self.path?,
+            line: self.line?.max(1),
+            column: self.column,
+            severity: self.severity.unwrap_or(default_severity),
+            code: self.code,
+            message: self.message.unwrap_or_default(),
+        })
+    }
+}
+
+const BUILT_IN_NAMES: &[&str] = &[
+    "$rustc",
+    "$tsc",
+    "$gcc",
+    "$eslint-stylish",
+    "$eslint-compact",
+    "$go",
+    "$grep",
+];
+
+/// Returns the patterns of a built-in matcher, such as `$gcc`.
+pub(crate) fn built_in(name: &str) -> Option<CustomProblemMatcher> {
+    let pattern = |regexp: &str| ProblemPattern {
+        regexp: regexp.to_string(),
+        ..ProblemPattern::default()
+    };
+    let patterns = match name.strip_suffix("-watch").unwrap_or(name) {
+        "$rustc" => ProblemPatterns::Multiple(vec![
+            ProblemPattern {
+                severity: Some(1),
+                code: Some(2),
+                message: Some(3),
+                ..pattern(r"^(error|warning)(?:\[(\w+)\])?: (.*)$")
+            },
+            ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                ..pattern(r"^\s*--> (.+?):(\d+):(\d+)$")
+            },
+        ]),
+        "$tsc" => ProblemPatterns::Single(ProblemPattern {
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            code: Some(5),
+            message: Some(6),
+            ..pattern(
+                r"^([^\s].*?)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$",
+            )
+        }),
+        "$gcc" => ProblemPatterns::Single(ProblemPattern {
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            ..pattern(r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$")
+        }),
+        "$eslint-stylish" => ProblemPatterns::Multiple(vec![
+            ProblemPattern {
+                file: Some(1),
+                ..pattern(r"^([^\s].*)$")
+            },
+            ProblemPattern {
+                line: Some(1),
+                column: Some(2),
+                severity: Some(3),
+                message: Some(4),
+                code: Some(5),
+                repeat: true,
+                ..pattern(r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$")
+            },
+        ]),
+        "$eslint-compact" => ProblemPatterns::Single(ProblemPattern {
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            code: Some(6),
+            ..pattern(
+                r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$",
+            )
+        }),
+        "$go" => ProblemPatterns::Single(ProblemPattern {
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            message: Some(4),
+            ..pattern(r"^\s*(?:\S+: )?([^:\s][^:]*\.go):(\d+)(?::(\d+))?: (.*)$")
+        }),
+        "$grep" => {
+            return Some(CustomProblemMatcher {
+                pattern: ProblemPatterns::Single(ProblemPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    message: Some(3),
+                    ..pattern(r"^([^\s:][^:]*):(\d+):(.*)$")
+                }),
+                severity: ProblemSeverity::Info,
+            });
+        }
+        _ => return None,
+    };
+    Some(CustomProblemMatcher {
+        pattern: patterns,
+        severity: ProblemSeverity::Error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn problem(
+        path: &str,
+        line: u32,
+        column: Option<u32>,
+        severity: ProblemSeverity,
+        code: Option<&str>,
+        message: &str,
+    ) -> TaskProblem {
+        TaskProblem {
+            path: path.to_string(),
+            line,
+            column,
+            severity,
+            code: code.map(ToString::to_string),
+            message: message.to_string(),
+        }
+    }
+
+    fn problems(matcher: &str, output: &str) -> Vec<TaskProblem> {
+        ProblemMatcher::BuiltIn(matcher.to_string())
+            .problems(output)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rustc_problem_matcher() {
+        let output = r#"
+   Compiling example v0.1.0 (/tmp/example)
+error[E0425]: cannot find value `y` in this scope
+ --> src/main.rs:2:13
+  |
+2 |     let x = y;
+  |             ^ not found in this scope
+
+warning: unused variable: `x`
+  --> src/lib.rs:10:9
+   |
+error: could not compile `example` (bin "example") due to 1 previous error
+"#;
+        assert_eq!(
+            problems("$rustc", output),
+            vec![
+                problem(
+                    "src/main.rs",
+                    2,
+                    Some(13),
+                    ProblemSeverity::Error,
+                    Some("E0425"),
+                    "cannot find value `y` in this scope"
+                ),
+                problem(
+                    "src/lib.rs",
+                    10,
+                    Some(9),
+                    ProblemSeverity::Warning,
+                    None,
+                    "unused variable: `x`"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tsc_gcc_go_and_grep_problem_matchers() {
+        assert_eq!(
+            problems(
+                "$tsc",
+                "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+                 src/util.ts:12:1 - warning TS6133: 'x' is declared but its value is never read.",
+            ),
+            vec![
+                problem(
+                    "src/index.ts",
+                    3,
+                    Some(7),
+                    ProblemSeverity::Error,
+                    Some("TS2322"),
+                    "Type 'string' is not assignable to type 'number'."
+                ),
+                problem(
+                    "src/util.ts",
+                    12,
+                    Some(1),
+                    ProblemSeverity::Warning,
+                    Some("TS6133"),
+                    "'x' is declared but its value is never read."
+                ),
+            ]
+        );
+        assert_eq!(
+            problems(
+                "$gcc",
+                "main.c: In function 'main':\n\
+                 main.c:5:3: error: 'y' undeclared (first use in this function)\n\
+                 main.c:4:7: warning: unused variable 'x' [-Wunused-variable]",
+            ),
+            vec![
+                problem(
+                    "main.c",
+                    5,
+                    Some(3),
+                    ProblemSeverity::Error,
+                    None,
+                    "'y' undeclared (first use in this function)"
+                ),
+                problem(
+                    "main.c",
+                    4,
+                    Some(7),
+                    ProblemSeverity::Warning,
+                    None,
+                    "unused variable 'x' [-Wunused-variable]"
+                ),
+            ]
+        );
+        assert_eq!(
+            problems(
+                "$grep",
+                "src/main.rs:12:    let x = 1;\nBinary file x.bin matches"
+            ),
+            vec![problem(
+                "src/main.rs",
+                12,
+                None,
+                ProblemSeverity::Info,
+                None,
+                "let x = 1;"
+            )]
+        );
+        assert_eq!(
+            problems("$go", "# example\n./main.go:8:2: undefined: fmt.Printn",),
+            vec![problem(
+                "./main.go",
+                8,
+                Some(2),
+                ProblemSeverity::Error,
+                None,
+                "undefined: fmt.Printn"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_eslint_problem_matchers() {
+        let output = "
+/project/src/a.js
+  1:10  error    'foo' is defined but never used  no-unused-vars
+  3:1   warning  Unexpected console statement     no-console
+
+/project/src/b.js
+  7:5  error  Missing semicolon  semi
+
+✖ 3 problems (2 errors, 1 warning)
+";
+        assert_eq!(
+            problems("$eslint-stylish", output),
+            vec![
+                problem(
+                    "/project/src/a.js",
+                    1,
+                    Some(10),
+                    ProblemSeverity::Error,
+                    Some("no-unused-vars"),
+                    "'foo' is defined but never used"
+                ),
+                problem(
+                    "/project/src/a.js",
+                    3,
+                    Some(1),
+                    ProblemSeverity::Warning,
+                    Some("no-console"),
+                    "Unexpected console statement"
+                ),
+                problem(
+                    "/project/src/b.js",
+                    7,
+                    Some(5),
+                    ProblemSeverity::Error,
+                    Some("semi"),
+                    "Missing semicolon"
+                ),
+            ]
+        );
+        assert_eq!(
+            problems(
+                "$eslint-compact",
+                "/project/src/a.js: line 1, col 10, Error - 'foo' is defined but never used (no-unused-vars)",
+            ),
+            vec![problem(
+                "/project/src/a.js",
+                1,
+                Some(10),
+                ProblemSeverity::Error,
+                Some("no-unused-vars"),
+                "'foo' is defined but never used"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_custom_problem_matcher() {
+        let matcher: ProblemMatcher = serde_json::from_value(serde_json::json!({
+            "pattern": {
+                "regexp": "^(.*)@(\\d+): (.*)$",
+                "file": 1,
+                "line": 2,
+                "message": 3
+            },
+            "severity": "warning"
+        }))
+        .unwrap();
+        assert_eq!(
+            matcher.problems("lib.py@4: line too long\nok").unwrap(),
+            vec![problem(
+                "lib.py",
+                4,
+                None,
+                ProblemSeverity::Warning,
+                None,
+                "line too long"
+            )]
+        );
+
+        assert!(
+            ProblemMatcher::BuiltIn("$unknown".into())
+                .problems("")
+                .is_err()
+        );
+    }
+}
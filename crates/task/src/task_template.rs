@@ -9,8 +9,8 @@ use util::serde::default_true;
 use util::{ResultExt, truncate_and_remove_front};
 
 use crate::{
-    AttachRequest, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId,
-    VariableName, ZED_VARIABLE_NAME_PREFIX, serde_helpers::non_empty_string_vec,
+    AttachRequest, ProblemMatcher, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext,
+    TaskId, VariableName, ZED_VARIABLE_NAME_PREFIX, serde_helpers::non_empty_string_vec,
 };
 
 /// A template definition of a Zed task to run.
@@ -72,6 +72,13 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Problem matchers to find errors and warnings in the task's output with,
+    /// which are then shown as diagnostics until the task is rerun.
+    /// Either names of built-in matchers (`$rustc`, `$tsc`, `$gcc`, `$eslint-stylish`,
+    /// `$eslint-compact`, `$go`, `$grep`), or custom matchers in VS Code's format.
+    /// Not supported in remote projects.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 #[derive(Deserialize, Eq, PartialEq, Clone, Debug)]
@@ -270,6 +277,7 @@ impl TaskTemplate {
                 show_summary: self.show_summary,
                 show_command: self.show_command,
                 show_rerun: true,
+                problem_matchers: self.problem_matchers.clone(),
             },
         })
     }
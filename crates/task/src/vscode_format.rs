@@ -3,7 +3,10 @@ use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{EnvVariableReplacer, TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    CustomProblemMatcher, EnvVariableReplacer, ProblemMatcher, TaskTemplate, TaskTemplates,
+    VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -104,6 +107,14 @@ impl VsCodeTaskDefinition {
         let Some(command) = self.command else {
             bail!("Missing `type` field in task");
         };
+        let problem_matchers = match self.other_attributes.get("problemMatcher") {
+            Some(serde_json_lenient::Value::Array(matchers)) => matchers
+                .iter()
+                .filter_map(|matcher| problem_matcher(matcher, &self.label))
+                .collect(),
+            Some(matcher) => problem_matcher(matcher, &self.label).into_iter().collect(),
+            None => Vec::new(),
+        };
 
         let (command, args) = match command {
             Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
@@ -117,6 +128,7 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            problem_matchers,
             ..TaskTemplate::default()
         };
         if let Some(options) = self.options {
@@ -127,6 +139,68 @@ impl VsCodeTaskDefinition {
     }
 }
 
+/// Converts a VS Code problem matcher, which is either the name of a built-in matcher,
+/// an object with its own `pattern`, or an object that extends a built-in matcher via `base`.
+fn problem_matcher(value: &serde_json_lenient::Value, label: &str) -> Option<ProblemMatcher> {
+    use serde_json_lenient::Value;
+
+    let matcher = match value {
+        Value::String(name) => Some(ProblemMatcher::BuiltIn(name.clone())),
+        Value::Object(object) => {
+            if let Some(location) = object.get("fileLocation")
+                && !is_supported_file_location(location)
+            {
+                log::warn!(
+                    "Problem matcher of a task `{label}` has an unsupported `fileLocation` {location}, \
+                    resolving its files against the task's working directory instead"
+                );
+            }
+            match (object.get("pattern"), object.get("base")) {
+                (Some(Value::String(name)), _) => Some(ProblemMatcher::BuiltIn(name.clone())),
+                (Some(_), _) => {
+                    serde_json_lenient::from_value::<CustomProblemMatcher>(value.clone())
+                        .ok()
+                        .map(ProblemMatcher::Custom)
+                }
+                (None, Some(Value::String(base))) => match object.get("severity") {
+                    Some(severity) => serde_json_lenient::from_value(severity.clone())
+                        .ok()
+                        .zip(crate::problem_matcher::built_in(base))
+                        .map(|(severity, matcher)| {
+                            ProblemMatcher::Custom(CustomProblemMatcher {
+                                severity,
+                                ..matcher
+                            })
+                        }),
+                    None => Some(ProblemMatcher::BuiltIn(base.clone())),
+                },
+                (None, _) => None,
+            }
+        }
+        _ => None,
+    };
+    if matcher.is_none() {
+        log::warn!("Skipping unsupported problem matcher {value} of a task `{label}`");
+    }
+    matcher
+}
+
+/// Zed resolves relative paths in a task's output against the task's working directory,
+/// which is VS Code's `${workspaceFolder}` unless the task sets another one, and keeps
+/// absolute paths as they are. So the only locations that differ are other directories.
+fn is_supported_file_location(location: &serde_json_lenient::Value) -> bool {
+    use serde_json_lenient::Value;
+
+    match location {
+        Value::String(_) => true,
+        Value::Array(parts) => parts
+            .get(1)
+            .and_then(Value::as_str)
+            .is_none_or(|directory| matches!(directory, "${workspaceFolder}" | "${workspaceRoot}")),
+        _ => false,
+    }
+}
+
 /// [`VsCodeTaskFile`] is a superset of Code's task definition format.
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
@@ -168,11 +242,11 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        ProblemMatcher, TaskTemplate, TaskTemplates, VsCodeTaskFile,
         vscode_format::{Command, VsCodeTaskDefinition},
     };
 
-    use super::{EnvVariableReplacer, generate_label};
+    use super::{EnvVariableReplacer, generate_label, problem_matcher};
 
     fn compare_without_other_attributes(lhs: VsCodeTaskDefinition, rhs: VsCodeTaskDefinition) {
         assert_eq!(
@@ -271,6 +345,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -282,18 +357,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc-watch".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -374,28 +452,33 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc-watch".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -416,6 +499,49 @@ mod tests {
         assert_eq!(vscode_definitions.tasks[3].label, "echo hello");
     }
 
+    #[test]
+    fn test_problem_matcher_conversion() {
+        let matcher =
+            |json: &str| problem_matcher(&serde_json_lenient::from_str(json).unwrap(), "");
+        assert_eq!(
+            matcher(r#"{ "base": "$gcc", "fileLocation": "absolute" }"#),
+            Some(ProblemMatcher::BuiltIn("$gcc".to_string()))
+        );
+        let Some(ProblemMatcher::Custom(custom)) = matcher(
+            r#"{ "base": "$gcc", "severity": "warning", "fileLocation": ["relative", "${workspaceFolder}"] }"#,
+        ) else {
+            panic!("expected the overrides to turn the base into a custom problem matcher");
+        };
+        assert_eq!(custom.severity, crate::ProblemSeverity::Warning);
+        assert_eq!(
+            ProblemMatcher::Custom(custom)
+                .problems("main.c:5:3: error: 'y' undeclared")
+                .unwrap()
+                .len(),
+            1,
+            "the base's patterns should be kept"
+        );
+        assert!(matcher(r#"{ "base": "$unknown", "severity": "warning" }"#).is_none());
+        assert_eq!(
+            matcher(r#"{ "owner": "go", "pattern": "$go" }"#),
+            Some(ProblemMatcher::BuiltIn("$go".to_string()))
+        );
+        let Some(ProblemMatcher::Custom(custom)) = matcher(
+            r#"{
+                "owner": "lint",
+                "severity": "warning",
+                "pattern": [
+                    { "regexp": "^([^\\s].*)$", "file": 1 },
+                    { "regexp": "^\\s+(\\d+): (.*)$", "line": 1, "message": 2, "loop": true }
+                ]
+            }"#,
+        ) else {
+            panic!("expected a custom problem matcher");
+        };
+        assert_eq!(custom.severity, crate::ProblemSeverity::Warning);
+        assert!(matcher(r#"{ "owner": "lint" }"#).is_none());
+    }
+
     #[test]
     fn test_generate_label() {
         assert_eq!(
@@ -140,6 +140,8 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The terminal's task has finished and will not output anything else.
+    TaskFinished,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -824,6 +826,12 @@ impl Default for TerminalContent {
     }
 }
 
+/// Tracks how much of a terminal's output has been read by [`Terminal::read_output`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OutputCursor {
+    line: usize,
+}
+
 #[derive(PartialEq, Eq)]
 pub enum SelectionPhase {
     Selecting,
@@ -1670,6 +1678,53 @@ impl Terminal {
         lines
     }
 
+    /// Returns the logical lines written since the last call with the same `cursor`,
+    /// joining soft-wrapped rows.
+    ///
+    /// The line under the terminal cursor is still being written, so it is only returned
+    /// when `include_current_line` is set, e.g. once the task has finished.
+    /// Lines are tracked through the scrollback, so once it is full
+    /// (see [`MAX_SCROLL_HISTORY_LINES`] for tasks) no further lines are returned.
+    pub fn read_output(
+        &self,
+        cursor: &mut OutputCursor,
+        include_current_line: bool,
+    ) -> Vec<String> {
+        let term = self.term.lock_unfair();
+        let grid = term.grid();
+        let history_size = grid.history_size() as i32;
+        let topmost_line = grid.topmost_line().0;
+        let end_line = if include_current_line {
+            grid.bottommost_line().0 + 1
+        } else {
+            self.find_logical_line_start(grid, grid.cursor.point.line.0, topmost_line)
+        };
+
+        let mut lines = Vec::new();
+        let mut current_line = (cursor.line as i32 - history_size).max(topmost_line);
+        while current_line < end_line {
+            let mut logical_line_end = current_line;
+            while logical_line_end + 1 < end_line
+                && grid[Line(logical_line_end)][Column(grid.columns() - 1)]
+                    .flags
+                    .contains(Flags::WRAPLINE)
+            {
+                logical_line_end += 1;
+            }
+            let logical_line = self.construct_logical_line(grid, current_line, logical_line_end);
+            lines.push(logical_line.trim_end().to_string());
+            current_line = logical_line_end + 1;
+        }
+        cursor.line = cursor.line.max((end_line + history_size).max(0) as usize);
+
+        if include_current_line {
+            while lines.last().is_some_and(|line| line.is_empty()) {
+                lines.pop();
+            }
+        }
+        lines
+    }
+
     fn find_logical_line_start(&self, grid: &Grid<Cell>, current: i32, topmost: i32) -> i32 {
         let mut line_start = current;
         while line_start > topmost {
@@ -2259,6 +2314,7 @@ impl Terminal {
             // After the task summary is output once, no more text is appended to the terminal.
             unsafe { append_text_to_term(&mut self.term.lock(), &lines_to_show) };
         }
+        cx.emit(Event::TaskFinished);
 
         match task.spawned_task.hide {
             HideStrategy::Never => {}
@@ -3029,6 +3085,39 @@ mod tests {
         assert!(line2_col0, "Second line should start at column 0");
     }
 
+    #[gpui::test]
+    async fn test_read_output(cx: &mut TestAppContext) {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+        let mut cursor = OutputCursor::default();
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_output(b"line1\nline2\npart", cx);
+            assert_eq!(terminal.read_output(&mut cursor, false), ["line1", "line2"]);
+            assert!(terminal.read_output(&mut cursor, false).is_empty());
+
+            terminal.write_output(b"ial\nline3\n", cx);
+            assert_eq!(
+                terminal.read_output(&mut cursor, false),
+                ["partial", "line3"]
+            );
+
+            terminal.write_output(b"last", cx);
+            assert_eq!(terminal.read_output(&mut cursor, true), ["last"]);
+            assert!(terminal.read_output(&mut cursor, true).is_empty());
+        });
+    }
+
     #[gpui::test]
     async fn test_write_output_preserves_existing_crlf(cx: &mut TestAppContext) {
         let terminal = cx.new(|cx| {
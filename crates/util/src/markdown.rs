@@ -157,6 +157,26 @@ impl Display for MarkdownCodeBlock<'_> {
     }
 }
 
+/// Splits text that starts with a frontmatter block, delimited by `---` lines, into the block's
+/// contents and the text after it. Returns `None` if the text doesn't start with a terminated
+/// frontmatter block. A leading byte order mark and `\r\n` line endings are accepted.
+pub fn split_frontmatter(text: &str) -> Option<(&str, &str)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut lines = text.split_inclusive('\n');
+    let start = lines.next()?;
+    if start.trim_end() != "---" || !start.ends_with('\n') {
+        return None;
+    }
+    let mut offset = start.len();
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Some((&text[start.len()..offset], &text[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
 // Copied from `pulldown-cmark-to-cmark-20.0.0` with changed names.
 // https://github.com/Byron/pulldown-cmark-to-cmark/blob/3c850de2d3d1d79f19ca5f375e1089a653cf3ff7/src/lib.rs#L1063
 // Apache License 2.0, same as this code.
@@ -252,6 +272,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_split_frontmatter() {
+        assert_eq!(split_frontmatter("Body"), None);
+        assert_eq!(split_frontmatter("---\nkey: value\nBody"), None);
+        assert_eq!(
+            split_frontmatter("---\nkey: value\n---\nBody"),
+            Some(("key: value\n", "Body"))
+        );
+        assert_eq!(
+            split_frontmatter("\u{feff}---\r\nkey: value\r\n---\r\nBody\r\n"),
+            Some(("key: value\r\n", "Body\r\n"))
+        );
+        assert_eq!(split_frontmatter("---\n---"), Some(("", "")));
+    }
+
     #[test]
     fn test_count_max_consecutive_chars() {
         assert_eq!(
@@ -0,0 +1,545 @@
+use std::sync::Arc;
+
+use collections::HashMap;
+use editor::Editor;
+use gpui::{Action, App, Context, HighlightStyle, StyledText, Task, TextStyle, Window, actions};
+use language::{CharClassifier, Point};
+use picker::{Picker, PickerDelegate};
+use settings::Settings;
+use theme::ThemeSettings;
+use ui::{ActiveTheme, Div, FluentBuilder, ParentElement, Styled, StyledTypography, h_flex, rems};
+
+use crate::{
+    Vim, VimSettings,
+    state::{Mode, VimGlobals},
+};
+
+actions!(
+    vim,
+    [
+        /// Expands the abbreviation before the cursor without inserting a character.
+        ExpandAbbreviation
+    ]
+);
+
+/// Defines an abbreviation, or lists the abbreviations starting with the given text.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct Abbreviate {
+    pub scope: AbbreviationScope,
+    pub definition: String,
+}
+
+/// Removes an abbreviation.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct Unabbreviate {
+    pub scope: AbbreviationScope,
+    pub lhs: String,
+}
+
+/// Removes all abbreviations.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct ClearAbbreviations {
+    pub scope: AbbreviationScope,
+}
+
+/// The modes an abbreviation command applies to, as in `:ab`, `:iab` and `:cab`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AbbreviationScope {
+    All,
+    Insert,
+    Command,
+}
+
+impl AbbreviationScope {
+    fn includes_insert(self) -> bool {
+        self != AbbreviationScope::Command
+    }
+
+    fn includes_command(self) -> bool {
+        self != AbbreviationScope::Insert
+    }
+}
+
+/// Abbreviations defined with `:ab` and friends during this session.
+///
+/// These take precedence over `vim.abbreviations` from the settings. An entry of
+/// `None` records that a settings abbreviation was removed with `:una` or `:abc`.
+#[derive(Default)]
+pub(crate) struct Abbreviations {
+    insert: HashMap<String, Option<String>>,
+    command: HashMap<String, Option<String>>,
+}
+
+impl Abbreviations {
+    fn resolved(overrides: &HashMap<String, Option<String>>, cx: &App) -> HashMap<String, String> {
+        let mut abbreviations = VimSettings::get_global(cx).abbreviations.clone();
+        for (lhs, rhs) in overrides {
+            match rhs {
+                Some(rhs) => abbreviations.insert(lhs.clone(), rhs.clone()),
+                None => abbreviations.remove(lhs),
+            };
+        }
+        abbreviations
+    }
+
+    fn insert_abbreviations(cx: &App) -> HashMap<String, String> {
+        Self::resolved(&cx.global::<VimGlobals>().abbreviations.insert, cx)
+    }
+
+    fn command_abbreviations(cx: &App) -> HashMap<String, String> {
+        Self::resolved(&cx.global::<VimGlobals>().abbreviations.command, cx)
+    }
+
+    fn define(&mut self, scope: AbbreviationScope, lhs: &str, rhs: &str) {
+        if scope.includes_insert() {
+            self.insert.insert(lhs.to_string(), Some(rhs.to_string()));
+        }
+        if scope.includes_command() {
+            self.command.insert(lhs.to_string(), Some(rhs.to_string()));
+        }
+    }
+
+    fn remove(&mut self, scope: AbbreviationScope, lhs: &str) {
+        if scope.includes_insert() {
+            self.insert.insert(lhs.to_string(), None);
+        }
+        if scope.includes_command() {
+            self.command.insert(lhs.to_string(), None);
+        }
+    }
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, _: &ExpandAbbreviation, window, cx| {
+        vim.expand_abbreviation("", window, cx);
+    });
+
+    Vim::action(editor, cx, |vim, action: &Abbreviate, window, cx| {
+        let definition = action.definition.trim_start();
+        let (lhs, rhs) = definition
+            .split_once(char::is_whitespace)
+            .map(|(lhs, rhs)| (lhs, rhs.trim_start()))
+            .unwrap_or((definition, ""));
+
+        if rhs.is_empty() {
+            vim.show_abbreviations(action.scope, lhs.to_string(), window, cx);
+            return;
+        }
+
+        let classifier = CharClassifier::new(None);
+        if !lhs.chars().last().is_some_and(|c| classifier.is_word(c)) {
+            let _ = window.prompt(
+                gpui::PromptLevel::Critical,
+                &format!("Invalid argument: {}", lhs),
+                None,
+                &["Cancel"],
+                cx,
+            );
+            return;
+        }
+
+        Vim::globals(cx)
+            .abbreviations
+            .define(action.scope, lhs, rhs);
+    });
+
+    Vim::action(editor, cx, |_, action: &Unabbreviate, window, cx| {
+        let lhs = action.lhs.trim();
+        let exists = (action.scope.includes_insert()
+            && Abbreviations::insert_abbreviations(cx).contains_key(lhs))
+            || (action.scope.includes_command()
+                && Abbreviations::command_abbreviations(cx).contains_key(lhs));
+        if !exists {
+            let _ = window.prompt(
+                gpui::PromptLevel::Critical,
+                &format!("No such abbreviation: {}", lhs),
+                None,
+                &["Cancel"],
+                cx,
+            );
+            return;
+        }
+        Vim::globals(cx).abbreviations.remove(action.scope, lhs);
+    });
+
+    Vim::action(editor, cx, |_, action: &ClearAbbreviations, _, cx| {
+        let mut defined = Vec::new();
+        if action.scope.includes_insert() {
+            defined.extend(Abbreviations::insert_abbreviations(cx).into_keys());
+        }
+        if action.scope.includes_command() {
+            defined.extend(Abbreviations::command_abbreviations(cx).into_keys());
+        }
+        let abbreviations = &mut Vim::globals(cx).abbreviations;
+        for lhs in defined {
+            abbreviations.remove(action.scope, &lhs);
+        }
+    });
+}
+
+/// Finds the abbreviation that ends `text`.
+///
+/// Like Vim, an abbreviation made only of keyword characters must not be preceded
+/// by another keyword character, and one that starts with a non-keyword character
+/// must be preceded by whitespace or the start of the line.
+fn find_abbreviation<'a>(
+    text: &str,
+    abbreviations: &'a HashMap<String, String>,
+    classifier: &CharClassifier,
+) -> Option<(&'a str, &'a str)> {
+    abbreviations
+        .iter()
+        .filter(|(lhs, _)| !lhs.is_empty() && text.ends_with(lhs.as_str()))
+        .filter(|(lhs, _)| {
+            let Some(previous) = text[..text.len() - lhs.len()].chars().next_back() else {
+                return true;
+            };
+            let starts_with_keyword = lhs.chars().next().is_some_and(|c| classifier.is_word(c));
+            previous.is_whitespace() || (starts_with_keyword && !classifier.is_word(previous))
+        })
+        .max_by_key(|(lhs, _)| lhs.len())
+        .map(|(lhs, rhs)| (lhs.as_str(), rhs.as_str()))
+}
+
+/// Whether `command` is one of the abbreviation commands, whose arguments must
+/// not be expanded so that abbreviations can be redefined and removed.
+fn is_abbreviation_command(command: &str) -> bool {
+    [
+        ("ab", "breviate"),
+        ("iab", "brev"),
+        ("cab", "brev"),
+        ("una", "bbreviate"),
+        ("iuna", "bbrev"),
+        ("cuna", "bbrev"),
+        ("abc", "lear"),
+        ("iabc", "lear"),
+        ("cabc", "lear"),
+    ]
+    .iter()
+    .any(|(prefix, suffix)| {
+        command
+            .strip_prefix(prefix)
+            .is_some_and(|rest| suffix.starts_with(rest))
+    })
+}
+
+/// Expands command-line abbreviations in `input`.
+///
+/// Each word followed by a non-keyword character is checked, and so is the last
+/// word, since Vim also expands abbreviations when the command is executed.
+pub(crate) fn expand_command_abbreviations(input: &str, cx: &App) -> Option<String> {
+    let abbreviations = Abbreviations::command_abbreviations(cx);
+    if abbreviations.is_empty() {
+        return None;
+    }
+    let classifier = CharClassifier::new(None);
+    let command = input
+        .trim_start_matches(|c: char| !classifier.is_word(c))
+        .split(|c: char| !classifier.is_word(c))
+        .next()
+        .unwrap_or_default();
+    if is_abbreviation_command(command) {
+        return None;
+    }
+
+    let mut expanded = String::new();
+    let mut copied = 0;
+    let triggers = input
+        .char_indices()
+        .filter(|(_, c)| !classifier.is_word(*c))
+        .map(|(ix, _)| ix)
+        .chain([input.len()]);
+    for ix in triggers {
+        if let Some((lhs, rhs)) = find_abbreviation(&input[..ix], &abbreviations, &classifier)
+            && ix - lhs.len() >= copied
+        {
+            expanded.push_str(&input[copied..ix - lhs.len()]);
+            expanded.push_str(rhs);
+            copied = ix;
+        }
+    }
+    if copied == 0 {
+        return None;
+    }
+    expanded.push_str(&input[copied..]);
+    Some(expanded)
+}
+
+impl Vim {
+    /// Expands the insert-mode abbreviation before each cursor. `trigger` is the
+    /// non-keyword text that was just typed after it, or empty when leaving
+    /// insert mode.
+    pub(crate) fn expand_abbreviation(
+        &mut self,
+        trigger: &str,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.mode != Mode::Insert || Vim::globals(cx).dot_replaying {
+            return;
+        }
+        let abbreviations = Abbreviations::insert_abbreviations(cx);
+        if abbreviations.is_empty() {
+            return;
+        }
+
+        let insertion = self.update_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let newest_id = editor.selections.newest_anchor().id;
+            let mut edits = Vec::new();
+            let mut insertion = None;
+            for selection in editor.selections.all::<Point>(&editor.display_snapshot(cx)) {
+                if !selection.is_empty() {
+                    continue;
+                }
+                let head = selection.head();
+                let classifier = snapshot.char_classifier_at(head);
+                if trigger.chars().any(|c| classifier.is_word(c)) {
+                    return None;
+                }
+                let line = snapshot
+                    .text_for_range(Point::new(head.row, 0)..head)
+                    .collect::<String>();
+                let Some(text) = line.strip_suffix(trigger) else {
+                    continue;
+                };
+                let Some((lhs, rhs)) = find_abbreviation(text, &abbreviations, &classifier) else {
+                    continue;
+                };
+
+                let start = Point::new(head.row, (text.len() - lhs.len()) as u32);
+                let end = Point::new(head.row, text.len() as u32);
+                edits.push((start..end, rhs.to_string()));
+                if selection.id == newest_id {
+                    let replaced = lhs.encode_utf16().count() + trigger.encode_utf16().count();
+                    insertion = Some((format!("{rhs}{trigger}"), -(replaced as isize)..0));
+                }
+            }
+            if !edits.is_empty() {
+                editor.edit(edits, cx);
+            }
+            insertion
+        });
+
+        // Record the expansion as an insertion that replaces the abbreviation,
+        // so that `.` and macros reproduce it even if it is later removed.
+        if let Some((text, range)) = insertion.flatten() {
+            Vim::globals(cx).observe_insertion(&text.into(), Some(range));
+        }
+    }
+
+    fn show_abbreviations(
+        &mut self,
+        scope: AbbreviationScope,
+        prefix: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace(window, cx) else {
+            return;
+        };
+
+        let insert = Abbreviations::insert_abbreviations(cx);
+        let command = Abbreviations::command_abbreviations(cx);
+        let mut matches = Vec::new();
+        if scope.includes_insert() {
+            for (lhs, rhs) in &insert {
+                let mode = if command.get(lhs) == Some(rhs) && scope.includes_command() {
+                    '!'
+                } else {
+                    'i'
+                };
+                matches.push(AbbreviationMatch {
+                    mode,
+                    lhs: lhs.clone(),
+                    rhs: rhs.clone(),
+                });
+            }
+        }
+        if scope.includes_command() {
+            for (lhs, rhs) in &command {
+                if scope.includes_insert() && insert.get(lhs) == Some(rhs) {
+                    continue;
+                }
+                matches.push(AbbreviationMatch {
+                    mode: 'c',
+                    lhs: lhs.clone(),
+                    rhs: rhs.clone(),
+                });
+            }
+        }
+        matches.retain(|abbreviation| abbreviation.lhs.starts_with(&prefix));
+        matches.sort_by(|a, b| a.lhs.cmp(&b.lhs).then(a.mode.cmp(&b.mode)));
+
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(window, cx, move |window, cx| {
+                let delegate = AbbreviationsViewDelegate {
+                    selected_index: 0,
+                    matches,
+                };
+                Picker::nonsearchable_uniform_list(delegate, window, cx)
+                    .width(rems(36.))
+                    .modal(true)
+            });
+        });
+    }
+}
+
+struct AbbreviationMatch {
+    mode: char,
+    lhs: String,
+    rhs: String,
+}
+
+pub struct AbbreviationsViewDelegate {
+    selected_index: usize,
+    matches: Vec<AbbreviationMatch>,
+}
+
+impl PickerDelegate for AbbreviationsViewDelegate {
+    type ListItem = Div;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+        cx.notify();
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        Arc::default()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<ui::SharedString> {
+        Some("No abbreviation found".into())
+    }
+
+    fn update_matches(
+        &mut self,
+        _: String,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _: bool, _: &mut Window, _: &mut Context<Picker<Self>>) {}
+
+    fn dismissed(&mut self, _: &mut Window, _: &mut Context<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let abbreviation = self.matches.get(ix)?;
+
+        let mut output = String::new();
+        let mut runs = Vec::new();
+        output.push(abbreviation.mode);
+        runs.push((
+            0..output.len(),
+            HighlightStyle::color(cx.theme().colors().text_muted),
+        ));
+        output.push_str("  ");
+        let start = output.len();
+        output.push_str(&abbreviation.lhs);
+        runs.push((
+            start..output.len(),
+            HighlightStyle::color(cx.theme().colors().text_accent),
+        ));
+        output.push_str("  ");
+        output.push_str(&abbreviation.rhs);
+
+        let theme = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().editor_foreground,
+            font_family: theme.buffer_font.family.clone(),
+            font_features: theme.buffer_font.features.clone(),
+            font_fallbacks: theme.buffer_font.fallbacks.clone(),
+            font_size: theme.buffer_font_size(cx).into(),
+            line_height: (theme.line_height() * theme.buffer_font_size(cx)).into(),
+            font_weight: theme.buffer_font.weight,
+            font_style: theme.buffer_font.style,
+            ..Default::default()
+        };
+
+        Some(
+            h_flex()
+                .when(selected, |el| el.bg(cx.theme().colors().element_selected))
+                .font_buffer(cx)
+                .text_buffer(cx)
+                .h(theme.buffer_font_size(cx) * theme.line_height())
+                .px_2()
+                .gap_1()
+                .child(StyledText::new(output).with_default_highlights(&text_style, runs)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use collections::HashMap;
+    use settings::SettingsStore;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_insert_abbreviation(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇ", Mode::Normal);
+        cx.simulate_keystrokes(": i a b space t e h space t h e enter");
+        cx.simulate_keystrokes("i t e h space s t e h space t e h");
+        cx.assert_state("the steh tehˇ", Mode::Insert);
+        cx.simulate_keystrokes("escape");
+        cx.assert_state("the steh thˇe", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_abbreviation_dot_repeat(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇ", Mode::Normal);
+        cx.simulate_keystrokes(": a b space s e p space , enter");
+        cx.simulate_keystrokes("a s e p . escape");
+        cx.assert_state(",ˇ.", Mode::Normal);
+
+        cx.simulate_keystrokes(": u n a space s e p enter");
+        cx.simulate_keystrokes("$ .");
+        cx.assert_state(",.,ˇ.", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_abbreviation_settings(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings(cx, |s| {
+                let mut abbreviations = HashMap::default();
+                abbreviations.insert("#i".into(), "#include".into());
+                abbreviations.insert("adn".into(), "and".into());
+                s.vim.get_or_insert_default().abbreviations = Some(abbreviations);
+            });
+        });
+
+        cx.set_state("ˇ", Mode::Normal);
+        cx.simulate_keystrokes("i # i space a # i space b a d n . escape");
+        cx.assert_state("#include a#i badnˇ.", Mode::Normal);
+
+        cx.simulate_keystrokes(": i a b c enter");
+        cx.simulate_keystrokes("o a d n space escape");
+        cx.assert_state("#include a#i badn.\nadnˇ ", Mode::Normal);
+    }
+}
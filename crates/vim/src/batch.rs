@@ -0,0 +1,646 @@
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use editor::{Editor, SelectionEffects};
+use futures::channel::oneshot;
+use gpui::{
+    Action, App, AsyncWindowContext, Context, Entity, Focusable, PromptLevel, Task, WeakEntity,
+    Window,
+};
+use language::{Anchor, Bias, Buffer, Point};
+use project::{Project, ProjectPath};
+use util::{ResultExt, paths::PathMatcher, rel_path::RelPath};
+use workspace::{
+    ItemHandle, Pane, SaveIntent, Workspace, item::ItemBufferKind,
+    notifications::DetachAndPromptErr,
+};
+
+use crate::{
+    Vim, VimAddon,
+    command::{VimNorm, WrappedAction},
+    normal::search::ReplaceCommand,
+    state::VimGlobals,
+};
+
+/// Replaces the argument list with the given files, or shows it when no files are given.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct VimArgs {
+    pub files: String,
+}
+
+/// Edits another file from the argument list.
+#[derive(Clone, Copy, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) enum GoToArgument {
+    Next,
+    Previous,
+    First,
+    Last,
+}
+
+/// Runs a command on every buffer, window, argument or search result.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct BatchCommand {
+    pub targets: BatchTargets,
+    pub action: WrappedAction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum BatchTargets {
+    /// `:argdo`, every file in the argument list.
+    Arguments,
+    /// `:bufdo`, every open buffer.
+    Buffers,
+    /// `:windo`, the active item of every pane.
+    Windows,
+    /// `:cdo`, every project search result (or diagnostic if there is no search).
+    Entries,
+    /// `:cfdo`, every file containing a project search result (or diagnostic).
+    Files,
+}
+
+/// The files given to the most recent `:args` command.
+#[derive(Default)]
+pub(crate) struct ArgumentList {
+    paths: Vec<ProjectPath>,
+    index: usize,
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, action: &VimArgs, window, cx| {
+        vim.set_arguments(&action.files, window, cx)
+    });
+    Vim::action(editor, cx, |vim, action: &GoToArgument, window, cx| {
+        vim.go_to_argument(*action, window, cx)
+    });
+    Vim::action(editor, cx, |vim, action: &BatchCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+}
+
+impl BatchTargets {
+    /// Splits a `:bufdo`-style command into its targets and the command to run on each.
+    pub(crate) fn parse(query: &str) -> Option<(Self, &str)> {
+        let (name, command) = query.split_once(char::is_whitespace)?;
+        let targets = match name.strip_suffix('!').unwrap_or(name) {
+            "argdo" => Self::Arguments,
+            "bufd" | "bufdo" => Self::Buffers,
+            "windo" => Self::Windows,
+            "cdo" => Self::Entries,
+            "cfd" | "cfdo" => Self::Files,
+            _ => return None,
+        };
+        Some((targets, command.trim_start()))
+    }
+}
+
+enum BatchTarget {
+    Item {
+        pane: Entity<Pane>,
+        item: Box<dyn ItemHandle>,
+    },
+    Location {
+        path: ProjectPath,
+        position: Option<TargetPosition>,
+    },
+}
+
+/// A position in a buffer that follows the edits made to it by earlier targets.
+struct TargetPosition {
+    buffer: Entity<Buffer>,
+    anchor: Anchor,
+}
+
+impl BatchCommand {
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let Some(workspace) = vim.workspace(window, cx) else {
+            return;
+        };
+        let targets = self.targets.collect(&workspace, cx);
+        let action = self.action.boxed_clone();
+        let workspace = workspace.downgrade();
+        cx.spawn_in(window, async move |_, cx| {
+            let targets = targets.await?;
+            for target in targets {
+                let (pane, item) = target.visit(&workspace, cx).await?;
+                let Some(editor) = cx.update(|_, cx| item.act_as::<Editor>(cx))? else {
+                    continue;
+                };
+                let Some(vim) = cx.update(|_, cx| {
+                    editor
+                        .read(cx)
+                        .addon::<VimAddon>()
+                        .map(|addon| addon.entity.clone())
+                })?
+                else {
+                    continue;
+                };
+
+                let task = vim.update_in(cx, |vim, window, cx| {
+                    vim.run_batched_action(&*action, window, cx)
+                })?;
+                if let Some(task) = task {
+                    task.await.log_err();
+                } else {
+                    // Actions are dispatched through the rendered dispatch tree, which only
+                    // contains the newly focused editor once the window is drawn again.
+                    next_frame(cx).await?;
+                    cx.update(|window, cx| {
+                        editor
+                            .focus_handle(cx)
+                            .dispatch_action(&*action, window, cx)
+                    })?;
+                }
+
+                let project =
+                    workspace.read_with(cx, |workspace, _| workspace.project().clone())?;
+                if !Pane::save_item(project, &pane.downgrade(), &*item, SaveIntent::SaveAll, cx)
+                    .await?
+                {
+                    break;
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to run command", window, cx, |_, _, _| None);
+    }
+}
+
+impl BatchTargets {
+    fn collect(
+        self,
+        workspace: &Entity<Workspace>,
+        cx: &mut App,
+    ) -> Task<Result<Vec<BatchTarget>>> {
+        match self {
+            Self::Arguments => {
+                let paths = &cx.global::<VimGlobals>().argument_list.paths;
+                if paths.is_empty() {
+                    return Task::ready(Err(anyhow::anyhow!("Argument list is empty")));
+                }
+                Task::ready(Ok(paths
+                    .iter()
+                    .map(|path| BatchTarget::Location {
+                        path: path.clone(),
+                        position: None,
+                    })
+                    .collect()))
+            }
+            Self::Buffers => {
+                let mut buffers = HashSet::default();
+                let mut targets = Vec::new();
+                for pane in workspace.read(cx).panes() {
+                    for item in pane.read(cx).items() {
+                        if item.buffer_kind(cx) != ItemBufferKind::Singleton {
+                            continue;
+                        }
+                        let Some(buffer) = item
+                            .act_as::<Editor>(cx)
+                            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+                        else {
+                            continue;
+                        };
+                        if buffers.insert(buffer.entity_id()) {
+                            targets.push(BatchTarget::Item {
+                                pane: pane.clone(),
+                                item: item.boxed_clone(),
+                            });
+                        }
+                    }
+                }
+                Task::ready(Ok(targets))
+            }
+            Self::Windows => Task::ready(Ok(workspace
+                .read(cx)
+                .panes()
+                .iter()
+                .filter_map(|pane| {
+                    Some(BatchTarget::Item {
+                        pane: pane.clone(),
+                        item: pane.read(cx).active_item()?,
+                    })
+                })
+                .collect())),
+            Self::Entries | Self::Files => {
+                let current = workspace
+                    .read(cx)
+                    .quickfix_lists()
+                    .read(cx)
+                    .current()
+                    .cloned();
+                let list = match current {
+                    Some(list) => Task::ready(Ok(list)),
+                    None => {
+                        workspace.update(cx, |workspace, cx| quickfix::default_list(workspace, cx))
+                    }
+                };
+                let project = workspace.read(cx).project().clone();
+                cx.spawn(async move |cx| {
+                    let mut entries = list.await?.entries;
+                    if entries.is_empty() {
+                        anyhow::bail!("No errors");
+                    }
+                    if self == Self::Files {
+                        let mut seen = HashSet::default();
+                        entries.retain(|entry| seen.insert(entry.path.clone()));
+                    }
+
+                    // Anchor the entries before running the command on any of them, as it
+                    // may edit the buffers of the entries that follow.
+                    let mut buffers = HashMap::default();
+                    let mut targets = Vec::with_capacity(entries.len());
+                    for entry in entries {
+                        let buffer = match buffers.get(&entry.path) {
+                            Some(buffer) => buffer.clone(),
+                            None => {
+                                let buffer = project
+                                    .update(cx, |project, cx| {
+                                        project.open_buffer(entry.path.clone(), cx)
+                                    })
+                                    .await?;
+                                buffers.insert(entry.path.clone(), buffer.clone());
+                                buffer
+                            }
+                        };
+                        let anchor = buffer.read_with(cx, |buffer, _| {
+                            buffer.anchor_before(buffer.clip_point(entry.position, Bias::Left))
+                        });
+                        targets.push(BatchTarget::Location {
+                            path: entry.path,
+                            position: Some(TargetPosition { buffer, anchor }),
+                        });
+                    }
+                    Ok(targets)
+                })
+            }
+        }
+    }
+}
+
+impl BatchTarget {
+    /// Opens and focuses the target, returning the item and the pane it is in.
+    async fn visit(
+        self,
+        workspace: &WeakEntity<Workspace>,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<(Entity<Pane>, Box<dyn ItemHandle>)> {
+        match self {
+            Self::Item { pane, item } => {
+                pane.update_in(cx, |pane, window, cx| {
+                    if let Some(ix) = pane.index_for_item(&*item) {
+                        pane.activate_item(ix, true, true, window, cx);
+                    }
+                })?;
+                Ok((pane, item))
+            }
+            Self::Location { path, position } => {
+                let item = workspace
+                    .update_in(cx, |workspace, window, cx| {
+                        workspace.open_path(path, None, true, window, cx)
+                    })?
+                    .await?;
+                let pane = workspace.update_in(cx, |workspace, window, cx| {
+                    if let Some(position) = position
+                        && let Some(editor) = item.act_as::<Editor>(cx)
+                    {
+                        let point = position
+                            .buffer
+                            .read(cx)
+                            .summary_for_anchor::<Point>(&position.anchor);
+                        editor.update(cx, |editor, cx| {
+                            editor.change_selections(
+                                SelectionEffects::default(),
+                                window,
+                                cx,
+                                |s| s.select_ranges([point..point]),
+                            );
+                        });
+                    }
+                    workspace
+                        .pane_for(&*item)
+                        .unwrap_or_else(|| workspace.active_pane().clone())
+                })?;
+                Ok((pane, item))
+            }
+        }
+    }
+}
+
+/// Waits until the window is drawn again.
+async fn next_frame(cx: &mut AsyncWindowContext) -> Result<()> {
+    let (tx, rx) = oneshot::channel();
+    cx.update(|window, _| {
+        window.on_next_frame(move |_, _| {
+            tx.send(()).ok();
+        });
+        window.refresh();
+    })?;
+    rx.await?;
+    Ok(())
+}
+
+impl Vim {
+    /// Starts the actions that finish asynchronously, so that `:bufdo` and friends
+    /// can wait for them before saving. Other actions are dispatched to the editor.
+    fn run_batched_action(
+        &mut self,
+        action: &dyn Action,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<Result<()>>> {
+        if let Some(action) = action.as_any().downcast_ref::<VimNorm>() {
+            let task = self.normal_command(action, window, cx);
+            return Some(cx.spawn(async move |_, _| {
+                task.await;
+                Ok(())
+            }));
+        }
+        if let Some(action) = action.as_any().downcast_ref::<ReplaceCommand>() {
+            return Some(self.substitute(action, window, cx));
+        }
+        None
+    }
+
+    fn set_arguments(&mut self, files: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace(window, cx) else {
+            return;
+        };
+        let files = files.split_whitespace().collect::<Vec<_>>();
+        if files.is_empty() {
+            let path_style = workspace.read(cx).path_style(cx);
+            let list = &cx.global::<VimGlobals>().argument_list;
+            let label = list
+                .paths
+                .iter()
+                .enumerate()
+                .map(|(ix, path)| {
+                    let path = path.path.display(path_style);
+                    if ix == list.index {
+                        format!("[{path}]")
+                    } else {
+                        path.into_owned()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.status_label = Some(label.into());
+            cx.notify();
+            return;
+        }
+
+        let paths = match expand_arguments(&files, workspace.read(cx).project(), cx) {
+            Ok(paths) => paths,
+            Err(err) => {
+                let _ = window.prompt(
+                    PromptLevel::Critical,
+                    &err.to_string(),
+                    None,
+                    &["Cancel"],
+                    cx,
+                );
+                return;
+            }
+        };
+        Vim::globals(cx).argument_list = ArgumentList { paths, index: 0 };
+        self.edit_argument(0, window, cx);
+    }
+
+    fn go_to_argument(
+        &mut self,
+        action: GoToArgument,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let list = &Vim::globals(cx).argument_list;
+        let len = list.paths.len();
+        let index = match action {
+            _ if len == 0 => Err("Argument list is empty"),
+            GoToArgument::Next if list.index + 1 >= len => Err("Cannot go beyond last file"),
+            GoToArgument::Next => Ok(list.index + 1),
+            GoToArgument::Previous if list.index == 0 => Err("Cannot go before first file"),
+            GoToArgument::Previous => Ok(list.index - 1),
+            GoToArgument::First => Ok(0),
+            GoToArgument::Last => Ok(len - 1),
+        };
+        match index {
+            Ok(index) => self.edit_argument(index, window, cx),
+            Err(message) => {
+                let _ = window.prompt(PromptLevel::Critical, message, None, &["Cancel"], cx);
+            }
+        }
+    }
+
+    fn edit_argument(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace(window, cx) else {
+            return;
+        };
+        let list = &mut Vim::globals(cx).argument_list;
+        let Some(path) = list.paths.get(index).cloned() else {
+            return;
+        };
+        list.index = index;
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .open_path(path, None, true, window, cx)
+                .detach_and_prompt_err("Failed to open file", window, cx, |_, _, _| None);
+        });
+    }
+}
+
+/// Resolves the arguments to `:args`, expanding globs against the project's files.
+fn expand_arguments(
+    files: &[&str],
+    project: &Entity<Project>,
+    cx: &App,
+) -> Result<Vec<ProjectPath>> {
+    let project = project.read(cx);
+    let path_style = project.path_style(cx);
+    let mut paths = Vec::new();
+    for file in files {
+        if !file.contains(['*', '?', '[', '{']) {
+            let path = match project.find_project_path(Path::new(file), cx) {
+                Some(path) => path,
+                None => {
+                    let worktree = project
+                        .visible_worktrees(cx)
+                        .next()
+                        .context("No worktree to open files in")?;
+                    ProjectPath {
+                        worktree_id: worktree.read(cx).id(),
+                        path: RelPath::new(Path::new(file), path_style)?.into_arc(),
+                    }
+                }
+            };
+            paths.push(path);
+            continue;
+        }
+
+        let matcher = PathMatcher::new([file], path_style)?;
+        let len = paths.len();
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            paths.extend(
+                worktree
+                    .files(false, 0)
+                    .filter(|entry| matcher.is_match(&entry.path))
+                    .map(|entry| ProjectPath {
+                        worktree_id: worktree.id(),
+                        path: entry.path.clone(),
+                    }),
+            );
+        }
+        if paths.len() == len {
+            anyhow::bail!("No match: {file}");
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use editor::Editor;
+    use gpui::TestAppContext;
+    use language::Point;
+    use util::path;
+    use workspace::{
+        OpenOptions,
+        quickfix::{QuickfixEntry, QuickfixList},
+    };
+
+    use crate::{VimAddon, test::VimTestContext};
+
+    async fn add_files(cx: &mut VimTestContext) {
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(path!("/root/dir/a.rs"), b"a\n".to_vec())
+            .await;
+        fs.as_fake()
+            .insert_file(path!("/root/dir/b.rs"), b"b\n".to_vec())
+            .await;
+        cx.run_until_parked();
+    }
+
+    fn active_path(cx: &mut VimTestContext) -> PathBuf {
+        cx.workspace(|workspace, _, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            let buffer = editor.read(cx).buffer().read(cx).as_singleton().unwrap();
+            buffer
+                .read(cx)
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx)
+        })
+    }
+
+    #[gpui::test]
+    async fn test_argument_list(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        add_files(&mut cx).await;
+
+        cx.simulate_keystrokes(": a r g s space d i r / * . r s enter");
+        assert_eq!(active_path(&mut cx), Path::new(path!("/root/dir/a.rs")));
+
+        cx.simulate_keystrokes(": n e x t enter");
+        assert_eq!(active_path(&mut cx), Path::new(path!("/root/dir/b.rs")));
+
+        cx.simulate_keystrokes(": a r g s enter");
+        let label = cx.workspace(|workspace, _, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            let vim = editor.read(cx).addon::<VimAddon>().unwrap().entity.clone();
+            vim.read(cx).status_label.clone()
+        });
+        assert_eq!(
+            label.as_deref(),
+            Some(path!("dir/a.rs [dir/b.rs] dir/file.rs"))
+        );
+
+        cx.simulate_keystrokes(": p r e v enter");
+        assert_eq!(active_path(&mut cx), Path::new(path!("/root/dir/a.rs")));
+
+        cx.simulate_keystrokes(": a r g d o space n o r m space A x enter");
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        assert_eq!(
+            fs.load(Path::new(path!("/root/dir/a.rs"))).await.unwrap(),
+            "ax\n"
+        );
+        assert_eq!(
+            fs.load(Path::new(path!("/root/dir/b.rs"))).await.unwrap(),
+            "bx\n"
+        );
+        assert_eq!(
+            fs.load(Path::new(path!("/root/dir/file.rs")))
+                .await
+                .unwrap(),
+            "x"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_bufdo(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        add_files(&mut cx).await;
+
+        for path in [path!("/root/dir/a.rs"), path!("/root/dir/b.rs")] {
+            cx.workspace(|workspace, window, cx| {
+                workspace.open_abs_path(PathBuf::from(path), OpenOptions::default(), window, cx)
+            })
+            .await
+            .unwrap();
+        }
+
+        cx.simulate_keystrokes(": b u f d o space % s / a / c / enter");
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        assert_eq!(
+            fs.load(Path::new(path!("/root/dir/a.rs"))).await.unwrap(),
+            "c\n"
+        );
+        assert_eq!(
+            fs.load(Path::new(path!("/root/dir/b.rs"))).await.unwrap(),
+            "b\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_cdo_follows_earlier_edits(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(path!("/root/dir/c.rs"), b"ab ab\n".to_vec())
+            .await;
+        cx.run_until_parked();
+
+        cx.workspace(|workspace, _, cx| {
+            let path = workspace
+                .project()
+                .read(cx)
+                .find_project_path(Path::new(path!("/root/dir/c.rs")), cx)
+                .unwrap();
+            let entries = [Point::new(0, 0), Point::new(0, 3)]
+                .into_iter()
+                .map(|position| QuickfixEntry {
+                    path: path.clone(),
+                    position,
+                    text: "ab".into(),
+                    severity: None,
+                })
+                .collect();
+            workspace.quickfix_lists().update(cx, |lists, cx| {
+                lists.push(QuickfixList::new("ab", entries), cx)
+            });
+        });
+
+        // The second entry moves along with the text inserted at the first one.
+        cx.simulate_keystrokes(": c d o space n o r m space i z enter");
+        assert_eq!(
+            fs.load(Path::new(path!("/root/dir/c.rs"))).await.unwrap(),
+            "zab zab\n"
+        );
+    }
+}
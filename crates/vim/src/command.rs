@@ -28,7 +28,7 @@ use std::{
     sync::OnceLock,
     time::Instant,
 };
-use task::{HideStrategy, RevealStrategy, SpawnInTerminal, TaskId};
+use task::{HideStrategy, ProblemMatcher, RevealStrategy, SpawnInTerminal, TaskId};
 use ui::ActiveTheme;
 use util::{
     ResultExt,
@@ -41,6 +41,11 @@ use zed_actions::{OpenDocs, RevealTarget};
 
 use crate::{
     ToggleMarksView, ToggleRegistersView, Vim, VimSettings,
+    abbreviations::{
+        Abbreviate, AbbreviationScope, ClearAbbreviations, Unabbreviate,
+        expand_command_abbreviations,
+    },
+    batch::{BatchCommand, BatchTargets, GoToArgument, VimArgs},
     motion::{EndOfDocument, Motion, MotionKind, StartOfDocument},
     normal::{
         JoinLines,
@@ -252,7 +257,7 @@ struct VimRead {
 
 #[derive(Clone, PartialEq, Action)]
 #[action(namespace = vim, no_json, no_register)]
-struct VimNorm {
+pub(crate) struct VimNorm {
     pub range: Option<CommandRange>,
     pub command: String,
     /// Places cursors at beginning of each given row.
@@ -261,7 +266,7 @@ struct VimNorm {
 }
 
 #[derive(Debug)]
-struct WrappedAction(Box<dyn Action>);
+pub(crate) struct WrappedAction(pub(crate) Box<dyn Action>);
 
 impl PartialEq for WrappedAction {
     fn eq(&self, other: &Self) -> bool {
@@ -775,83 +780,7 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     });
 
     Vim::action(editor, cx, |vim, action: &VimNorm, window, cx| {
-        let keystrokes = action
-            .command
-            .chars()
-            .map(|c| Keystroke::parse(&c.to_string()).unwrap())
-            .collect();
-        vim.switch_mode(Mode::Normal, true, window, cx);
-        if let Some(override_rows) = &action.override_rows {
-            vim.update_editor(cx, |_, editor, cx| {
-                editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
-                    s.replace_cursors_with(|map| {
-                        override_rows
-                            .iter()
-                            .map(|row| Point::new(*row, 0).to_display_point(map))
-                            .collect()
-                    });
-                });
-            });
-        } else if let Some(range) = &action.range {
-            let result = vim.update_editor(cx, |vim, editor, cx| {
-                let range = range.buffer_range(vim, editor, window, cx)?;
-                editor.change_selections(
-                    SelectionEffects::no_scroll().nav_history(false),
-                    window,
-                    cx,
-                    |s| {
-                        s.select_ranges(
-                            (range.start.0..=range.end.0)
-                                .map(|line| Point::new(line, 0)..Point::new(line, 0)),
-                        );
-                    },
-                );
-                anyhow::Ok(())
-            });
-            if let Some(Err(err)) = result {
-                log::error!("Error selecting range: {}", err);
-                return;
-            }
-        };
-
-        let Some(workspace) = vim.workspace(window, cx) else {
-            return;
-        };
-        let task = workspace.update(cx, |workspace, cx| {
-            workspace.send_keystrokes_impl(keystrokes, window, cx)
-        });
-        let had_range = action.range.is_some();
-        let had_override = action.override_rows.is_some();
-
-        cx.spawn_in(window, async move |vim, cx| {
-            task.await;
-            vim.update_in(cx, |vim, window, cx| {
-                if matches!(vim.mode, Mode::Insert | Mode::Replace) {
-                    vim.normal_before(&Default::default(), window, cx);
-                } else {
-                    vim.switch_mode(Mode::Normal, true, window, cx);
-                }
-                if had_override || had_range {
-                    vim.update_editor(cx, |_, editor, cx| {
-                        editor.change_selections(SelectionEffects::default(), window, cx, |s| {
-                            s.select_anchor_ranges([s.newest_anchor().range()]);
-                        });
-                        if let Some(tx_id) = editor
-                            .buffer()
-                            .update(cx, |multi, cx| multi.last_transaction_id(cx))
-                        {
-                            let last_sel = editor.selections.disjoint_anchors_arc();
-                            editor.modify_transaction_selection_history(tx_id, |old| {
-                                old.0 = old.0.get(..1).unwrap_or(&[]).into();
-                                old.1 = Some(last_sel);
-                            });
-                        }
-                    });
-                }
-            })
-            .log_err();
-        })
-        .detach();
+        vim.normal_command(action, window, cx).detach();
     });
 
     Vim::action(editor, cx, |vim, _: &CountCommand, window, cx| {
@@ -981,6 +910,14 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
 
     Vim::action(editor, cx, |vim, action: &ShellExec, window, cx| {
         action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &QuickfixCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &VimGrep, window, cx| {
+        action.run(vim, window, cx)
     })
 }
 
@@ -1529,6 +1466,19 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
             .filename(|_action, filename| Some(VimEdit { filename }.boxed_clone())),
         VimCommand::new(("tabnew", ""), workspace::NewFile)
             .filename(|_action, filename| Some(VimEdit { filename }.boxed_clone())),
+        VimCommand::new(
+            ("ar", "gs"),
+            VimArgs {
+                files: String::new(),
+            },
+        )
+        .args(|_, files| Some(VimArgs { files }.boxed_clone())),
+        VimCommand::new(("n", "ext"), GoToArgument::Next),
+        VimCommand::new(("N", "ext"), GoToArgument::Previous),
+        VimCommand::new(("prev", "ious"), GoToArgument::Previous),
+        VimCommand::new(("fir", "st"), GoToArgument::First),
+        VimCommand::new(("rew", "ind"), GoToArgument::First),
+        VimCommand::new(("la", "st"), GoToArgument::Last),
         VimCommand::new(
             ("q", "uit"),
             workspace::CloseActiveItem {
@@ -1699,21 +1649,39 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         .bang(workspace::CloseInactiveTabsAndPanes {
             save_intent: Some(SaveIntent::Skip),
         }),
-        VimCommand::str(("cl", "ist"), "diagnostics::Deploy"),
+        VimCommand::new(("cl", "ist"), quickfix::Open),
+        VimCommand::new(("cope", "n"), quickfix::Open),
+        VimCommand::new(("ccl", "ose"), quickfix::Close),
         VimCommand::new(("cc", ""), editor::actions::Hover),
         VimCommand::new(("ll", ""), editor::actions::Hover),
-        VimCommand::new(("cn", "ext"), editor::actions::GoToDiagnostic::default())
-            .range(wrap_count),
-        VimCommand::new(
-            ("cp", "revious"),
-            editor::actions::GoToPreviousDiagnostic::default(),
-        )
-        .range(wrap_count),
+        VimCommand::new(("cn", "ext"), quickfix::Next).range(wrap_count),
+        VimCommand::new(("cp", "revious"), quickfix::Previous).range(wrap_count),
+        VimCommand::new(("cN", "ext"), quickfix::Previous).range(wrap_count),
+        VimCommand::new(("cfir", "st"), quickfix::First),
+        VimCommand::new(("cr", "ewind"), quickfix::First),
+        VimCommand::new(("cla", "st"), quickfix::Last),
+        VimCommand::new(("col", "der"), quickfix::Older).range(wrap_count),
+        VimCommand::new(("cnew", "er"), quickfix::Newer).range(wrap_count),
+        VimCommand::new(("gr", "ep"), ArgumentRequired)
+            .args(|_, args| Some(VimGrep { args }.boxed_clone())),
         VimCommand::new(
-            ("cN", "ext"),
-            editor::actions::GoToPreviousDiagnostic::default(),
+            ("mak", "e"),
+            QuickfixCommand {
+                command: "make".into(),
+                problem_matchers: QuickfixCommand::build_problem_matchers(),
+            },
         )
-        .range(wrap_count),
+        .args(|_, args| {
+            Some(
+                QuickfixCommand {
+                    command: format!("make {args}"),
+                    problem_matchers: QuickfixCommand::build_problem_matchers(),
+                }
+                .boxed_clone(),
+            )
+        }),
+        VimCommand::new(("ln", "ext"), editor::actions::GoToDiagnostic::default())
+            .range(wrap_count),
         VimCommand::new(
             ("lp", "revious"),
             editor::actions::GoToPreviousDiagnostic::default(),
@@ -1747,6 +1715,30 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         VimCommand::new(("reg", "isters"), ToggleRegistersView).bang(ToggleRegistersView),
         VimCommand::new(("di", "splay"), ToggleRegistersView).bang(ToggleRegistersView),
         VimCommand::new(("marks", ""), ToggleMarksView).bang(ToggleMarksView),
+        abbreviate(("ab", "breviate"), AbbreviationScope::All),
+        abbreviate(("iab", "brev"), AbbreviationScope::Insert),
+        abbreviate(("cab", "brev"), AbbreviationScope::Command),
+        unabbreviate(("una", "bbreviate"), AbbreviationScope::All),
+        unabbreviate(("iuna", "bbrev"), AbbreviationScope::Insert),
+        unabbreviate(("cuna", "bbrev"), AbbreviationScope::Command),
+        VimCommand::new(
+            ("abc", "lear"),
+            ClearAbbreviations {
+                scope: AbbreviationScope::All,
+            },
+        ),
+        VimCommand::new(
+            ("iabc", "lear"),
+            ClearAbbreviations {
+                scope: AbbreviationScope::Insert,
+            },
+        ),
+        VimCommand::new(
+            ("cabc", "lear"),
+            ClearAbbreviations {
+                scope: AbbreviationScope::Command,
+            },
+        ),
         VimCommand::new(("delm", "arks"), ArgumentRequired)
             .bang(DeleteMarks::AllLocal)
             .args(|_, args| Some(DeleteMarks::Marks(args).boxed_clone())),
@@ -1780,6 +1772,22 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
     ]
 }
 
+fn abbreviate(pattern: (&'static str, &'static str), scope: AbbreviationScope) -> VimCommand {
+    VimCommand::new(
+        pattern,
+        Abbreviate {
+            scope,
+            definition: "".into(),
+        },
+    )
+    .args(move |_, definition| Some(Abbreviate { scope, definition }.boxed_clone()))
+}
+
+fn unabbreviate(pattern: (&'static str, &'static str), scope: AbbreviationScope) -> VimCommand {
+    VimCommand::new(pattern, ArgumentRequired)
+        .args(move |_, lhs| Some(Unabbreviate { scope, lhs }.boxed_clone()))
+}
+
 struct VimCommands(Vec<VimCommand>);
 // safety: we only ever access this from the main thread (as ensured by the cx argument)
 // actions are not Sync so we can't otherwise use a OnceLock.
@@ -1825,6 +1833,55 @@ fn wrap_count(action: Box<dyn Action>, range: &CommandRange) -> Option<Box<dyn A
     })
 }
 
+fn parse_substitute(query: &str, range: &Option<CommandRange>) -> Option<Box<dyn Action>> {
+    let mut substitute = "substitute".chars().peekable();
+    let mut query = query.chars().peekable();
+    while substitute
+        .peek()
+        .is_some_and(|char| Some(char) == query.peek())
+    {
+        substitute.next();
+        query.next();
+    }
+    let replacement = Replacement::parse(query)?;
+    let range = range.clone().unwrap_or(CommandRange {
+        start: Position::CurrentLine { offset: 0 },
+        end: None,
+    });
+    Some(ReplaceCommand { replacement, range }.boxed_clone())
+}
+
+/// Parses a complete ex command, such as the one given to `:bufdo`, into an action.
+fn parse_ex_command(input: &str, cx: &mut App) -> Option<Box<dyn Action>> {
+    let (range, query) = VimCommand::parse_range(input);
+    let query = query.trim_start();
+    if query.is_empty() {
+        return range.map(|range| GoToLine { range }.boxed_clone());
+    }
+    if (query.starts_with('g') || query.starts_with('v'))
+        && let Some((command, range, search, invert)) = OnMatchingLines::parse(query, &range)
+    {
+        let action = parse_ex_command(command.trim(), cx)?;
+        return Some(
+            OnMatchingLines {
+                range,
+                search,
+                action: WrappedAction(action),
+                invert,
+            }
+            .boxed_clone(),
+        );
+    }
+    if query.starts_with('s')
+        && let Some(action) = parse_substitute(query, &range)
+    {
+        return Some(action);
+    }
+    commands(cx)
+        .iter()
+        .find_map(|command| command.parse(query, &range, cx))
+}
+
 pub fn command_interceptor(
     mut input: &str,
     workspace: WeakEntity<Workspace>,
@@ -1833,12 +1890,34 @@ pub fn command_interceptor(
     while input.starts_with(':') {
         input = &input[1..];
     }
+    let expanded = expand_command_abbreviations(input, cx);
+    let input = expanded.as_deref().unwrap_or(input);
 
     let (range, query) = VimCommand::parse_range(input);
     let range_prefix = input[0..(input.len() - query.len())].to_string();
     let has_trailing_space = query.ends_with(" ");
     let mut query = query.as_str().trim_start();
 
+    if let Some((targets, command)) = BatchTargets::parse(query) {
+        let Some(action) = parse_ex_command(command, cx) else {
+            return Task::ready(CommandInterceptResult::default());
+        };
+        let string = input.to_string();
+        let positions = generate_positions(&string, &(range_prefix + query));
+        return Task::ready(CommandInterceptResult {
+            results: vec![CommandInterceptItem {
+                action: BatchCommand {
+                    targets,
+                    action: WrappedAction(action),
+                }
+                .boxed_clone(),
+                string,
+                positions,
+            }],
+            exclusive: false,
+        });
+    }
+
     let on_matching_lines = (query.starts_with('g') || query.starts_with('v'))
         .then(|| {
             let (pattern, range, search, invert) = OnMatchingLines::parse(query, &range)?;
@@ -1877,24 +1956,7 @@ pub fn command_interceptor(
             exclusive: false,
         });
     } else if query.starts_with('s') {
-        let mut substitute = "substitute".chars().peekable();
-        let mut query = query.chars().peekable();
-        while substitute
-            .peek()
-            .is_some_and(|char| Some(char) == query.peek())
-        {
-            substitute.next();
-            query.next();
-        }
-        if let Some(replacement) = Replacement::parse(query) {
-            let range = range.clone().unwrap_or(CommandRange {
-                start: Position::CurrentLine { offset: 0 },
-                end: None,
-            });
-            Some(ReplaceCommand { replacement, range }.boxed_clone())
-        } else {
-            None
-        }
+        parse_substitute(query, &range)
     } else if query.contains('!') {
         ShellExec::parse(query, range.clone())
     } else if on_matching_lines.is_some() {
@@ -2283,7 +2345,169 @@ pub struct ShellExec {
     is_read: bool,
 }
 
+/// Runs a command such as `make` or `grep`, and replaces the quickfix list with
+/// the locations that its problem matchers find in its output.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub struct QuickfixCommand {
+    command: String,
+    problem_matchers: Vec<ProblemMatcher>,
+}
+
+/// Runs the `grepprg` setting with the given arguments, as `:grep` does.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct VimGrep {
+    args: String,
+}
+
+impl VimGrep {
+    fn command(&self, grepprg: &str) -> String {
+        if grepprg.contains("$*") {
+            grepprg.replace("$*", &self.args)
+        } else {
+            format!("{grepprg} {}", self.args)
+        }
+    }
+
+    fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        QuickfixCommand {
+            command: self.command(&VimSettings::get_global(cx).grepprg),
+            problem_matchers: vec![ProblemMatcher::BuiltIn("$grep".into())],
+        }
+        .run(vim, window, cx)
+    }
+}
+
+impl QuickfixCommand {
+    /// The matchers for the output of `:make`, which covers the compilers that
+    /// have a built-in problem matcher.
+    fn build_problem_matchers() -> Vec<ProblemMatcher> {
+        ["$rustc", "$gcc", "$tsc", "$go"]
+            .into_iter()
+            .map(|name| ProblemMatcher::BuiltIn(name.into()))
+            .collect()
+    }
+
+    fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let Some(workspace) = vim.workspace(window, cx) else {
+            return;
+        };
+        let project = workspace.read(cx).project().clone();
+        let command = self.command.clone();
+        let problem_matchers = self.problem_matchers.clone();
+        let process = project.update(cx, |project, cx| project.exec_in_shell(command.clone(), cx));
+        cx.spawn_in(window, async move |_, cx| {
+            let mut process = process.await?;
+            process.stdin(Stdio::null());
+            process.stdout(Stdio::piped());
+            process.stderr(Stdio::piped());
+            let output = cx
+                .background_spawn(async move { process.output().await })
+                .await?;
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                let list = quickfix::output_list(command, &text, &problem_matchers, &project, cx);
+                let is_empty = list.entries.is_empty();
+                quickfix::push_list(workspace, list, window, cx);
+                if !is_empty {
+                    quickfix::go_to_entry(workspace, |lists, cx| lists.select(0, cx), window, cx);
+                }
+            })
+        })
+        .detach_and_prompt_err("Failed to run command", window, cx, |_, _, _| None);
+    }
+}
+
 impl Vim {
+    /// Runs the keystrokes of a `:normal` command, returning a task that completes
+    /// once they have all been handled.
+    pub(crate) fn normal_command(
+        &mut self,
+        action: &VimNorm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<()> {
+        let keystrokes = action
+            .command
+            .chars()
+            .map(|c| Keystroke::parse(&c.to_string()).unwrap())
+            .collect();
+        self.switch_mode(Mode::Normal, true, window, cx);
+        if let Some(override_rows) = &action.override_rows {
+            self.update_editor(cx, |_, editor, cx| {
+                editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                    s.replace_cursors_with(|map| {
+                        override_rows
+                            .iter()
+                            .map(|row| Point::new(*row, 0).to_display_point(map))
+                            .collect()
+                    });
+                });
+            });
+        } else if let Some(range) = &action.range {
+            let result = self.update_editor(cx, |vim, editor, cx| {
+                let range = range.buffer_range(vim, editor, window, cx)?;
+                editor.change_selections(
+                    SelectionEffects::no_scroll().nav_history(false),
+                    window,
+                    cx,
+                    |s| {
+                        s.select_ranges(
+                            (range.start.0..=range.end.0)
+                                .map(|line| Point::new(line, 0)..Point::new(line, 0)),
+                        );
+                    },
+                );
+                anyhow::Ok(())
+            });
+            if let Some(Err(err)) = result {
+                log::error!("Error selecting range: {}", err);
+                return Task::ready(());
+            }
+        };
+
+        let Some(workspace) = self.workspace(window, cx) else {
+            return Task::ready(());
+        };
+        let task = workspace.update(cx, |workspace, cx| {
+            workspace.send_keystrokes_impl(keystrokes, window, cx)
+        });
+        let had_range = action.range.is_some();
+        let had_override = action.override_rows.is_some();
+
+        cx.spawn_in(window, async move |vim, cx| {
+            task.await;
+            vim.update_in(cx, |vim, window, cx| {
+                if matches!(vim.mode, Mode::Insert | Mode::Replace) {
+                    vim.normal_before(&Default::default(), window, cx);
+                } else {
+                    vim.switch_mode(Mode::Normal, true, window, cx);
+                }
+                if had_override || had_range {
+                    vim.update_editor(cx, |_, editor, cx| {
+                        editor.change_selections(SelectionEffects::default(), window, cx, |s| {
+                            s.select_anchor_ranges([s.newest_anchor().range()]);
+                        });
+                        if let Some(tx_id) = editor
+                            .buffer()
+                            .update(cx, |multi, cx| multi.last_transaction_id(cx))
+                        {
+                            let last_sel = editor.selections.disjoint_anchors_arc();
+                            editor.modify_transaction_selection_history(tx_id, |old| {
+                                old.0 = old.0.get(..1).unwrap_or(&[]).into();
+                                old.1 = Some(last_sel);
+                            });
+                        }
+                    });
+                }
+            })
+            .log_err();
+        })
+    }
+
     pub fn cancel_running_command(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if self.running_command.take().is_some() {
             self.update_editor(cx, |_, editor, cx| {
@@ -2479,6 +2703,7 @@ impl ShellExec {
                     show_summary: false,
                     show_command: false,
                     show_rerun: false,
+                    problem_matchers: Vec::new(),
                 };
 
                 let task_status = workspace.spawn_in_terminal(spawn_in_terminal, window, cx);
@@ -2642,6 +2867,20 @@ mod test {
     use util::path;
     use workspace::{OpenOptions, Workspace};
 
+    use super::VimGrep;
+
+    #[test]
+    fn test_grep_command() {
+        let grep = VimGrep {
+            args: "foo src".into(),
+        };
+        assert_eq!(grep.command("grep -rn"), "grep -rn foo src");
+        assert_eq!(
+            grep.command("rg --no-heading -n $* --hidden"),
+            "rg --no-heading -n foo src --hidden"
+        );
+    }
+
     #[gpui::test]
     async fn test_command_basics(cx: &mut TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -1,3 +1,5 @@
+use crate::abbreviations::Abbreviations;
+use crate::batch::ArgumentList;
 use crate::command::command_interceptor;
 use crate::motion::MotionKind;
 use crate::normal::repeat::Replayer;
@@ -240,6 +242,10 @@ pub struct VimGlobals {
     pub focused_vim: Option<WeakEntity<Vim>>,
 
     pub marks: HashMap<EntityId, Entity<MarksState>>,
+
+    pub abbreviations: Abbreviations,
+
+    pub argument_list: ArgumentList,
 }
 
 pub struct MarksState {
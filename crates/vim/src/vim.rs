@@ -3,6 +3,8 @@
 #[cfg(test)]
 mod test;
 
+mod abbreviations;
+mod batch;
 mod change_list;
 mod command;
 mod digraph;
@@ -959,6 +961,8 @@ impl Vim {
             visual::register(editor, cx);
             change_list::register(editor, cx);
             digraph::register(editor, cx);
+            abbreviations::register(editor, cx);
+            batch::register(editor, cx);
 
             if editor.is_focused(window) {
                 cx.defer_in(window, |vim, window, cx| {
@@ -1086,7 +1090,12 @@ impl Vim {
             EditorEvent::InputHandled {
                 text,
                 utf16_range_to_replace: range_to_replace,
-            } => Vim::globals(cx).observe_insertion(text, range_to_replace.clone()),
+            } => {
+                Vim::globals(cx).observe_insertion(text, range_to_replace.clone());
+                if range_to_replace.is_none() && text.chars().count() == 1 {
+                    self.expand_abbreviation(text, window, cx);
+                }
+            }
             EditorEvent::TransactionBegun { transaction_id } => {
                 self.transaction_begun(*transaction_id, window, cx)
             }
@@ -2101,6 +2110,8 @@ struct VimSettings {
     pub use_smartcase_find: bool,
     pub gdefault: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
+    pub abbreviations: HashMap<String, String>,
+    pub grepprg: String,
     pub highlight_on_yank_duration: u64,
     pub cursor_shape: CursorShapeSettings,
 }
@@ -2187,6 +2198,8 @@ impl Settings for VimSettings {
             use_smartcase_find: vim.use_smartcase_find.unwrap(),
             gdefault: vim.gdefault.unwrap(),
             custom_digraphs: vim.custom_digraphs.unwrap(),
+            abbreviations: vim.abbreviations.unwrap(),
+            grepprg: vim.grepprg.unwrap(),
             highlight_on_yank_duration: vim.highlight_on_yank_duration.unwrap(),
             cursor_shape: vim.cursor_shape.unwrap().into(),
         }
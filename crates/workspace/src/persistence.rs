@@ -27,7 +27,8 @@ use project::{
 
 use language::{LanguageName, Toolchain, ToolchainScope};
 use remote::{
-    DockerConnectionOptions, RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions,
+    CommandConnectionOptions, DockerConnectionOptions, RemoteConnectionOptions, SocketAddress,
+    SocketConnectionOptions, SocketTokenSource, SshConnectionOptions, WslConnectionOptions,
 };
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -457,6 +458,14 @@ impl Column for Breakpoint {
     }
 }
 
+/// The command lines of a command connection, stored as JSON in the `host` column.
+#[derive(Serialize, Deserialize)]
+struct PersistedCommandConnection {
+    exec: Vec<String>,
+    exec_interactive: Option<Vec<String>>,
+    upload: Vec<Vec<String>>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 struct SerializedPixels(gpui::Pixels);
 impl sqlez::bindable::StaticColumnCount for SerializedPixels {}
@@ -1518,6 +1527,23 @@ impl WorkspaceDb {
                 use_podman = Some(options.use_podman);
                 user = Some(options.remote_user);
             }
+            RemoteConnectionOptions::Socket(options) => {
+                kind = RemoteConnectionKind::Socket;
+                host = Some(options.address.to_string());
+                name = options.nickname;
+                // Only where the token comes from is persisted, never the token itself.
+                user = Some(options.token_source.to_string());
+            }
+            RemoteConnectionOptions::Command(options) => {
+                kind = RemoteConnectionKind::Command;
+                host = Some(serde_json::to_string(&PersistedCommandConnection {
+                    exec: options.exec,
+                    exec_interactive: options.exec_interactive,
+                    upload: options.upload,
+                })?);
+                name = Some(options.name);
+                user = None;
+            }
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(options) => {
                 kind = RemoteConnectionKind::Ssh;
@@ -1773,6 +1799,24 @@ impl WorkspaceDb {
                     use_podman: use_podman?,
                 }))
             }
+            RemoteConnectionKind::Socket => {
+                Some(RemoteConnectionOptions::Socket(SocketConnectionOptions {
+                    address: SocketAddress::parse(&host?).log_err()?,
+                    token_source: SocketTokenSource::parse(&user?).log_err()?,
+                    nickname: name,
+                }))
+            }
+            RemoteConnectionKind::Command => {
+                let persisted: PersistedCommandConnection =
+                    serde_json::from_str(&host?).log_err()?;
+                Some(RemoteConnectionOptions::Command(CommandConnectionOptions {
+                    name: name?,
+                    exec: persisted.exec,
+                    exec_interactive: persisted.exec_interactive,
+                    upload: persisted.upload,
+                    upload_binary_over_exec: false,
+                }))
+            }
         }
     }
 
@@ -3571,6 +3615,72 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_socket_remote_connection_round_trip() {
+        let db = WorkspaceDb::open_test_db("test_socket_remote_connection_round_trip").await;
+
+        let options = RemoteConnectionOptions::Socket(SocketConnectionOptions {
+            address: SocketAddress::Tcp {
+                host: "devbox".into(),
+                port: 7001,
+            },
+            token_source: SocketTokenSource::File("/home/me/.zed-token".into()),
+            nickname: Some("Devbox".into()),
+        });
+        let id = db
+            .get_or_create_remote_connection(options.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_or_create_remote_connection(options.clone())
+                .await
+                .unwrap(),
+            id
+        );
+        assert_eq!(db.remote_connection(id).unwrap(), options);
+    }
+
+    #[gpui::test]
+    async fn test_command_remote_connection_round_trip() {
+        let db = WorkspaceDb::open_test_db("test_command_remote_connection_round_trip").await;
+
+        let options = RemoteConnectionOptions::Command(CommandConnectionOptions {
+            name: "dev-pod".into(),
+            exec: vec![
+                "kubectl".into(),
+                "exec".into(),
+                "-i".into(),
+                "dev-pod".into(),
+                "--".into(),
+            ],
+            exec_interactive: Some(vec![
+                "kubectl".into(),
+                "exec".into(),
+                "-it".into(),
+                "dev-pod".into(),
+                "--".into(),
+            ]),
+            upload: vec![vec![
+                "kubectl".into(),
+                "cp".into(),
+                "{src}".into(),
+                "dev-pod:{dst}".into(),
+            ]],
+            upload_binary_over_exec: false,
+        });
+        let id = db
+            .get_or_create_remote_connection(options.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_or_create_remote_connection(options.clone())
+                .await
+                .unwrap(),
+            id
+        );
+        assert_eq!(db.remote_connection(id).unwrap(), options);
+    }
+
     #[gpui::test]
     async fn test_simple_split() {
         zlog::init_test();
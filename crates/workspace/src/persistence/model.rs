@@ -34,6 +34,8 @@ pub(crate) enum RemoteConnectionKind {
     Ssh,
     Wsl,
     Docker,
+    Socket,
+    Command,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -104,6 +106,8 @@ impl RemoteConnectionKind {
             RemoteConnectionKind::Ssh => "ssh",
             RemoteConnectionKind::Wsl => "wsl",
             RemoteConnectionKind::Docker => "docker",
+            RemoteConnectionKind::Socket => "socket",
+            RemoteConnectionKind::Command => "command",
         }
     }
 
@@ -112,6 +116,8 @@ impl RemoteConnectionKind {
             "ssh" => Some(Self::Ssh),
             "wsl" => Some(Self::Wsl),
             "docker" => Some(Self::Docker),
+            "socket" => Some(Self::Socket),
+            "command" => Some(Self::Command),
             _ => None,
         }
     }
@@ -0,0 +1,204 @@
+use anyhow::{Result, anyhow};
+use gpui::{Context, EventEmitter, SharedString};
+use language::{DiagnosticSeverity, Point};
+use project::ProjectPath;
+
+/// How many lists are kept for `:colder` and `:cnewer`, as in Vim.
+const MAX_HISTORY_LEN: usize = 10;
+
+/// A single location in a [`QuickfixList`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuickfixEntry {
+    pub path: ProjectPath,
+    pub position: Point,
+    /// The message of a diagnostic, or the matching line of a search result.
+    pub text: SharedString,
+    pub severity: Option<DiagnosticSeverity>,
+}
+
+/// A list of locations to step through, such as project search results,
+/// diagnostics, or the errors found in a task's output.
+#[derive(Clone, Debug)]
+pub struct QuickfixList {
+    pub title: SharedString,
+    pub entries: Vec<QuickfixEntry>,
+    selected_index: usize,
+}
+
+impl QuickfixList {
+    pub fn new(title: impl Into<SharedString>, entries: Vec<QuickfixEntry>) -> Self {
+        Self {
+            title: title.into(),
+            entries,
+            selected_index: 0,
+        }
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    pub fn selected_entry(&self) -> Option<&QuickfixEntry> {
+        self.entries.get(self.selected_index)
+    }
+}
+
+pub enum QuickfixEvent {
+    /// The current list, or its selected entry, changed.
+    Changed,
+}
+
+/// The quickfix lists of a workspace. New lists are pushed on top of the
+/// older ones, which can be returned to with [`QuickfixLists::older`].
+#[derive(Default)]
+pub struct QuickfixLists {
+    lists: Vec<QuickfixList>,
+    current_index: usize,
+}
+
+impl EventEmitter<QuickfixEvent> for QuickfixLists {}
+
+impl QuickfixLists {
+    pub fn lists(&self) -> &[QuickfixList] {
+        &self.lists
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current_index
+    }
+
+    pub fn current(&self) -> Option<&QuickfixList> {
+        self.lists.get(self.current_index)
+    }
+
+    /// Makes `list` the current list. Lists newer than the current one are
+    /// discarded, and the oldest list is dropped once the history is full.
+    pub fn push(&mut self, list: QuickfixList, cx: &mut Context<Self>) {
+        self.lists.truncate(self.current_index + 1);
+        self.lists.push(list);
+        if self.lists.len() > MAX_HISTORY_LEN {
+            self.lists.remove(0);
+        }
+        self.current_index = self.lists.len() - 1;
+        cx.emit(QuickfixEvent::Changed);
+        cx.notify();
+    }
+
+    pub fn older(&mut self, count: usize, cx: &mut Context<Self>) -> Result<()> {
+        if self.lists.is_empty() || self.current_index == 0 {
+            return Err(anyhow!("At bottom of quickfix stack"));
+        }
+        self.current_index = self.current_index.saturating_sub(count);
+        cx.emit(QuickfixEvent::Changed);
+        cx.notify();
+        Ok(())
+    }
+
+    pub fn newer(&mut self, count: usize, cx: &mut Context<Self>) -> Result<()> {
+        if self.current_index + 1 >= self.lists.len() {
+            return Err(anyhow!("At top of quickfix stack"));
+        }
+        self.current_index = (self.current_index + count).min(self.lists.len() - 1);
+        cx.emit(QuickfixEvent::Changed);
+        cx.notify();
+        Ok(())
+    }
+
+    /// Selects the entry at `index` in the current list.
+    pub fn select(&mut self, index: usize, cx: &mut Context<Self>) -> Result<QuickfixEntry> {
+        let list = self
+            .lists
+            .get_mut(self.current_index)
+            .filter(|list| !list.entries.is_empty())
+            .ok_or_else(|| anyhow!("No errors"))?;
+        let entry = list
+            .entries
+            .get(index)
+            .cloned()
+            .ok_or_else(|| anyhow!("No more items"))?;
+        list.selected_index = index;
+        cx.emit(QuickfixEvent::Changed);
+        cx.notify();
+        Ok(entry)
+    }
+
+    /// Selects the entry `delta` entries after (or before) the selected one.
+    pub fn select_relative(
+        &mut self,
+        delta: isize,
+        cx: &mut Context<Self>,
+    ) -> Result<QuickfixEntry> {
+        let list = self
+            .current()
+            .filter(|list| !list.entries.is_empty())
+            .ok_or_else(|| anyhow!("No errors"))?;
+        let index = usize::try_from(list.selected_index as isize + delta)
+            .map_err(|_| anyhow!("No more items"))?;
+        self.select(index, cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use util::rel_path::RelPath;
+
+    fn list(title: &'static str, len: u32) -> QuickfixList {
+        QuickfixList::new(
+            title,
+            (0..len)
+                .map(|row| QuickfixEntry {
+                    path: ProjectPath {
+                        worktree_id: project::WorktreeId::from_usize(0),
+                        path: RelPath::unix("a.rs").unwrap().into(),
+                    },
+                    position: Point::new(row, 0),
+                    text: SharedString::default(),
+                    severity: None,
+                })
+                .collect(),
+        )
+    }
+
+    #[gpui::test]
+    fn test_quickfix_history(cx: &mut TestAppContext) {
+        let lists = cx.new(|_| QuickfixLists::default());
+        lists.update(cx, |lists, cx| {
+            assert!(lists.older(1, cx).is_err());
+            for ix in 0..12 {
+                lists.push(list(["a", "b", "c"][ix % 3], 2), cx);
+            }
+            assert_eq!(lists.lists().len(), MAX_HISTORY_LEN);
+            assert!(lists.newer(1, cx).is_err());
+
+            lists.older(2, cx).unwrap();
+            assert_eq!(lists.current().unwrap().title.as_ref(), "a");
+            lists.newer(1, cx).unwrap();
+            assert_eq!(lists.current().unwrap().title.as_ref(), "b");
+
+            // Pushing a list discards the newer ones.
+            lists.push(list("d", 2), cx);
+            assert_eq!(lists.lists().len(), MAX_HISTORY_LEN);
+            assert_eq!(lists.current().unwrap().title.as_ref(), "d");
+            assert!(lists.newer(1, cx).is_err());
+            lists.older(20, cx).unwrap();
+            assert_eq!(lists.current_index(), 0);
+        });
+    }
+
+    #[gpui::test]
+    fn test_quickfix_selection(cx: &mut TestAppContext) {
+        let lists = cx.new(|_| QuickfixLists::default());
+        lists.update(cx, |lists, cx| {
+            assert!(lists.select_relative(1, cx).is_err());
+            lists.push(list("a", 3), cx);
+            assert_eq!(lists.select_relative(1, cx).unwrap().position.row, 1);
+            assert_eq!(lists.select_relative(1, cx).unwrap().position.row, 2);
+            assert!(lists.select_relative(1, cx).is_err());
+            assert_eq!(lists.current().unwrap().selected_index(), 2);
+            assert_eq!(lists.select_relative(-2, cx).unwrap().position.row, 0);
+            assert!(lists.select_relative(-1, cx).is_err());
+        });
+    }
+}
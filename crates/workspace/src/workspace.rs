@@ -9,6 +9,7 @@ pub mod pane;
 pub mod pane_group;
 mod path_list;
 mod persistence;
+pub mod quickfix;
 pub mod searchable;
 mod security_modal;
 pub mod shared_screen;
@@ -29,6 +30,7 @@ pub use multi_workspace::{
     ToggleWorkspaceSidebar,
 };
 pub use path_list::PathList;
+use quickfix::QuickfixLists;
 pub use toast_layer::{ToastAction, ToastLayer, ToastView};
 
 use anyhow::{Context as _, Result, anyhow};
@@ -82,7 +84,7 @@ pub use persistence::{
 use postage::stream::Stream;
 use project::{
     DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
-    WorktreeSettings,
+    WorktreeSettings, WriteBehindResolution,
     debugger::{breakpoint_store::BreakpointStoreEvent, session::ThreadStatus},
     project_settings::ProjectSettings,
     toolchain_store::ToolchainStoreEvent,
@@ -1253,6 +1255,7 @@ pub struct Workspace {
     status_bar: Entity<StatusBar>,
     pub(crate) modal_layer: Entity<ModalLayer>,
     toast_layer: Entity<ToastLayer>,
+    quickfix_lists: Entity<QuickfixLists>,
     titlebar_item: Option<AnyView>,
     notifications: Notifications,
     suppressed_notifications: HashSet<NotificationId>,
@@ -1397,6 +1400,10 @@ impl Workspace {
                     this.update_window_edited(window, cx);
                 }
 
+                project::Event::WriteBehindConflict { buffer } => {
+                    this.resolve_write_behind_conflict(buffer.clone(), window, cx);
+                }
+
                 project::Event::Closed => {
                     window.remove_window();
                 }
@@ -1551,6 +1558,7 @@ impl Workspace {
         cx.emit(Event::WorkspaceCreated(weak_handle.clone()));
         let modal_layer = cx.new(|_| ModalLayer::new());
         let toast_layer = cx.new(|_| ToastLayer::new());
+        let quickfix_lists = cx.new(|_| QuickfixLists::default());
         cx.subscribe(
             &modal_layer,
             |_, _, _: &modal_layer::ModalOpenedEvent, cx| {
@@ -1650,6 +1658,7 @@ impl Workspace {
             status_bar,
             modal_layer,
             toast_layer,
+            quickfix_lists,
             titlebar_item: None,
             active_worktree_override: None,
             notifications: Notifications::default(),
@@ -2048,6 +2057,10 @@ impl Workspace {
         &self.project
     }
 
+    pub fn quickfix_lists(&self) -> &Entity<QuickfixLists> {
+        &self.quickfix_lists
+    }
+
     pub fn path_style(&self, cx: &App) -> PathStyle {
         self.project.read(cx).path_style(cx)
     }
@@ -2856,7 +2869,8 @@ impl Workspace {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Task<Result<bool>> {
-        if self.project.read(cx).is_disconnected(cx) {
+        let project = self.project.read(cx);
+        if project.is_disconnected(cx) && !project.is_working_offline(cx) {
             return Task::ready(Ok(true));
         }
         let dirty_items = self
@@ -5177,6 +5191,42 @@ impl Workspace {
         self.last_window_title = Some(title);
     }
 
+    fn resolve_write_behind_conflict(
+        &mut self,
+        buffer: Entity<Buffer>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let path = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.full_path(cx).to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let answer = window.prompt(
+            PromptLevel::Warning,
+            &format!("{path} changed on the remote host while you were offline."),
+            Some("Do you want to overwrite it with the changes you made offline?"),
+            &["Overwrite Remote", "Keep Remote Version", "Decide Later"],
+            cx,
+        );
+        let project = self.project.clone();
+        cx.spawn(async move |_, cx| {
+            let resolution = match answer.await? {
+                0 => WriteBehindResolution::Overwrite,
+                1 => WriteBehindResolution::Discard,
+                _ => WriteBehindResolution::Defer,
+            };
+            project
+                .update(cx, |project, cx| {
+                    project.buffer_store().update(cx, |buffer_store, cx| {
+                        buffer_store.resolve_write_behind_conflict(buffer, resolution, cx)
+                    })
+                })
+                .await
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn update_window_edited(&mut self, window: &mut Window, cx: &mut App) {
         let is_edited = !self.project.read(cx).is_disconnected(cx) && !self.dirty_items.is_empty();
         if is_edited != self.window_edited {
@@ -8498,6 +8548,12 @@ pub fn workspace_windows_for_location(
                 (RemoteConnectionOptions::Docker(a), RemoteConnectionOptions::Docker(b)) => {
                     a.container_id == b.container_id
                 }
+                (RemoteConnectionOptions::Socket(a), RemoteConnectionOptions::Socket(b)) => {
+                    a.address == b.address
+                }
+                (RemoteConnectionOptions::Command(a), RemoteConnectionOptions::Command(b)) => {
+                    a.exec == b.exec
+                }
                 #[cfg(any(test, feature = "test-support"))]
                 (RemoteConnectionOptions::Mock(a), RemoteConnectionOptions::Mock(b)) => {
                     a.id == b.id
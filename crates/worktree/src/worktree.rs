@@ -344,6 +344,7 @@ enum ScanState {
     RootUpdated {
         new_path: Arc<SanitizedPath>,
     },
+    UnloadedDirectoriesChanged,
 }
 
 struct UpdateObservationState {
@@ -357,6 +358,9 @@ pub enum Event {
     UpdatedEntries(UpdatedEntriesSet),
     UpdatedGitRepositories(UpdatedGitRepositoriesSet),
     DeletedEntry(ProjectEntryId),
+    /// Files changed inside directories that haven't been scanned, which the worktree doesn't
+    /// track.
+    UnloadedDirectoriesChanged,
 }
 
 impl EventEmitter<Event> for Worktree {}
@@ -1134,6 +1138,9 @@ impl LocalWorktree {
                         ScanState::RootUpdated { new_path } => {
                             this.update_abs_path_and_refresh(new_path, cx);
                         }
+                        ScanState::UnloadedDirectoriesChanged => {
+                            cx.emit(Event::UnloadedDirectoriesChanged);
+                        }
                     }
                 });
             }
@@ -1288,6 +1295,116 @@ impl LocalWorktree {
         self.settings.clone()
     }
 
+    /// Lists the files inside directories that haven't been scanned yet, without adding them
+    /// to the worktree.
+    ///
+    /// Lazily-scanned worktrees use this to find files that aren't in the snapshot.
+    pub fn walk_unloaded_files(
+        &self,
+        include_ignored: bool,
+        cx: &Context<Worktree>,
+    ) -> mpsc::UnboundedReceiver<Vec<Arc<RelPath>>> {
+        let dirs = self
+            .snapshot
+            .entries(include_ignored, 0)
+            .filter(|entry| entry.kind.is_unloaded() && !entry.is_external)
+            .map(|entry| entry.path.clone())
+            .collect();
+        self.walk_unloaded_dirs(dirs, include_ignored, cx)
+    }
+
+    /// Lists the files inside the given unscanned directories, without adding them to the
+    /// worktree. The files are sent in batches as the directories are walked, until the
+    /// receiver is dropped.
+    pub fn walk_unloaded_dirs(
+        &self,
+        dirs: Vec<Arc<RelPath>>,
+        include_ignored: bool,
+        cx: &Context<Worktree>,
+    ) -> mpsc::UnboundedReceiver<Vec<Arc<RelPath>>> {
+        const BATCH_SIZE: usize = 1024;
+
+        let snapshot = self.snapshot();
+        let fs = self.fs.clone();
+        let settings = self.settings.clone();
+        let (files_tx, files_rx) = mpsc::unbounded();
+        cx.background_spawn(async move {
+            let mut stack = Vec::new();
+            for path in dirs {
+                let abs_path = snapshot.absolutize(&path);
+                let ignore_stack = snapshot
+                    .ignore_stack_for_abs_path(&abs_path, true, fs.as_ref())
+                    .await;
+                stack.push((path, abs_path, ignore_stack));
+            }
+
+            let mut files = Vec::new();
+            while let Some((path, abs_path, mut ignore_stack)) = stack.pop() {
+                let gitignore_path = abs_path.join(GITIGNORE);
+                if fs.is_file(&gitignore_path).await
+                    && let Some(ignore) = build_gitignore(&gitignore_path, fs.as_ref())
+                        .await
+                        .log_err()
+                {
+                    ignore_stack = ignore_stack.append(
+                        IgnoreKind::Gitignore(abs_path.as_path().into()),
+                        Arc::new(ignore),
+                    );
+                }
+
+                let Some(mut children) = fs.read_dir(&abs_path).await.log_err() else {
+                    continue;
+                };
+                while let Some(child_abs_path) = children.next().await {
+                    let Some(child_abs_path) = child_abs_path.log_err() else {
+                        continue;
+                    };
+                    let Some(child_path) = child_abs_path
+                        .file_name()
+                        .and_then(|name| RelPath::unix(name.to_str()?).ok())
+                        .map(|name| path.join(name))
+                    else {
+                        continue;
+                    };
+                    if settings.is_path_excluded(&child_path) {
+                        continue;
+                    }
+                    let Ok(Some(metadata)) = fs.metadata(&child_abs_path).await else {
+                        continue;
+                    };
+                    let is_ignored =
+                        ignore_stack.is_abs_path_ignored(&child_abs_path, metadata.is_dir);
+                    if is_ignored && !include_ignored {
+                        continue;
+                    }
+                    if !metadata.is_dir {
+                        if !metadata.is_fifo {
+                            files.push(child_path);
+                        }
+                    } else if !metadata.is_symlink {
+                        let child_ignore_stack = if is_ignored {
+                            IgnoreStack::all()
+                        } else {
+                            ignore_stack.clone()
+                        };
+                        stack.push((child_path, child_abs_path, child_ignore_stack));
+                    }
+                }
+
+                if files.len() >= BATCH_SIZE
+                    && files_tx.unbounded_send(mem::take(&mut files)).is_err()
+                {
+                    return;
+                }
+            }
+            if !files.is_empty() {
+                files_tx.unbounded_send(files).ok();
+            }
+        })
+        .detach();
+        files_rx
+    }
+
     fn load_binary_file(
         &self,
         path: &RelPath,
@@ -2779,8 +2896,13 @@ impl LocalSnapshot {
 }
 
 impl BackgroundScannerState {
-    fn should_scan_directory(&self, entry: &Entry) -> bool {
-        (self.scanning_enabled && !entry.is_external && (!entry.is_ignored || entry.is_always_included))
+    fn should_scan_directory(&self, entry: &Entry, settings: &WorktreeSettings) -> bool {
+        (self.scanning_enabled
+            && !entry.is_external
+            && (!entry.is_ignored || entry.is_always_included)
+            && (!settings.lazy_file_scan
+                || entry.is_always_included
+                || settings.is_dir_always_indexed(&entry.path)))
             || entry.path.file_name() == Some(DOT_GIT)
             || entry.path.file_name() == Some(local_settings_folder_name())
             || entry.path.file_name() == Some(local_vscode_folder_name())
@@ -4033,6 +4155,7 @@ impl BackgroundScanner {
         let skipped_dirs_in_dot_git = [FSMONITOR_DAEMON, LFS_DIR];
 
         let mut relative_paths = Vec::with_capacity(abs_paths.len());
+        let mut unloaded_dirs_changed = false;
         let mut dot_git_abs_paths = Vec::new();
         let mut work_dirs_needing_exclude_update = Vec::new();
         abs_paths.sort_unstable();
@@ -4141,6 +4264,7 @@ impl BackgroundScanner {
                 });
                 if !parent_dir_is_loaded {
                     log::debug!("ignoring event {relative_path:?} within unloaded directory");
+                    unloaded_dirs_changed = true;
                     skip_ix(&mut ranges_to_drop, ix);
                     continue;
                 }
@@ -4161,6 +4285,12 @@ impl BackgroundScanner {
             }
         }
 
+        if unloaded_dirs_changed {
+            self.status_updates_tx
+                .unbounded_send(ScanState::UnloadedDirectoriesChanged)
+                .ok();
+        }
+
         if relative_paths.is_empty() && dot_git_abs_paths.is_empty() {
             return;
         }
@@ -4568,7 +4698,7 @@ impl BackgroundScanner {
         for entry in &mut new_entries {
             state.reuse_entry_id(entry);
             if entry.is_dir() {
-                if state.should_scan_directory(entry) {
+                if state.should_scan_directory(entry, &self.settings) {
                     job_ix += 1;
                 } else {
                     log::debug!("defer scanning directory {:?}", entry.path);
@@ -4686,7 +4816,7 @@ impl BackgroundScanner {
                     fs_entry.is_hidden = self.settings.is_path_hidden(path);
 
                     if let (Some(scan_queue_tx), true) = (&scan_queue_tx, is_dir) {
-                        if state.should_scan_directory(&fs_entry)
+                        if state.should_scan_directory(&fs_entry, &self.settings)
                             || (fs_entry.path.is_empty()
                                 && abs_path.file_name() == Some(OsStr::new(DOT_GIT)))
                         {
@@ -4977,7 +5107,7 @@ impl BackgroundScanner {
                 // Scan any directories that were previously ignored and weren't previously scanned.
                 if was_ignored && !entry.is_ignored && entry.kind.is_unloaded() {
                     let state = self.state.lock().await;
-                    if state.should_scan_directory(&entry) {
+                    if state.should_scan_directory(&entry, &self.settings) {
                         state
                             .enqueue_scan_dir(
                                 abs_path.clone(),
@@ -18,6 +18,12 @@ pub struct WorktreeSettings {
     /// This field contains all ancestors of the `file_scan_inclusions`. It's used to
     /// determine whether to terminate worktree scanning for a given dir.
     pub parent_dir_scan_inclusions: PathMatcher,
+    /// Whether directories are only scanned once something asks for their contents.
+    pub lazy_file_scan: bool,
+    pub file_scan_always_index: PathMatcher,
+    /// This field contains all ancestors of the `file_scan_always_index` globs, which have to be
+    /// scanned to reach them.
+    pub parent_dir_always_index: PathMatcher,
     pub private_files: PathMatcher,
     pub hidden_files: PathMatcher,
     pub read_only_files: PathMatcher,
@@ -42,6 +48,13 @@ impl WorktreeSettings {
         }
     }
 
+    /// Returns whether a directory should be scanned up front when `lazy_file_scan` is enabled.
+    pub fn is_dir_always_indexed(&self, path: &RelPath) -> bool {
+        path.is_empty()
+            || self.file_scan_always_index.is_match(path)
+            || self.parent_dir_always_index.is_match(path)
+    }
+
     pub fn is_path_hidden(&self, path: &RelPath) -> bool {
         path.ancestors()
             .any(|ancestor| self.hidden_files.is_match(ancestor))
@@ -64,16 +77,9 @@ impl Settings for WorktreeSettings {
         let private_files = worktree.private_files.unwrap().0;
         let hidden_files = worktree.hidden_files.unwrap();
         let read_only_files = worktree.read_only_files.unwrap_or_default();
-        let parsed_file_scan_inclusions: Vec<String> = file_scan_inclusions
-            .iter()
-            .flat_map(|glob| {
-                Path::new(glob)
-                    .ancestors()
-                    .skip(1)
-                    .map(|a| a.to_string_lossy().into())
-            })
-            .filter(|p: &String| !p.is_empty())
-            .collect();
+        let file_scan_always_index = worktree.file_scan_always_index.unwrap_or_default();
+        let parsed_file_scan_inclusions = glob_ancestors(&file_scan_inclusions);
+        let parsed_file_scan_always_index = glob_ancestors(&file_scan_always_index);
 
         Self {
             project_name: worktree.project_name,
@@ -88,6 +94,16 @@ impl Settings for WorktreeSettings {
             .unwrap(),
             file_scan_inclusions: path_matchers(file_scan_inclusions, "file_scan_inclusions")
                 .unwrap(),
+            lazy_file_scan: worktree.lazy_file_scan.unwrap_or(false),
+            file_scan_always_index: path_matchers(file_scan_always_index, "file_scan_always_index")
+                .log_err()
+                .unwrap_or_default(),
+            parent_dir_always_index: path_matchers(
+                parsed_file_scan_always_index,
+                "file_scan_always_index",
+            )
+            .log_err()
+            .unwrap_or_default(),
             private_files: path_matchers(private_files, "private_files")
                 .log_err()
                 .unwrap_or_default(),
@@ -101,6 +117,19 @@ impl Settings for WorktreeSettings {
     }
 }
 
+fn glob_ancestors(globs: &[String]) -> Vec<String> {
+    globs
+        .iter()
+        .flat_map(|glob| {
+            Path::new(glob)
+                .ancestors()
+                .skip(1)
+                .map(|a| a.to_string_lossy().into())
+        })
+        .filter(|p: &String| !p.is_empty())
+        .collect()
+}
+
 fn path_matchers(mut values: Vec<String>, context: &'static str) -> anyhow::Result<PathMatcher> {
     values.sort();
     PathMatcher::new(values, PathStyle::local())
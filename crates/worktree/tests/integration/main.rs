@@ -3,6 +3,7 @@ mod worktree_settings;
 use anyhow::Result;
 use encoding_rs;
 use fs::{FakeFs, Fs, RealFs, RemoveOptions};
+use futures::StreamExt as _;
 use git::{DOT_GIT, GITIGNORE, REPO_EXCLUDE};
 use gpui::{AppContext as _, BackgroundExecutor, BorrowAppContext, Context, Task, TestAppContext};
 use parking_lot::Mutex;
@@ -985,6 +986,122 @@ async fn test_file_scan_inclusions_reindexes_on_setting_change(cx: &mut TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_lazy_file_scan(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings.project.worktree.lazy_file_scan = Some(true);
+                settings.project.worktree.file_scan_always_index =
+                    Some(vec!["services/api".to_string()]);
+            });
+        });
+    });
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".gitignore": "build\n",
+            "README.md": "",
+            "services": {
+                "api": {
+                    "handlers": {
+                        "users.rs": "",
+                    },
+                    "main.rs": "",
+                },
+            },
+            "web": {
+                "build": {
+                    "out.js": "",
+                },
+                "components": {
+                    "button.tsx": "",
+                },
+                "index.ts": "",
+            },
+        }),
+    )
+    .await;
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        true,
+        WorktreeId::from_proto(0),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    // Only the root and the directories matching `file_scan_always_index` are scanned.
+    tree.read_with(cx, |tree, _| {
+        assert_eq!(
+            tree.entries(true, 0)
+                .map(|entry| (entry.path.as_ref(), entry.kind.is_unloaded()))
+                .collect::<Vec<_>>(),
+            vec![
+                (rel_path(""), false),
+                (rel_path(".gitignore"), false),
+                (rel_path("README.md"), false),
+                (rel_path("services"), false),
+                (rel_path("services/api"), false),
+                (rel_path("services/api/handlers"), false),
+                (rel_path("services/api/handlers/users.rs"), false),
+                (rel_path("services/api/main.rs"), false),
+                (rel_path("web"), true),
+            ]
+        );
+    });
+
+    // Files in unscanned directories can be found without loading them into the worktree.
+    let mut unloaded_files = tree
+        .update(cx, |tree, cx| {
+            tree.as_local().unwrap().walk_unloaded_files(false, cx)
+        })
+        .concat()
+        .await;
+    unloaded_files.sort();
+    assert_eq!(
+        unloaded_files
+            .iter()
+            .map(|path| path.as_unix_str())
+            .collect::<Vec<_>>(),
+        ["web/components/button.tsx", "web/index.ts"]
+    );
+    tree.read_with(cx, |tree, _| {
+        assert!(tree.entry_for_path(rel_path("web/index.ts")).is_none());
+    });
+
+    // Expanding a directory scans it, but not its subdirectories.
+    let web_id = tree.read_with(cx, |tree, _| {
+        tree.entry_for_path(rel_path("web")).unwrap().id
+    });
+    tree.update(cx, |tree, cx| tree.expand_entry(web_id, cx))
+        .unwrap()
+        .await
+        .unwrap();
+    tree.read_with(cx, |tree, _| {
+        assert_eq!(
+            tree.entries(true, 0)
+                .filter(|entry| entry.path.starts_with(rel_path("web")))
+                .map(|entry| (entry.path.as_ref(), entry.kind.is_unloaded()))
+                .collect::<Vec<_>>(),
+            vec![
+                (rel_path("web"), false),
+                (rel_path("web/build"), true),
+                (rel_path("web/components"), true),
+                (rel_path("web/index.ts"), false),
+            ]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_file_scan_exclusions(cx: &mut TestAppContext) {
     init_test(cx);
@@ -4,7 +4,7 @@ use std::{
 };
 
 use fs::RealFs;
-use settings::WorktreeId;
+use settings::{SettingsStore, WorktreeId};
 use worktree::Worktree;
 
 fn main() {
@@ -21,28 +21,45 @@ fn main() {
         let fs = Arc::new(RealFs::new(None, cx.background_executor().clone()));
 
         cx.spawn(async move |cx| {
-            let worktree = Worktree::local(
-                Path::new(&worktree_root_path),
-                true,
-                fs,
-                Arc::new(AtomicUsize::new(0)),
-                true,
-                WorktreeId::from_proto(0),
-                cx,
-            )
-            .await
-            .expect("Worktree initialization to succeed");
-            let did_finish_scan =
-                worktree.update(cx, |this, _| this.as_local().unwrap().scan_complete());
-            let start = std::time::Instant::now();
-            did_finish_scan.await;
-            let elapsed = start.elapsed();
-            let (files, directories) =
-                worktree.read_with(cx, |this, _| (this.file_count(), this.dir_count()));
-            println!(
-                "{:?} for {directories} directories and {files} files",
-                elapsed
-            );
+            // Compare a full scan with a lazy one, which only scans the root directory and
+            // the directories matched by `file_scan_always_index`. Each mode runs both first and
+            // last, so that neither is favored by a file system cache warmed up by the other.
+            for lazy_file_scan in [false, true, true, false] {
+                cx.update(|cx| {
+                    cx.update_global::<SettingsStore, _>(|store, cx| {
+                        store
+                            .set_user_settings(
+                                &format!(r#"{{ "lazy_file_scan": {lazy_file_scan} }}"#),
+                                cx,
+                            )
+                            .unwrap();
+                    })
+                });
+
+                let worktree = Worktree::local(
+                    Path::new(&worktree_root_path),
+                    true,
+                    fs.clone(),
+                    Arc::new(AtomicUsize::new(0)),
+                    true,
+                    WorktreeId::from_proto(0),
+                    cx,
+                )
+                .await
+                .expect("Worktree initialization to succeed");
+                let did_finish_scan =
+                    worktree.update(cx, |this, _| this.as_local().unwrap().scan_complete());
+                let start = std::time::Instant::now();
+                did_finish_scan.await;
+                let elapsed = start.elapsed();
+                let (files, directories) =
+                    worktree.read_with(cx, |this, _| (this.file_count(), this.dir_count()));
+                let mode = if lazy_file_scan { "lazy" } else { "eager" };
+                println!(
+                    "{mode} scan: {:?} for {directories} directories and {files} files",
+                    elapsed
+                );
+            }
             cx.update(|cx| {
                 cx.quit();
             })
@@ -58,6 +58,7 @@ use project::{DirectoryLister, DisableAiSettings, ProjectItem};
 use project_panel::ProjectPanel;
 use prompt_store::PromptBuilder;
 use quick_action_bar::QuickActionBar;
+use quickfix::QuickfixPanel;
 use recent_projects::open_remote_project;
 use release_channel::{AppCommitSha, AppVersion, ReleaseChannel};
 use rope::Rope;
@@ -632,6 +633,7 @@ fn initialize_panels(
             workspace_handle.clone(),
             cx.clone(),
         );
+        let quickfix_panel = QuickfixPanel::load(workspace_handle.clone(), cx.clone());
         let debug_panel = DebugPanel::load(workspace_handle.clone(), cx);
 
         async fn add_panel_when_ready(
@@ -656,6 +658,7 @@ fn initialize_panels(
             add_panel_when_ready(git_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(channels_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(notification_panel, workspace_handle.clone(), cx.clone()),
+            add_panel_when_ready(quickfix_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(debug_panel, workspace_handle.clone(), cx.clone()),
             initialize_agent_panel(workspace_handle, prompt_builder, cx.clone()).map(|r| r.log_err()),
         );
@@ -741,7 +744,8 @@ async fn initialize_agent_panel(
             workspace
                 .register_action(agent_ui::AgentPanel::toggle_focus)
                 .register_action(agent_ui::AgentPanel::toggle)
-                .register_action(agent_ui::InlineAssistant::inline_assist);
+                .register_action(agent_ui::InlineAssistant::inline_assist)
+                .register_action(agent_ui::InlineAssistant::inline_assist_excerpts);
         }
     })?;
 
@@ -4852,6 +4856,7 @@ mod tests {
                 "project_search",
                 "project_symbols",
                 "projects",
+                "quickfix",
                 "recent_projects",
                 "remote_debug",
                 "repl",
@@ -5010,6 +5015,7 @@ mod tests {
             git_ui::init(cx);
             project_panel::init(cx);
             outline_panel::init(cx);
+            quickfix::init(cx);
             terminal_view::init(cx);
             copilot_chat::init(
                 app_state.fs.clone(),
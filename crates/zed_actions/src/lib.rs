@@ -511,6 +511,15 @@ pub mod assistant {
     pub struct InlineAssist {
         pub prompt: Option<String>,
     }
+
+    /// Deploys the inline assistant on every excerpt of the active multibuffer,
+    /// such as project search or diagnostics results, so one prompt edits them all.
+    #[derive(Clone, Default, Deserialize, PartialEq, JsonSchema, Action)]
+    #[action(namespace = assistant)]
+    #[serde(deny_unknown_fields)]
+    pub struct InlineAssistExcerpts {
+        pub prompt: Option<String>,
+    }
 }
 
 /// Opens the recent projects interface.